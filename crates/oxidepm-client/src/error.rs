@@ -0,0 +1,64 @@
+//! Error types for the client library
+
+/// Client error type
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("Daemon not running")]
+    DaemonNotRunning,
+
+    /// The daemon handled the request and reported an error
+    #[error("{message}")]
+    Daemon {
+        message: String,
+        /// Machine-readable code, e.g. "app_not_found"
+        code: Option<String>,
+        /// Remediation hint, if the daemon has one
+        hint: Option<String>,
+    },
+
+    /// The daemon answered with a response of the wrong kind
+    #[error("Unexpected response from daemon (expected {0})")]
+    UnexpectedResponse(&'static str),
+
+    /// Talking to the daemon failed
+    #[error(transparent)]
+    Ipc(oxidepm_core::Error),
+}
+
+/// Result type alias for client operations
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+impl ClientError {
+    /// Machine-readable error code (see `oxidepm_core::ErrorCode`)
+    pub fn code(&self) -> &str {
+        match self {
+            ClientError::DaemonNotRunning => "daemon_unreachable",
+            ClientError::Daemon { code, .. } => code.as_deref().unwrap_or("general"),
+            ClientError::UnexpectedResponse(_) => "ipc_error",
+            ClientError::Ipc(e) => e.code(),
+        }
+    }
+
+    /// Remediation hint, if one is known
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            ClientError::Daemon { hint, .. } => hint.as_deref(),
+            ClientError::Ipc(e) => e.hint(),
+            _ => None,
+        }
+    }
+
+    /// Whether the selector did not match any app
+    pub fn is_not_found(&self) -> bool {
+        self.code() == "app_not_found"
+    }
+}
+
+impl From<oxidepm_core::Error> for ClientError {
+    fn from(e: oxidepm_core::Error) -> Self {
+        match e {
+            oxidepm_core::Error::DaemonNotRunning => ClientError::DaemonNotRunning,
+            e => ClientError::Ipc(e),
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! OxidePM Client - Async API for controlling the daemon from Rust
+//!
+//! ```no_run
+//! use oxidepm_client::{AppMode, AppSpec, OxideClient};
+//!
+//! # async fn run() -> oxidepm_client::Result<()> {
+//! let client = OxideClient::connect()?;
+//! let spec = AppSpec::new("api".into(), AppMode::Node, "server.js".into(), "/srv/api".into());
+//! let started = client.start(spec).await?;
+//! for app in client.status().await? {
+//!     println!("{} {}", app.spec.name, app.state.status);
+//! }
+//! client.stop(started.id).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod error;
+
+pub use error::{ClientError, Result};
+pub use oxidepm_core::{
+    AppEvent, AppHealth, AppInfo, AppMode, AppOpOutcome, AppOpResult, AppSpec, AppStatus,
+    ApplyReport, EventKind, Heartbeat, PortMapping, Selector, SystemInfo,
+};
+pub use oxidepm_ipc::{Request, Response};
+
+use oxidepm_core::constants;
+use oxidepm_ipc::IpcClient;
+use std::path::PathBuf;
+
+/// App the daemon started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartedApp {
+    pub id: u32,
+    pub name: String,
+}
+
+/// Match a daemon response against the one a request expects
+macro_rules! expect {
+    ($response:expr, $expected:literal, $pattern:pat => $value:expr) => {
+        match $response {
+            $pattern => Ok($value),
+            _ => Err(ClientError::UnexpectedResponse($expected)),
+        }
+    };
+}
+
+/// Client for a running OxidePM daemon
+///
+/// Selectors accept anything the CLI does: `"all"`, an id, a name (or
+/// `"namespace/name"`), `"@tag"` or `"ns:namespace"`.
+pub struct OxideClient {
+    ipc: IpcClient,
+}
+
+impl OxideClient {
+    /// Connect to the daemon at the default socket (`~/.oxidepm/daemon.sock`)
+    pub fn connect() -> Result<Self> {
+        Self::connect_to(constants::socket_path())
+    }
+
+    /// Connect to the daemon listening on `socket_path`
+    pub fn connect_to(socket_path: impl Into<PathBuf>) -> Result<Self> {
+        let ipc = IpcClient::new(socket_path.into());
+        if !ipc.is_daemon_running() {
+            return Err(ClientError::DaemonNotRunning);
+        }
+        Ok(Self { ipc })
+    }
+
+    /// Send a raw IPC request; daemon errors come back as [`ClientError::Daemon`]
+    pub async fn send(&self, request: &Request) -> Result<Response> {
+        match self.ipc.send(request).await? {
+            Response::Error {
+                message,
+                code,
+                hint,
+            } => Err(ClientError::Daemon {
+                message,
+                code,
+                hint,
+            }),
+            response => Ok(response),
+        }
+    }
+
+    /// Check that the daemon answers, returning its heartbeat (absent from older daemons)
+    pub async fn ping(&self) -> Result<Option<Heartbeat>> {
+        expect!(self.send(&Request::Ping).await?, "pong", Response::Pong { heartbeat } => heartbeat)
+    }
+
+    /// Start a new app
+    pub async fn start(&self, spec: AppSpec) -> Result<StartedApp> {
+        expect!(
+            self.send(&Request::Start { spec, progress: false }).await?,
+            "started",
+            Response::Started { id, name } => StartedApp { id, name }
+        )
+    }
+
+    /// Stop the selected apps
+    pub async fn stop(&self, selector: impl Into<Selector>) -> Result<Vec<AppOpResult>> {
+        let selector = selector.into();
+        expect!(self.send(&Request::Stop { selector, if_exists: false }).await?, "stopped", Response::Stopped { results, .. } => results)
+    }
+
+    /// Restart the selected apps
+    pub async fn restart(&self, selector: impl Into<Selector>) -> Result<Vec<AppOpResult>> {
+        let selector = selector.into();
+        expect!(
+            self.send(&Request::Restart {
+                selector,
+                reset_counter: false,
+                bulk: Default::default(),
+                if_exists: false,
+            }).await?,
+            "restarted",
+            Response::Restarted { results, .. } => results
+        )
+    }
+
+    /// Stop the selected apps and remove them from the registry
+    pub async fn delete(&self, selector: impl Into<Selector>) -> Result<Vec<AppOpResult>> {
+        let selector = selector.into();
+        expect!(self.send(&Request::Delete { selector, purge: false }).await?, "deleted", Response::Deleted { results, .. } => results)
+    }
+
+    /// Delete the selected apps along with their logs and run/metrics history
+    pub async fn purge(&self, selector: impl Into<Selector>) -> Result<Vec<AppOpResult>> {
+        let selector = selector.into();
+        expect!(self.send(&Request::Delete { selector, purge: true }).await?, "deleted", Response::Deleted { results, .. } => results)
+    }
+
+    /// Gracefully reload the selected apps, returning how many were reloaded
+    pub async fn reload(&self, selector: impl Into<Selector>) -> Result<usize> {
+        let selector = selector.into();
+        expect!(self.send(&Request::Reload { selector }).await?, "reloaded", Response::Reloaded { count } => count)
+    }
+
+    /// Every app with its current state
+    pub async fn status(&self) -> Result<Vec<AppInfo>> {
+        expect!(self.send(&Request::Status).await?, "status", Response::Status { apps, .. } => apps)
+    }
+
+    /// One app with its current state and lifetime stats
+    pub async fn show(&self, selector: impl Into<Selector>) -> Result<AppInfo> {
+        let selector = selector.into();
+        expect!(self.send(&Request::Show { selector }).await?, "show", Response::Show { app } => app)
+    }
+
+    /// The last `lines` lines of an app's stdout and stderr
+    pub async fn logs(&self, selector: impl Into<Selector>, lines: usize) -> Result<Vec<String>> {
+        let request = Request::Logs {
+            selector: selector.into(),
+            lines,
+            follow: false,
+            stdout: false,
+            stderr: false,
+            since_secs: None,
+        };
+        expect!(self.send(&request).await?, "log_lines", Response::LogLines { lines } => lines)
+    }
+
+    /// The latest supervisor events for the selected apps, oldest first
+    pub async fn events(
+        &self,
+        selector: impl Into<Selector>,
+        limit: usize,
+    ) -> Result<Vec<AppEvent>> {
+        let selector = selector.into();
+        expect!(self.send(&Request::Events { selector, limit }).await?, "events", Response::Events { events } => events)
+    }
+
+    /// Health history of the selected apps that have a health check
+    pub async fn health(&self, selector: impl Into<Selector>) -> Result<Vec<AppHealth>> {
+        let selector = selector.into();
+        expect!(self.send(&Request::Health { selector }).await?, "health", Response::Health { apps } => apps)
+    }
+
+    /// Truncate the selected apps' log files, returning how many apps were flushed
+    pub async fn flush(&self, selector: impl Into<Selector>) -> Result<usize> {
+        let request = Request::Flush {
+            selector: selector.into(),
+            older_than_secs: None,
+            keep_bytes: None,
+        };
+        expect!(self.send(&request).await?, "flushed", Response::Flushed { count, .. } => count)
+    }
+
+    /// Reconcile the daemon with a declared app list
+    pub async fn apply(
+        &self,
+        specs: Vec<AppSpec>,
+        prune: bool,
+        dry_run: bool,
+    ) -> Result<ApplyReport> {
+        let request = Request::Apply {
+            specs,
+            prune,
+            dry_run,
+        };
+        expect!(self.send(&request).await?, "applied", Response::Applied { report } => report)
+    }
+
+    /// Save the current app list for `resurrect`
+    pub async fn save(&self) -> Result<usize> {
+        expect!(self.send(&Request::Save { output: None }).await?, "saved", Response::Saved { count, .. } => count)
+    }
+
+    /// Start the apps from the last `save`
+    pub async fn resurrect(&self) -> Result<usize> {
+        expect!(self.send(&Request::Resurrect { file: None, all: false }).await?, "resurrected", Response::Resurrected { count, .. } => count)
+    }
+
+    /// Daemon and host overview
+    pub async fn system_info(&self) -> Result<SystemInfo> {
+        expect!(self.send(&Request::SystemInfo).await?, "system_info", Response::SystemInfo { info } => info)
+    }
+
+    /// Ports held by running apps, in port order
+    pub async fn ports(&self) -> Result<Vec<PortMapping>> {
+        expect!(self.send(&Request::Ports).await?, "ports", Response::Ports { ports } => ports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_ipc::IpcServer;
+    use tempfile::tempdir;
+
+    /// Answer one request with `response`, returning the request
+    async fn serve_one(server: IpcServer, response: Response) -> Request {
+        let mut conn = server.accept().await.unwrap();
+        let envelope = conn.read_request().await.unwrap().unwrap();
+        conn.send_response(envelope.id, &response).await.unwrap();
+        envelope.message
+    }
+
+    #[test]
+    fn test_connect_without_daemon() {
+        let dir = tempdir().unwrap();
+        let err = OxideClient::connect_to(dir.path().join("missing.sock"))
+            .err()
+            .unwrap();
+        assert!(matches!(err, ClientError::DaemonNotRunning));
+        assert_eq!(err.code(), "daemon_unreachable");
+    }
+
+    #[tokio::test]
+    async fn test_start_and_stop() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let server = IpcServer::bind(&socket_path).await.unwrap();
+        let client = OxideClient::connect_to(&socket_path).unwrap();
+
+        let daemon = tokio::spawn(serve_one(
+            server,
+            Response::Started {
+                id: 7,
+                name: "api".into(),
+            },
+        ));
+        let spec = AppSpec::new(
+            "api".into(),
+            AppMode::Cmd,
+            "server".into(),
+            PathBuf::from("/srv"),
+        );
+        let started = client.start(spec).await.unwrap();
+        assert_eq!(
+            started,
+            StartedApp {
+                id: 7,
+                name: "api".into()
+            }
+        );
+        assert!(matches!(daemon.await.unwrap(), Request::Start { spec, .. } if spec.name == "api"));
+
+        let server = IpcServer::bind(&socket_path).await.unwrap();
+        let results = vec![AppOpResult {
+            id: 7,
+            name: "api".into(),
+            outcome: AppOpOutcome::Done,
+        }];
+        let daemon = tokio::spawn(serve_one(
+            server,
+            Response::Stopped {
+                count: 1,
+                results: results.clone(),
+            },
+        ));
+        assert_eq!(client.stop(7).await.unwrap(), results);
+        assert!(matches!(
+            daemon.await.unwrap(),
+            Request::Stop {
+                selector: Selector::ById(7),
+                if_exists: false,
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_daemon_errors() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let server = IpcServer::bind(&socket_path).await.unwrap();
+        let client = OxideClient::connect_to(&socket_path).unwrap();
+
+        let not_found = Response::from_error(&oxidepm_core::Error::AppNotFound("ghost".into()));
+        let daemon = tokio::spawn(serve_one(server, not_found));
+        let err = client.show("ghost").await.unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(err.to_string(), "App not found: ghost");
+        daemon.await.unwrap();
+
+        let server = IpcServer::bind(&socket_path).await.unwrap();
+        let daemon = tokio::spawn(serve_one(server, Response::Pong { heartbeat: None }));
+        let err = client.status().await.unwrap_err();
+        assert!(matches!(err, ClientError::UnexpectedResponse("status")));
+        daemon.await.unwrap();
+    }
+}
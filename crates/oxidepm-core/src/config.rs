@@ -6,12 +6,16 @@
 //! - JSON (.json)
 
 use serde::Deserialize;
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use crate::constants::*;
 use crate::error::{Error, Result};
-use crate::types::{AppMode, AppSpec, HealthCheck, Hooks, RestartPolicy};
+use crate::types::{
+    validate_app_name, AppMode, AppSpec, EnvFileAction, EnvSource, HealthCheck, Hook, Hooks,
+    Limits, LogSettings, LogTrigger, PortConflictPolicy, RestartPolicy, Sandbox, UnhealthyAction,
+    WaitTarget, Warmup,
+};
 
 /// Supported configuration file formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -47,6 +51,12 @@ pub struct HealthCheckConfig {
     pub http_url: Option<String>,
     /// Script to execute for health check
     pub script: Option<String>,
+    /// TCP port to connect to
+    pub tcp_port: Option<u16>,
+    /// Host for the TCP check (default: 127.0.0.1)
+    pub tcp_host: Option<String>,
+    /// Regex the HTTP response body or script stdout must match
+    pub expect_output: Option<String>,
     /// Expected HTTP status codes (default: [200])
     #[serde(default)]
     pub expected_status: Vec<u16>,
@@ -56,6 +66,12 @@ pub struct HealthCheckConfig {
     pub timeout_secs: Option<u64>,
     /// Number of consecutive failures before marking unhealthy (default: 3)
     pub retries: Option<u32>,
+    /// Seconds to wait before the first check (default: 5)
+    pub initial_delay_secs: Option<u64>,
+    /// Consecutive passes required to count as healthy (default: 1)
+    pub success_threshold: Option<u32>,
+    /// restart, restart_with_backoff, notify_only or stop (default: notify_only)
+    pub on_unhealthy: Option<UnhealthyAction>,
 }
 
 impl HealthCheckConfig {
@@ -65,27 +81,46 @@ impl HealthCheckConfig {
         HealthCheck {
             http_url: self.http_url,
             script: self.script,
-            expected_status: if self.expected_status.is_empty() { default.expected_status } else { self.expected_status },
+            tcp_port: self.tcp_port,
+            tcp_host: self.tcp_host,
+            expect_output: self.expect_output,
+            expected_status: if self.expected_status.is_empty() {
+                default.expected_status
+            } else {
+                self.expected_status
+            },
             interval_secs: self.interval_secs.unwrap_or(default.interval_secs),
             timeout_secs: self.timeout_secs.unwrap_or(default.timeout_secs),
             retries: self.retries.unwrap_or(default.retries),
+            initial_delay_secs: self
+                .initial_delay_secs
+                .unwrap_or(default.initial_delay_secs),
+            success_threshold: self
+                .success_threshold
+                .unwrap_or(default.success_threshold)
+                .max(1),
+            on_unhealthy: self.on_unhealthy.unwrap_or(default.on_unhealthy),
         }
     }
 }
 
 /// Event hooks configuration from config file
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default)]
 pub struct HooksConfig {
-    /// Script to run after process starts
-    pub on_start: Option<String>,
-    /// Script to run after process stops
-    pub on_stop: Option<String>,
-    /// Script to run after restart
-    pub on_restart: Option<String>,
-    /// Script to run when process crashes
-    pub on_crash: Option<String>,
-    /// Script to run on error
-    pub on_error: Option<String>,
+    /// Run after process starts
+    pub on_start: Option<Hook>,
+    /// Run after process stops
+    pub on_stop: Option<Hook>,
+    /// Run after restart
+    pub on_restart: Option<Hook>,
+    /// Run when process crashes
+    pub on_crash: Option<Hook>,
+    /// Run on error
+    pub on_error: Option<Hook>,
+    /// Run during a reload, before the old process is stopped
+    pub on_pre_reload: Option<Hook>,
+    /// Run after a reload has completed
+    pub on_post_reload: Option<Hook>,
 }
 
 impl HooksConfig {
@@ -97,6 +132,8 @@ impl HooksConfig {
             on_restart: self.on_restart,
             on_crash: self.on_crash,
             on_error: self.on_error,
+            on_pre_reload: self.on_pre_reload,
+            on_post_reload: self.on_post_reload,
         }
     }
 }
@@ -104,27 +141,58 @@ impl HooksConfig {
 /// Configuration file structure (oxidepm.config.toml/yaml/json)
 #[derive(Debug, Deserialize)]
 pub struct ConfigFile {
+    /// Namespace for every app that does not set its own
+    pub namespace: Option<String>,
     #[serde(default)]
     pub apps: Vec<AppConfig>,
+    /// Templates expanded into `apps` when the file is parsed
+    #[serde(default)]
+    pub templates: Vec<AppTemplate>,
 }
 
 /// Single app configuration from config file
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub name: String,
+    /// Project namespace (default: the file's `namespace`, else "default")
+    pub namespace: Option<String>,
     pub mode: Option<String>,
     pub script: Option<String>,
     pub bin: Option<String>,
     pub cwd: Option<String>,
+    /// Workspace package (name or path) to run `script` in when cwd is a monorepo root
+    pub workspace: Option<String>,
+    /// Cargo features to enable (cargo mode)
+    #[serde(default)]
+    pub cargo_features: Vec<String>,
+    /// Cargo profile: "dev", "release" (default) or a custom profile
+    pub cargo_profile: Option<String>,
+    /// Target triple to build for
+    pub cargo_target: Option<String>,
     #[serde(default)]
     pub args: Vec<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
     pub env_file: Option<String>,
+    /// "restart" or "reload" the app when its env_file changes
+    pub watch_env_file: Option<EnvFileAction>,
     #[serde(default)]
     pub watch: bool,
     #[serde(default)]
     pub ignore: Vec<String>,
+    /// Files and directories to watch, relative to cwd (default: the whole cwd)
+    #[serde(default)]
+    pub watch_paths: Vec<PathBuf>,
+    /// Only restart for changes to files with these extensions, e.g. ["rs", "toml"]
+    #[serde(default)]
+    pub watch_extensions: Vec<String>,
+    /// Poll for changes every N ms instead of using native file events
+    pub watch_poll_ms: Option<u64>,
+    /// Binary mode: restart when the executable changes on disk instead of only warning
+    #[serde(default)]
+    pub reload_on_change: bool,
+    /// Seconds the build may take before the start is abandoned
+    pub build_timeout_secs: Option<u64>,
     pub restart_delay: Option<u64>,
     pub max_restarts: Option<u32>,
     pub kill_timeout: Option<u64>,
@@ -134,21 +202,52 @@ pub struct AppConfig {
     /// Number of instances to run (default: 1)
     #[serde(default = "default_instances")]
     pub instances: u32,
-    /// Port for the application
-    pub port: Option<u16>,
-    /// Port range for clustered instances (start, end)
+    /// Port for the application, or "auto" to get a free one
+    pub port: Option<PortSetting>,
+    /// Port range for clustered instances, or to pick "auto" ports from
     pub port_range: Option<PortRange>,
-    /// Health check configuration
+    /// "reject" (default) or "reassign" when the port is already taken
+    pub on_port_conflict: Option<PortConflictPolicy>,
+    /// Liveness check configuration (also accepted as `liveness_check`)
+    #[serde(alias = "liveness_check")]
     pub health_check: Option<HealthCheckConfig>,
+    /// Readiness check: failing it marks the app not ready instead of restarting it
+    pub readiness_check: Option<HealthCheckConfig>,
+    /// Requests fired once the app is healthy, before it counts as ready
+    pub warmup: Option<Warmup>,
+    /// Services (tcp://host:port, http(s) URLs) and files to wait for before launch
+    #[serde(default)]
+    pub wait_for: Vec<String>,
+    /// Seconds to wait for `wait_for` before the start fails (default: 60)
+    pub wait_for_timeout_secs: Option<u64>,
     /// Maximum memory in MB before auto-restart
     pub max_memory_mb: Option<u64>,
     /// Event hooks configuration
     pub hooks: Option<HooksConfig>,
+    /// User the app's hooks run as
+    pub hook_user: Option<String>,
     /// Process tags for grouping (use @tag selector syntax)
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Apps in the same namespace that resurrect starts before this one
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Start band: lower priorities start first (default: 0)
+    #[serde(default)]
+    pub priority: i32,
+    /// Milliseconds to wait before starting, which also holds back later bands
+    pub startup_delay: Option<u64>,
     /// Maximum uptime in seconds before auto-restart (prevents memory leaks)
     pub max_uptime_secs: Option<u64>,
+    /// Run the app in Linux namespaces (private /tmp, read-only cwd, no network)
+    pub sandbox: Option<Sandbox>,
+    /// Resource limits: nofile, nproc, core
+    pub limits: Option<Limits>,
+    /// Custom stdout/stderr log files, relative to cwd
+    pub logs: Option<LogSettings>,
+    /// Patterns in the output that notify, restart or run a command
+    #[serde(default)]
+    pub triggers: Vec<LogTrigger>,
 }
 
 fn default_instances() -> u32 {
@@ -162,6 +261,234 @@ pub struct PortRange {
     pub end: u16,
 }
 
+/// `port` setting: a fixed port, or `"auto"` to have the daemon pick one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortSetting {
+    Fixed(u16),
+    Auto,
+}
+
+impl<'de> Deserialize<'de> for PortSetting {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(u16),
+            Keyword(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(port) => Ok(PortSetting::Fixed(port)),
+            Raw::Keyword(keyword) if keyword == "auto" => Ok(PortSetting::Auto),
+            Raw::Keyword(other) => Err(serde::de::Error::custom(format!(
+                "invalid port \"{}\": expected a number or \"auto\"",
+                other
+            ))),
+        }
+    }
+}
+
+/// App template that expands into several apps at load time
+///
+/// Strings in the template may use `{{index}}` (0-based), `{{port}}` (the
+/// template's `port` plus the index) and `{{key}}` for each matrix
+/// parameter. A string that is only a placeholder takes the value's type,
+/// so `max_memory_mb = "{{memory}}"` stays a number.
+#[derive(Debug, Deserialize)]
+pub struct AppTemplate {
+    /// Number of apps to create
+    pub count: Option<u32>,
+    /// Parameters to combine, one app per combination of values
+    #[serde(default)]
+    pub matrix: BTreeMap<String, Vec<serde_json::Value>>,
+    /// App settings, as in `[[apps]]`
+    #[serde(flatten)]
+    pub app: serde_json::Map<String, serde_json::Value>,
+}
+
+impl AppTemplate {
+    /// The apps this template stands for, in index order
+    pub fn expand(&self) -> Result<Vec<AppConfig>> {
+        let label = self
+            .app
+            .get("name")
+            .and_then(|name| name.as_str())
+            .unwrap_or("<unnamed>");
+        if let Some((key, _)) = self.matrix.iter().find(|(_, values)| values.is_empty()) {
+            return Err(Error::ConfigError(format!(
+                "Template '{}': matrix parameter '{}' has no values",
+                label, key
+            )));
+        }
+        let combinations = match (self.count, self.matrix.is_empty()) {
+            (Some(count), true) => vec![BTreeMap::new(); count as usize],
+            (None, false) => matrix_combinations(&self.matrix),
+            (Some(_), false) => {
+                return Err(Error::ConfigError(format!(
+                    "Template '{}' sets both count and matrix",
+                    label
+                )))
+            }
+            (None, true) => {
+                return Err(Error::ConfigError(format!(
+                    "Template '{}' needs a count or a matrix",
+                    label
+                )))
+            }
+        };
+        // A fixed port is the first app's; the others count up from it
+        let base_port = match self.app.get("port") {
+            Some(serde_json::Value::Number(port)) if !self.matrix.contains_key("port") => {
+                port.as_u64()
+            }
+            _ => None,
+        };
+
+        let mut names = HashSet::new();
+        let mut apps = Vec::with_capacity(combinations.len());
+        for (index, mut params) in combinations.into_iter().enumerate() {
+            params.insert("index".to_string(), serde_json::Value::from(index));
+            let mut app = self.app.clone();
+            if let Some(base) = base_port {
+                let port = base + index as u64;
+                if port > u16::MAX as u64 {
+                    return Err(Error::ConfigError(format!(
+                        "Template '{}': port {} is out of range",
+                        label, port
+                    )));
+                }
+                params.insert("port".to_string(), serde_json::Value::from(port));
+                app.insert("port".to_string(), serde_json::Value::from(port));
+            }
+
+            let mut app =
+                substitute(serde_json::Value::Object(app), &params).map_err(|placeholder| {
+                    Error::ConfigError(format!(
+                        "Template '{}': unknown placeholder {{{{{}}}}}",
+                        label, placeholder
+                    ))
+                })?;
+            // Lists and maps of strings keep numbers filled in as strings
+            for key in ["args", "env", "tags"] {
+                match app.get_mut(key) {
+                    Some(serde_json::Value::Array(items)) => {
+                        items.iter_mut().for_each(stringify_number)
+                    }
+                    Some(serde_json::Value::Object(map)) => {
+                        map.values_mut().for_each(stringify_number)
+                    }
+                    _ => {}
+                }
+            }
+            let app: AppConfig = serde_json::from_value(app)
+                .map_err(|e| Error::ConfigError(format!("Template '{}': {}", label, e)))?;
+            if !names.insert(app.name.clone()) {
+                return Err(Error::ConfigError(format!(
+                    "Template '{}' gives several apps the name '{}'; use {{{{index}}}} or a matrix parameter in the name",
+                    label, app.name
+                )));
+            }
+            apps.push(app);
+        }
+        Ok(apps)
+    }
+}
+
+fn stringify_number(value: &mut serde_json::Value) {
+    if let serde_json::Value::Number(number) = value {
+        *value = serde_json::Value::String(number.to_string());
+    }
+}
+
+/// Every combination of matrix values, the first parameter changing slowest
+fn matrix_combinations(
+    matrix: &BTreeMap<String, Vec<serde_json::Value>>,
+) -> Vec<BTreeMap<String, serde_json::Value>> {
+    matrix
+        .iter()
+        .fold(vec![BTreeMap::new()], |combinations, (key, values)| {
+            combinations
+                .iter()
+                .flat_map(|params| {
+                    values.iter().map(move |value| {
+                        let mut params = params.clone();
+                        params.insert(key.clone(), value.clone());
+                        params
+                    })
+                })
+                .collect()
+        })
+}
+
+/// Placeholders filled in when an instance is launched rather than here
+const LAUNCH_PLACEHOLDERS: &[&str] = &["instance", "instances"];
+
+/// Fill `{{name}}` placeholders in every string of `value`; the error is the
+/// first placeholder without a parameter. Launch placeholders such as
+/// `{{instance}}` are left for the supervisor.
+fn substitute(
+    value: serde_json::Value,
+    params: &BTreeMap<String, serde_json::Value>,
+) -> std::result::Result<serde_json::Value, String> {
+    use serde_json::Value;
+
+    match value {
+        Value::String(s) => {
+            let whole = s
+                .strip_prefix("{{")
+                .and_then(|rest| rest.strip_suffix("}}"))
+                .filter(|name| !name.contains("{{") && !name.contains("}}"));
+            if let Some(name) = whole {
+                let name = name.trim();
+                if !params.contains_key(name) && LAUNCH_PLACEHOLDERS.contains(&name) {
+                    return Ok(Value::String(s));
+                }
+                return params.get(name).cloned().ok_or_else(|| name.to_string());
+            }
+
+            let mut out = String::with_capacity(s.len());
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find("{{") {
+                let Some(len) = rest[start..].find("}}") else {
+                    break;
+                };
+                let name = rest[start + 2..start + len].trim();
+                match params.get(name) {
+                    Some(Value::String(param)) => {
+                        out.push_str(&rest[..start]);
+                        out.push_str(param);
+                    }
+                    Some(param) => {
+                        out.push_str(&rest[..start]);
+                        out.push_str(&param.to_string());
+                    }
+                    None if LAUNCH_PLACEHOLDERS.contains(&name) => {
+                        out.push_str(&rest[..start + len + 2])
+                    }
+                    None => return Err(name.to_string()),
+                }
+                rest = &rest[start + len + 2..];
+            }
+            out.push_str(rest);
+            Ok(Value::String(out))
+        }
+        Value::Array(items) => Ok(Value::Array(
+            items
+                .into_iter()
+                .map(|item| substitute(item, params))
+                .collect::<std::result::Result<_, _>>()?,
+        )),
+        Value::Object(map) => Ok(Value::Object(
+            map.into_iter()
+                .map(|(key, item)| Ok((key, substitute(item, params)?)))
+                .collect::<std::result::Result<_, String>>()?,
+        )),
+        other => Ok(other),
+    }
+}
+
 impl ConfigFile {
     /// Load config from file, automatically detecting format from extension
     pub fn load(path: &Path) -> Result<Self> {
@@ -192,19 +519,27 @@ impl ConfigFile {
     /// Parse TOML config content
     pub fn from_toml(content: &str) -> Result<Self> {
         let config: ConfigFile = toml::from_str(content)?;
-        Ok(config)
+        config.expand_templates()
     }
 
     /// Parse YAML config content
     pub fn from_yaml(content: &str) -> Result<Self> {
         let config: ConfigFile = serde_yaml::from_str(content)?;
-        Ok(config)
+        config.expand_templates()
     }
 
     /// Parse JSON config content
     pub fn from_json(content: &str) -> Result<Self> {
         let config: ConfigFile = serde_json::from_str(content)?;
-        Ok(config)
+        config.expand_templates()
+    }
+
+    /// Append the apps generated by `templates`
+    fn expand_templates(mut self) -> Result<Self> {
+        for template in std::mem::take(&mut self.templates) {
+            self.apps.extend(template.expand()?);
+        }
+        Ok(self)
     }
 
     /// Find and load config file from current directory
@@ -223,11 +558,36 @@ impl ConfigFile {
         )))
     }
 
+    /// Keep only the apps named in `only` (if any), minus those in `except`
+    pub fn select(mut self, only: &[String], except: &[String]) -> Result<Self> {
+        for name in only.iter().chain(except) {
+            if !self.apps.iter().any(|app| &app.name == name) {
+                let available: Vec<&str> = self.apps.iter().map(|app| app.name.as_str()).collect();
+                return Err(Error::ConfigError(format!(
+                    "App '{}' is not defined in the config file (available: {})",
+                    name,
+                    available.join(", ")
+                )));
+            }
+        }
+
+        self.apps.retain(|app| {
+            (only.is_empty() || only.contains(&app.name)) && !except.contains(&app.name)
+        });
+        Ok(self)
+    }
+
     /// Convert to AppSpec list
     pub fn into_specs(self, base_dir: &Path) -> Result<Vec<AppSpec>> {
+        let namespace = self.namespace;
         self.apps
             .into_iter()
-            .map(|app| app.into_spec(base_dir))
+            .map(|mut app| {
+                if app.namespace.is_none() {
+                    app.namespace = namespace.clone();
+                }
+                app.into_spec(base_dir)
+            })
             .collect()
     }
 }
@@ -235,6 +595,16 @@ impl ConfigFile {
 impl AppConfig {
     /// Convert to AppSpec
     pub fn into_spec(self, base_dir: &Path) -> Result<AppSpec> {
+        let namespace = self
+            .namespace
+            .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
+        if !validate_app_name(&namespace) {
+            return Err(Error::config(format!(
+                "Invalid namespace '{}' for app '{}': only alphanumeric characters, underscores, and hyphens are allowed",
+                namespace, self.name
+            )));
+        }
+
         // Determine mode
         let mode = if let Some(mode_str) = &self.mode {
             mode_str.parse::<AppMode>()?
@@ -266,15 +636,62 @@ impl AppConfig {
 
         // Load env file if specified
         let mut env = self.env;
-        if let Some(env_file) = &self.env_file {
-            let env_path = if Path::new(env_file).is_absolute() {
-                Path::new(env_file).to_path_buf()
+        let mut env_sources: BTreeMap<String, EnvSource> = env
+            .keys()
+            .map(|key| (key.clone(), EnvSource::Config))
+            .collect();
+        let mut env_file = None;
+        if let Some(file) = &self.env_file {
+            let env_path = if Path::new(file).is_absolute() {
+                Path::new(file).to_path_buf()
             } else {
-                cwd.join(env_file)
+                cwd.join(file)
             };
             if env_path.exists() {
-                load_env_file(&env_path, &mut env)?;
+                let mut from_file = HashMap::new();
+                load_env_file(&env_path, &mut from_file)?;
+                env_sources.extend(
+                    from_file
+                        .keys()
+                        .map(|key| (key.clone(), EnvSource::EnvFile)),
+                );
+                env.extend(from_file);
             }
+            env_file = Some(env_path);
+        }
+        if self.watch_env_file.is_some() && env_file.is_none() {
+            return Err(Error::config(format!(
+                "watch_env_file of app '{}' needs an env_file",
+                self.name
+            )));
+        }
+
+        // Log paths are relative to the app's cwd
+        let mut logs = self.logs.unwrap_or_default();
+        logs.out = logs.out.map(|p| cwd.join(p));
+        logs.err = logs.err.map(|p| cwd.join(p));
+        logs.validate()?;
+        for trigger in &self.triggers {
+            trigger.validate()?;
+        }
+        if self
+            .readiness_check
+            .as_ref()
+            .is_some_and(|rc| rc.on_unhealthy.is_some())
+        {
+            return Err(Error::config(format!(
+                "readiness_check of app '{}' cannot set on_unhealthy: failing readiness never restarts the app",
+                self.name
+            )));
+        }
+        if let Some(warmup) = &self.warmup {
+            warmup.validate().map_err(|e| match e {
+                Error::ConfigError(msg) => Error::config(format!("app '{}': {}", self.name, msg)),
+                other => other,
+            })?;
+        }
+        for target in &self.wait_for {
+            target.parse::<WaitTarget>()?;
         }
 
         // Build ignore patterns
@@ -292,45 +709,85 @@ impl AppConfig {
             crash_window_secs: DEFAULT_CRASH_WINDOW_SECS,
         };
 
-        // Convert health check config
+        // Convert health check configs
         let health_check = self.health_check.map(|hc| hc.into_health_check());
+        let readiness_check = self.readiness_check.map(|rc| rc.into_health_check());
 
         // Convert port range
         let port_range = self.port_range.map(|pr| (pr.start, pr.end));
 
         // Convert hooks config
         let hooks = self.hooks.map(|h| h.into_hooks()).unwrap_or_default();
+        hooks.validate()?;
 
         Ok(AppSpec {
             id: 0, // Will be assigned by database
             name: self.name,
+            namespace,
             mode,
             command,
             args: self.args,
             cwd,
+            workspace: self.workspace,
+            cargo_features: self.cargo_features,
+            cargo_profile: self.cargo_profile,
+            cargo_target: self.cargo_target,
             env,
+            env_sources,
+            env_file,
+            watch_env_file: self.watch_env_file,
             watch: self.watch,
             ignore_patterns,
+            watch_paths: self.watch_paths,
+            watch_extensions: normalize_extensions(self.watch_extensions),
+            watch_poll_ms: self.watch_poll_ms,
+            reload_on_change: self.reload_on_change,
+            build_timeout_secs: self.build_timeout_secs,
             restart_policy,
             kill_timeout_ms: self.kill_timeout.unwrap_or(DEFAULT_KILL_TIMEOUT_MS),
             created_at: chrono::Utc::now(),
             instances: self.instances,
             instance_id: None,
-            port: self.port,
+            port: match self.port {
+                Some(PortSetting::Fixed(port)) => Some(port),
+                _ => None,
+            },
             port_range,
+            on_port_conflict: self.on_port_conflict.unwrap_or_default(),
+            auto_port: self.port == Some(PortSetting::Auto),
             health_check,
+            readiness_check,
+            warmup: self.warmup,
+            wait_for: self.wait_for,
+            wait_for_timeout_secs: self.wait_for_timeout_secs,
             max_memory_mb: self.max_memory_mb,
-            startup_delay_ms: None,
+            startup_delay_ms: self.startup_delay,
             env_inherit: false,
             hooks,
+            hook_user: self.hook_user,
             tags: self.tags,
+            depends_on: self.depends_on,
+            priority: self.priority,
             max_uptime_secs: self.max_uptime_secs,
+            sandbox: self.sandbox,
+            limits: self.limits.unwrap_or_default(),
+            logs,
+            triggers: self.triggers,
         })
     }
 }
 
+/// Strip leading dots and lowercase, so ".RS" and "rs" match the same files
+pub fn normalize_extensions(extensions: Vec<String>) -> Vec<String> {
+    extensions
+        .into_iter()
+        .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect()
+}
+
 /// Load environment variables from a .env file
-fn load_env_file(path: &Path, env: &mut HashMap<String, String>) -> Result<()> {
+pub fn load_env_file(path: &Path, env: &mut HashMap<String, String>) -> Result<()> {
     let content = std::fs::read_to_string(path)?;
     for line in content.lines() {
         let line = line.trim();
@@ -356,6 +813,7 @@ fn load_env_file(path: &Path, env: &mut HashMap<String, String>) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{LogOverflow, LogTimestamps, TriggerAction};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -410,6 +868,7 @@ apps:
     health_check:
       http_url: "http://localhost:3000/health"
       interval_secs: 30
+      on_unhealthy: restart_with_backoff
   - name: api
     mode: cargo
     bin: api-server
@@ -422,11 +881,12 @@ apps:
         assert_eq!(config.apps.len(), 2);
         assert_eq!(config.apps[0].name, "web");
         assert_eq!(config.apps[0].instances, 4);
-        assert_eq!(config.apps[0].port, Some(3000));
+        assert_eq!(config.apps[0].port, Some(PortSetting::Fixed(3000)));
         assert!(config.apps[0].watch);
         assert!(config.apps[0].health_check.is_some());
         let hc = config.apps[0].health_check.as_ref().unwrap();
         assert_eq!(hc.http_url, Some("http://localhost:3000/health".to_string()));
+        assert_eq!(hc.on_unhealthy, Some(UnhealthyAction::RestartWithBackoff));
         assert_eq!(hc.interval_secs, Some(30));
         assert_eq!(config.apps[1].name, "api");
         assert_eq!(config.apps[1].instances, 2);
@@ -466,11 +926,225 @@ apps:
         assert_eq!(config.apps.len(), 2);
         assert_eq!(config.apps[0].name, "web");
         assert_eq!(config.apps[0].instances, 4);
-        assert_eq!(config.apps[0].port, Some(3000));
+        assert_eq!(config.apps[0].port, Some(PortSetting::Fixed(3000)));
         assert!(config.apps[0].watch);
         assert!(config.apps[0].health_check.is_some());
     }
 
+    #[test]
+    fn test_config_auto_port() {
+        let config_content = r#"
+[[apps]]
+name = "preview"
+script = "server.js"
+port = "auto"
+port_range = { start = 4100, end = 4199 }
+"#;
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(config_content.as_bytes()).unwrap();
+
+        let config = ConfigFile::load(file.path()).unwrap();
+        assert_eq!(config.apps[0].port, Some(PortSetting::Auto));
+        let spec = config
+            .apps
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_spec(Path::new("/tmp"))
+            .unwrap();
+        assert!(spec.auto_port);
+        assert_eq!(spec.port, None);
+        assert_eq!(spec.port_range, Some((4100, 4199)));
+
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(b"[[apps]]\nname = \"x\"\nport = \"any\"\n")
+            .unwrap();
+        let err = ConfigFile::load(file.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("expected a number or \"auto\""),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_config_limits() {
+        let config_content = "[[apps]]\nname = \"api\"\nscript = \"server.js\"\nlimits = { nofile = 65535, core = \"unlimited\" }\n";
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(config_content.as_bytes()).unwrap();
+
+        let config = ConfigFile::load(file.path()).unwrap();
+        let limits = config.apps[0].limits.clone().unwrap();
+        assert_eq!(limits.nofile, Some(65535));
+        assert_eq!(limits.nproc, None);
+        assert_eq!(limits.core, Some(Limits::UNLIMITED));
+
+        // The stored form round-trips through the spec's serialization
+        let json = serde_json::to_string(&limits).unwrap();
+        assert_eq!(serde_json::from_str::<Limits>(&json).unwrap(), limits);
+
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(b"[[apps]]\nname = \"x\"\nlimits = { core = \"big\" }\n")
+            .unwrap();
+        assert!(ConfigFile::load(file.path()).is_err());
+    }
+
+    #[test]
+    fn test_config_template_count() {
+        let content = r#"
+[[apps]]
+name = "api"
+script = "api.js"
+
+[[templates]]
+name = "worker-{{index}}"
+script = "worker.js"
+count = 3
+port = 4000
+args = ["--shard", "{{index}}"]
+env = { PORT = "{{port}}", LABEL = "w{{index}}" }
+"#;
+        let config = ConfigFile::from_toml(content).unwrap();
+        assert!(config.templates.is_empty());
+        let names: Vec<&str> = config.apps.iter().map(|app| app.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "worker-0", "worker-1", "worker-2"]);
+
+        let worker = &config.apps[3];
+        assert_eq!(worker.port, Some(PortSetting::Fixed(4002)));
+        assert_eq!(worker.args, vec!["--shard", "2"]);
+        assert_eq!(worker.env.get("PORT").map(String::as_str), Some("4002"));
+        assert_eq!(worker.env.get("LABEL").map(String::as_str), Some("w2"));
+
+        // Expanded apps can be picked like declared ones
+        let config = ConfigFile::from_toml(content)
+            .unwrap()
+            .select(&["worker-1".to_string()], &[])
+            .unwrap();
+        assert_eq!(config.apps.len(), 1);
+
+        // Instance placeholders are left for launch time
+        let content = "[[templates]]\nname = \"w-{{index}}\"\nscript = \"w.js\"\ncount = 1\nargs = [\"--id={{index}}.{{instance}}\", \"{{instances}}\"]\n";
+        let apps = ConfigFile::from_toml(content).unwrap().apps;
+        assert_eq!(apps[0].args, vec!["--id=0.{{instance}}", "{{instances}}"]);
+        assert!(ConfigFile::from_toml(&content.replace("{{instances}}", "{{shard}}")).is_err());
+    }
+
+    #[test]
+    fn test_config_template_matrix() {
+        let content = r#"
+templates:
+  - name: "{{queue}}-{{size}}"
+    script: worker.js
+    max_memory_mb: "{{memory}}"
+    matrix:
+      queue: [email, billing]
+      size: [small, large]
+      memory: [256]
+"#;
+        let apps = ConfigFile::from_yaml(content).unwrap().apps;
+        let names: Vec<&str> = apps.iter().map(|app| app.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "email-small",
+                "email-large",
+                "billing-small",
+                "billing-large"
+            ]
+        );
+        assert!(apps.iter().all(|app| app.max_memory_mb == Some(256)));
+    }
+
+    #[test]
+    fn test_config_template_errors() {
+        let error = |content: &str| ConfigFile::from_toml(content).unwrap_err().to_string();
+
+        assert!(error("[[templates]]\nname = \"w\"\n").contains("needs a count or a matrix"));
+        assert!(
+            error("[[templates]]\nname = \"w{{index}}\"\ncount = 2\nmatrix = { a = [1] }\n")
+                .contains("both count and matrix")
+        );
+        assert!(
+            error("[[templates]]\nname = \"w\"\ncount = 2\n").contains("several apps the name 'w'")
+        );
+        assert!(error("[[templates]]\nname = \"w{{id}}\"\ncount = 2\n")
+            .contains("unknown placeholder {{id}}"));
+        // {{port}} needs a fixed port to count from
+        assert!(error("[[templates]]\nname = \"w{{port}}\"\ncount = 2\n")
+            .contains("unknown placeholder {{port}}"));
+        assert!(
+            error("[[templates]]\nname = \"w{{index}}\"\ncount = 2\nport = 65535\n")
+                .contains("out of range")
+        );
+    }
+
+    #[test]
+    fn test_config_select() {
+        let content = r#"
+[[apps]]
+name = "api"
+script = "api.js"
+
+[[apps]]
+name = "web"
+script = "web.js"
+
+[[apps]]
+name = "worker"
+script = "worker.js"
+"#;
+        let names = |config: ConfigFile| -> Vec<String> {
+            config.apps.into_iter().map(|app| app.name).collect()
+        };
+        let select = |only: &[&str], except: &[&str]| {
+            let only: Vec<String> = only.iter().map(|s| s.to_string()).collect();
+            let except: Vec<String> = except.iter().map(|s| s.to_string()).collect();
+            ConfigFile::from_toml(content)
+                .unwrap()
+                .select(&only, &except)
+        };
+
+        assert_eq!(names(select(&["web"], &[]).unwrap()), vec!["web"]);
+        assert_eq!(names(select(&[], &["web"]).unwrap()), vec!["api", "worker"]);
+        assert_eq!(
+            names(select(&["api", "web"], &["api"]).unwrap()),
+            vec!["web"]
+        );
+        assert!(matches!(select(&["nope"], &[]), Err(Error::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_config_namespace() {
+        let content = r#"
+namespace = "shop"
+
+[[apps]]
+name = "api"
+script = "api.js"
+
+[[apps]]
+name = "worker"
+namespace = "jobs"
+script = "worker.js"
+"#;
+        let specs = ConfigFile::from_toml(content)
+            .unwrap()
+            .into_specs(Path::new("/project"))
+            .unwrap();
+        assert_eq!(specs[0].namespace, "shop");
+        assert_eq!(specs[1].namespace, "jobs");
+
+        let specs = ConfigFile::from_toml("[[apps]]\nname = \"api\"\nscript = \"api.js\"\n")
+            .unwrap()
+            .into_specs(Path::new("/project"))
+            .unwrap();
+        assert_eq!(specs[0].namespace, DEFAULT_NAMESPACE);
+
+        let invalid =
+            ConfigFile::from_toml("namespace = \"a b\"\n[[apps]]\nname = \"api\"\n").unwrap();
+        assert!(invalid.into_specs(Path::new("/project")).is_err());
+    }
+
     #[test]
     fn test_config_not_found() {
         let result = ConfigFile::load(Path::new("/nonexistent/config.toml"));
@@ -481,22 +1155,34 @@ apps:
     fn test_app_config_to_spec_with_new_fields() {
         let app_config = AppConfig {
             name: "test".to_string(),
+            namespace: None,
             mode: Some("node".to_string()),
             script: Some("app.js".to_string()),
             bin: None,
             cwd: Some("./src".to_string()),
+            workspace: None,
+            cargo_features: vec![],
+            cargo_profile: None,
+            cargo_target: None,
             args: vec!["--verbose".to_string()],
             env: HashMap::from([("NODE_ENV".to_string(), "production".to_string())]),
             env_file: None,
+            watch_env_file: None,
             watch: true,
             ignore: vec!["dist".to_string()],
+            watch_paths: vec![],
+            watch_extensions: vec![],
+            watch_poll_ms: None,
+            reload_on_change: false,
+            build_timeout_secs: None,
             restart_delay: Some(1000),
             max_restarts: Some(5),
             kill_timeout: Some(5000),
             no_autorestart: false,
             instances: 4,
-            port: Some(3000),
+            port: Some(PortSetting::Fixed(3000)),
             port_range: Some(PortRange { start: 3000, end: 3003 }),
+            on_port_conflict: Some(PortConflictPolicy::Reassign),
             health_check: Some(HealthCheckConfig {
                 http_url: Some("http://localhost:3000/health".to_string()),
                 script: None,
@@ -504,17 +1190,53 @@ apps:
                 interval_secs: Some(30),
                 timeout_secs: Some(10),
                 retries: Some(5),
+                ..Default::default()
+            }),
+            readiness_check: Some(HealthCheckConfig {
+                http_url: Some("http://localhost:3000/ready".to_string()),
+                retries: Some(1),
+                ..Default::default()
             }),
+            warmup: None,
+            wait_for: Vec::new(),
+            wait_for_timeout_secs: None,
             max_memory_mb: Some(512),
             hooks: Some(HooksConfig {
-                on_start: Some("echo started".to_string()),
-                on_crash: Some("/scripts/notify.sh".to_string()),
+                on_start: Some(Hook::from("echo started")),
+                on_crash: Some(Hook::from("/scripts/notify.sh")),
                 on_stop: None,
                 on_restart: None,
                 on_error: None,
+                on_pre_reload: None,
+                on_post_reload: None,
             }),
+            hook_user: None,
             tags: vec!["web".to_string(), "production".to_string()],
+            depends_on: vec![],
+            priority: 10,
+            startup_delay: Some(2000),
             max_uptime_secs: Some(86400),
+            sandbox: None,
+            logs: Some(LogSettings {
+                out: Some("logs/api.log".into()),
+                err: None,
+                merge: true,
+                timestamps: LogTimestamps::Rfc3339,
+                overflow: LogOverflow::DropOldest,
+                max_line_bytes: None,
+                raw: true,
+            }),
+            limits: Some(Limits {
+                nofile: Some(65535),
+                nproc: None,
+                core: Some(Limits::UNLIMITED),
+            }),
+            triggers: vec![LogTrigger {
+                pattern: "FATAL: out of memory".to_string(),
+                action: TriggerAction::Restart,
+                run: None,
+                cooldown_secs: 30,
+            }],
         };
 
         let base_dir = Path::new("/project");
@@ -530,38 +1252,63 @@ apps:
         assert_eq!(spec.instances, 4);
         assert_eq!(spec.port, Some(3000));
         assert_eq!(spec.port_range, Some((3000, 3003)));
+        assert_eq!(spec.on_port_conflict, PortConflictPolicy::Reassign);
         assert_eq!(spec.max_memory_mb, Some(512));
 
+        assert_eq!(spec.stderr_log_path(), spec.cwd.join("logs/api.log"));
+        assert_eq!(spec.logs.timestamps, LogTimestamps::Rfc3339);
+        assert_eq!(spec.logs.overflow, LogOverflow::DropOldest);
+        assert!(spec.logs.raw);
         let hc = spec.health_check.unwrap();
         assert_eq!(hc.http_url, Some("http://localhost:3000/health".to_string()));
         assert_eq!(hc.expected_status, vec![200, 201]);
         assert_eq!(hc.interval_secs, 30);
         assert_eq!(hc.timeout_secs, 10);
         assert_eq!(hc.retries, 5);
+        let rc = spec.readiness_check.unwrap();
+        assert_eq!(rc.http_url, Some("http://localhost:3000/ready".to_string()));
+        assert_eq!(rc.retries, 1);
+
+        assert_eq!(spec.triggers[0].action, TriggerAction::Restart);
 
         // Test hooks
-        assert_eq!(spec.hooks.on_start, Some("echo started".to_string()));
-        assert_eq!(spec.hooks.on_crash, Some("/scripts/notify.sh".to_string()));
+        assert_eq!(spec.hooks.on_start, Some(Hook::from("echo started")));
+        assert_eq!(spec.hooks.on_crash, Some(Hook::from("/scripts/notify.sh")));
         assert!(spec.hooks.on_stop.is_none());
 
         // Test tags and max_uptime
         assert_eq!(spec.tags, vec!["web", "production"]);
         assert_eq!(spec.max_uptime_secs, Some(86400));
+        assert_eq!(spec.priority, 10);
+        assert_eq!(spec.startup_delay_ms, Some(2000));
+        assert_eq!(spec.limits.nofile, Some(65535));
+        assert_eq!(spec.limits.core, Some(Limits::UNLIMITED));
     }
 
     #[test]
     fn test_app_config_to_spec() {
         let app_config = AppConfig {
             name: "test".to_string(),
+            namespace: None,
             mode: Some("node".to_string()),
             script: Some("app.js".to_string()),
             bin: None,
             cwd: Some("./src".to_string()),
+            workspace: None,
+            cargo_features: vec![],
+            cargo_profile: None,
+            cargo_target: None,
             args: vec!["--verbose".to_string()],
             env: HashMap::from([("NODE_ENV".to_string(), "production".to_string())]),
             env_file: None,
+            watch_env_file: None,
             watch: true,
             ignore: vec!["dist".to_string()],
+            watch_paths: vec![],
+            watch_extensions: vec![],
+            watch_poll_ms: None,
+            reload_on_change: false,
+            build_timeout_secs: None,
             restart_delay: Some(1000),
             max_restarts: Some(5),
             kill_timeout: Some(5000),
@@ -569,11 +1316,24 @@ apps:
             instances: 1,
             port: None,
             port_range: None,
+            on_port_conflict: None,
             health_check: None,
+            readiness_check: None,
+            warmup: None,
+            wait_for: Vec::new(),
+            wait_for_timeout_secs: None,
             max_memory_mb: None,
             hooks: None,
+            hook_user: None,
             tags: vec![],
+            depends_on: vec![],
+            priority: 0,
+            startup_delay: None,
             max_uptime_secs: None,
+            sandbox: None,
+            limits: None,
+            logs: None,
+            triggers: Vec::new(),
         };
 
         let base_dir = Path::new("/project");
@@ -600,6 +1360,7 @@ script = "server.js"
 [apps.hooks]
 on_crash = "/scripts/notify.sh"
 on_restart = "echo 'Restarted!' >> /tmp/hooks.log"
+on_pre_reload = "./drain.sh"
 "#;
         let mut file = NamedTempFile::with_suffix(".toml").unwrap();
         file.write_all(config_content.as_bytes()).unwrap();
@@ -608,9 +1369,63 @@ on_restart = "echo 'Restarted!' >> /tmp/hooks.log"
         assert_eq!(config.apps.len(), 1);
         assert!(config.apps[0].hooks.is_some());
         let hooks = config.apps[0].hooks.as_ref().unwrap();
-        assert_eq!(hooks.on_crash, Some("/scripts/notify.sh".to_string()));
-        assert_eq!(hooks.on_restart, Some("echo 'Restarted!' >> /tmp/hooks.log".to_string()));
+        assert_eq!(hooks.on_crash, Some(Hook::from("/scripts/notify.sh")));
+        assert_eq!(
+            hooks.on_restart,
+            Some(Hook::from("echo 'Restarted!' >> /tmp/hooks.log"))
+        );
+        assert_eq!(hooks.on_pre_reload, Some(Hook::from("./drain.sh")));
         assert!(hooks.on_start.is_none());
+        assert!(hooks.on_post_reload.is_none());
+    }
+
+    #[test]
+    fn test_config_with_exec_hooks() {
+        let config_content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+hook_user = "deploy"
+
+[apps.hooks]
+on_start = "./started.sh"
+on_crash = { command = "/usr/bin/curl", args = ["-d", "{app_name} exited {exit_code}", "https://hooks.example.com"], timeout_secs = 5 }
+"#;
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(config_content.as_bytes()).unwrap();
+
+        let config = ConfigFile::load(file.path()).unwrap();
+        let spec = config
+            .apps
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_spec(file.path().parent().unwrap())
+            .unwrap();
+        assert_eq!(spec.hook_user.as_deref(), Some("deploy"));
+        assert_eq!(spec.hooks.on_start, Some(Hook::from("./started.sh")));
+        let crash = spec.hooks.on_crash.unwrap();
+        assert_eq!(crash.timeout_secs(), 5);
+        match crash {
+            Hook::Detailed(hook) => {
+                assert_eq!(hook.command.as_deref(), Some("/usr/bin/curl"));
+                assert_eq!(hook.args[1], "{app_name} exited {exit_code}");
+            }
+            other => panic!("expected a command hook, got {:?}", other),
+        }
+
+        let invalid = config_content.replace("command = \"/usr/bin/curl\"", "run = \"curl\"");
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(invalid.as_bytes()).unwrap();
+        let config = ConfigFile::load(file.path()).unwrap();
+        let err = config
+            .apps
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_spec(file.path().parent().unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("hooks.on_crash"));
     }
 
     #[test]
@@ -630,8 +1445,11 @@ apps:
         assert_eq!(config.apps.len(), 1);
         assert!(config.apps[0].hooks.is_some());
         let hooks = config.apps[0].hooks.as_ref().unwrap();
-        assert_eq!(hooks.on_start, Some("echo 'starting' >> /tmp/hooks.log".to_string()));
-        assert_eq!(hooks.on_crash, Some("/scripts/notify.sh".to_string()));
+        assert_eq!(
+            hooks.on_start,
+            Some(Hook::from("echo 'starting' >> /tmp/hooks.log"))
+        );
+        assert_eq!(hooks.on_crash, Some(Hook::from("/scripts/notify.sh")));
     }
 
     #[test]
@@ -653,7 +1471,84 @@ EMPTY=
         assert_eq!(env.get("API_KEY"), Some(&"secret123".to_string()));
         assert_eq!(env.get("DEBUG"), Some(&"true".to_string()));
         assert_eq!(env.get("EMPTY"), Some(&"".to_string()));
-        assert!(env.get("Comment").is_none());
+        assert!(!env.contains_key("Comment"));
+    }
+
+    #[test]
+    fn test_env_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".env"),
+            "DATABASE_URL=postgres://db/app\nPORT=4000\n",
+        )
+        .unwrap();
+        let config_content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+env_file = ".env"
+env = { PORT = "3000", NODE_ENV = "production" }
+"#;
+        let config = ConfigFile::parse(config_content, ConfigFormat::Toml).unwrap();
+        let spec = config
+            .apps
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_spec(dir.path())
+            .unwrap();
+
+        // The env file is applied over the config's env table
+        assert_eq!(spec.env["PORT"], "4000");
+        assert_eq!(spec.env_source("PORT"), EnvSource::EnvFile);
+        assert_eq!(spec.env_source("DATABASE_URL"), EnvSource::EnvFile);
+        assert_eq!(spec.env_source("NODE_ENV"), EnvSource::Config);
+        assert_eq!(spec.env_file, Some(dir.path().join(".env")));
+        assert_eq!(spec.watch_env_file, None);
+    }
+
+    #[test]
+    fn test_watch_env_file_needs_env_file() {
+        let config_content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+watch_env_file = "restart"
+"#;
+        let config = ConfigFile::parse(config_content, ConfigFormat::Toml).unwrap();
+        let app = config.apps.into_iter().next().unwrap();
+        assert!(app.into_spec(Path::new("/tmp")).is_err());
+    }
+
+    #[test]
+    fn test_watch_filters() {
+        let config_content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+watch = true
+watch_paths = ["src", "config/app.json"]
+watch_extensions = [".JS", "json"]
+watch_poll_ms = 500
+"#;
+        let config = ConfigFile::parse(config_content, ConfigFormat::Toml).unwrap();
+        let spec = config
+            .apps
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_spec(Path::new("/srv/api"))
+            .unwrap();
+
+        assert_eq!(spec.watch_extensions, vec!["js", "json"]);
+        assert_eq!(spec.watch_poll_ms, Some(500));
+        assert_eq!(
+            spec.watch_targets(),
+            vec![
+                PathBuf::from("/srv/api/src"),
+                PathBuf::from("/srv/api/config/app.json")
+            ]
+        );
     }
 
     #[test]
@@ -665,6 +1560,7 @@ EMPTY=
             interval_secs: None,
             timeout_secs: None,
             retries: None,
+            ..Default::default()
         };
 
         let hc = hc_config.into_health_check();
@@ -674,6 +1570,123 @@ EMPTY=
         assert_eq!(hc.interval_secs, 30);
         assert_eq!(hc.timeout_secs, 5);
         assert_eq!(hc.retries, 3);
+        assert_eq!(hc.initial_delay_secs, 5);
+        assert_eq!(hc.success_threshold, 1);
+    }
+
+    #[test]
+    fn test_liveness_and_readiness_checks() {
+        let config_content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+
+[apps.liveness_check]
+tcp_port = 3000
+on_unhealthy = "restart"
+
+[apps.readiness_check]
+http_url = "http://localhost:3000/ready"
+"#;
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(config_content.as_bytes()).unwrap();
+
+        let specs = ConfigFile::load(file.path())
+            .unwrap()
+            .into_specs(Path::new("/srv"))
+            .unwrap();
+        let liveness = specs[0].health_check.as_ref().unwrap();
+        assert_eq!(liveness.tcp_port, Some(3000));
+        assert_eq!(liveness.on_unhealthy, UnhealthyAction::Restart);
+        let readiness = specs[0].readiness_check.as_ref().unwrap();
+        assert_eq!(
+            readiness.http_url.as_deref(),
+            Some("http://localhost:3000/ready")
+        );
+
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(format!("{}on_unhealthy = \"stop\"\n", config_content).as_bytes())
+            .unwrap();
+        let err = ConfigFile::load(file.path())
+            .unwrap()
+            .into_specs(Path::new("/srv"))
+            .unwrap_err();
+        assert!(err.to_string().contains("on_unhealthy"));
+    }
+
+    #[test]
+    fn test_warmup() {
+        let config_content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+
+[apps.warmup]
+urls = ["http://localhost:3000/warmup"]
+requests = 10
+"#;
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(config_content.as_bytes()).unwrap();
+
+        let specs = ConfigFile::load(file.path())
+            .unwrap()
+            .into_specs(Path::new("/srv"))
+            .unwrap();
+        let warmup = specs[0].warmup.as_ref().unwrap();
+        assert_eq!(
+            warmup.urls,
+            vec!["http://localhost:3000/warmup".to_string()]
+        );
+        assert_eq!(warmup.requests, 10);
+        assert_eq!(warmup.script, None);
+        assert_eq!(warmup.timeout_secs, DEFAULT_WARMUP_TIMEOUT_SECS);
+
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(
+            b"[[apps]]\nname = \"api\"\nscript = \"server.js\"\n\n[apps.warmup]\nrequests = 3\n",
+        )
+        .unwrap();
+        let err = ConfigFile::load(file.path())
+            .unwrap()
+            .into_specs(Path::new("/srv"))
+            .unwrap_err();
+        assert!(err.to_string().contains("warmup needs"), "{}", err);
+    }
+
+    #[test]
+    fn test_wait_for() {
+        let config_content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+wait_for = ["tcp://db:5432", "http://localhost:8200/health", "run/ready"]
+wait_for_timeout_secs = 120
+"#;
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(config_content.as_bytes()).unwrap();
+
+        let specs = ConfigFile::load(file.path())
+            .unwrap()
+            .into_specs(Path::new("/srv"))
+            .unwrap();
+        assert_eq!(
+            specs[0].wait_targets().unwrap(),
+            vec![
+                WaitTarget::Tcp("db:5432".into()),
+                WaitTarget::Http("http://localhost:8200/health".into()),
+                WaitTarget::Path("run/ready".into()),
+            ]
+        );
+        assert_eq!(specs[0].wait_for_timeout_secs, Some(120));
+
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(config_content.replace("tcp://db:5432", "tcp://db").as_bytes())
+            .unwrap();
+        let err = ConfigFile::load(file.path())
+            .unwrap()
+            .into_specs(Path::new("/srv"))
+            .unwrap_err();
+        assert!(err.to_string().contains("tcp://host:port"), "{}", err);
     }
 
     #[test]
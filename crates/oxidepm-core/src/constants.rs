@@ -1,6 +1,7 @@
 //! Constants and default values for OxidePM
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Default OxidePM home directory name
 pub const OXIDEPM_DIR: &str = ".oxidepm";
@@ -14,6 +15,46 @@ pub const DB_FILE: &str = "oxidepm.db";
 /// Default saved processes file
 pub const SAVED_FILE: &str = "saved.json";
 
+/// Append-only log of control requests, one JSON object per line
+pub const AUDIT_FILE: &str = "audit.log";
+
+/// Liveness record the daemon rewrites every `HEARTBEAT_INTERVAL_SECS`
+pub const HEARTBEAT_FILE: &str = "heartbeat.json";
+
+/// Compact binary snapshot of saved processes (fast resurrect path)
+pub const SNAPSHOT_FILE: &str = "saved.snapshot";
+
+/// Max apps started concurrently during resurrect
+pub const RESURRECT_CONCURRENCY: usize = 16;
+
+/// How many apps a stop/restart/delete over a selector handles at once
+pub const BULK_OP_CONCURRENCY: usize = 16;
+
+/// How long a group rollout waits for a batch to become healthy
+pub const DEFAULT_ROLLOUT_HEALTH_TIMEOUT_SECS: u64 = 60;
+
+/// How long an app without a health check must stay up to count as healthy
+/// in a rollout, so one that crashes on startup halts it
+pub const ROLLOUT_SETTLE_MS: i64 = 1000;
+
+/// Encrypted secrets store
+pub const SECRETS_FILE: &str = "secrets.json";
+
+/// Key used to encrypt the secrets store
+pub const SECRET_KEY_FILE: &str = "secret.key";
+
+/// Environment variable overriding the secret key file location
+pub const SECRET_KEY_ENV: &str = "OXIDEPM_SECRET_KEY_FILE";
+
+/// Remote daemons the CLI can target with --host
+pub const HOSTS_FILE: &str = "hosts.toml";
+
+/// Daemon settings re-read on SIGHUP
+pub const DAEMON_CONFIG_FILE: &str = "daemon.toml";
+
+/// Name for the daemon on this machine when several are listed together
+pub const LOCAL_HOST: &str = "local";
+
 /// Default log directory name
 pub const LOGS_DIR: &str = "logs";
 
@@ -50,15 +91,66 @@ pub const DEFAULT_IGNORE_PATTERNS: &[&str] = &[
     ".DS_Store",
 ];
 
+/// Files in an app's cwd whose patterns watch mode also ignores
+pub const WATCH_IGNORE_FILES: &[&str] = &[".gitignore", ".oxidepmignore"];
+
 /// Default restart delay in milliseconds
 pub const DEFAULT_RESTART_DELAY_MS: u64 = 500;
 
 /// Default max restarts before errored state
 pub const DEFAULT_MAX_RESTARTS: u32 = 15;
 
+/// Default wait before the first health check in seconds
+pub const DEFAULT_HEALTH_INITIAL_DELAY_SECS: u64 = 5;
+
+/// Default time an app's warm-up may take before it is marked ready anyway
+pub const DEFAULT_WARMUP_TIMEOUT_SECS: u64 = 60;
+
+/// Default time an app waits for its `wait_for` targets before the start fails
+pub const DEFAULT_WAIT_FOR_TIMEOUT_SECS: u64 = 60;
+
+/// Supervisor events kept per app in the database
+pub const EVENTS_RETENTION: usize = 1000;
+
+/// Lines in a page of logs when the client does not ask for a number
+pub const DEFAULT_LOG_PAGE_LINES: usize = 100;
+
+/// Most lines returned in one page of logs
+pub const MAX_LOG_PAGE_LINES: usize = 1000;
+
+/// Default number of events shown by `oxidepm events`
+pub const DEFAULT_EVENTS_LIMIT: usize = 50;
+
+/// Default number of entries shown by `oxidepm audit`
+pub const DEFAULT_AUDIT_LIMIT: usize = 100;
+
+/// Health check results kept per app
+pub const HEALTH_HISTORY_SIZE: usize = 50;
+
+/// Default seconds a hook may run before it is killed
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 30;
+
+/// Default seconds a log trigger stays quiet after firing
+pub const DEFAULT_TRIGGER_COOLDOWN_SECS: u64 = 60;
+
+/// Upper bound for exponential restart backoff in milliseconds
+pub const MAX_RESTART_BACKOFF_MS: u64 = 60_000;
+
+/// Ports handed out to `port = "auto"` apps that set no `port_range`
+pub const DEFAULT_AUTO_PORT_RANGE: (u16, u16) = (20000, 29999);
+
+/// Environment variable overriding the auto port range (`START-END`)
+pub const AUTO_PORT_RANGE_ENV: &str = "OXIDEPM_AUTO_PORT_RANGE";
+
 /// Default kill timeout in milliseconds
 pub const DEFAULT_KILL_TIMEOUT_MS: u64 = 3000;
 
+/// How long prepare (e.g. `cargo build`) may run before the start is abandoned
+pub const DEFAULT_BUILD_TIMEOUT_SECS: u64 = 1800;
+
+/// Namespace for apps started without one
+pub const DEFAULT_NAMESPACE: &str = "default";
+
 /// Default crash window in seconds (for crash loop detection)
 pub const DEFAULT_CRASH_WINDOW_SECS: u64 = 60;
 
@@ -68,19 +160,169 @@ pub const DEFAULT_LOG_MAX_SIZE: u64 = 10 * 1024 * 1024;
 /// Default max log files to keep
 pub const DEFAULT_LOG_MAX_FILES: usize = 5;
 
+/// Longest captured line kept, in bytes; the rest is cut with a marker
+pub const DEFAULT_LOG_MAX_LINE_BYTES: usize = 16 * 1024;
+
+/// Captured lines buffered per stream while the log writer catches up
+pub const LOG_BUFFER_LINES: usize = 10_000;
+
+/// Environment variable with the global disk budget for app logs (e.g. "2G")
+pub const LOG_BUDGET_ENV: &str = "OXIDEPM_LOG_BUDGET";
+
+/// How often the daemon enforces the log disk budget
+pub const LOG_VACUUM_INTERVAL_SECS: u64 = 60;
+
+/// How long a database query waits for another connection's lock
+pub const DB_BUSY_TIMEOUT_MS: u64 = 5000;
+
+/// How often the daemon runs `PRAGMA optimize` on its database
+pub const DB_MAINTENANCE_INTERVAL_SECS: u64 = 3600;
+
+/// How often app CPU and memory are sampled for summary reports
+pub const REPORT_SAMPLE_INTERVAL_SECS: u64 = 60;
+
+/// Minimum time between two VACUUMs of the database
+pub const DB_VACUUM_INTERVAL_SECS: u64 = 7 * 24 * 3600;
+
+/// Environment variable pointing the daemon at an external database
+/// (`postgres://...`); unset means the SQLite file in the data directory
+pub const DATABASE_URL_ENV: &str = "OXIDEPM_DATABASE_URL";
+
+/// Environment variable setting the daemon socket's file mode, in octal
+/// (default 0600)
+pub const SOCKET_MODE_ENV: &str = "OXIDEPM_SOCKET_MODE";
+
+/// Environment variable naming the group (name or GID) that owns the socket
+pub const SOCKET_GROUP_ENV: &str = "OXIDEPM_SOCKET_GROUP";
+
+/// Environment variable listing users (names or UIDs, comma-separated) that
+/// may use the socket besides the daemon's own user and root
+pub const ALLOWED_UIDS_ENV: &str = "OXIDEPM_ALLOWED_UIDS";
+
+/// Environment variable listing groups (names or GIDs, comma-separated) whose
+/// members may use the socket, matched against the client's primary group
+pub const ALLOWED_GIDS_ENV: &str = "OXIDEPM_ALLOWED_GIDS";
+
+/// Environment variable overriding the home directory (socket, database,
+/// logs), so several independent daemons can run side by side
+pub const HOME_ENV: &str = "OXIDEPM_HOME";
+
+/// Environment variable selecting ephemeral mode (`1`/`true`): the daemon keeps
+/// everything in memory and the CLI looks for it under the temp directory.
+/// `oxidepmd --ephemeral` sets it for the daemon and its children.
+pub const EPHEMERAL_ENV: &str = "OXIDEPM_EPHEMERAL";
+
+/// Environment variable enabling auto-save of the process list every N minutes
+pub const AUTOSAVE_ENV: &str = "OXIDEPM_AUTOSAVE";
+
+/// How often the daemon writes its heartbeat file
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 5;
+
+/// How often the watchdog checks that the daemon's event loop is responsive
+pub const WATCHDOG_PROBE_MS: u64 = 250;
+
+/// A watchdog probe this much later than scheduled counts as an event-loop stall
+pub const WATCHDOG_STALL_MS: u64 = 1000;
+
+/// Environment variable with a TCP address (e.g. "0.0.0.0:9700") the daemon also listens on
+pub const TCP_LISTEN_ENV: &str = "OXIDEPM_TCP_LISTEN";
+
+/// Environment variable with the token TCP clients must present
+pub const TCP_TOKEN_ENV: &str = "OXIDEPM_TCP_TOKEN";
+
+/// How long a TCP client has to authenticate
+pub const TCP_AUTH_TIMEOUT_SECS: u64 = 5;
+
 /// Default debounce time for watch mode in milliseconds
 pub const DEFAULT_DEBOUNCE_MS: u64 = 200;
 
+/// Default interval for the polling watch backend in milliseconds
+pub const DEFAULT_WATCH_POLL_MS: u64 = 1000;
+
+/// How often binary-mode apps are checked for a replaced executable
+pub const BINARY_CHECK_INTERVAL_SECS: u64 = 2;
+
+/// How often watched env files are checked for changes
+pub const ENV_FILE_CHECK_INTERVAL_SECS: u64 = 2;
+
+/// How long drained apps keep running before they are stopped
+pub const DEFAULT_DRAIN_TIMEOUT_SECS: u64 = 30;
+
 /// Default metrics polling interval in seconds
 pub const DEFAULT_METRICS_INTERVAL_SECS: u64 = 2;
 
-/// Get the OxidePM home directory
+/// Environment variable overriding how often app CPU and memory are sampled,
+/// in seconds
+pub const METRICS_INTERVAL_ENV: &str = "OXIDEPM_METRICS_INTERVAL";
+
+/// How long connecting to the daemon (and the TCP handshake) may take
+pub const IPC_CONNECT_TIMEOUT_MS: u64 = 5000;
+
+/// How long a read-only request waits for its response by default
+pub const DEFAULT_IPC_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Environment variable overriding the request timeout, in seconds (0 waits forever)
+pub const IPC_TIMEOUT_ENV: &str = "OXIDEPM_IPC_TIMEOUT";
+
+/// How often a read-only request is retried after a connection failure or timeout
+pub const DEFAULT_IPC_RETRIES: u32 = 2;
+
+/// Delay before the first retry; it doubles for each further one, plus jitter
+pub const IPC_RETRY_DELAY_MS: u64 = 100;
+
+/// How long a client waits for a start, restart or other long request
+/// before it carries on in the background as an operation
+pub const DEFAULT_REQUEST_BUDGET_SECS: u64 = 60;
+
+/// Environment variable overriding the request budget, in seconds (0 waits forever)
+pub const REQUEST_BUDGET_ENV: &str = "OXIDEPM_REQUEST_BUDGET";
+
+/// How often a client polls an operation that carries on in the background
+pub const OPERATION_POLL_MS: u64 = 1000;
+
+/// How long the daemon remembers a finished operation
+pub const OPERATION_RETENTION_SECS: u64 = 3600;
+
+/// Finished operations the daemon remembers at most
+pub const MAX_FINISHED_OPERATIONS: usize = 100;
+
+/// Output lines kept per operation
+pub const OPERATION_LOG_LINES: usize = 20;
+
+/// Get the OxidePM home directory: `OXIDEPM_HOME` when set, otherwise
+/// `~/.oxidepm`. An ephemeral daemon keeps its socket and logs under the temp
+/// directory instead, so nothing is written to $HOME.
 pub fn oxidepm_home() -> PathBuf {
+    if let Some(home) = std::env::var_os(HOME_ENV).filter(|home| !home.is_empty()) {
+        return PathBuf::from(home);
+    }
+    if ephemeral() {
+        let user = std::env::var("USER").unwrap_or_else(|_| "ephemeral".to_string());
+        return std::env::temp_dir().join(format!("oxidepm-{}", user));
+    }
     dirs::home_dir()
         .map(|h| h.join(OXIDEPM_DIR))
         .unwrap_or_else(|| PathBuf::from(OXIDEPM_DIR))
 }
 
+/// Home directory of a named profile (`--profile work` is `~/.oxidepm-work`)
+pub fn profile_home(name: &str) -> crate::Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(crate::Error::ConfigError(format!(
+            "Invalid profile name '{}': use letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    let dir = format!("{}-{}", OXIDEPM_DIR, name);
+    Ok(dirs::home_dir()
+        .map(|h| h.join(&dir))
+        .unwrap_or_else(|| PathBuf::from(dir)))
+}
+
 /// Get the socket path
 pub fn socket_path() -> PathBuf {
     oxidepm_home().join(SOCKET_FILE)
@@ -96,6 +338,138 @@ pub fn saved_path() -> PathBuf {
     oxidepm_home().join(SAVED_FILE)
 }
 
+/// Get the audit log path
+pub fn audit_path() -> PathBuf {
+    oxidepm_home().join(AUDIT_FILE)
+}
+
+/// Get the heartbeat file path
+pub fn heartbeat_path() -> PathBuf {
+    oxidepm_home().join(HEARTBEAT_FILE)
+}
+
+/// Get the binary snapshot path
+pub fn snapshot_path() -> PathBuf {
+    oxidepm_home().join(SNAPSHOT_FILE)
+}
+
+/// Get the remote hosts file path
+pub fn hosts_path() -> PathBuf {
+    oxidepm_home().join(HOSTS_FILE)
+}
+
+/// Get the daemon settings file path
+pub fn daemon_config_path() -> PathBuf {
+    oxidepm_home().join(DAEMON_CONFIG_FILE)
+}
+
+/// Get the encrypted secrets store path
+pub fn secrets_path() -> PathBuf {
+    oxidepm_home().join(SECRETS_FILE)
+}
+
+/// Get the secret key file path (`OXIDEPM_SECRET_KEY_FILE` overrides the default)
+pub fn secret_key_path() -> PathBuf {
+    std::env::var_os(SECRET_KEY_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| oxidepm_home().join(SECRET_KEY_FILE))
+}
+
+/// Get the auto port range (`OXIDEPM_AUTO_PORT_RANGE` overrides the default)
+pub fn auto_port_range() -> (u16, u16) {
+    std::env::var(AUTO_PORT_RANGE_ENV)
+        .ok()
+        .and_then(|value| parse_port_range(&value))
+        .unwrap_or(DEFAULT_AUTO_PORT_RANGE)
+}
+
+/// Parse a `START-END` port range
+pub fn parse_port_range(value: &str) -> Option<(u16, u16)> {
+    let (start, end) = value.trim().split_once('-')?;
+    let (start, end) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+    (start <= end).then_some((start, end))
+}
+
+/// Get the TCP address and token the daemon should listen with (`OXIDEPM_TCP_LISTEN`/`OXIDEPM_TCP_TOKEN`)
+pub fn tcp_listen() -> Option<(String, Option<String>)> {
+    let addr = std::env::var(TCP_LISTEN_ENV)
+        .ok()
+        .filter(|a| !a.trim().is_empty())?;
+    let token = std::env::var(TCP_TOKEN_ENV).ok().filter(|t| !t.is_empty());
+    Some((addr, token))
+}
+
+/// Whether ephemeral mode is on (`OXIDEPM_EPHEMERAL`)
+pub fn ephemeral() -> bool {
+    std::env::var(EPHEMERAL_ENV).is_ok_and(|value| matches!(value.trim(), "1" | "true" | "yes"))
+}
+
+/// Get the external database URL (`OXIDEPM_DATABASE_URL`)
+pub fn database_url() -> Option<String> {
+    std::env::var(DATABASE_URL_ENV)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+}
+
+/// Get the global log disk budget in bytes (`OXIDEPM_LOG_BUDGET`, unset means no budget)
+pub fn log_budget() -> Option<u64> {
+    std::env::var(LOG_BUDGET_ENV)
+        .ok()
+        .and_then(|value| parse_size(&value))
+}
+
+/// Get the auto-save interval in minutes (`OXIDEPM_AUTOSAVE`, unset or 0 means off)
+pub fn autosave_minutes() -> Option<u64> {
+    std::env::var(AUTOSAVE_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|minutes| *minutes > 0)
+}
+
+/// Get the metrics sampling interval in seconds (`OXIDEPM_METRICS_INTERVAL`)
+pub fn metrics_interval_secs() -> u64 {
+    std::env::var(METRICS_INTERVAL_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_METRICS_INTERVAL_SECS)
+}
+
+/// Get the request timeout for read-only IPC requests (`OXIDEPM_IPC_TIMEOUT`;
+/// 0 means none)
+pub fn ipc_request_timeout() -> Option<Duration> {
+    let secs = std::env::var(IPC_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_IPC_REQUEST_TIMEOUT_SECS);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Get the daemon's time budget for long requests (`OXIDEPM_REQUEST_BUDGET`;
+/// 0 means none)
+pub fn request_budget() -> Option<Duration> {
+    let secs = std::env::var(REQUEST_BUDGET_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_REQUEST_BUDGET_SECS);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Parse a size like "100M", "1.5G", "512K" or a plain number of bytes
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim().to_uppercase();
+    let value = value.strip_suffix('B').unwrap_or(&value);
+    let (number, multiplier) = match value.char_indices().last()? {
+        (i, 'K') => (&value[..i], 1u64 << 10),
+        (i, 'M') => (&value[..i], 1 << 20),
+        (i, 'G') => (&value[..i], 1 << 30),
+        (i, 'T') => (&value[..i], 1 << 40),
+        _ => (value, 1),
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    (number.is_finite() && number >= 0.0).then_some((number * multiplier as f64) as u64)
+}
+
 /// Get the logs directory
 pub fn logs_dir() -> PathBuf {
     oxidepm_home().join(LOGS_DIR)
@@ -121,6 +495,14 @@ mod tests {
         assert!(home.to_string_lossy().contains(".oxidepm"));
     }
 
+    #[test]
+    fn test_profile_home() {
+        let home = profile_home("work").unwrap();
+        assert!(home.ends_with(".oxidepm-work"));
+        assert!(profile_home("").is_err());
+        assert!(profile_home("../etc").is_err());
+    }
+
     #[test]
     fn test_socket_path() {
         let path = socket_path();
@@ -132,4 +514,24 @@ mod tests {
         let path = log_path("myapp", "out");
         assert!(path.to_string_lossy().contains("myapp-out.log"));
     }
+
+    #[test]
+    fn test_parse_port_range() {
+        assert_eq!(parse_port_range("20000-20100"), Some((20000, 20100)));
+        assert_eq!(parse_port_range(" 3000 - 3000 "), Some((3000, 3000)));
+        assert_eq!(parse_port_range("3001-3000"), None);
+        assert_eq!(parse_port_range("3000"), None);
+        assert_eq!(parse_port_range("3000-70000"), None);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("100M"), Some(100 * 1024 * 1024));
+        assert_eq!(parse_size("1.5g"), Some(1536 * 1024 * 1024));
+        assert_eq!(parse_size("512KB"), Some(512 * 1024));
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("lots"), None);
+        assert_eq!(parse_size("-1M"), None);
+        assert_eq!(parse_size(""), None);
+    }
 }
@@ -0,0 +1,88 @@
+//! Daemon settings (`~/.oxidepm/daemon.toml`), re-read on SIGHUP and
+//! `oxidepm daemon-reload` without restarting the daemon
+//!
+//! ```toml
+//! log_budget = "2G"           # OXIDEPM_LOG_BUDGET takes precedence
+//! request_budget_secs = 120   # OXIDEPM_REQUEST_BUDGET takes precedence
+//! ```
+
+use crate::constants;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DaemonConfig {
+    /// Global disk budget for app logs, e.g. "2G"; the oldest rotated
+    /// files go first once it is exceeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_budget: Option<String>,
+    /// Seconds a client waits on a long request before it carries on in
+    /// the background; 0 for no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_budget_secs: Option<u64>,
+}
+
+impl DaemonConfig {
+    /// Load `~/.oxidepm/daemon.toml`; a missing file means the defaults
+    pub fn load() -> Result<Self> {
+        Self::load_from(&constants::daemon_config_path())
+    }
+
+    /// Load settings from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse and validate a settings file
+    pub fn parse(content: &str) -> Result<Self> {
+        let config: DaemonConfig = toml::from_str(content)?;
+        if let Some(budget) = &config.log_budget {
+            if constants::parse_size(budget).is_none() {
+                return Err(Error::ConfigError(format!(
+                    "Invalid log_budget '{}' (e.g. 500M, 2G)",
+                    budget
+                )));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Log disk budget in bytes: `OXIDEPM_LOG_BUDGET`, else `log_budget`
+    pub fn log_budget(&self) -> Option<u64> {
+        constants::log_budget()
+            .or_else(|| self.log_budget.as_deref().and_then(constants::parse_size))
+    }
+
+    /// How long a client waits on a long request: `OXIDEPM_REQUEST_BUDGET`,
+    /// else `request_budget_secs`, else `DEFAULT_REQUEST_BUDGET_SECS`
+    pub fn request_budget(&self) -> Option<Duration> {
+        match self.request_budget_secs {
+            Some(secs) if std::env::var(constants::REQUEST_BUDGET_ENV).is_err() => {
+                (secs > 0).then(|| Duration::from_secs(secs))
+            }
+            _ => constants::request_budget(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_daemon_config() {
+        let config = DaemonConfig::parse("log_budget = \"2G\"\nrequest_budget_secs = 0").unwrap();
+        assert_eq!(config.log_budget.as_deref(), Some("2G"));
+        assert_eq!(config.request_budget_secs, Some(0));
+
+        assert_eq!(DaemonConfig::parse("").unwrap(), DaemonConfig::default());
+        assert!(DaemonConfig::parse("log_budget = \"lots\"").is_err());
+        assert!(DaemonConfig::parse("log_budgte = \"2G\"").is_err());
+    }
+}
@@ -20,6 +20,9 @@ pub enum Error {
     #[error("Build failed: {0}")]
     BuildFailed(String),
 
+    #[error("Dependency download failed ({kind}): {message}")]
+    DependencyFetchFailed { kind: FetchFailure, message: String },
+
     #[error("Process failed to start: {0}")]
     ProcessStartFailed(String),
 
@@ -47,6 +50,9 @@ pub enum Error {
     #[error("Invalid mode: {0}")]
     InvalidMode(String),
 
+    #[error("Invalid signal: {0}")]
+    InvalidSignal(String),
+
     #[error("File not found: {0}")]
     FileNotFound(PathBuf),
 
@@ -59,6 +65,15 @@ pub enum Error {
     #[error("Health check failed")]
     HealthCheckFailed,
 
+    #[error("Port conflict: {0}")]
+    PortConflict(String),
+
+    #[error("Secret not found: {0}")]
+    SecretNotFound(String),
+
+    #[error("Secret store error: {0}")]
+    SecretError(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -72,6 +87,171 @@ pub enum Error {
     YamlError(#[from] serde_yaml::Error),
 }
 
+/// Stable error taxonomy shared by the daemon, CLI exit codes and JSON output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    General,
+    InvalidInput,
+    AppNotFound,
+    DaemonUnreachable,
+    BuildFailed,
+    StartFailed,
+    ConfigError,
+    AlreadyExists,
+    Timeout,
+    PermissionDenied,
+    HealthCheckFailed,
+    IpcError,
+}
+
+impl ErrorCode {
+    /// Stable machine-readable code
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::General => "general",
+            ErrorCode::InvalidInput => "invalid_input",
+            ErrorCode::AppNotFound => "app_not_found",
+            ErrorCode::DaemonUnreachable => "daemon_unreachable",
+            ErrorCode::BuildFailed => "build_failed",
+            ErrorCode::StartFailed => "start_failed",
+            ErrorCode::ConfigError => "config_error",
+            ErrorCode::AlreadyExists => "already_exists",
+            ErrorCode::Timeout => "timeout",
+            ErrorCode::PermissionDenied => "permission_denied",
+            ErrorCode::HealthCheckFailed => "health_check_failed",
+            ErrorCode::IpcError => "ipc_error",
+        }
+    }
+
+    /// Process exit code for the CLI (2 is left to argument parsing errors)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorCode::General => 1,
+            ErrorCode::InvalidInput => 2,
+            ErrorCode::AppNotFound => 3,
+            ErrorCode::DaemonUnreachable => 4,
+            ErrorCode::BuildFailed => 5,
+            ErrorCode::StartFailed => 6,
+            ErrorCode::ConfigError => 7,
+            ErrorCode::AlreadyExists => 8,
+            ErrorCode::Timeout => 9,
+            ErrorCode::PermissionDenied => 10,
+            ErrorCode::HealthCheckFailed => 11,
+            ErrorCode::IpcError => 12,
+        }
+    }
+
+    /// Parse a code received over IPC (unknown codes map to General)
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "invalid_input" => ErrorCode::InvalidInput,
+            "app_not_found" => ErrorCode::AppNotFound,
+            "daemon_unreachable" => ErrorCode::DaemonUnreachable,
+            "build_failed" => ErrorCode::BuildFailed,
+            "start_failed" => ErrorCode::StartFailed,
+            "config_error" => ErrorCode::ConfigError,
+            "already_exists" | "port_conflict" => ErrorCode::AlreadyExists,
+            "timeout" => ErrorCode::Timeout,
+            "permission_denied" => ErrorCode::PermissionDenied,
+            "health_check_failed" => ErrorCode::HealthCheckFailed,
+            "ipc_error" => ErrorCode::IpcError,
+            // Dependency download failures are a refinement of build failures
+            c if c.starts_with("fetch_") => ErrorCode::BuildFailed,
+            _ => ErrorCode::General,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Failure classes recognised in dependency download output (npm install, cargo fetch)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchFailure {
+    /// Registry unreachable, DNS failure, timeouts
+    Network,
+    /// Private registry rejected the credentials
+    RegistryAuth,
+    /// No space left on the device holding the cache or target dir
+    DiskFull,
+}
+
+impl FetchFailure {
+    /// Stable machine-readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            FetchFailure::Network => "fetch_network",
+            FetchFailure::RegistryAuth => "fetch_registry_auth",
+            FetchFailure::DiskFull => "fetch_disk_full",
+        }
+    }
+
+    /// Suggested remediation for the user
+    pub fn hint(&self) -> &'static str {
+        match self {
+            FetchFailure::Network => {
+                "Check network connectivity and proxy settings (HTTP_PROXY/HTTPS_PROXY), then retry"
+            }
+            FetchFailure::RegistryAuth => {
+                "Check registry credentials (.npmrc auth token, ~/.cargo/credentials.toml) for the private registry"
+            }
+            FetchFailure::DiskFull => {
+                "Free disk space (e.g. clear the npm/cargo cache or old target directories), then retry"
+            }
+        }
+    }
+
+    /// Classify prepare output into a known failure class
+    pub fn classify(output: &str) -> Option<Self> {
+        let lower = output.to_lowercase();
+
+        const DISK_FULL: &[&str] = &["no space left on device", "enospc", "disk quota exceeded"];
+        const AUTH: &[&str] = &[
+            "e401",
+            "e403",
+            "401 unauthorized",
+            "403 forbidden",
+            "authentication required",
+            "unable to authenticate",
+            "failed to authenticate",
+            "no token found",
+        ];
+        const NETWORK: &[&str] = &[
+            "enotfound",
+            "etimedout",
+            "econnrefused",
+            "econnreset",
+            "eai_again",
+            "network request",
+            "could not resolve host",
+            "failed to download",
+            "spurious network error",
+            "connection timed out",
+            "network is unreachable",
+            "failed to get successful http response",
+        ];
+
+        if DISK_FULL.iter().any(|p| lower.contains(p)) {
+            Some(FetchFailure::DiskFull)
+        } else if AUTH.iter().any(|p| lower.contains(p)) {
+            Some(FetchFailure::RegistryAuth)
+        } else if NETWORK.iter().any(|p| lower.contains(p)) {
+            Some(FetchFailure::Network)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for FetchFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
 /// Result type alias for OxidePM
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -91,6 +271,66 @@ impl Error {
     pub fn process_start<S: Into<String>>(msg: S) -> Self {
         Error::ProcessStartFailed(msg.into())
     }
+
+    /// Build a prepare error, classifying dependency download failures
+    pub fn from_build_output<S: Into<String>>(output: S) -> Self {
+        let message = output.into();
+        match FetchFailure::classify(&message) {
+            Some(kind) => Error::DependencyFetchFailed { kind, message },
+            None => Error::BuildFailed(message),
+        }
+    }
+
+    /// Taxonomy class of this error
+    pub fn kind(&self) -> ErrorCode {
+        match self {
+            Error::AppNotFound(_) => ErrorCode::AppNotFound,
+            Error::AppAlreadyExists(_) | Error::DaemonAlreadyRunning | Error::PortConflict(_) => {
+                ErrorCode::AlreadyExists
+            }
+            Error::DaemonNotRunning | Error::IpcConnectionFailed(_) => ErrorCode::DaemonUnreachable,
+            Error::BuildFailed(_) | Error::DependencyFetchFailed { .. } => ErrorCode::BuildFailed,
+            Error::ProcessStartFailed(_) | Error::ProcessNotRunning(_) => ErrorCode::StartFailed,
+            Error::ConfigError(_)
+            | Error::ConfigNotFound(_)
+            | Error::TomlError(_)
+            | Error::YamlError(_)
+            | Error::SecretNotFound(_)
+            | Error::SecretError(_) => ErrorCode::ConfigError,
+            Error::InvalidSelector(_) | Error::InvalidMode(_) | Error::InvalidSignal(_) => {
+                ErrorCode::InvalidInput
+            }
+            Error::PermissionDenied(_) => ErrorCode::PermissionDenied,
+            Error::Timeout(_) => ErrorCode::Timeout,
+            Error::HealthCheckFailed => ErrorCode::HealthCheckFailed,
+            Error::IpcError(_) => ErrorCode::IpcError,
+            Error::DbError(_)
+            | Error::FileNotFound(_)
+            | Error::IoError(_)
+            | Error::JsonError(_) => ErrorCode::General,
+        }
+    }
+
+    /// Most specific machine-readable code (e.g. `fetch_network` rather than `build_failed`)
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::DependencyFetchFailed { kind, .. } => kind.code(),
+            Error::PortConflict(_) => "port_conflict",
+            _ => self.kind().as_str(),
+        }
+    }
+
+    /// Remediation hint, if one is known
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Error::DependencyFetchFailed { kind, .. } => Some(kind.hint()),
+            Error::SecretNotFound(_) => Some("Set it with `oxidepm secret set <NAME>`"),
+            Error::PortConflict(_) => {
+                Some("Pass --env PORT=<port>, or set on_port_conflict = \"reassign\" to pick a free port")
+            }
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -109,4 +349,59 @@ mod tests {
         let err: Error = io_err.into();
         assert!(matches!(err, Error::IoError(_)));
     }
+
+    #[test]
+    fn test_error_codes() {
+        let err = Error::AppNotFound("api".to_string());
+        assert_eq!(err.code(), "app_not_found");
+        assert_eq!(err.kind().exit_code(), 3);
+        assert_eq!(Error::DaemonNotRunning.kind().exit_code(), 4);
+        assert_eq!(
+            ErrorCode::from_code("app_not_found"),
+            ErrorCode::AppNotFound
+        );
+        assert_eq!(
+            ErrorCode::from_code("fetch_disk_full"),
+            ErrorCode::BuildFailed
+        );
+        assert_eq!(ErrorCode::from_code("something_new"), ErrorCode::General);
+
+        let err = Error::PortConflict("port 3000 is used by api".to_string());
+        assert_eq!(err.code(), "port_conflict");
+        assert_eq!(ErrorCode::from_code(err.code()), ErrorCode::AlreadyExists);
+    }
+
+    #[test]
+    fn test_classify_fetch_failures() {
+        assert_eq!(
+            FetchFailure::classify(
+                "npm ERR! code ENOTFOUND\nnpm ERR! getaddrinfo ENOTFOUND registry.npmjs.org"
+            ),
+            Some(FetchFailure::Network)
+        );
+        assert_eq!(
+            FetchFailure::classify("npm ERR! code E401\nnpm ERR! Unable to authenticate"),
+            Some(FetchFailure::RegistryAuth)
+        );
+        assert_eq!(
+            FetchFailure::classify("error: failed to write: No space left on device (os error 28)"),
+            Some(FetchFailure::DiskFull)
+        );
+        assert_eq!(
+            FetchFailure::classify("error[E0308]: mismatched types"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_build_output() {
+        let err = Error::from_build_output("warning: spurious network error (2 tries remaining)");
+        assert_eq!(err.code(), "fetch_network");
+        assert_eq!(err.kind(), ErrorCode::BuildFailed);
+        assert!(err.hint().is_some());
+
+        let err = Error::from_build_output("error: could not compile `app`");
+        assert!(matches!(err, Error::BuildFailed(_)));
+        assert_eq!(err.code(), "build_failed");
+    }
 }
@@ -0,0 +1,207 @@
+//! Remote daemons the CLI can manage (`~/.oxidepm/hosts.toml`)
+//!
+//! ```toml
+//! [hosts.web1]
+//! address = "10.0.0.11:9700"
+//! token_env = "OXIDEPM_WEB1_TOKEN"
+//!
+//! [hosts.staging]
+//! socket = "/run/oxidepm-staging.sock"   # e.g. forwarded with `ssh -L`
+//! ```
+
+use crate::constants;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Where to reach one daemon
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostConfig {
+    /// Unix socket of the daemon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<PathBuf>,
+    /// `host:port` of a daemon listening on TCP (`OXIDEPM_TCP_LISTEN`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Token the TCP daemon expects (`OXIDEPM_TCP_TOKEN`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Read the token from this environment variable instead of the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+}
+
+impl HostConfig {
+    /// The token to present, from `token_env` if set, otherwise `token`
+    pub fn token(&self) -> Option<String> {
+        self.token_env
+            .as_ref()
+            .and_then(|var| std::env::var(var).ok())
+            .or_else(|| self.token.clone())
+    }
+
+    /// Human-readable location, for listings
+    pub fn location(&self) -> String {
+        match (&self.address, &self.socket) {
+            (Some(address), _) => format!("tcp://{}", address),
+            (None, Some(socket)) => socket.display().to_string(),
+            (None, None) => "-".to_string(),
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<()> {
+        match (&self.socket, &self.address) {
+            (Some(_), Some(_)) => Err(Error::ConfigError(format!(
+                "Host '{}' sets both socket and address; use one",
+                name
+            ))),
+            (None, None) => Err(Error::ConfigError(format!(
+                "Host '{}' needs a socket or an address",
+                name
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Named remote daemons
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostsFile {
+    #[serde(default)]
+    pub hosts: BTreeMap<String, HostConfig>,
+}
+
+impl HostsFile {
+    /// Load `~/.oxidepm/hosts.toml`; a missing file means no hosts
+    pub fn load() -> Result<Self> {
+        Self::load_from(&constants::hosts_path())
+    }
+
+    /// Load hosts from a specific file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse and validate a hosts file
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: HostsFile = toml::from_str(content)?;
+        for (name, host) in &file.hosts {
+            if name == constants::LOCAL_HOST {
+                return Err(Error::ConfigError(format!(
+                    "Host name '{}' is reserved for this machine's daemon",
+                    name
+                )));
+            }
+            host.validate(name)?;
+        }
+        Ok(file)
+    }
+
+    /// Look up a host by name
+    pub fn get(&self, name: &str) -> Result<&HostConfig> {
+        self.hosts.get(name).ok_or_else(|| self.unknown_host(name))
+    }
+
+    /// Hosts named in a comma-separated list; "all" selects every host
+    pub fn select(&self, names: &str) -> Result<Vec<(&str, &HostConfig)>> {
+        let mut selected: Vec<(&str, &HostConfig)> = Vec::new();
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if name == "all" {
+                selected.extend(self.hosts.iter().map(|(n, h)| (n.as_str(), h)));
+            } else {
+                let (name, host) = self
+                    .hosts
+                    .get_key_value(name)
+                    .ok_or_else(|| self.unknown_host(name))?;
+                selected.push((name.as_str(), host));
+            }
+        }
+        selected.sort_by_key(|(name, _)| *name);
+        selected.dedup_by_key(|(name, _)| *name);
+        Ok(selected)
+    }
+
+    fn unknown_host(&self, name: &str) -> Error {
+        let known: Vec<&str> = self.hosts.keys().map(String::as_str).collect();
+        let known = if known.is_empty() {
+            format!("none configured in {}", constants::hosts_path().display())
+        } else {
+            format!("configured: {}", known.join(", "))
+        };
+        Error::ConfigError(format!("Unknown host '{}' ({})", name, known))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hosts() {
+        let file = HostsFile::parse(
+            r#"
+            [hosts.web1]
+            address = "10.0.0.11:9700"
+            token = "s3cret"
+
+            [hosts.staging]
+            socket = "/run/oxidepm-staging.sock"
+            "#,
+        )
+        .unwrap();
+
+        let web1 = file.get("web1").unwrap();
+        assert_eq!(web1.address.as_deref(), Some("10.0.0.11:9700"));
+        assert_eq!(web1.token().as_deref(), Some("s3cret"));
+        assert_eq!(web1.location(), "tcp://10.0.0.11:9700");
+        assert_eq!(
+            file.get("staging").unwrap().location(),
+            "/run/oxidepm-staging.sock"
+        );
+
+        let err = file.get("db1").unwrap_err().to_string();
+        assert!(err.contains("web1") && err.contains("staging"));
+    }
+
+    #[test]
+    fn test_host_needs_one_endpoint() {
+        assert!(HostsFile::parse("[hosts.a]\ntoken = \"x\"").is_err());
+        assert!(HostsFile::parse("[hosts.a]\naddress = \"h:1\"\nsocket = \"/s\"").is_err());
+        assert!(HostsFile::parse("[hosts.local]\nsocket = \"/s\"").is_err());
+    }
+
+    #[test]
+    fn test_select_hosts() {
+        let file =
+            HostsFile::parse("[hosts.a]\nsocket = \"/a\"\n[hosts.b]\nsocket = \"/b\"").unwrap();
+        let names = |spec| -> Vec<&str> {
+            file.select(spec)
+                .unwrap()
+                .into_iter()
+                .map(|(n, _)| n)
+                .collect()
+        };
+        assert_eq!(names("b"), vec!["b"]);
+        assert_eq!(names("b, all"), vec!["a", "b"]);
+        assert!(names("").is_empty());
+        assert!(file.select("a,c").is_err());
+    }
+
+    #[test]
+    fn test_token_env_wins() {
+        let host = HostConfig {
+            address: Some("h:1".into()),
+            token: Some("from-file".into()),
+            token_env: Some("OXIDEPM_TEST_HOST_TOKEN".into()),
+            ..Default::default()
+        };
+        assert_eq!(host.token().as_deref(), Some("from-file"));
+        std::env::set_var("OXIDEPM_TEST_HOST_TOKEN", "from-env");
+        assert_eq!(host.token().as_deref(), Some("from-env"));
+        std::env::remove_var("OXIDEPM_TEST_HOST_TOKEN");
+    }
+}
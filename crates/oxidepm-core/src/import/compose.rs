@@ -0,0 +1,504 @@
+//! Docker Compose service import
+//!
+//! Only services that run a process we can start directly are converted:
+//! those with a `command`/`entrypoint`, or a `build` whose Dockerfile has a
+//! `CMD`/`ENTRYPOINT`. Image-only services (databases, caches) are skipped.
+
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::{package_manager_command, sanitize_name, ImportResult};
+use crate::config::load_env_file;
+use crate::error::{Error, Result};
+use crate::types::{AppMode, AppSpec};
+
+/// Compose file names, in lookup order
+pub const COMPOSE_FILES: &[&str] = &[
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yml",
+    "docker-compose.yaml",
+];
+
+/// Top-level compose file
+#[derive(Debug, Deserialize)]
+pub struct ComposeFile {
+    #[serde(default)]
+    pub services: BTreeMap<String, Service>,
+}
+
+/// The subset of a compose service that maps onto an AppSpec
+#[derive(Debug, Default, Deserialize)]
+pub struct Service {
+    pub image: Option<String>,
+    pub build: Option<Build>,
+    pub command: Option<CommandLine>,
+    pub entrypoint: Option<CommandLine>,
+    pub working_dir: Option<String>,
+    pub environment: Option<Environment>,
+    pub env_file: Option<StringOrList>,
+    #[serde(default)]
+    pub ports: Vec<Port>,
+    pub restart: Option<String>,
+    pub deploy: Option<Deploy>,
+    pub depends_on: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Build {
+    Context(String),
+    Full {
+        context: Option<String>,
+        dockerfile: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum CommandLine {
+    Shell(String),
+    Exec(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Environment {
+    Map(BTreeMap<String, Value>),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StringOrList {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Port {
+    Number(u16),
+    Short(String),
+    Long {
+        target: u16,
+        published: Option<Value>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Deploy {
+    pub replicas: Option<u32>,
+}
+
+/// Parse a compose file
+pub fn parse(yaml: &str) -> Result<ComposeFile> {
+    serde_yaml::from_str(yaml)
+        .map_err(|e| Error::ConfigError(format!("Invalid compose file: {}", e)))
+}
+
+/// Convert compose services into AppSpecs
+///
+/// Relative paths (build contexts, env files, working dirs) resolve against
+/// `base_dir`, the directory holding the compose file.
+pub fn into_specs(file: ComposeFile, base_dir: &Path) -> ImportResult {
+    let mut result = ImportResult::default();
+
+    for (service_name, service) in file.services {
+        let name = sanitize_name(&service_name);
+        match service.into_spec(&name, base_dir, &mut result) {
+            Ok(Some(spec)) => result.specs.push(spec),
+            Ok(None) => {}
+            Err(e) => result.warn(&name, format!("skipped: {}", e)),
+        }
+    }
+
+    result
+}
+
+impl Service {
+    fn into_spec(
+        self,
+        name: &str,
+        base_dir: &Path,
+        result: &mut ImportResult,
+    ) -> Result<Option<AppSpec>> {
+        let context = self.build.as_ref().map(|build| match build {
+            Build::Context(dir) => base_dir.join(dir),
+            Build::Full { context, .. } => base_dir.join(context.as_deref().unwrap_or(".")),
+        });
+        let dockerfile = self.build.as_ref().and_then(|build| match build {
+            Build::Full {
+                dockerfile: Some(file),
+                ..
+            } => Some(file.clone()),
+            _ => None,
+        });
+
+        // Container working directory, used to map absolute container paths back
+        let mut container_dir = self.working_dir.clone().filter(|dir| dir.starts_with('/'));
+
+        let mut argv = split_command_line(self.entrypoint.as_ref());
+        argv.extend(split_command_line(self.command.as_ref()));
+        if argv.is_empty() {
+            if let Some(context) = &context {
+                let path = context.join(dockerfile.as_deref().unwrap_or("Dockerfile"));
+                if let Ok(content) = std::fs::read_to_string(&path) {
+                    let parsed = parse_dockerfile(&content);
+                    argv = parsed.argv;
+                    container_dir = container_dir.or(parsed.workdir);
+                }
+            }
+        }
+        if argv.is_empty() {
+            let reason = match &self.image {
+                Some(image) => format!("image-only service ({}), keep it in Compose", image),
+                None => "no command, entrypoint or Dockerfile CMD".to_string(),
+            };
+            result.warn(name, format!("skipped: {}", reason));
+            return Ok(None);
+        }
+
+        // Host working directory
+        let cwd = match &self.working_dir {
+            Some(dir) if !dir.starts_with('/') => base_dir.join(dir),
+            Some(dir) if Path::new(dir).is_dir() => PathBuf::from(dir),
+            other => {
+                let cwd = context.clone().unwrap_or_else(|| base_dir.to_path_buf());
+                if let Some(dir) = other {
+                    result.warn(
+                        name,
+                        format!(
+                            "working_dir {} is a container path, using {}",
+                            dir,
+                            cwd.display()
+                        ),
+                    );
+                }
+                cwd
+            }
+        };
+
+        if let Some(dir) = &container_dir {
+            argv = argv.into_iter().map(|arg| relativize(arg, dir)).collect();
+        }
+        let (mode, command, args) = resolve_argv(argv);
+
+        let mut spec = AppSpec::try_new(name.to_string(), mode, command, cwd)?;
+        spec.args = args;
+
+        // env_file first, then inline environment (Compose precedence)
+        let mut env = HashMap::new();
+        let env_files = match self.env_file {
+            Some(StringOrList::One(file)) => vec![file],
+            Some(StringOrList::Many(files)) => files,
+            None => Vec::new(),
+        };
+        for file in env_files {
+            let path = base_dir.join(&file);
+            if path.exists() {
+                load_env_file(&path, &mut env)?;
+            } else {
+                result.warn(name, format!("env_file {} not found", file));
+            }
+        }
+        match self.environment {
+            Some(Environment::Map(vars)) => {
+                for (key, value) in vars {
+                    if let Some(value) = scalar_string(&value).or_else(|| std::env::var(&key).ok())
+                    {
+                        env.insert(key, value);
+                    }
+                }
+            }
+            Some(Environment::List(vars)) => {
+                for var in vars {
+                    match var.split_once('=') {
+                        Some((key, value)) => {
+                            env.insert(key.to_string(), value.to_string());
+                        }
+                        None => {
+                            if let Ok(value) = std::env::var(&var) {
+                                env.insert(var, value);
+                            }
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+        spec.env = env;
+
+        let ports: Vec<(Option<u16>, u16)> = self.ports.iter().filter_map(parse_port).collect();
+        if let Some(&(published, target)) = ports.first() {
+            // Without a container in between the process binds its own port
+            spec.port = Some(target);
+            if published.is_some_and(|p| p != target) {
+                result.warn(
+                    name,
+                    format!(
+                        "was published on host port {}, the process now listens on {} directly",
+                        published.unwrap_or(target),
+                        target
+                    ),
+                );
+            }
+        }
+        if ports.len() > 1 {
+            result.warn(name, "only the first port is tracked");
+        }
+
+        if let Some(restart) = &self.restart {
+            spec.restart_policy.auto_restart = restart != "no";
+        }
+        if let Some(replicas) = self.deploy.and_then(|d| d.replicas) {
+            spec.instances = replicas.max(1);
+        }
+        if self.depends_on.is_some() {
+            result.warn(
+                name,
+                "depends_on is not preserved, start order is not guaranteed",
+            );
+        }
+
+        Ok(Some(spec))
+    }
+}
+
+/// CMD/ENTRYPOINT/WORKDIR of the final Dockerfile stage
+#[derive(Debug, Default, PartialEq)]
+struct Dockerfile {
+    argv: Vec<String>,
+    workdir: Option<String>,
+}
+
+fn parse_dockerfile(content: &str) -> Dockerfile {
+    let mut entrypoint = Vec::new();
+    let mut cmd = Vec::new();
+    let mut workdir = None;
+
+    // Join line continuations before splitting into instructions
+    let joined = content.replace("\\\r\n", " ").replace("\\\n", " ");
+    for line in joined.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (instruction, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match instruction.to_ascii_uppercase().as_str() {
+            "FROM" => {
+                entrypoint.clear();
+                cmd.clear();
+                workdir = None;
+            }
+            "WORKDIR" => workdir = Some(rest.to_string()),
+            "ENTRYPOINT" => entrypoint = dockerfile_argv(rest),
+            "CMD" => cmd = dockerfile_argv(rest),
+            _ => {}
+        }
+    }
+
+    entrypoint.extend(cmd);
+    Dockerfile {
+        argv: entrypoint,
+        workdir,
+    }
+}
+
+/// Exec form (`["node", "app.js"]`) or shell form (`node app.js`)
+fn dockerfile_argv(value: &str) -> Vec<String> {
+    serde_json::from_str::<Vec<String>>(value).unwrap_or_else(|_| split_command(value))
+}
+
+fn split_command_line(command: Option<&CommandLine>) -> Vec<String> {
+    match command {
+        Some(CommandLine::Shell(line)) => split_command(line),
+        Some(CommandLine::Exec(argv)) => argv.clone(),
+        None => Vec::new(),
+    }
+}
+
+/// Split a command line on whitespace, honouring single and double quotes
+fn split_command(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+/// Map `/app/server.js` to `server.js` when `/app` is the container workdir
+fn relativize(arg: String, container_dir: &str) -> String {
+    let prefix = format!("{}/", container_dir.trim_end_matches('/'));
+    match arg.strip_prefix(&prefix) {
+        Some(rest) if !rest.is_empty() => rest.to_string(),
+        _ => arg,
+    }
+}
+
+fn resolve_argv(mut argv: Vec<String>) -> (AppMode, String, Vec<String>) {
+    let program = argv.remove(0);
+    if let Some(resolved) = package_manager_command(&program, argv.clone()) {
+        return resolved;
+    }
+
+    let is_node = matches!(
+        Path::new(&program).file_name().and_then(|n| n.to_str()),
+        Some("node") | Some("nodejs")
+    );
+    if is_node && argv.first().is_some_and(|a| !a.starts_with('-')) {
+        let script = argv.remove(0);
+        return (AppMode::Node, script, argv);
+    }
+
+    (AppMode::Cmd, program, argv)
+}
+
+/// Parse a port mapping into (published, target)
+fn parse_port(port: &Port) -> Option<(Option<u16>, u16)> {
+    match port {
+        Port::Number(target) => Some((None, *target)),
+        Port::Long { target, published } => {
+            let published = published
+                .as_ref()
+                .and_then(scalar_string)
+                .and_then(|p| first_port(&p));
+            Some((published, *target))
+        }
+        Port::Short(spec) => {
+            // [ip:][published:]target[/protocol]
+            let spec = spec.split('/').next().unwrap_or(spec);
+            let parts: Vec<&str> = spec.rsplitn(3, ':').collect();
+            let target = first_port(parts[0])?;
+            let published = parts.get(1).and_then(|p| first_port(p));
+            Some((published, target))
+        }
+    }
+}
+
+/// First port of a single port or a "3000-3005" range
+fn first_port(value: &str) -> Option<u16> {
+    value.split('-').next()?.trim().parse().ok()
+}
+
+fn scalar_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compose_import() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("worker")).unwrap();
+        std::fs::write(
+            dir.path().join("worker/Dockerfile"),
+            "FROM rust AS build\nCMD [\"cargo\", \"build\"]\n\nFROM node:20\nWORKDIR /app\nCOPY . .\nCMD [\"node\", \"/app/dist/worker.js\", \"--queue\", \\\n  \"jobs\"]\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(".env"), "API_KEY=secret\n").unwrap();
+
+        let yaml = r#"
+services:
+  db:
+    image: postgres:16
+  api:
+    build: .
+    command: npm run dev
+    working_dir: /usr/src/app
+    env_file: .env
+    environment:
+      NODE_ENV: development
+      DEBUG: 1
+    ports:
+      - "8080:3000"
+    restart: "no"
+  worker:
+    build:
+      context: ./worker
+    environment:
+      - QUEUE=jobs
+    deploy:
+      replicas: 2
+    depends_on: [api]
+"#;
+        let result = into_specs(parse(yaml).unwrap(), dir.path());
+        assert_eq!(result.specs.len(), 2);
+
+        let api = &result.specs[0];
+        assert_eq!(api.name, "api");
+        assert_eq!(api.mode, AppMode::Npm);
+        assert_eq!(api.command, "dev");
+        assert_eq!(api.cwd, dir.path());
+        assert_eq!(api.port, Some(3000));
+        assert_eq!(api.env.get("API_KEY"), Some(&"secret".to_string()));
+        assert_eq!(api.env.get("DEBUG"), Some(&"1".to_string()));
+        assert!(!api.restart_policy.auto_restart);
+
+        let worker = &result.specs[1];
+        assert_eq!(worker.mode, AppMode::Node);
+        assert_eq!(worker.command, "dist/worker.js");
+        assert_eq!(worker.args, vec!["--queue", "jobs"]);
+        assert_eq!(worker.cwd, dir.path().join("./worker"));
+        assert_eq!(worker.instances, 2);
+        assert_eq!(worker.env.get("QUEUE"), Some(&"jobs".to_string()));
+
+        let warnings = result.warnings.join("\n");
+        assert!(warnings.contains("db: skipped: image-only service"));
+        assert!(warnings.contains("api: was published on host port 8080"));
+        assert!(warnings.contains("worker: depends_on"));
+    }
+
+    #[test]
+    fn test_split_command_and_ports() {
+        assert_eq!(
+            split_command(r#"sh -c "echo 'hi there'" ''"#),
+            vec!["sh", "-c", "echo 'hi there'", ""]
+        );
+        assert_eq!(
+            parse_port(&Port::Short("127.0.0.1:8080:80/tcp".into())),
+            Some((Some(8080), 80))
+        );
+        assert_eq!(
+            parse_port(&Port::Short("3000-3005:4000-4005".into())),
+            Some((Some(3000), 4000))
+        );
+        assert_eq!(parse_port(&Port::Short("9000".into())), Some((None, 9000)));
+        assert_eq!(parse_port(&Port::Number(5000)), Some((None, 5000)));
+    }
+}
@@ -0,0 +1,95 @@
+//! Import app definitions from other process managers
+
+pub mod compose;
+pub mod pm2;
+
+use crate::types::{validate_app_name, AppMode, AppSpec};
+
+/// Result of converting a foreign config into AppSpecs
+#[derive(Debug, Default)]
+pub struct ImportResult {
+    /// Converted app specs, ready to start
+    pub specs: Vec<AppSpec>,
+    /// Fields that could not be mapped exactly
+    pub warnings: Vec<String>,
+}
+
+impl ImportResult {
+    fn warn(&mut self, app: &str, message: impl std::fmt::Display) {
+        self.warnings.push(format!("{}: {}", app, message));
+    }
+}
+
+/// Turn a foreign app name into a valid OxidePM app name
+pub(crate) fn sanitize_name(name: &str) -> String {
+    if validate_app_name(name) {
+        return name.to_string();
+    }
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_matches('-');
+    if sanitized.is_empty() {
+        "app".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// Map `npm run dev` / `yarn start` style invocations onto the package manager modes
+pub(crate) fn package_manager_command(
+    program: &str,
+    mut args: Vec<String>,
+) -> Option<(AppMode, String, Vec<String>)> {
+    let program = std::path::Path::new(program)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(program);
+    let mode = match program {
+        "npm" => AppMode::Npm,
+        "pnpm" => AppMode::Pnpm,
+        "yarn" => AppMode::Yarn,
+        _ => return None,
+    };
+    if args.first().map(String::as_str) == Some("run") {
+        args.remove(0);
+    }
+    if args.is_empty() {
+        return Some((mode, "start".to_string(), args));
+    }
+    let script = args.remove(0);
+    Some((mode, script, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(sanitize_name("api"), "api");
+        assert_eq!(sanitize_name("my.api server"), "my-api-server");
+        assert_eq!(sanitize_name("@@"), "app");
+    }
+
+    #[test]
+    fn test_package_manager_command() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            package_manager_command("npm", args(&["run", "dev", "--", "-p"])),
+            Some((AppMode::Npm, "dev".to_string(), args(&["--", "-p"])))
+        );
+        assert_eq!(
+            package_manager_command("/usr/bin/yarn", Vec::new()),
+            Some((AppMode::Yarn, "start".to_string(), Vec::new()))
+        );
+        assert_eq!(package_manager_command("node", args(&["app.js"])), None);
+    }
+}
@@ -0,0 +1,453 @@
+//! PM2 ecosystem file and dump import
+//!
+//! `ecosystem.config.js` files are JavaScript; the CLI evaluates them with
+//! node and hands the resulting JSON to [`parse`]. `ecosystem.json` and
+//! `~/.pm2/dump.pm2` are plain JSON and can be parsed directly.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::{package_manager_command, sanitize_name, ImportResult};
+use crate::error::{Error, Result};
+use crate::types::{AppMode, AppSpec};
+
+/// Ecosystem file names, in lookup order
+pub const ECOSYSTEM_FILES: &[&str] = &[
+    "ecosystem.config.js",
+    "ecosystem.config.cjs",
+    "ecosystem.json",
+];
+
+/// PM2 options that have no OxidePM equivalent yet
+const UNSUPPORTED_OPTIONS: &[&str] = &[
+    "cron_restart",
+    "node_args",
+    "interpreter_args",
+    "exp_backoff_restart_delay",
+    "min_uptime",
+    "listen_timeout",
+];
+
+/// A single app entry from an ecosystem file or a dump
+#[derive(Debug, Default, Deserialize)]
+pub struct Pm2App {
+    pub name: Option<String>,
+    pub script: Option<String>,
+    /// Absolute script path (dump.pm2)
+    pub pm_exec_path: Option<String>,
+    pub args: Option<Value>,
+    pub cwd: Option<String>,
+    /// Working directory (dump.pm2)
+    pub pm_cwd: Option<String>,
+    pub interpreter: Option<String>,
+    /// Interpreter (dump.pm2)
+    pub exec_interpreter: Option<String>,
+    /// "fork", "cluster", "fork_mode" or "cluster_mode"
+    pub exec_mode: Option<String>,
+    /// Number, "max", or a negative offset from the CPU count
+    pub instances: Option<Value>,
+    /// "300M", "1G" or a byte count
+    pub max_memory_restart: Option<Value>,
+    #[serde(default)]
+    pub env: HashMap<String, Value>,
+    pub watch: Option<Value>,
+    pub ignore_watch: Option<Value>,
+    pub autorestart: Option<bool>,
+    pub max_restarts: Option<u32>,
+    pub restart_delay: Option<u64>,
+    pub kill_timeout: Option<u64>,
+    /// Everything else, including `env_<name>` profiles
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Ecosystem {
+    Apps { apps: Vec<Pm2App> },
+    List(Vec<Pm2App>),
+    Single(Box<Pm2App>),
+}
+
+/// Parse ecosystem JSON (`{ apps: [...] }`, a bare list) or a dump.pm2 file
+pub fn parse(json: &str) -> Result<Vec<Pm2App>> {
+    let ecosystem: Ecosystem = serde_json::from_str(json)
+        .map_err(|e| Error::ConfigError(format!("Invalid PM2 config: {}", e)))?;
+    Ok(match ecosystem {
+        Ecosystem::Apps { apps } => apps,
+        Ecosystem::List(apps) => apps,
+        Ecosystem::Single(app) => vec![*app],
+    })
+}
+
+/// Location of PM2's process dump (`$PM2_HOME/dump.pm2`, default `~/.pm2`)
+pub fn dump_path() -> PathBuf {
+    std::env::var_os("PM2_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".pm2")))
+        .unwrap_or_else(|| PathBuf::from(".pm2"))
+        .join("dump.pm2")
+}
+
+/// Convert PM2 apps into AppSpecs
+///
+/// `env_name` selects an `env_<name>` profile layered over `env`, like
+/// `pm2 start ecosystem.config.js --env <name>`. Relative cwds resolve
+/// against `base_dir`. Dumps list every cluster worker separately, so
+/// repeated names are collapsed into one spec.
+pub fn into_specs(apps: Vec<Pm2App>, base_dir: &Path, env_name: Option<&str>) -> ImportResult {
+    let mut result = ImportResult::default();
+    let mut seen = HashSet::new();
+
+    for app in apps {
+        match app.into_spec(base_dir, env_name, &mut result) {
+            Ok(spec) => {
+                if seen.insert(spec.name.clone()) {
+                    result.specs.push(spec);
+                }
+            }
+            Err(e) => result.warnings.push(format!("skipped app: {}", e)),
+        }
+    }
+
+    result
+}
+
+impl Pm2App {
+    fn into_spec(
+        mut self,
+        base_dir: &Path,
+        env_name: Option<&str>,
+        result: &mut ImportResult,
+    ) -> Result<AppSpec> {
+        let script = self
+            .pm_exec_path
+            .take()
+            .or(self.script.take())
+            .ok_or_else(|| Error::ConfigError("missing 'script'".to_string()))?;
+
+        let name = match &self.name {
+            Some(name) => sanitize_name(name),
+            None => sanitize_name(
+                Path::new(&script)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("app"),
+            ),
+        };
+        if self.name.as_deref().is_some_and(|n| n != name) {
+            result.warn(
+                &name,
+                format!("renamed from '{}'", self.name.as_deref().unwrap_or("")),
+            );
+        }
+
+        let cwd = match self.pm_cwd.take().or(self.cwd.take()) {
+            Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+            Some(dir) => base_dir.join(dir),
+            None => base_dir.to_path_buf(),
+        };
+
+        let args = string_list(self.args.as_ref());
+        let interpreter = self.exec_interpreter.take().or(self.interpreter.take());
+        let (mode, command, args) = resolve_command(&script, interpreter.as_deref(), args);
+
+        let mut spec = AppSpec::try_new(name.clone(), mode, command, cwd)?;
+        spec.args = args;
+
+        // Environment: base env, then the selected env_<name> profile
+        let mut env = env_strings(&self.env);
+        if let Some(profile) = env_name {
+            match self.extra.get(&format!("env_{}", profile)) {
+                Some(Value::Object(vars)) => {
+                    let vars: HashMap<String, Value> =
+                        vars.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                    env.extend(env_strings(&vars));
+                }
+                _ => result.warn(&name, format!("no env_{} profile, using base env", profile)),
+            }
+        }
+        spec.env = env;
+
+        spec.instances = self.instances.as_ref().map(resolve_instances).unwrap_or(1);
+        let cluster = self
+            .exec_mode
+            .as_deref()
+            .is_some_and(|m| m.starts_with("cluster"));
+        if cluster && spec.instances > 1 {
+            // PM2 workers share one port; OxidePM instances get PORT+i
+            if let Some(port) = spec.env.get("PORT").and_then(|p| p.parse::<u16>().ok()) {
+                spec.port = Some(port);
+                result.warn(
+                    &name,
+                    format!(
+                        "cluster instances will listen on ports {}+i instead of sharing {}",
+                        port, port
+                    ),
+                );
+            }
+        }
+
+        if let Some(limit) = &self.max_memory_restart {
+            match parse_memory_mb(limit) {
+                Some(mb) => spec.max_memory_mb = Some(mb),
+                None => result.warn(&name, format!("invalid max_memory_restart {}", limit)),
+            }
+        }
+
+        match &self.watch {
+            Some(Value::Bool(watch)) => spec.watch = *watch,
+            Some(Value::String(_)) | Some(Value::Array(_)) => {
+                spec.watch = true;
+                result.warn(
+                    &name,
+                    "watch paths are not supported, watching the whole cwd",
+                );
+            }
+            _ => {}
+        }
+        spec.ignore_patterns
+            .extend(string_list(self.ignore_watch.as_ref()));
+
+        if let Some(autorestart) = self.autorestart {
+            spec.restart_policy.auto_restart = autorestart;
+        }
+        if let Some(max_restarts) = self.max_restarts {
+            spec.restart_policy.max_restarts = max_restarts;
+        }
+        if let Some(delay) = self.restart_delay {
+            spec.restart_policy.restart_delay_ms = delay;
+        }
+        if let Some(timeout) = self.kill_timeout {
+            spec.kill_timeout_ms = timeout;
+        }
+
+        for option in UNSUPPORTED_OPTIONS {
+            if self.extra.get(*option).is_some_and(is_set) {
+                result.warn(
+                    &name,
+                    format!("'{}' is not supported and was ignored", option),
+                );
+            }
+        }
+
+        Ok(spec)
+    }
+}
+
+/// Pick the runner mode, command and args for a PM2 script/interpreter pair
+fn resolve_command(
+    script: &str,
+    interpreter: Option<&str>,
+    mut args: Vec<String>,
+) -> (AppMode, String, Vec<String>) {
+    // `script: "npm", args: "run dev"` style package manager scripts
+    if let Some(resolved) = package_manager_command(script, args.clone()) {
+        return resolved;
+    }
+
+    let extension = Path::new(script).extension().and_then(|e| e.to_str());
+    let interpreter = match interpreter {
+        Some(i) => Some(i),
+        None => match extension {
+            Some("py") => Some("python3"),
+            Some("sh") => Some("bash"),
+            Some("rb") => Some("ruby"),
+            Some("php") => Some("php"),
+            Some("pl") => Some("perl"),
+            Some(_) => Some("node"),
+            None => Some("none"),
+        },
+    };
+
+    match interpreter {
+        Some("node") | Some("nodejs") => (AppMode::Node, script.to_string(), args),
+        Some("none") | None => (AppMode::Cmd, script.to_string(), args),
+        Some(other) => {
+            args.insert(0, script.to_string());
+            (AppMode::Cmd, other.to_string(), args)
+        }
+    }
+}
+
+/// Resolve PM2 `instances`: 0, "max" and -1 are relative to the CPU count
+fn resolve_instances(value: &Value) -> u32 {
+    let requested = match value {
+        Value::Number(n) => n.as_i64().unwrap_or(1),
+        Value::String(s) if s.eq_ignore_ascii_case("max") => 0,
+        Value::String(s) => s.trim().parse().unwrap_or(1),
+        _ => 1,
+    };
+    if requested > 0 {
+        return requested as u32;
+    }
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get() as i64)
+        .unwrap_or(1);
+    (cpus + requested).max(1) as u32
+}
+
+/// Parse `max_memory_restart` ("150M", "1G", "512K" or bytes) into MB
+fn parse_memory_mb(value: &Value) -> Option<u64> {
+    let bytes = match value {
+        Value::Number(n) => n.as_u64()?,
+        Value::String(s) => {
+            let s = s.trim();
+            let (number, unit) =
+                s.split_at(s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len()));
+            let number: f64 = number.trim().parse().ok()?;
+            let multiplier = match unit.to_ascii_uppercase().as_str() {
+                "" => 1.0,
+                "K" | "KB" => 1024.0,
+                "M" | "MB" => 1024.0 * 1024.0,
+                "G" | "GB" => 1024.0 * 1024.0 * 1024.0,
+                _ => return None,
+            };
+            (number * multiplier) as u64
+        }
+        _ => return None,
+    };
+    Some(bytes.div_ceil(1024 * 1024).max(1))
+}
+
+/// Accept either a string (split on whitespace) or a list of strings
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) => s.split_whitespace().map(String::from).collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| match v {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Stringify scalar env values; PM2 allows numbers and booleans
+fn env_strings(env: &HashMap<String, Value>) -> HashMap<String, String> {
+    env.iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return None,
+            };
+            Some((key.clone(), value))
+        })
+        .collect()
+}
+
+fn is_set(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => false,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ecosystem() {
+        let json = r#"{
+            "apps": [
+                {
+                    "name": "api",
+                    "script": "server.js",
+                    "cwd": "backend",
+                    "args": "--verbose --port 3000",
+                    "exec_mode": "cluster",
+                    "instances": 4,
+                    "max_memory_restart": "300M",
+                    "env": { "NODE_ENV": "development", "PORT": 3000 },
+                    "env_production": { "NODE_ENV": "production" },
+                    "ignore_watch": ["uploads"],
+                    "autorestart": false,
+                    "cron_restart": "0 0 * * *"
+                },
+                {
+                    "name": "web",
+                    "script": "npm",
+                    "args": ["run", "serve"]
+                }
+            ]
+        }"#;
+
+        let apps = parse(json).unwrap();
+        let result = into_specs(apps, Path::new("/srv"), Some("production"));
+        assert_eq!(result.specs.len(), 2);
+
+        let api = &result.specs[0];
+        assert_eq!(api.mode, AppMode::Node);
+        assert_eq!(api.command, "server.js");
+        assert_eq!(api.args, vec!["--verbose", "--port", "3000"]);
+        assert_eq!(api.cwd, Path::new("/srv/backend"));
+        assert_eq!(api.instances, 4);
+        assert_eq!(api.port, Some(3000));
+        assert_eq!(api.max_memory_mb, Some(300));
+        assert_eq!(api.env.get("NODE_ENV"), Some(&"production".to_string()));
+        assert_eq!(api.env.get("PORT"), Some(&"3000".to_string()));
+        assert!(api.ignore_patterns.contains(&"uploads".to_string()));
+        assert!(!api.restart_policy.auto_restart);
+        assert!(result.warnings.iter().any(|w| w.contains("cron_restart")));
+
+        let web = &result.specs[1];
+        assert_eq!(web.mode, AppMode::Npm);
+        assert_eq!(web.command, "serve");
+        assert!(web.args.is_empty());
+    }
+
+    #[test]
+    fn test_parse_dump() {
+        // Dumps hold one entry per cluster worker
+        let worker = r#"{
+            "name": "worker",
+            "pm_exec_path": "/srv/app/worker.py",
+            "pm_cwd": "/srv/app",
+            "exec_interpreter": "python3",
+            "exec_mode": "fork_mode",
+            "instances": 2,
+            "max_memory_restart": 104857600,
+            "args": ["--queue", "default"],
+            "env": { "QUEUE": "default", "unique_id": "abc" },
+            "pm_id": 0,
+            "node_args": []
+        }"#;
+        let json = format!("[{}, {}]", worker, worker);
+
+        let result = into_specs(parse(&json).unwrap(), Path::new("/"), None);
+        assert_eq!(result.specs.len(), 1);
+        let spec = &result.specs[0];
+        assert_eq!(spec.mode, AppMode::Cmd);
+        assert_eq!(spec.command, "python3");
+        assert_eq!(spec.args, vec!["/srv/app/worker.py", "--queue", "default"]);
+        assert_eq!(spec.cwd, Path::new("/srv/app"));
+        assert_eq!(spec.instances, 2);
+        assert_eq!(spec.max_memory_mb, Some(100));
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_instances_and_memory() {
+        let cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1) as u32;
+        assert_eq!(resolve_instances(&Value::from("max")), cpus);
+        assert_eq!(resolve_instances(&Value::from(0)), cpus);
+        assert_eq!(resolve_instances(&Value::from(-1)), (cpus - 1).max(1));
+        assert_eq!(resolve_instances(&Value::from("3")), 3);
+
+        assert_eq!(parse_memory_mb(&Value::from("1G")), Some(1024));
+        assert_eq!(parse_memory_mb(&Value::from("512K")), Some(1));
+        assert_eq!(parse_memory_mb(&Value::from("lots")), None);
+    }
+}
@@ -2,10 +2,21 @@
 
 pub mod config;
 pub mod constants;
+pub mod daemon_config;
+pub mod env;
 pub mod error;
+pub mod hosts;
+pub mod import;
+pub mod saved;
+pub mod secrets;
 pub mod types;
+pub mod validate;
 
 pub use config::*;
 pub use constants::*;
-pub use error::{Error, Result};
+pub use daemon_config::DaemonConfig;
+pub use error::{Error, ErrorCode, FetchFailure, Result};
+pub use hosts::{HostConfig, HostsFile};
+pub use saved::{SavedState, SAVED_STATE_VERSION};
 pub use types::*;
+pub use validate::{validate_config, ConfigIssue, Severity};
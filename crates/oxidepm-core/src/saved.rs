@@ -0,0 +1,192 @@
+//! Saved process list (`oxidepm save`)
+//!
+//! Written as a versioned envelope so later releases can change the format
+//! without breaking `resurrect`. Files from before the envelope (a bare JSON
+//! array of apps) are still read, and apps a newer release wrote with
+//! settings this one cannot read are skipped rather than failing the load.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::types::{AppSpec, DesiredState};
+
+/// Current saved-state format version
+pub const SAVED_STATE_VERSION: u32 = 1;
+
+/// Apps saved for `resurrect`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedState {
+    /// Format version; 0 for files written before the envelope
+    pub version: u32,
+    /// Release that wrote the file
+    pub oxidepm_version: String,
+    pub saved_at: Option<DateTime<Utc>>,
+    pub apps: Vec<AppSpec>,
+    /// Qualified names of apps that were stopped on purpose
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stopped: Vec<String>,
+}
+
+/// What a saved file may look like on disk
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawSavedState {
+    Envelope {
+        version: u32,
+        #[serde(default)]
+        oxidepm_version: String,
+        #[serde(default)]
+        saved_at: Option<DateTime<Utc>>,
+        apps: Vec<Value>,
+        #[serde(default)]
+        stopped: Vec<String>,
+    },
+    Legacy(Vec<Value>),
+}
+
+impl SavedState {
+    /// Envelope for the apps as of now
+    pub fn new(apps: Vec<AppSpec>) -> Self {
+        Self {
+            version: SAVED_STATE_VERSION,
+            oxidepm_version: env!("CARGO_PKG_VERSION").to_string(),
+            saved_at: Some(Utc::now()),
+            apps,
+            stopped: Vec::new(),
+        }
+    }
+
+    /// Mark apps (by qualified name) as stopped on purpose
+    pub fn with_stopped(mut self, stopped: Vec<String>) -> Self {
+        self.stopped = stopped;
+        self
+    }
+
+    /// Whether `resurrect` should start the app
+    pub fn desired_state(&self, spec: &AppSpec) -> DesiredState {
+        let name = spec.qualified_name();
+        if self.stopped.contains(&name) {
+            DesiredState::Stopped
+        } else {
+            DesiredState::Running
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Parse a saved file, returning warnings about anything that was skipped
+    pub fn parse(content: &str) -> Result<(Self, Vec<String>)> {
+        let raw: RawSavedState = serde_json::from_str(content)
+            .map_err(|e| Error::ConfigError(format!("Invalid saved state: {}", e)))?;
+        let (version, oxidepm_version, saved_at, raw_apps, stopped) = match raw {
+            RawSavedState::Envelope {
+                version,
+                oxidepm_version,
+                saved_at,
+                apps,
+                stopped,
+            } => (version, oxidepm_version, saved_at, apps, stopped),
+            RawSavedState::Legacy(apps) => (0, String::new(), None, apps, Vec::new()),
+        };
+
+        let mut warnings = Vec::new();
+        if version > SAVED_STATE_VERSION {
+            warnings.push(format!(
+                "Saved state is format v{} from oxidepm {} (this release reads v{}); settings it does not know are ignored",
+                version, oxidepm_version, SAVED_STATE_VERSION
+            ));
+        }
+
+        let mut apps = Vec::with_capacity(raw_apps.len());
+        for (index, app) in raw_apps.into_iter().enumerate() {
+            let name = app
+                .get("name")
+                .and_then(Value::as_str)
+                .map_or_else(|| format!("#{}", index + 1), String::from);
+            match serde_json::from_value::<AppSpec>(app) {
+                Ok(spec) => apps.push(spec),
+                Err(e) => warnings.push(format!("Skipped saved app {}: {}", name, e)),
+            }
+        }
+
+        Ok((
+            Self {
+                version,
+                oxidepm_version,
+                saved_at,
+                apps,
+                stopped,
+            },
+            warnings,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AppMode;
+    use std::path::PathBuf;
+
+    fn app(name: &str) -> AppSpec {
+        AppSpec::new(
+            name.to_string(),
+            AppMode::Cmd,
+            "server".to_string(),
+            PathBuf::from("/srv"),
+        )
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let json = SavedState::new(vec![app("api"), app("worker")])
+            .with_stopped(vec![app("worker").qualified_name()])
+            .to_json()
+            .unwrap();
+        let (state, warnings) = SavedState::parse(&json).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(state.version, SAVED_STATE_VERSION);
+        assert_eq!(state.oxidepm_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(state.apps.len(), 2);
+        assert_eq!(state.apps[1].name, "worker");
+        assert_eq!(state.desired_state(&state.apps[0]), DesiredState::Running);
+        assert_eq!(state.desired_state(&state.apps[1]), DesiredState::Stopped);
+    }
+
+    #[test]
+    fn test_reads_legacy_array() {
+        let json = serde_json::to_string(&vec![app("api")]).unwrap();
+        let (state, warnings) = SavedState::parse(&json).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(state.version, 0);
+        assert_eq!(state.apps[0].name, "api");
+        assert_eq!(state.desired_state(&state.apps[0]), DesiredState::Running);
+    }
+
+    #[test]
+    fn test_tolerates_newer_versions() {
+        let mut known = serde_json::to_value(app("api")).unwrap();
+        known["added_in_v9"] = serde_json::json!({ "anything": true });
+        let mut unreadable = serde_json::to_value(app("gpu")).unwrap();
+        unreadable["mode"] = serde_json::json!("wasm");
+        let json = serde_json::json!({
+            "version": 9,
+            "oxidepm_version": "9.0.0",
+            "compression": "none",
+            "apps": [known, unreadable],
+        });
+
+        let (state, warnings) = SavedState::parse(&json.to_string()).unwrap();
+        assert_eq!(state.apps.len(), 1);
+        assert_eq!(state.apps[0].name, "api");
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("format v9"));
+        assert!(warnings[1].contains("gpu"));
+
+        assert!(SavedState::parse("{\"apps\": 3}").is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! Encrypted secret storage
+//!
+//! Secrets live in `~/.oxidepm/secrets.json`, each value sealed with
+//! ChaCha20-Poly1305 under a 256-bit key kept in a separate key file
+//! (`~/.oxidepm/secret.key`, or `OXIDEPM_SECRET_KEY_FILE`). App specs only
+//! hold `${secret:NAME}` references, which are resolved when a process is
+//! spawned, so plaintext never reaches the database or the save file.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::constants;
+use crate::error::{Error, Result};
+use crate::types::AppSpec;
+
+/// Store format version
+const STORE_VERSION: u32 = 1;
+
+/// Key length in bytes (ChaCha20)
+const KEY_LEN: usize = 32;
+
+/// `${secret:NAME}` reference in env values and args
+static SECRET_REF: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\$\{secret:([A-Za-z0-9_.-]+)\}").expect("valid regex"));
+
+static SECRET_NAME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z0-9_.-]+$").expect("valid regex"));
+
+/// Validate a secret name (letters, digits, `_`, `-`, `.`)
+pub fn validate_secret_name(name: &str) -> bool {
+    SECRET_NAME.is_match(name)
+}
+
+/// Whether a string contains `${secret:NAME}` references
+pub fn has_references(value: &str) -> bool {
+    SECRET_REF.is_match(value)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreFile {
+    version: u32,
+    /// Secret name -> base64(nonce || ciphertext || tag)
+    #[serde(default)]
+    secrets: BTreeMap<String, String>,
+}
+
+/// Encrypted key/value store for secrets
+#[derive(Debug, Clone)]
+pub struct SecretStore {
+    path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl SecretStore {
+    pub fn new(path: PathBuf, key_path: PathBuf) -> Self {
+        Self { path, key_path }
+    }
+
+    /// Store at the default location under the OxidePM home
+    pub fn open_default() -> Self {
+        Self::new(constants::secrets_path(), constants::secret_key_path())
+    }
+
+    /// Names of all stored secrets, sorted
+    pub fn names(&self) -> Result<Vec<String>> {
+        Ok(self.load()?.secrets.into_keys().collect())
+    }
+
+    /// Decrypt a secret
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        let store = self.load()?;
+        let Some(sealed) = store.secrets.get(name) else {
+            return Ok(None);
+        };
+        let key = self.read_key()?;
+        open(&key, name, sealed).map(Some)
+    }
+
+    /// Encrypt and store a secret, creating the key file on first use
+    pub fn set(&self, name: &str, value: &str) -> Result<()> {
+        if !validate_secret_name(name) {
+            return Err(Error::SecretError(format!(
+                "Invalid secret name '{}': only letters, digits, '_', '-' and '.' are allowed",
+                name
+            )));
+        }
+        let key = match self.read_key() {
+            Ok(key) => key,
+            Err(_) if !self.key_path.exists() => self.create_key()?,
+            Err(e) => return Err(e),
+        };
+        let mut store = self.load()?;
+        store
+            .secrets
+            .insert(name.to_string(), seal(&key, name, value)?);
+        self.write(&store)
+    }
+
+    /// Delete a secret, returning whether it existed
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let mut store = self.load()?;
+        let existed = store.secrets.remove(name).is_some();
+        if existed {
+            self.write(&store)?;
+        }
+        Ok(existed)
+    }
+
+    /// Replace every `${secret:NAME}` reference in `value`
+    pub fn resolve(&self, value: &str) -> Result<String> {
+        if !has_references(value) {
+            return Ok(value.to_string());
+        }
+        let store = self.load()?;
+        let key = self.read_key()?;
+
+        let mut error = None;
+        let resolved = SECRET_REF.replace_all(value, |caps: &Captures| {
+            let name = &caps[1];
+            let result = match store.secrets.get(name) {
+                Some(sealed) => open(&key, name, sealed),
+                None => Err(Error::SecretNotFound(name.to_string())),
+            };
+            result.unwrap_or_else(|e| {
+                error.get_or_insert(e);
+                String::new()
+            })
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(resolved.into_owned()),
+        }
+    }
+
+    /// Copy of `spec` with secret references in env values and args resolved
+    pub fn resolve_spec(&self, spec: &AppSpec) -> Result<AppSpec> {
+        let mut resolved = spec.clone();
+        for value in resolved.env.values_mut().chain(resolved.args.iter_mut()) {
+            if has_references(value) {
+                *value = self.resolve(value)?;
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn load(&self) -> Result<StoreFile> {
+        if !self.path.exists() {
+            return Ok(StoreFile {
+                version: STORE_VERSION,
+                ..Default::default()
+            });
+        }
+        let store: StoreFile = serde_json::from_str(&std::fs::read_to_string(&self.path)?)?;
+        if store.version != STORE_VERSION {
+            return Err(Error::SecretError(format!(
+                "Unsupported secrets store version {} (expected {})",
+                store.version, STORE_VERSION
+            )));
+        }
+        Ok(store)
+    }
+
+    /// Write the store atomically with owner-only permissions
+    fn write(&self, store: &StoreFile) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        write_private(&tmp, serde_json::to_string_pretty(store)?.as_bytes())?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn read_key(&self) -> Result<LessSafeKey> {
+        let encoded = std::fs::read_to_string(&self.key_path).map_err(|e| {
+            Error::SecretError(format!(
+                "Cannot read secret key {}: {}",
+                self.key_path.display(),
+                e
+            ))
+        })?;
+        let bytes = BASE64.decode(encoded.trim()).map_err(|_| {
+            Error::SecretError(format!(
+                "Invalid secret key file {}",
+                self.key_path.display()
+            ))
+        })?;
+        aead_key(&bytes)
+    }
+
+    fn create_key(&self) -> Result<LessSafeKey> {
+        let mut bytes = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| Error::SecretError("Failed to generate secret key".to_string()))?;
+        if let Some(parent) = self.key_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_private(
+            &self.key_path,
+            format!("{}\n", BASE64.encode(bytes)).as_bytes(),
+        )?;
+        aead_key(&bytes)
+    }
+}
+
+fn aead_key(bytes: &[u8]) -> Result<LessSafeKey> {
+    UnboundKey::new(&CHACHA20_POLY1305, bytes)
+        .map(LessSafeKey::new)
+        .map_err(|_| Error::SecretError(format!("Secret key must be {} bytes", KEY_LEN)))
+}
+
+/// Encrypt `value`, binding it to `name` so sealed values can't be swapped
+fn seal(key: &LessSafeKey, name: &str, value: &str) -> Result<String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| Error::SecretError("Failed to generate nonce".to_string()))?;
+
+    let mut in_out = value.as_bytes().to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(name.as_bytes()),
+        &mut in_out,
+    )
+    .map_err(|_| Error::SecretError(format!("Failed to encrypt secret '{}'", name)))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&in_out);
+    Ok(BASE64.encode(sealed))
+}
+
+fn open(key: &LessSafeKey, name: &str, sealed: &str) -> Result<String> {
+    let invalid = || {
+        Error::SecretError(format!(
+            "Cannot decrypt secret '{}' (wrong key or corrupted store)",
+            name
+        ))
+    };
+
+    let mut bytes = BASE64.decode(sealed).map_err(|_| invalid())?;
+    if bytes.len() < NONCE_LEN {
+        return Err(invalid());
+    }
+    let mut in_out = bytes.split_off(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(&bytes).map_err(|_| invalid())?;
+    let plain = key
+        .open_in_place(nonce, Aad::from(name.as_bytes()), &mut in_out)
+        .map_err(|_| invalid())?;
+    String::from_utf8(plain.to_vec()).map_err(|_| invalid())
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::AppMode;
+    use tempfile::tempdir;
+
+    fn store(dir: &Path) -> SecretStore {
+        SecretStore::new(dir.join("secrets.json"), dir.join("secret.key"))
+    }
+
+    #[test]
+    fn test_set_get_remove() {
+        let dir = tempdir().unwrap();
+        let store = store(dir.path());
+
+        store.set("DB_PASSWORD", "hunter2").unwrap();
+        assert_eq!(
+            store.get("DB_PASSWORD").unwrap().as_deref(),
+            Some("hunter2")
+        );
+        assert_eq!(store.names().unwrap(), vec!["DB_PASSWORD"]);
+
+        // Nothing is stored in plaintext
+        let raw = std::fs::read_to_string(dir.path().join("secrets.json")).unwrap();
+        assert!(!raw.contains("hunter2"));
+
+        assert!(store.remove("DB_PASSWORD").unwrap());
+        assert!(!store.remove("DB_PASSWORD").unwrap());
+        assert!(store.get("DB_PASSWORD").unwrap().is_none());
+        assert!(store.set("bad name", "x").is_err());
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let dir = tempdir().unwrap();
+        store(dir.path()).set("TOKEN", "abc").unwrap();
+
+        let other = tempdir().unwrap();
+        let foreign = SecretStore::new(
+            dir.path().join("secrets.json"),
+            other.path().join("secret.key"),
+        );
+        store(other.path()).set("X", "y").unwrap();
+        assert!(matches!(foreign.get("TOKEN"), Err(Error::SecretError(_))));
+    }
+
+    #[test]
+    fn test_resolve_spec() {
+        let dir = tempdir().unwrap();
+        let store = store(dir.path());
+        store.set("DB_PASSWORD", "hunter2").unwrap();
+
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Cmd,
+            "server".to_string(),
+            PathBuf::from("/"),
+        );
+        spec.env.insert(
+            "DATABASE_URL".to_string(),
+            "postgres://app:${secret:DB_PASSWORD}@db/app".to_string(),
+        );
+        spec.args = vec!["--password=${secret:DB_PASSWORD}".to_string()];
+
+        let resolved = store.resolve_spec(&spec).unwrap();
+        assert_eq!(
+            resolved.env["DATABASE_URL"],
+            "postgres://app:hunter2@db/app"
+        );
+        assert_eq!(resolved.args, vec!["--password=hunter2"]);
+        // The original keeps the reference
+        assert!(has_references(&spec.env["DATABASE_URL"]));
+
+        spec.env
+            .insert("MISSING".to_string(), "${secret:NOPE}".to_string());
+        assert!(matches!(
+            store.resolve_spec(&spec),
+            Err(Error::SecretNotFound(_))
+        ));
+    }
+}
@@ -2,8 +2,8 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use crate::constants::*;
@@ -16,10 +16,18 @@ fn default_instances() -> u32 {
     1
 }
 
+fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
+}
+
 fn default_kill_timeout() -> u64 {
     DEFAULT_KILL_TIMEOUT_MS
 }
 
+fn default_trigger_cooldown() -> u64 {
+    DEFAULT_TRIGGER_COOLDOWN_SECS
+}
+
 fn default_ignore_patterns() -> Vec<String> {
     DEFAULT_IGNORE_PATTERNS
         .iter()
@@ -42,19 +50,156 @@ pub fn validate_app_name(name: &str) -> bool {
     !name.is_empty() && APP_NAME_REGEX.is_match(name)
 }
 
-/// Event hooks configuration - scripts to run when process events occur
+/// Placeholder in app args for the cluster instance index (0 without a cluster)
+pub const INSTANCE_PLACEHOLDER: &str = "{{instance}}";
+
+/// Placeholder in app args for the number of cluster instances
+pub const INSTANCES_PLACEHOLDER: &str = "{{instances}}";
+
+/// `{name}` placeholder in a hook argument
+static HOOK_PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{([a-z_]+)\}").expect("Invalid hook placeholder regex"));
+
+/// A hook command: a string run with `sh -c`, or a table that runs a program
+/// directly with interpolated arguments
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Hook {
+    Shell(String),
+    Detailed(HookSpec),
+}
+
+/// Table form of a hook (`on_crash = { command = "...", args = [...] }`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookSpec {
+    /// Shell command, run with `sh -c`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<String>,
+    /// Program run directly, without a shell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Arguments of `command`; `{app_name}`, `{pid}` and the like are
+    /// replaced with the event's values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Seconds before the hook is killed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+impl Hook {
+    /// Seconds the hook may run before it is killed
+    pub fn timeout_secs(&self) -> u64 {
+        match self {
+            Hook::Detailed(spec) => spec.timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS),
+            Hook::Shell(_) => DEFAULT_HOOK_TIMEOUT_SECS,
+        }
+    }
+
+    /// Reject tables that set both or neither of `run` and `command`,
+    /// arguments for a shell command and a zero timeout
+    pub fn validate(&self) -> Result<()> {
+        let spec = match self {
+            Hook::Shell(script) if script.trim().is_empty() => {
+                return Err(Error::config("hook command is empty"))
+            }
+            Hook::Shell(_) => return Ok(()),
+            Hook::Detailed(spec) => spec,
+        };
+        let blank = |field: &Option<String>| {
+            field
+                .as_deref()
+                .map_or(true, |value| value.trim().is_empty())
+        };
+        match (blank(&spec.run), blank(&spec.command)) {
+            (true, true) => return Err(Error::config("hook needs either `run` or `command`")),
+            (false, false) => {
+                return Err(Error::config("hook cannot set both `run` and `command`"))
+            }
+            (false, true) if !spec.args.is_empty() => {
+                return Err(Error::config(
+                    "hook `args` need `command`; `run` is a shell command line",
+                ))
+            }
+            _ => {}
+        }
+        if spec.timeout_secs == Some(0) {
+            return Err(Error::config("hook timeout_secs must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+impl HookSpec {
+    /// `args` with `{app_name}`, `{pid}` and the like replaced by `values`;
+    /// placeholders without a value become empty
+    pub fn interpolated_args(&self, values: &[(&str, String)]) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|arg| {
+                HOOK_PLACEHOLDER
+                    .replace_all(arg, |caps: &regex::Captures| {
+                        values
+                            .iter()
+                            .find(|(name, _)| *name == &caps[1])
+                            .map(|(_, value)| value.clone())
+                            .unwrap_or_default()
+                    })
+                    .into_owned()
+            })
+            .collect()
+    }
+}
+
+impl From<String> for Hook {
+    fn from(script: String) -> Self {
+        Hook::Shell(script)
+    }
+}
+
+impl From<&str> for Hook {
+    fn from(script: &str) -> Self {
+        Hook::Shell(script.to_string())
+    }
+}
+
+impl std::fmt::Display for Hook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hook::Shell(script) => write!(f, "{}", script),
+            Hook::Detailed(spec) => match (&spec.run, &spec.command) {
+                (Some(run), _) => write!(f, "{}", run),
+                (None, Some(command)) => {
+                    write!(f, "{}", command)?;
+                    for arg in &spec.args {
+                        write!(f, " {}", arg)?;
+                    }
+                    Ok(())
+                }
+                (None, None) => Ok(()),
+            },
+        }
+    }
+}
+
+/// Event hooks configuration - commands to run when process events occur
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Hooks {
-    /// Script to run after process starts
-    pub on_start: Option<String>,
-    /// Script to run after process stops
-    pub on_stop: Option<String>,
-    /// Script to run after restart
-    pub on_restart: Option<String>,
-    /// Script to run when process crashes
-    pub on_crash: Option<String>,
-    /// Script to run on error
-    pub on_error: Option<String>,
+    /// Run after process starts
+    pub on_start: Option<Hook>,
+    /// Run after process stops
+    pub on_stop: Option<Hook>,
+    /// Run after restart
+    pub on_restart: Option<Hook>,
+    /// Run when process crashes
+    pub on_crash: Option<Hook>,
+    /// Run on error
+    pub on_error: Option<Hook>,
+    /// Run during a reload once the replacement is up, before the old
+    /// process is stopped (e.g. to drain it)
+    pub on_pre_reload: Option<Hook>,
+    /// Run after a reload has completed
+    pub on_post_reload: Option<Hook>,
 }
 
 impl Hooks {
@@ -65,20 +210,317 @@ impl Hooks {
             && self.on_restart.is_none()
             && self.on_crash.is_none()
             && self.on_error.is_none()
+            && self.on_pre_reload.is_none()
+            && self.on_post_reload.is_none()
     }
 
-    /// Get the hook script for a specific event
-    pub fn get(&self, event: HookEvent) -> Option<&str> {
+    /// Get the hook for a specific event
+    pub fn get(&self, event: HookEvent) -> Option<&Hook> {
         match event {
-            HookEvent::Start => self.on_start.as_deref(),
-            HookEvent::Stop => self.on_stop.as_deref(),
-            HookEvent::Restart => self.on_restart.as_deref(),
-            HookEvent::Crash => self.on_crash.as_deref(),
-            HookEvent::Error => self.on_error.as_deref(),
+            HookEvent::Start => self.on_start.as_ref(),
+            HookEvent::Stop => self.on_stop.as_ref(),
+            HookEvent::Restart => self.on_restart.as_ref(),
+            HookEvent::Crash => self.on_crash.as_ref(),
+            HookEvent::Error => self.on_error.as_ref(),
+            HookEvent::PreReload => self.on_pre_reload.as_ref(),
+            HookEvent::PostReload => self.on_post_reload.as_ref(),
+        }
+    }
+
+    /// Configured hooks by config key (`on_crash`)
+    pub fn entries(&self) -> impl Iterator<Item = (&'static str, &Hook)> {
+        [
+            ("on_start", &self.on_start),
+            ("on_stop", &self.on_stop),
+            ("on_restart", &self.on_restart),
+            ("on_crash", &self.on_crash),
+            ("on_error", &self.on_error),
+            ("on_pre_reload", &self.on_pre_reload),
+            ("on_post_reload", &self.on_post_reload),
+        ]
+        .into_iter()
+        .filter_map(|(key, hook)| hook.as_ref().map(|hook| (key, hook)))
+    }
+
+    /// Validate every configured hook, naming the one that fails
+    pub fn validate(&self) -> Result<()> {
+        for (key, hook) in self.entries() {
+            if let Err(e) = hook.validate() {
+                return Err(match e {
+                    Error::ConfigError(msg) => Error::config(format!("hooks.{}: {}", key, msg)),
+                    other => other,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What a log trigger does when its pattern matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerAction {
+    /// Send a notification with the matching line
+    Notify,
+    /// Restart the app
+    Restart,
+    /// Run the trigger's `run` command
+    Hook,
+}
+
+impl std::fmt::Display for TriggerAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerAction::Notify => write!(f, "notify"),
+            TriggerAction::Restart => write!(f, "restart"),
+            TriggerAction::Hook => write!(f, "hook"),
+        }
+    }
+}
+
+/// Regex over an app's stdout/stderr that fires an action (`[[apps.triggers]]`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogTrigger {
+    pub pattern: String,
+    pub action: TriggerAction,
+    /// Command for `action = "hook"`, run with `sh -c`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<String>,
+    /// Seconds to ignore further matches after firing, so a burst of
+    /// matching lines fires once
+    #[serde(default = "default_trigger_cooldown")]
+    pub cooldown_secs: u64,
+}
+
+impl LogTrigger {
+    /// Reject patterns that do not compile and hooks without a command
+    pub fn validate(&self) -> Result<()> {
+        Regex::new(&self.pattern).map_err(|e| {
+            Error::config(format!("invalid trigger pattern '{}': {}", self.pattern, e))
+        })?;
+        if self.action == TriggerAction::Hook
+            && self
+                .run
+                .as_deref()
+                .map_or(true, |run| run.trim().is_empty())
+        {
+            return Err(Error::config(format!(
+                "trigger '{}' has action = \"hook\" but no `run` command",
+                self.pattern
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Linux namespace isolation for an app (`[apps.sandbox]`)
+///
+/// The app always gets its own user, mount and PID namespaces and keeps no
+/// capabilities. Under a root daemon it runs as `nobody`, so its working
+/// directory must be readable (and `writable` paths writable) by that user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sandbox {
+    /// Give the app an empty /tmp of its own
+    #[serde(default = "default_true")]
+    pub private_tmp: bool,
+    /// Mount the working directory read-only
+    #[serde(default = "default_true")]
+    pub read_only_cwd: bool,
+    /// Keep host networking; otherwise the app gets an empty network namespace
+    #[serde(default)]
+    pub network: bool,
+    /// Paths that stay writable inside a read-only working directory (relative to cwd)
+    #[serde(default)]
+    pub writable: Vec<PathBuf>,
+    /// Additional paths to mount read-only
+    #[serde(default)]
+    pub read_only: Vec<PathBuf>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            private_tmp: true,
+            read_only_cwd: true,
+            network: false,
+            writable: Vec::new(),
+            read_only: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Timestamp written in front of each captured line (`logs.timestamps`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogTimestamps {
+    /// `[2026-10-17 09:00:00] `, UTC to the second
+    #[default]
+    Seconds,
+    /// `[2026-10-17T09:00:00.123Z] `, followed by `[<instance>] ` for cluster instances
+    Rfc3339,
+    /// Lines are written as the app printed them
+    Off,
+}
+
+/// What happens when an app prints faster than its log is written (`logs.overflow`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogOverflow {
+    /// Stop reading the app's output until the writer catches up; the app
+    /// blocks once its pipe is full
+    #[default]
+    Block,
+    /// Keep reading and drop the oldest buffered lines
+    DropOldest,
+}
+
+/// Where an app's output is written (`[apps.logs]`). Unset paths default to
+/// `~/.oxidepm/logs/<name>-out.log` and `<name>-err.log`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogSettings {
+    /// stdout log file (`/dev/null` discards the output)
+    #[serde(default)]
+    pub out: Option<PathBuf>,
+    /// stderr log file
+    #[serde(default)]
+    pub err: Option<PathBuf>,
+    /// Write stderr into the stdout log
+    #[serde(default)]
+    pub merge: bool,
+    /// Timestamp format in front of each line
+    #[serde(default)]
+    pub timestamps: LogTimestamps,
+    /// What to do when the log writer falls behind
+    #[serde(default)]
+    pub overflow: LogOverflow,
+    /// Longest line kept, in bytes (default: 16 KiB); the rest is cut with a marker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_line_bytes: Option<usize>,
+    /// Store output exactly as printed: no timestamps, truncation or escaping
+    #[serde(default)]
+    pub raw: bool,
+}
+
+impl LogSettings {
+    /// Reject settings that cannot be written: directories, or stdout and
+    /// stderr sharing a file without `merge`
+    pub fn validate(&self) -> Result<()> {
+        for path in self.out.iter().chain(&self.err) {
+            if path.is_dir() {
+                return Err(Error::config(format!(
+                    "log path {} is a directory",
+                    path.display()
+                )));
+            }
+        }
+        if self.max_line_bytes == Some(0) {
+            return Err(Error::config("logs.max_line_bytes must be at least 1"));
+        }
+        if !self.merge && self.out.is_some() && self.out == self.err {
+            return Err(Error::config(
+                "logs.out and logs.err are the same file; set logs.merge = true to combine them",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Settings for one cluster instance: custom files get the instance id
+    /// appended (`app.log` -> `app-2.log`) so instances do not share a file
+    pub fn for_instance(&self, instance_id: u32) -> Self {
+        let suffixed = |path: &PathBuf| {
+            if path == Path::new(DEV_NULL) {
+                return path.clone();
+            }
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let name = match path.extension() {
+                Some(ext) => format!("{}-{}.{}", stem, instance_id, ext.to_string_lossy()),
+                None => format!("{}-{}", stem, instance_id),
+            };
+            path.with_file_name(name)
+        };
+        Self {
+            out: self.out.as_ref().map(suffixed),
+            err: self.err.as_ref().map(suffixed),
+            merge: self.merge,
+            timestamps: self.timestamps,
+            overflow: self.overflow,
+            max_line_bytes: self.max_line_bytes,
+            raw: self.raw,
+        }
+    }
+}
+
+const DEV_NULL: &str = "/dev/null";
+
+/// Resource limits set with setrlimit before the app starts (`[apps.limits]`).
+/// Each accepts a number or "unlimited".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Limits {
+    /// Max open file descriptors (RLIMIT_NOFILE)
+    #[serde(default, deserialize_with = "deserialize_limit")]
+    pub nofile: Option<u64>,
+    /// Max processes/threads for the user (RLIMIT_NPROC)
+    #[serde(default, deserialize_with = "deserialize_limit")]
+    pub nproc: Option<u64>,
+    /// Max core dump size in bytes (RLIMIT_CORE)
+    #[serde(default, deserialize_with = "deserialize_limit")]
+    pub core: Option<u64>,
+}
+
+impl Limits {
+    /// Stored value for "unlimited" (RLIM_INFINITY)
+    pub const UNLIMITED: u64 = u64::MAX;
+
+    pub fn is_empty(&self) -> bool {
+        self.nofile.is_none() && self.nproc.is_none() && self.core.is_none()
+    }
+
+    /// Set limits as (name, value) pairs
+    pub fn entries(&self) -> impl Iterator<Item = (&'static str, u64)> {
+        [
+            ("nofile", self.nofile),
+            ("nproc", self.nproc),
+            ("core", self.core),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|v| (name, v)))
+    }
+
+    /// Display form of a limit value
+    pub fn format_value(value: u64) -> String {
+        if value == Self::UNLIMITED {
+            "unlimited".to_string()
+        } else {
+            value.to_string()
         }
     }
 }
 
+fn deserialize_limit<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u64),
+        Keyword(String),
+    }
+
+    match Option::<Raw>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Raw::Number(value)) => Ok(Some(value)),
+        Some(Raw::Keyword(keyword)) if keyword == "unlimited" => Ok(Some(Limits::UNLIMITED)),
+        Some(Raw::Keyword(other)) => Err(serde::de::Error::custom(format!(
+            "invalid limit \"{}\": expected a number or \"unlimited\"",
+            other
+        ))),
+    }
+}
+
 /// Hook event types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HookEvent {
@@ -87,6 +529,8 @@ pub enum HookEvent {
     Restart,
     Crash,
     Error,
+    PreReload,
+    PostReload,
 }
 
 impl HookEvent {
@@ -97,6 +541,8 @@ impl HookEvent {
             HookEvent::Restart => "restart",
             HookEvent::Crash => "crash",
             HookEvent::Error => "error",
+            HookEvent::PreReload => "pre_reload",
+            HookEvent::PostReload => "post_reload",
         }
     }
 }
@@ -108,12 +554,21 @@ impl std::fmt::Display for HookEvent {
 }
 
 /// Health check configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HealthCheck {
     /// HTTP endpoint to check (e.g., "http://localhost:3000/health")
     pub http_url: Option<String>,
     /// Script to execute for health check
     pub script: Option<String>,
+    /// TCP port to connect to (e.g., a database or gRPC listener)
+    #[serde(default)]
+    pub tcp_port: Option<u16>,
+    /// Host for the TCP check (default: 127.0.0.1)
+    #[serde(default)]
+    pub tcp_host: Option<String>,
+    /// Regex the HTTP response body or script stdout must match
+    #[serde(default)]
+    pub expect_output: Option<String>,
     /// Expected HTTP status codes (default: 200)
     pub expected_status: Vec<u16>,
     /// Interval between checks in seconds
@@ -122,6 +577,189 @@ pub struct HealthCheck {
     pub timeout_secs: u64,
     /// Number of consecutive failures before marking unhealthy
     pub retries: u32,
+    /// Wait before the first check, giving the app time to boot
+    #[serde(default = "default_health_initial_delay")]
+    pub initial_delay_secs: u64,
+    /// Consecutive passes required before the app counts as healthy
+    #[serde(default = "default_success_threshold")]
+    pub success_threshold: u32,
+    /// What to do once the app is unhealthy
+    #[serde(default)]
+    pub on_unhealthy: UnhealthyAction,
+}
+
+fn default_health_initial_delay() -> u64 {
+    DEFAULT_HEALTH_INITIAL_DELAY_SECS
+}
+
+fn default_success_threshold() -> u32 {
+    1
+}
+
+/// Action taken when an app fails its health check `retries` times in a row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnhealthyAction {
+    /// Restart after `restart_delay_ms`
+    Restart,
+    /// Restart with exponential backoff while the app keeps failing
+    RestartWithBackoff,
+    /// Mark the app errored and send notifications, leaving it running
+    #[default]
+    NotifyOnly,
+    /// Stop the app
+    Stop,
+}
+
+impl UnhealthyAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnhealthyAction::Restart => "restart",
+            UnhealthyAction::RestartWithBackoff => "restart_with_backoff",
+            UnhealthyAction::NotifyOnly => "notify_only",
+            UnhealthyAction::Stop => "stop",
+        }
+    }
+}
+
+impl std::fmt::Display for UnhealthyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Which of an app's checks a health result belongs to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Probe {
+    /// `health_check`: failing it applies `on_unhealthy`
+    #[default]
+    Liveness,
+    /// `readiness_check`: failing it only marks the app not ready
+    Readiness,
+}
+
+impl Probe {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Probe::Liveness => "liveness",
+            Probe::Readiness => "readiness",
+        }
+    }
+}
+
+impl std::fmt::Display for Probe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Requests fired at an app once it is healthy, before it counts as ready,
+/// so the first real requests after a start or reload do not pay for cold
+/// caches, JIT or connection pools
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warmup {
+    /// URLs to GET, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<String>,
+    /// How many times each URL is requested
+    #[serde(default = "default_warmup_requests")]
+    pub requests: u32,
+    /// Command run with `sh -c` in the app's cwd once the requests are done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Give up after this many seconds and mark the app ready anyway
+    #[serde(default = "default_warmup_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_warmup_requests() -> u32 {
+    1
+}
+
+fn default_warmup_timeout() -> u64 {
+    DEFAULT_WARMUP_TIMEOUT_SECS
+}
+
+impl Warmup {
+    /// Reject a warm-up with nothing to do, no requests or no time to do them
+    pub fn validate(&self) -> Result<()> {
+        if self.urls.is_empty() && self.script.is_none() {
+            return Err(Error::config("warmup needs `urls` or a `script`"));
+        }
+        if self.requests == 0 {
+            return Err(Error::config("warmup `requests` must be at least 1"));
+        }
+        if self.timeout_secs == 0 {
+            return Err(Error::config("warmup `timeout_secs` must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// A service or file an app waits for before it is launched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WaitTarget {
+    /// `tcp://host:port`, up once it accepts a connection
+    Tcp(String),
+    /// An http(s) URL, up once it answers with a 2xx status
+    Http(String),
+    /// A file or directory, relative to the app's cwd, up once it exists
+    Path(PathBuf),
+}
+
+impl FromStr for WaitTarget {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            let valid = addr
+                .rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+            if !valid {
+                return Err(Error::config(format!(
+                    "invalid wait_for target '{}': expected tcp://host:port",
+                    s
+                )));
+            }
+            return Ok(WaitTarget::Tcp(addr.to_string()));
+        }
+        if s.starts_with("http://") || s.starts_with("https://") {
+            return Ok(WaitTarget::Http(s.to_string()));
+        }
+        if let Some((scheme, _)) = s.split_once("://") {
+            return Err(Error::config(format!(
+                "invalid wait_for target '{}': unsupported scheme '{}' (use tcp, http, https or a path)",
+                s, scheme
+            )));
+        }
+        if s.is_empty() {
+            return Err(Error::config("wait_for target is empty"));
+        }
+        Ok(WaitTarget::Path(PathBuf::from(s)))
+    }
+}
+
+impl std::fmt::Display for WaitTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WaitTarget::Tcp(addr) => write!(f, "tcp://{}", addr),
+            WaitTarget::Http(url) => write!(f, "{}", url),
+            WaitTarget::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// What the daemon does when an app's port is held by another app or process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortConflictPolicy {
+    /// Refuse to start the app
+    #[default]
+    Reject,
+    /// Start on the next free port, passed to the app as `PORT`
+    Reassign,
 }
 
 impl Default for HealthCheck {
@@ -129,10 +767,16 @@ impl Default for HealthCheck {
         Self {
             http_url: None,
             script: None,
+            tcp_port: None,
+            tcp_host: None,
+            expect_output: None,
             expected_status: vec![200],
             interval_secs: 30,
             timeout_secs: 5,
             retries: 3,
+            initial_delay_secs: DEFAULT_HEALTH_INITIAL_DELAY_SECS,
+            success_threshold: 1,
+            on_unhealthy: UnhealthyAction::default(),
         }
     }
 }
@@ -151,6 +795,90 @@ impl HealthCheck {
             ..Default::default()
         }
     }
+
+    pub fn tcp(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            tcp_host: Some(host.into()),
+            tcp_port: Some(port),
+            ..Default::default()
+        }
+    }
+
+    /// Require the HTTP body or script stdout to match a regex
+    pub fn with_expect_output(mut self, pattern: impl Into<String>) -> Self {
+        self.expect_output = Some(pattern.into());
+        self
+    }
+
+    /// Host and port for the TCP check, if configured
+    pub fn tcp_addr(&self) -> Option<String> {
+        self.tcp_port.map(|port| {
+            format!(
+                "{}:{}",
+                self.tcp_host.as_deref().unwrap_or("127.0.0.1"),
+                port
+            )
+        })
+    }
+
+    /// What is being checked, for logs and notifications
+    pub fn endpoint(&self) -> String {
+        if let Some(url) = &self.http_url {
+            url.clone()
+        } else if let Some(addr) = self.tcp_addr() {
+            format!("tcp://{}", addr)
+        } else if let Some(script) = &self.script {
+            format!("script: {}", script)
+        } else {
+            "none".to_string()
+        }
+    }
+}
+
+/// Where one of an app's environment variables comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvSource {
+    /// The daemon's own environment, or the CLI's with `--env-inherit`
+    Inherited,
+    /// An env file (`--env-file`, or `env_file` in a config file)
+    EnvFile,
+    /// The `env` table of a config file
+    Config,
+    /// `--env` on the command line
+    Flag,
+    /// Set on the app some other way (API, import, `oxidepm env set`)
+    #[default]
+    App,
+}
+
+impl EnvSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnvSource::Inherited => "inherited",
+            EnvSource::EnvFile => "env_file",
+            EnvSource::Config => "config",
+            EnvSource::Flag => "--env",
+            EnvSource::App => "app",
+        }
+    }
+}
+
+impl std::fmt::Display for EnvSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// What the daemon does once a watched env file changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvFileAction {
+    /// Stop the app and start it with the new variables
+    Restart,
+    /// Start a replacement with the new variables before stopping the old
+    /// process, as `oxidepm reload` does
+    Reload,
 }
 
 /// Application specification - defines how to run a process
@@ -158,17 +886,58 @@ impl HealthCheck {
 pub struct AppSpec {
     pub id: u32,
     pub name: String,
+    // Project namespace; names only need to be unique within one
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
     pub mode: AppMode,
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
     pub cwd: PathBuf,
+    /// Workspace package to run the npm/pnpm/yarn script in, with cwd as the monorepo root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// Cargo features to build with (cargo mode)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cargo_features: Vec<String>,
+    /// Cargo profile to build with: dev, release (default) or a custom one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo_profile: Option<String>,
+    /// Target triple to build for, e.g. x86_64-unknown-linux-musl
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo_target: Option<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Where the variables in `env` came from; unlisted ones count as `App`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env_sources: BTreeMap<String, EnvSource>,
+    /// Env file the `env_file` variables were read from, as an absolute path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<PathBuf>,
+    /// Re-read `env_file` when it changes and restart or reload the app
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch_env_file: Option<EnvFileAction>,
     #[serde(default)]
     pub watch: bool,
     #[serde(default = "default_ignore_patterns")]
     pub ignore_patterns: Vec<String>,
+    /// Files and directories to watch, relative to cwd (empty: the whole cwd)
+    #[serde(default)]
+    pub watch_paths: Vec<PathBuf>,
+    /// Only react to changes in files with these extensions (empty: any file)
+    #[serde(default)]
+    pub watch_extensions: Vec<String>,
+    /// Poll for changes at this interval instead of using inotify/FSEvents,
+    /// for NFS mounts and Docker volumes where native events go missing
+    #[serde(default)]
+    pub watch_poll_ms: Option<u64>,
+    /// Restart when the executable changes on disk (binary mode); otherwise only warn
+    #[serde(default)]
+    pub reload_on_change: bool,
+    /// Give up on prepare (build) after this many seconds
+    /// (default `DEFAULT_BUILD_TIMEOUT_SECS`)
+    #[serde(default)]
+    pub build_timeout_secs: Option<u64>,
     #[serde(default)]
     pub restart_policy: RestartPolicy,
     #[serde(default = "default_kill_timeout")]
@@ -184,9 +953,27 @@ pub struct AppSpec {
     pub port: Option<u16>,
     #[serde(default)]
     pub port_range: Option<(u16, u16)>,
-    // Health checks
+    #[serde(default)]
+    pub on_port_conflict: PortConflictPolicy,
+    /// Let the daemon pick a free port (from `port_range` if set) and pass it as `PORT`
+    #[serde(default)]
+    pub auto_port: bool,
+    /// Liveness check; failing it applies its `on_unhealthy` action
     #[serde(default)]
     pub health_check: Option<HealthCheck>,
+    /// Readiness check; failing it marks the app not ready, without restarting it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readiness_check: Option<HealthCheck>,
+    /// Requests fired once the app is healthy; it is not ready until they finish
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warmup: Option<Warmup>,
+    /// Services and files (see `WaitTarget`) that must be up before launch
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wait_for: Vec<String>,
+    /// Give up on `wait_for` after this many seconds
+    /// (default `DEFAULT_WAIT_FOR_TIMEOUT_SECS`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_for_timeout_secs: Option<u64>,
     // Memory limit (auto-restart if exceeded)
     #[serde(default)]
     pub max_memory_mb: Option<u64>,
@@ -199,12 +986,33 @@ pub struct AppSpec {
     // Event hooks
     #[serde(default)]
     pub hooks: Hooks,
+    // User hooks run as; defaults to the daemon's user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook_user: Option<String>,
     // Process tags for grouping (use @tag selector syntax)
     #[serde(default)]
     pub tags: Vec<String>,
+    // Apps (by name, in the same namespace) resurrect starts before this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    // Start band: lower priorities start first on resurrect and config start
+    #[serde(default)]
+    pub priority: i32,
     // Maximum uptime in seconds before auto-restart (prevents memory leaks)
     #[serde(default)]
     pub max_uptime_secs: Option<u64>,
+    // Namespace isolation (Linux only)
+    #[serde(default)]
+    pub sandbox: Option<Sandbox>,
+    // Resource limits (setrlimit)
+    #[serde(default)]
+    pub limits: Limits,
+    // Custom log destinations
+    #[serde(default)]
+    pub logs: LogSettings,
+    // Actions fired by patterns in the output
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<LogTrigger>,
 }
 
 impl AppSpec {
@@ -228,16 +1036,29 @@ impl AppSpec {
         Ok(Self {
             id: 0,
             name,
+            namespace: default_namespace(),
             mode,
             command,
             args: Vec::new(),
             cwd,
+            workspace: None,
+            cargo_features: Vec::new(),
+            cargo_profile: None,
+            cargo_target: None,
             env: HashMap::new(),
+            env_sources: BTreeMap::new(),
+            env_file: None,
+            watch_env_file: None,
             watch: false,
             ignore_patterns: DEFAULT_IGNORE_PATTERNS
                 .iter()
                 .map(|s| s.to_string())
                 .collect(),
+            watch_paths: Vec::new(),
+            watch_extensions: Vec::new(),
+            watch_poll_ms: None,
+            reload_on_change: false,
+            build_timeout_secs: None,
             restart_policy: RestartPolicy::default(),
             kill_timeout_ms: DEFAULT_KILL_TIMEOUT_MS,
             created_at: Utc::now(),
@@ -245,16 +1066,73 @@ impl AppSpec {
             instance_id: None,
             port: None,
             port_range: None,
+            on_port_conflict: PortConflictPolicy::default(),
+            auto_port: false,
             health_check: None,
+            readiness_check: None,
+            warmup: None,
+            wait_for: Vec::new(),
+            wait_for_timeout_secs: None,
             max_memory_mb: None,
             startup_delay_ms: None,
             env_inherit: false,
             hooks: Hooks::default(),
+            hook_user: None,
             tags: Vec::new(),
+            depends_on: Vec::new(),
+            priority: 0,
             max_uptime_secs: None,
+            sandbox: None,
+            limits: Limits::default(),
+            logs: LogSettings::default(),
+            triggers: Vec::new(),
         })
     }
 
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Where the app's variable `key` came from
+    pub fn env_source(&self, key: &str) -> EnvSource {
+        self.env_sources.get(key).copied().unwrap_or_default()
+    }
+
+    /// Environment the process starts with: `inherited` (the daemon's own)
+    /// overlaid with the app's variables, with where each one came from
+    pub fn resolved_env(
+        &self,
+        inherited: impl IntoIterator<Item = (String, String)>,
+    ) -> BTreeMap<String, (String, EnvSource)> {
+        let mut env: BTreeMap<String, (String, EnvSource)> = inherited
+            .into_iter()
+            .map(|(key, value)| (key, (value, EnvSource::Inherited)))
+            .collect();
+        for (key, value) in &self.env {
+            env.insert(key.clone(), (value.clone(), self.env_source(key)));
+        }
+        env
+    }
+
+    /// Name including the namespace (`shop/api`); plain name in the default namespace
+    pub fn qualified_name(&self) -> String {
+        if self.namespace == DEFAULT_NAMESPACE {
+            self.name.clone()
+        } else {
+            format!("{}/{}", self.namespace, self.name)
+        }
+    }
+
+    /// File-safe name used for log files (`shop.api`)
+    pub fn log_name(&self) -> String {
+        if self.namespace == DEFAULT_NAMESPACE {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+
     pub fn with_hooks(mut self, hooks: Hooks) -> Self {
         self.hooks = hooks;
         self
@@ -275,18 +1153,56 @@ impl AppSpec {
         self
     }
 
-    pub fn with_health_check(mut self, health_check: HealthCheck) -> Self {
-        self.health_check = Some(health_check);
+    pub fn with_port_conflict(mut self, policy: PortConflictPolicy) -> Self {
+        self.on_port_conflict = policy;
         self
     }
 
-    pub fn with_max_memory(mut self, max_mb: u64) -> Self {
-        self.max_memory_mb = Some(max_mb);
+    pub fn with_auto_port(mut self) -> Self {
+        self.auto_port = true;
         self
     }
 
-    pub fn with_startup_delay(mut self, delay_ms: u64) -> Self {
-        self.startup_delay_ms = Some(delay_ms);
+    /// Port the process will listen on: an explicit `PORT` env var, else `port`
+    pub fn requested_port(&self) -> Option<u16> {
+        self.env
+            .get("PORT")
+            .and_then(|p| p.parse().ok())
+            .or(self.port)
+    }
+
+    pub fn with_health_check(mut self, health_check: HealthCheck) -> Self {
+        self.health_check = Some(health_check);
+        self
+    }
+
+    pub fn with_readiness_check(mut self, readiness_check: HealthCheck) -> Self {
+        self.readiness_check = Some(readiness_check);
+        self
+    }
+
+    pub fn with_warmup(mut self, warmup: Warmup) -> Self {
+        self.warmup = Some(warmup);
+        self
+    }
+
+    /// The parsed `wait_for` targets
+    pub fn wait_targets(&self) -> Result<Vec<WaitTarget>> {
+        self.wait_for.iter().map(|target| target.parse()).collect()
+    }
+
+    pub fn with_max_memory(mut self, max_mb: u64) -> Self {
+        self.max_memory_mb = Some(max_mb);
+        self
+    }
+
+    pub fn with_startup_delay(mut self, delay_ms: u64) -> Self {
+        self.startup_delay_ms = Some(delay_ms);
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
         self
     }
 
@@ -305,11 +1221,46 @@ impl AppSpec {
         self
     }
 
+    pub fn with_sandbox(mut self, sandbox: Sandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_logs(mut self, logs: LogSettings) -> Self {
+        self.logs = logs;
+        self
+    }
+
+    /// File the app's stdout is written to
+    pub fn stdout_log_path(&self) -> PathBuf {
+        self.logs
+            .out
+            .clone()
+            .unwrap_or_else(|| log_path(&self.log_name(), "out"))
+    }
+
+    /// File the app's stderr is written to (the stdout file when merged)
+    pub fn stderr_log_path(&self) -> PathBuf {
+        if self.logs.merge {
+            return self.stdout_log_path();
+        }
+        self.logs
+            .err
+            .clone()
+            .unwrap_or_else(|| log_path(&self.log_name(), "err"))
+    }
+
     /// Create a clone for a specific instance in a cluster
     pub fn for_instance(&self, instance_id: u32, port: Option<u16>) -> Self {
         let mut instance = self.clone();
         instance.instance_id = Some(instance_id);
         instance.name = format!("{}-{}", self.name, instance_id);
+        instance.logs = self.logs.for_instance(instance_id);
         if let Some(p) = port {
             instance.port = Some(p);
             instance.env.insert("PORT".to_string(), p.to_string());
@@ -317,6 +1268,60 @@ impl AppSpec {
         instance
     }
 
+    /// The spec a process is launched with: `{{instance}}` and `{{instances}}`
+    /// in args filled in, and env vars telling the process which instance it
+    /// is (`NODE_APP_INSTANCE` as PM2 sets it, `OXIDE_INSTANCE_ID`,
+    /// `OXIDE_INSTANCES`, `OXIDE_APP_ID` and `OXIDE_PORT`). Variables the
+    /// app's own `env` sets are left alone.
+    pub fn for_launch(&self) -> Self {
+        let mut launch = self.clone();
+        let instance = self.instance_id.unwrap_or(0).to_string();
+        let instances = self.instances.max(1).to_string();
+        for arg in &mut launch.args {
+            if arg.contains(INSTANCE_PLACEHOLDER) || arg.contains(INSTANCES_PLACEHOLDER) {
+                *arg = arg
+                    .replace(INSTANCE_PLACEHOLDER, &instance)
+                    .replace(INSTANCES_PLACEHOLDER, &instances);
+            }
+        }
+        let mut set_default = |key: &str, value: String| {
+            launch.env.entry(key.to_string()).or_insert(value);
+        };
+        set_default("NODE_APP_INSTANCE", instance.clone());
+        set_default("OXIDE_INSTANCE_ID", instance);
+        set_default("OXIDE_INSTANCES", instances);
+        set_default("OXIDE_APP_ID", self.id.to_string());
+        if let Some(port) = self.requested_port() {
+            set_default("OXIDE_PORT", port.to_string());
+        }
+        launch
+    }
+
+    /// Take the variables of a re-read `env_file`: they replace the ones the
+    /// file set before, and `--env` flags still win over them. Returns whether
+    /// `env` changed.
+    pub fn apply_env_file(&mut self, vars: HashMap<String, String>) -> bool {
+        let before = self.env.clone();
+        let stale: Vec<String> = self
+            .env_sources
+            .iter()
+            .filter(|(_, source)| **source == EnvSource::EnvFile)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stale {
+            self.env.remove(&key);
+            self.env_sources.remove(&key);
+        }
+        for (key, value) in vars {
+            if self.env_sources.get(&key) == Some(&EnvSource::Flag) {
+                continue;
+            }
+            self.env_sources.insert(key.clone(), EnvSource::EnvFile);
+            self.env.insert(key, value);
+        }
+        self.env != before
+    }
+
     pub fn with_args(mut self, args: Vec<String>) -> Self {
         self.args = args;
         self
@@ -336,6 +1341,34 @@ impl AppSpec {
         self.ignore_patterns = patterns;
         self
     }
+
+    /// Absolute paths watch mode should watch
+    pub fn watch_targets(&self) -> Vec<PathBuf> {
+        if self.watch_paths.is_empty() {
+            return vec![self.cwd.clone()];
+        }
+        self.watch_paths
+            .iter()
+            .map(|path| self.cwd.join(path))
+            .collect()
+    }
+
+    /// Compare the user-declared parts of two specs, ignoring the
+    /// daemon-assigned id, creation time and cluster instance index
+    pub fn definition_eq(&self, other: &AppSpec) -> bool {
+        fn definition(spec: &AppSpec) -> Option<serde_json::Value> {
+            let mut value = serde_json::to_value(spec).ok()?;
+            if let Some(map) = value.as_object_mut() {
+                map.remove("id");
+                map.remove("created_at");
+                map.remove("instance_id");
+                // Provenance only; the variables themselves are compared
+                map.remove("env_sources");
+            }
+            Some(value)
+        }
+        definition(self).is_some_and(|a| Some(a) == definition(other))
+    }
 }
 
 /// Application runtime mode
@@ -349,6 +1382,8 @@ pub enum AppMode {
     Yarn,
     Cargo,
     Rust,
+    /// An executable built elsewhere (e.g. by CI), run as is
+    Binary,
 }
 
 impl AppMode {
@@ -390,6 +1425,7 @@ impl AppMode {
             AppMode::Yarn => "yarn",
             AppMode::Cargo => "cargo",
             AppMode::Rust => "rust",
+            AppMode::Binary => "binary",
         }
     }
 }
@@ -406,6 +1442,7 @@ impl FromStr for AppMode {
             "yarn" => Ok(AppMode::Yarn),
             "cargo" => Ok(AppMode::Cargo),
             "rust" => Ok(AppMode::Rust),
+            "binary" => Ok(AppMode::Binary),
             _ => Err(Error::InvalidMode(s.to_string())),
         }
     }
@@ -417,6 +1454,71 @@ impl std::fmt::Display for AppMode {
     }
 }
 
+/// Start order of each app as `(band, tier)`, to start in ascending order
+///
+/// The band is the app's `priority`, raised to the band of any app it
+/// `depends_on` (matched by name in its namespace) so a dependency never
+/// starts after its dependent. Within a band the tier is 0 for apps without
+/// dependencies there, else one more than the highest tier among them.
+/// Unknown dependencies and cycles are reported in `warnings` and ignored.
+pub fn start_order(apps: &[AppSpec], warnings: &mut Vec<String>) -> Vec<(i32, usize)> {
+    fn visit(
+        i: usize,
+        apps: &[AppSpec],
+        index: &HashMap<(&str, &str), usize>,
+        order: &mut [Option<(i32, usize)>],
+        visiting: &mut [bool],
+        warnings: &mut Vec<String>,
+    ) -> (i32, usize) {
+        if let Some(key) = order[i] {
+            return key;
+        }
+        if visiting[i] {
+            warnings.push(format!(
+                "Dependency cycle through '{}' ignored",
+                apps[i].qualified_name()
+            ));
+            return (apps[i].priority, 0);
+        }
+        visiting[i] = true;
+        let mut deps = Vec::new();
+        for dep in &apps[i].depends_on {
+            match index.get(&(apps[i].namespace.as_str(), dep.as_str())) {
+                Some(&d) => deps.push(visit(d, apps, index, order, visiting, warnings)),
+                None => warnings.push(format!(
+                    "'{}' depends on unknown app '{}'",
+                    apps[i].qualified_name(),
+                    dep
+                )),
+            }
+        }
+        let band = deps
+            .iter()
+            .map(|&(band, _)| band)
+            .fold(apps[i].priority, i32::max);
+        let tier = deps
+            .iter()
+            .filter(|&&(dep_band, _)| dep_band == band)
+            .map(|&(_, tier)| tier + 1)
+            .max()
+            .unwrap_or(0);
+        visiting[i] = false;
+        order[i] = Some((band, tier));
+        (band, tier)
+    }
+
+    let index: HashMap<(&str, &str), usize> = apps
+        .iter()
+        .enumerate()
+        .map(|(i, spec)| ((spec.namespace.as_str(), spec.name.as_str()), i))
+        .collect();
+    let mut order = vec![None; apps.len()];
+    let mut visiting = vec![false; apps.len()];
+    (0..apps.len())
+        .map(|i| visit(i, apps, &index, &mut order, &mut visiting, warnings))
+        .collect()
+}
+
 /// Application status
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -427,6 +1529,8 @@ pub enum AppStatus {
     Stopped,
     Errored,
     Building,
+    /// Frozen with SIGSTOP until resumed
+    Paused,
 }
 
 impl AppStatus {
@@ -438,11 +1542,16 @@ impl AppStatus {
             AppStatus::Stopped => "stopped",
             AppStatus::Errored => "errored",
             AppStatus::Building => "building",
+            AppStatus::Paused => "paused",
         }
     }
 
+    /// Whether the app has a live (or about to start) process; a paused one counts
     pub fn is_running(&self) -> bool {
-        matches!(self, AppStatus::Running | AppStatus::Starting | AppStatus::Building)
+        matches!(
+            self,
+            AppStatus::Running | AppStatus::Starting | AppStatus::Building | AppStatus::Paused
+        )
     }
 }
 
@@ -463,11 +1572,106 @@ impl FromStr for AppStatus {
             "stopped" => Ok(AppStatus::Stopped),
             "errored" => Ok(AppStatus::Errored),
             "building" => Ok(AppStatus::Building),
+            "paused" => Ok(AppStatus::Paused),
             _ => Err(Error::ConfigError(format!("Invalid status: {}", s))),
         }
     }
 }
 
+/// Signal `oxidepm signal` passes through to an app, typically to reopen
+/// logs or reload config. SIGKILL, SIGSTOP and SIGCONT are left to `stop`,
+/// `pause` and `resume`, which keep the app's status in step.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AppSignal {
+    Sighup,
+    Sigint,
+    Sigquit,
+    Sigusr1,
+    Sigusr2,
+    Sigalrm,
+    Sigterm,
+    Sigwinch,
+}
+
+impl AppSignal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AppSignal::Sighup => "SIGHUP",
+            AppSignal::Sigint => "SIGINT",
+            AppSignal::Sigquit => "SIGQUIT",
+            AppSignal::Sigusr1 => "SIGUSR1",
+            AppSignal::Sigusr2 => "SIGUSR2",
+            AppSignal::Sigalrm => "SIGALRM",
+            AppSignal::Sigterm => "SIGTERM",
+            AppSignal::Sigwinch => "SIGWINCH",
+        }
+    }
+}
+
+impl FromStr for AppSignal {
+    type Err = Error;
+
+    /// Accepts "SIGUSR2", "USR2" or "usr2"
+    fn from_str(s: &str) -> Result<Self> {
+        let upper = s.trim().to_uppercase();
+        match upper.strip_prefix("SIG").unwrap_or(&upper) {
+            "HUP" => Ok(AppSignal::Sighup),
+            "INT" => Ok(AppSignal::Sigint),
+            "QUIT" => Ok(AppSignal::Sigquit),
+            "USR1" => Ok(AppSignal::Sigusr1),
+            "USR2" => Ok(AppSignal::Sigusr2),
+            "ALRM" => Ok(AppSignal::Sigalrm),
+            "TERM" => Ok(AppSignal::Sigterm),
+            "WINCH" => Ok(AppSignal::Sigwinch),
+            "KILL" | "STOP" | "CONT" => Err(Error::InvalidSignal(format!(
+                "{} (use stop, pause or resume instead)",
+                s
+            ))),
+            _ => Err(Error::InvalidSignal(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for AppSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Whether an app should be running, as last requested by the user
+///
+/// `resurrect` only starts apps whose desired state is running, so an app
+/// stopped on purpose stays stopped across a reboot.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DesiredState {
+    #[default]
+    Running,
+    Stopped,
+}
+
+impl DesiredState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DesiredState::Running => "running",
+            DesiredState::Stopped => "stopped",
+        }
+    }
+}
+
+impl FromStr for DesiredState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "running" => Ok(DesiredState::Running),
+            "stopped" => Ok(DesiredState::Stopped),
+            _ => Err(Error::ConfigError(format!("Invalid desired state: {}", s))),
+        }
+    }
+}
+
 /// Runtime state of an application
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunState {
@@ -478,12 +1682,36 @@ pub struct RunState {
     pub status: AppStatus,
     #[serde(default)]
     pub restarts: u32,
+    /// Automatic restarts in a row without `crash_window_secs` of uptime in
+    /// between; they stop once this reaches `max_restarts`
+    #[serde(default)]
+    pub restarts_in_window: u32,
+    /// When the pending automatic restart is due
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_restart_at: Option<DateTime<Utc>>,
     #[serde(default)]
     pub uptime_secs: u64,
     #[serde(default)]
     pub cpu_percent: f32,
     #[serde(default)]
     pub memory_bytes: u64,
+    /// Processes in the app's tree, its own and everything it started (Linux
+    /// only); CPU, memory, threads, descriptors and disk I/O cover them all
+    #[serde(default)]
+    pub processes: Option<u32>,
+    /// Open file descriptors (Linux only)
+    #[serde(default)]
+    pub open_fds: Option<u32>,
+    /// OS threads (Linux only)
+    #[serde(default)]
+    pub threads: Option<u32>,
+    #[serde(default)]
+    pub disk_read_bytes_per_sec: u64,
+    #[serde(default)]
+    pub disk_write_bytes_per_sec: u64,
+    /// Output lines dropped because the log writer fell behind
+    #[serde(default)]
+    pub log_lines_dropped: u64,
     #[serde(default)]
     pub last_exit_code: Option<i32>,
     #[serde(default)]
@@ -495,12 +1723,28 @@ pub struct RunState {
     pub last_health_check: Option<DateTime<Utc>>,
     #[serde(default)]
     pub health_check_failures: u32,
+    /// Passing its readiness check (always true without one); a cluster is
+    /// ready while any of its instances is
+    #[serde(default)]
+    pub ready: bool,
+    #[serde(default)]
+    pub readiness_failures: u32,
+    /// Drained for maintenance: reported not ready, and its log triggers
+    /// hold off until it is undrained
+    #[serde(default)]
+    pub draining: bool,
     // Port info
     #[serde(default)]
     pub port: Option<u16>,
     // Instance info for clusters
     #[serde(default)]
     pub instance_id: Option<u32>,
+    /// Lifetime figures from the database, filled in for detail views
+    #[serde(default)]
+    pub stats: AppStats,
+    /// Build behind the running binary (cargo and rust modes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildInfo>,
 }
 
 impl RunState {
@@ -510,16 +1754,29 @@ impl RunState {
             pid: None,
             status: AppStatus::Stopped,
             restarts: 0,
+            restarts_in_window: 0,
+            next_restart_at: None,
             uptime_secs: 0,
             cpu_percent: 0.0,
             memory_bytes: 0,
+            processes: None,
+            open_fds: None,
+            threads: None,
+            disk_read_bytes_per_sec: 0,
+            disk_write_bytes_per_sec: 0,
+            log_lines_dropped: 0,
             last_exit_code: None,
             started_at: None,
             healthy: false,
             last_health_check: None,
             health_check_failures: 0,
+            ready: false,
+            readiness_failures: 0,
+            draining: false,
             port: None,
             instance_id: None,
+            stats: AppStats::default(),
+            build: None,
         }
     }
 
@@ -529,111 +1786,852 @@ impl RunState {
             pid: Some(pid),
             status: AppStatus::Running,
             restarts: 0,
+            restarts_in_window: 0,
+            next_restart_at: None,
             uptime_secs: 0,
             cpu_percent: 0.0,
             memory_bytes: 0,
+            processes: None,
+            open_fds: None,
+            threads: None,
+            disk_read_bytes_per_sec: 0,
+            disk_write_bytes_per_sec: 0,
+            log_lines_dropped: 0,
             last_exit_code: None,
             started_at: Some(Utc::now()),
             healthy: true,
             last_health_check: None,
             health_check_failures: 0,
+            ready: true,
+            readiness_failures: 0,
+            draining: false,
             port: None,
             instance_id: None,
+            stats: AppStats::default(),
+            build: None,
+        }
+    }
+
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn with_instance(mut self, instance_id: u32) -> Self {
+        self.instance_id = Some(instance_id);
+        self
+    }
+
+    /// Whether automatic restarts have given up under `policy`
+    pub fn restarts_exhausted(&self, policy: &RestartPolicy) -> bool {
+        self.restarts_in_window >= policy.max_restarts
+    }
+}
+
+/// Restart policy configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartPolicy {
+    pub auto_restart: bool,
+    pub max_restarts: u32,
+    pub restart_delay_ms: u64,
+    pub crash_window_secs: u64,
+}
+
+impl RestartPolicy {
+    /// Delay before the given restart attempt (0-based), doubling from
+    /// `restart_delay_ms` up to `MAX_RESTART_BACKOFF_MS`
+    pub fn backoff_delay_ms(&self, attempt: u32) -> u64 {
+        self.restart_delay_ms
+            .saturating_mul(1u64 << attempt.min(16))
+            .min(MAX_RESTART_BACKOFF_MS.max(self.restart_delay_ms))
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            auto_restart: true,
+            max_restarts: DEFAULT_MAX_RESTARTS,
+            restart_delay_ms: DEFAULT_RESTART_DELAY_MS,
+            crash_window_secs: DEFAULT_CRASH_WINDOW_SECS,
+        }
+    }
+}
+
+/// Selector for targeting apps by id, name, tag, namespace, or all
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    All,
+    ById(u32),
+    /// Select by name; `namespace/name` pins the namespace
+    ByName(String),
+    /// Select by tag using @tagname syntax
+    ByTag(String),
+    /// Select every app in a namespace using ns:name syntax
+    ByNamespace(String),
+}
+
+impl Selector {
+    /// Parse a selector string
+    /// - "all" -> All
+    /// - "123" -> ById(123)
+    /// - "@tagname" -> ByTag("tagname")
+    /// - "ns:shop" -> ByNamespace("shop")
+    /// - "appname" or "shop/appname" -> ByName(..)
+    pub fn parse(s: &str) -> Self {
+        if s.eq_ignore_ascii_case("all") {
+            Selector::All
+        } else if let Some(tag) = s.strip_prefix('@') {
+            Selector::ByTag(tag.to_string())
+        } else if let Some(namespace) = s.strip_prefix("ns:") {
+            Selector::ByNamespace(namespace.to_string())
+        } else if let Ok(id) = s.parse::<u32>() {
+            Selector::ById(id)
+        } else {
+            Selector::ByName(s.to_string())
+        }
+    }
+
+    /// Check if this selector matches an app spec
+    pub fn matches(&self, spec: &AppSpec) -> bool {
+        match self {
+            Selector::All => true,
+            Selector::ById(id) => spec.id == *id,
+            Selector::ByName(name) => match name.split_once('/') {
+                Some((namespace, name)) => spec.namespace == namespace && spec.name == name,
+                None => spec.name == *name,
+            },
+            Selector::ByTag(tag) => spec.tags.contains(tag),
+            Selector::ByNamespace(namespace) => spec.namespace == *namespace,
+        }
+    }
+}
+
+impl std::fmt::Display for Selector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Selector::All => write!(f, "all"),
+            Selector::ById(id) => write!(f, "{}", id),
+            Selector::ByName(name) => write!(f, "{}", name),
+            Selector::ByTag(tag) => write!(f, "@{}", tag),
+            Selector::ByNamespace(namespace) => write!(f, "ns:{}", namespace),
+        }
+    }
+}
+
+impl From<&str> for Selector {
+    fn from(s: &str) -> Self {
+        Selector::parse(s)
+    }
+}
+
+impl From<u32> for Selector {
+    fn from(id: u32) -> Self {
+        Selector::ById(id)
+    }
+}
+
+// Selectors travel in their string form (`@web`, `ns:shop`, ...) so tag and
+// namespace selectors don't collapse into names on the way to the daemon
+impl Serialize for Selector {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Selector::ById(id) => serializer.serialize_u32(*id),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Selector {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Id(u32),
+            Text(String),
+        }
+
+        // `null` is how older clients sent `all`
+        Ok(match Option::<Repr>::deserialize(deserializer)? {
+            None => Selector::All,
+            Some(Repr::Id(id)) => Selector::ById(id),
+            Some(Repr::Text(text)) => Selector::parse(&text),
+        })
+    }
+}
+
+/// Full application info (spec + state) for status display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppInfo {
+    pub spec: AppSpec,
+    pub state: RunState,
+}
+
+impl AppInfo {
+    pub fn new(spec: AppSpec, state: RunState) -> Self {
+        Self { spec, state }
+    }
+}
+
+/// A single health check outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthRecord {
+    pub timestamp: DateTime<Utc>,
+    pub healthy: bool,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Current health and recent check history of one of an app's checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppHealth {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub probe: Probe,
+    /// What is being checked (URL, tcp://host:port or script)
+    pub endpoint: String,
+    /// Passing; for readiness checks, whether the app is ready
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// Oldest first
+    pub history: Vec<HealthRecord>,
+}
+
+/// What happened to an app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Started,
+    Stopped,
+    Crashed,
+    Restarted,
+    Reloaded,
+    Unhealthy,
+    /// Failed its readiness check
+    NotReady,
+    /// Passing its readiness check again
+    Ready,
+    /// Frozen with `oxidepm pause`
+    Paused,
+    /// Continued with `oxidepm resume`
+    Resumed,
+    /// Sent a signal with `oxidepm signal`; the reason names it
+    Signalled,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Started => "started",
+            EventKind::Stopped => "stopped",
+            EventKind::Crashed => "crashed",
+            EventKind::Restarted => "restarted",
+            EventKind::Reloaded => "reloaded",
+            EventKind::Unhealthy => "unhealthy",
+            EventKind::NotReady => "not_ready",
+            EventKind::Ready => "ready",
+            EventKind::Paused => "paused",
+            EventKind::Resumed => "resumed",
+            EventKind::Signalled => "signalled",
+        }
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "started" => Ok(EventKind::Started),
+            "stopped" => Ok(EventKind::Stopped),
+            "crashed" => Ok(EventKind::Crashed),
+            "restarted" => Ok(EventKind::Restarted),
+            "reloaded" => Ok(EventKind::Reloaded),
+            "unhealthy" => Ok(EventKind::Unhealthy),
+            "not_ready" => Ok(EventKind::NotReady),
+            "ready" => Ok(EventKind::Ready),
+            "paused" => Ok(EventKind::Paused),
+            "resumed" => Ok(EventKind::Resumed),
+            "signalled" => Ok(EventKind::Signalled),
+            _ => Err(Error::ConfigError(format!("Invalid event kind: {}", s))),
+        }
+    }
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A control request as recorded in the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Who sent it, as the daemon saw them: "uid 1000 (alice)", "tcp 10.0.0.5:40112"
+    pub actor: String,
+    /// What the client says it is, e.g. "web"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// Request type, e.g. "restart"
+    pub action: String,
+    /// What the request was for, e.g. "api" or "@web"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub success: bool,
+    /// Response type, or the error message when it failed
+    pub result: String,
+}
+
+/// Supervisor event for the audit trail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppEvent {
+    pub app_id: u32,
+    /// App name at the time of the event
+    pub name: String,
+    pub kind: EventKind,
+    pub timestamp: DateTime<Utc>,
+    /// Why it happened, e.g. "health", "memory_limit", "manual"
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+}
+
+impl AppEvent {
+    pub fn new(app_id: u32, name: impl Into<String>, kind: EventKind) -> Self {
+        Self {
+            app_id,
+            name: name.into(),
+            kind,
+            timestamp: Utc::now(),
+            reason: None,
+            exit_code: None,
+        }
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+}
+
+/// Restart and availability figures that survive daemon restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppStats {
+    #[serde(default)]
+    pub total_restarts: u32,
+    #[serde(default)]
+    pub crashes: u32,
+    #[serde(default)]
+    pub last_crash_at: Option<DateTime<Utc>>,
+    /// Time spent down after crashes, until the app was started or stopped
+    #[serde(default)]
+    pub downtime_secs: u64,
+    /// Mean time between failures: running time divided by crashes
+    #[serde(default)]
+    pub mtbf_secs: Option<u64>,
+}
+
+/// How the binary an app runs was built
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// `debug` or `release`
+    pub profile: String,
+    pub binary_path: PathBuf,
+    pub duration_ms: u64,
+    /// `HEAD` of the git repository the app lives in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    /// SHA-256 of the binary, hex encoded (binary mode)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl BuildInfo {
+    /// `profile` of binaries oxidepm did not build itself
+    pub const PREBUILT: &'static str = "prebuilt";
+
+    /// Abbreviated commit hash for display
+    pub fn short_commit(&self) -> Option<&str> {
+        self.git_commit.as_deref().map(|c| &c[..c.len().min(12)])
+    }
+
+    /// Abbreviated checksum for display
+    pub fn short_sha256(&self) -> Option<&str> {
+        self.sha256.as_deref().map(|c| &c[..c.len().min(12)])
+    }
+}
+
+/// Daemon liveness, returned by `ping` and written to `heartbeat.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub pid: u32,
+    pub version: String,
+    pub uptime_secs: u64,
+    /// Apps the supervisor tracks
+    pub managed: usize,
+    /// Tracked apps that are running
+    pub running: usize,
+    /// When the heartbeat was taken
+    pub at: DateTime<Utc>,
+    /// Event-loop stalls detected since the daemon started
+    #[serde(default)]
+    pub stalls: u64,
+    /// Longest stall seen, in milliseconds
+    #[serde(default)]
+    pub max_stall_ms: u64,
+}
+
+impl Heartbeat {
+    /// Seconds since the heartbeat was taken
+    pub fn age_secs(&self) -> u64 {
+        (Utc::now() - self.at).num_seconds().max(0) as u64
+    }
+
+    /// Whether the daemon has missed several heartbeats in a row
+    pub fn is_stale(&self) -> bool {
+        self.age_secs() > 3 * HEARTBEAT_INTERVAL_SECS
+    }
+}
+
+/// Daemon-wide overview for `oxidepm top`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub version: String,
+    pub daemon_pid: u32,
+    pub daemon_uptime_secs: u64,
+    /// Managed apps (cluster instances counted individually)
+    pub total_processes: usize,
+    /// Managed apps per status, keyed by `AppStatus::as_str`
+    pub by_status: BTreeMap<String, usize>,
+    /// Combined usage of all supervised processes
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    pub host: HostInfo,
+    /// State of the daemon's database
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db: Option<DbHealth>,
+}
+
+/// Size and maintenance state of the daemon's database
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DbHealth {
+    /// "sqlite" or "postgres"
+    pub backend: String,
+    /// Database file, or the server URL without credentials
+    pub location: String,
+    pub size_bytes: u64,
+    /// Write-ahead log not yet checkpointed into the main file (SQLite)
+    #[serde(default)]
+    pub wal_bytes: u64,
+    /// Unused pages a VACUUM would give back (SQLite)
+    #[serde(default)]
+    pub free_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal_mode: Option<String>,
+    #[serde(default)]
+    pub last_vacuum: Option<DateTime<Utc>>,
+}
+
+/// Which of an app's log files to read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    #[default]
+    Out,
+    Err,
+}
+
+impl LogStream {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogStream::Out => "out",
+            LogStream::Err => "err",
+        }
+    }
+}
+
+impl std::str::FromStr for LogStream {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s {
+            "out" | "stdout" => Ok(LogStream::Out),
+            "err" | "stderr" => Ok(LogStream::Err),
+            other => Err(format!(
+                "Invalid log stream '{}'. Valid values: out, err",
+                other
+            )),
+        }
+    }
+}
+
+/// A page of log lines in chronological order, across rotated files
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogPage {
+    pub stream: LogStream,
+    pub lines: Vec<String>,
+    /// Pass as `before` for the lines preceding this page; unset at the start of history
+    #[serde(default)]
+    pub older: Option<String>,
+    /// Pass as `after` for the lines following this page
+    #[serde(default)]
+    pub newer: Option<String>,
+}
+
+/// A port claimed by a managed app, for `oxidepm ports`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortMapping {
+    pub port: u16,
+    pub app_id: u32,
+    pub name: String,
+    pub status: AppStatus,
+}
+
+/// Host machine figures reported by the daemon
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostInfo {
+    #[serde(default)]
+    pub hostname: Option<String>,
+    pub cpu_count: usize,
+    /// 1, 5 and 15 minute load averages
+    pub load_average: [f64; 3],
+    pub total_memory_bytes: u64,
+    pub used_memory_bytes: u64,
+    pub uptime_secs: u64,
+}
+
+/// Outcome of reconciling the daemon against a desired app list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApplyReport {
+    /// Apps that were not running and have been started
+    pub started: Vec<String>,
+    /// Apps whose definition changed and were restarted with the new spec
+    pub reloaded: Vec<String>,
+    /// Apps removed because they are no longer declared (--prune)
+    pub deleted: Vec<String>,
+    /// Apps already matching their declaration
+    pub unchanged: Vec<String>,
+    /// Apps that could not be applied, as "name: error"
+    pub failed: Vec<String>,
+    /// Whether this is only a plan (nothing was changed)
+    pub dry_run: bool,
+}
+
+impl ApplyReport {
+    /// Whether applying would change anything
+    pub fn has_changes(&self) -> bool {
+        !(self.started.is_empty() && self.reloaded.is_empty() && self.deleted.is_empty())
+    }
+}
+
+/// What a stop/restart/delete over a selector did to one app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppOpResult {
+    pub id: u32,
+    pub name: String,
+    pub outcome: AppOpOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AppOpOutcome {
+    Done,
+    /// Nothing to do, e.g. starting an app that is already running
+    Skipped,
+    /// Stop of an app that was not running
+    AlreadyStopped,
+    /// The selector matched no app (with `if_exists`); `name` is the selector
+    /// and `id` is 0
+    NotFound,
+    Failed {
+        error: String,
+    },
+}
+
+impl AppOpResult {
+    /// Result for a selector that matched no app
+    pub fn not_found(selector: impl std::fmt::Display) -> Self {
+        Self {
+            id: 0,
+            name: selector.to_string(),
+            outcome: AppOpOutcome::NotFound,
+        }
+    }
+
+    /// Number of results where the operation was carried out
+    pub fn count_done(results: &[AppOpResult]) -> usize {
+        results
+            .iter()
+            .filter(|r| r.outcome == AppOpOutcome::Done)
+            .count()
+    }
+}
+
+/// Operation `oxidepm group` rolls out over the selected apps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl GroupAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupAction::Start => "start",
+            GroupAction::Stop => "stop",
+            GroupAction::Restart => "restart",
         }
     }
+}
 
-    pub fn with_port(mut self, port: u16) -> Self {
-        self.port = Some(port);
-        self
+impl std::fmt::Display for GroupAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
     }
+}
 
-    pub fn with_instance(mut self, instance_id: u32) -> Self {
-        self.instance_id = Some(instance_id);
-        self
+/// How a group operation proceeds through the selected apps
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rollout {
+    /// Apps handled per batch; 0 handles all of them at once
+    #[serde(default)]
+    pub batch_size: usize,
+    /// Wait for each started batch to be healthy before moving on, and halt
+    /// the rollout when it is not
+    #[serde(default)]
+    pub wait_healthy: bool,
+    /// How long to wait for a batch to become healthy
+    /// (default `DEFAULT_ROLLOUT_HEALTH_TIMEOUT_SECS`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_timeout_secs: Option<u64>,
+}
+
+impl Rollout {
+    /// Split `ids` into the batches the rollout goes through
+    pub fn batches<'a>(&self, ids: &'a [u32]) -> Vec<&'a [u32]> {
+        let size = if self.batch_size == 0 {
+            ids.len().max(1)
+        } else {
+            self.batch_size
+        };
+        ids.chunks(size).collect()
     }
 }
 
-/// Restart policy configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RestartPolicy {
-    pub auto_restart: bool,
-    pub max_restarts: u32,
-    pub restart_delay_ms: u64,
-    pub crash_window_secs: u64,
+/// How a restart over several apps works through them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkOptions {
+    /// Apps handled at once; 0 uses `BULK_OP_CONCURRENCY`
+    #[serde(default)]
+    pub parallel: usize,
+    /// Begin no more apps once one fails; those left are reported as skipped
+    #[serde(default)]
+    pub halt_on_error: bool,
+    /// Stream a line per finished app ahead of the response
+    #[serde(default)]
+    pub progress: bool,
 }
 
-impl Default for RestartPolicy {
-    fn default() -> Self {
-        Self {
-            auto_restart: true,
-            max_restarts: DEFAULT_MAX_RESTARTS,
-            restart_delay_ms: DEFAULT_RESTART_DELAY_MS,
-            crash_window_secs: DEFAULT_CRASH_WINDOW_SECS,
+impl BulkOptions {
+    /// Apps handled at once
+    pub fn concurrency(&self) -> usize {
+        if self.parallel == 0 {
+            crate::constants::BULK_OP_CONCURRENCY
+        } else {
+            self.parallel
         }
     }
 }
 
-/// Selector for targeting apps by id, name, tag, or all
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(untagged)]
-pub enum Selector {
-    All,
-    ById(u32),
-    ByName(String),
-    /// Select by tag using @tagname syntax
-    ByTag(String),
+/// Changes to an app's stored spec (`oxidepm update`); unset fields are
+/// left as they are. A memory or uptime limit of 0 removes the limit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uptime_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_restart: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_restarts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_delay_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kill_timeout_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+    /// Replaces the app's tags
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
 }
 
-impl Selector {
-    /// Parse a selector string
-    /// - "all" -> All
-    /// - "123" -> ById(123)
-    /// - "@tagname" -> ByTag("tagname")
-    /// - "appname" -> ByName("appname")
-    pub fn parse(s: &str) -> Self {
-        if s.eq_ignore_ascii_case("all") {
-            Selector::All
-        } else if let Some(tag) = s.strip_prefix('@') {
-            Selector::ByTag(tag.to_string())
-        } else if let Ok(id) = s.parse::<u32>() {
-            Selector::ById(id)
-        } else {
-            Selector::ByName(s.to_string())
-        }
+impl SpecUpdate {
+    /// Whether nothing would change
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
     }
 
-    /// Check if this selector matches an app spec
-    pub fn matches(&self, spec: &AppSpec) -> bool {
-        match self {
-            Selector::All => true,
-            Selector::ById(id) => spec.id == *id,
-            Selector::ByName(name) => spec.name == *name,
-            Selector::ByTag(tag) => spec.tags.contains(tag),
+    /// Apply the set fields to `spec`
+    pub fn apply(&self, spec: &mut AppSpec) {
+        if let Some(mb) = self.max_memory_mb {
+            spec.max_memory_mb = (mb > 0).then_some(mb);
+        }
+        if let Some(secs) = self.max_uptime_secs {
+            spec.max_uptime_secs = (secs > 0).then_some(secs);
+        }
+        if let Some(watch) = self.watch {
+            spec.watch = watch;
+        }
+        if let Some(auto_restart) = self.auto_restart {
+            spec.restart_policy.auto_restart = auto_restart;
+        }
+        if let Some(max_restarts) = self.max_restarts {
+            spec.restart_policy.max_restarts = max_restarts;
+        }
+        if let Some(delay) = self.restart_delay_ms {
+            spec.restart_policy.restart_delay_ms = delay;
+        }
+        if let Some(timeout) = self.kill_timeout_ms {
+            spec.kill_timeout_ms = timeout;
+        }
+        if let Some(port) = self.port {
+            spec.port = Some(port);
+        }
+        if let Some(check) = &self.health_check {
+            spec.health_check = Some(check.clone());
+        }
+        if let Some(tags) = &self.tags {
+            spec.tags = tags.clone();
         }
     }
 }
 
-impl std::fmt::Display for Selector {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Selector::All => write!(f, "all"),
-            Selector::ById(id) => write!(f, "{}", id),
-            Selector::ByName(name) => write!(f, "{}", name),
-            Selector::ByTag(tag) => write!(f, "@{}", tag),
+/// Status of a single preflight check result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Error,
+    Info,
+    Fixed,
+}
+
+/// Result of a single preflight check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix_hint: Option<String>,
+}
+
+/// Preflight check results for a project, as run by `oxidepm check`,
+/// `oxidepm start` and the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightSummary {
+    pub results: Vec<CheckResult>,
+    pub warnings: usize,
+    pub errors: usize,
+    pub has_blocking_issues: bool,
+}
+
+impl PreflightSummary {
+    pub fn new(results: Vec<CheckResult>) -> Self {
+        let warnings = results
+            .iter()
+            .filter(|r| r.status == CheckStatus::Warn)
+            .count();
+        let errors = results
+            .iter()
+            .filter(|r| r.status == CheckStatus::Error)
+            .count();
+
+        // Blocking issues are warnings about missing deps (node_modules) or errors
+        let has_blocking_issues = results.iter().any(|r| {
+            r.status == CheckStatus::Error
+                || (r.status == CheckStatus::Warn && r.message.contains("node_modules"))
+        });
+
+        Self {
+            results,
+            warnings,
+            errors,
+            has_blocking_issues,
         }
     }
+
+    /// Check if there are issues that would prevent starting
+    pub fn can_start(&self) -> bool {
+        !self.has_blocking_issues
+    }
+
+    /// The warnings and errors, in the order they were found
+    pub fn issues(&self) -> impl Iterator<Item = &CheckResult> {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.status, CheckStatus::Warn | CheckStatus::Error))
+    }
 }
 
-/// Full application info (spec + state) for status display
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppInfo {
-    pub spec: AppSpec,
-    pub state: RunState,
+/// A long request (start, restart, reload, rollout, apply, resurrect) the
+/// daemon tracks by id; one that outlasts its time budget carries on in the
+/// background and is polled by its id until it finishes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationInfo {
+    pub id: u64,
+    /// Request type, e.g. "start"
+    pub action: String,
+    /// What the request was for, e.g. "api" or "@web"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    pub state: OperationState,
+    /// How far along it is, for requests over several apps
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u8>,
+    /// Latest output lines: build output, or a line per finished app
+    #[serde(default)]
+    pub log: Vec<String>,
+    /// Lines output so far, including those that no longer fit in `log`
+    #[serde(default)]
+    pub log_lines: usize,
 }
 
-impl AppInfo {
-    pub fn new(spec: AppSpec, state: RunState) -> Self {
-        Self { spec, state }
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum OperationState {
+    Running,
+    Succeeded,
+    Failed { error: String },
+}
+
+impl OperationInfo {
+    pub fn is_finished(&self) -> bool {
+        self.state != OperationState::Running
     }
 }
 
@@ -641,6 +2639,95 @@ impl AppInfo {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_rollout_batches() {
+        let ids = [1, 2, 3, 4, 5];
+        assert_eq!(Rollout::default().batches(&ids), vec![&ids[..]]);
+        let batched = Rollout {
+            batch_size: 2,
+            ..Default::default()
+        };
+        assert_eq!(batched.batches(&ids), vec![&[1, 2][..], &[3, 4], &[5]]);
+        assert!(batched.batches(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_wait_target() {
+        assert_eq!(
+            "tcp://db:5432".parse::<WaitTarget>().unwrap(),
+            WaitTarget::Tcp("db:5432".into())
+        );
+        assert_eq!(
+            "http://localhost:8200/health".parse::<WaitTarget>().unwrap(),
+            WaitTarget::Http("http://localhost:8200/health".into())
+        );
+        assert_eq!(
+            "run/ready.sock".parse::<WaitTarget>().unwrap(),
+            WaitTarget::Path("run/ready.sock".into())
+        );
+        assert_eq!(
+            "tcp://db:5432".parse::<WaitTarget>().unwrap().to_string(),
+            "tcp://db:5432"
+        );
+        for invalid in ["tcp://db", "tcp://:5432", "redis://cache:6379", " "] {
+            assert!(invalid.parse::<WaitTarget>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_resolved_env() {
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/srv"),
+        );
+        spec.env.insert("PORT".to_string(), "3000".to_string());
+        spec.env
+            .insert("NODE_ENV".to_string(), "production".to_string());
+        spec.env_sources
+            .insert("NODE_ENV".to_string(), EnvSource::EnvFile);
+
+        let inherited = [("PATH", "/usr/bin"), ("NODE_ENV", "development")]
+            .map(|(k, v)| (k.to_string(), v.to_string()));
+        let env = spec.resolved_env(inherited);
+        assert_eq!(env["PATH"], ("/usr/bin".to_string(), EnvSource::Inherited));
+        assert_eq!(
+            env["NODE_ENV"],
+            ("production".to_string(), EnvSource::EnvFile)
+        );
+        assert_eq!(env["PORT"], ("3000".to_string(), EnvSource::App));
+
+        // Provenance alone does not change the definition
+        let mut other = spec.clone();
+        other.env_sources.clear();
+        assert!(spec.definition_eq(&other));
+    }
+
+    #[test]
+    fn test_definition_eq() {
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/srv"),
+        );
+
+        let mut same = spec.clone();
+        same.id = 42;
+        same.created_at = Utc::now() + chrono::Duration::hours(1);
+        assert!(spec.definition_eq(&same));
+
+        let mut changed = spec.clone();
+        changed
+            .env
+            .insert("NODE_ENV".to_string(), "production".to_string());
+        assert!(!spec.definition_eq(&changed));
+
+        let changed = spec.clone().with_instances(2);
+        assert!(!spec.definition_eq(&changed));
+    }
+
     #[test]
     fn test_app_mode_from_extension() {
         assert_eq!(AppMode::from_extension("rs"), Some(AppMode::Rust));
@@ -657,6 +2744,68 @@ mod tests {
         assert!("invalid".parse::<AppMode>().is_err());
     }
 
+    #[test]
+    fn test_start_order() {
+        let app = |name: &str, deps: &[&str]| {
+            let mut spec = AppSpec::new(name.into(), AppMode::Cmd, "server".into(), "/srv".into());
+            spec.depends_on = deps.iter().map(|dep| dep.to_string()).collect();
+            spec
+        };
+        let apps = vec![
+            app("web", &["api", "db"]),
+            app("api", &["db"]),
+            app("db", &[]),
+            app("worker", &[]),
+            app("api", &["db"]).with_namespace("shop"),
+            app("ping", &["pong"]),
+            app("pong", &["ping"]),
+        ];
+
+        let mut warnings = Vec::new();
+        let order = start_order(&apps, &mut warnings);
+        assert_eq!(&order[..5], &[(0, 2), (0, 1), (0, 0), (0, 0), (0, 0)]);
+        // A cycle is broken, not fatal
+        assert_eq!(order[5].1.abs_diff(order[6].1), 1);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("'shop/api' depends on unknown app 'db'"));
+        assert!(warnings[1].contains("cycle"));
+    }
+
+    #[test]
+    fn test_start_order_priority() {
+        let app = |name: &str, priority: i32, deps: &[&str]| {
+            let mut spec = AppSpec::new(name.into(), AppMode::Cmd, "server".into(), "/srv".into())
+                .with_priority(priority);
+            spec.depends_on = deps.iter().map(|dep| dep.to_string()).collect();
+            spec
+        };
+        let apps = vec![
+            app("web", 20, &[]),
+            app("api", 10, &[]),
+            app("db", 0, &[]),
+            // Depends on a later band, so it waits for it
+            app("cache", 0, &["api"]),
+            app("jobs", 10, &["api"]),
+        ];
+
+        let mut warnings = Vec::new();
+        let order = start_order(&apps, &mut warnings);
+        assert_eq!(order, vec![(20, 0), (10, 0), (0, 0), (10, 1), (10, 1)]);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_app_signal_from_str() {
+        assert_eq!("SIGUSR2".parse::<AppSignal>().unwrap(), AppSignal::Sigusr2);
+        assert_eq!("hup".parse::<AppSignal>().unwrap(), AppSignal::Sighup);
+        assert!("SIGKILL".parse::<AppSignal>().is_err());
+        assert!("SIGFOO".parse::<AppSignal>().is_err());
+        assert_eq!(
+            serde_json::to_string(&AppSignal::Sigusr1).unwrap(),
+            "\"SIGUSR1\""
+        );
+    }
+
     #[test]
     fn test_restart_policy_default() {
         let policy = RestartPolicy::default();
@@ -665,14 +2814,59 @@ mod tests {
         assert_eq!(policy.restart_delay_ms, DEFAULT_RESTART_DELAY_MS);
     }
 
+    #[test]
+    fn test_restart_policy_backoff() {
+        let policy = RestartPolicy {
+            restart_delay_ms: 1000,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff_delay_ms(0), 1000);
+        assert_eq!(policy.backoff_delay_ms(1), 2000);
+        assert_eq!(policy.backoff_delay_ms(3), 8000);
+        assert_eq!(policy.backoff_delay_ms(10), MAX_RESTART_BACKOFF_MS);
+        assert_eq!(policy.backoff_delay_ms(u32::MAX), MAX_RESTART_BACKOFF_MS);
+    }
+
+    #[test]
+    fn test_unhealthy_action_serde() {
+        let hc: HealthCheck = serde_json::from_str(
+            r#"{"http_url": null, "script": "true", "expected_status": [200], "interval_secs": 1, "timeout_secs": 1, "retries": 1, "on_unhealthy": "restart_with_backoff"}"#,
+        )
+        .unwrap();
+        assert_eq!(hc.on_unhealthy, UnhealthyAction::RestartWithBackoff);
+        assert_eq!(hc.initial_delay_secs, DEFAULT_HEALTH_INITIAL_DELAY_SECS);
+        assert_eq!(hc.success_threshold, 1);
+        assert_eq!(
+            HealthCheck::default().on_unhealthy,
+            UnhealthyAction::NotifyOnly
+        );
+    }
+
     #[test]
     fn test_selector_parse() {
         assert_eq!(Selector::parse("all"), Selector::All);
         assert_eq!(Selector::parse("ALL"), Selector::All);
         assert_eq!(Selector::parse("123"), Selector::ById(123));
-        assert_eq!(Selector::parse("myapp"), Selector::ByName("myapp".to_string()));
-        assert_eq!(Selector::parse("@production"), Selector::ByTag("production".to_string()));
-        assert_eq!(Selector::parse("@web-servers"), Selector::ByTag("web-servers".to_string()));
+        assert_eq!(
+            Selector::parse("myapp"),
+            Selector::ByName("myapp".to_string())
+        );
+        assert_eq!(
+            Selector::parse("@production"),
+            Selector::ByTag("production".to_string())
+        );
+        assert_eq!(
+            Selector::parse("@web-servers"),
+            Selector::ByTag("web-servers".to_string())
+        );
+        assert_eq!(
+            Selector::parse("ns:shop"),
+            Selector::ByNamespace("shop".to_string())
+        );
+        assert_eq!(
+            Selector::parse("shop/api"),
+            Selector::ByName("shop/api".to_string())
+        );
     }
 
     #[test]
@@ -697,6 +2891,15 @@ mod tests {
         assert!(Selector::ByTag("production".to_string()).matches(&spec_with_id));
         assert!(Selector::ByTag("web".to_string()).matches(&spec_with_id));
         assert!(!Selector::ByTag("staging".to_string()).matches(&spec_with_id));
+        assert!(Selector::ByNamespace("default".to_string()).matches(&spec_with_id));
+        assert!(Selector::ByName("default/test-app".to_string()).matches(&spec_with_id));
+
+        let shop = spec_with_id.with_namespace("shop");
+        assert!(Selector::ByNamespace("shop".to_string()).matches(&shop));
+        assert!(Selector::ByName("shop/test-app".to_string()).matches(&shop));
+        assert!(!Selector::ByName("blog/test-app".to_string()).matches(&shop));
+        assert_eq!(shop.qualified_name(), "shop/test-app");
+        assert_eq!(shop.log_name(), "shop.test-app");
     }
 
     #[test]
@@ -704,7 +2907,32 @@ mod tests {
         assert_eq!(Selector::All.to_string(), "all");
         assert_eq!(Selector::ById(123).to_string(), "123");
         assert_eq!(Selector::ByName("myapp".to_string()).to_string(), "myapp");
-        assert_eq!(Selector::ByTag("production".to_string()).to_string(), "@production");
+        assert_eq!(
+            Selector::ByTag("production".to_string()).to_string(),
+            "@production"
+        );
+        assert_eq!(
+            Selector::ByNamespace("shop".to_string()).to_string(),
+            "ns:shop"
+        );
+    }
+
+    #[test]
+    fn test_selector_serde_roundtrip() {
+        for selector in [
+            Selector::All,
+            Selector::ById(7),
+            Selector::ByName("shop/api".to_string()),
+            Selector::ByTag("web".to_string()),
+            Selector::ByNamespace("shop".to_string()),
+        ] {
+            let json = serde_json::to_string(&selector).unwrap();
+            assert_eq!(serde_json::from_str::<Selector>(&json).unwrap(), selector);
+        }
+        assert_eq!(
+            serde_json::from_str::<Selector>("null").unwrap(),
+            Selector::All
+        );
     }
 
     #[test]
@@ -735,6 +2963,61 @@ mod tests {
         assert_eq!(spec.max_uptime_secs, Some(86400));
     }
 
+    #[test]
+    fn test_app_spec_log_paths() {
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/app"),
+        );
+        assert_eq!(spec.stdout_log_path(), log_path("api", "out"));
+        assert_eq!(spec.stderr_log_path(), log_path("api", "err"));
+
+        let spec = spec.with_logs(LogSettings {
+            out: Some(PathBuf::from("/var/log/api.log")),
+            err: Some(PathBuf::from("/dev/null")),
+            merge: false,
+            timestamps: LogTimestamps::Rfc3339,
+            overflow: LogOverflow::DropOldest,
+            max_line_bytes: Some(4096),
+            raw: false,
+        });
+        assert_eq!(spec.stderr_log_path(), PathBuf::from("/dev/null"));
+        let instance = spec.for_instance(2, None);
+        assert_eq!(
+            instance.stdout_log_path(),
+            PathBuf::from("/var/log/api-2.log")
+        );
+        assert_eq!(instance.stderr_log_path(), PathBuf::from("/dev/null"));
+        assert_eq!(instance.logs.timestamps, LogTimestamps::Rfc3339);
+        assert_eq!(instance.logs.overflow, LogOverflow::DropOldest);
+        assert_eq!(instance.logs.max_line_bytes, Some(4096));
+
+        let mut logs = spec.logs.clone();
+        logs.merge = true;
+        let spec = spec.with_logs(logs);
+        assert_eq!(spec.stderr_log_path(), PathBuf::from("/var/log/api.log"));
+        assert!(spec.logs.validate().is_ok());
+
+        let same = LogSettings {
+            out: Some(PathBuf::from("/var/log/api.log")),
+            err: Some(PathBuf::from("/var/log/api.log")),
+            ..Default::default()
+        };
+        assert!(same.validate().is_err());
+        let dir = LogSettings {
+            out: Some(std::env::temp_dir()),
+            ..Default::default()
+        };
+        assert!(dir.validate().is_err());
+        let empty_lines = LogSettings {
+            max_line_bytes: Some(0),
+            ..Default::default()
+        };
+        assert!(empty_lines.validate().is_err());
+    }
+
     #[test]
     fn test_app_status_is_running() {
         assert!(AppStatus::Running.is_running());
@@ -775,25 +3058,85 @@ mod tests {
         let mut hooks = Hooks::default();
         assert!(hooks.is_empty());
 
-        hooks.on_start = Some("echo 'started'".to_string());
+        hooks.on_start = Some(Hook::from("echo 'started'"));
         assert!(!hooks.is_empty());
     }
 
     #[test]
     fn test_hooks_get() {
         let hooks = Hooks {
-            on_start: Some("start.sh".to_string()),
-            on_stop: Some("stop.sh".to_string()),
+            on_start: Some(Hook::from("start.sh")),
+            on_stop: Some(Hook::from("stop.sh")),
             on_restart: None,
-            on_crash: Some("crash.sh".to_string()),
+            on_crash: Some(Hook::from("crash.sh")),
             on_error: None,
+            on_pre_reload: None,
+            on_post_reload: Some(Hook::from("deployed.sh")),
         };
 
-        assert_eq!(hooks.get(HookEvent::Start), Some("start.sh"));
-        assert_eq!(hooks.get(HookEvent::Stop), Some("stop.sh"));
+        assert_eq!(hooks.get(HookEvent::Start), Some(&Hook::from("start.sh")));
+        assert_eq!(hooks.get(HookEvent::Stop), Some(&Hook::from("stop.sh")));
         assert_eq!(hooks.get(HookEvent::Restart), None);
-        assert_eq!(hooks.get(HookEvent::Crash), Some("crash.sh"));
+        assert_eq!(hooks.get(HookEvent::Crash), Some(&Hook::from("crash.sh")));
         assert_eq!(hooks.get(HookEvent::Error), None);
+        assert_eq!(
+            hooks.get(HookEvent::PostReload),
+            Some(&Hook::from("deployed.sh"))
+        );
+    }
+
+    #[test]
+    fn test_hook_forms() {
+        let shell: Hook = serde_json::from_str(r#""./notify.sh""#).unwrap();
+        assert_eq!(shell, Hook::from("./notify.sh"));
+        assert_eq!(shell.timeout_secs(), DEFAULT_HOOK_TIMEOUT_SECS);
+
+        let exec: Hook = serde_json::from_str(
+            r#"{"command": "/usr/bin/curl", "args": ["-d", "{app_name}"], "timeout_secs": 5}"#,
+        )
+        .unwrap();
+        assert_eq!(exec.timeout_secs(), 5);
+        assert_eq!(exec.to_string(), "/usr/bin/curl -d {app_name}");
+        assert!(exec.validate().is_ok());
+
+        let Hook::Detailed(spec) = &exec else {
+            unreachable!()
+        };
+        let values = [
+            ("app_name", "api".to_string()),
+            ("log_line", "{app_name} {pid}".to_string()),
+        ];
+        assert_eq!(spec.interpolated_args(&values), vec!["-d", "api"]);
+        let spec = HookSpec {
+            args: vec!["{log_line}".into(), "pid={pid}".into(), "{}".into()],
+            ..spec.clone()
+        };
+        assert_eq!(
+            spec.interpolated_args(&values),
+            vec!["{app_name} {pid}", "pid=", "{}"]
+        );
+
+        let invalid = |json: &str| {
+            serde_json::from_str::<Hook>(json)
+                .unwrap()
+                .validate()
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(invalid(r#"{"timeout_secs": 5}"#).contains("either `run` or `command`"));
+        assert!(invalid(r#"{"run": "a", "command": "b"}"#).contains("both"));
+        assert!(invalid(r#"{"run": "echo", "args": ["x"]}"#).contains("`args` need `command`"));
+        assert!(invalid(r#"{"run": "echo", "timeout_secs": 0}"#).contains("at least 1"));
+
+        let hooks = Hooks {
+            on_crash: Some(Hook::from(" ")),
+            ..Default::default()
+        };
+        assert!(hooks
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("hooks.on_crash: hook command is empty"));
     }
 
     #[test]
@@ -803,13 +3146,14 @@ mod tests {
         assert_eq!(HookEvent::Restart.as_str(), "restart");
         assert_eq!(HookEvent::Crash.as_str(), "crash");
         assert_eq!(HookEvent::Error.as_str(), "error");
+        assert_eq!(HookEvent::PostReload.as_str(), "post_reload");
     }
 
     #[test]
     fn test_app_spec_with_hooks() {
         let hooks = Hooks {
-            on_start: Some("echo 'started'".to_string()),
-            on_crash: Some("/scripts/notify.sh".to_string()),
+            on_start: Some(Hook::from("echo 'started'")),
+            on_crash: Some(Hook::from("/scripts/notify.sh")),
             ..Default::default()
         };
 
@@ -821,8 +3165,8 @@ mod tests {
         )
         .with_hooks(hooks);
 
-        assert_eq!(spec.hooks.on_start, Some("echo 'started'".to_string()));
-        assert_eq!(spec.hooks.on_crash, Some("/scripts/notify.sh".to_string()));
+        assert_eq!(spec.hooks.on_start, Some(Hook::from("echo 'started'")));
+        assert_eq!(spec.hooks.on_crash, Some(Hook::from("/scripts/notify.sh")));
         assert!(spec.hooks.on_stop.is_none());
     }
 
@@ -865,6 +3209,38 @@ mod tests {
         assert!(spec.env_inherit);
     }
 
+    #[test]
+    fn test_spec_update_apply() {
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/project"),
+        );
+        spec.max_uptime_secs = Some(3600);
+        spec.tags = vec!["old".to_string()];
+
+        let update = SpecUpdate {
+            max_memory_mb: Some(512),
+            max_uptime_secs: Some(0),
+            watch: Some(true),
+            max_restarts: Some(3),
+            tags: Some(vec!["web".to_string()]),
+            ..Default::default()
+        };
+        assert!(!update.is_empty());
+        assert!(SpecUpdate::default().is_empty());
+        update.apply(&mut spec);
+
+        assert_eq!(spec.max_memory_mb, Some(512));
+        assert_eq!(spec.max_uptime_secs, None);
+        assert!(spec.watch);
+        assert_eq!(spec.restart_policy.max_restarts, 3);
+        assert!(spec.restart_policy.auto_restart);
+        assert_eq!(spec.tags, vec!["web".to_string()]);
+        assert_eq!(spec.kill_timeout_ms, DEFAULT_KILL_TIMEOUT_MS);
+    }
+
     #[test]
     fn test_validate_app_name_valid() {
         assert!(validate_app_name("myapp"));
@@ -897,6 +3273,120 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_app_spec_for_launch() {
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/app"),
+        )
+        .with_instances(4)
+        .with_args(vec![
+            "--shard={{instance}}/{{instances}}".to_string(),
+            "--verbose".to_string(),
+        ]);
+        spec.id = 7;
+
+        let launch = spec.for_instance(2, Some(3002)).for_launch();
+        assert_eq!(launch.args, vec!["--shard=2/4", "--verbose"]);
+        assert_eq!(
+            launch.env.get("NODE_APP_INSTANCE").map(String::as_str),
+            Some("2")
+        );
+        assert_eq!(
+            launch.env.get("OXIDE_INSTANCE_ID").map(String::as_str),
+            Some("2")
+        );
+        assert_eq!(
+            launch.env.get("OXIDE_INSTANCES").map(String::as_str),
+            Some("4")
+        );
+        assert_eq!(
+            launch.env.get("OXIDE_APP_ID").map(String::as_str),
+            Some("7")
+        );
+        assert_eq!(
+            launch.env.get("OXIDE_PORT").map(String::as_str),
+            Some("3002")
+        );
+
+        // A single app is instance 0 of 1, and the spec itself is untouched
+        let single = AppSpec::new(
+            "web".to_string(),
+            AppMode::Node,
+            "web.js".to_string(),
+            PathBuf::from("/app"),
+        )
+        .for_launch();
+        assert_eq!(
+            single.env.get("NODE_APP_INSTANCE").map(String::as_str),
+            Some("0")
+        );
+        assert_eq!(
+            single.env.get("OXIDE_INSTANCES").map(String::as_str),
+            Some("1")
+        );
+        assert!(!single.env.contains_key("OXIDE_PORT"));
+        assert!(!spec.env.contains_key("NODE_APP_INSTANCE"));
+
+        // What the user set wins over the injected values
+        let env = HashMap::from([
+            ("NODE_APP_INSTANCE".to_string(), "primary".to_string()),
+            ("OXIDE_APP_ID".to_string(), "billing".to_string()),
+        ]);
+        let launch = spec
+            .clone()
+            .with_env(env)
+            .for_instance(1, Some(3001))
+            .for_launch();
+        assert_eq!(
+            launch.env.get("NODE_APP_INSTANCE").map(String::as_str),
+            Some("primary")
+        );
+        assert_eq!(
+            launch.env.get("OXIDE_APP_ID").map(String::as_str),
+            Some("billing")
+        );
+        assert_eq!(
+            launch.env.get("OXIDE_INSTANCE_ID").map(String::as_str),
+            Some("1")
+        );
+    }
+
+    #[test]
+    fn test_apply_env_file() {
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/app"),
+        );
+        for (key, value, source) in [
+            ("DB_PASSWORD", "old", EnvSource::EnvFile),
+            ("REMOVED", "x", EnvSource::EnvFile),
+            ("PORT", "3000", EnvSource::Flag),
+            ("NODE_ENV", "production", EnvSource::Config),
+        ] {
+            spec.env.insert(key.to_string(), value.to_string());
+            spec.env_sources.insert(key.to_string(), source);
+        }
+
+        let vars = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let file = vars(&[("DB_PASSWORD", "new"), ("PORT", "9999")]);
+        assert!(spec.apply_env_file(file.clone()));
+        assert_eq!(spec.env["DB_PASSWORD"], "new");
+        assert!(!spec.env.contains_key("REMOVED"));
+        assert_eq!(spec.env["PORT"], "3000");
+        assert_eq!(spec.env["NODE_ENV"], "production");
+        assert!(!spec.apply_env_file(file));
+    }
+
     #[test]
     fn test_app_spec_try_new_invalid() {
         let result = AppSpec::try_new(
@@ -0,0 +1,862 @@
+//! Config file validation
+//!
+//! Unlike [`ConfigFile::load`](crate::ConfigFile::load), which stops at the
+//! first problem and silently ignores unknown keys, validation collects every
+//! issue it can find with a line number, so it can be used as a CI check.
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+use crate::config::{ConfigFile, ConfigFormat, PortSetting};
+use crate::constants::DEFAULT_NAMESPACE;
+use crate::error::Error;
+use crate::types::{validate_app_name, AppMode, LogTimestamps, WaitTarget};
+
+/// Keys accepted at the top level of a config file
+const TOP_LEVEL_KEYS: &[&str] = &["namespace", "apps"];
+
+/// Keys accepted for each app (mirrors `AppConfig`)
+const APP_KEYS: &[&str] = &[
+    "name",
+    "namespace",
+    "mode",
+    "script",
+    "workspace",
+    "bin",
+    "cargo_features",
+    "cargo_profile",
+    "cargo_target",
+    "cwd",
+    "args",
+    "env",
+    "env_file",
+    "watch_env_file",
+    "watch",
+    "ignore",
+    "watch_paths",
+    "watch_extensions",
+    "watch_poll_ms",
+    "reload_on_change",
+    "build_timeout_secs",
+    "restart_delay",
+    "max_restarts",
+    "kill_timeout",
+    "no_autorestart",
+    "instances",
+    "port",
+    "port_range",
+    "on_port_conflict",
+    "health_check",
+    "liveness_check",
+    "readiness_check",
+    "warmup",
+    "wait_for",
+    "wait_for_timeout_secs",
+    "max_memory_mb",
+    "hooks",
+    "hook_user",
+    "tags",
+    "depends_on",
+    "priority",
+    "startup_delay",
+    "max_uptime_secs",
+    "sandbox",
+    "limits",
+    "logs",
+    "triggers",
+];
+
+const HEALTH_CHECK_KEYS: &[&str] = &[
+    "http_url",
+    "script",
+    "tcp_port",
+    "tcp_host",
+    "expect_output",
+    "expected_status",
+    "interval_secs",
+    "timeout_secs",
+    "retries",
+    "initial_delay_secs",
+    "success_threshold",
+    "on_unhealthy",
+];
+
+const WARMUP_KEYS: &[&str] = &["urls", "requests", "script", "timeout_secs"];
+
+const HOOK_KEYS: &[&str] = &[
+    "on_start",
+    "on_stop",
+    "on_restart",
+    "on_crash",
+    "on_error",
+    "on_pre_reload",
+    "on_post_reload",
+];
+
+/// Keys of a hook given as a table
+const HOOK_SPEC_KEYS: &[&str] = &["run", "command", "args", "timeout_secs"];
+
+const PORT_RANGE_KEYS: &[&str] = &["start", "end"];
+
+const LOGS_KEYS: &[&str] = &[
+    "out",
+    "err",
+    "merge",
+    "timestamps",
+    "overflow",
+    "max_line_bytes",
+    "raw",
+];
+
+const TRIGGER_KEYS: &[&str] = &["pattern", "action", "run", "cooldown_secs"];
+
+const LIMITS_KEYS: &[&str] = &["nofile", "nproc", "core"];
+
+const SANDBOX_KEYS: &[&str] = &[
+    "private_tmp",
+    "read_only_cwd",
+    "network",
+    "writable",
+    "read_only",
+];
+
+/// How serious a validation issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single problem found in a config file
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    /// 1-based line number, when it could be located
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// App the issue belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn error(line: Option<usize>, app: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            line,
+            app: app.map(String::from),
+            message: message.into(),
+        }
+    }
+
+    fn warning(line: Option<usize>, app: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            line,
+            app: app.map(String::from),
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if let Some(app) = &self.app {
+            write!(f, "[{}] ", app)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Validate config file content, returning every issue found
+pub fn validate_config(content: &str, format: ConfigFormat) -> Vec<ConfigIssue> {
+    // Syntax first: nothing else can be checked without a document
+    let document = match parse_value(content, format) {
+        Ok(value) => value,
+        Err(issue) => return vec![issue],
+    };
+
+    let mut issues = Vec::new();
+    check_keys(content, &document, &mut issues);
+
+    // Type errors, with the parser's location
+    let config = match typed_parse(content, format) {
+        Ok(config) => config,
+        Err(issue) => {
+            issues.push(issue);
+            return issues;
+        }
+    };
+
+    check_apps(content, &config, &mut issues);
+    issues.sort_by_key(|issue| issue.line.unwrap_or(0));
+    issues
+}
+
+fn parse_value(content: &str, format: ConfigFormat) -> Result<Value, ConfigIssue> {
+    match format {
+        ConfigFormat::Toml => toml::from_str(content).map_err(|e| toml_issue(content, &e)),
+        ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| yaml_issue(&e)),
+        ConfigFormat::Json => serde_json::from_str(content).map_err(|e| json_issue(&e)),
+    }
+}
+
+fn typed_parse(content: &str, format: ConfigFormat) -> Result<ConfigFile, ConfigIssue> {
+    match format {
+        ConfigFormat::Toml => toml::from_str(content).map_err(|e| toml_issue(content, &e)),
+        ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| yaml_issue(&e)),
+        ConfigFormat::Json => serde_json::from_str(content).map_err(|e| json_issue(&e)),
+    }
+}
+
+fn toml_issue(content: &str, err: &toml::de::Error) -> ConfigIssue {
+    let line = err.span().map(|span| line_of_offset(content, span.start));
+    ConfigIssue::error(line, None, err.message().to_string())
+}
+
+fn yaml_issue(err: &serde_yaml::Error) -> ConfigIssue {
+    let line = err.location().map(|loc| loc.line());
+    // serde_yaml appends " at line X column Y"; the line is reported separately
+    let message = err.to_string();
+    let message = message
+        .split(" at line ")
+        .next()
+        .unwrap_or(&message)
+        .to_string();
+    ConfigIssue::error(line, None, message)
+}
+
+fn json_issue(err: &serde_json::Error) -> ConfigIssue {
+    let message = err.to_string();
+    let message = message
+        .split(" at line ")
+        .next()
+        .unwrap_or(&message)
+        .to_string();
+    ConfigIssue::error(Some(err.line()), None, message)
+}
+
+fn line_of_offset(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// Report keys that the loader would silently ignore
+fn check_keys(content: &str, document: &Value, issues: &mut Vec<ConfigIssue>) {
+    let Some(root) = document.as_object() else {
+        issues.push(ConfigIssue::error(
+            None,
+            None,
+            "config must be a table/object with an `apps` list",
+        ));
+        return;
+    };
+    unknown_keys(content, root, TOP_LEVEL_KEYS, None, 0, issues);
+
+    let Some(apps) = root.get("apps").and_then(Value::as_array) else {
+        return;
+    };
+    for app in apps {
+        let Some(app) = app.as_object() else { continue };
+        let name = app.get("name").and_then(Value::as_str);
+        let start = name.and_then(|n| app_line(content, n)).unwrap_or(0);
+
+        unknown_keys(content, app, APP_KEYS, name, start, issues);
+        for (key, known) in [
+            ("health_check", HEALTH_CHECK_KEYS),
+            ("liveness_check", HEALTH_CHECK_KEYS),
+            ("readiness_check", HEALTH_CHECK_KEYS),
+            ("warmup", WARMUP_KEYS),
+            ("hooks", HOOK_KEYS),
+            ("port_range", PORT_RANGE_KEYS),
+            ("sandbox", SANDBOX_KEYS),
+            ("limits", LIMITS_KEYS),
+            ("logs", LOGS_KEYS),
+        ] {
+            if let Some(table) = app.get(key).and_then(Value::as_object) {
+                unknown_keys(content, table, known, name, start, issues);
+            }
+        }
+        for hook in app
+            .get("hooks")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flat_map(|hooks| hooks.values())
+        {
+            if let Some(table) = hook.as_object() {
+                unknown_keys(content, table, HOOK_SPEC_KEYS, name, start, issues);
+            }
+        }
+        for trigger in app
+            .get("triggers")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(table) = trigger.as_object() {
+                unknown_keys(content, table, TRIGGER_KEYS, name, start, issues);
+            }
+        }
+    }
+}
+
+fn unknown_keys(
+    content: &str,
+    table: &serde_json::Map<String, Value>,
+    known: &[&str],
+    app: Option<&str>,
+    from_line: usize,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    for key in table.keys() {
+        if known.contains(&key.as_str()) {
+            continue;
+        }
+        let mut message = format!("unknown key `{}`", key);
+        if let Some(suggestion) = closest(key, known) {
+            message.push_str(&format!(" (did you mean `{}`?)", suggestion));
+        }
+        issues.push(ConfigIssue::error(
+            key_line(content, key, from_line),
+            app,
+            message,
+        ));
+    }
+}
+
+/// Semantic checks on successfully parsed apps
+fn check_apps(content: &str, config: &ConfigFile, issues: &mut Vec<ConfigIssue>) {
+    if config.apps.is_empty() {
+        issues.push(ConfigIssue::warning(None, None, "no apps defined"));
+    }
+
+    let mut seen = HashSet::new();
+    for app in &config.apps {
+        let name = app.name.as_str();
+        let start = app_line(content, name);
+        let line = |key: &str| start.and_then(|s| key_line(content, key, s)).or(start);
+        let app_ref = Some(name);
+
+        if !validate_app_name(name) {
+            issues.push(ConfigIssue::error(
+                line("name"),
+                app_ref,
+                "invalid name: only letters, digits, `_` and `-` are allowed",
+            ));
+        }
+        let namespace = app
+            .namespace
+            .as_deref()
+            .or(config.namespace.as_deref())
+            .unwrap_or(DEFAULT_NAMESPACE);
+        if !validate_app_name(namespace) {
+            issues.push(ConfigIssue::error(
+                line("namespace"),
+                app_ref,
+                "invalid namespace: only letters, digits, `_` and `-` are allowed",
+            ));
+        }
+        if !seen.insert((namespace, name)) {
+            issues.push(ConfigIssue::error(
+                line("name"),
+                app_ref,
+                "duplicate app name",
+            ));
+        }
+
+        if let Some(mode) = &app.mode {
+            if mode.parse::<AppMode>().is_err() {
+                issues.push(ConfigIssue::error(
+                    line("mode"),
+                    app_ref,
+                    format!(
+                        "invalid mode `{}` (expected cmd, node, npm, pnpm, yarn, cargo or rust)",
+                        mode
+                    ),
+                ));
+            }
+        }
+        if app.script.is_some() && app.bin.is_some() {
+            issues.push(ConfigIssue::warning(
+                line("bin"),
+                app_ref,
+                "both `script` and `bin` are set; `script` wins",
+            ));
+        }
+
+        if app.instances == 0 {
+            issues.push(ConfigIssue::error(
+                line("instances"),
+                app_ref,
+                "`instances` must be at least 1",
+            ));
+        }
+
+        if let Some(range) = &app.port_range {
+            if range.start > range.end {
+                issues.push(ConfigIssue::error(
+                    line("port_range"),
+                    app_ref,
+                    format!(
+                        "port_range start {} is after end {}",
+                        range.start, range.end
+                    ),
+                ));
+            } else {
+                let size = u32::from(range.end - range.start) + 1;
+                if size < app.instances {
+                    issues.push(ConfigIssue::error(
+                        line("port_range"),
+                        app_ref,
+                        format!(
+                            "port_range {}-{} has {} port(s) but `instances` is {}",
+                            range.start, range.end, size, app.instances
+                        ),
+                    ));
+                }
+            }
+            if matches!(app.port, Some(PortSetting::Fixed(_))) {
+                issues.push(ConfigIssue::warning(
+                    line("port"),
+                    app_ref,
+                    "both `port` and `port_range` are set; `port_range` wins",
+                ));
+            }
+        } else if let Some(PortSetting::Fixed(port)) = app.port {
+            if u32::from(port) + app.instances.saturating_sub(1) > u32::from(u16::MAX) {
+                issues.push(ConfigIssue::error(
+                    line("port"),
+                    app_ref,
+                    format!(
+                        "port {} + {} instances runs past 65535",
+                        port, app.instances
+                    ),
+                ));
+            }
+        }
+
+        if let Some(sandbox) = &app.sandbox {
+            if !cfg!(target_os = "linux") {
+                issues.push(ConfigIssue::error(
+                    line("sandbox"),
+                    app_ref,
+                    "`sandbox` is only supported on Linux",
+                ));
+            }
+            for path in &sandbox.writable {
+                if path.is_absolute()
+                    || path
+                        .components()
+                        .any(|c| c == std::path::Component::ParentDir)
+                {
+                    issues.push(ConfigIssue::error(
+                        line("writable"),
+                        app_ref,
+                        format!(
+                            "sandbox writable path {} must stay inside the working directory",
+                            path.display()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let Some(logs) = &app.logs {
+            if logs.merge && logs.err.is_some() {
+                issues.push(ConfigIssue::warning(
+                    line("err"),
+                    app_ref,
+                    "logs.err is ignored when logs.merge = true",
+                ));
+            } else if logs.out.is_some() && logs.out == logs.err {
+                issues.push(ConfigIssue::error(
+                    line("err"),
+                    app_ref,
+                    "logs.out and logs.err are the same file; set logs.merge = true to combine them",
+                ));
+            }
+            if logs.raw && logs.timestamps != LogTimestamps::default() {
+                issues.push(ConfigIssue::warning(
+                    line("timestamps"),
+                    app_ref,
+                    "logs.timestamps is ignored when logs.raw = true",
+                ));
+            }
+            if logs.max_line_bytes == Some(0) {
+                issues.push(ConfigIssue::error(
+                    line("max_line_bytes"),
+                    app_ref,
+                    "logs.max_line_bytes must be at least 1",
+                ));
+            }
+        }
+
+        if let Some(hooks) = &app.hooks {
+            for (key, hook) in hooks.clone().into_hooks().entries() {
+                if let Err(Error::ConfigError(message)) = hook.validate() {
+                    issues.push(ConfigIssue::error(
+                        line(key),
+                        app_ref,
+                        format!("hooks.{}: {}", key, message),
+                    ));
+                }
+            }
+        }
+
+        for target in &app.wait_for {
+            if let Err(Error::ConfigError(message)) = target.parse::<WaitTarget>() {
+                issues.push(ConfigIssue::error(line("wait_for"), app_ref, message));
+            }
+        }
+        if let Some(warmup) = &app.warmup {
+            if let Err(Error::ConfigError(message)) = warmup.validate() {
+                issues.push(ConfigIssue::error(line("warmup"), app_ref, message));
+            }
+        }
+
+        // Each trigger is reported at its own `pattern` line
+        let mut from = start.unwrap_or(0);
+        for trigger in &app.triggers {
+            let at = key_line(content, "pattern", from);
+            from = at.map_or(from, |l| l + 1);
+            if let Err(Error::ConfigError(message)) = trigger.validate() {
+                issues.push(ConfigIssue::error(at.or(start), app_ref, message));
+            }
+        }
+
+        for (section, check) in [
+            ("health_check", &app.health_check),
+            ("readiness_check", &app.readiness_check),
+        ] {
+            let Some(hc) = check else { continue };
+            // `health_check` may be spelled `liveness_check`; nested keys are
+            // looked up from the section's own line
+            let spellings: &[&str] = match section {
+                "health_check" => &["health_check", "liveness_check"],
+                _ => &[section],
+            };
+            let at = start
+                .and_then(|s| spellings.iter().find_map(|key| key_line(content, key, s)))
+                .or(start);
+            let nested = |key: &str| at.and_then(|s| key_line(content, key, s)).or(at);
+
+            if hc.http_url.is_none() && hc.script.is_none() && hc.tcp_port.is_none() {
+                issues.push(ConfigIssue::warning(
+                    at,
+                    app_ref,
+                    format!(
+                        "{} has none of `http_url`, `tcp_port` or `script` and will never run",
+                        section
+                    ),
+                ));
+            }
+            if hc.success_threshold == Some(0) {
+                issues.push(ConfigIssue::error(
+                    nested("success_threshold"),
+                    app_ref,
+                    "success_threshold must be at least 1",
+                ));
+            }
+            if let Some(pattern) = &hc.expect_output {
+                if let Err(e) = Regex::new(pattern) {
+                    issues.push(ConfigIssue::error(
+                        nested("expect_output"),
+                        app_ref,
+                        format!("invalid expect_output regex: {}", e),
+                    ));
+                } else if hc.http_url.is_none() && hc.script.is_none() {
+                    issues.push(ConfigIssue::warning(
+                        nested("expect_output"),
+                        app_ref,
+                        "expect_output only applies to `http_url` and `script` checks",
+                    ));
+                }
+            }
+            if section == "readiness_check" && hc.on_unhealthy.is_some() {
+                issues.push(ConfigIssue::error(
+                    nested("on_unhealthy"),
+                    app_ref,
+                    "readiness_check cannot set on_unhealthy: failing readiness never restarts the app",
+                ));
+            }
+        }
+    }
+}
+
+/// Line declaring the app with this name
+fn app_line(content: &str, name: &str) -> Option<usize> {
+    let pattern = format!(
+        r#"(^|[\s"'{{,-])name["']?\s*[:=]\s*["']?{}["']?\s*(,|$|\}})"#,
+        regex::escape(name)
+    );
+    let re = Regex::new(&pattern).ok()?;
+    content
+        .lines()
+        .position(|line| re.is_match(line))
+        .map(|i| i + 1)
+}
+
+/// First line at or after `from_line` that declares `key` (`key =`, `key:`,
+/// `"key":` or a `[apps.key]` table header)
+fn key_line(content: &str, key: &str, from_line: usize) -> Option<usize> {
+    let pattern = format!(
+        r#"(^|[\s"'\[.{{,-]){}["']?\s*([:=]|\])"#,
+        regex::escape(key)
+    );
+    let re = Regex::new(&pattern).ok()?;
+    content
+        .lines()
+        .enumerate()
+        .skip(from_line.saturating_sub(1))
+        .find(|(_, line)| re.is_match(line))
+        .map(|(i, _)| i + 1)
+}
+
+/// Known key within edit distance 2, for "did you mean" hints
+fn closest<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_toml() {
+        let content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+instances = 4
+port_range = { start = 3000, end = 3001 }
+wacth = true
+
+[[apps]]
+name = "api"
+script = "worker.js"
+
+[apps.health_check]
+interval_secs = 10
+"#;
+        let issues = validate_config(content, ConfigFormat::Toml);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+
+        assert!(messages.contains(
+            &"line 6: [api] port_range 3000-3001 has 2 port(s) but `instances` is 4".to_string()
+        ));
+        assert!(messages
+            .contains(&"line 7: [api] unknown key `wacth` (did you mean `watch`?)".to_string()));
+        assert!(messages.iter().any(|m| m.contains("duplicate app name")));
+        assert!(messages.iter().any(|m| m.contains("will never run")));
+        assert!(issues.iter().any(|i| i.severity == Severity::Warning));
+    }
+
+    #[test]
+    fn test_validate_health_check_matchers() {
+        let content = "apps:\n  - name: db\n    script: db.js\n    health_check:\n      tcp_port: 5432\n      success_threshold: 0\n      expect_output: \"ok\"\n  - name: web\n    script: web.js\n    health_check:\n      http_url: http://localhost/health\n      expect_output: \"(unclosed\"\n";
+        let issues = validate_config(content, ConfigFormat::Yaml);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+
+        assert!(messages.contains(&"line 6: [db] success_threshold must be at least 1".to_string()));
+        assert!(messages.contains(
+            &"line 7: [db] expect_output only applies to `http_url` and `script` checks"
+                .to_string()
+        ));
+        assert!(issues.iter().any(|i| i.is_error() && i.line == Some(12)));
+        assert!(!messages.iter().any(|m| m.contains("will never run")));
+    }
+
+    #[test]
+    fn test_validate_readiness_check() {
+        let content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+
+[apps.liveness_check]
+tcp_port = 3000
+success_threshold = 1
+
+[apps.readiness_check]
+http_url = "http://localhost:3000/ready"
+success_threshold = 0
+on_unhealthy = "restart"
+"#;
+        let issues = validate_config(content, ConfigFormat::Toml);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+
+        assert!(
+            messages.contains(&"line 12: [api] success_threshold must be at least 1".to_string())
+        );
+        assert!(messages.contains(
+            &"line 13: [api] readiness_check cannot set on_unhealthy: failing readiness never restarts the app".to_string()
+        ));
+        assert_eq!(issues.len(), 2);
+    }
+
+    #[test]
+    fn test_validate_hooks() {
+        let content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+hook_user = "deploy"
+
+[apps.hooks]
+on_start = "./started.sh"
+on_crash = { command = "/usr/bin/curl", args = ["{app_name}"], timeout = 5 }
+on_stop = { run = "./stopped.sh", command = "/bin/true" }
+"#;
+        let issues = validate_config(content, ConfigFormat::Toml);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+
+        assert!(messages.contains(&"line 9: [api] unknown key `timeout`".to_string()));
+        assert!(messages.contains(
+            &"line 10: [api] hooks.on_stop: hook cannot set both `run` and `command`".to_string()
+        ));
+        assert_eq!(issues.len(), 2);
+    }
+
+    #[test]
+    fn test_validate_accepts_app_options() {
+        let content = r#"
+[[apps]]
+name = "api"
+script = "server.js"
+build_timeout_secs = 600
+workspace = "api"
+env_file = ".env"
+watch_env_file = "reload"
+reload_on_change = true
+cargo_features = ["tls"]
+cargo_profile = "dev"
+cargo_target = "x86_64-unknown-linux-musl"
+watch = true
+watch_paths = ["src"]
+watch_extensions = ["js"]
+watch_poll_ms = 500
+depends_on = ["db"]
+"#;
+        let issues = validate_config(content, ConfigFormat::Toml);
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_validate_type_error_line() {
+        let content = "apps:\n  - name: web\n    script: app.js\n    instances: lots\n";
+        let issues = validate_config(content, ConfigFormat::Yaml);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_error());
+        assert_eq!(issues[0].line, Some(4));
+
+        let issues = validate_config(
+            "{\"apps\": [\n  {\"name\": \"web\",}\n]}",
+            ConfigFormat::Json,
+        );
+        assert_eq!(issues[0].line, Some(2));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_validate_sandbox() {
+        let content = "[[apps]]\nname = \"grader\"\nscript = \"run.js\"\n\n[apps.sandbox]\nnetwork = true\nwritable = [\"out\", \"../etc\"]\nprivate_temp = true\n";
+        let issues = validate_config(content, ConfigFormat::Toml);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+
+        assert!(messages.contains(
+            &"line 7: [grader] sandbox writable path ../etc must stay inside the working directory"
+                .to_string()
+        ));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("unknown key `private_temp` (did you mean `private_tmp`?)")));
+        assert_eq!(issues.len(), 2);
+    }
+
+    #[test]
+    fn test_validate_logs() {
+        let content = "apps:\n  - name: api\n    script: a.js\n    logs:\n      out: api.log\n      err: api.log\n  - name: web\n    script: w.js\n    logs:\n      merge: true\n      err: web.err\n";
+        let issues = validate_config(content, ConfigFormat::Yaml);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+
+        assert!(messages.contains(&"line 6: [api] logs.out and logs.err are the same file; set logs.merge = true to combine them".to_string()));
+        assert!(messages
+            .contains(&"line 11: [web] logs.err is ignored when logs.merge = true".to_string()));
+
+        let content = "[[apps]]\nname = \"dump\"\nscript = \"d.js\"\n\n[apps.logs]\nraw = true\ntimestamps = \"rfc3339\"\nmax_line_bytes = 0\n";
+        let issues = validate_config(content, ConfigFormat::Toml);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+        assert!(messages.contains(
+            &"line 7: [dump] logs.timestamps is ignored when logs.raw = true".to_string()
+        ));
+        assert!(
+            messages.contains(&"line 8: [dump] logs.max_line_bytes must be at least 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_triggers() {
+        let content = "[[apps]]\nname = \"api\"\nscript = \"a.js\"\n\n[[apps.triggers]]\npattern = \"FATAL: out of memory\"\naction = \"restart\"\n\n[[apps.triggers]]\npattern = \"panic:(\"\naction = \"notify\"\n\n[[apps.triggers]]\npattern = \"deadlock\"\naction = \"hook\"\ncooldown = 5\n";
+        let issues = validate_config(content, ConfigFormat::Toml);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("line 10: [api] invalid trigger pattern 'panic:('")));
+        assert!(messages.contains(
+            &"line 14: [api] trigger 'deadlock' has action = \"hook\" but no `run` command"
+                .to_string()
+        ));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("unknown key `cooldown`")));
+        assert_eq!(issues.len(), 3);
+    }
+
+    #[test]
+    fn test_validate_clean_config() {
+        let content =
+            r#"{"apps": [{"name": "web", "script": "app.js", "instances": 2, "port": 3000}]}"#;
+        assert!(validate_config(content, ConfigFormat::Json).is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("wacth", "watch"), 2);
+        assert_eq!(closest("instance", APP_KEYS), Some("instances"));
+        assert_eq!(closest("totally_unrelated", APP_KEYS), None);
+    }
+}
@@ -1,11 +1,14 @@
 //! Apps repository - CRUD operations for applications
 
-use oxidepm_core::{AppMode, AppSpec, Error, RestartPolicy, Result};
+use async_trait::async_trait;
+use oxidepm_core::{AppMode, AppSpec, DesiredState, Error, RestartPolicy, Result};
 use sqlx::sqlite::SqlitePool;
 use sqlx::Row;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
+use crate::storage::AppStore;
+
 /// Repository for app operations
 pub struct AppsRepository {
     pool: SqlitePool,
@@ -15,48 +18,39 @@ impl AppsRepository {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
     }
+}
 
-    /// Insert a new app
-    pub async fn insert(&self, spec: &AppSpec) -> Result<u32> {
-        let args_json = serde_json::to_string(&spec.args)?;
-        let env_json = serde_json::to_string(&spec.env)?;
-        let ignore_json = serde_json::to_string(&spec.ignore_patterns)?;
+#[async_trait]
+impl AppStore for AppsRepository {
+    async fn insert(&self, spec: &AppSpec) -> Result<u32> {
+        insert_app(&self.pool, spec).await
+    }
 
-        let result = sqlx::query(
-            r#"
-            INSERT INTO apps (
-                name, mode, command, args, cwd, env, watch, ignore_patterns,
-                auto_restart, max_restarts, restart_delay_ms, crash_window_secs, kill_timeout_ms
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&spec.name)
-        .bind(spec.mode.as_str())
-        .bind(&spec.command)
-        .bind(&args_json)
-        .bind(spec.cwd.to_string_lossy().to_string())
-        .bind(&env_json)
-        .bind(spec.watch)
-        .bind(&ignore_json)
-        .bind(spec.restart_policy.auto_restart)
-        .bind(spec.restart_policy.max_restarts as i64)
-        .bind(spec.restart_policy.restart_delay_ms as i64)
-        .bind(spec.restart_policy.crash_window_secs as i64)
-        .bind(spec.kill_timeout_ms as i64)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| Error::DbError(e.to_string()))?;
+    async fn insert_many(&self, specs: &[AppSpec]) -> Result<Vec<u32>> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
 
-        Ok(result.last_insert_rowid() as u32)
+        let mut ids = Vec::with_capacity(specs.len());
+        for spec in specs {
+            ids.push(insert_app(&mut *tx, spec).await?);
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+        Ok(ids)
     }
 
-    /// Get app by ID
-    pub async fn get_by_id(&self, id: u32) -> Result<Option<AppSpec>> {
+    async fn get_by_id(&self, id: u32) -> Result<Option<AppSpec>> {
         let row = sqlx::query(
             r#"
-            SELECT id, name, mode, command, args, cwd, env, watch, ignore_patterns,
+            SELECT id, name, namespace, mode, command, args, cwd, env, watch, ignore_patterns,
                    auto_restart, max_restarts, restart_delay_ms, crash_window_secs,
-                   kill_timeout_ms, created_at
+                   kill_timeout_ms, created_at, tags, max_memory_mb, max_uptime_secs, port,
+                   health_check
             FROM apps WHERE id = ?
             "#,
         )
@@ -71,16 +65,17 @@ impl AppsRepository {
         }
     }
 
-    /// Get app by name
-    pub async fn get_by_name(&self, name: &str) -> Result<Option<AppSpec>> {
+    async fn get_by_name(&self, namespace: &str, name: &str) -> Result<Option<AppSpec>> {
         let row = sqlx::query(
             r#"
-            SELECT id, name, mode, command, args, cwd, env, watch, ignore_patterns,
+            SELECT id, name, namespace, mode, command, args, cwd, env, watch, ignore_patterns,
                    auto_restart, max_restarts, restart_delay_ms, crash_window_secs,
-                   kill_timeout_ms, created_at
-            FROM apps WHERE name = ?
+                   kill_timeout_ms, created_at, tags, max_memory_mb, max_uptime_secs, port,
+                   health_check
+            FROM apps WHERE namespace = ? AND name = ?
             "#,
         )
+        .bind(namespace)
         .bind(name)
         .fetch_optional(&self.pool)
         .await
@@ -92,13 +87,31 @@ impl AppsRepository {
         }
     }
 
-    /// Get all apps
-    pub async fn get_all(&self) -> Result<Vec<AppSpec>> {
+    async fn find_by_name(&self, name: &str) -> Result<Vec<AppSpec>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, namespace, mode, command, args, cwd, env, watch, ignore_patterns,
+                   auto_restart, max_restarts, restart_delay_ms, crash_window_secs,
+                   kill_timeout_ms, created_at, tags, max_memory_mb, max_uptime_secs, port,
+                   health_check
+            FROM apps WHERE name = ? ORDER BY id
+            "#,
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+
+        rows.iter().map(row_to_app_spec).collect()
+    }
+
+    async fn get_all(&self) -> Result<Vec<AppSpec>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, name, mode, command, args, cwd, env, watch, ignore_patterns,
+            SELECT id, name, namespace, mode, command, args, cwd, env, watch, ignore_patterns,
                    auto_restart, max_restarts, restart_delay_ms, crash_window_secs,
-                   kill_timeout_ms, created_at
+                   kill_timeout_ms, created_at, tags, max_memory_mb, max_uptime_secs, port,
+                   health_check
             FROM apps ORDER BY id
             "#,
         )
@@ -109,8 +122,81 @@ impl AppsRepository {
         rows.iter().map(row_to_app_spec).collect()
     }
 
-    /// Delete app by ID
-    pub async fn delete(&self, id: u32) -> Result<bool> {
+    async fn update(&self, spec: &AppSpec) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE apps SET
+                name = ?, namespace = ?, mode = ?, command = ?, args = ?, cwd = ?, env = ?,
+                watch = ?, ignore_patterns = ?, auto_restart = ?, max_restarts = ?,
+                restart_delay_ms = ?, crash_window_secs = ?, kill_timeout_ms = ?, tags = ?,
+                max_memory_mb = ?, max_uptime_secs = ?, port = ?, health_check = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&spec.name)
+        .bind(&spec.namespace)
+        .bind(spec.mode.as_str())
+        .bind(&spec.command)
+        .bind(serde_json::to_string(&spec.args)?)
+        .bind(spec.cwd.to_string_lossy().to_string())
+        .bind(serde_json::to_string(&spec.env)?)
+        .bind(spec.watch)
+        .bind(serde_json::to_string(&spec.ignore_patterns)?)
+        .bind(spec.restart_policy.auto_restart)
+        .bind(spec.restart_policy.max_restarts as i64)
+        .bind(spec.restart_policy.restart_delay_ms as i64)
+        .bind(spec.restart_policy.crash_window_secs as i64)
+        .bind(spec.kill_timeout_ms as i64)
+        .bind(serde_json::to_string(&spec.tags)?)
+        .bind(spec.max_memory_mb.map(|mb| mb as i64))
+        .bind(spec.max_uptime_secs.map(|secs| secs as i64))
+        .bind(spec.port.map(i64::from))
+        .bind(health_check_json(spec)?)
+        .bind(spec.id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn update_env(&self, id: u32, env: &HashMap<String, String>) -> Result<bool> {
+        let env_json = serde_json::to_string(env)?;
+        let result = sqlx::query("UPDATE apps SET env = ? WHERE id = ?")
+            .bind(&env_json)
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_desired_state(&self, id: u32, state: DesiredState) -> Result<bool> {
+        let result =
+            sqlx::query("UPDATE apps SET desired_state = ? WHERE id = ? AND desired_state != ?")
+                .bind(state.as_str())
+                .bind(id as i64)
+                .bind(state.as_str())
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Error::DbError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn desired_states(&self) -> Result<HashMap<u32, DesiredState>> {
+        let rows: Vec<(i64, String)> = sqlx::query_as("SELECT id, desired_state FROM apps")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(id, state)| Ok((id as u32, state.parse()?)))
+            .collect()
+    }
+
+    async fn delete(&self, id: u32) -> Result<bool> {
         let result = sqlx::query("DELETE FROM apps WHERE id = ?")
             .bind(id as i64)
             .execute(&self.pool)
@@ -120,9 +206,37 @@ impl AppsRepository {
         Ok(result.rows_affected() > 0)
     }
 
-    /// Delete app by name
-    pub async fn delete_by_name(&self, name: &str) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM apps WHERE name = ?")
+    async fn delete_with_history(&self, id: u32) -> Result<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        // Explicit rather than relying on ON DELETE CASCADE, which only
+        // applies while foreign keys are enforced
+        for table in ["metrics", "runs", "events", "app_stats"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE app_id = ?", table))
+                .bind(id as i64)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| Error::DbError(e.to_string()))?;
+        }
+        let result = sqlx::query("DELETE FROM apps WHERE id = ?")
+            .bind(id as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_by_name(&self, namespace: &str, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM apps WHERE namespace = ? AND name = ?")
+            .bind(namespace)
             .bind(name)
             .execute(&self.pool)
             .await
@@ -131,8 +245,7 @@ impl AppsRepository {
         Ok(result.rows_affected() > 0)
     }
 
-    /// Delete all apps
-    pub async fn delete_all(&self) -> Result<u64> {
+    async fn delete_all(&self) -> Result<u64> {
         let result = sqlx::query("DELETE FROM apps")
             .execute(&self.pool)
             .await
@@ -141,19 +254,19 @@ impl AppsRepository {
         Ok(result.rows_affected())
     }
 
-    /// Check if app exists by name
-    pub async fn exists(&self, name: &str) -> Result<bool> {
-        let row: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM apps WHERE name = ?")
-            .bind(name)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| Error::DbError(e.to_string()))?;
+    async fn exists(&self, namespace: &str, name: &str) -> Result<bool> {
+        let row: (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM apps WHERE namespace = ? AND name = ?")
+                .bind(namespace)
+                .bind(name)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| Error::DbError(e.to_string()))?;
 
         Ok(row.0 > 0)
     }
 
-    /// Get next available ID
-    pub async fn next_id(&self) -> Result<u32> {
+    async fn next_id(&self) -> Result<u32> {
         let row: (i64,) = sqlx::query_as("SELECT COALESCE(MAX(id), 0) + 1 FROM apps")
             .fetch_one(&self.pool)
             .await
@@ -163,9 +276,72 @@ impl AppsRepository {
     }
 }
 
-fn row_to_app_spec(row: &sqlx::sqlite::SqliteRow) -> Result<AppSpec> {
+async fn insert_app<'e, E>(executor: E, spec: &AppSpec) -> Result<u32>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
+    let args_json = serde_json::to_string(&spec.args)?;
+    let env_json = serde_json::to_string(&spec.env)?;
+    let ignore_json = serde_json::to_string(&spec.ignore_patterns)?;
+    let tags_json = serde_json::to_string(&spec.tags)?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO apps (
+            name, namespace, mode, command, args, cwd, env, watch, ignore_patterns,
+            auto_restart, max_restarts, restart_delay_ms, crash_window_secs, kill_timeout_ms, tags,
+            max_memory_mb, max_uptime_secs, port, health_check
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&spec.name)
+    .bind(&spec.namespace)
+    .bind(spec.mode.as_str())
+    .bind(&spec.command)
+    .bind(&args_json)
+    .bind(spec.cwd.to_string_lossy().to_string())
+    .bind(&env_json)
+    .bind(spec.watch)
+    .bind(&ignore_json)
+    .bind(spec.restart_policy.auto_restart)
+    .bind(spec.restart_policy.max_restarts as i64)
+    .bind(spec.restart_policy.restart_delay_ms as i64)
+    .bind(spec.restart_policy.crash_window_secs as i64)
+    .bind(spec.kill_timeout_ms as i64)
+    .bind(&tags_json)
+    .bind(spec.max_memory_mb.map(|mb| mb as i64))
+    .bind(spec.max_uptime_secs.map(|secs| secs as i64))
+    .bind(spec.port.map(i64::from))
+    .bind(health_check_json(spec)?)
+    .execute(executor)
+    .await
+    .map_err(|e| Error::DbError(e.to_string()))?;
+
+    Ok(result.last_insert_rowid() as u32)
+}
+
+/// The health check as stored in the `health_check` column
+pub(crate) fn health_check_json(spec: &AppSpec) -> Result<Option<String>> {
+    Ok(spec
+        .health_check
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?)
+}
+
+pub(crate) fn row_to_app_spec<'r, R>(row: &'r R) -> Result<AppSpec>
+where
+    R: Row,
+    for<'a> &'a str: sqlx::ColumnIndex<R>,
+    i64: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    bool: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    String: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    Option<i64>: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    Option<String>: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
     let id: i64 = row.get("id");
     let name: String = row.get("name");
+    let namespace: String = row.get("namespace");
     let mode_str: String = row.get("mode");
     let command: String = row.get("command");
     let args_json: String = row.get("args");
@@ -179,11 +355,20 @@ fn row_to_app_spec(row: &sqlx::sqlite::SqliteRow) -> Result<AppSpec> {
     let crash_window_secs: i64 = row.get("crash_window_secs");
     let kill_timeout_ms: i64 = row.get("kill_timeout_ms");
     let created_at_str: String = row.get("created_at");
+    let tags_json: String = row.get("tags");
+    let max_memory_mb: Option<i64> = row.get("max_memory_mb");
+    let max_uptime_secs: Option<i64> = row.get("max_uptime_secs");
+    let port: Option<i64> = row.get("port");
+    let health_check_json: Option<String> = row.get("health_check");
 
     let mode: AppMode = mode_str.parse()?;
     let args: Vec<String> = serde_json::from_str(&args_json)?;
     let env: HashMap<String, String> = serde_json::from_str(&env_json)?;
     let ignore_patterns: Vec<String> = serde_json::from_str(&ignore_json)?;
+    let tags: Vec<String> = serde_json::from_str(&tags_json)?;
+    let health_check = health_check_json
+        .map(|json| serde_json::from_str(&json))
+        .transpose()?;
     let created_at = chrono::DateTime::parse_from_rfc3339(&created_at_str)
         .map(|dt| dt.with_timezone(&chrono::Utc))
         .unwrap_or_else(|_| chrono::Utc::now());
@@ -191,13 +376,30 @@ fn row_to_app_spec(row: &sqlx::sqlite::SqliteRow) -> Result<AppSpec> {
     Ok(AppSpec {
         id: id as u32,
         name,
+        namespace,
         mode,
         command,
         args,
         cwd: PathBuf::from(cwd_str),
+        // Workspace package and cargo build options (defaults - not persisted in DB yet)
+        workspace: None,
+        cargo_features: Vec::new(),
+        cargo_profile: None,
+        cargo_target: None,
         env,
+        // Provenance is not persisted
+        env_sources: BTreeMap::new(),
+        // Env file watching (defaults - not persisted in DB yet)
+        env_file: None,
+        watch_env_file: None,
         watch,
         ignore_patterns,
+        // Watch filters (defaults - not persisted in DB yet)
+        watch_paths: Vec::new(),
+        watch_extensions: Vec::new(),
+        watch_poll_ms: None,
+        reload_on_change: false,
+        build_timeout_secs: None,
         restart_policy: RestartPolicy {
             auto_restart,
             max_restarts: max_restarts as u32,
@@ -209,23 +411,36 @@ fn row_to_app_spec(row: &sqlx::sqlite::SqliteRow) -> Result<AppSpec> {
         // Clustering fields (defaults - not persisted in DB yet)
         instances: 1,
         instance_id: None,
-        // Port management fields
-        port: None,
+        // Port management fields (only the fixed port is persisted)
+        port: port.map(|port| port as u16),
         port_range: None,
-        // Health check field
-        health_check: None,
+        on_port_conflict: Default::default(),
+        auto_port: false,
+        // Health check field (readiness checks and warm-ups are not persisted)
+        health_check,
+        readiness_check: None,
+        warmup: None,
+        wait_for: Vec::new(),
+        wait_for_timeout_secs: None,
         // Memory limit field
-        max_memory_mb: None,
+        max_memory_mb: max_memory_mb.map(|mb| mb as u64),
         // Startup delay (defaults - not persisted in DB yet)
         startup_delay_ms: None,
         // Environment inheritance (defaults - not persisted in DB yet)
         env_inherit: false,
         // Event hooks (defaults - not persisted in DB yet)
         hooks: oxidepm_core::Hooks::default(),
-        // Process tags (defaults - not persisted in DB yet)
-        tags: Vec::new(),
-        // Maximum uptime (defaults - not persisted in DB yet)
-        max_uptime_secs: None,
+        hook_user: None,
+        tags,
+        // Dependencies and start band (defaults - not persisted in DB yet)
+        depends_on: Vec::new(),
+        priority: 0,
+        // Maximum uptime
+        max_uptime_secs: max_uptime_secs.map(|secs| secs as u64),
+        sandbox: None,
+        limits: Default::default(),
+        logs: Default::default(),
+        triggers: Vec::new(),
     })
 }
 
@@ -248,12 +463,13 @@ mod tests {
         let (db, _dir) = setup_db().await;
         let apps = db.apps();
 
-        let spec = AppSpec::new(
+        let mut spec = AppSpec::new(
             "test-app".to_string(),
             AppMode::Node,
             "server.js".to_string(),
             PathBuf::from("/app"),
         );
+        spec.tags = vec!["web".to_string()];
 
         let id = apps.insert(&spec).await.unwrap();
         assert!(id > 0);
@@ -263,6 +479,7 @@ mod tests {
         let retrieved = retrieved.unwrap();
         assert_eq!(retrieved.name, "test-app");
         assert_eq!(retrieved.mode, AppMode::Node);
+        assert_eq!(retrieved.tags, vec!["web"]);
     }
 
     #[tokio::test]
@@ -279,14 +496,125 @@ mod tests {
 
         apps.insert(&spec).await.unwrap();
 
-        let retrieved = apps.get_by_name("my-app").await.unwrap();
+        let retrieved = apps.get_by_name("default", "my-app").await.unwrap();
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().mode, AppMode::Cargo);
 
-        let not_found = apps.get_by_name("nonexistent").await.unwrap();
+        let not_found = apps.get_by_name("default", "nonexistent").await.unwrap();
         assert!(not_found.is_none());
     }
 
+    #[tokio::test]
+    async fn test_same_name_in_namespaces() {
+        let (db, _dir) = setup_db().await;
+        let apps = db.apps();
+
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/project"),
+        );
+        apps.insert(&spec.clone().with_namespace("shop"))
+            .await
+            .unwrap();
+        apps.insert(&spec.clone().with_namespace("blog"))
+            .await
+            .unwrap();
+        assert!(apps
+            .insert(&spec.clone().with_namespace("shop"))
+            .await
+            .is_err());
+
+        let shop = apps.get_by_name("shop", "api").await.unwrap().unwrap();
+        assert_eq!(shop.namespace, "shop");
+        assert_eq!(apps.find_by_name("api").await.unwrap().len(), 2);
+        assert!(!apps.exists("default", "api").await.unwrap());
+
+        assert!(apps.delete_by_name("blog", "api").await.unwrap());
+        assert_eq!(apps.find_by_name("api").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_env() {
+        let (db, _dir) = setup_db().await;
+        let apps = db.apps();
+
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/project"),
+        );
+        let id = apps.insert(&spec).await.unwrap();
+
+        let env = HashMap::from([("LOG_LEVEL".to_string(), "debug".to_string())]);
+        assert!(apps.update_env(id, &env).await.unwrap());
+        assert_eq!(apps.get_by_id(id).await.unwrap().unwrap().env, env);
+        assert!(!apps.update_env(id + 1, &env).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let (db, _dir) = setup_db().await;
+        let apps = db.apps();
+
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/project"),
+        );
+        spec.id = apps.insert(&spec).await.unwrap();
+
+        spec.name = "gateway".to_string();
+        spec.watch = true;
+        spec.restart_policy.max_restarts = 3;
+        spec.tags = vec!["web".to_string()];
+        assert!(apps.update(&spec).await.unwrap());
+
+        let stored = apps.get_by_id(spec.id).await.unwrap().unwrap();
+        assert_eq!(stored.name, "gateway");
+        assert!(stored.watch);
+        assert_eq!(stored.restart_policy.max_restarts, 3);
+        assert_eq!(stored.tags, vec!["web".to_string()]);
+        assert!(apps.get_by_name("default", "api").await.unwrap().is_none());
+
+        spec.id += 1;
+        assert!(!apps.update(&spec).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_desired_state() {
+        let (db, _dir) = setup_db().await;
+        let apps = db.apps();
+
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "server.js".to_string(),
+            PathBuf::from("/project"),
+        );
+        let id = apps.insert(&spec).await.unwrap();
+        assert_eq!(
+            apps.desired_states().await.unwrap()[&id],
+            DesiredState::Running
+        );
+
+        assert!(apps
+            .set_desired_state(id, DesiredState::Stopped)
+            .await
+            .unwrap());
+        assert!(!apps
+            .set_desired_state(id, DesiredState::Stopped)
+            .await
+            .unwrap());
+        assert_eq!(
+            apps.desired_states().await.unwrap()[&id],
+            DesiredState::Stopped
+        );
+    }
+
     #[tokio::test]
     async fn test_delete() {
         let (db, _dir) = setup_db().await;
@@ -304,6 +632,40 @@ mod tests {
         assert!(apps.get_by_id(id).await.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn test_delete_with_history() {
+        let (db, _dir) = setup_db().await;
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "app.js".to_string(),
+            PathBuf::from("/"),
+        );
+        let id = db.apps().insert(&spec).await.unwrap();
+        let other = db
+            .apps()
+            .insert(&AppSpec {
+                name: "web".to_string(),
+                ..spec
+            })
+            .await
+            .unwrap();
+        let metrics = db.metrics();
+        for app_id in [id, other] {
+            db.runs()
+                .insert(app_id, &oxidepm_core::RunState::running(app_id, 100))
+                .await
+                .unwrap();
+            metrics.insert(app_id, 1.0, 1024).await.unwrap();
+        }
+
+        assert!(db.apps().delete_with_history(id).await.unwrap());
+        assert!(db.runs().get_latest(id).await.unwrap().is_none());
+        assert!(metrics.get_latest(id).await.unwrap().is_none());
+        assert!(db.runs().get_latest(other).await.unwrap().is_some());
+        assert!(!db.apps().delete_with_history(id).await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_get_all() {
         let (db, _dir) = setup_db().await;
@@ -322,4 +684,39 @@ mod tests {
         let all = apps.get_all().await.unwrap();
         assert_eq!(all.len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_insert_many() {
+        let (db, _dir) = setup_db().await;
+        let apps = db.apps();
+
+        let specs: Vec<AppSpec> = (0..5)
+            .map(|i| {
+                AppSpec::new(
+                    format!("batch-{}", i),
+                    AppMode::Cmd,
+                    "true".to_string(),
+                    PathBuf::from("/"),
+                )
+            })
+            .collect();
+
+        let ids = apps.insert_many(&specs).await.unwrap();
+        assert_eq!(ids.len(), 5);
+        assert_eq!(
+            apps.get_by_id(ids[3]).await.unwrap().unwrap().name,
+            "batch-3"
+        );
+
+        // A duplicate name rolls back the whole batch
+        let fresh = AppSpec::new(
+            "batch-new".to_string(),
+            AppMode::Cmd,
+            "true".to_string(),
+            PathBuf::from("/"),
+        );
+        let dupes = vec![fresh, specs[0].clone()];
+        assert!(apps.insert_many(&dupes).await.is_err());
+        assert_eq!(apps.get_all().await.unwrap().len(), 5);
+    }
 }
@@ -0,0 +1,131 @@
+//! Audit repository - who sent which control request, and how it went
+
+use async_trait::async_trait;
+use oxidepm_core::{AuditEntry, Error, Result};
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+
+use crate::events::{format_timestamp, parse_timestamp};
+use crate::storage::AuditStore;
+
+/// Repository for the audit log
+#[derive(Clone)]
+pub struct AuditRepository {
+    pool: SqlitePool,
+}
+
+impl AuditRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditStore for AuditRepository {
+    async fn insert(&self, entry: &AuditEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit (timestamp, actor, client, action, target, success, result)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(format_timestamp(&entry.timestamp))
+        .bind(&entry.actor)
+        .bind(&entry.client)
+        .bind(&entry.action)
+        .bind(&entry.target)
+        .bind(entry.success)
+        .bind(&entry.result)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT timestamp, actor, client, action, target, success, result
+            FROM audit
+            ORDER BY id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+
+        let mut entries = rows
+            .iter()
+            .map(row_to_audit_entry)
+            .collect::<Result<Vec<_>>>()?;
+        entries.reverse();
+        Ok(entries)
+    }
+}
+
+pub(crate) fn row_to_audit_entry<'r, R>(row: &'r R) -> Result<AuditEntry>
+where
+    R: Row,
+    for<'a> &'a str: sqlx::ColumnIndex<R>,
+    bool: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    String: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
+    let timestamp: String = row.get("timestamp");
+    Ok(AuditEntry {
+        timestamp: parse_timestamp(&timestamp)?,
+        actor: row.get("actor"),
+        client: row.get("client"),
+        action: row.get("action"),
+        target: row.get("target"),
+        success: row.get("success"),
+        result: row.get("result"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use chrono::Utc;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_audit_log() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).await.unwrap();
+        let audit = db.audit();
+
+        let entry = |action: &str, success| AuditEntry {
+            timestamp: Utc::now(),
+            actor: "uid 1000 (alice)".to_string(),
+            client: Some("web".to_string()),
+            action: action.to_string(),
+            target: Some("api".to_string()),
+            success,
+            result: "ok".to_string(),
+        };
+        audit.insert(&entry("start", true)).await.unwrap();
+        audit.insert(&entry("restart", false)).await.unwrap();
+        audit
+            .insert(&AuditEntry {
+                client: None,
+                target: None,
+                ..entry("save", true)
+            })
+            .await
+            .unwrap();
+
+        let all = audit.recent(10).await.unwrap();
+        let actions: Vec<&str> = all.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, vec!["start", "restart", "save"]);
+        assert!(!all[1].success);
+        assert_eq!(all[0].client.as_deref(), Some("web"));
+        assert_eq!(all[2].target, None);
+
+        let last = audit.recent(1).await.unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].action, "save");
+    }
+}
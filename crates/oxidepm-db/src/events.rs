@@ -0,0 +1,189 @@
+//! Events repository - supervisor audit trail
+
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use oxidepm_core::{constants, AppEvent, Error, Result};
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+
+use crate::storage::{EventStore, StatsStore};
+use crate::StatsRepository;
+
+/// Repository for supervisor events
+#[derive(Clone)]
+pub struct EventsRepository {
+    pool: SqlitePool,
+}
+
+impl EventsRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EventStore for EventsRepository {
+    async fn insert(&self, event: &AppEvent) -> Result<u32> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO events (app_id, name, kind, reason, exit_code, timestamp)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(event.app_id as i64)
+        .bind(&event.name)
+        .bind(event.kind.as_str())
+        .bind(&event.reason)
+        .bind(event.exit_code)
+        .bind(format_timestamp(&event.timestamp))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM events
+            WHERE app_id = ? AND id <= (
+                SELECT id FROM events WHERE app_id = ? ORDER BY id DESC LIMIT 1 OFFSET ?
+            )
+            "#,
+        )
+        .bind(event.app_id as i64)
+        .bind(event.app_id as i64)
+        .bind(constants::EVENTS_RETENTION as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+
+        StatsRepository::new(self.pool.clone())
+            .record(event)
+            .await?;
+
+        Ok(result.last_insert_rowid() as u32)
+    }
+
+    async fn get_by_apps(&self, app_ids: &[u32], limit: usize) -> Result<Vec<AppEvent>> {
+        if app_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; app_ids.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT app_id, name, kind, reason, exit_code, timestamp
+            FROM events
+            WHERE app_id IN ({})
+            ORDER BY timestamp DESC, id DESC
+            LIMIT ?
+            "#,
+            placeholders
+        );
+        let mut query = sqlx::query(&sql);
+        for id in app_ids {
+            query = query.bind(*id as i64);
+        }
+        let rows = query
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        let mut events = rows.iter().map(row_to_event).collect::<Result<Vec<_>>>()?;
+        events.reverse();
+        Ok(events)
+    }
+}
+
+pub(crate) fn row_to_event<'r, R>(row: &'r R) -> Result<AppEvent>
+where
+    R: Row,
+    for<'a> &'a str: sqlx::ColumnIndex<R>,
+    i64: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    i32: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    String: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
+    let app_id: i64 = row.get("app_id");
+    let kind: String = row.get("kind");
+    let timestamp: String = row.get("timestamp");
+
+    Ok(AppEvent {
+        app_id: app_id as u32,
+        name: row.get("name"),
+        kind: kind.parse()?,
+        timestamp: parse_timestamp(&timestamp)?,
+        reason: row.get("reason"),
+        exit_code: row.get("exit_code"),
+    })
+}
+
+/// Fixed-width UTC timestamps sort correctly as text
+pub(crate) fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+pub(crate) fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| Error::DbError(format!("Invalid timestamp '{}': {}", timestamp, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use oxidepm_core::{AppMode, AppSpec, EventKind};
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_insert_and_query_events() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).await.unwrap();
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "app.js".to_string(),
+            PathBuf::from("/"),
+        );
+        let api = db.apps().insert(&spec).await.unwrap();
+        let worker = db
+            .apps()
+            .insert(&AppSpec {
+                name: "worker".to_string(),
+                ..spec
+            })
+            .await
+            .unwrap();
+
+        let events = db.events();
+        events
+            .insert(&AppEvent::new(api, "api", EventKind::Started))
+            .await
+            .unwrap();
+        events
+            .insert(&AppEvent::new(worker, "worker", EventKind::Crashed).with_exit_code(Some(1)))
+            .await
+            .unwrap();
+        events
+            .insert(&AppEvent::new(api, "api", EventKind::Restarted).with_reason("health"))
+            .await
+            .unwrap();
+
+        let all = events.get_by_apps(&[api, worker], 10).await.unwrap();
+        let kinds: Vec<EventKind> = all.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![EventKind::Started, EventKind::Crashed, EventKind::Restarted]
+        );
+        assert_eq!(all[1].exit_code, Some(1));
+        assert_eq!(all[2].reason.as_deref(), Some("health"));
+
+        let latest = events.get_by_apps(&[api], 1).await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].kind, EventKind::Restarted);
+
+        // Events go away with their app
+        db.apps().delete(api).await.unwrap();
+        assert!(events.get_by_apps(&[api], 10).await.unwrap().is_empty());
+    }
+}
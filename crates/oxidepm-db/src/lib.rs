@@ -1,77 +1,161 @@
-//! OxidePM Database - SQLite persistence layer
+//! OxidePM Database - persistence layer over SQLite (default), Postgres or memory
 
 pub mod apps;
+pub mod audit;
+pub mod events;
+pub mod memory;
 pub mod metrics;
+pub mod postgres;
 pub mod runs;
 pub mod schema;
+pub mod sqlite;
+pub mod stats;
+pub mod storage;
 
-use oxidepm_core::{Error, Result};
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use chrono::{DateTime, Utc};
+use oxidepm_core::{DbHealth, Error, Result};
 use std::path::Path;
-use tracing::info;
+use std::sync::Arc;
 
 pub use apps::AppsRepository;
+pub use audit::AuditRepository;
+pub use events::EventsRepository;
+pub use memory::MemoryStorage;
+pub use postgres::PostgresStorage;
 pub use runs::RunsRepository;
+pub use sqlite::{check_writable, SqliteStorage};
+pub use stats::StatsRepository;
+pub use storage::{AppStore, AuditStore, EventStore, MetricsStore, RunStore, StatsStore, Storage};
 
-/// Database connection and operations
+/// Database connection and operations, on whichever backend was opened
+#[derive(Clone)]
 pub struct Database {
-    pool: SqlitePool,
+    storage: Arc<dyn Storage>,
 }
 
 impl Database {
-    /// Create a new database connection
+    /// Open the SQLite database at `path`
     pub async fn new(path: &Path) -> Result<Self> {
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| Error::DbError(e.to_string()))?;
-        }
-
-        let url = format!("sqlite:{}?mode=rwc", path.display());
-        info!("Connecting to database: {}", url);
-
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect(&url)
-            .await
-            .map_err(|e| Error::DbError(e.to_string()))?;
+        Ok(Self::from_storage(Arc::new(
+            SqliteStorage::open(path).await?,
+        )))
+    }
 
-        // Set database file permissions to owner-only (0600) for security
-        #[cfg(unix)]
+    /// Connect to the database at `url`: `postgres://...` or `sqlite:<path>`.
+    /// A Postgres database may be shared by several hosts; each daemon only
+    /// sees the apps stored under its `host`.
+    pub async fn connect(url: &str, host: &str) -> Result<Self> {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            return Ok(Self::from_storage(Arc::new(
+                PostgresStorage::connect(url, host).await?,
+            )));
+        }
+        match url
+            .strip_prefix("sqlite://")
+            .or_else(|| url.strip_prefix("sqlite:"))
         {
-            use std::os::unix::fs::PermissionsExt;
-            if let Err(e) = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)) {
-                tracing::warn!("Failed to set database file permissions: {}", e);
-            }
+            Some(path) => Self::new(Path::new(path)).await,
+            None => Err(Error::ConfigError(format!(
+                "Unsupported database URL '{}': expected postgres://... or sqlite:<path>",
+                redact_url(url)
+            ))),
         }
+    }
 
-        // Initialize schema
-        sqlx::query(schema::SCHEMA)
-            .execute(&pool)
-            .await
-            .map_err(|e| Error::DbError(e.to_string()))?;
+    /// Keep everything in memory; nothing is written to disk
+    pub fn memory() -> Self {
+        Self::from_storage(Arc::new(MemoryStorage::new()))
+    }
 
-        info!("Database initialized");
-        Ok(Self { pool })
+    /// Use an already opened storage backend
+    pub fn from_storage(storage: Arc<dyn Storage>) -> Self {
+        Self { storage }
     }
 
-    /// Get the connection pool
-    pub fn pool(&self) -> &SqlitePool {
-        &self.pool
+    /// Backend name, e.g. "sqlite"
+    pub fn backend(&self) -> &'static str {
+        self.storage.backend()
     }
 
     /// Get apps repository
-    pub fn apps(&self) -> AppsRepository {
-        AppsRepository::new(self.pool.clone())
+    pub fn apps(&self) -> Arc<dyn AppStore> {
+        self.storage.apps()
+    }
+
+    /// Get events repository
+    pub fn events(&self) -> Arc<dyn EventStore> {
+        self.storage.events()
     }
 
     /// Get runs repository
-    pub fn runs(&self) -> RunsRepository {
-        RunsRepository::new(self.pool.clone())
+    pub fn runs(&self) -> Arc<dyn RunStore> {
+        self.storage.runs()
+    }
+
+    /// Get stats repository
+    pub fn stats(&self) -> Arc<dyn StatsStore> {
+        self.storage.stats()
+    }
+
+    /// Get metrics repository
+    pub fn metrics(&self) -> Arc<dyn MetricsStore> {
+        self.storage.metrics()
+    }
+
+    /// Get audit log repository
+    pub fn audit(&self) -> Arc<dyn AuditStore> {
+        self.storage.audit()
+    }
+
+    /// Refresh the query planner statistics
+    pub async fn optimize(&self) -> Result<()> {
+        self.storage.optimize().await
+    }
+
+    /// Give unused space back and record when this happened
+    pub async fn vacuum(&self) -> Result<()> {
+        self.storage.vacuum().await
+    }
+
+    /// When the database was last vacuumed, if ever
+    pub async fn last_vacuum(&self) -> Result<Option<DateTime<Utc>>> {
+        self.storage.last_vacuum().await
+    }
+
+    /// Size and maintenance state of the database
+    pub async fn health(&self) -> Result<DbHealth> {
+        self.storage.health().await
     }
 
     /// Close the database connection
     pub async fn close(&self) {
-        self.pool.close().await;
+        self.storage.close().await;
+    }
+}
+
+/// Current time in the format of SQLite's CURRENT_TIMESTAMP
+pub(crate) fn sql_now() -> String {
+    Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// `url` without its password or query parameters, for logs and status output
+pub fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let rest = rest.split('?').next().unwrap_or_default();
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let authority = match authority.rsplit_once('@') {
+        Some((userinfo, host)) => {
+            let user = userinfo.split(':').next().unwrap_or_default();
+            format!("{}@{}", user, host)
+        }
+        None => authority.to_string(),
+    };
+    if path.is_empty() {
+        format!("{}://{}", scheme, authority)
+    } else {
+        format!("{}://{}/{}", scheme, authority, path)
     }
 }
 
@@ -87,6 +171,32 @@ mod tests {
 
         let db = Database::new(&db_path).await.unwrap();
         assert!(db_path.exists());
+        assert_eq!(db.backend(), "sqlite");
+        db.close().await;
+
+        let url = format!("sqlite:{}", dir.path().join("other.db").display());
+        let db = Database::connect(&url, "web1").await.unwrap();
+        assert_eq!(db.backend(), "sqlite");
         db.close().await;
+
+        let err = Database::connect("mysql://root:secret@db/app", "web1")
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("mysql://root@db/app"), "{}", err);
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("postgres://opm:hunter2@db:5432/oxidepm"),
+            "postgres://opm@db:5432/oxidepm"
+        );
+        assert_eq!(
+            redact_url("postgres://db/oxidepm?user=opm&password=hunter2"),
+            "postgres://db/oxidepm"
+        );
+        assert_eq!(redact_url("postgresql://db"), "postgresql://db");
+        assert_eq!(redact_url("sqlite:/tmp/x.db"), "sqlite:/tmp/x.db");
     }
 }
@@ -0,0 +1,633 @@
+//! In-memory storage - nothing touches the disk and everything is gone when
+//! the daemon exits. Used by `oxidepmd --ephemeral` for CI jobs and containers.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use oxidepm_core::{
+    constants, AppEvent, AppSpec, AppStats, AppStatus, AuditEntry, DbHealth, DesiredState, Error,
+    EventKind, Result, RunState,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::metrics::MetricsSnapshot;
+use crate::runs::RunRecord;
+use crate::sql_now;
+use crate::stats::StatsRow;
+use crate::storage::{
+    AppStore, AuditStore, EventStore, MetricsStore, RunStore, StatsStore, Storage,
+};
+
+#[derive(Default)]
+struct Tables {
+    apps: BTreeMap<u32, (AppSpec, DesiredState)>,
+    runs: BTreeMap<u32, RunRecord>,
+    events: BTreeMap<u32, AppEvent>,
+    stats: HashMap<u32, StatsRow>,
+    metrics: BTreeMap<u32, (u32, MetricsSnapshot)>,
+    audit: Vec<AuditEntry>,
+    /// Last ID handed out per table; like AUTOINCREMENT, IDs are never reused
+    last_app_id: u32,
+    last_run_id: u32,
+    last_event_id: u32,
+    last_metric_id: u32,
+    last_vacuum: Option<DateTime<Utc>>,
+}
+
+impl Tables {
+    fn check_unique(&self, spec: &AppSpec) -> Result<()> {
+        let taken = self
+            .apps
+            .values()
+            .any(|(app, _)| app.namespace == spec.namespace && app.name == spec.name);
+        if taken {
+            return Err(Error::AppAlreadyExists(spec.name.clone()));
+        }
+        Ok(())
+    }
+
+    fn insert_app(&mut self, spec: &AppSpec) -> u32 {
+        self.last_app_id += 1;
+        let id = self.last_app_id;
+        let mut spec = spec.clone();
+        spec.id = id;
+        self.apps.insert(id, (spec, DesiredState::Running));
+        id
+    }
+
+    fn remove_history(&mut self, app_id: u32) {
+        self.runs.retain(|_, run| run.app_id != app_id);
+        self.events.retain(|_, event| event.app_id != app_id);
+        self.stats.remove(&app_id);
+        self.metrics.retain(|_, (id, _)| *id != app_id);
+    }
+}
+
+/// Storage held in the daemon's memory
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    tables: Arc<Mutex<Tables>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tables> {
+        // A panic while holding the lock leaves the tables usable
+        self.tables
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+
+    fn apps(&self) -> Arc<dyn AppStore> {
+        Arc::new(self.clone())
+    }
+
+    fn runs(&self) -> Arc<dyn RunStore> {
+        Arc::new(self.clone())
+    }
+
+    fn events(&self) -> Arc<dyn EventStore> {
+        Arc::new(self.clone())
+    }
+
+    fn stats(&self) -> Arc<dyn StatsStore> {
+        Arc::new(self.clone())
+    }
+
+    fn metrics(&self) -> Arc<dyn MetricsStore> {
+        Arc::new(self.clone())
+    }
+
+    fn audit(&self) -> Arc<dyn AuditStore> {
+        Arc::new(self.clone())
+    }
+
+    async fn optimize(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn vacuum(&self) -> Result<()> {
+        self.lock().last_vacuum = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn last_vacuum(&self) -> Result<Option<DateTime<Utc>>> {
+        Ok(self.lock().last_vacuum)
+    }
+
+    async fn health(&self) -> Result<DbHealth> {
+        Ok(DbHealth {
+            backend: self.backend().to_string(),
+            location: "in memory".to_string(),
+            size_bytes: 0,
+            wal_bytes: 0,
+            free_bytes: 0,
+            journal_mode: None,
+            last_vacuum: self.lock().last_vacuum,
+        })
+    }
+
+    async fn close(&self) {}
+}
+
+#[async_trait]
+impl AppStore for MemoryStorage {
+    async fn insert(&self, spec: &AppSpec) -> Result<u32> {
+        let mut tables = self.lock();
+        tables.check_unique(spec)?;
+        Ok(tables.insert_app(spec))
+    }
+
+    async fn insert_many(&self, specs: &[AppSpec]) -> Result<Vec<u32>> {
+        let mut tables = self.lock();
+        // All or nothing, like the SQL backends' transaction
+        for (i, spec) in specs.iter().enumerate() {
+            tables.check_unique(spec)?;
+            if specs[..i]
+                .iter()
+                .any(|other| other.namespace == spec.namespace && other.name == spec.name)
+            {
+                return Err(Error::AppAlreadyExists(spec.name.clone()));
+            }
+        }
+        Ok(specs.iter().map(|spec| tables.insert_app(spec)).collect())
+    }
+
+    async fn get_by_id(&self, id: u32) -> Result<Option<AppSpec>> {
+        Ok(self.lock().apps.get(&id).map(|(spec, _)| spec.clone()))
+    }
+
+    async fn get_by_name(&self, namespace: &str, name: &str) -> Result<Option<AppSpec>> {
+        Ok(self
+            .lock()
+            .apps
+            .values()
+            .find(|(spec, _)| spec.namespace == namespace && spec.name == name)
+            .map(|(spec, _)| spec.clone()))
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Vec<AppSpec>> {
+        Ok(self
+            .lock()
+            .apps
+            .values()
+            .filter(|(spec, _)| spec.name == name)
+            .map(|(spec, _)| spec.clone())
+            .collect())
+    }
+
+    async fn get_all(&self) -> Result<Vec<AppSpec>> {
+        Ok(self
+            .lock()
+            .apps
+            .values()
+            .map(|(spec, _)| spec.clone())
+            .collect())
+    }
+
+    async fn update(&self, spec: &AppSpec) -> Result<bool> {
+        match self.lock().apps.get_mut(&spec.id) {
+            Some((stored, _)) => {
+                *stored = spec.clone();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn update_env(&self, id: u32, env: &HashMap<String, String>) -> Result<bool> {
+        match self.lock().apps.get_mut(&id) {
+            Some((spec, _)) => {
+                spec.env = env.clone();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn set_desired_state(&self, id: u32, state: DesiredState) -> Result<bool> {
+        match self.lock().apps.get_mut(&id) {
+            Some((_, desired)) if *desired != state => {
+                *desired = state;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn desired_states(&self) -> Result<HashMap<u32, DesiredState>> {
+        Ok(self
+            .lock()
+            .apps
+            .iter()
+            .map(|(id, (_, state))| (*id, *state))
+            .collect())
+    }
+
+    async fn delete(&self, id: u32) -> Result<bool> {
+        Ok(self.lock().apps.remove(&id).is_some())
+    }
+
+    async fn delete_with_history(&self, id: u32) -> Result<bool> {
+        let mut tables = self.lock();
+        tables.remove_history(id);
+        Ok(tables.apps.remove(&id).is_some())
+    }
+
+    async fn delete_by_name(&self, namespace: &str, name: &str) -> Result<bool> {
+        let mut tables = self.lock();
+        let before = tables.apps.len();
+        tables
+            .apps
+            .retain(|_, (spec, _)| !(spec.namespace == namespace && spec.name == name));
+        Ok(tables.apps.len() < before)
+    }
+
+    async fn delete_all(&self) -> Result<u64> {
+        let mut tables = self.lock();
+        let count = tables.apps.len() as u64;
+        tables.apps.clear();
+        Ok(count)
+    }
+
+    async fn exists(&self, namespace: &str, name: &str) -> Result<bool> {
+        Ok(self.get_by_name(namespace, name).await?.is_some())
+    }
+
+    async fn next_id(&self) -> Result<u32> {
+        Ok(self.lock().apps.keys().next_back().map_or(1, |id| id + 1))
+    }
+}
+
+#[async_trait]
+impl RunStore for MemoryStorage {
+    async fn insert(&self, app_id: u32, state: &RunState) -> Result<u32> {
+        let mut tables = self.lock();
+        tables.last_run_id += 1;
+        let id = tables.last_run_id;
+        tables.runs.insert(
+            id,
+            RunRecord {
+                id,
+                app_id,
+                pid: state.pid,
+                status: state.status,
+                restarts: state.restarts,
+                start_time: sql_now(),
+                stop_time: None,
+                exit_code: None,
+                build: state.build.clone(),
+            },
+        );
+        Ok(id)
+    }
+
+    async fn update_status(&self, run_id: u32, status: AppStatus) -> Result<()> {
+        if let Some(run) = self.lock().runs.get_mut(&run_id) {
+            run.status = status;
+        }
+        Ok(())
+    }
+
+    async fn update_stop(&self, run_id: u32, exit_code: Option<i32>) -> Result<()> {
+        if let Some(run) = self.lock().runs.get_mut(&run_id) {
+            run.status = AppStatus::Stopped;
+            run.stop_time = Some(sql_now());
+            run.exit_code = exit_code;
+        }
+        Ok(())
+    }
+
+    async fn finish_latest(
+        &self,
+        app_id: u32,
+        status: AppStatus,
+        exit_code: Option<i32>,
+    ) -> Result<()> {
+        let mut tables = self.lock();
+        let latest = tables
+            .runs
+            .values_mut()
+            .rev()
+            .find(|run| run.app_id == app_id);
+        if let Some(run) = latest.filter(|run| run.stop_time.is_none()) {
+            run.status = status;
+            run.stop_time = Some(sql_now());
+            run.exit_code = exit_code;
+        }
+        Ok(())
+    }
+
+    async fn increment_restarts(&self, run_id: u32) -> Result<()> {
+        if let Some(run) = self.lock().runs.get_mut(&run_id) {
+            run.restarts += 1;
+        }
+        Ok(())
+    }
+
+    async fn get_latest(&self, app_id: u32) -> Result<Option<RunRecord>> {
+        Ok(self
+            .lock()
+            .runs
+            .values()
+            .rev()
+            .find(|run| run.app_id == app_id)
+            .cloned())
+    }
+
+    async fn get_by_app(&self, app_id: u32, limit: usize) -> Result<Vec<RunRecord>> {
+        Ok(self
+            .lock()
+            .runs
+            .values()
+            .rev()
+            .filter(|run| run.app_id == app_id)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_by_app(&self, app_id: u32) -> Result<u64> {
+        let mut tables = self.lock();
+        let before = tables.runs.len();
+        tables.runs.retain(|_, run| run.app_id != app_id);
+        Ok((before - tables.runs.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl EventStore for MemoryStorage {
+    async fn insert(&self, event: &AppEvent) -> Result<u32> {
+        let mut tables = self.lock();
+        tables.last_event_id += 1;
+        let id = tables.last_event_id;
+        tables.events.insert(id, event.clone());
+
+        let stale: Vec<u32> = tables
+            .events
+            .iter()
+            .rev()
+            .filter(|(_, e)| e.app_id == event.app_id)
+            .skip(constants::EVENTS_RETENTION)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in stale {
+            tables.events.remove(&id);
+        }
+
+        if !matches!(
+            event.kind,
+            EventKind::Reloaded
+                | EventKind::Unhealthy
+                | EventKind::NotReady
+                | EventKind::Ready
+                | EventKind::Paused
+                | EventKind::Resumed
+                | EventKind::Signalled
+        ) {
+            tables.stats.entry(event.app_id).or_default().apply(event)?;
+        }
+        Ok(id)
+    }
+
+    async fn get_by_apps(&self, app_ids: &[u32], limit: usize) -> Result<Vec<AppEvent>> {
+        let tables = self.lock();
+        let mut events: Vec<(&u32, &AppEvent)> = tables
+            .events
+            .iter()
+            .filter(|(_, event)| app_ids.contains(&event.app_id))
+            .collect();
+        // Newest first, as the SQL backends order them
+        events.sort_by(|a, b| b.1.timestamp.cmp(&a.1.timestamp).then(b.0.cmp(a.0)));
+        let mut events: Vec<AppEvent> = events
+            .into_iter()
+            .take(limit)
+            .map(|(_, e)| e.clone())
+            .collect();
+        events.reverse();
+        Ok(events)
+    }
+}
+
+#[async_trait]
+impl StatsStore for MemoryStorage {
+    async fn record(&self, event: &AppEvent) -> Result<()> {
+        if matches!(
+            event.kind,
+            EventKind::Reloaded
+                | EventKind::Unhealthy
+                | EventKind::NotReady
+                | EventKind::Ready
+                | EventKind::Paused
+                | EventKind::Resumed
+                | EventKind::Signalled
+        ) {
+            return Ok(());
+        }
+        self.lock()
+            .stats
+            .entry(event.app_id)
+            .or_default()
+            .apply(event)
+    }
+
+    async fn get(&self, app_id: u32) -> Result<AppStats> {
+        match self.lock().stats.get(&app_id) {
+            Some(stats) => stats.to_stats(Utc::now()),
+            None => Ok(AppStats::default()),
+        }
+    }
+}
+
+#[async_trait]
+impl MetricsStore for MemoryStorage {
+    async fn insert(&self, app_id: u32, cpu_percent: f32, memory_bytes: u64) -> Result<()> {
+        let mut tables = self.lock();
+        tables.last_metric_id += 1;
+        let id = tables.last_metric_id;
+        tables.metrics.insert(
+            id,
+            (
+                app_id,
+                MetricsSnapshot {
+                    cpu_percent,
+                    memory_bytes,
+                    timestamp: sql_now(),
+                },
+            ),
+        );
+        Ok(())
+    }
+
+    async fn get_latest(&self, app_id: u32) -> Result<Option<MetricsSnapshot>> {
+        Ok(self.get_history(app_id, 1).await?.pop())
+    }
+
+    async fn get_history(&self, app_id: u32, limit: usize) -> Result<Vec<MetricsSnapshot>> {
+        Ok(self
+            .lock()
+            .metrics
+            .values()
+            .rev()
+            .filter(|(id, _)| *id == app_id)
+            .take(limit)
+            .map(|(_, snapshot)| snapshot.clone())
+            .collect())
+    }
+
+    async fn cleanup(&self, keep_per_app: usize) -> Result<u64> {
+        let mut tables = self.lock();
+        let mut kept: HashMap<u32, usize> = HashMap::new();
+        let stale: Vec<u32> = tables
+            .metrics
+            .iter()
+            .rev()
+            .filter(|(_, (app_id, _))| {
+                let count = kept.entry(*app_id).or_default();
+                *count += 1;
+                *count > keep_per_app
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &stale {
+            tables.metrics.remove(id);
+        }
+        Ok(stale.len() as u64)
+    }
+
+    async fn delete_by_app(&self, app_id: u32) -> Result<u64> {
+        let mut tables = self.lock();
+        let before = tables.metrics.len();
+        tables.metrics.retain(|_, (id, _)| *id != app_id);
+        Ok((before - tables.metrics.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl AuditStore for MemoryStorage {
+    async fn insert(&self, entry: &AuditEntry) -> Result<()> {
+        self.lock().audit.push(entry.clone());
+        Ok(())
+    }
+
+    async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let tables = self.lock();
+        let skip = tables.audit.len().saturating_sub(limit);
+        Ok(tables.audit[skip..].to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use chrono::Duration;
+    use oxidepm_core::AppMode;
+    use std::path::PathBuf;
+
+    #[tokio::test]
+    async fn test_memory_storage() {
+        let db = Database::memory();
+        assert_eq!(db.backend(), "memory");
+
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "app.js".to_string(),
+            PathBuf::from("/"),
+        );
+        let id = db.apps().insert(&spec).await.unwrap();
+        assert!(matches!(
+            db.apps().insert(&spec).await,
+            Err(Error::AppAlreadyExists(_))
+        ));
+        let other = db
+            .apps()
+            .insert(&AppSpec {
+                name: "web".to_string(),
+                ..spec.clone()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            db.apps()
+                .get_by_name("default", "api")
+                .await
+                .unwrap()
+                .unwrap()
+                .id,
+            id
+        );
+        assert!(db
+            .apps()
+            .set_desired_state(id, DesiredState::Stopped)
+            .await
+            .unwrap());
+        assert!(!db
+            .apps()
+            .set_desired_state(id, DesiredState::Stopped)
+            .await
+            .unwrap());
+
+        db.runs()
+            .insert(id, &RunState::running(id, 100))
+            .await
+            .unwrap();
+        db.runs()
+            .finish_latest(id, AppStatus::Errored, Some(1))
+            .await
+            .unwrap();
+        let run = db.runs().get_latest(id).await.unwrap().unwrap();
+        assert_eq!((run.status, run.exit_code), (AppStatus::Errored, Some(1)));
+
+        let t0 = Utc::now() - Duration::seconds(100);
+        for (kind, secs) in [
+            (EventKind::Started, 0),
+            (EventKind::Crashed, 40),
+            (EventKind::Started, 50),
+        ] {
+            let mut event = AppEvent::new(id, "api", kind);
+            event.timestamp = t0 + Duration::seconds(secs);
+            db.events().insert(&event).await.unwrap();
+        }
+        assert_eq!(
+            db.events().get_by_apps(&[id], 2).await.unwrap()[1].kind,
+            EventKind::Started
+        );
+        let stats = db.stats().get(id).await.unwrap();
+        assert_eq!((stats.crashes, stats.downtime_secs), (1, 10));
+
+        for app_id in [id, other] {
+            for memory in [1, 2, 3] {
+                db.metrics().insert(app_id, 1.0, memory).await.unwrap();
+            }
+        }
+        assert_eq!(db.metrics().cleanup(2).await.unwrap(), 2);
+        assert_eq!(
+            db.metrics()
+                .get_latest(id)
+                .await
+                .unwrap()
+                .unwrap()
+                .memory_bytes,
+            3
+        );
+
+        assert!(db.apps().delete_with_history(id).await.unwrap());
+        assert!(db.runs().get_latest(id).await.unwrap().is_none());
+        assert_eq!(db.metrics().get_history(other, 10).await.unwrap().len(), 2);
+        // IDs are not reused
+        let again = db.apps().insert(&spec).await.unwrap();
+        assert!(again > other);
+    }
+}
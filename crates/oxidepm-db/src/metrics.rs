@@ -1,9 +1,12 @@
 //! Metrics repository - CPU/memory tracking
 
+use async_trait::async_trait;
 use oxidepm_core::{Error, Result};
 use sqlx::sqlite::SqlitePool;
 use sqlx::Row;
 
+use crate::storage::MetricsStore;
+
 /// Repository for metrics operations
 pub struct MetricsRepository {
     pool: SqlitePool,
@@ -13,9 +16,11 @@ impl MetricsRepository {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
     }
+}
 
-    /// Insert a metrics snapshot
-    pub async fn insert(&self, app_id: u32, cpu_percent: f32, memory_bytes: u64) -> Result<()> {
+#[async_trait]
+impl MetricsStore for MetricsRepository {
+    async fn insert(&self, app_id: u32, cpu_percent: f32, memory_bytes: u64) -> Result<()> {
         sqlx::query(
             r#"
             INSERT INTO metrics (app_id, cpu_percent, memory_bytes)
@@ -32,8 +37,7 @@ impl MetricsRepository {
         Ok(())
     }
 
-    /// Get latest metrics for an app
-    pub async fn get_latest(&self, app_id: u32) -> Result<Option<MetricsSnapshot>> {
+    async fn get_latest(&self, app_id: u32) -> Result<Option<MetricsSnapshot>> {
         let row = sqlx::query(
             r#"
             SELECT cpu_percent, memory_bytes, timestamp
@@ -63,12 +67,7 @@ impl MetricsRepository {
         }
     }
 
-    /// Get metrics history for an app
-    pub async fn get_history(
-        &self,
-        app_id: u32,
-        limit: usize,
-    ) -> Result<Vec<MetricsSnapshot>> {
+    async fn get_history(&self, app_id: u32, limit: usize) -> Result<Vec<MetricsSnapshot>> {
         let rows = sqlx::query(
             r#"
             SELECT cpu_percent, memory_bytes, timestamp
@@ -99,8 +98,7 @@ impl MetricsRepository {
             .collect())
     }
 
-    /// Cleanup old metrics (keep last N per app)
-    pub async fn cleanup(&self, keep_per_app: usize) -> Result<u64> {
+    async fn cleanup(&self, keep_per_app: usize) -> Result<u64> {
         let result = sqlx::query(
             r#"
             DELETE FROM metrics
@@ -120,8 +118,7 @@ impl MetricsRepository {
         Ok(result.rows_affected())
     }
 
-    /// Delete all metrics for an app
-    pub async fn delete_by_app(&self, app_id: u32) -> Result<u64> {
+    async fn delete_by_app(&self, app_id: u32) -> Result<u64> {
         let result = sqlx::query("DELETE FROM metrics WHERE app_id = ?")
             .bind(app_id as i64)
             .execute(&self.pool)
@@ -142,7 +139,6 @@ pub struct MetricsSnapshot {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::Database;
     use oxidepm_core::{AppMode, AppSpec};
     use std::path::PathBuf;
@@ -168,7 +164,7 @@ mod tests {
     #[tokio::test]
     async fn test_insert_and_get_metrics() {
         let (db, app_id, _dir) = setup_db_with_app().await;
-        let metrics = MetricsRepository::new(db.pool().clone());
+        let metrics = db.metrics();
 
         metrics.insert(app_id, 25.5, 1024 * 1024).await.unwrap();
 
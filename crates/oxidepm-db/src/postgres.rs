@@ -0,0 +1,1073 @@
+//! Postgres storage - one database shared by the daemons of several hosts
+//!
+//! Apps are stored per host; runs, events, stats and metrics hang off the
+//! app IDs, which are unique across hosts. Timestamps are kept as text in
+//! the same formats as the SQLite backend so rows decode the same way.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use oxidepm_core::{
+    constants, AppEvent, AppSpec, AppStats, AppStatus, AuditEntry, DbHealth, DesiredState, Error,
+    EventKind, Result, RunState,
+};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use crate::apps::{health_check_json, row_to_app_spec};
+use crate::audit::row_to_audit_entry;
+use crate::events::{format_timestamp, row_to_event};
+use crate::metrics::MetricsSnapshot;
+use crate::runs::{row_to_run_record, RunRecord};
+use crate::sql_now;
+use crate::stats::StatsRow;
+use crate::storage::{
+    AppStore, AuditStore, EventStore, MetricsStore, RunStore, StatsStore, Storage,
+};
+
+/// Postgres schema; each statement runs on its own
+pub const SCHEMA: &[&str] = &[
+    r#"CREATE TABLE IF NOT EXISTS apps (
+        id BIGSERIAL PRIMARY KEY,
+        host TEXT NOT NULL,
+        name TEXT NOT NULL,
+        namespace TEXT NOT NULL DEFAULT 'default',
+        mode TEXT NOT NULL,
+        command TEXT NOT NULL,
+        args TEXT NOT NULL DEFAULT '[]',
+        cwd TEXT NOT NULL,
+        env TEXT NOT NULL DEFAULT '{}',
+        watch BOOLEAN NOT NULL DEFAULT FALSE,
+        ignore_patterns TEXT NOT NULL DEFAULT '[]',
+        auto_restart BOOLEAN NOT NULL DEFAULT TRUE,
+        max_restarts BIGINT NOT NULL DEFAULT 15,
+        restart_delay_ms BIGINT NOT NULL DEFAULT 500,
+        crash_window_secs BIGINT NOT NULL DEFAULT 60,
+        kill_timeout_ms BIGINT NOT NULL DEFAULT 3000,
+        created_at TEXT NOT NULL,
+        desired_state TEXT NOT NULL DEFAULT 'running',
+        tags TEXT NOT NULL DEFAULT '[]',
+        max_memory_mb BIGINT,
+        max_uptime_secs BIGINT,
+        port BIGINT,
+        health_check TEXT,
+        UNIQUE (host, namespace, name)
+    )"#,
+    // Columns added after the first release
+    "ALTER TABLE apps ADD COLUMN IF NOT EXISTS max_memory_mb BIGINT",
+    "ALTER TABLE apps ADD COLUMN IF NOT EXISTS max_uptime_secs BIGINT",
+    "ALTER TABLE apps ADD COLUMN IF NOT EXISTS port BIGINT",
+    "ALTER TABLE apps ADD COLUMN IF NOT EXISTS health_check TEXT",
+    r#"CREATE TABLE IF NOT EXISTS runs (
+        id BIGSERIAL PRIMARY KEY,
+        app_id BIGINT NOT NULL REFERENCES apps(id) ON DELETE CASCADE,
+        pid BIGINT,
+        status TEXT NOT NULL,
+        restarts BIGINT NOT NULL DEFAULT 0,
+        start_time TEXT NOT NULL DEFAULT to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+        stop_time TEXT,
+        exit_code INTEGER,
+        build_profile TEXT,
+        build_duration_ms BIGINT,
+        binary_path TEXT,
+        git_commit TEXT,
+        binary_sha256 TEXT
+    )"#,
+    "CREATE INDEX IF NOT EXISTS idx_runs_app_id ON runs(app_id)",
+    r#"CREATE TABLE IF NOT EXISTS events (
+        id BIGSERIAL PRIMARY KEY,
+        app_id BIGINT NOT NULL REFERENCES apps(id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        reason TEXT,
+        exit_code INTEGER,
+        timestamp TEXT NOT NULL
+    )"#,
+    "CREATE INDEX IF NOT EXISTS idx_events_app_id ON events(app_id)",
+    r#"CREATE TABLE IF NOT EXISTS app_stats (
+        app_id BIGINT PRIMARY KEY REFERENCES apps(id) ON DELETE CASCADE,
+        total_restarts BIGINT NOT NULL DEFAULT 0,
+        crashes BIGINT NOT NULL DEFAULT 0,
+        last_crash_at TEXT,
+        downtime_secs BIGINT NOT NULL DEFAULT 0,
+        uptime_secs BIGINT NOT NULL DEFAULT 0,
+        up_since TEXT,
+        down_since TEXT
+    )"#,
+    r#"CREATE TABLE IF NOT EXISTS metrics (
+        id BIGSERIAL PRIMARY KEY,
+        app_id BIGINT NOT NULL REFERENCES apps(id) ON DELETE CASCADE,
+        timestamp TEXT NOT NULL DEFAULT to_char(now() AT TIME ZONE 'UTC', 'YYYY-MM-DD HH24:MI:SS'),
+        cpu_percent DOUBLE PRECISION,
+        memory_bytes BIGINT
+    )"#,
+    "CREATE INDEX IF NOT EXISTS idx_metrics_app_id ON metrics(app_id)",
+    r#"CREATE TABLE IF NOT EXISTS audit (
+        id BIGSERIAL PRIMARY KEY,
+        host TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        actor TEXT NOT NULL,
+        client TEXT,
+        action TEXT NOT NULL,
+        target TEXT,
+        success BOOLEAN NOT NULL,
+        result TEXT NOT NULL
+    )"#,
+    "CREATE INDEX IF NOT EXISTS idx_audit_host ON audit(host, id)",
+    r#"CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    )"#,
+];
+
+const APP_COLUMNS: &str = "id, name, namespace, mode, command, args, cwd, env, watch, ignore_patterns, \
+     auto_restart, max_restarts, restart_delay_ms, crash_window_secs, kill_timeout_ms, created_at, tags, \
+     max_memory_mb, max_uptime_secs, port, health_check";
+
+const RUN_COLUMNS: &str = "id, app_id, pid, status, restarts, start_time, stop_time, exit_code, \
+     build_profile, build_duration_ms, binary_path, git_commit, binary_sha256";
+
+/// Tables maintenance runs over
+const TABLES: &str = "apps, runs, events, app_stats, metrics, audit, meta";
+
+fn db_error(e: sqlx::Error) -> Error {
+    Error::DbError(e.to_string())
+}
+
+/// Storage in a Postgres database
+pub struct PostgresStorage {
+    pool: PgPool,
+    host: String,
+    location: String,
+}
+
+impl PostgresStorage {
+    /// Connect to `url`, create the tables if needed and keep to the apps of `host`
+    pub async fn connect(url: &str, host: &str) -> Result<Self> {
+        let location = crate::redact_url(url);
+        info!("Connecting to database: {} (host {})", location, host);
+
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_millis(constants::DB_BUSY_TIMEOUT_MS))
+            .connect(url)
+            .await
+            .map_err(|e| Error::DbError(format!("Failed to connect to {}: {}", location, e)))?;
+
+        for statement in SCHEMA {
+            sqlx::query(statement)
+                .execute(&pool)
+                .await
+                .map_err(db_error)?;
+        }
+
+        info!("Database initialized");
+        Ok(Self {
+            pool,
+            host: host.to_string(),
+            location,
+        })
+    }
+
+    /// Get the connection pool
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    fn backend(&self) -> &'static str {
+        "postgres"
+    }
+
+    fn apps(&self) -> Arc<dyn AppStore> {
+        Arc::new(PgApps {
+            pool: self.pool.clone(),
+            host: self.host.clone(),
+        })
+    }
+
+    fn runs(&self) -> Arc<dyn RunStore> {
+        Arc::new(PgRuns {
+            pool: self.pool.clone(),
+        })
+    }
+
+    fn events(&self) -> Arc<dyn EventStore> {
+        Arc::new(PgEvents {
+            pool: self.pool.clone(),
+        })
+    }
+
+    fn stats(&self) -> Arc<dyn StatsStore> {
+        Arc::new(PgStats {
+            pool: self.pool.clone(),
+        })
+    }
+
+    fn metrics(&self) -> Arc<dyn MetricsStore> {
+        Arc::new(PgMetrics {
+            pool: self.pool.clone(),
+        })
+    }
+
+    fn audit(&self) -> Arc<dyn AuditStore> {
+        Arc::new(PgAudit {
+            pool: self.pool.clone(),
+            host: self.host.clone(),
+        })
+    }
+
+    async fn optimize(&self) -> Result<()> {
+        sqlx::query(&format!("ANALYZE {}", TABLES))
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn vacuum(&self) -> Result<()> {
+        sqlx::query(&format!("VACUUM {}", TABLES))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(format!("VACUUM failed: {}", e)))?;
+        sqlx::query(
+            "INSERT INTO meta (key, value) VALUES ('last_vacuum', $1) \
+             ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn last_vacuum(&self) -> Result<Option<DateTime<Utc>>> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT value FROM meta WHERE key = 'last_vacuum'")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(db_error)?;
+        Ok(row
+            .and_then(|(value,)| DateTime::parse_from_rfc3339(&value).ok())
+            .map(|at| at.with_timezone(&Utc)))
+    }
+
+    async fn health(&self) -> Result<DbHealth> {
+        let (size,): (i64,) = sqlx::query_as("SELECT pg_database_size(current_database())")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(DbHealth {
+            backend: self.backend().to_string(),
+            location: self.location.clone(),
+            size_bytes: size.max(0) as u64,
+            wal_bytes: 0,
+            free_bytes: 0,
+            journal_mode: None,
+            last_vacuum: self.last_vacuum().await?,
+        })
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+/// Apps of one host
+struct PgApps {
+    pool: PgPool,
+    host: String,
+}
+
+async fn insert_app<'e, E>(executor: E, host: &str, spec: &AppSpec) -> Result<u32>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+{
+    let (id,): (i64,) = sqlx::query_as(
+        r#"
+        INSERT INTO apps (
+            host, name, namespace, mode, command, args, cwd, env, watch, ignore_patterns,
+            auto_restart, max_restarts, restart_delay_ms, crash_window_secs, kill_timeout_ms,
+            created_at, tags, max_memory_mb, max_uptime_secs, port, health_check
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+                  $18, $19, $20, $21)
+        RETURNING id
+        "#,
+    )
+    .bind(host)
+    .bind(&spec.name)
+    .bind(&spec.namespace)
+    .bind(spec.mode.as_str())
+    .bind(&spec.command)
+    .bind(serde_json::to_string(&spec.args)?)
+    .bind(spec.cwd.to_string_lossy().to_string())
+    .bind(serde_json::to_string(&spec.env)?)
+    .bind(spec.watch)
+    .bind(serde_json::to_string(&spec.ignore_patterns)?)
+    .bind(spec.restart_policy.auto_restart)
+    .bind(spec.restart_policy.max_restarts as i64)
+    .bind(spec.restart_policy.restart_delay_ms as i64)
+    .bind(spec.restart_policy.crash_window_secs as i64)
+    .bind(spec.kill_timeout_ms as i64)
+    .bind(spec.created_at.to_rfc3339())
+    .bind(serde_json::to_string(&spec.tags)?)
+    .bind(spec.max_memory_mb.map(|mb| mb as i64))
+    .bind(spec.max_uptime_secs.map(|secs| secs as i64))
+    .bind(spec.port.map(i64::from))
+    .bind(health_check_json(spec)?)
+    .fetch_one(executor)
+    .await
+    .map_err(db_error)?;
+
+    Ok(id as u32)
+}
+
+#[async_trait]
+impl AppStore for PgApps {
+    async fn insert(&self, spec: &AppSpec) -> Result<u32> {
+        insert_app(&self.pool, &self.host, spec).await
+    }
+
+    async fn insert_many(&self, specs: &[AppSpec]) -> Result<Vec<u32>> {
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let mut ids = Vec::with_capacity(specs.len());
+        for spec in specs {
+            ids.push(insert_app(&mut *tx, &self.host, spec).await?);
+        }
+        tx.commit().await.map_err(db_error)?;
+        Ok(ids)
+    }
+
+    async fn get_by_id(&self, id: u32) -> Result<Option<AppSpec>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM apps WHERE host = $1 AND id = $2",
+            APP_COLUMNS
+        ))
+        .bind(&self.host)
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+        row.as_ref().map(row_to_app_spec).transpose()
+    }
+
+    async fn get_by_name(&self, namespace: &str, name: &str) -> Result<Option<AppSpec>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM apps WHERE host = $1 AND namespace = $2 AND name = $3",
+            APP_COLUMNS
+        ))
+        .bind(&self.host)
+        .bind(namespace)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+        row.as_ref().map(row_to_app_spec).transpose()
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Vec<AppSpec>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM apps WHERE host = $1 AND name = $2 ORDER BY id",
+            APP_COLUMNS
+        ))
+        .bind(&self.host)
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+        rows.iter().map(row_to_app_spec).collect()
+    }
+
+    async fn get_all(&self) -> Result<Vec<AppSpec>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM apps WHERE host = $1 ORDER BY id",
+            APP_COLUMNS
+        ))
+        .bind(&self.host)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+        rows.iter().map(row_to_app_spec).collect()
+    }
+
+    async fn update(&self, spec: &AppSpec) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE apps SET
+                name = $1, namespace = $2, mode = $3, command = $4, args = $5, cwd = $6,
+                env = $7, watch = $8, ignore_patterns = $9, auto_restart = $10,
+                max_restarts = $11, restart_delay_ms = $12, crash_window_secs = $13,
+                kill_timeout_ms = $14, tags = $15, max_memory_mb = $16, max_uptime_secs = $17,
+                port = $18, health_check = $19
+            WHERE host = $20 AND id = $21
+            "#,
+        )
+        .bind(&spec.name)
+        .bind(&spec.namespace)
+        .bind(spec.mode.as_str())
+        .bind(&spec.command)
+        .bind(serde_json::to_string(&spec.args)?)
+        .bind(spec.cwd.to_string_lossy().to_string())
+        .bind(serde_json::to_string(&spec.env)?)
+        .bind(spec.watch)
+        .bind(serde_json::to_string(&spec.ignore_patterns)?)
+        .bind(spec.restart_policy.auto_restart)
+        .bind(spec.restart_policy.max_restarts as i64)
+        .bind(spec.restart_policy.restart_delay_ms as i64)
+        .bind(spec.restart_policy.crash_window_secs as i64)
+        .bind(spec.kill_timeout_ms as i64)
+        .bind(serde_json::to_string(&spec.tags)?)
+        .bind(spec.max_memory_mb.map(|mb| mb as i64))
+        .bind(spec.max_uptime_secs.map(|secs| secs as i64))
+        .bind(spec.port.map(i64::from))
+        .bind(health_check_json(spec)?)
+        .bind(&self.host)
+        .bind(spec.id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn update_env(&self, id: u32, env: &HashMap<String, String>) -> Result<bool> {
+        let result = sqlx::query("UPDATE apps SET env = $1 WHERE host = $2 AND id = $3")
+            .bind(serde_json::to_string(env)?)
+            .bind(&self.host)
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_desired_state(&self, id: u32, state: DesiredState) -> Result<bool> {
+        let result =
+            sqlx::query("UPDATE apps SET desired_state = $1 WHERE host = $2 AND id = $3 AND desired_state != $1")
+                .bind(state.as_str())
+                .bind(&self.host)
+                .bind(id as i64)
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn desired_states(&self) -> Result<HashMap<u32, DesiredState>> {
+        let rows: Vec<(i64, String)> =
+            sqlx::query_as("SELECT id, desired_state FROM apps WHERE host = $1")
+                .bind(&self.host)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(db_error)?;
+        rows.into_iter()
+            .map(|(id, state)| Ok((id as u32, state.parse()?)))
+            .collect()
+    }
+
+    async fn delete(&self, id: u32) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM apps WHERE host = $1 AND id = $2")
+            .bind(&self.host)
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_with_history(&self, id: u32) -> Result<bool> {
+        // Foreign keys are always enforced here, so the history goes with the app
+        self.delete(id).await
+    }
+
+    async fn delete_by_name(&self, namespace: &str, name: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM apps WHERE host = $1 AND namespace = $2 AND name = $3")
+                .bind(&self.host)
+                .bind(namespace)
+                .bind(name)
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_all(&self) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM apps WHERE host = $1")
+            .bind(&self.host)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn exists(&self, namespace: &str, name: &str) -> Result<bool> {
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM apps WHERE host = $1 AND namespace = $2 AND name = $3",
+        )
+        .bind(&self.host)
+        .bind(namespace)
+        .bind(name)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(count > 0)
+    }
+
+    async fn next_id(&self) -> Result<u32> {
+        // IDs are shared by all hosts
+        let (id,): (i64,) = sqlx::query_as("SELECT COALESCE(MAX(id), 0) + 1 FROM apps")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(id as u32)
+    }
+}
+
+struct PgRuns {
+    pool: PgPool,
+}
+
+#[async_trait]
+impl RunStore for PgRuns {
+    async fn insert(&self, app_id: u32, state: &RunState) -> Result<u32> {
+        let build = state.build.as_ref();
+        let (id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO runs (app_id, pid, status, restarts, build_profile, build_duration_ms, binary_path, git_commit, binary_sha256)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+        )
+        .bind(app_id as i64)
+        .bind(state.pid.map(|p| p as i64))
+        .bind(state.status.as_str())
+        .bind(state.restarts as i64)
+        .bind(build.map(|b| b.profile.clone()))
+        .bind(build.map(|b| b.duration_ms as i64))
+        .bind(build.map(|b| b.binary_path.to_string_lossy().to_string()))
+        .bind(build.and_then(|b| b.git_commit.clone()))
+        .bind(build.and_then(|b| b.sha256.clone()))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(id as u32)
+    }
+
+    async fn update_status(&self, run_id: u32, status: AppStatus) -> Result<()> {
+        sqlx::query("UPDATE runs SET status = $1 WHERE id = $2")
+            .bind(status.as_str())
+            .bind(run_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn update_stop(&self, run_id: u32, exit_code: Option<i32>) -> Result<()> {
+        sqlx::query(
+            "UPDATE runs SET status = 'stopped', stop_time = $1, exit_code = $2 WHERE id = $3",
+        )
+        .bind(sql_now())
+        .bind(exit_code)
+        .bind(run_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn finish_latest(
+        &self,
+        app_id: u32,
+        status: AppStatus,
+        exit_code: Option<i32>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE runs
+            SET status = $1, stop_time = $2, exit_code = $3
+            WHERE id = (SELECT MAX(id) FROM runs WHERE app_id = $4)
+              AND stop_time IS NULL
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(sql_now())
+        .bind(exit_code)
+        .bind(app_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn increment_restarts(&self, run_id: u32) -> Result<()> {
+        sqlx::query("UPDATE runs SET restarts = restarts + 1 WHERE id = $1")
+            .bind(run_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get_latest(&self, app_id: u32) -> Result<Option<RunRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM runs WHERE app_id = $1 ORDER BY id DESC LIMIT 1",
+            RUN_COLUMNS
+        ))
+        .bind(app_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+        row.as_ref().map(row_to_run_record).transpose()
+    }
+
+    async fn get_by_app(&self, app_id: u32, limit: usize) -> Result<Vec<RunRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM runs WHERE app_id = $1 ORDER BY id DESC LIMIT $2",
+            RUN_COLUMNS
+        ))
+        .bind(app_id as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+        rows.iter().map(row_to_run_record).collect()
+    }
+
+    async fn delete_by_app(&self, app_id: u32) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM runs WHERE app_id = $1")
+            .bind(app_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
+
+struct PgEvents {
+    pool: PgPool,
+}
+
+#[async_trait]
+impl EventStore for PgEvents {
+    async fn insert(&self, event: &AppEvent) -> Result<u32> {
+        let (id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO events (app_id, name, kind, reason, exit_code, timestamp)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#,
+        )
+        .bind(event.app_id as i64)
+        .bind(&event.name)
+        .bind(event.kind.as_str())
+        .bind(&event.reason)
+        .bind(event.exit_code)
+        .bind(format_timestamp(&event.timestamp))
+        .fetch_one(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM events
+            WHERE app_id = $1 AND id <= (
+                SELECT id FROM events WHERE app_id = $1 ORDER BY id DESC LIMIT 1 OFFSET $2
+            )
+            "#,
+        )
+        .bind(event.app_id as i64)
+        .bind(constants::EVENTS_RETENTION as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        PgStats {
+            pool: self.pool.clone(),
+        }
+        .record(event)
+        .await?;
+
+        Ok(id as u32)
+    }
+
+    async fn get_by_apps(&self, app_ids: &[u32], limit: usize) -> Result<Vec<AppEvent>> {
+        if app_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<i64> = app_ids.iter().map(|id| *id as i64).collect();
+        let rows = sqlx::query(
+            r#"
+            SELECT app_id, name, kind, reason, exit_code, timestamp
+            FROM events
+            WHERE app_id = ANY($1)
+            ORDER BY timestamp DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(ids)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut events = rows.iter().map(row_to_event).collect::<Result<Vec<_>>>()?;
+        events.reverse();
+        Ok(events)
+    }
+}
+
+struct PgStats {
+    pool: PgPool,
+}
+
+#[async_trait]
+impl StatsStore for PgStats {
+    async fn record(&self, event: &AppEvent) -> Result<()> {
+        if matches!(
+            event.kind,
+            EventKind::Reloaded
+                | EventKind::Unhealthy
+                | EventKind::NotReady
+                | EventKind::Ready
+                | EventKind::Paused
+                | EventKind::Resumed
+                | EventKind::Signalled
+        ) {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        sqlx::query("INSERT INTO app_stats (app_id) VALUES ($1) ON CONFLICT (app_id) DO NOTHING")
+            .bind(event.app_id as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        let row = sqlx::query(
+            r#"
+            SELECT total_restarts, crashes, last_crash_at, downtime_secs, uptime_secs, up_since, down_since
+            FROM app_stats WHERE app_id = $1 FOR UPDATE
+            "#,
+        )
+        .bind(event.app_id as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        let mut stats = StatsRow::from_row(&row);
+        stats.apply(event)?;
+
+        sqlx::query(
+            r#"
+            UPDATE app_stats
+            SET total_restarts = $2, crashes = $3, last_crash_at = $4, downtime_secs = $5,
+                uptime_secs = $6, up_since = $7, down_since = $8
+            WHERE app_id = $1
+            "#,
+        )
+        .bind(event.app_id as i64)
+        .bind(stats.total_restarts)
+        .bind(stats.crashes)
+        .bind(stats.last_crash_at)
+        .bind(stats.downtime_secs)
+        .bind(stats.uptime_secs)
+        .bind(stats.up_since)
+        .bind(stats.down_since)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get(&self, app_id: u32) -> Result<AppStats> {
+        let row = sqlx::query(
+            r#"
+            SELECT total_restarts, crashes, last_crash_at, downtime_secs,
+                   uptime_secs, up_since, down_since
+            FROM app_stats
+            WHERE app_id = $1
+            "#,
+        )
+        .bind(app_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        match row {
+            Some(row) => StatsRow::from_row(&row).to_stats(Utc::now()),
+            None => Ok(AppStats::default()),
+        }
+    }
+}
+
+struct PgMetrics {
+    pool: PgPool,
+}
+
+fn row_to_snapshot(row: &sqlx::postgres::PgRow) -> MetricsSnapshot {
+    let cpu: Option<f64> = row.get("cpu_percent");
+    let mem: Option<i64> = row.get("memory_bytes");
+    MetricsSnapshot {
+        cpu_percent: cpu.unwrap_or_default() as f32,
+        memory_bytes: mem.unwrap_or_default() as u64,
+        timestamp: row.get("timestamp"),
+    }
+}
+
+#[async_trait]
+impl MetricsStore for PgMetrics {
+    async fn insert(&self, app_id: u32, cpu_percent: f32, memory_bytes: u64) -> Result<()> {
+        sqlx::query("INSERT INTO metrics (app_id, cpu_percent, memory_bytes) VALUES ($1, $2, $3)")
+            .bind(app_id as i64)
+            .bind(cpu_percent as f64)
+            .bind(memory_bytes as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn get_latest(&self, app_id: u32) -> Result<Option<MetricsSnapshot>> {
+        let row = sqlx::query(
+            "SELECT cpu_percent, memory_bytes, timestamp FROM metrics WHERE app_id = $1 ORDER BY id DESC LIMIT 1",
+        )
+        .bind(app_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(row.as_ref().map(row_to_snapshot))
+    }
+
+    async fn get_history(&self, app_id: u32, limit: usize) -> Result<Vec<MetricsSnapshot>> {
+        let rows = sqlx::query(
+            "SELECT cpu_percent, memory_bytes, timestamp FROM metrics WHERE app_id = $1 ORDER BY id DESC LIMIT $2",
+        )
+        .bind(app_id as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(rows.iter().map(row_to_snapshot).collect())
+    }
+
+    async fn cleanup(&self, keep_per_app: usize) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM metrics
+            WHERE id IN (
+                SELECT id FROM (
+                    SELECT id, ROW_NUMBER() OVER (PARTITION BY app_id ORDER BY id DESC) AS rn
+                    FROM metrics
+                ) ranked WHERE rn > $1
+            )
+            "#,
+        )
+        .bind(keep_per_app as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_by_app(&self, app_id: u32) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM metrics WHERE app_id = $1")
+            .bind(app_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Audit entries of one host
+struct PgAudit {
+    pool: PgPool,
+    host: String,
+}
+
+#[async_trait]
+impl AuditStore for PgAudit {
+    async fn insert(&self, entry: &AuditEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit (host, timestamp, actor, client, action, target, success, result)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(&self.host)
+        .bind(format_timestamp(&entry.timestamp))
+        .bind(&entry.actor)
+        .bind(&entry.client)
+        .bind(&entry.action)
+        .bind(&entry.target)
+        .bind(entry.success)
+        .bind(&entry.result)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT timestamp, actor, client, action, target, success, result
+            FROM audit
+            WHERE host = $1
+            ORDER BY id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(&self.host)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let mut entries = rows
+            .iter()
+            .map(row_to_audit_entry)
+            .collect::<Result<Vec<_>>>()?;
+        entries.reverse();
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::AppMode;
+    use std::path::PathBuf;
+
+    /// Runs against the server in `OXIDEPM_TEST_POSTGRES_URL`; skipped when unset
+    async fn connect(host: &str) -> Option<PostgresStorage> {
+        let url = std::env::var("OXIDEPM_TEST_POSTGRES_URL").ok()?;
+        Some(PostgresStorage::connect(&url, host).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_postgres_storage() {
+        let suffix = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let (Some(web1), Some(web2)) = (
+            connect(&format!("web1-{}", suffix)).await,
+            connect(&format!("web2-{}", suffix)).await,
+        ) else {
+            return;
+        };
+
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "app.js".to_string(),
+            PathBuf::from("/srv"),
+        );
+        spec.tags = vec!["web".to_string()];
+        spec.env.insert("PORT".to_string(), "3000".to_string());
+        let id = web1.apps().insert(&spec).await.unwrap();
+        // Same name on another host
+        let other = web2.apps().insert(&spec).await.unwrap();
+        assert_ne!(id, other);
+
+        let stored = web1
+            .apps()
+            .get_by_name("default", "api")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.id, id);
+        assert_eq!(stored.tags, vec!["web"]);
+        assert_eq!(stored.env["PORT"], "3000");
+        assert_eq!(stored.created_at.timestamp(), spec.created_at.timestamp());
+        assert_eq!(web1.apps().get_all().await.unwrap().len(), 1);
+        assert!(web1.apps().get_by_id(other).await.unwrap().is_none());
+        assert!(web1
+            .apps()
+            .set_desired_state(id, DesiredState::Stopped)
+            .await
+            .unwrap());
+        assert_eq!(
+            web1.apps().desired_states().await.unwrap()[&id],
+            DesiredState::Stopped
+        );
+
+        let mut state = RunState::new(id);
+        state.status = AppStatus::Running;
+        state.pid = Some(42);
+        web1.runs().insert(id, &state).await.unwrap();
+        web1.runs()
+            .finish_latest(id, AppStatus::Errored, Some(1))
+            .await
+            .unwrap();
+        let run = web1.runs().get_latest(id).await.unwrap().unwrap();
+        assert_eq!(run.status, AppStatus::Errored);
+        assert_eq!(run.exit_code, Some(1));
+        assert!(run.stop_time.is_some());
+
+        let t0 = Utc::now() - chrono::Duration::seconds(100);
+        let at = |kind, secs| {
+            let mut event = AppEvent::new(id, "api", kind);
+            event.timestamp = t0 + chrono::Duration::seconds(secs);
+            event
+        };
+        let events = web1.events();
+        events.insert(&at(EventKind::Started, 0)).await.unwrap();
+        events.insert(&at(EventKind::Crashed, 40)).await.unwrap();
+        events.insert(&at(EventKind::Restarted, 50)).await.unwrap();
+        events.insert(&at(EventKind::Started, 50)).await.unwrap();
+        let kinds: Vec<EventKind> = events
+            .get_by_apps(&[id], 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                EventKind::Started,
+                EventKind::Crashed,
+                EventKind::Restarted,
+                EventKind::Started
+            ]
+        );
+        let stats = web1.stats().get(id).await.unwrap();
+        assert_eq!(
+            (stats.crashes, stats.total_restarts, stats.downtime_secs),
+            (1, 1, 10)
+        );
+
+        web1.metrics().insert(id, 12.5, 2048).await.unwrap();
+        let latest = web1.metrics().get_latest(id).await.unwrap().unwrap();
+        assert_eq!(latest.memory_bytes, 2048);
+
+        // Stored with microsecond precision
+        let entry = AuditEntry {
+            timestamp: chrono::SubsecRound::trunc_subsecs(Utc::now(), 6),
+            actor: "uid 0 (root)".to_string(),
+            client: None,
+            action: "stop".to_string(),
+            target: Some("api".to_string()),
+            success: true,
+            result: "op_results".to_string(),
+        };
+        web1.audit().insert(&entry).await.unwrap();
+        assert_eq!(web1.audit().recent(10).await.unwrap(), vec![entry]);
+        assert!(web2.audit().recent(10).await.unwrap().is_empty());
+
+        web1.optimize().await.unwrap();
+        let health = web1.health().await.unwrap();
+        assert_eq!(health.backend, "postgres");
+        assert!(health.size_bytes > 0);
+
+        assert!(web1.apps().delete_with_history(id).await.unwrap());
+        assert!(web1.runs().get_latest(id).await.unwrap().is_none());
+        assert!(events.get_by_apps(&[id], 10).await.unwrap().is_empty());
+        assert_eq!(web2.apps().delete_all().await.unwrap(), 1);
+    }
+}
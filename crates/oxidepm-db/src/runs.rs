@@ -1,10 +1,14 @@
 //! Runs repository - execution history tracking
 
-use oxidepm_core::{AppStatus, Error, Result, RunState};
+use async_trait::async_trait;
+use oxidepm_core::{AppStatus, BuildInfo, Error, Result, RunState};
 use sqlx::sqlite::SqlitePool;
 use sqlx::Row;
 
+use crate::storage::RunStore;
+
 /// Repository for run history operations
+#[derive(Clone)]
 pub struct RunsRepository {
     pool: SqlitePool,
 }
@@ -13,19 +17,26 @@ impl RunsRepository {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
     }
+}
 
-    /// Insert a new run record
-    pub async fn insert(&self, app_id: u32, state: &RunState) -> Result<u32> {
+#[async_trait]
+impl RunStore for RunsRepository {
+    async fn insert(&self, app_id: u32, state: &RunState) -> Result<u32> {
         let result = sqlx::query(
             r#"
-            INSERT INTO runs (app_id, pid, status, restarts)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO runs (app_id, pid, status, restarts, build_profile, build_duration_ms, binary_path, git_commit, binary_sha256)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(app_id as i64)
         .bind(state.pid.map(|p| p as i64))
         .bind(state.status.as_str())
         .bind(state.restarts as i64)
+        .bind(state.build.as_ref().map(|b| b.profile.clone()))
+        .bind(state.build.as_ref().map(|b| b.duration_ms as i64))
+        .bind(state.build.as_ref().map(|b| b.binary_path.to_string_lossy().to_string()))
+        .bind(state.build.as_ref().and_then(|b| b.git_commit.clone()))
+        .bind(state.build.as_ref().and_then(|b| b.sha256.clone()))
         .execute(&self.pool)
         .await
         .map_err(|e| Error::DbError(e.to_string()))?;
@@ -33,8 +44,7 @@ impl RunsRepository {
         Ok(result.last_insert_rowid() as u32)
     }
 
-    /// Update run status
-    pub async fn update_status(&self, run_id: u32, status: AppStatus) -> Result<()> {
+    async fn update_status(&self, run_id: u32, status: AppStatus) -> Result<()> {
         sqlx::query("UPDATE runs SET status = ? WHERE id = ?")
             .bind(status.as_str())
             .bind(run_id as i64)
@@ -45,8 +55,7 @@ impl RunsRepository {
         Ok(())
     }
 
-    /// Update run on stop
-    pub async fn update_stop(&self, run_id: u32, exit_code: Option<i32>) -> Result<()> {
+    async fn update_stop(&self, run_id: u32, exit_code: Option<i32>) -> Result<()> {
         sqlx::query(
             r#"
             UPDATE runs
@@ -65,8 +74,33 @@ impl RunsRepository {
         Ok(())
     }
 
-    /// Increment restart count
-    pub async fn increment_restarts(&self, run_id: u32) -> Result<()> {
+    async fn finish_latest(
+        &self,
+        app_id: u32,
+        status: AppStatus,
+        exit_code: Option<i32>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE runs
+            SET status = ?,
+                stop_time = CURRENT_TIMESTAMP,
+                exit_code = ?
+            WHERE id = (SELECT MAX(id) FROM runs WHERE app_id = ?)
+              AND stop_time IS NULL
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(exit_code)
+        .bind(app_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn increment_restarts(&self, run_id: u32) -> Result<()> {
         sqlx::query("UPDATE runs SET restarts = restarts + 1 WHERE id = ?")
             .bind(run_id as i64)
             .execute(&self.pool)
@@ -76,11 +110,11 @@ impl RunsRepository {
         Ok(())
     }
 
-    /// Get latest run for an app
-    pub async fn get_latest(&self, app_id: u32) -> Result<Option<RunRecord>> {
+    async fn get_latest(&self, app_id: u32) -> Result<Option<RunRecord>> {
         let row = sqlx::query(
             r#"
-            SELECT id, app_id, pid, status, restarts, start_time, stop_time, exit_code
+            SELECT id, app_id, pid, status, restarts, start_time, stop_time, exit_code,
+                   build_profile, build_duration_ms, binary_path, git_commit, binary_sha256
             FROM runs
             WHERE app_id = ?
             ORDER BY id DESC
@@ -99,10 +133,11 @@ impl RunsRepository {
     }
 
     /// Get all runs for an app
-    pub async fn get_by_app(&self, app_id: u32, limit: usize) -> Result<Vec<RunRecord>> {
+    async fn get_by_app(&self, app_id: u32, limit: usize) -> Result<Vec<RunRecord>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, app_id, pid, status, restarts, start_time, stop_time, exit_code
+            SELECT id, app_id, pid, status, restarts, start_time, stop_time, exit_code,
+                   build_profile, build_duration_ms, binary_path, git_commit, binary_sha256
             FROM runs
             WHERE app_id = ?
             ORDER BY id DESC
@@ -118,8 +153,7 @@ impl RunsRepository {
         rows.iter().map(row_to_run_record).collect()
     }
 
-    /// Delete all runs for an app
-    pub async fn delete_by_app(&self, app_id: u32) -> Result<u64> {
+    async fn delete_by_app(&self, app_id: u32) -> Result<u64> {
         let result = sqlx::query("DELETE FROM runs WHERE app_id = ?")
             .bind(app_id as i64)
             .execute(&self.pool)
@@ -141,9 +175,17 @@ pub struct RunRecord {
     pub start_time: String,
     pub stop_time: Option<String>,
     pub exit_code: Option<i32>,
+    pub build: Option<BuildInfo>,
 }
 
-fn row_to_run_record(row: &sqlx::sqlite::SqliteRow) -> Result<RunRecord> {
+pub(crate) fn row_to_run_record<'r, R>(row: &'r R) -> Result<RunRecord>
+where
+    R: Row,
+    for<'a> &'a str: sqlx::ColumnIndex<R>,
+    i64: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    i32: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    String: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
     let id: i64 = row.get("id");
     let app_id: i64 = row.get("app_id");
     let pid: Option<i64> = row.get("pid");
@@ -152,6 +194,20 @@ fn row_to_run_record(row: &sqlx::sqlite::SqliteRow) -> Result<RunRecord> {
     let start_time: String = row.get("start_time");
     let stop_time: Option<String> = row.get("stop_time");
     let exit_code: Option<i32> = row.get("exit_code");
+    let build_profile: Option<String> = row.get("build_profile");
+    let build_duration_ms: Option<i64> = row.get("build_duration_ms");
+    let binary_path: Option<String> = row.get("binary_path");
+    let git_commit: Option<String> = row.get("git_commit");
+    let sha256: Option<String> = row.get("binary_sha256");
+    let build = build_profile
+        .zip(binary_path)
+        .map(|(profile, binary_path)| BuildInfo {
+            profile,
+            binary_path: binary_path.into(),
+            duration_ms: build_duration_ms.unwrap_or(0) as u64,
+            git_commit,
+            sha256,
+        });
 
     let status: AppStatus = status_str.parse()?;
 
@@ -164,6 +220,7 @@ fn row_to_run_record(row: &sqlx::sqlite::SqliteRow) -> Result<RunRecord> {
         start_time,
         stop_time,
         exit_code,
+        build,
     })
 }
 
@@ -222,4 +279,39 @@ mod tests {
         assert_eq!(latest.status, AppStatus::Stopped);
         assert_eq!(latest.exit_code, Some(0));
     }
+
+    #[tokio::test]
+    async fn test_build_metadata_and_finish() {
+        let (db, app_id, _dir) = setup_db_with_app().await;
+        let runs = db.runs();
+
+        let build = BuildInfo {
+            profile: "release".to_string(),
+            binary_path: PathBuf::from("/srv/api/target/release/api"),
+            duration_ms: 4200,
+            git_commit: Some("0123456789abcdef".to_string()),
+            sha256: Some(
+                "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string(),
+            ),
+        };
+        let mut state = RunState::running(app_id, 12345);
+        state.build = Some(build.clone());
+        runs.insert(app_id, &state).await.unwrap();
+
+        runs.finish_latest(app_id, AppStatus::Errored, Some(101))
+            .await
+            .unwrap();
+        let latest = runs.get_latest(app_id).await.unwrap().unwrap();
+        assert_eq!(latest.build, Some(build));
+        assert_eq!(latest.status, AppStatus::Errored);
+        assert_eq!(latest.exit_code, Some(101));
+        assert!(latest.stop_time.is_some());
+
+        // A closed run is left alone
+        runs.finish_latest(app_id, AppStatus::Stopped, Some(0))
+            .await
+            .unwrap();
+        let latest = runs.get_latest(app_id).await.unwrap().unwrap();
+        assert_eq!(latest.status, AppStatus::Errored);
+    }
 }
@@ -4,7 +4,8 @@
 pub const SCHEMA: &str = r#"
 CREATE TABLE IF NOT EXISTS apps (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
-    name TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    namespace TEXT NOT NULL DEFAULT 'default',
     mode TEXT NOT NULL,
     command TEXT NOT NULL,
     args TEXT NOT NULL DEFAULT '[]',
@@ -17,7 +18,19 @@ CREATE TABLE IF NOT EXISTS apps (
     restart_delay_ms INTEGER NOT NULL DEFAULT 500,
     crash_window_secs INTEGER NOT NULL DEFAULT 60,
     kill_timeout_ms INTEGER NOT NULL DEFAULT 3000,
-    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    desired_state TEXT NOT NULL DEFAULT 'running',
+    tags TEXT NOT NULL DEFAULT '[]',
+    max_memory_mb INTEGER,
+    max_uptime_secs INTEGER,
+    port INTEGER,
+    health_check TEXT,
+    UNIQUE (namespace, name)
+);
+
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
 );
 
 CREATE TABLE IF NOT EXISTS runs (
@@ -29,12 +42,42 @@ CREATE TABLE IF NOT EXISTS runs (
     start_time TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
     stop_time TEXT,
     exit_code INTEGER,
+    build_profile TEXT,
+    build_duration_ms INTEGER,
+    binary_path TEXT,
+    git_commit TEXT,
+    binary_sha256 TEXT,
     FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
 );
 
 CREATE INDEX IF NOT EXISTS idx_runs_app_id ON runs(app_id);
 CREATE INDEX IF NOT EXISTS idx_runs_status ON runs(status);
 
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    app_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    reason TEXT,
+    exit_code INTEGER,
+    timestamp TEXT NOT NULL,
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_app_id ON events(app_id);
+
+CREATE TABLE IF NOT EXISTS app_stats (
+    app_id INTEGER PRIMARY KEY,
+    total_restarts INTEGER NOT NULL DEFAULT 0,
+    crashes INTEGER NOT NULL DEFAULT 0,
+    last_crash_at TEXT,
+    downtime_secs INTEGER NOT NULL DEFAULT 0,
+    uptime_secs INTEGER NOT NULL DEFAULT 0,
+    up_since TEXT,
+    down_since TEXT,
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
+);
+
 CREATE TABLE IF NOT EXISTS metrics (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     app_id INTEGER NOT NULL,
@@ -46,4 +89,82 @@ CREATE TABLE IF NOT EXISTS metrics (
 
 CREATE INDEX IF NOT EXISTS idx_metrics_app_id ON metrics(app_id);
 CREATE INDEX IF NOT EXISTS idx_metrics_timestamp ON metrics(timestamp);
+
+CREATE TABLE IF NOT EXISTS audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    client TEXT,
+    action TEXT NOT NULL,
+    target TEXT,
+    success INTEGER NOT NULL,
+    result TEXT NOT NULL
+);
+"#;
+
+/// Rebuild an `apps` table created before namespaces existed.
+///
+/// SQLite cannot drop the old `UNIQUE (name)` constraint in place, so the
+/// table is copied. Must run with foreign keys disabled so the runs and
+/// metrics rows survive the drop.
+pub const MIGRATE_APPS_NAMESPACE: &str = r#"
+CREATE TABLE apps_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    namespace TEXT NOT NULL DEFAULT 'default',
+    mode TEXT NOT NULL,
+    command TEXT NOT NULL,
+    args TEXT NOT NULL DEFAULT '[]',
+    cwd TEXT NOT NULL,
+    env TEXT NOT NULL DEFAULT '{}',
+    watch INTEGER NOT NULL DEFAULT 0,
+    ignore_patterns TEXT NOT NULL DEFAULT '[]',
+    auto_restart INTEGER NOT NULL DEFAULT 1,
+    max_restarts INTEGER NOT NULL DEFAULT 15,
+    restart_delay_ms INTEGER NOT NULL DEFAULT 500,
+    crash_window_secs INTEGER NOT NULL DEFAULT 60,
+    kill_timeout_ms INTEGER NOT NULL DEFAULT 3000,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (namespace, name)
+);
+
+INSERT INTO apps_new (
+    id, name, mode, command, args, cwd, env, watch, ignore_patterns,
+    auto_restart, max_restarts, restart_delay_ms, crash_window_secs,
+    kill_timeout_ms, created_at
+)
+SELECT id, name, mode, command, args, cwd, env, watch, ignore_patterns,
+       auto_restart, max_restarts, restart_delay_ms, crash_window_secs,
+       kill_timeout_ms, created_at
+FROM apps;
+
+DROP TABLE apps;
+ALTER TABLE apps_new RENAME TO apps;
 "#;
+
+/// Add the desired state (running/stopped) to an `apps` table that lacks it
+pub const MIGRATE_APPS_DESIRED_STATE: &str =
+    "ALTER TABLE apps ADD COLUMN desired_state TEXT NOT NULL DEFAULT 'running'";
+
+/// Add the tags (JSON list) to an `apps` table that lacks them
+pub const MIGRATE_APPS_TAGS: &str = "ALTER TABLE apps ADD COLUMN tags TEXT NOT NULL DEFAULT '[]'";
+
+/// Add the settings `oxidepm update` changes (memory and uptime limits,
+/// port, health check as JSON) to an `apps` table that lacks them
+pub const MIGRATE_APPS_LIMITS: &[&str] = &[
+    "ALTER TABLE apps ADD COLUMN max_memory_mb INTEGER",
+    "ALTER TABLE apps ADD COLUMN max_uptime_secs INTEGER",
+    "ALTER TABLE apps ADD COLUMN port INTEGER",
+    "ALTER TABLE apps ADD COLUMN health_check TEXT",
+];
+
+/// Add build metadata to a `runs` table that lacks it
+pub const MIGRATE_RUNS_BUILD: &[&str] = &[
+    "ALTER TABLE runs ADD COLUMN build_profile TEXT",
+    "ALTER TABLE runs ADD COLUMN build_duration_ms INTEGER",
+    "ALTER TABLE runs ADD COLUMN binary_path TEXT",
+    "ALTER TABLE runs ADD COLUMN git_commit TEXT",
+];
+
+/// Add the binary checksum to a `runs` table that lacks it
+pub const MIGRATE_RUNS_SHA256: &str = "ALTER TABLE runs ADD COLUMN binary_sha256 TEXT";
@@ -0,0 +1,406 @@
+//! SQLite storage - the default, one database file per host
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use oxidepm_core::{constants, DbHealth, Error, Result};
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePool, SqlitePoolOptions,
+    SqliteSynchronous,
+};
+use sqlx::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use crate::metrics::MetricsRepository;
+use crate::schema;
+use crate::storage::{
+    AppStore, AuditStore, EventStore, MetricsStore, RunStore, StatsStore, Storage,
+};
+use crate::{AppsRepository, AuditRepository, EventsRepository, RunsRepository, StatsRepository};
+
+/// Storage in a local SQLite file
+pub struct SqliteStorage {
+    pool: SqlitePool,
+    path: PathBuf,
+}
+
+impl SqliteStorage {
+    /// Open (or create) the database file and bring its schema up to date
+    pub async fn open(path: &Path) -> Result<Self> {
+        // Ensure parent directory exists
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| Error::DbError(e.to_string()))?;
+        }
+
+        info!("Connecting to database: {}", path.display());
+
+        // WAL lets readers work while the daemon writes; the busy timeout
+        // covers the remaining writer-writer contention
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(Duration::from_millis(constants::DB_BUSY_TIMEOUT_MS));
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        // Set database file permissions to owner-only (0600) for security,
+        // including the WAL files that hold recent writes
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for suffix in ["", "-wal", "-shm"] {
+                let mut file = path.as_os_str().to_owned();
+                file.push(suffix);
+                let file = Path::new(&file);
+                if !file.exists() {
+                    continue;
+                }
+                if let Err(e) =
+                    std::fs::set_permissions(file, std::fs::Permissions::from_mode(0o600))
+                {
+                    tracing::warn!("Failed to set database file permissions: {}", e);
+                }
+            }
+        }
+
+        // Initialize schema
+        sqlx::query(schema::SCHEMA)
+            .execute(&pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+        migrate(&pool).await?;
+
+        info!("Database initialized");
+        Ok(Self {
+            pool,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Get the connection pool
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStorage {
+    fn backend(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn apps(&self) -> Arc<dyn AppStore> {
+        Arc::new(AppsRepository::new(self.pool.clone()))
+    }
+
+    fn runs(&self) -> Arc<dyn RunStore> {
+        Arc::new(RunsRepository::new(self.pool.clone()))
+    }
+
+    fn events(&self) -> Arc<dyn EventStore> {
+        Arc::new(EventsRepository::new(self.pool.clone()))
+    }
+
+    fn stats(&self) -> Arc<dyn StatsStore> {
+        Arc::new(StatsRepository::new(self.pool.clone()))
+    }
+
+    fn metrics(&self) -> Arc<dyn MetricsStore> {
+        Arc::new(MetricsRepository::new(self.pool.clone()))
+    }
+
+    fn audit(&self) -> Arc<dyn AuditStore> {
+        Arc::new(AuditRepository::new(self.pool.clone()))
+    }
+
+    async fn optimize(&self) -> Result<()> {
+        sqlx::query("PRAGMA optimize")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn vacuum(&self) -> Result<()> {
+        sqlx::query("VACUUM")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(format!("VACUUM failed: {}", e)))?;
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+        sqlx::query("INSERT OR REPLACE INTO meta (key, value) VALUES ('last_vacuum', ?)")
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn last_vacuum(&self) -> Result<Option<DateTime<Utc>>> {
+        let row: Option<(String,)> =
+            sqlx::query_as("SELECT value FROM meta WHERE key = 'last_vacuum'")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| Error::DbError(e.to_string()))?;
+        Ok(row
+            .and_then(|(value,)| DateTime::parse_from_rfc3339(&value).ok())
+            .map(|at| at.with_timezone(&Utc)))
+    }
+
+    async fn health(&self) -> Result<DbHealth> {
+        let pragma = |name: &'static str| async move {
+            sqlx::query_as::<_, (i64,)>(name)
+                .fetch_one(&self.pool)
+                .await
+                .map(|(value,)| value.max(0) as u64)
+                .map_err(|e| Error::DbError(e.to_string()))
+        };
+        let page_size = pragma("PRAGMA page_size").await?;
+        let free_pages = pragma("PRAGMA freelist_count").await?;
+        let (journal_mode,): (String,) = sqlx::query_as("PRAGMA journal_mode")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        let file_size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let mut wal_path = self.path.clone().into_os_string();
+        wal_path.push("-wal");
+        Ok(DbHealth {
+            backend: self.backend().to_string(),
+            location: self.path.display().to_string(),
+            size_bytes: file_size(&self.path),
+            wal_bytes: file_size(Path::new(&wal_path)),
+            free_bytes: free_pages * page_size,
+            journal_mode: Some(journal_mode),
+            last_vacuum: self.last_vacuum().await?,
+        })
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+/// Check that the existing database at `path` can be written now: it opens
+/// read-write and no other connection holds the write lock for longer than `wait`
+pub async fn check_writable(path: &Path, wait: Duration) -> Result<()> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(false)
+        .busy_timeout(wait);
+    let result = async {
+        let mut conn = SqliteConnection::connect_with(&options).await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut conn).await?;
+        sqlx::query("ROLLBACK").execute(&mut conn).await?;
+        conn.close().await
+    }
+    .await;
+    result.map_err(|e| Error::DbError(e.to_string()))
+}
+
+/// Bring databases created by older releases up to the current schema
+async fn migrate(pool: &SqlitePool) -> Result<()> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('apps')")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+    let has_column = |column: &str| columns.iter().any(|(name,)| name == column);
+
+    if !has_column("namespace") {
+        migrate_namespace(pool).await?;
+    }
+    if !has_column("desired_state") {
+        info!("Adding desired state to apps table");
+        sqlx::query(schema::MIGRATE_APPS_DESIRED_STATE)
+            .execute(pool)
+            .await
+            .map_err(|e| Error::DbError(format!("Failed to migrate apps table: {}", e)))?;
+    }
+    if !has_column("tags") {
+        info!("Adding tags to apps table");
+        sqlx::query(schema::MIGRATE_APPS_TAGS)
+            .execute(pool)
+            .await
+            .map_err(|e| Error::DbError(format!("Failed to migrate apps table: {}", e)))?;
+    }
+    if !has_column("max_memory_mb") {
+        info!("Adding limits, port and health check to apps table");
+        for statement in schema::MIGRATE_APPS_LIMITS {
+            sqlx::query(statement)
+                .execute(pool)
+                .await
+                .map_err(|e| Error::DbError(format!("Failed to migrate apps table: {}", e)))?;
+        }
+    }
+
+    let run_columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('runs')")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+    if !run_columns.iter().any(|(name,)| name == "build_profile") {
+        info!("Adding build metadata to runs table");
+        for statement in schema::MIGRATE_RUNS_BUILD {
+            sqlx::query(statement)
+                .execute(pool)
+                .await
+                .map_err(|e| Error::DbError(format!("Failed to migrate runs table: {}", e)))?;
+        }
+    }
+    if !run_columns.iter().any(|(name,)| name == "binary_sha256") {
+        info!("Adding binary checksums to runs table");
+        sqlx::query(schema::MIGRATE_RUNS_SHA256)
+            .execute(pool)
+            .await
+            .map_err(|e| Error::DbError(format!("Failed to migrate runs table: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Rebuild the apps table with the namespace column
+async fn migrate_namespace(pool: &SqlitePool) -> Result<()> {
+    info!("Migrating apps table to namespaced names");
+    // PRAGMA foreign_keys is per connection and ignored inside a transaction
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+    let result = async {
+        let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+        sqlx::query(schema::MIGRATE_APPS_NAMESPACE)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+    .await;
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+
+    result.map_err(|e| Error::DbError(format!("Failed to migrate apps table: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_wal_and_maintenance() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db = SqliteStorage::open(&db_path).await.unwrap();
+
+        let health = db.health().await.unwrap();
+        assert_eq!(health.journal_mode.as_deref(), Some("wal"));
+        assert!(health.size_bytes > 0);
+        assert_eq!(health.last_vacuum, None);
+
+        db.optimize().await.unwrap();
+        db.vacuum().await.unwrap();
+        let health = db.health().await.unwrap();
+        assert!(health.last_vacuum.is_some());
+        assert_eq!(health.free_bytes, 0);
+        db.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_migrates_apps_without_namespace() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("old.db");
+
+        // Database as written by a release without namespaces
+        let pool = SqlitePoolOptions::new()
+            .connect(&format!("sqlite:{}?mode=rwc", db_path.display()))
+            .await
+            .unwrap();
+        let old_schema = schema::SCHEMA
+            .replace("    namespace TEXT NOT NULL DEFAULT 'default',\n", "")
+            .replace("    desired_state TEXT NOT NULL DEFAULT 'running',\n", "")
+            .replace("    tags TEXT NOT NULL DEFAULT '[]',\n", "")
+            .replace(
+                "    max_memory_mb INTEGER,\n    max_uptime_secs INTEGER,\n    port INTEGER,\n    health_check TEXT,\n",
+                "",
+            )
+            .replace(
+                "    build_profile TEXT,\n    build_duration_ms INTEGER,\n",
+                "",
+            )
+            .replace("    binary_path TEXT,\n    git_commit TEXT,\n", "")
+            .replace("name TEXT NOT NULL,", "name TEXT NOT NULL UNIQUE,")
+            .replace(",\n    UNIQUE (namespace, name)", "");
+        sqlx::query(&old_schema).execute(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO apps (name, mode, command, cwd) VALUES ('api', 'cmd', 'true', '/')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO runs (app_id, status) VALUES (1, 'running')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let db = SqliteStorage::open(&db_path).await.unwrap();
+        let app = db
+            .apps()
+            .get_by_name("default", "api")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(app.namespace, "default");
+        assert_eq!(app.max_memory_mb, None);
+        assert_eq!(
+            db.apps().desired_states().await.unwrap().get(&app.id),
+            Some(&oxidepm_core::DesiredState::Running)
+        );
+        let runs: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM runs")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(runs.0, 1);
+        let run = db.runs().get_latest(app.id).await.unwrap().unwrap();
+        assert_eq!(run.build, None);
+
+        // The same name is now allowed in another namespace
+        let mut other = app.clone();
+        other.namespace = "shop".to_string();
+        db.apps().insert(&other).await.unwrap();
+        db.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_check_writable() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let wait = Duration::from_millis(50);
+        assert!(check_writable(&db_path, wait).await.is_err());
+
+        let db = SqliteStorage::open(&db_path).await.unwrap();
+        check_writable(&db_path, wait).await.unwrap();
+
+        // Another connection holding the write lock
+        let mut conn = db.pool().acquire().await.unwrap();
+        sqlx::query("BEGIN IMMEDIATE")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        let err = check_writable(&db_path, wait).await.unwrap_err();
+        assert!(err.to_string().contains("locked"));
+        sqlx::query("ROLLBACK").execute(&mut *conn).await.unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+//! Stats repository - lifetime restart and availability figures
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use oxidepm_core::{AppEvent, AppStats, Error, EventKind, Result};
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+
+use crate::events::{format_timestamp, parse_timestamp};
+use crate::storage::StatsStore;
+
+/// Repository for per-app stats, maintained from supervisor events
+#[derive(Clone)]
+pub struct StatsRepository {
+    pool: SqlitePool,
+}
+
+impl StatsRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl StatsStore for StatsRepository {
+    /// Fold an event into the app's counters.
+    ///
+    /// Downtime runs from a crash until the app is started or stopped again;
+    /// running time (for MTBF) runs from a start until a stop or crash.
+    async fn record(&self, event: &AppEvent) -> Result<()> {
+        let set = match event.kind {
+            EventKind::Started => format!(
+                "downtime_secs = downtime_secs + {}, down_since = NULL, up_since = ?",
+                elapsed_since("down_since")
+            ),
+            EventKind::Stopped => format!(
+                "uptime_secs = uptime_secs + {}, up_since = NULL, \
+                 downtime_secs = downtime_secs + {}, down_since = NULL",
+                elapsed_since("up_since"),
+                elapsed_since("down_since")
+            ),
+            EventKind::Crashed => format!(
+                "uptime_secs = uptime_secs + {}, up_since = NULL, \
+                 crashes = crashes + 1, last_crash_at = ?, down_since = ?",
+                elapsed_since("up_since")
+            ),
+            EventKind::Restarted => "total_restarts = total_restarts + 1".to_string(),
+            EventKind::Reloaded
+            | EventKind::Unhealthy
+            | EventKind::NotReady
+            | EventKind::Ready
+            | EventKind::Paused
+            | EventKind::Resumed
+            | EventKind::Signalled => return Ok(()),
+        };
+
+        sqlx::query("INSERT INTO app_stats (app_id) VALUES (?) ON CONFLICT (app_id) DO NOTHING")
+            .bind(event.app_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        // Every placeholder in the SET clause takes the event time
+        let sql = format!("UPDATE app_stats SET {} WHERE app_id = ?", set);
+        let timestamp = format_timestamp(&event.timestamp);
+        let mut query = sqlx::query(&sql);
+        for _ in 0..set.matches('?').count() {
+            query = query.bind(&timestamp);
+        }
+        query
+            .bind(event.app_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Error::DbError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, app_id: u32) -> Result<AppStats> {
+        let row = sqlx::query(
+            r#"
+            SELECT total_restarts, crashes, last_crash_at, downtime_secs,
+                   uptime_secs, up_since, down_since
+            FROM app_stats
+            WHERE app_id = ?
+            "#,
+        )
+        .bind(app_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::DbError(e.to_string()))?;
+
+        match row {
+            Some(row) => StatsRow::from_row(&row).to_stats(Utc::now()),
+            None => Ok(AppStats::default()),
+        }
+    }
+}
+
+/// Whole seconds from the timestamp in `column` to the bound event time.
+/// julianday() is a float, so round rather than truncate.
+fn elapsed_since(column: &str) -> String {
+    format!(
+        "COALESCE(MAX(0, CAST(ROUND((julianday(?) - julianday({})) * 86400) AS INTEGER)), 0)",
+        column
+    )
+}
+
+/// Stored stats counters, for backends that update them outside SQL
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StatsRow {
+    pub total_restarts: i64,
+    pub crashes: i64,
+    pub last_crash_at: Option<String>,
+    pub downtime_secs: i64,
+    pub uptime_secs: i64,
+    pub up_since: Option<String>,
+    pub down_since: Option<String>,
+}
+
+impl StatsRow {
+    pub fn from_row<'r, R>(row: &'r R) -> Self
+    where
+        R: Row,
+        for<'a> &'a str: sqlx::ColumnIndex<R>,
+        i64: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+        String: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    {
+        Self {
+            total_restarts: row.get("total_restarts"),
+            crashes: row.get("crashes"),
+            last_crash_at: row.get("last_crash_at"),
+            downtime_secs: row.get("downtime_secs"),
+            uptime_secs: row.get("uptime_secs"),
+            up_since: row.get("up_since"),
+            down_since: row.get("down_since"),
+        }
+    }
+
+    /// Fold an event in, the same way `StatsRepository::record` does in SQL
+    pub fn apply(&mut self, event: &AppEvent) -> Result<()> {
+        let timestamp = format_timestamp(&event.timestamp);
+        // Whole seconds from `since` to the event, rounded like julianday()
+        let elapsed = |since: Option<String>| -> Result<i64> {
+            match since {
+                Some(since) => {
+                    let millis = (event.timestamp - parse_timestamp(&since)?).num_milliseconds();
+                    Ok((millis as f64 / 1000.0).round().max(0.0) as i64)
+                }
+                None => Ok(0),
+            }
+        };
+        match event.kind {
+            EventKind::Started => {
+                self.downtime_secs += elapsed(self.down_since.take())?;
+                self.up_since = Some(timestamp);
+            }
+            EventKind::Stopped => {
+                self.uptime_secs += elapsed(self.up_since.take())?;
+                self.downtime_secs += elapsed(self.down_since.take())?;
+            }
+            EventKind::Crashed => {
+                self.uptime_secs += elapsed(self.up_since.take())?;
+                self.crashes += 1;
+                self.last_crash_at = Some(timestamp.clone());
+                self.down_since = Some(timestamp);
+            }
+            EventKind::Restarted => self.total_restarts += 1,
+            EventKind::Reloaded
+            | EventKind::Unhealthy
+            | EventKind::NotReady
+            | EventKind::Ready
+            | EventKind::Paused
+            | EventKind::Resumed
+            | EventKind::Signalled => {}
+        }
+        Ok(())
+    }
+
+    /// Stats as of `now`, counting any ongoing run or outage
+    pub fn to_stats(&self, now: DateTime<Utc>) -> Result<AppStats> {
+        let ongoing = |since: &Option<String>| -> Result<u64> {
+            match since {
+                Some(since) => Ok((now - parse_timestamp(since)?).num_seconds().max(0) as u64),
+                None => Ok(0),
+            }
+        };
+        let uptime_secs = self.uptime_secs as u64 + ongoing(&self.up_since)?;
+        let crashes = self.crashes as u32;
+
+        Ok(AppStats {
+            total_restarts: self.total_restarts as u32,
+            crashes,
+            last_crash_at: self
+                .last_crash_at
+                .as_deref()
+                .map(parse_timestamp)
+                .transpose()?,
+            downtime_secs: self.downtime_secs as u64 + ongoing(&self.down_since)?,
+            mtbf_secs: (crashes > 0).then(|| uptime_secs / crashes as u64),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Database;
+    use chrono::{Duration, SubsecRound, Utc};
+    use oxidepm_core::{AppEvent, AppMode, AppSpec, EventKind};
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_stats_from_events() {
+        let dir = tempdir().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).await.unwrap();
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Node,
+            "app.js".to_string(),
+            PathBuf::from("/"),
+        );
+        let id = db.apps().insert(&spec).await.unwrap();
+
+        assert_eq!(db.stats().get(id).await.unwrap().total_restarts, 0);
+
+        let t0 = Utc::now().trunc_subsecs(0) - Duration::seconds(1000);
+        let at = |kind, secs| {
+            let mut event = AppEvent::new(id, "api", kind);
+            event.timestamp = t0 + Duration::seconds(secs);
+            event
+        };
+        let events = db.events();
+        // Up 100s, crash, down 30s, up 70s, stopped 100s, then running since t0+300
+        events.insert(&at(EventKind::Started, 0)).await.unwrap();
+        events.insert(&at(EventKind::Crashed, 100)).await.unwrap();
+        events.insert(&at(EventKind::Restarted, 130)).await.unwrap();
+        events.insert(&at(EventKind::Started, 130)).await.unwrap();
+        events.insert(&at(EventKind::Stopped, 200)).await.unwrap();
+        events.insert(&at(EventKind::Started, 300)).await.unwrap();
+
+        let stats = db.stats().get(id).await.unwrap();
+        assert_eq!(stats.total_restarts, 1);
+        assert_eq!(stats.crashes, 1);
+        assert_eq!(stats.last_crash_at, Some(t0 + Duration::seconds(100)));
+        assert_eq!(stats.downtime_secs, 30);
+        let mtbf = stats.mtbf_secs.unwrap();
+        assert!((870..880).contains(&mtbf), "mtbf was {}", mtbf);
+    }
+}
@@ -0,0 +1,177 @@
+//! Storage traits - the persistence API every backend implements
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use oxidepm_core::{
+    AppEvent, AppSpec, AppStats, AppStatus, AuditEntry, DbHealth, DesiredState, Result, RunState,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::metrics::MetricsSnapshot;
+use crate::runs::RunRecord;
+
+/// A persistence backend (SQLite, Postgres)
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Backend name, e.g. "sqlite"
+    fn backend(&self) -> &'static str;
+
+    fn apps(&self) -> Arc<dyn AppStore>;
+    fn runs(&self) -> Arc<dyn RunStore>;
+    fn events(&self) -> Arc<dyn EventStore>;
+    fn stats(&self) -> Arc<dyn StatsStore>;
+    fn metrics(&self) -> Arc<dyn MetricsStore>;
+    fn audit(&self) -> Arc<dyn AuditStore>;
+
+    /// Refresh the query planner statistics
+    async fn optimize(&self) -> Result<()>;
+
+    /// Give unused space back and record when this happened
+    async fn vacuum(&self) -> Result<()>;
+
+    /// When the storage was last vacuumed, if ever
+    async fn last_vacuum(&self) -> Result<Option<DateTime<Utc>>>;
+
+    /// Size and maintenance state
+    async fn health(&self) -> Result<DbHealth>;
+
+    /// Close the connections
+    async fn close(&self);
+}
+
+/// App specs and their desired state
+#[async_trait]
+pub trait AppStore: Send + Sync {
+    /// Insert a new app
+    async fn insert(&self, spec: &AppSpec) -> Result<u32>;
+
+    /// Insert many apps in a single transaction, returning their IDs in order
+    async fn insert_many(&self, specs: &[AppSpec]) -> Result<Vec<u32>>;
+
+    /// Get app by ID
+    async fn get_by_id(&self, id: u32) -> Result<Option<AppSpec>>;
+
+    /// Get app by name within a namespace
+    async fn get_by_name(&self, namespace: &str, name: &str) -> Result<Option<AppSpec>>;
+
+    /// Get apps with this name in any namespace
+    async fn find_by_name(&self, name: &str) -> Result<Vec<AppSpec>>;
+
+    /// Get all apps
+    async fn get_all(&self) -> Result<Vec<AppSpec>>;
+
+    /// Rewrite the stored fields of an app, including its name, from `spec`
+    async fn update(&self, spec: &AppSpec) -> Result<bool>;
+
+    /// Replace the stored environment of an app
+    async fn update_env(&self, id: u32, env: &HashMap<String, String>) -> Result<bool>;
+
+    /// Record whether an app should be running
+    async fn set_desired_state(&self, id: u32, state: DesiredState) -> Result<bool>;
+
+    /// Desired state of every app, by ID
+    async fn desired_states(&self) -> Result<HashMap<u32, DesiredState>>;
+
+    /// Delete app by ID
+    async fn delete(&self, id: u32) -> Result<bool>;
+
+    /// Delete an app together with its run history, events, stats and
+    /// metrics, in one transaction
+    async fn delete_with_history(&self, id: u32) -> Result<bool>;
+
+    /// Delete app by name within a namespace
+    async fn delete_by_name(&self, namespace: &str, name: &str) -> Result<bool>;
+
+    /// Delete all apps
+    async fn delete_all(&self) -> Result<u64>;
+
+    /// Check if app exists by name within a namespace
+    async fn exists(&self, namespace: &str, name: &str) -> Result<bool>;
+
+    /// Get next available ID
+    async fn next_id(&self) -> Result<u32>;
+}
+
+/// Run history
+#[async_trait]
+pub trait RunStore: Send + Sync {
+    /// Insert a new run record
+    async fn insert(&self, app_id: u32, state: &RunState) -> Result<u32>;
+
+    /// Update run status
+    async fn update_status(&self, run_id: u32, status: AppStatus) -> Result<()>;
+
+    /// Update run on stop
+    async fn update_stop(&self, run_id: u32, exit_code: Option<i32>) -> Result<()>;
+
+    /// Close the app's open run, if any, with its final status
+    async fn finish_latest(
+        &self,
+        app_id: u32,
+        status: AppStatus,
+        exit_code: Option<i32>,
+    ) -> Result<()>;
+
+    /// Increment restart count
+    async fn increment_restarts(&self, run_id: u32) -> Result<()>;
+
+    /// Get latest run for an app
+    async fn get_latest(&self, app_id: u32) -> Result<Option<RunRecord>>;
+
+    /// Get the latest `limit` runs for an app, newest first
+    async fn get_by_app(&self, app_id: u32, limit: usize) -> Result<Vec<RunRecord>>;
+
+    /// Delete all runs for an app
+    async fn delete_by_app(&self, app_id: u32) -> Result<u64>;
+}
+
+/// Supervisor events
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    /// Record an event and fold it into the app's stats, keeping only the
+    /// newest `EVENTS_RETENTION` events per app
+    async fn insert(&self, event: &AppEvent) -> Result<u32>;
+
+    /// Latest `limit` events across the given apps, oldest first
+    async fn get_by_apps(&self, app_ids: &[u32], limit: usize) -> Result<Vec<AppEvent>>;
+}
+
+/// Control requests, kept for review on shared machines
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    /// Append an entry; entries are never updated or removed
+    async fn insert(&self, entry: &AuditEntry) -> Result<()>;
+
+    /// Latest `limit` entries, oldest first
+    async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>>;
+}
+
+/// Per-app lifetime stats, maintained from supervisor events
+#[async_trait]
+pub trait StatsStore: Send + Sync {
+    /// Fold an event into the app's counters
+    async fn record(&self, event: &AppEvent) -> Result<()>;
+
+    /// Stats for an app as of now, counting any ongoing run or outage
+    async fn get(&self, app_id: u32) -> Result<AppStats>;
+}
+
+/// CPU/memory samples
+#[async_trait]
+pub trait MetricsStore: Send + Sync {
+    /// Insert a metrics snapshot
+    async fn insert(&self, app_id: u32, cpu_percent: f32, memory_bytes: u64) -> Result<()>;
+
+    /// Get latest metrics for an app
+    async fn get_latest(&self, app_id: u32) -> Result<Option<MetricsSnapshot>>;
+
+    /// Get metrics history for an app, newest first
+    async fn get_history(&self, app_id: u32, limit: usize) -> Result<Vec<MetricsSnapshot>>;
+
+    /// Cleanup old metrics (keep last N per app)
+    async fn cleanup(&self, keep_per_app: usize) -> Result<u64>;
+
+    /// Delete all metrics for an app
+    async fn delete_by_app(&self, app_id: u32) -> Result<u64>;
+}
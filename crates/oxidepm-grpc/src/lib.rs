@@ -0,0 +1,587 @@
+//! OxidePM gRPC API Server
+//!
+//! Exposes the daemon's IPC protocol over gRPC (see `proto/oxidepm.proto`) so
+//! orchestration tools can drive it with generated clients in any language.
+
+use oxidepm_core::{AppEvent, AppInfo, AppMode, AppOpOutcome, AppOpResult, AppSpec, Selector};
+use oxidepm_ipc::{IpcClient, Request, Response};
+use oxidepm_logs::LogReader;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::MetadataValue;
+use tonic::{Code, Status};
+use tracing::info;
+
+pub mod pb {
+    tonic::include_proto!("oxidepm.v1");
+}
+
+use pb::process_manager_server::{ProcessManager, ProcessManagerServer};
+
+/// Lines sent before following when a logs request does not set them
+const DEFAULT_LOG_LINES: usize = 100;
+
+/// Events sent before following when an events request does not set a limit
+const DEFAULT_EVENTS_LIMIT: usize = 50;
+
+/// How often the daemon is polled for new events while following
+const EVENTS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Buffered messages per streaming call
+const STREAM_BUFFER: usize = 64;
+
+/// gRPC service forwarding every call to the daemon over IPC
+#[derive(Clone)]
+pub struct GrpcService {
+    client: Arc<IpcClient>,
+}
+
+impl GrpcService {
+    pub fn new(socket_path: PathBuf) -> Self {
+        Self {
+            client: Arc::new(IpcClient::new(socket_path).with_client_name("grpc")),
+        }
+    }
+
+    /// Send a request, turning daemon errors into gRPC statuses
+    async fn call(&self, request: &Request) -> Result<Response, Status> {
+        call(&self.client, request).await
+    }
+}
+
+async fn call(client: &IpcClient, request: &Request) -> Result<Response, Status> {
+    match client.send(request).await {
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => Err(error_status(message, code.as_deref(), hint.as_deref())),
+        Ok(response) => Ok(response),
+        Err(e) => Err(error_status(e.to_string(), Some(e.code()), None)),
+    }
+}
+
+/// Map a daemon error to a gRPC status; the daemon's own code and hint are kept
+/// in the `oxidepm-code` and `oxidepm-hint` metadata entries
+pub fn error_status(message: String, code: Option<&str>, hint: Option<&str>) -> Status {
+    let grpc_code = match code {
+        Some("app_not_found") => Code::NotFound,
+        Some("invalid_input") | Some("config_error") => Code::InvalidArgument,
+        Some("already_exists") | Some("port_conflict") => Code::AlreadyExists,
+        Some("daemon_unreachable") | Some("ipc_error") => Code::Unavailable,
+        Some("timeout") => Code::DeadlineExceeded,
+        Some("permission_denied") => Code::PermissionDenied,
+        _ => Code::Internal,
+    };
+
+    let mut status = Status::new(grpc_code, message);
+    for (key, value) in [("oxidepm-code", code), ("oxidepm-hint", hint)] {
+        if let Some(value) = value.and_then(|v| MetadataValue::try_from(v).ok()) {
+            status.metadata_mut().insert(key, value);
+        }
+    }
+    status
+}
+
+fn unexpected() -> Status {
+    Status::internal("Unexpected response from daemon")
+}
+
+impl From<AppInfo> for pb::App {
+    fn from(info: AppInfo) -> Self {
+        let AppInfo { spec, state } = info;
+        Self {
+            id: spec.id,
+            name: spec.name,
+            namespace: spec.namespace,
+            mode: spec.mode.to_string(),
+            status: state.status.to_string(),
+            pid: state.pid,
+            restarts: state.restarts,
+            cpu_percent: state.cpu_percent,
+            memory_bytes: state.memory_bytes,
+            uptime_secs: state.uptime_secs,
+            port: state.port.or(spec.port).map(u32::from),
+            tags: spec.tags,
+        }
+    }
+}
+
+impl From<AppOpResult> for pb::AppResult {
+    fn from(result: AppOpResult) -> Self {
+        let (outcome, error) = match result.outcome {
+            AppOpOutcome::Done => (pb::app_result::Outcome::Done, String::new()),
+            AppOpOutcome::Skipped => (pb::app_result::Outcome::Skipped, String::new()),
+            AppOpOutcome::AlreadyStopped => {
+                (pb::app_result::Outcome::AlreadyStopped, String::new())
+            }
+            AppOpOutcome::NotFound => (pb::app_result::Outcome::NotFound, String::new()),
+            AppOpOutcome::Failed { error } => (pb::app_result::Outcome::Failed, error),
+        };
+        Self {
+            id: result.id,
+            name: result.name,
+            outcome: outcome as i32,
+            error,
+        }
+    }
+}
+
+impl From<AppEvent> for pb::Event {
+    fn from(event: AppEvent) -> Self {
+        Self {
+            app_id: event.app_id,
+            name: event.name,
+            kind: event.kind.as_str().to_string(),
+            timestamp: event.timestamp.to_rfc3339(),
+            reason: event.reason,
+            exit_code: event.exit_code,
+        }
+    }
+}
+
+fn operation_response(count: usize, results: Vec<AppOpResult>) -> pb::OperationResponse {
+    pb::OperationResponse {
+        count: count as u32,
+        results: results.into_iter().map(Into::into).collect(),
+    }
+}
+
+/// Build the app spec for a start request
+#[allow(clippy::result_large_err)] // tonic::Status is what every handler returns
+fn start_spec(req: pb::StartRequest) -> Result<AppSpec, Status> {
+    if req.name.is_empty() || req.command.is_empty() {
+        return Err(Status::invalid_argument("name and command are required"));
+    }
+
+    let cwd = if req.cwd.is_empty() {
+        std::env::current_dir().map_err(|e| Status::internal(e.to_string()))?
+    } else {
+        PathBuf::from(req.cwd)
+    };
+    let mode = if req.mode.is_empty() {
+        AppMode::detect(&cwd).unwrap_or(AppMode::Cmd)
+    } else {
+        req.mode
+            .parse()
+            .map_err(|e: oxidepm_core::Error| Status::invalid_argument(e.to_string()))?
+    };
+
+    let mut spec = AppSpec::new(req.name, mode, req.command, cwd);
+    spec.args = req.args;
+    spec.env = req.env.into_iter().collect();
+    if req.instances > 0 {
+        spec.instances = req.instances;
+    }
+    if let Some(port) = req.port {
+        let port = u16::try_from(port)
+            .map_err(|_| Status::invalid_argument(format!("Invalid port: {}", port)))?;
+        spec.port = Some(port);
+    }
+    if !req.namespace.is_empty() {
+        spec.namespace = req.namespace;
+    }
+    spec.tags = req.tags;
+    spec.watch = req.watch;
+    Ok(spec)
+}
+
+#[tonic::async_trait]
+impl ProcessManager for GrpcService {
+    type LogsStream = ReceiverStream<Result<pb::LogLine, Status>>;
+    type EventsStream = ReceiverStream<Result<pb::Event, Status>>;
+
+    async fn ping(
+        &self,
+        _request: tonic::Request<pb::PingRequest>,
+    ) -> Result<tonic::Response<pb::PingResponse>, Status> {
+        match self.call(&Request::Ping).await? {
+            Response::Pong { heartbeat } => {
+                let mut reply = pb::PingResponse {
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    ..Default::default()
+                };
+                if let Some(heartbeat) = heartbeat {
+                    reply.daemon_version = heartbeat.version;
+                    reply.daemon_uptime_secs = heartbeat.uptime_secs;
+                    reply.managed = heartbeat.managed as u32;
+                    reply.running = heartbeat.running as u32;
+                }
+                Ok(tonic::Response::new(reply))
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn start(
+        &self,
+        request: tonic::Request<pb::StartRequest>,
+    ) -> Result<tonic::Response<pb::StartResponse>, Status> {
+        let spec = start_spec(request.into_inner())?;
+        match self
+            .call(&Request::Start {
+                spec,
+                progress: false,
+            })
+            .await?
+        {
+            Response::Started { id, name } => {
+                Ok(tonic::Response::new(pb::StartResponse { id, name }))
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn stop(
+        &self,
+        request: tonic::Request<pb::SelectorRequest>,
+    ) -> Result<tonic::Response<pb::OperationResponse>, Status> {
+        let selector = Selector::parse(&request.into_inner().selector);
+        match self
+            .call(&Request::Stop {
+                selector,
+                if_exists: false,
+            })
+            .await?
+        {
+            Response::Stopped { count, results } => {
+                Ok(tonic::Response::new(operation_response(count, results)))
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn restart(
+        &self,
+        request: tonic::Request<pb::SelectorRequest>,
+    ) -> Result<tonic::Response<pb::OperationResponse>, Status> {
+        let selector = Selector::parse(&request.into_inner().selector);
+        match self
+            .call(&Request::Restart {
+                selector,
+                reset_counter: false,
+                bulk: Default::default(),
+                if_exists: false,
+            })
+            .await?
+        {
+            Response::Restarted { count, results } => {
+                Ok(tonic::Response::new(operation_response(count, results)))
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn delete(
+        &self,
+        request: tonic::Request<pb::SelectorRequest>,
+    ) -> Result<tonic::Response<pb::OperationResponse>, Status> {
+        let selector = Selector::parse(&request.into_inner().selector);
+        match self
+            .call(&Request::Delete {
+                selector,
+                purge: false,
+            })
+            .await?
+        {
+            Response::Deleted { count, results } => {
+                Ok(tonic::Response::new(operation_response(count, results)))
+            }
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn status(
+        &self,
+        _request: tonic::Request<pb::StatusRequest>,
+    ) -> Result<tonic::Response<pb::StatusResponse>, Status> {
+        match self.call(&Request::Status).await? {
+            Response::Status {
+                apps,
+                supervision_paused,
+            } => Ok(tonic::Response::new(pb::StatusResponse {
+                apps: apps.into_iter().map(Into::into).collect(),
+                supervision_paused,
+            })),
+            _ => Err(unexpected()),
+        }
+    }
+
+    async fn logs(
+        &self,
+        request: tonic::Request<pb::LogsRequest>,
+    ) -> Result<tonic::Response<Self::LogsStream>, Status> {
+        let req = request.into_inner();
+        let selector = Selector::parse(&req.selector);
+        let (stdout, stderr) = (req.stdout_only, req.stderr_only);
+        let lines = match req.lines {
+            0 => DEFAULT_LOG_LINES,
+            n => n as usize,
+        };
+
+        let recent = match self
+            .call(&Request::Logs {
+                selector: selector.clone(),
+                lines,
+                follow: false,
+                stdout,
+                stderr,
+                since_secs: None,
+            })
+            .await?
+        {
+            Response::LogLines { lines } => lines,
+            _ => return Err(unexpected()),
+        };
+
+        // Resolve the files up front so a bad selector fails the call itself
+        let mut paths = Vec::new();
+        if req.follow {
+            let spec = match self.call(&Request::Show { selector }).await? {
+                Response::Show { app } => app.spec,
+                _ => return Err(unexpected()),
+            };
+            let (stdout_path, stderr_path) = (spec.stdout_log_path(), spec.stderr_log_path());
+            if stdout || !stderr {
+                paths.push(stdout_path.clone());
+            }
+            if (stderr || !stdout) && stderr_path != stdout_path {
+                paths.push(stderr_path);
+            }
+        }
+
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        tokio::spawn(async move {
+            for line in recent {
+                if tx.send(Ok(pb::LogLine { line })).await.is_err() {
+                    return;
+                }
+            }
+
+            for path in paths {
+                let mut follower = match LogReader::new(path).follow() {
+                    Ok(follower) => follower,
+                    Err(e) => {
+                        let _ = tx.send(Err(Status::internal(e.to_string()))).await;
+                        return;
+                    }
+                };
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    loop {
+                        tokio::select! {
+                            line = follower.recv() => match line {
+                                Some(line) => {
+                                    if tx.send(Ok(pb::LogLine { line })).await.is_err() {
+                                        break;
+                                    }
+                                }
+                                None => break,
+                            },
+                            _ = tx.closed() => break,
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn events(
+        &self,
+        request: tonic::Request<pb::EventsRequest>,
+    ) -> Result<tonic::Response<Self::EventsStream>, Status> {
+        let req = request.into_inner();
+        let selector = Selector::parse(&req.selector);
+        let limit = match req.limit {
+            0 => DEFAULT_EVENTS_LIMIT,
+            n => n as usize,
+        };
+
+        let events_request = Request::Events { selector, limit };
+        let recent = match self.call(&events_request).await? {
+            Response::Events { events } => events,
+            _ => return Err(unexpected()),
+        };
+
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let client = self.client.clone();
+        let follow = req.follow;
+        tokio::spawn(async move {
+            let mut last_seen = recent.last().map(|e| e.timestamp);
+            for event in recent {
+                if tx.send(Ok(event.into())).await.is_err() {
+                    return;
+                }
+            }
+            if !follow {
+                return;
+            }
+
+            // The daemon has no event subscription, so poll for anything newer
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(EVENTS_POLL_INTERVAL) => {}
+                    _ = tx.closed() => return,
+                }
+
+                let events = match call(&client, &events_request).await {
+                    Ok(Response::Events { events }) => events,
+                    Ok(_) => {
+                        let _ = tx.send(Err(unexpected())).await;
+                        return;
+                    }
+                    Err(status) => {
+                        let _ = tx.send(Err(status)).await;
+                        return;
+                    }
+                };
+
+                for event in events {
+                    if last_seen.is_some_and(|seen| event.timestamp <= seen) {
+                        continue;
+                    }
+                    last_seen = Some(event.timestamp);
+                    if tx.send(Ok(event.into())).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(tonic::Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Start the gRPC server
+pub async fn start_server(bind_addr: &str, socket_path: PathBuf) -> std::io::Result<()> {
+    let addr: SocketAddr = bind_addr
+        .parse()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let service = GrpcService::new(socket_path);
+
+    info!("Starting OxidePM gRPC API on {}", bind_addr);
+
+    tonic::transport::Server::builder()
+        .add_service(ProcessManagerServer::new(service))
+        .serve(addr)
+        .await
+        .map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::{AppStatus, EventKind, RunState};
+
+    #[test]
+    fn test_error_status_mapping() {
+        let status = error_status(
+            "App not found: api".into(),
+            Some("app_not_found"),
+            Some("Run 'oxidepm status'"),
+        );
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "App not found: api");
+        assert_eq!(
+            status.metadata().get("oxidepm-code").unwrap(),
+            "app_not_found"
+        );
+        assert_eq!(
+            status.metadata().get("oxidepm-hint").unwrap(),
+            "Run 'oxidepm status'"
+        );
+
+        assert_eq!(
+            error_status("x".into(), Some("invalid_input"), None).code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            error_status("x".into(), Some("daemon_unreachable"), None).code(),
+            Code::Unavailable
+        );
+        assert_eq!(error_status("x".into(), None, None).code(), Code::Internal);
+    }
+
+    #[test]
+    fn test_app_conversion() {
+        let mut spec = AppSpec::new(
+            "api".into(),
+            AppMode::Node,
+            "server.js".into(),
+            PathBuf::from("/srv"),
+        );
+        spec.id = 3;
+        spec.port = Some(3000);
+        spec.tags = vec!["web".into()];
+        let mut state = RunState::new(3);
+        state.status = AppStatus::Running;
+        state.pid = Some(4242);
+
+        let app = pb::App::from(AppInfo::new(spec, state));
+        assert_eq!(app.id, 3);
+        assert_eq!(app.mode, "node");
+        assert_eq!(app.status, "running");
+        assert_eq!(app.pid, Some(4242));
+        assert_eq!(app.port, Some(3000));
+        assert_eq!(app.tags, vec!["web".to_string()]);
+    }
+
+    #[test]
+    fn test_result_and_event_conversion() {
+        let result = pb::AppResult::from(AppOpResult {
+            id: 1,
+            name: "api".into(),
+            outcome: AppOpOutcome::Failed {
+                error: "boom".into(),
+            },
+        });
+        assert_eq!(result.outcome(), pb::app_result::Outcome::Failed);
+        assert_eq!(result.error, "boom");
+
+        let event =
+            pb::Event::from(AppEvent::new(1, "api", EventKind::Crashed).with_reason("health"));
+        assert_eq!(event.kind, "crashed");
+        assert_eq!(event.reason.as_deref(), Some("health"));
+        assert!(chrono::DateTime::parse_from_rfc3339(&event.timestamp).is_ok());
+    }
+
+    #[test]
+    fn test_start_spec() {
+        let req = pb::StartRequest {
+            name: "worker".into(),
+            command: "worker.sh".into(),
+            cwd: "/srv".into(),
+            mode: "cmd".into(),
+            instances: 2,
+            port: Some(8080),
+            ..Default::default()
+        };
+        let spec = start_spec(req).unwrap();
+        assert_eq!(spec.mode, AppMode::Cmd);
+        assert_eq!(spec.instances, 2);
+        assert_eq!(spec.port, Some(8080));
+
+        let bad_port = pb::StartRequest {
+            name: "worker".into(),
+            command: "worker.sh".into(),
+            port: Some(70000),
+            ..Default::default()
+        };
+        assert_eq!(
+            start_spec(bad_port).unwrap_err().code(),
+            Code::InvalidArgument
+        );
+
+        let missing = pb::StartRequest::default();
+        assert_eq!(
+            start_spec(missing).unwrap_err().code(),
+            Code::InvalidArgument
+        );
+    }
+}
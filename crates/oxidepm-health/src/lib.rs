@@ -1,11 +1,17 @@
 //! OxidePM Health Check System
 //!
-//! Provides HTTP endpoint and script-based health checks for processes.
+//! Provides HTTP endpoint, TCP connect and script-based health checks for
+//! processes, optionally matching the HTTP body or script stdout against a
+//! regex, runs the warm-up requests fired before an app counts as ready and
+//! checks the `wait_for` targets an app waits for before it is launched.
 
 use chrono::{DateTime, Utc};
-use oxidepm_core::HealthCheck;
+use oxidepm_core::{HealthCheck, WaitTarget, Warmup};
+use regex::Regex;
+use std::path::Path;
 use std::process::Stdio;
 use std::time::Duration;
+use tokio::net::TcpStream;
 use tokio::process::Command;
 use tokio::time::timeout;
 use tracing::{debug, warn};
@@ -39,7 +45,25 @@ impl HealthCheckResult {
     }
 }
 
-/// Health checker that performs HTTP and script-based health checks
+/// Outcome of an app's warm-up
+#[derive(Debug, Clone)]
+pub struct WarmupResult {
+    /// Requests that got a response
+    pub sent: u32,
+    /// Requests that failed or got an error status
+    pub failed: u32,
+    pub duration_ms: u64,
+    /// Why the warm-up fell short: the first failure, or the timeout
+    pub message: Option<String>,
+}
+
+impl WarmupResult {
+    pub fn succeeded(&self) -> bool {
+        self.failed == 0 && self.message.is_none()
+    }
+}
+
+/// Health checker that performs HTTP, TCP and script-based health checks
 pub struct HealthChecker {
     client: reqwest::Client,
 }
@@ -59,14 +83,38 @@ impl HealthChecker {
         let start = std::time::Instant::now();
         let timeout_duration = Duration::from_secs(config.timeout_secs);
 
+        let expect = match config.expect_output.as_deref().map(Regex::new).transpose() {
+            Ok(expect) => expect,
+            Err(e) => {
+                return HealthCheckResult::unhealthy(
+                    0,
+                    format!("Invalid expect_output regex: {}", e),
+                )
+            }
+        };
+
         // Try HTTP check first if configured
         if let Some(url) = &config.http_url {
-            return self.check_http(url, &config.expected_status, timeout_duration).await;
+            return self
+                .check_http(
+                    url,
+                    &config.expected_status,
+                    expect.as_ref(),
+                    timeout_duration,
+                )
+                .await;
+        }
+
+        // Then a TCP connect check
+        if let Some(addr) = config.tcp_addr() {
+            return self.check_tcp(&addr, timeout_duration).await;
         }
 
         // Try script check if configured
         if let Some(script) = &config.script {
-            return self.check_script(script, timeout_duration).await;
+            return self
+                .check_script(script, expect.as_ref(), timeout_duration)
+                .await;
         }
 
         // No check configured, assume healthy
@@ -78,18 +126,37 @@ impl HealthChecker {
         &self,
         url: &str,
         expected_status: &[u16],
+        expect: Option<&Regex>,
         timeout_duration: Duration,
     ) -> HealthCheckResult {
         let start = std::time::Instant::now();
 
-        let result = timeout(timeout_duration, self.client.get(url).send()).await;
+        // The body is only read when it has to be matched
+        let result = timeout(timeout_duration, async {
+            let response = self.client.get(url).send().await?;
+            let status = response.status().as_u16();
+            let body = match expect {
+                Some(_) => Some(response.text().await?),
+                None => None,
+            };
+            Ok::<_, reqwest::Error>((status, body))
+        })
+        .await;
 
         let duration_ms = start.elapsed().as_millis() as u64;
 
         match result {
-            Ok(Ok(response)) => {
-                let status = response.status().as_u16();
+            Ok(Ok((status, body))) => {
                 if expected_status.contains(&status) || (expected_status.is_empty() && status == 200) {
+                    if let (Some(re), Some(body)) = (expect, body) {
+                        if !re.is_match(&body) {
+                            warn!("Health check failed: {} body did not match /{}/", url, re);
+                            return HealthCheckResult::unhealthy(
+                                duration_ms,
+                                format!("Body did not match /{}/", re),
+                            );
+                        }
+                    }
                     debug!("Health check passed: {} returned {}", url, status);
                     HealthCheckResult::healthy(duration_ms)
                 } else {
@@ -111,26 +178,73 @@ impl HealthChecker {
         }
     }
 
+    /// Perform TCP connect health check
+    async fn check_tcp(&self, addr: &str, timeout_duration: Duration) -> HealthCheckResult {
+        let start = std::time::Instant::now();
+
+        let result = timeout(timeout_duration, TcpStream::connect(addr)).await;
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(Ok(_)) => {
+                debug!("Health check passed: connected to {}", addr);
+                HealthCheckResult::healthy(duration_ms)
+            }
+            Ok(Err(e)) => {
+                warn!("Health check failed: {} - {}", addr, e);
+                HealthCheckResult::unhealthy(duration_ms, format!("Connect failed: {}", e))
+            }
+            Err(_) => {
+                warn!("Health check timed out: {}", addr);
+                HealthCheckResult::unhealthy(duration_ms, "Timeout")
+            }
+        }
+    }
+
     /// Perform script-based health check
-    async fn check_script(&self, script: &str, timeout_duration: Duration) -> HealthCheckResult {
+    async fn check_script(
+        &self,
+        script: &str,
+        expect: Option<&Regex>,
+        timeout_duration: Duration,
+    ) -> HealthCheckResult {
         let start = std::time::Instant::now();
 
+        let stdout = if expect.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        };
         let result = timeout(
             timeout_duration,
             Command::new("sh")
                 .arg("-c")
                 .arg(script)
-                .stdout(Stdio::null())
+                .stdin(Stdio::null())
+                .stdout(stdout)
                 .stderr(Stdio::null())
-                .status(),
+                .kill_on_drop(true)
+                .output(),
         )
         .await;
 
         let duration_ms = start.elapsed().as_millis() as u64;
 
         match result {
-            Ok(Ok(status)) => {
+            Ok(Ok(output)) => {
+                let status = output.status;
                 if status.success() {
+                    if let Some(re) = expect {
+                        let stdout = String::from_utf8_lossy(&output.stdout);
+                        if !re.is_match(&stdout) {
+                            warn!("Health check script output did not match /{}/", re);
+                            return HealthCheckResult::unhealthy(
+                                duration_ms,
+                                format!("Output did not match /{}/", re),
+                            );
+                        }
+                    }
                     debug!("Health check script passed");
                     HealthCheckResult::healthy(duration_ms)
                 } else {
@@ -151,6 +265,95 @@ impl HealthChecker {
     }
 }
 
+impl HealthChecker {
+    /// Request each warm-up URL `requests` times, one request at a time,
+    /// then run the script in `cwd`. Failures do not stop the warm-up; the
+    /// whole of it is bounded by `timeout_secs`.
+    pub async fn warm_up(&self, warmup: &Warmup, cwd: &Path) -> WarmupResult {
+        let start = std::time::Instant::now();
+        let mut result = WarmupResult {
+            sent: 0,
+            failed: 0,
+            duration_ms: 0,
+            message: None,
+        };
+
+        let run = async {
+            for url in &warmup.urls {
+                for _ in 0..warmup.requests {
+                    let failure = match self.client.get(url).send().await {
+                        Ok(response) if response.status().is_success() => None,
+                        Ok(response) => Some(format!("{} returned {}", url, response.status())),
+                        Err(e) => Some(format!("{}: {}", url, e)),
+                    };
+                    match failure {
+                        None => result.sent += 1,
+                        Some(message) => {
+                            debug!("Warm-up request failed: {}", message);
+                            result.failed += 1;
+                            result.message.get_or_insert(message);
+                        }
+                    }
+                }
+            }
+
+            if let Some(script) = &warmup.script {
+                let status = Command::new("sh")
+                    .arg("-c")
+                    .arg(script)
+                    .current_dir(cwd)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .kill_on_drop(true)
+                    .status()
+                    .await;
+                let failure = match status {
+                    Ok(status) if status.success() => None,
+                    Ok(status) => Some(format!(
+                        "Warm-up script exited with code {}",
+                        status.code().unwrap_or(-1)
+                    )),
+                    Err(e) => Some(format!("Warm-up script error: {}", e)),
+                };
+                if let Some(message) = failure {
+                    result.message.get_or_insert(message);
+                }
+            }
+        };
+
+        let timed_out = timeout(Duration::from_secs(warmup.timeout_secs), run)
+            .await
+            .is_err();
+        if timed_out {
+            result.message = Some(format!("Timed out after {}s", warmup.timeout_secs));
+        }
+        result.duration_ms = start.elapsed().as_millis() as u64;
+        result
+    }
+}
+
+impl HealthChecker {
+    /// Whether a `wait_for` target is up, giving it at most `timeout_duration`
+    pub async fn reachable(
+        &self,
+        target: &WaitTarget,
+        cwd: &Path,
+        timeout_duration: Duration,
+    ) -> bool {
+        match target {
+            // Failures are expected while waiting, so unlike checks they are not logged
+            WaitTarget::Tcp(addr) => timeout(timeout_duration, TcpStream::connect(addr.as_str()))
+                .await
+                .is_ok_and(|stream| stream.is_ok()),
+            WaitTarget::Http(url) => timeout(timeout_duration, self.client.get(url).send())
+                .await
+                .is_ok_and(|response| response.is_ok_and(|r| r.status().is_success())),
+            WaitTarget::Path(path) => cwd.join(path).exists(),
+        }
+    }
+}
+
 impl Default for HealthChecker {
     fn default() -> Self {
         Self::new()
@@ -162,6 +365,7 @@ pub struct HealthMonitor {
     checker: HealthChecker,
     config: HealthCheck,
     consecutive_failures: u32,
+    consecutive_successes: u32,
 }
 
 impl HealthMonitor {
@@ -170,20 +374,25 @@ impl HealthMonitor {
             checker: HealthChecker::new(),
             config,
             consecutive_failures: 0,
+            consecutive_successes: 0,
         }
     }
 
-    /// Perform a single health check and update failure count
+    /// Perform a single health check and update failure/success streaks
     pub async fn check(&mut self) -> HealthCheckResult {
         let result = self.checker.check(&self.config).await;
+        self.record(result.healthy);
+        result
+    }
 
-        if result.healthy {
+    fn record(&mut self, passed: bool) {
+        if passed {
             self.consecutive_failures = 0;
+            self.consecutive_successes += 1;
         } else {
             self.consecutive_failures += 1;
+            self.consecutive_successes = 0;
         }
-
-        result
     }
 
     /// Check if the process should be considered unhealthy
@@ -191,14 +400,25 @@ impl HealthMonitor {
         self.consecutive_failures >= self.config.retries
     }
 
+    /// Whether enough consecutive checks passed to count as healthy
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_successes >= self.config.success_threshold.max(1)
+    }
+
+    /// Wait before the first check
+    pub fn initial_delay(&self) -> Duration {
+        Duration::from_secs(self.config.initial_delay_secs)
+    }
+
     /// Get the check interval
     pub fn interval(&self) -> Duration {
         Duration::from_secs(self.config.interval_secs)
     }
 
-    /// Reset failure counter
+    /// Reset failure and success counters
     pub fn reset(&mut self) {
         self.consecutive_failures = 0;
+        self.consecutive_successes = 0;
     }
 }
 
@@ -230,6 +450,35 @@ mod tests {
         assert!(!result.healthy);
     }
 
+    #[tokio::test]
+    async fn test_health_check_script_output_match() {
+        let checker = HealthChecker::new();
+        let config = HealthCheck::script("echo 'status: ok'").with_expect_output(r"status:\s*ok");
+        assert!(checker.check(&config).await.healthy);
+
+        let config =
+            HealthCheck::script("echo 'status: degraded'").with_expect_output(r"status:\s*ok");
+        let result = checker.check(&config).await;
+        assert!(!result.healthy);
+        assert!(result.message.unwrap().contains("did not match"));
+
+        let config = HealthCheck::script("echo ok").with_expect_output("(unclosed");
+        assert!(!checker.check(&config).await.healthy);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_tcp() {
+        let checker = HealthChecker::new();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let config = HealthCheck::tcp("127.0.0.1", port);
+        assert!(checker.check(&config).await.healthy);
+
+        drop(listener);
+        assert!(!checker.check(&config).await.healthy);
+    }
+
     #[test]
     fn test_health_monitor_failure_counting() {
         let config = HealthCheck {
@@ -244,4 +493,94 @@ mod tests {
         monitor.consecutive_failures = 3;
         assert!(monitor.is_unhealthy());
     }
+
+    #[test]
+    fn test_health_monitor_success_threshold() {
+        let config = HealthCheck {
+            success_threshold: 2,
+            ..Default::default()
+        };
+        let mut monitor = HealthMonitor::new(config);
+
+        monitor.record(true);
+        assert!(!monitor.is_healthy());
+        monitor.record(false);
+        monitor.record(true);
+        assert!(!monitor.is_healthy());
+        monitor.record(true);
+        assert!(monitor.is_healthy());
+        assert!(!monitor.is_unhealthy());
+    }
+
+    #[tokio::test]
+    async fn test_warm_up() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let hits = Arc::new(AtomicU32::new(0));
+        let served = Arc::clone(&hits);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                served.fetch_add(1, Ordering::SeqCst);
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let checker = HealthChecker::new();
+        let dir = tempfile::tempdir().unwrap();
+        let warmup = Warmup {
+            urls: vec![format!("http://127.0.0.1:{}/warmup", port)],
+            requests: 3,
+            script: Some("touch warmed".into()),
+            timeout_secs: 5,
+        };
+        let result = checker.warm_up(&warmup, dir.path()).await;
+        assert!(result.succeeded(), "{:?}", result.message);
+        assert_eq!(result.sent, 3);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        assert!(dir.path().join("warmed").exists());
+
+        let warmup = Warmup {
+            urls: Vec::new(),
+            requests: 1,
+            script: Some("exit 2".into()),
+            timeout_secs: 5,
+        };
+        let result = checker.warm_up(&warmup, dir.path()).await;
+        assert!(!result.succeeded());
+        assert!(result.message.unwrap().contains("code 2"));
+
+        let warmup = Warmup {
+            script: Some("sleep 5".into()),
+            timeout_secs: 1,
+            ..warmup
+        };
+        let result = checker.warm_up(&warmup, dir.path()).await;
+        assert_eq!(result.message.as_deref(), Some("Timed out after 1s"));
+    }
+
+    #[tokio::test]
+    async fn test_reachable() {
+        let checker = HealthChecker::new();
+        let dir = tempfile::tempdir().unwrap();
+        let wait = Duration::from_secs(1);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = WaitTarget::Tcp(listener.local_addr().unwrap().to_string());
+        assert!(checker.reachable(&target, dir.path(), wait).await);
+        drop(listener);
+        assert!(!checker.reachable(&target, dir.path(), wait).await);
+
+        let target = WaitTarget::Path("ready".into());
+        assert!(!checker.reachable(&target, dir.path(), wait).await);
+        std::fs::write(dir.path().join("ready"), "").unwrap();
+        assert!(checker.reachable(&target, dir.path(), wait).await);
+    }
 }
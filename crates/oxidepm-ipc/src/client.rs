@@ -1,49 +1,218 @@
-//! IPC Client - Unix socket client for CLI
+//! IPC Client - Unix socket (or authenticated TCP) client for CLI
 
-use oxidepm_core::{Error, Result};
+use oxidepm_core::{constants, Error, HostConfig, OperationInfo, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::future::Future;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::UnixStream;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpStream, UnixStream};
 use tracing::{debug, info, warn};
 
-use crate::protocol::{Request, Response};
+use crate::protocol::{AuthChallenge, AuthHello, Envelope, Request, Response};
+
+/// Longest handshake reply accepted from a TCP daemon
+const MAX_AUTH_REPLY: usize = 64 * 1024;
+
+/// Where the daemon listens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Unix socket
+    Unix(PathBuf),
+    /// Remote daemon on TCP, authenticated with a shared token
+    Tcp { address: String, token: String },
+}
+
+impl Endpoint {
+    /// Endpoint for a configured host
+    pub fn for_host(name: &str, host: &HostConfig) -> Result<Self> {
+        if let Some(address) = &host.address {
+            let token = host.token().ok_or_else(|| {
+                Error::ConfigError(format!(
+                    "Host '{}' has an address but no token (set token or token_env)",
+                    name
+                ))
+            })?;
+            return Ok(Endpoint::Tcp {
+                address: address.clone(),
+                token,
+            });
+        }
+        host.socket.clone().map(Endpoint::Unix).ok_or_else(|| {
+            Error::ConfigError(format!("Host '{}' needs a socket or an address", name))
+        })
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Unix(path) => write!(f, "{}", path.display()),
+            Endpoint::Tcp { address, .. } => write!(f, "tcp://{}", address),
+        }
+    }
+}
+
+/// A connected byte stream to the daemon
+pub trait IpcStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> IpcStream for T {}
+
+/// Connection held between requests, with whatever it has buffered
+type Connection = BufReader<Box<dyn IpcStream>>;
 
 /// IPC Client for CLI communication with daemon
+///
+/// Connecting is bounded by a timeout, and so is waiting for the response to
+/// a read-only request, which is also retried after a connection failure or
+/// timeout. Mutations are never retried or cut off: a build or rollout may
+/// take as long as it takes.
 pub struct IpcClient {
-    socket_path: PathBuf,
+    endpoint: Endpoint,
+    /// Start the local daemon when it is not running
+    auto_start: bool,
+    next_id: AtomicU64,
+    /// Sent with every request for the daemon's audit log
+    client_name: Option<String>,
+    connect_timeout: Duration,
+    /// None waits forever
+    request_timeout: Option<Duration>,
+    retries: u32,
+    /// Idle connections kept for the next requests
+    pool: Mutex<Vec<Connection>>,
+    /// How many idle connections to keep (0: a new one per request)
+    max_idle: usize,
+    /// Poll a request that carries on in the background until it finishes,
+    /// rather than returning `OperationPending`
+    follow_operations: bool,
 }
 
 impl IpcClient {
-    /// Create a new IPC client
+    /// Create a new IPC client for the local daemon, starting it on demand
     pub fn new(socket_path: PathBuf) -> Self {
-        Self { socket_path }
+        Self {
+            auto_start: true,
+            ..Self::with_endpoint(Endpoint::Unix(socket_path))
+        }
     }
 
-    /// Check if daemon is running
-    pub fn is_daemon_running(&self) -> bool {
-        self.socket_path.exists()
+    /// Create a client for any endpoint; the daemon behind it is never started
+    pub fn with_endpoint(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint,
+            auto_start: false,
+            next_id: AtomicU64::new(1),
+            client_name: None,
+            connect_timeout: Duration::from_millis(constants::IPC_CONNECT_TIMEOUT_MS),
+            request_timeout: constants::ipc_request_timeout(),
+            retries: constants::DEFAULT_IPC_RETRIES,
+            pool: Mutex::new(Vec::new()),
+            max_idle: 0,
+            follow_operations: true,
+        }
     }
 
-    /// Connect to daemon (without auto-start)
-    pub async fn connect(&self) -> Result<UnixStream> {
-        if !self.socket_path.exists() {
-            return Err(Error::DaemonNotRunning);
+    /// Bound connecting, and waiting for the response to a read-only request
+    /// (`None` waits forever)
+    pub fn with_timeouts(mut self, connect: Duration, request: Option<Duration>) -> Self {
+        self.connect_timeout = connect;
+        self.request_timeout = request;
+        self
+    }
+
+    /// How often a read-only request is retried
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Keep the connection open between requests, for callers that poll
+    pub fn with_connection_reuse(self) -> Self {
+        self.with_connection_pool(1)
+    }
+
+    /// Keep up to `max_idle` connections open between requests, for callers
+    /// that send several at once. Only read-only requests reuse them.
+    pub fn with_connection_pool(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Return `OperationPending` for a request that outlasts the daemon's
+    /// time budget, instead of waiting for the operation to finish
+    pub fn with_background_operations(mut self) -> Self {
+        self.follow_operations = false;
+        self
+    }
+
+    /// Tell the daemon what is sending the requests, e.g. "web"
+    pub fn with_client_name(mut self, name: impl Into<String>) -> Self {
+        self.client_name = Some(name.into());
+        self
+    }
+
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    /// Check if daemon is running (always assumed for TCP daemons; connecting tells)
+    pub fn is_daemon_running(&self) -> bool {
+        match &self.endpoint {
+            Endpoint::Unix(socket_path) => socket_path.exists(),
+            Endpoint::Tcp { .. } => true,
         }
+    }
 
-        UnixStream::connect(&self.socket_path)
+    /// Connect to daemon (without auto-start)
+    pub async fn connect(&self) -> Result<Box<dyn IpcStream>> {
+        tokio::time::timeout(self.connect_timeout, self.open())
             .await
-            .map_err(|e| match e.kind() {
-                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused => {
-                    Error::DaemonNotRunning
+            .map_err(|_| {
+                Error::Timeout(format!(
+                    "connecting to the daemon at {} took over {}ms",
+                    self.endpoint,
+                    self.connect_timeout.as_millis()
+                ))
+            })?
+    }
+
+    async fn open(&self) -> Result<Box<dyn IpcStream>> {
+        match &self.endpoint {
+            Endpoint::Unix(socket_path) => {
+                if !socket_path.exists() {
+                    return Err(Error::DaemonNotRunning);
                 }
-                _ => Error::IpcConnectionFailed(e.to_string()),
-            })
+
+                let stream =
+                    UnixStream::connect(socket_path)
+                        .await
+                        .map_err(|e| match e.kind() {
+                            std::io::ErrorKind::NotFound
+                            | std::io::ErrorKind::ConnectionRefused => Error::DaemonNotRunning,
+                            _ => Error::IpcConnectionFailed(e.to_string()),
+                        })?;
+                Ok(Box::new(stream))
+            }
+            Endpoint::Tcp { address, token } => {
+                let mut stream = TcpStream::connect(address)
+                    .await
+                    .map_err(|e| Error::IpcConnectionFailed(format!("{}: {}", address, e)))?;
+                stream.set_nodelay(true).ok();
+                authenticate(&mut stream, address, token).await?;
+                Ok(Box::new(stream))
+            }
+        }
     }
 
     /// Connect to daemon, starting it if necessary (PM2 behavior)
-    pub async fn connect_or_start(&self) -> Result<UnixStream> {
+    pub async fn connect_or_start(&self) -> Result<Box<dyn IpcStream>> {
+        if !self.auto_start {
+            return self.connect().await;
+        }
+
         match self.connect().await {
             Ok(stream) => Ok(stream),
             Err(Error::DaemonNotRunning) => {
@@ -99,12 +268,16 @@ impl IpcClient {
         Ok(())
     }
 
-    /// Send a request and receive response
-    pub async fn send(&self, request: &Request) -> Result<Response> {
-        let mut stream = self.connect_or_start().await?;
-
-        // Send request
-        let mut json = serde_json::to_string(request)?;
+    /// Write a request tagged with a fresh id, returning the id
+    async fn write_request<S: AsyncWrite + Unpin + ?Sized>(
+        &self,
+        stream: &mut S,
+        request: &Request,
+    ) -> Result<u64> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut json = serde_json::to_string(
+            &Envelope::new(Some(id), request).with_client(self.client_name.clone()),
+        )?;
         json.push('\n');
 
         stream
@@ -117,22 +290,193 @@ impl IpcClient {
             .await
             .map_err(|e| Error::IpcError(format!("Flush error: {}", e)))?;
 
-        debug!("Sent request: {:?}", request);
+        debug!("Sent request {}: {:?}", id, request);
+        Ok(id)
+    }
 
-        // Read response
-        let mut reader = BufReader::new(stream);
-        let mut line = String::new();
+    /// Send a request and receive response
+    pub async fn send(&self, request: &Request) -> Result<Response> {
+        self.send_with_progress(request, |_| {}).await
+    }
 
-        reader
-            .read_line(&mut line)
-            .await
-            .map_err(|e| Error::IpcError(format!("Read error: {}", e)))?;
+    /// Send a request and receive its response, passing any build output the
+    /// daemon streams ahead of it to `on_progress`. A request that carries on
+    /// in the background is waited for, unless background operations are on.
+    pub async fn send_with_progress<F>(&self, request: &Request, on_progress: F) -> Result<Response>
+    where
+        F: FnMut(&str),
+    {
+        self.send_with_operation(request, on_progress, |_| {}).await
+    }
 
-        let response: Response = serde_json::from_str(line.trim())
-            .map_err(|e| Error::IpcError(format!("Invalid response: {}", e)))?;
+    /// `send_with_progress`, also passing every poll of a request that
+    /// carries on in the background to `on_operation`
+    pub async fn send_with_operation<F, G>(
+        &self,
+        request: &Request,
+        mut on_progress: F,
+        mut on_operation: G,
+    ) -> Result<Response>
+    where
+        F: FnMut(&str),
+        G: FnMut(&OperationInfo),
+    {
+        match self.request(request, &mut on_progress).await? {
+            Response::OperationPending { operation } if self.follow_operations => {
+                debug!(
+                    "{} carries on in the background as operation {}",
+                    operation.action, operation.id
+                );
+                on_operation(&operation);
+                // Lines so far were streamed with the request
+                self.follow_operation(operation.id, operation.log_lines, on_progress, on_operation)
+                    .await
+            }
+            response => Ok(response),
+        }
+    }
 
-        debug!("Received response: {:?}", response);
-        Ok(response)
+    /// Poll a background operation until it finishes, returning the response
+    /// its request would have had. Output past the first `seen_lines` lines
+    /// goes to `on_progress` (as much of it as the daemon keeps), and every
+    /// poll to `on_operation`.
+    pub async fn follow_operation<F, G>(
+        &self,
+        id: u64,
+        mut seen_lines: usize,
+        mut on_progress: F,
+        mut on_operation: G,
+    ) -> Result<Response>
+    where
+        F: FnMut(&str),
+        G: FnMut(&OperationInfo),
+    {
+        loop {
+            match self
+                .request(&Request::OperationStatus { id }, &mut |_| {})
+                .await?
+            {
+                Response::Operation { operation, result } => {
+                    let new_lines = operation.log_lines.saturating_sub(seen_lines);
+                    let skip = operation.log.len().saturating_sub(new_lines);
+                    for line in &operation.log[skip..] {
+                        on_progress(line);
+                    }
+                    seen_lines = operation.log_lines;
+                    on_operation(&operation);
+                    if let Some(result) = result {
+                        return Ok(*result);
+                    }
+                    tokio::time::sleep(Duration::from_millis(constants::OPERATION_POLL_MS)).await
+                }
+                response => return Ok(response),
+            }
+        }
+    }
+
+    /// Send a request, with the timeout and retries read-only requests get
+    async fn request<F>(&self, request: &Request, on_progress: &mut F) -> Result<Response>
+    where
+        F: FnMut(&str),
+    {
+        if !request.is_read_only() {
+            return self.exchange(request, on_progress).await;
+        }
+
+        let mut attempt = 0;
+        loop {
+            let result =
+                with_timeout(self.request_timeout, self.exchange(request, on_progress)).await;
+            match result {
+                Err(e) if attempt < self.retries && is_transient(&e) => {
+                    attempt += 1;
+                    let delay = retry_delay(attempt);
+                    debug!("{}; retrying in {}ms", e, delay.as_millis());
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// One round trip: write the request and read until its response
+    async fn exchange<F>(&self, request: &Request, on_progress: &mut F) -> Result<Response>
+    where
+        F: FnMut(&str),
+    {
+        // A mutation goes out on a new connection: one whose response is lost
+        // may already have run, so it must never be written twice
+        let idle = if request.is_read_only() {
+            self.pooled()
+        } else {
+            None
+        };
+        let mut pooled = false;
+        let (mut reader, mut id) = match idle {
+            Some(mut reader) => match self.write_request(reader.get_mut(), request).await {
+                Ok(id) => {
+                    pooled = true;
+                    (reader, id)
+                }
+                // The idle connection is gone (the daemon restarted, say);
+                // write the request again where someone is listening
+                Err(_) => self.fresh_request(request).await?,
+            },
+            None => self.fresh_request(request).await?,
+        };
+
+        loop {
+            let mut line = String::new();
+            let read = reader.read_line(&mut line).await;
+            // A stale pooled connection can still take the write and only
+            // fail on the read; ending before any response byte, the
+            // read-only request gets one more try on a new connection
+            if std::mem::take(&mut pooled) && !matches!(read, Ok(n) if n > 0) {
+                debug!("Pooled connection closed before a response; reconnecting");
+                (reader, id) = self.fresh_request(request).await?;
+                continue;
+            }
+            let read = read.map_err(|e| Error::IpcError(format!("Read error: {}", e)))?;
+            if read == 0 {
+                return Err(Error::IpcError("Daemon closed the connection".to_string()));
+            }
+
+            let envelope: Envelope<Response> = serde_json::from_str(line.trim())
+                .map_err(|e| Error::IpcError(format!("Invalid response: {}", e)))?;
+            // Daemons that predate request ids do not echo one
+            if envelope.id.is_none() || envelope.id == Some(id) {
+                if let Response::BuildProgress { line } = &envelope.message {
+                    on_progress(line);
+                    continue;
+                }
+                debug!("Received response: {:?}", envelope.message);
+                self.release(reader);
+                return Ok(envelope.message);
+            }
+        }
+    }
+
+    /// Write the request on a new connection
+    async fn fresh_request(&self, request: &Request) -> Result<(Connection, u64)> {
+        let mut reader = BufReader::new(self.connect_or_start().await?);
+        let id = self.write_request(reader.get_mut(), request).await?;
+        Ok((reader, id))
+    }
+
+    /// Take an idle connection, if there is one. A request abandoned midway
+    /// drops its connection, so a taken one is always clean.
+    fn pooled(&self) -> Option<Connection> {
+        self.pool.lock().ok()?.pop()
+    }
+
+    /// Keep a connection that finished a request for the next one, while
+    /// the pool has room
+    fn release(&self, reader: Connection) {
+        if let Ok(mut idle) = self.pool.lock() {
+            if idle.len() < self.max_idle {
+                idle.push(reader);
+            }
+        }
     }
 
     /// Send a request and receive a stream of responses (for logs -f)
@@ -141,20 +485,7 @@ impl IpcClient {
         F: FnMut(Response) -> bool, // Return false to stop
     {
         let mut stream = self.connect_or_start().await?;
-
-        // Send request
-        let mut json = serde_json::to_string(request)?;
-        json.push('\n');
-
-        stream
-            .write_all(json.as_bytes())
-            .await
-            .map_err(|e| Error::IpcError(format!("Write error: {}", e)))?;
-
-        stream
-            .flush()
-            .await
-            .map_err(|e| Error::IpcError(format!("Flush error: {}", e)))?;
+        let id = self.write_request(&mut stream, request).await?;
 
         // Read responses until closed or callback returns false
         let mut reader = BufReader::new(stream);
@@ -163,8 +494,11 @@ impl IpcClient {
             match reader.read_line(&mut line).await {
                 Ok(0) => break, // Connection closed
                 Ok(_) => {
-                    if let Ok(response) = serde_json::from_str::<Response>(line.trim()) {
-                        if !on_response(response) {
+                    if let Ok(envelope) = serde_json::from_str::<Envelope<Response>>(line.trim()) {
+                        if envelope.id.is_some_and(|reply_to| reply_to != id) {
+                            continue;
+                        }
+                        if !on_response(envelope.message) {
                             break;
                         }
                     }
@@ -179,7 +513,7 @@ impl IpcClient {
     /// Ping the daemon
     pub async fn ping(&self) -> Result<bool> {
         match self.send(&Request::Ping).await {
-            Ok(Response::Pong) => Ok(true),
+            Ok(Response::Pong { .. }) => Ok(true),
             Ok(_) => Ok(false),
             Err(Error::DaemonNotRunning) => Ok(false),
             Err(e) => Err(e),
@@ -187,6 +521,90 @@ impl IpcClient {
     }
 }
 
+/// Run `request`, failing with a timeout error once `timeout` passes
+async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    request: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let Some(timeout) = timeout else {
+        return request.await;
+    };
+    tokio::time::timeout(timeout, request).await.map_err(|_| {
+        Error::Timeout(format!(
+            "no response from the daemon within {}s",
+            timeout.as_secs_f32()
+        ))
+    })?
+}
+
+/// Failures a retry may get past: a connection that broke or a daemon that
+/// did not answer in time
+fn is_transient(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::IpcError(_) | Error::IpcConnectionFailed(_) | Error::Timeout(_)
+    )
+}
+
+/// Exponential backoff with up to half as much again of random jitter, so
+/// clients that failed together do not retry together
+fn retry_delay(attempt: u32) -> Duration {
+    let base = constants::IPC_RETRY_DELAY_MS << (attempt - 1).min(10);
+    let mut random = [0u8; 8];
+    let jitter = match SystemRandom::new().fill(&mut random) {
+        Ok(()) => u64::from_le_bytes(random) % (base / 2 + 1),
+        Err(_) => 0,
+    };
+    Duration::from_millis(base + jitter)
+}
+
+/// Answer the daemon's challenge on a fresh TCP connection with proof of the
+/// token and wait for the daemon to accept it
+async fn authenticate(stream: &mut TcpStream, address: &str, token: &str) -> Result<()> {
+    let challenge: AuthChallenge = serde_json::from_slice(&read_auth_line(stream).await?)
+        .map_err(|e| Error::IpcError(format!("Invalid authentication challenge: {}", e)))?;
+    let mut hello = serde_json::to_string(&AuthHello::answer(&challenge, token))?;
+    hello.push('\n');
+    stream
+        .write_all(hello.as_bytes())
+        .await
+        .map_err(|e| Error::IpcError(format!("Write error: {}", e)))?;
+
+    let reply: Envelope<Response> = serde_json::from_slice(&read_auth_line(stream).await?)
+        .map_err(|e| Error::IpcError(format!("Invalid authentication reply: {}", e)))?;
+    match reply.message {
+        Response::Ok { .. } => Ok(()),
+        Response::Error { .. } => Err(Error::PermissionDenied(format!(
+            "{} rejected the token",
+            address
+        ))),
+        _ => Err(Error::IpcError(
+            "Unexpected authentication reply".to_string(),
+        )),
+    }
+}
+
+/// Read one line of the handshake byte by byte, so nothing past it is
+/// buffered and lost
+async fn read_auth_line(stream: &mut TcpStream) -> Result<Vec<u8>> {
+    let mut line = Vec::new();
+    loop {
+        let byte = stream.read_u8().await.map_err(|e| {
+            Error::IpcError(format!(
+                "Daemon closed the connection during authentication: {}",
+                e
+            ))
+        })?;
+        if byte == b'\n' {
+            return Ok(line);
+        }
+        if line.len() >= MAX_AUTH_REPLY {
+            return Err(Error::IpcError("Authentication reply too long".to_string()));
+        }
+        line.push(byte);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,7 +613,10 @@ mod tests {
     #[test]
     fn test_client_creation() {
         let client = IpcClient::new(PathBuf::from("/tmp/test.sock"));
-        assert_eq!(client.socket_path, PathBuf::from("/tmp/test.sock"));
+        assert_eq!(
+            client.endpoint,
+            Endpoint::Unix(PathBuf::from("/tmp/test.sock"))
+        );
     }
 
     #[tokio::test]
@@ -208,4 +629,244 @@ mod tests {
 
         assert!(matches!(result, Err(Error::DaemonNotRunning)));
     }
+
+    #[tokio::test]
+    async fn test_send_with_progress() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let server = crate::server::IpcServer::bind(&socket_path).await.unwrap();
+        tokio::spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            let request = conn.read_request().await.unwrap().unwrap();
+            for line in ["Compiling api v0.1.0", "Finished release"] {
+                let progress = Response::BuildProgress {
+                    line: line.to_string(),
+                };
+                conn.send_response(request.id, &progress).await.unwrap();
+            }
+            conn.send_response(request.id, &Response::ok("started"))
+                .await
+                .unwrap();
+        });
+
+        let client = IpcClient::new(socket_path);
+        let mut lines = Vec::new();
+        let response = client
+            .send_with_progress(&Request::Ping, |line| lines.push(line.to_string()))
+            .await
+            .unwrap();
+
+        assert!(matches!(response, Response::Ok { message } if message == "started"));
+        assert_eq!(lines, vec!["Compiling api v0.1.0", "Finished release"]);
+    }
+
+    #[tokio::test]
+    async fn test_follows_background_operations() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let server = crate::server::IpcServer::bind(&socket_path).await.unwrap();
+        tokio::spawn(async move {
+            let operation = OperationInfo {
+                id: 7,
+                action: "start".to_string(),
+                target: Some("api".to_string()),
+                started_at: chrono::Utc::now(),
+                finished_at: None,
+                state: oxidepm_core::OperationState::Running,
+                percent: None,
+                log: vec!["Compiling api".to_string()],
+                log_lines: 1,
+            };
+            loop {
+                let mut conn = server.accept().await.unwrap();
+                let request = conn.read_request().await.unwrap().unwrap();
+                let response = match request.message {
+                    Request::OperationStatus { id: 7 } => Response::Operation {
+                        operation: OperationInfo {
+                            state: oxidepm_core::OperationState::Succeeded,
+                            log: vec!["Compiling api".to_string(), "Finished".to_string()],
+                            log_lines: 2,
+                            ..operation.clone()
+                        },
+                        result: Some(Box::new(Response::ok("started"))),
+                    },
+                    _ => Response::OperationPending {
+                        operation: operation.clone(),
+                    },
+                };
+                conn.send_response(request.id, &response).await.unwrap();
+            }
+        });
+
+        let stop = Request::Stop {
+            selector: oxidepm_core::Selector::All,
+            if_exists: false,
+        };
+        let client = IpcClient::with_endpoint(Endpoint::Unix(socket_path));
+        let mut lines = Vec::new();
+        let response = client
+            .send_with_progress(&stop, |line| lines.push(line.to_string()))
+            .await
+            .unwrap();
+        assert!(matches!(response, Response::Ok { message } if message == "started"));
+        // Only output the request had not streamed yet
+        assert_eq!(lines, vec!["Finished"]);
+
+        let client = client.with_background_operations();
+        let response = client.send(&stop).await.unwrap();
+        assert!(matches!(response, Response::OperationPending { operation } if operation.id == 7));
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let server = crate::server::IpcServer::bind(&socket_path).await.unwrap();
+        tokio::spawn(async move {
+            // A hung daemon: reads the request and never answers
+            let mut conn = server.accept().await.unwrap();
+            let _ = conn.read_request().await;
+            std::future::pending::<()>().await;
+            drop(conn);
+        });
+
+        let client = IpcClient::with_endpoint(Endpoint::Unix(socket_path))
+            .with_timeouts(Duration::from_secs(1), Some(Duration::from_millis(100)))
+            .with_retries(0);
+        let result = client.send(&Request::Status).await;
+        assert!(matches!(result, Err(Error::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_retries_read_only_requests() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let server = crate::server::IpcServer::bind(&socket_path).await.unwrap();
+        let accepted = std::sync::Arc::new(AtomicU64::new(0));
+        let counter = std::sync::Arc::clone(&accepted);
+        tokio::spawn(async move {
+            loop {
+                let mut conn = server.accept().await.unwrap();
+                let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+                let request = conn.read_request().await.unwrap().unwrap();
+                // The first connection and every mutation break off unanswered
+                if !first && request.message.is_read_only() {
+                    conn.send_response(request.id, &Response::ok("pong"))
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+
+        let client = IpcClient::with_endpoint(Endpoint::Unix(socket_path)).with_retries(2);
+        assert!(client.send(&Request::Status).await.is_ok());
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+
+        let stop = Request::Stop {
+            selector: oxidepm_core::Selector::All,
+            if_exists: false,
+        };
+        assert!(client.send(&stop).await.is_err());
+        assert_eq!(accepted.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_connection_reuse() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let server = crate::server::IpcServer::bind(&socket_path).await.unwrap();
+        let accepted = std::sync::Arc::new(AtomicU64::new(0));
+        let counter = std::sync::Arc::clone(&accepted);
+        tokio::spawn(async move {
+            loop {
+                let mut conn = server.accept().await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    while let Ok(Some(request)) = conn.read_request().await {
+                        conn.send_response(request.id, &Response::ok("pong"))
+                            .await
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        let client = IpcClient::with_endpoint(Endpoint::Unix(socket_path)).with_connection_reuse();
+        for _ in 0..3 {
+            assert!(client.send(&Request::Status).await.is_ok());
+        }
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+        // Requests at once each need a connection, and the pool keeps them
+        let client = client.with_connection_pool(4);
+        send_burst(&client).await;
+        let opened = accepted.load(Ordering::SeqCst);
+        send_burst(&client).await;
+        assert_eq!(accepted.load(Ordering::SeqCst), opened);
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_stale_pooled_connection() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("daemon.sock");
+        let server = crate::server::IpcServer::bind(&socket_path).await.unwrap();
+        let accepted = std::sync::Arc::new(AtomicU64::new(0));
+        let stops = std::sync::Arc::new(AtomicU64::new(0));
+        let counter = std::sync::Arc::clone(&accepted);
+        let stop_counter = std::sync::Arc::clone(&stops);
+        tokio::spawn(async move {
+            loop {
+                let mut conn = server.accept().await.unwrap();
+                let first = counter.fetch_add(1, Ordering::SeqCst) == 0;
+                let stops = std::sync::Arc::clone(&stop_counter);
+                tokio::spawn(async move {
+                    let mut answered = 0;
+                    while let Ok(Some(request)) = conn.read_request().await {
+                        // A stop goes unanswered, as if the daemon died
+                        // running it; the first connection goes away after
+                        // one answer
+                        if matches!(request.message, Request::Stop { .. }) {
+                            stops.fetch_add(1, Ordering::SeqCst);
+                            break;
+                        }
+                        if first && answered == 1 {
+                            break;
+                        }
+                        conn.send_response(request.id, &Response::ok("pong"))
+                            .await
+                            .unwrap();
+                        answered += 1;
+                    }
+                });
+            }
+        });
+
+        let client = IpcClient::with_endpoint(Endpoint::Unix(socket_path)).with_connection_reuse();
+        assert!(client.send(&Request::Status).await.is_ok());
+
+        // A mutation skips the pool, and a lost response is an error rather
+        // than a second run
+        let stop = Request::Stop {
+            selector: oxidepm_core::Selector::All,
+            if_exists: false,
+        };
+        assert!(client.send(&stop).await.is_err());
+        assert_eq!(stops.load(Ordering::SeqCst), 1);
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+
+        // A read-only request on the stale pooled connection moves to a new one
+        assert!(client.send(&Request::Status).await.is_ok());
+        assert_eq!(accepted.load(Ordering::SeqCst), 3);
+        assert_eq!(stops.load(Ordering::SeqCst), 1);
+    }
+
+    /// Send three status requests at once
+    async fn send_burst(client: &IpcClient) {
+        let (a, b, c) = tokio::join!(
+            client.send(&Request::Status),
+            client.send(&Request::Status),
+            client.send(&Request::Status)
+        );
+        assert!(a.is_ok() && b.is_ok() && c.is_ok());
+    }
 }
@@ -4,6 +4,8 @@ pub mod client;
 pub mod protocol;
 pub mod server;
 
-pub use client::IpcClient;
-pub use protocol::{Request, Response};
-pub use server::IpcServer;
+pub use client::{Endpoint, IpcClient};
+pub use protocol::{AuthChallenge, AuthHello, Envelope, Request, Response};
+pub use server::{
+    IpcConnection, IpcServer, PeerCred, RequestReader, ResponseWriter, SocketAccess, TcpIpcServer,
+};
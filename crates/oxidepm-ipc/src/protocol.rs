@@ -1,26 +1,130 @@
 //! IPC Protocol - Request/Response types
 
-use oxidepm_core::{AppInfo, AppSpec, Selector};
+use oxidepm_core::{
+    constants, AppEvent, AppHealth, AppInfo, AppOpResult, AppSignal, AppSpec, ApplyReport,
+    AuditEntry, BuildInfo, BulkOptions, EnvSource, Error, GroupAction, Heartbeat, LogPage,
+    LogStream, OperationInfo, PortMapping, PreflightSummary, Rollout, Selector, SpecUpdate,
+    SystemInfo,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// A message on the wire, tagged with the id of the request it belongs to
+///
+/// The client picks the id; every response to that request carries it back,
+/// so several requests can be in flight on one connection. Messages without
+/// an id (one request per connection) are still accepted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    #[serde(
+        rename = "request_id",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub id: Option<u64>,
+    /// What the client says it is (e.g. "web"), recorded in the audit log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    #[serde(flatten)]
+    pub message: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(id: Option<u64>, message: T) -> Self {
+        Self {
+            id,
+            client: None,
+            message,
+        }
+    }
+
+    pub fn with_client(mut self, client: Option<String>) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+/// First line the daemon sends on a TCP connection: a fresh random nonce
+/// (hex) the client must prove it knows the token for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthChallenge {
+    pub nonce: String,
+}
+
+/// The client's answer to an [`AuthChallenge`]; the daemon replies with
+/// `Response::Ok` or a `permission_denied` error before reading requests.
+/// The token itself never crosses the wire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthHello {
+    pub proof: String,
+}
+
+impl AuthHello {
+    /// Answer `challenge` with HMAC-SHA256 of its nonce, keyed by `token` (hex)
+    pub fn answer(challenge: &AuthChallenge, token: &str) -> Self {
+        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, token.as_bytes());
+        let tag = ring::hmac::sign(&key, challenge.nonce.as_bytes());
+        Self {
+            proof: tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect(),
+        }
+    }
+}
 
 /// IPC Request from CLI to daemon
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum Request {
     /// Check if daemon is alive
     Ping,
 
     /// Start a new process
-    Start { spec: AppSpec },
+    Start {
+        spec: AppSpec,
+        /// Stream build output as `BuildProgress` responses before the result
+        #[serde(default)]
+        progress: bool,
+    },
 
     /// Stop process(es)
-    Stop { selector: Selector },
+    Stop {
+        selector: Selector,
+        /// Report a selector that matches no app as a `not_found` result
+        /// instead of an error
+        #[serde(default)]
+        if_exists: bool,
+    },
+
+    /// Restart process(es); stopped apps are started
+    Restart {
+        selector: Selector,
+        /// Clear the restart count and crash-loop streak
+        #[serde(default)]
+        reset_counter: bool,
+        #[serde(default)]
+        bulk: BulkOptions,
+        /// Report a selector that matches no app as a `not_found` result
+        /// instead of an error
+        #[serde(default)]
+        if_exists: bool,
+    },
 
-    /// Restart process(es)
-    Restart { selector: Selector },
+    /// Start, stop or restart the selected apps batch by batch
+    Group {
+        selector: Selector,
+        action: GroupAction,
+        #[serde(default)]
+        rollout: Rollout,
+    },
 
     /// Delete process(es) from registry
-    Delete { selector: Selector },
+    Delete {
+        selector: Selector,
+        /// Also remove logs, hook logs and run/metrics history
+        #[serde(default)]
+        purge: bool,
+    },
 
     /// Get status of all processes
     Status,
@@ -35,13 +139,41 @@ pub enum Request {
         follow: bool,
         stdout: bool,
         stderr: bool,
+        /// Only lines written in the last this many seconds, including rotated files
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        since_secs: Option<u64>,
+    },
+
+    /// Get a page of one log stream, to scroll through its history
+    LogPage {
+        selector: Selector,
+        #[serde(default)]
+        stream: LogStream,
+        lines: usize,
+        /// Lines before this cursor (a previous page's `older`)
+        #[serde(default)]
+        before: Option<String>,
+        /// Lines after this cursor (a previous page's `newer`)
+        #[serde(default)]
+        after: Option<String>,
     },
 
     /// Save current process list
-    Save,
+    Save {
+        /// Write here instead of the default saved-state file
+        #[serde(default)]
+        output: Option<PathBuf>,
+    },
 
     /// Restore saved processes
-    Resurrect,
+    Resurrect {
+        /// Read this file instead of the default saved state
+        #[serde(default)]
+        file: Option<PathBuf>,
+        /// Also start apps that were saved as stopped
+        #[serde(default)]
+        all: bool,
+    },
 
     /// Stop daemon and all processes
     Kill,
@@ -49,28 +181,171 @@ pub enum Request {
     /// Graceful reload (zero-downtime restart)
     Reload { selector: Selector },
 
-    /// Flush/truncate log files for process(es)
-    Flush { selector: Selector },
+    /// Flush log files for process(es): truncate them, or with either option
+    /// set, prune by age and/or total size instead
+    Flush {
+        selector: Selector,
+        /// Delete rotated files (and empty active files) not written in this many seconds
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        older_than_secs: Option<u64>,
+        /// Delete the oldest rotated files until each app's logs fit in this many bytes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        keep_bytes: Option<u64>,
+    },
 
     /// Describe a process (get what command would run)
     Describe { selector: Selector },
+
+    /// Describe an app that is not registered yet, as `start` would run it
+    DescribeSpec { spec: AppSpec },
+
+    /// Suspend auto-restarts, health actions and watch restarts daemon-wide
+    PauseSupervision,
+
+    /// Re-enable supervision after a pause
+    ResumeSupervision,
+
+    /// Take apps out of rotation for maintenance: they report not ready,
+    /// their log triggers hold off, and after `timeout_secs` they stop
+    Drain {
+        selector: Selector,
+        #[serde(default = "default_drain_timeout")]
+        timeout_secs: u64,
+    },
+
+    /// End a drain, starting the apps it stopped
+    Undrain { selector: Selector },
+
+    /// Freeze apps with SIGSTOP on their process tree
+    Pause { selector: Selector },
+
+    /// Continue paused apps with SIGCONT
+    Resume { selector: Selector },
+
+    /// Pass a signal to apps, or to their whole process tree with `tree`
+    Signal {
+        selector: Selector,
+        signal: AppSignal,
+        #[serde(default)]
+        tree: bool,
+    },
+
+    /// Reconcile running apps with a declared app list
+    Apply {
+        specs: Vec<AppSpec>,
+        /// Delete apps that are not in `specs`
+        prune: bool,
+        /// Only report what would change
+        dry_run: bool,
+    },
+
+    /// Get an app's environment
+    Env { selector: Selector },
+
+    /// Run a command in an app's working directory, environment and user,
+    /// streaming its output as `BuildProgress` responses before the result
+    Exec {
+        selector: Selector,
+        command: Vec<String>,
+    },
+
+    /// Get recent health check results
+    Health { selector: Selector },
+
+    /// Get the latest supervisor events
+    Events {
+        selector: Selector,
+        #[serde(default = "default_events_limit")]
+        limit: usize,
+    },
+
+    /// Persist environment overrides into the stored spec
+    SetEnv {
+        selector: Selector,
+        #[serde(default)]
+        set: HashMap<String, String>,
+        #[serde(default)]
+        unset: Vec<String>,
+        /// Restart running apps so they pick up the change
+        #[serde(default)]
+        restart: bool,
+    },
+
+    /// Rename an app, moving its log files to the new name
+    Rename { selector: Selector, name: String },
+
+    /// Change fields of the stored spec
+    UpdateSpec {
+        selector: Selector,
+        update: SpecUpdate,
+        /// Restart running apps so the change takes effect
+        #[serde(default)]
+        restart: bool,
+        /// Gracefully reload running apps instead of restarting them
+        #[serde(default)]
+        reload: bool,
+    },
+
+    /// Re-read notify.toml and daemon.toml (as SIGHUP does)
+    ReloadDaemonConfig,
+
+    /// Run the preflight checks `oxidepm check` runs, in a directory on the
+    /// daemon's host
+    Check {
+        dir: PathBuf,
+        /// Fix what can be fixed (install dependencies, create .env)
+        #[serde(default)]
+        fix: bool,
+    },
+
+    /// Get daemon and host overview
+    SystemInfo,
+
+    /// List ports held by running apps
+    Ports,
+
+    /// Get the latest audit log entries
+    Audit {
+        #[serde(default = "default_audit_limit")]
+        limit: usize,
+    },
+
+    /// Poll a request that carries on in the background
+    OperationStatus { id: u64 },
+
+    /// List running and recently finished operations
+    Operations,
 }
 
 /// IPC Response from daemon to CLI
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum Response {
-    /// Ping response
-    Pong,
+    /// Ping response; the heartbeat is missing from daemons that predate it
+    Pong {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        heartbeat: Option<Heartbeat>,
+    },
 
     /// Success with message
     Ok { message: String },
 
-    /// Error with message
-    Error { message: String },
+    /// Error with message, plus optional machine-readable code and remediation hint
+    Error {
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hint: Option<String>,
+    },
 
     /// Status response with all app info
-    Status { apps: Vec<AppInfo> },
+    Status {
+        apps: Vec<AppInfo>,
+        #[serde(default)]
+        supervision_paused: bool,
+    },
 
     /// Show response with single app detail
     Show { app: AppInfo },
@@ -81,29 +356,176 @@ pub enum Response {
     /// Single log line (for streaming)
     LogLine { line: String },
 
+    /// A page of log lines with cursors to the neighbouring pages
+    LogPage { page: LogPage },
+
     /// Start response with app ID
     Started { id: u32, name: String },
 
+    /// Build output line of a `Start`, a finished app of a `Restart` or an
+    /// output line of an `Exec`, sent ahead of the final response when the
+    /// request asked for progress
+    BuildProgress { line: String },
+
     /// Stop response
-    Stopped { count: usize },
+    Stopped {
+        count: usize,
+        /// Per-app outcome
+        #[serde(default)]
+        results: Vec<AppOpResult>,
+    },
 
     /// Restart response
-    Restarted { count: usize },
+    Restarted {
+        count: usize,
+        /// Per-app outcome
+        #[serde(default)]
+        results: Vec<AppOpResult>,
+    },
+
+    /// Group operation response
+    GroupDone {
+        action: GroupAction,
+        /// Per-app outcome, in rollout order
+        results: Vec<AppOpResult>,
+        /// Number of batches that ran
+        batches: usize,
+        /// Why the rollout stopped early, if it did
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        halted: Option<String>,
+    },
 
     /// Delete response
-    Deleted { count: usize },
+    Deleted {
+        count: usize,
+        /// Per-app outcome
+        #[serde(default)]
+        results: Vec<AppOpResult>,
+    },
 
     /// Save response
     Saved { count: usize, path: String },
 
     /// Resurrect response
-    Resurrected { count: usize },
+    Resurrected {
+        count: usize,
+        /// Saved apps left stopped because they were stopped on purpose
+        #[serde(default)]
+        stopped: usize,
+        /// Saved apps that could not be read (e.g. written by a newer release)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
+    },
 
     /// Reload response
     Reloaded { count: usize },
 
+    /// Drain response
+    Drained { count: usize, timeout_secs: u64 },
+
+    /// Undrain response; `started` counts apps the drain had stopped
+    Undrained { count: usize, started: usize },
+
+    /// Pause response
+    Paused {
+        count: usize,
+        /// Per-app outcome
+        #[serde(default)]
+        results: Vec<AppOpResult>,
+    },
+
+    /// Resume response
+    Resumed {
+        count: usize,
+        /// Per-app outcome
+        #[serde(default)]
+        results: Vec<AppOpResult>,
+    },
+
+    /// Signal response
+    Signalled {
+        count: usize,
+        /// Per-app outcome
+        #[serde(default)]
+        results: Vec<AppOpResult>,
+    },
+
     /// Flush response
-    Flushed { count: usize },
+    Flushed {
+        count: usize,
+        #[serde(default)]
+        freed_bytes: u64,
+    },
+
+    /// Apply response
+    Applied { report: ApplyReport },
+
+    /// App environment: the spec's variables plus what it inherits from the daemon
+    Env {
+        name: String,
+        env: HashMap<String, String>,
+        inherited: HashMap<String, String>,
+    },
+
+    /// Exec response: how the command exited (128 + signal if it was killed)
+    Executed { exit_code: i32 },
+
+    /// Environment override response
+    EnvUpdated { count: usize, restarted: usize },
+
+    /// App renamed; `restarted` when it was running and came back up
+    Renamed {
+        id: u32,
+        from: String,
+        to: String,
+        restarted: bool,
+    },
+
+    /// Stored spec update response
+    SpecUpdated {
+        count: usize,
+        restarted: usize,
+        /// Per-app outcome; a failed restart/reload after the update is a
+        /// failure too
+        #[serde(default)]
+        results: Vec<AppOpResult>,
+    },
+
+    /// Daemon config reloaded; one line per setting that changed
+    DaemonConfigReloaded { changes: Vec<String> },
+
+    /// Preflight check results
+    Checked { summary: PreflightSummary },
+
+    /// Health history for every selected app with a health check
+    Health { apps: Vec<AppHealth> },
+
+    /// Supervisor events, oldest first
+    Events { events: Vec<AppEvent> },
+
+    /// Daemon and host overview
+    SystemInfo { info: SystemInfo },
+
+    /// Ports held by running apps, in port order
+    Ports { ports: Vec<PortMapping> },
+
+    /// Audit log entries, oldest first
+    Audit { entries: Vec<AuditEntry> },
+
+    /// The request outlasted its time budget and carries on in the
+    /// background; poll it with `OperationStatus`
+    OperationPending { operation: OperationInfo },
+
+    /// Where a background operation is, and once it has finished, the
+    /// response the request would have had
+    Operation {
+        operation: OperationInfo,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<Box<Response>>,
+    },
+
+    /// Running and recently finished operations, oldest first
+    Operations { operations: Vec<OperationInfo> },
 
     /// Describe response with app details
     Described {
@@ -111,12 +533,127 @@ pub enum Response {
         command: String,
         args: Vec<String>,
         cwd: String,
-        env: std::collections::HashMap<String, String>,
+        /// Environment the process starts with, including inherited variables
+        env: HashMap<String, String>,
         mode: String,
+        /// Build behind the current (or last) process, for compiled modes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        build: Option<BuildInfo>,
+        /// Exact argv the runner executes
+        #[serde(default)]
+        argv: Vec<String>,
+        /// Build command run before starting, for compiled modes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prepare: Option<Vec<String>>,
+        /// Where each variable in `env` comes from
+        #[serde(default)]
+        env_sources: BTreeMap<String, EnvSource>,
     },
 }
 
+impl Request {
+    /// Requests that only read daemon state. They run concurrently with each
+    /// other and are cancelled if the client disconnects; everything else is
+    /// serialized and always runs to completion.
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Request::Ping
+                | Request::Status
+                | Request::Show { .. }
+                | Request::Logs { .. }
+                | Request::LogPage { .. }
+                | Request::Describe { .. }
+                | Request::DescribeSpec { .. }
+                | Request::Env { .. }
+                | Request::Health { .. }
+                | Request::Events { .. }
+                | Request::SystemInfo
+                | Request::Ports
+                | Request::Audit { .. }
+                | Request::OperationStatus { .. }
+                | Request::Operations
+                | Request::Check { fix: false, .. }
+        )
+    }
+
+    /// Request type as sent on the wire, e.g. "restart"
+    pub fn action(&self) -> String {
+        type_tag(self)
+    }
+
+    /// What the request is for, for the audit log
+    pub fn target(&self) -> Option<String> {
+        match self {
+            Request::Start { spec, .. } | Request::DescribeSpec { spec } => Some(spec.name.clone()),
+            Request::Stop { selector, .. }
+            | Request::Restart { selector, .. }
+            | Request::Group { selector, .. }
+            | Request::Delete { selector, .. }
+            | Request::Show { selector }
+            | Request::Logs { selector, .. }
+            | Request::LogPage { selector, .. }
+            | Request::Reload { selector }
+            | Request::Flush { selector, .. }
+            | Request::Describe { selector }
+            | Request::Env { selector }
+            | Request::Exec { selector, .. }
+            | Request::Health { selector }
+            | Request::Events { selector, .. }
+            | Request::SetEnv { selector, .. }
+            | Request::Rename { selector, .. }
+            | Request::UpdateSpec { selector, .. }
+            | Request::Drain { selector, .. }
+            | Request::Undrain { selector }
+            | Request::Pause { selector }
+            | Request::Resume { selector }
+            | Request::Signal { selector, .. } => Some(selector.to_string()),
+            Request::Save { output: path } | Request::Resurrect { file: path, .. } => {
+                path.as_ref().map(|path| path.display().to_string())
+            }
+            Request::Check { dir, .. } => Some(dir.display().to_string()),
+            Request::Apply { specs, .. } => Some(
+                specs
+                    .iter()
+                    .map(|spec| spec.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            Request::Ping
+            | Request::Status
+            | Request::Kill
+            | Request::PauseSupervision
+            | Request::ResumeSupervision
+            | Request::SystemInfo
+            | Request::Ports
+            | Request::Audit { .. }
+            | Request::Operations
+            | Request::ReloadDaemonConfig => None,
+            Request::OperationStatus { id } => Some(id.to_string()),
+        }
+    }
+
+    /// Whether the request adds, removes, renames or reconfigures apps
+    /// (what auto-save reacts to)
+    pub fn changes_topology(&self) -> bool {
+        matches!(
+            self,
+            Request::Start { .. }
+                | Request::Delete { .. }
+                | Request::Rename { .. }
+                | Request::UpdateSpec { .. }
+                | Request::Resurrect { .. }
+                | Request::Apply { dry_run: false, .. }
+        )
+    }
+}
+
 impl Response {
+    /// Response type as sent on the wire, e.g. "restarted"
+    pub fn kind(&self) -> String {
+        type_tag(self)
+    }
+
     pub fn ok<S: Into<String>>(message: S) -> Self {
         Response::Ok {
             message: message.into(),
@@ -126,6 +663,17 @@ impl Response {
     pub fn error<S: Into<String>>(message: S) -> Self {
         Response::Error {
             message: message.into(),
+            code: None,
+            hint: None,
+        }
+    }
+
+    /// Build an error response carrying the error's code and hint
+    pub fn from_error(err: &Error) -> Self {
+        Response::Error {
+            message: err.to_string(),
+            code: Some(err.code().to_string()),
+            hint: err.hint().map(String::from),
         }
     }
 
@@ -135,17 +683,41 @@ impl Response {
 
     pub fn error_message(&self) -> Option<&str> {
         match self {
-            Response::Error { message } => Some(message),
+            Response::Error { message, .. } => Some(message),
             _ => None,
         }
     }
 }
 
+fn default_events_limit() -> usize {
+    constants::DEFAULT_EVENTS_LIMIT
+}
+
+fn default_audit_limit() -> usize {
+    constants::DEFAULT_AUDIT_LIMIT
+}
+
+fn default_drain_timeout() -> u64 {
+    constants::DEFAULT_DRAIN_TIMEOUT_SECS
+}
+
+/// The serde tag of a request or response
+fn type_tag<T: Serialize>(message: &T) -> String {
+    serde_json::to_value(message)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("type")
+                .and_then(|tag| tag.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use oxidepm_core::AppMode;
-    use std::path::PathBuf;
 
     #[test]
     fn test_request_serialize() {
@@ -156,6 +728,7 @@ mod tests {
                 "app.js".to_string(),
                 PathBuf::from("/app"),
             ),
+            progress: false,
         };
 
         let json = serde_json::to_string(&req).unwrap();
@@ -171,16 +744,35 @@ mod tests {
         assert!(json.contains("Process started"));
     }
 
+    #[test]
+    fn test_error_response_code_and_hint() {
+        let err = oxidepm_core::Error::from_build_output("npm ERR! code ENOTFOUND");
+        let json = serde_json::to_string(&Response::from_error(&err)).unwrap();
+        assert!(json.contains("fetch_network"));
+
+        // Plain errors omit the optional fields and old payloads still parse
+        let json = serde_json::to_string(&Response::error("boom")).unwrap();
+        assert!(!json.contains("code"));
+        let parsed: Response =
+            serde_json::from_str(r#"{"type":"error","message":"boom"}"#).unwrap();
+        assert_eq!(parsed.error_message(), Some("boom"));
+    }
+
     #[test]
     fn test_selector_in_request() {
         let req = Request::Stop {
             selector: Selector::ByName("myapp".to_string()),
+            if_exists: true,
         };
         let json = serde_json::to_string(&req).unwrap();
         let parsed: Request = serde_json::from_str(&json).unwrap();
         match parsed {
-            Request::Stop { selector } => {
+            Request::Stop {
+                selector,
+                if_exists,
+            } => {
                 assert_eq!(selector, Selector::ByName("myapp".to_string()));
+                assert!(if_exists);
             }
             _ => panic!("Wrong request type"),
         }
@@ -190,13 +782,21 @@ mod tests {
     fn test_flush_request_serialize() {
         let req = Request::Flush {
             selector: Selector::All,
+            older_than_secs: None,
+            keep_bytes: Some(1024),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("flush"));
         let parsed: Request = serde_json::from_str(&json).unwrap();
         match parsed {
-            Request::Flush { selector } => {
+            Request::Flush {
+                selector,
+                older_than_secs,
+                keep_bytes,
+            } => {
                 assert_eq!(selector, Selector::All);
+                assert_eq!(older_than_secs, None);
+                assert_eq!(keep_bytes, Some(1024));
             }
             _ => panic!("Wrong request type"),
         }
@@ -204,7 +804,10 @@ mod tests {
 
     #[test]
     fn test_flushed_response_serialize() {
-        let resp = Response::Flushed { count: 3 };
+        let resp = Response::Flushed {
+            count: 3,
+            freed_bytes: 0,
+        };
         let json = serde_json::to_string(&resp).unwrap();
         assert!(json.contains("flushed"));
         assert!(json.contains("3"));
@@ -239,11 +842,248 @@ mod tests {
             cwd: "/app".to_string(),
             env,
             mode: "node".to_string(),
+            build: None,
+            argv: vec!["node".to_string(), "/app/server.js".to_string()],
+            prepare: None,
+            env_sources: BTreeMap::from([("NODE_ENV".to_string(), EnvSource::EnvFile)]),
         };
         let json = serde_json::to_string(&resp).unwrap();
         assert!(json.contains("described"));
         assert!(json.contains("myapp"));
         assert!(json.contains("node"));
         assert!(json.contains("NODE_ENV"));
+        assert!(json.contains("env_file"));
+
+        // Replies from daemons without argv/env provenance still parse
+        let old = r#"{"type":"described","name":"a","command":"c","args":[],"cwd":"/","env":{},"mode":"cmd"}"#;
+        let Response::Described {
+            argv, env_sources, ..
+        } = serde_json::from_str(old).unwrap()
+        else {
+            panic!("Wrong response type")
+        };
+        assert!(argv.is_empty() && env_sources.is_empty());
+    }
+
+    #[test]
+    fn test_events_request_default_limit() {
+        let parsed: Request =
+            serde_json::from_str(r#"{"type":"events","selector":"@web"}"#).unwrap();
+        match parsed {
+            Request::Events { selector, limit } => {
+                assert_eq!(selector, Selector::ByTag("web".to_string()));
+                assert_eq!(limit, constants::DEFAULT_EVENTS_LIMIT);
+            }
+            _ => panic!("Expected Events request"),
+        }
+    }
+
+    #[test]
+    fn test_update_spec_request_defaults() {
+        let parsed: Request = serde_json::from_str(
+            r#"{"type":"update_spec","selector":"api","update":{"max_memory_mb":512,"watch":true}}"#,
+        )
+        .unwrap();
+        assert!(parsed.changes_topology());
+        match parsed {
+            Request::UpdateSpec {
+                selector,
+                update,
+                restart,
+                reload,
+            } => {
+                assert_eq!(selector, Selector::ByName("api".to_string()));
+                assert_eq!(update.max_memory_mb, Some(512));
+                assert_eq!(update.watch, Some(true));
+                assert_eq!(update.port, None);
+                assert!(!restart && !reload);
+            }
+            _ => panic!("Wrong request type"),
+        }
+    }
+
+    #[test]
+    fn test_set_env_request_defaults() {
+        let parsed: Request = serde_json::from_str(
+            r#"{"type":"set_env","selector":"api","set":{"LOG_LEVEL":"debug"}}"#,
+        )
+        .unwrap();
+        match parsed {
+            Request::SetEnv {
+                selector,
+                set,
+                unset,
+                restart,
+            } => {
+                assert_eq!(selector, Selector::ByName("api".to_string()));
+                assert_eq!(set.get("LOG_LEVEL").map(String::as_str), Some("debug"));
+                assert!(unset.is_empty());
+                assert!(!restart);
+            }
+            _ => panic!("Wrong request type"),
+        }
+    }
+
+    #[test]
+    fn test_envelope_serialization() {
+        let envelope = Envelope::new(
+            Some(7),
+            Request::Show {
+                selector: Selector::ByName("api".to_string()),
+            },
+        );
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert!(json.contains(r#""request_id":7"#) && json.contains(r#""type":"show""#));
+
+        let parsed: Envelope<Request> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.id, Some(7));
+        assert!(
+            matches!(parsed.message, Request::Show { selector } if selector == Selector::ByName("api".to_string()))
+        );
+
+        // Clients that predate request ids send bare messages
+        let parsed: Envelope<Response> =
+            serde_json::from_str(r#"{"type":"flushed","count":2}"#).unwrap();
+        assert_eq!(parsed.id, None);
+        assert!(matches!(
+            parsed.message,
+            Response::Flushed {
+                count: 2,
+                freed_bytes: 0
+            }
+        ));
+
+        // Messages with an `id` field of their own keep it
+        let started = Envelope::new(
+            Some(3),
+            Response::Started {
+                id: 42,
+                name: "api".to_string(),
+            },
+        );
+        let parsed: Envelope<Response> =
+            serde_json::from_str(&serde_json::to_string(&started).unwrap()).unwrap();
+        assert_eq!(parsed.id, Some(3));
+        assert!(matches!(parsed.message, Response::Started { id: 42, .. }));
+    }
+
+    #[test]
+    fn test_bulk_response_results() {
+        // Daemons that predate per-app results only send the count
+        let parsed: Response = serde_json::from_str(r#"{"type":"stopped","count":2}"#).unwrap();
+        assert!(matches!(parsed, Response::Stopped { count: 2, results } if results.is_empty()));
+
+        let resp = Response::Restarted {
+            count: 0,
+            results: vec![oxidepm_core::AppOpResult {
+                id: 1,
+                name: "api".to_string(),
+                outcome: oxidepm_core::AppOpOutcome::Failed {
+                    error: "boom".to_string(),
+                },
+            }],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""status":"failed","error":"boom""#));
+
+        // Clients that predate --if-exists leave it out
+        let parsed: Request = serde_json::from_str(r#"{"type":"stop","selector":"api"}"#).unwrap();
+        assert!(matches!(
+            parsed,
+            Request::Stop {
+                if_exists: false,
+                ..
+            }
+        ));
+        let resp = Response::Stopped {
+            count: 0,
+            results: vec![oxidepm_core::AppOpResult::not_found("api")],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains(r#""name":"api","outcome":{"status":"not_found"}"#));
+
+        let parsed: Response =
+            serde_json::from_str(r#"{"type":"spec_updated","count":1,"restarted":0}"#).unwrap();
+        assert!(
+            matches!(parsed, Response::SpecUpdated { count: 1, results, .. } if results.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_save_requests_without_paths() {
+        // Clients that predate save --output / resurrect --file send bare tags
+        let parsed: Request = serde_json::from_str(r#"{"type":"save"}"#).unwrap();
+        assert!(matches!(parsed, Request::Save { output: None }));
+        let parsed: Request = serde_json::from_str(r#"{"type":"resurrect"}"#).unwrap();
+        assert!(matches!(
+            parsed,
+            Request::Resurrect {
+                file: None,
+                all: false
+            }
+        ));
+
+        let parsed: Response = serde_json::from_str(r#"{"type":"resurrected","count":1}"#).unwrap();
+        assert!(
+            matches!(parsed, Response::Resurrected { count: 1, stopped: 0, warnings } if warnings.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_request_changes_topology() {
+        let apply = |dry_run| Request::Apply {
+            specs: Vec::new(),
+            prune: true,
+            dry_run,
+        };
+        assert!(apply(false).changes_topology());
+        assert!(!apply(true).changes_topology());
+        assert!(Request::Delete {
+            selector: Selector::All,
+            purge: false,
+        }
+        .changes_topology());
+        assert!(!Request::Save { output: None }.changes_topology());
+    }
+
+    #[test]
+    fn test_request_is_read_only() {
+        assert!(Request::Status.is_read_only());
+        assert!(Request::LogPage {
+            selector: Selector::All,
+            stream: LogStream::Err,
+            lines: 10,
+            before: None,
+            after: None,
+        }
+        .is_read_only());
+        assert!(!Request::Save { output: None }.is_read_only());
+        assert!(!Request::Stop {
+            selector: Selector::All,
+            if_exists: false,
+        }
+        .is_read_only());
+    }
+
+    #[test]
+    fn test_audit_fields() {
+        let restart = Request::Restart {
+            selector: Selector::ByTag("web".to_string()),
+            reset_counter: false,
+            bulk: BulkOptions::default(),
+            if_exists: false,
+        };
+        assert_eq!(restart.action(), "restart");
+        assert_eq!(restart.target().as_deref(), Some("@web"));
+        assert_eq!(Request::Save { output: None }.target(), None);
+        assert_eq!(Response::ok("done").kind(), "ok");
+
+        // The client name is optional, so older clients still parse
+        let envelope = Envelope::new(Some(1), Request::Status).with_client(Some("web".to_string()));
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert!(json.contains(r#""client":"web""#));
+        let parsed: Envelope<Request> =
+            serde_json::from_str(r#"{"request_id":1,"type":"status"}"#).unwrap();
+        assert_eq!(parsed.client, None);
     }
 }
@@ -1,25 +1,77 @@
-//! IPC Server - Unix socket server for daemon
+//! IPC Server - Unix socket (and optional authenticated TCP) server for daemon
 
-use oxidepm_core::{Error, Result};
+use oxidepm_core::{constants, Error, Result};
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
-use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{UnixListener, UnixStream};
-use tracing::{debug, error, info};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tracing::{debug, error, info, warn};
 
 /// Maximum IPC message size (10MB) to prevent memory exhaustion attacks
 const MAX_MESSAGE_SIZE: u64 = 10 * 1024 * 1024;
 
-use crate::protocol::{Request, Response};
+/// Maximum size of the TCP authentication line
+const MAX_AUTH_SIZE: u64 = 64 * 1024;
+
+/// Random bytes in a TCP authentication challenge
+const NONCE_LEN: usize = 32;
+
+use crate::protocol::{AuthChallenge, AuthHello, Envelope, Request, Response};
+
+type BoxRead = Box<dyn AsyncRead + Send + Unpin>;
+type BoxWrite = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Who may connect to the daemon's Unix socket
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SocketAccess {
+    /// File mode of the socket (0600 when unset)
+    pub mode: Option<u32>,
+    /// Group given ownership of the socket file
+    pub group: Option<u32>,
+    /// Users allowed besides the daemon's own user and root
+    pub uids: Vec<u32>,
+    /// Groups allowed, matched against the peer's primary group
+    pub gids: Vec<u32>,
+}
+
+impl SocketAccess {
+    /// Whether `peer` may talk to a daemon running as `owner_uid`
+    pub fn allows(&self, owner_uid: u32, peer: &PeerCred) -> bool {
+        peer.uid == 0
+            || peer.uid == owner_uid
+            || self.uids.contains(&peer.uid)
+            || self.gids.contains(&peer.gid)
+    }
+}
+
+/// Credentials of the process on the other end of a Unix socket (SO_PEERCRED)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerCred {
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: Option<i32>,
+}
 
 /// IPC Server for daemon
 pub struct IpcServer {
     socket_path: PathBuf,
     listener: UnixListener,
+    access: SocketAccess,
+    /// User the socket belongs to, i.e. the daemon's
+    owner_uid: u32,
 }
 
 impl IpcServer {
-    /// Bind to a Unix socket
+    /// Bind to a Unix socket only the daemon's own user (and root) may use
     pub async fn bind(socket_path: &Path) -> Result<Self> {
+        Self::bind_with_access(socket_path, SocketAccess::default()).await
+    }
+
+    /// Bind to a Unix socket with the given permissions and peer allowlist
+    pub async fn bind_with_access(socket_path: &Path, access: SocketAccess) -> Result<Self> {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
         // Remove stale socket if exists
         if socket_path.exists() {
             std::fs::remove_file(socket_path)?;
@@ -33,32 +85,69 @@ impl IpcServer {
         let listener = UnixListener::bind(socket_path)
             .map_err(|e| Error::IpcError(format!("Failed to bind socket: {}", e)))?;
 
-        // Set socket permissions to owner-only (0600) for security
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o600))
-                .map_err(|e| Error::IpcError(format!("Failed to set socket permissions: {}", e)))?;
+        // Owner-only (0600) unless configured otherwise
+        let mode = access.mode.unwrap_or(0o600);
+        std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(mode))
+            .map_err(|e| Error::IpcError(format!("Failed to set socket permissions: {}", e)))?;
+        if let Some(gid) = access.group {
+            std::os::unix::fs::chown(socket_path, None, Some(gid)).map_err(|e| {
+                Error::IpcError(format!("Failed to give the socket to group {}: {}", gid, e))
+            })?;
         }
+        let owner_uid = std::fs::metadata(socket_path)?.uid();
 
-        info!("IPC server listening on {}", socket_path.display());
+        info!(
+            "IPC server listening on {} (mode {:o})",
+            socket_path.display(),
+            mode
+        );
 
         Ok(Self {
             socket_path: socket_path.to_path_buf(),
             listener,
+            access,
+            owner_uid,
         })
     }
 
-    /// Accept a new connection
+    /// Accept the next connection from an allowed peer. Others are told
+    /// they are not allowed and dropped.
     pub async fn accept(&self) -> Result<IpcConnection> {
-        let (stream, _) = self
-            .listener
-            .accept()
-            .await
-            .map_err(|e| Error::IpcError(format!("Accept failed: {}", e)))?;
+        loop {
+            let (stream, _) = self
+                .listener
+                .accept()
+                .await
+                .map_err(|e| Error::IpcError(format!("Accept failed: {}", e)))?;
 
-        debug!("Accepted IPC connection");
-        Ok(IpcConnection::new(stream))
+            let peer = stream
+                .peer_cred()
+                .map(|cred| PeerCred {
+                    uid: cred.uid(),
+                    gid: cred.gid(),
+                    pid: cred.pid(),
+                })
+                .map_err(|e| Error::IpcError(format!("Failed to read peer credentials: {}", e)))?;
+            let mut conn = IpcConnection::new(stream);
+            conn.peer = Some(peer);
+            if self.access.allows(self.owner_uid, &peer) {
+                debug!(
+                    "Accepted IPC connection from uid {} (pid {:?})",
+                    peer.uid, peer.pid
+                );
+                return Ok(conn);
+            }
+
+            warn!(
+                "Rejected IPC connection from uid {} gid {} (pid {:?}): not allowed",
+                peer.uid, peer.gid, peer.pid
+            );
+            tokio::spawn(async move {
+                let err =
+                    Error::PermissionDenied(format!("uid {} may not use this daemon", peer.uid));
+                conn.reject(&err).await;
+            });
+        }
     }
 
     /// Get the socket path
@@ -78,63 +167,231 @@ impl Drop for IpcServer {
     }
 }
 
+/// IPC server on TCP for remote clients; every connection must prove it
+/// knows the token (HMAC challenge-response) before its requests are read
+pub struct TcpIpcServer {
+    listener: TcpListener,
+    token: String,
+}
+
+impl TcpIpcServer {
+    /// Bind to a TCP address
+    pub async fn bind(addr: &str, token: String) -> Result<Self> {
+        if token.is_empty() {
+            return Err(Error::ConfigError(
+                "A token is required to listen on TCP".to_string(),
+            ));
+        }
+
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| Error::IpcError(format!("Failed to bind {}: {}", addr, e)))?;
+        info!("IPC server listening on tcp://{}", addr);
+        if !listener.local_addr()?.ip().is_loopback() {
+            warn!(
+                "tcp://{} is reachable from other hosts; the token is never sent, but requests and responses are not encrypted",
+                addr
+            );
+        }
+
+        Ok(Self { listener, token })
+    }
+
+    /// Accept a new connection; call [`IpcConnection::authenticate`] before serving it
+    pub async fn accept(&self) -> Result<(IpcConnection, SocketAddr)> {
+        let (stream, peer) = self
+            .listener
+            .accept()
+            .await
+            .map_err(|e| Error::IpcError(format!("Accept failed: {}", e)))?;
+        stream.set_nodelay(true).ok();
+
+        debug!("Accepted TCP IPC connection from {}", peer);
+        Ok((IpcConnection::from_tcp(stream), peer))
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
 /// Single IPC connection
 pub struct IpcConnection {
-    stream: UnixStream,
+    reader: RequestReader,
+    writer: ResponseWriter,
+    /// Who is on the other end, for Unix socket connections
+    peer: Option<PeerCred>,
 }
 
 impl IpcConnection {
     pub fn new(stream: UnixStream) -> Self {
-        Self { stream }
+        let (read_half, write_half) = stream.into_split();
+        Self::from_halves(Box::new(read_half), Box::new(write_half))
+    }
+
+    pub fn from_tcp(stream: TcpStream) -> Self {
+        let (read_half, write_half) = stream.into_split();
+        Self::from_halves(Box::new(read_half), Box::new(write_half))
+    }
+
+    fn from_halves(read_half: BoxRead, write_half: BoxWrite) -> Self {
+        Self {
+            reader: RequestReader {
+                reader: BufReader::new(read_half),
+            },
+            writer: ResponseWriter { writer: write_half },
+            peer: None,
+        }
+    }
+
+    /// Credentials of the connected process, for Unix socket connections
+    pub fn peer(&self) -> Option<PeerCred> {
+        self.peer
+    }
+
+    /// Challenge the client with a fresh nonce, check its [`AuthHello`]
+    /// against `token` and tell it the result
+    pub async fn authenticate(&mut self, token: &str) -> Result<()> {
+        let mut nonce = [0u8; NONCE_LEN];
+        ring::rand::SecureRandom::fill(&ring::rand::SystemRandom::new(), &mut nonce).map_err(
+            |_| Error::IpcError("Failed to generate an authentication nonce".to_string()),
+        )?;
+        let challenge = AuthChallenge {
+            nonce: nonce.iter().map(|b| format!("{:02x}", b)).collect(),
+        };
+        let mut json = serde_json::to_string(&challenge)?;
+        json.push('\n');
+        self.writer
+            .writer
+            .write_all(json.as_bytes())
+            .await
+            .map_err(|e| Error::IpcError(format!("Write error: {}", e)))?;
+
+        let timeout = Duration::from_secs(constants::TCP_AUTH_TIMEOUT_SECS);
+        let mut line = String::new();
+        let read = tokio::time::timeout(
+            timeout,
+            (&mut self.reader.reader)
+                .take(MAX_AUTH_SIZE)
+                .read_line(&mut line),
+        )
+        .await
+        .map_err(|_| Error::Timeout("client did not authenticate".to_string()))?
+        .map_err(|e| Error::IpcError(format!("Read error: {}", e)))?;
+
+        let presented = (read > 0)
+            .then(|| serde_json::from_str::<AuthHello>(line.trim()).ok())
+            .flatten()
+            .map(|hello| hello.proof);
+        let expected = AuthHello::answer(&challenge, token).proof;
+        let accepted = presented.is_some_and(|presented| proofs_match(&presented, &expected));
+
+        if !accepted {
+            let err = Error::PermissionDenied("invalid token".to_string());
+            let _ = self.send_response(None, &Response::from_error(&err)).await;
+            return Err(err);
+        }
+        self.send_response(None, &Response::ok("authenticated"))
+            .await
+    }
+
+    /// Answer the client's first request with `err`. Reading it first means
+    /// the client is not writing into a closed socket when the answer comes.
+    pub async fn reject(&mut self, err: &Error) {
+        let timeout = Duration::from_secs(constants::TCP_AUTH_TIMEOUT_SECS);
+        let id = match tokio::time::timeout(timeout, self.read_request()).await {
+            Ok(Ok(Some(envelope))) => envelope.id,
+            _ => None,
+        };
+        let _ = self.send_response(id, &Response::from_error(err)).await;
+    }
+
+    /// Split into halves so requests can be read while responses are written
+    pub fn into_split(self) -> (RequestReader, ResponseWriter) {
+        (self.reader, self.writer)
     }
 
     /// Read a request from the connection
-    pub async fn read_request(&mut self) -> Result<Option<Request>> {
-        // Limit read size to prevent memory exhaustion attacks
-        let limited_reader = (&mut self.stream).take(MAX_MESSAGE_SIZE);
-        let mut reader = BufReader::new(limited_reader);
+    pub async fn read_request(&mut self) -> Result<Option<Envelope<Request>>> {
+        self.reader.read_request().await
+    }
+
+    /// Send a response to the request with the given id
+    pub async fn send_response(&mut self, id: Option<u64>, response: &Response) -> Result<()> {
+        self.writer.send_response(id, response).await
+    }
+}
+
+/// Reading half of a connection
+pub struct RequestReader {
+    reader: BufReader<BoxRead>,
+}
+
+impl RequestReader {
+    /// Read the next request, or None once the client has disconnected
+    pub async fn read_request(&mut self) -> Result<Option<Envelope<Request>>> {
+        // Limit read size to prevent memory exhaustion attacks. The buffer is
+        // kept across calls so pipelined requests are not lost.
+        let mut limited_reader = (&mut self.reader).take(MAX_MESSAGE_SIZE);
         let mut line = String::new();
 
-        match reader.read_line(&mut line).await {
+        match limited_reader.read_line(&mut line).await {
             Ok(0) => Ok(None), // Connection closed
             Ok(_) => {
-                let request: Request = serde_json::from_str(line.trim())
+                let request: Envelope<Request> = serde_json::from_str(line.trim())
                     .map_err(|e| Error::IpcError(format!("Invalid request: {}", e)))?;
-                debug!("Received request: {:?}", request);
+                debug!("Received request {:?}: {:?}", request.id, request.message);
                 Ok(Some(request))
             }
             Err(e) => Err(Error::IpcError(format!("Read error: {}", e))),
         }
     }
+}
+
+/// Writing half of a connection
+pub struct ResponseWriter {
+    writer: BoxWrite,
+}
 
-    /// Send a response
-    pub async fn send_response(&mut self, response: &Response) -> Result<()> {
-        let mut json = serde_json::to_string(response)?;
+impl ResponseWriter {
+    /// Send a response to the request with the given id
+    pub async fn send_response(&mut self, id: Option<u64>, response: &Response) -> Result<()> {
+        let mut json = serde_json::to_string(&Envelope::new(id, response))?;
         json.push('\n');
 
-        self.stream
+        self.writer
             .write_all(json.as_bytes())
             .await
             .map_err(|e| Error::IpcError(format!("Write error: {}", e)))?;
 
-        self.stream
+        self.writer
             .flush()
             .await
             .map_err(|e| Error::IpcError(format!("Flush error: {}", e)))?;
 
-        debug!("Sent response: {:?}", response);
+        debug!("Sent response {:?}: {:?}", id, response);
         Ok(())
     }
 
     /// Send a log line (for streaming)
-    pub async fn send_log_line(&mut self, line: &str) -> Result<()> {
+    pub async fn send_log_line(&mut self, id: Option<u64>, line: &str) -> Result<()> {
         let response = Response::LogLine {
             line: line.to_string(),
         };
-        self.send_response(&response).await
+        self.send_response(id, &response).await
     }
 }
 
+/// Compare proofs without returning early on the first differing byte
+fn proofs_match(presented: &str, expected: &str) -> bool {
+    let (a, b) = (presented.as_bytes(), expected.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,4 +408,140 @@ mod tests {
         drop(server);
         assert!(!socket_path.exists());
     }
+
+    #[tokio::test]
+    async fn test_pipelined_requests() {
+        let dir = tempdir().unwrap();
+        let socket_path = dir.path().join("test.sock");
+        let server = IpcServer::bind(&socket_path).await.unwrap();
+
+        let mut client = UnixStream::connect(&socket_path).await.unwrap();
+        client
+            .write_all(
+                b"{\"request_id\":1,\"type\":\"status\"}\n{\"request_id\":2,\"type\":\"ping\"}\n",
+            )
+            .await
+            .unwrap();
+
+        let (mut reader, mut writer) = server.accept().await.unwrap().into_split();
+        let first = reader.read_request().await.unwrap().unwrap();
+        let second = reader.read_request().await.unwrap().unwrap();
+        assert!(matches!(
+            (first.id, &first.message),
+            (Some(1), Request::Status)
+        ));
+        assert!(matches!(
+            (second.id, &second.message),
+            (Some(2), Request::Ping)
+        ));
+
+        // Answer out of order; the ids tell the client which is which
+        writer
+            .send_response(second.id, &Response::Pong { heartbeat: None })
+            .await
+            .unwrap();
+        writer
+            .send_response(first.id, &Response::ok("done"))
+            .await
+            .unwrap();
+
+        let mut lines = BufReader::new(client).lines();
+        let reply: Envelope<Response> =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(
+            (reply.id, reply.message),
+            (Some(2), Response::Pong { .. })
+        ));
+        let reply: Envelope<Response> =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(
+            (reply.id, reply.message),
+            (Some(1), Response::Ok { .. })
+        ));
+
+        drop(lines);
+        assert!(reader.read_request().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_authentication() {
+        use crate::client::{Endpoint, IpcClient};
+
+        let server = TcpIpcServer::bind("127.0.0.1:0", "s3cret".to_string())
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap().to_string();
+
+        let client = IpcClient::with_endpoint(Endpoint::Tcp {
+            address: address.clone(),
+            token: "s3cret".to_string(),
+        });
+        let daemon = async {
+            let (mut conn, _) = server.accept().await.unwrap();
+            conn.authenticate(server.token()).await.unwrap();
+            let envelope = conn.read_request().await.unwrap().unwrap();
+            assert!(matches!(envelope.message, Request::Ping));
+            conn.send_response(envelope.id, &Response::Pong { heartbeat: None })
+                .await
+                .unwrap();
+        };
+        let (reply, ()) = tokio::join!(client.send(&Request::Ping), daemon);
+        assert!(matches!(reply.unwrap(), Response::Pong { .. }));
+
+        let intruder = IpcClient::with_endpoint(Endpoint::Tcp {
+            address,
+            token: "guess".to_string(),
+        });
+        let daemon = async {
+            let (mut conn, _) = server.accept().await.unwrap();
+            assert!(conn.authenticate(server.token()).await.is_err());
+        };
+        let (reply, ()) = tokio::join!(intruder.send(&Request::Status), daemon);
+        assert!(matches!(reply, Err(Error::PermissionDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_tcp_proof_is_bound_to_challenge() {
+        let server = TcpIpcServer::bind("127.0.0.1:0", "s3cret".to_string())
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+
+        // A proof sniffed from an earlier handshake does not answer a new challenge
+        let replay = async {
+            let stream = TcpStream::connect(address).await.unwrap();
+            let (read_half, mut write_half) = stream.into_split();
+            let mut lines = BufReader::new(read_half).lines();
+            let challenge: AuthChallenge =
+                serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+            let earlier = AuthChallenge {
+                nonce: "00".repeat(NONCE_LEN),
+            };
+            assert_ne!(challenge.nonce, earlier.nonce);
+            let hello = serde_json::to_string(&AuthHello::answer(&earlier, "s3cret")).unwrap();
+            assert!(!hello.contains("s3cret"));
+            write_half
+                .write_all(format!("{}\n", hello).as_bytes())
+                .await
+                .unwrap();
+            let reply: Envelope<Response> =
+                serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+            assert!(matches!(reply.message, Response::Error { .. }));
+        };
+        let daemon = async {
+            let (mut conn, _) = server.accept().await.unwrap();
+            assert!(matches!(
+                conn.authenticate(server.token()).await,
+                Err(Error::PermissionDenied(_))
+            ));
+        };
+        tokio::join!(replay, daemon);
+    }
+
+    #[test]
+    fn test_proofs_match() {
+        assert!(proofs_match("abc", "abc"));
+        assert!(!proofs_match("abd", "abc"));
+        assert!(!proofs_match("ab", "abc"));
+    }
 }
@@ -1,12 +1,18 @@
-//! OxidePM Logs - Log management, rotation, and streaming
+//! OxidePM Logs - Log management, rotation, pruning, and streaming
 
+mod line;
+mod page;
+mod queue;
 mod reader;
 mod rotation;
+mod vacuum;
 mod writer;
 
+pub use page::{read_page, LogCursor};
 pub use reader::LogReader;
 pub use rotation::RotationConfig;
-pub use writer::{LogCapture, LogWriter};
+pub use vacuum::{log_files, remove_older_than, trim_to_size, LogFile};
+pub use writer::{LinePrefix, LineTap, LogCapture, LogWriter};
 
 use oxidepm_core::{constants, Result};
 use std::path::PathBuf;
@@ -0,0 +1,127 @@
+//! Reading captured output into bounded lines
+//!
+//! Lines are read as bytes so a process printing binary data cannot stop
+//! the capture, and no more than the app's `max_line_bytes` of a line is
+//! ever held in memory.
+
+use std::fmt::Write;
+use std::io;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+/// Read through the next newline into `buf`, unmodified, stopping early
+/// after `max` bytes. Returns false at the end of the stream.
+pub(crate) async fn read_chunk<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max: usize,
+) -> io::Result<bool> {
+    buf.clear();
+    while buf.len() < max {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            break;
+        }
+        let room = max - buf.len();
+        let (len, done) = match available.iter().position(|&b| b == b'\n') {
+            Some(newline) if newline < room => (newline + 1, true),
+            _ => (available.len().min(room), false),
+        };
+        buf.extend_from_slice(&available[..len]);
+        reader.consume(len);
+        if done {
+            break;
+        }
+    }
+    Ok(!buf.is_empty())
+}
+
+/// Discard the rest of the current line, returning how many bytes it had
+pub(crate) async fn skip_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<usize> {
+    let mut skipped = 0;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(skipped);
+        }
+        match available.iter().position(|&b| b == b'\n') {
+            Some(newline) => {
+                reader.consume(newline + 1);
+                return Ok(skipped + newline);
+            }
+            None => {
+                let len = available.len();
+                reader.consume(len);
+                skipped += len;
+            }
+        }
+    }
+}
+
+/// Text of a captured line. Invalid UTF-8 and control characters other than
+/// tab and escape (used for colours) are written as `\xNN`.
+pub(crate) fn sanitize(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let mut rest = line.strip_suffix(b"\r").unwrap_or(line);
+    let mut text = String::with_capacity(rest.len());
+    while !rest.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(rest) {
+            Ok(valid) => (valid, 0),
+            Err(e) => {
+                let valid = std::str::from_utf8(&rest[..e.valid_up_to()]).unwrap_or_default();
+                (valid, e.error_len().unwrap_or(rest.len() - e.valid_up_to()))
+            }
+        };
+        for c in valid.chars() {
+            if c.is_control() && c != '\t' && c != '\x1b' {
+                let _ = write!(text, "\\x{:02x}", c as u32);
+            } else {
+                text.push(c);
+            }
+        }
+        let invalid_start = valid.len();
+        for byte in &rest[invalid_start..invalid_start + invalid] {
+            let _ = write!(text, "\\x{:02x}", byte);
+        }
+        rest = &rest[invalid_start + invalid..];
+    }
+    text
+}
+
+/// Mark a line that was cut short
+pub(crate) fn truncated(mut line: String, cut: usize) -> String {
+    let _ = write!(line, "... [{} bytes truncated]", cut);
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize(b"plain line\r\n"), "plain line");
+        assert_eq!(sanitize("caf\u{e9}\tok".as_bytes()), "caf\u{e9}\tok");
+        assert_eq!(sanitize(b"\x1b[31mred\x1b[0m"), "\x1b[31mred\x1b[0m");
+        assert_eq!(
+            sanitize(b"bin\x00\xff\xfeary\x07"),
+            "bin\\x00\\xff\\xfeary\\x07"
+        );
+        // A sequence cut off at the end is escaped byte by byte
+        assert_eq!(sanitize(b"euro \xe2\x82"), "euro \\xe2\\x82");
+    }
+
+    #[tokio::test]
+    async fn test_read_chunk_and_skip_line() {
+        let mut reader = tokio::io::BufReader::new(&b"short\nthis line is long\nnext"[..]);
+        let mut buf = Vec::new();
+
+        assert!(read_chunk(&mut reader, &mut buf, 8).await.unwrap());
+        assert_eq!(buf, b"short\n");
+        assert!(read_chunk(&mut reader, &mut buf, 8).await.unwrap());
+        assert_eq!(buf, b"this lin");
+        assert_eq!(skip_line(&mut reader).await.unwrap(), 9);
+        assert!(read_chunk(&mut reader, &mut buf, 8).await.unwrap());
+        assert_eq!(buf, b"next");
+        assert!(!read_chunk(&mut reader, &mut buf, 8).await.unwrap());
+    }
+}
@@ -0,0 +1,393 @@
+//! Paging through a log and its rotated copies
+//!
+//! A log is read as one chronological stream: the oldest rotated copy
+//! (`path.N`) first and the active file last. Positions are handed out as
+//! cursors of the form `<inode>:<offset>`. Rotation renames files without
+//! changing their inode, so a cursor keeps pointing at the same line while
+//! the files shift around it. Only complete lines of the active file are
+//! returned, so a line still being written shows up whole on a later page.
+
+use crate::vacuum::{log_files, oldest_first, LogFile};
+use oxidepm_core::{Error, LogPage, LogStream, Result};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How much is read at a time when scanning backwards
+const CHUNK_SIZE: u64 = 8192;
+
+/// A position in a log: a byte offset in the file with the given inode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogCursor {
+    pub file: u64,
+    pub offset: u64,
+}
+
+impl fmt::Display for LogCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.offset)
+    }
+}
+
+impl FromStr for LogCursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::config(format!("Invalid log cursor '{}'", s));
+        let (file, offset) = s.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            file: file.parse().map_err(|_| invalid())?,
+            offset: offset.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// One file of the stream
+struct Segment {
+    path: PathBuf,
+    inode: u64,
+    /// Bytes up to and including the last complete line
+    len: u64,
+}
+
+impl Segment {
+    fn cursor(&self, offset: u64) -> LogCursor {
+        LogCursor {
+            file: self.inode,
+            offset,
+        }
+    }
+}
+
+/// The log's files, oldest first
+fn segments(path: &Path) -> Result<Vec<Segment>> {
+    let files = oldest_first(log_files(path));
+
+    let mut segments = Vec::with_capacity(files.len());
+    for LogFile { path, rotated, .. } in files {
+        let Ok(mut file) = File::open(&path) else {
+            // Rotated away since it was listed
+            continue;
+        };
+        let metadata = file.metadata()?;
+        let len = match rotated {
+            true => metadata.len(),
+            false => complete_len(&mut file, metadata.len())?,
+        };
+        segments.push(Segment {
+            path,
+            inode: metadata.ino(),
+            len,
+        });
+    }
+    Ok(segments)
+}
+
+/// Length of `file` up to and including its last newline
+fn complete_len(file: &mut File, size: u64) -> Result<u64> {
+    let mut end = size;
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK_SIZE);
+        let mut buffer = vec![0u8; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut buffer)?;
+        if let Some(newline) = buffer.iter().rposition(|&b| b == b'\n') {
+            return Ok(start + newline as u64 + 1);
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+fn decode(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+/// Up to `n` lines ending at `end`, and the offset of the first one
+fn lines_before(path: &Path, end: u64, n: usize) -> Result<(Vec<String>, u64)> {
+    if n == 0 || end == 0 {
+        return Ok((Vec::new(), end));
+    }
+    let mut file = File::open(path)?;
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut pos = end;
+
+    // Each newline before the last byte ends the line in front of a complete one
+    let separators = |buffer: &[u8]| match buffer.split_last() {
+        Some((_, rest)) => rest.iter().filter(|&&b| b == b'\n').count(),
+        None => 0,
+    };
+    while pos > 0 && separators(&buffer) < n {
+        let start = pos.saturating_sub(CHUNK_SIZE);
+        let mut chunk = vec![0u8; (pos - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+        pos = start;
+    }
+
+    // Unless at the start of the file, the first piece is the tail of an earlier line
+    let skip = match pos {
+        0 => 0,
+        _ => buffer
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(buffer.len(), |i| i + 1),
+    };
+    let mut lines: Vec<(u64, String)> = Vec::new();
+    let mut offset = skip;
+    for line in buffer[skip..].split_inclusive(|&b| b == b'\n') {
+        lines.push((pos + offset as u64, decode(line)));
+        offset += line.len();
+    }
+
+    let lines = lines.split_off(lines.len().saturating_sub(n));
+    let first = lines.first().map_or(end, |(offset, _)| *offset);
+    Ok((lines.into_iter().map(|(_, line)| line).collect(), first))
+}
+
+/// Up to `n` lines starting at `start` and ending by `len`, and the offset after them
+fn lines_after(path: &Path, start: u64, len: u64, n: usize) -> Result<(Vec<String>, u64)> {
+    if n == 0 || start >= len {
+        return Ok((Vec::new(), start));
+    }
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut reader = BufReader::new(file.take(len - start));
+
+    let mut lines = Vec::new();
+    let mut offset = start;
+    let mut line = Vec::new();
+    while lines.len() < n {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        offset += read as u64;
+        lines.push(decode(&line));
+    }
+    Ok((lines, offset))
+}
+
+/// The `n` lines before `before`, or the newest `n` lines
+fn read_before(
+    path: &Path,
+    before: Option<LogCursor>,
+    n: usize,
+) -> Result<(Vec<String>, Option<LogCursor>, Option<LogCursor>)> {
+    let segments = segments(path)?;
+    let Some(last) = segments.len().checked_sub(1) else {
+        return Ok((Vec::new(), None, None));
+    };
+    let (mut index, mut end) = match before {
+        None => (last, segments[last].len),
+        Some(cursor) => match segments.iter().position(|s| s.inode == cursor.file) {
+            Some(index) => (index, cursor.offset.min(segments[index].len)),
+            // That part of the log has been rotated out
+            None => return Ok((Vec::new(), None, None)),
+        },
+    };
+    let newer = segments[index].cursor(end);
+
+    let mut lines: Vec<String> = Vec::new();
+    loop {
+        let (mut page, first) = lines_before(&segments[index].path, end, n - lines.len())?;
+        page.append(&mut lines);
+        lines = page;
+        end = first;
+        if lines.len() >= n || index == 0 {
+            break;
+        }
+        index -= 1;
+        end = segments[index].len;
+    }
+
+    let older = (index > 0 || end > 0).then(|| segments[index].cursor(end));
+    Ok((lines, older, Some(newer)))
+}
+
+/// The `n` lines after `after`
+fn read_after(
+    path: &Path,
+    after: LogCursor,
+    n: usize,
+) -> Result<(Vec<String>, Option<LogCursor>, Option<LogCursor>)> {
+    let segments = segments(path)?;
+    if segments.is_empty() {
+        return Ok((Vec::new(), None, None));
+    }
+    let (mut index, mut offset) = match segments.iter().position(|s| s.inode == after.file) {
+        Some(index) => (index, after.offset.min(segments[index].len)),
+        // Rotated out: carry on from the oldest line still kept
+        None => (0, 0),
+    };
+    let older = (index > 0 || offset > 0).then(|| segments[index].cursor(offset));
+
+    let mut lines = Vec::new();
+    loop {
+        let segment = &segments[index];
+        let (mut page, next) = lines_after(&segment.path, offset, segment.len, n - lines.len())?;
+        lines.append(&mut page);
+        offset = next;
+        if lines.len() >= n || index + 1 == segments.len() {
+            break;
+        }
+        index += 1;
+        offset = 0;
+    }
+
+    Ok((lines, older, Some(segments[index].cursor(offset))))
+}
+
+/// Read a page of the log at `path`: the `lines` before `before`, the
+/// `lines` after `after`, or the newest `lines` when neither is given
+pub fn read_page(
+    path: &Path,
+    stream: LogStream,
+    lines: usize,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Result<LogPage> {
+    let (lines, older, newer) = match (before, after) {
+        (Some(_), Some(_)) => {
+            return Err(Error::config("Use either 'before' or 'after', not both"))
+        }
+        (Some(before), None) => read_before(path, Some(before.parse()?), lines)?,
+        (None, Some(after)) => read_after(path, after.parse()?, lines)?,
+        (None, None) => read_before(path, None, lines)?,
+    };
+    Ok(LogPage {
+        stream,
+        lines,
+        older: older.map(|c| c.to_string()),
+        newer: newer.map(|c| c.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    /// `app.log.2` holds lines 1-3, `app.log.1` lines 4-6, `app.log` lines 7-9
+    /// and a partial line
+    fn rotated_log(dir: &TempDir) -> PathBuf {
+        let path = dir.path().join("app.log");
+        let write = |name: &str, content: &str| {
+            let mut file = File::create(dir.path().join(name)).unwrap();
+            file.write_all(content.as_bytes()).unwrap();
+        };
+        write("app.log.2", "line 1\nline 2\nline 3\n");
+        write("app.log.1", "line 4\nline 5\nline 6\n");
+        write("app.log", "line 7\nline 8\nline 9\npartial");
+        path
+    }
+
+    fn page(path: &Path, lines: usize, before: Option<&str>, after: Option<&str>) -> LogPage {
+        read_page(path, LogStream::Out, lines, before, after).unwrap()
+    }
+
+    #[test]
+    fn test_newest_lines_skip_partial_line() {
+        let dir = TempDir::new().unwrap();
+        let path = rotated_log(&dir);
+
+        let newest = page(&path, 2, None, None);
+        assert_eq!(newest.lines, vec!["line 8", "line 9"]);
+        assert!(newest.older.is_some());
+    }
+
+    #[test]
+    fn test_page_backwards_across_rotated_files() {
+        let dir = TempDir::new().unwrap();
+        let path = rotated_log(&dir);
+
+        let mut seen = Vec::new();
+        let mut before: Option<String> = None;
+        loop {
+            let page = page(&path, 4, before.as_deref(), None);
+            let mut lines = page.lines;
+            lines.append(&mut seen);
+            seen = lines;
+            match page.older {
+                Some(older) => before = Some(older),
+                None => break,
+            }
+        }
+        let expected: Vec<String> = (1..=9).map(|i| format!("line {}", i)).collect();
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn test_page_forwards_and_follow_new_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = rotated_log(&dir);
+
+        let first = page(&path, 9, None, None);
+        assert_eq!(first.lines.len(), 9);
+        assert!(first.older.is_none());
+
+        // Lines 3..5 start after line 2
+        let start = page(&path, 7, None, None).older.unwrap();
+        let next = page(&path, 3, None, Some(&start));
+        assert_eq!(next.lines, vec!["line 3", "line 4", "line 5"]);
+
+        // Nothing new until the partial line is finished
+        let end = first.newer.unwrap();
+        assert!(page(&path, 10, None, Some(&end)).lines.is_empty());
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b" done\nline 11\n").unwrap();
+        assert_eq!(
+            page(&path, 10, None, Some(&end)).lines,
+            vec!["partial done", "line 11"]
+        );
+    }
+
+    #[test]
+    fn test_cursor_survives_rotation() {
+        let dir = TempDir::new().unwrap();
+        let path = rotated_log(&dir);
+        let before = page(&path, 1, None, None).older.unwrap();
+
+        // Rotate: .2 is dropped, .1 -> .2, active -> .1
+        std::fs::remove_file(dir.path().join("app.log.2")).unwrap();
+        std::fs::rename(dir.path().join("app.log.1"), dir.path().join("app.log.2")).unwrap();
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        File::create(&path)
+            .unwrap()
+            .write_all(b"line 10\n")
+            .unwrap();
+
+        assert_eq!(
+            page(&path, 2, Some(&before), None).lines,
+            vec!["line 7", "line 8"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        let dir = TempDir::new().unwrap();
+        let path = rotated_log(&dir);
+        assert!(read_page(&path, LogStream::Out, 5, Some("nope"), None).is_err());
+        assert!(read_page(&path, LogStream::Out, 5, Some("1:2"), Some("1:2")).is_err());
+    }
+
+    #[test]
+    fn test_missing_log() {
+        let dir = TempDir::new().unwrap();
+        let empty = page(&dir.path().join("none.log"), 5, None, None);
+        assert!(empty.lines.is_empty());
+        assert!(empty.older.is_none() && empty.newer.is_none());
+    }
+}
@@ -0,0 +1,131 @@
+//! Bounded buffer between a process pipe and its log writer
+//!
+//! Each captured stream has one reader pushing lines and one writer taking
+//! them in batches. When the buffer is full the overflow policy decides:
+//! `Block` stops reading until the writer catches up, so the app blocks on
+//! its full pipe; `DropOldest` keeps reading and counts what it discards.
+
+use oxidepm_core::LogOverflow;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+pub(crate) struct LineQueue<T> {
+    lines: Mutex<VecDeque<T>>,
+    capacity: usize,
+    overflow: LogOverflow,
+    dropped: Arc<AtomicU64>,
+    closed: AtomicBool,
+    /// Woken when lines are added or the queue is closed
+    filled: Notify,
+    /// Woken when lines are taken
+    drained: Notify,
+}
+
+impl<T> LineQueue<T> {
+    pub(crate) fn new(capacity: usize, overflow: LogOverflow, dropped: Arc<AtomicU64>) -> Self {
+        Self {
+            lines: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            overflow,
+            dropped,
+            closed: AtomicBool::new(false),
+            filled: Notify::new(),
+            drained: Notify::new(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<T>> {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Add a line, waiting for room or dropping the oldest line when full
+    pub(crate) async fn push(&self, line: T) {
+        loop {
+            {
+                let mut lines = self.lock();
+                if lines.len() < self.capacity || self.overflow == LogOverflow::DropOldest {
+                    if lines.len() >= self.capacity {
+                        lines.pop_front();
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    lines.push_back(line);
+                    self.filled.notify_one();
+                    return;
+                }
+            }
+            self.drained.notified().await;
+        }
+    }
+
+    /// Take every buffered line, waiting for some; `None` once closed and empty
+    pub(crate) async fn take(&self) -> Option<Vec<T>> {
+        loop {
+            {
+                let mut lines = self.lock();
+                if !lines.is_empty() {
+                    let batch = lines.drain(..).collect();
+                    self.drained.notify_one();
+                    return Some(batch);
+                }
+                if self.closed.load(Ordering::Acquire) {
+                    return None;
+                }
+            }
+            self.filled.notified().await;
+        }
+    }
+
+    /// No more lines will be pushed
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.filled.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drop_oldest() {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let queue = LineQueue::new(2, LogOverflow::DropOldest, Arc::clone(&dropped));
+        for line in ["a", "b", "c", "d"] {
+            queue.push(line.to_string()).await;
+        }
+        queue.close();
+
+        assert_eq!(
+            queue.take().await,
+            Some(vec!["c".to_string(), "d".to_string()])
+        );
+        assert_eq!(queue.take().await, None);
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_block_waits_for_writer() {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let queue = Arc::new(LineQueue::new(2, LogOverflow::Block, Arc::clone(&dropped)));
+
+        let reader = Arc::clone(&queue);
+        let pushing = tokio::spawn(async move {
+            for i in 0..10 {
+                reader.push(i.to_string()).await;
+            }
+            reader.close();
+        });
+
+        let mut taken = Vec::new();
+        while let Some(batch) = queue.take().await {
+            assert!(batch.len() <= 2);
+            taken.extend(batch);
+        }
+        pushing.await.unwrap();
+
+        assert_eq!(taken, (0..10).map(|i| i.to_string()).collect::<Vec<_>>());
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+    }
+}
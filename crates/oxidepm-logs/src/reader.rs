@@ -1,10 +1,13 @@
 //! Log reader for tail and follow operations
 
+use crate::vacuum::{log_files, oldest_first};
+use crate::writer::TIMESTAMP_FORMAT;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use oxidepm_core::{Error, Result};
-use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tokio::sync::mpsc;
 use tracing::debug;
 
@@ -18,87 +21,46 @@ impl LogReader {
         Self { path }
     }
 
-    /// Read the last N lines from the log file
+    /// Read the last N lines, continuing into rotated files (`path.1`,
+    /// `path.2`, ...) when the active file holds fewer
     pub fn tail(&self, n: usize) -> Result<Vec<String>> {
-        if !self.path.exists() {
-            return Ok(vec![]);
-        }
-
-        let file = File::open(&self.path)?;
-        let reader = BufReader::new(file);
-
-        // Read all lines and keep last N
-        let mut lines: VecDeque<String> = VecDeque::with_capacity(n + 1);
-
-        for line_result in reader.lines() {
-            let line = line_result?;
-            lines.push_back(line);
-            if lines.len() > n {
-                lines.pop_front();
+        let mut lines = Vec::new();
+        for file in oldest_first(log_files(&self.path)).iter().rev() {
+            if lines.len() >= n {
+                break;
             }
+            let mut older = tail_file(&file.path, n - lines.len())?;
+            older.append(&mut lines);
+            lines = older;
         }
-
-        Ok(lines.into_iter().collect())
+        Ok(lines)
     }
 
-    /// Read the last N lines efficiently (seeking from end)
+    /// Read the last N lines of the active file efficiently (seeking from end)
     pub fn tail_efficient(&self, n: usize) -> Result<Vec<String>> {
-        if !self.path.exists() {
-            return Ok(vec![]);
-        }
-
-        let mut file = File::open(&self.path)?;
-        let file_size = file.metadata()?.len();
-
-        if file_size == 0 {
-            return Ok(vec![]);
-        }
+        tail_file(&self.path, n)
+    }
 
-        // Start reading from end, chunk by chunk
-        let chunk_size = 8192u64;
+    /// Read the lines written at or after `since`, at most the last `n` of
+    /// them, continuing into rotated files. Lines without a timestamp go
+    /// with the line before them.
+    pub fn since(&self, since: DateTime<Utc>, n: usize) -> Result<Vec<String>> {
+        let cutoff = SystemTime::from(since);
         let mut lines = Vec::new();
-        let mut position = file_size;
-        let mut partial_line = String::new();
-
-        while position > 0 && lines.len() < n {
-            let read_size = std::cmp::min(chunk_size, position);
-            position -= read_size;
-
-            file.seek(SeekFrom::Start(position))?;
-
-            let mut buffer = vec![0u8; read_size as usize];
-            file.read_exact(&mut buffer)?;
-
-            // Convert to string and split by newlines
-            let chunk = String::from_utf8_lossy(&buffer);
-            let chunk_with_partial = format!("{}{}", chunk, partial_line);
-
-            let mut chunk_lines: Vec<&str> = chunk_with_partial.lines().collect();
-
-            // The first element might be partial (continuing from previous chunk)
-            if position > 0 && !chunk_lines.is_empty() {
-                partial_line = chunk_lines.remove(0).to_string();
-            } else {
-                partial_line.clear();
+        for file in oldest_first(log_files(&self.path)).iter().rev() {
+            // Nothing in a file last written before the cutoff is recent enough
+            if lines.len() >= n || file.modified < cutoff {
+                break;
             }
-
-            // Add lines in reverse (we're reading backwards)
-            for line in chunk_lines.into_iter().rev() {
-                if lines.len() >= n {
-                    break;
-                }
-                lines.push(line.to_string());
+            let (mut recent, whole) = read_since(&file.path, since)?;
+            recent.drain(..recent.len().saturating_sub(n - lines.len()));
+            recent.append(&mut lines);
+            lines = recent;
+            // Older files only hold older lines
+            if !whole {
+                break;
             }
         }
-
-        // Add any remaining partial line
-        if !partial_line.is_empty() && lines.len() < n {
-            lines.push(partial_line);
-        }
-
-        // Reverse to get correct order
-        lines.reverse();
-
         Ok(lines)
     }
 
@@ -136,6 +98,111 @@ impl LogReader {
     }
 }
 
+/// Read the last N lines of one file, seeking from the end
+fn tail_file(path: &Path, n: usize) -> Result<Vec<String>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+
+    if file_size == 0 {
+        return Ok(vec![]);
+    }
+
+    // Start reading from end, chunk by chunk
+    let chunk_size = 8192u64;
+    let mut lines = Vec::new();
+    let mut position = file_size;
+    let mut partial_line = String::new();
+
+    while position > 0 && lines.len() < n {
+        let read_size = std::cmp::min(chunk_size, position);
+        position -= read_size;
+
+        file.seek(SeekFrom::Start(position))?;
+
+        let mut buffer = vec![0u8; read_size as usize];
+        file.read_exact(&mut buffer)?;
+
+        // Convert to string and split by newlines
+        let chunk = String::from_utf8_lossy(&buffer);
+        let chunk_with_partial = format!("{}{}", chunk, partial_line);
+
+        let mut chunk_lines: Vec<&str> = chunk_with_partial.lines().collect();
+
+        // The first element might be partial (continuing from previous chunk)
+        if position > 0 && !chunk_lines.is_empty() {
+            partial_line = chunk_lines.remove(0).to_string();
+        } else {
+            partial_line.clear();
+        }
+
+        // Add lines in reverse (we're reading backwards)
+        for line in chunk_lines.into_iter().rev() {
+            if lines.len() >= n {
+                break;
+            }
+            lines.push(line.to_string());
+        }
+    }
+
+    // Add any remaining partial line
+    if !partial_line.is_empty() && lines.len() < n {
+        lines.push(partial_line);
+    }
+
+    // Reverse to get correct order
+    lines.reverse();
+
+    Ok(lines)
+}
+
+/// Lines of one file from the first one stamped at or after `since`, and
+/// whether that was the whole file
+fn read_since(path: &Path, since: DateTime<Utc>) -> Result<(Vec<String>, bool)> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = Vec::new();
+    // Lines before the first timestamp, kept if that one is recent
+    let mut pending = Vec::new();
+    let mut recent = None;
+    let mut whole = true;
+
+    for line in reader.split(b'\n') {
+        let line = String::from_utf8_lossy(&line?)
+            .trim_end_matches('\r')
+            .to_string();
+        if let Some(timestamp) = line_timestamp(&line) {
+            recent = Some(timestamp >= since);
+        }
+        match recent {
+            None => pending.push(line),
+            Some(true) => {
+                lines.append(&mut pending);
+                lines.push(line);
+            }
+            Some(false) => {
+                pending.clear();
+                whole = false;
+            }
+        }
+    }
+    // A file without any timestamps is judged by when it was written
+    lines.append(&mut pending);
+    Ok((lines, whole))
+}
+
+/// The timestamp `LogWriter` put in front of a line, in either format
+fn line_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    let (stamp, _) = line.strip_prefix('[')?.split_once(']')?;
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(stamp) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    let timestamp = NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT).ok()?;
+    Some(timestamp.and_utc())
+}
+
 /// Follow a file for new content
 async fn follow_file(path: &Path, tx: mpsc::Sender<String>) -> Result<()> {
     use notify::{RecommendedWatcher, RecursiveMode, Watcher};
@@ -186,7 +253,6 @@ async fn follow_file(path: &Path, tx: mpsc::Sender<String>) -> Result<()> {
 
         for line_result in reader.lines() {
             let line = line_result?;
-            position += line.len() as u64 + 1; // +1 for newline
 
             if tx.send(line).await.is_err() {
                 return Ok(()); // Channel closed
@@ -264,6 +330,90 @@ mod tests {
         assert!(lines[9].contains("100"));
     }
 
+    #[test]
+    fn test_tail_across_rotated_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        for (file, range) in [
+            ("app.log.2", 1..=3),
+            ("app.log.1", 4..=6),
+            ("app.log", 7..=8),
+        ] {
+            let mut file = File::create(dir.path().join(file)).unwrap();
+            for i in range {
+                writeln!(file, "Line {}", i).unwrap();
+            }
+        }
+
+        let reader = LogReader::new(path);
+        assert_eq!(reader.tail(2).unwrap(), vec!["Line 7", "Line 8"]);
+        assert_eq!(
+            reader.tail(5).unwrap(),
+            vec!["Line 4", "Line 5", "Line 6", "Line 7", "Line 8"]
+        );
+        assert_eq!(reader.tail(100).unwrap().len(), 8);
+        // The efficient variant stays within the active file
+        assert_eq!(reader.tail_efficient(5).unwrap(), vec!["Line 7", "Line 8"]);
+    }
+
+    #[test]
+    fn test_since_across_rotated_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(
+            dir.path().join("app.log.1"),
+            "[2026-10-17 09:00:00] old\n[2026-10-17 10:00:00] start\n  continued\n",
+        )
+        .unwrap();
+        std::fs::write(&path, "[2026-10-17 11:00:00] end\n").unwrap();
+
+        let reader = LogReader::new(path);
+        let since = |stamp: &str| {
+            NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT)
+                .unwrap()
+                .and_utc()
+        };
+        assert_eq!(
+            reader
+                .since(since("2026-10-17 10:00:00"), usize::MAX)
+                .unwrap(),
+            vec![
+                "[2026-10-17 10:00:00] start",
+                "  continued",
+                "[2026-10-17 11:00:00] end"
+            ]
+        );
+        assert_eq!(
+            reader.since(since("2026-10-17 08:00:00"), 2).unwrap(),
+            vec!["  continued", "[2026-10-17 11:00:00] end"]
+        );
+        assert_eq!(
+            reader
+                .since(since("2026-10-17 10:30:00"), usize::MAX)
+                .unwrap(),
+            vec!["[2026-10-17 11:00:00] end"]
+        );
+    }
+
+    #[test]
+    fn test_line_timestamp() {
+        let since = DateTime::parse_from_rfc3339("2026-10-17T09:00:00.500Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            line_timestamp("[2026-10-17T09:00:00.500Z] [2] ready"),
+            Some(since)
+        );
+        assert_eq!(
+            line_timestamp("[2026-10-17 09:00:00] ready")
+                .unwrap()
+                .to_rfc3339(),
+            "2026-10-17T09:00:00+00:00"
+        );
+        assert_eq!(line_timestamp("[info] ready"), None);
+        assert_eq!(line_timestamp("ready"), None);
+    }
+
     #[test]
     fn test_size() {
         let dir = TempDir::new().unwrap();
@@ -0,0 +1,209 @@
+//! Pruning of log files by age and size
+
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// A log file on disk: an active file or one of its rotated copies
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+    pub rotated: bool,
+}
+
+impl LogFile {
+    fn stat(path: PathBuf, rotated: bool) -> Option<Self> {
+        let metadata = fs::metadata(&path).ok()?;
+        // /dev/null, FIFOs and the like have nothing to prune
+        if !metadata.is_file() {
+            return None;
+        }
+        Some(Self {
+            path,
+            size: metadata.len(),
+            modified: metadata.modified().ok()?,
+            rotated,
+        })
+    }
+}
+
+/// The active log file at `path` and its rotated copies (`path.1`, `path.2`, ...)
+pub fn log_files(path: &Path) -> Vec<LogFile> {
+    let mut files: Vec<LogFile> = LogFile::stat(path.to_path_buf(), false)
+        .into_iter()
+        .collect();
+
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return files;
+    };
+    let prefix = format!("{}.", name.to_string_lossy());
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let is_rotated = file_name
+            .to_string_lossy()
+            .strip_prefix(&prefix)
+            .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()));
+        if is_rotated {
+            files.extend(LogFile::stat(entry.path(), true));
+        }
+    }
+    files
+}
+
+/// Order a log's files from the oldest rotated copy to the active file
+pub(crate) fn oldest_first(mut files: Vec<LogFile>) -> Vec<LogFile> {
+    // `path.3` is older than `path.1`; the active file is newest
+    let index = |file: &LogFile| match file.rotated {
+        true => file
+            .path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+            .unwrap_or(0),
+        false => 0,
+    };
+    files.sort_by_key(|file| std::cmp::Reverse(index(file)));
+    files
+}
+
+/// Delete rotated files last written before `cutoff`, and empty active files
+/// that have not been written since. Returns the number of bytes freed.
+pub fn remove_older_than(files: &mut Vec<LogFile>, cutoff: SystemTime) -> u64 {
+    let mut freed = 0;
+    files.retain_mut(|file| {
+        if file.modified >= cutoff || file.size == 0 {
+            return true;
+        }
+        if file.rotated {
+            match fs::remove_file(&file.path) {
+                Ok(()) => {
+                    freed += file.size;
+                    return false;
+                }
+                Err(e) => warn!("Failed to remove {}: {}", file.path.display(), e),
+            }
+        } else {
+            // Writers open logs in append mode, so truncating in place is safe
+            match OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .open(&file.path)
+            {
+                Ok(_) => {
+                    freed += file.size;
+                    file.size = 0;
+                }
+                Err(e) => warn!("Failed to truncate {}: {}", file.path.display(), e),
+            }
+        }
+        true
+    });
+    freed
+}
+
+/// Delete rotated files, oldest first, until the total size of `files` is
+/// at most `max_bytes`. Active files are never touched, so the total can
+/// stay above the limit. Returns the number of bytes freed.
+pub fn trim_to_size(files: &mut Vec<LogFile>, max_bytes: u64) -> u64 {
+    let mut total: u64 = files.iter().map(|file| file.size).sum();
+    files.sort_by_key(|file| file.modified);
+
+    let mut freed = 0;
+    files.retain(|file| {
+        if total <= max_bytes || !file.rotated {
+            return true;
+        }
+        match fs::remove_file(&file.path) {
+            Ok(()) => {
+                total -= file.size;
+                freed += file.size;
+                false
+            }
+            Err(e) => {
+                warn!("Failed to remove {}: {}", file.path.display(), e);
+                true
+            }
+        }
+    });
+    freed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Write `size` bytes to `path` and backdate it by `age_secs`
+    fn write_log(path: &Path, size: usize, age_secs: u64) {
+        fs::write(path, vec![b'x'; size]).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age_secs);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_log_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("api-out.log");
+        write_log(&path, 10, 0);
+        write_log(&dir.path().join("api-out.log.1"), 20, 60);
+        write_log(&dir.path().join("api-out.log.bak"), 30, 60);
+        write_log(&dir.path().join("api-err.log.1"), 40, 60);
+
+        let mut files = log_files(&path);
+        files.sort_by_key(|file| file.size);
+        let found: Vec<_> = files.iter().map(|file| (file.size, file.rotated)).collect();
+        assert_eq!(found, vec![(10, false), (20, true)]);
+    }
+
+    #[test]
+    fn test_remove_older_than() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("api-out.log");
+        write_log(&path, 10, 3 * 86400);
+        write_log(&dir.path().join("api-out.log.1"), 20, 3 * 86400);
+        write_log(&dir.path().join("api-out.log.2"), 30, 10 * 86400);
+
+        let mut files = log_files(&path);
+        let cutoff = SystemTime::now() - Duration::from_secs(7 * 86400);
+        assert_eq!(remove_older_than(&mut files, cutoff), 30);
+        assert!(!dir.path().join("api-out.log.2").exists());
+
+        let cutoff = SystemTime::now() - Duration::from_secs(86400);
+        assert_eq!(remove_older_than(&mut files, cutoff), 30);
+        assert!(!dir.path().join("api-out.log.1").exists());
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_trim_to_size() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("api-out.log");
+        write_log(&path, 50, 0);
+        write_log(&dir.path().join("api-out.log.1"), 30, 60);
+        write_log(&dir.path().join("api-out.log.2"), 30, 120);
+        write_log(&dir.path().join("api-out.log.3"), 30, 180);
+
+        let mut files = log_files(&path);
+        assert_eq!(trim_to_size(&mut files, 100), 60);
+        assert!(dir.path().join("api-out.log.1").exists());
+        assert!(!dir.path().join("api-out.log.2").exists());
+        assert!(!dir.path().join("api-out.log.3").exists());
+
+        // The active file is kept even when it alone is over the limit
+        assert_eq!(trim_to_size(&mut files, 10), 30);
+        assert!(path.exists());
+    }
+}
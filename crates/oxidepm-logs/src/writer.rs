@@ -1,23 +1,65 @@
 //! Log writer with rotation support
 
-use chrono::Utc;
-use oxidepm_core::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use oxidepm_core::{constants, AppSpec, LogOverflow, LogTimestamps, Result};
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, BufReader};
 use tokio::process::{ChildStderr, ChildStdout};
 use tokio::sync::mpsc;
 use tracing::{debug, warn};
 
+use crate::line::{read_chunk, sanitize, skip_line, truncated};
+use crate::queue::LineQueue;
 use crate::rotation::RotationConfig;
 
+/// Format of the timestamp each line is prefixed with, in UTC
+pub(crate) const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// What is written in front of each line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinePrefix {
+    pub timestamps: LogTimestamps,
+    /// Cluster instance, written after RFC 3339 timestamps
+    pub instance: Option<u32>,
+}
+
+impl LinePrefix {
+    /// The prefix an app's logs are configured with
+    pub fn for_app(spec: &AppSpec) -> Self {
+        Self {
+            timestamps: spec.logs.timestamps,
+            instance: spec.instance_id,
+        }
+    }
+
+    fn format(&self, now: DateTime<Utc>) -> String {
+        match self.timestamps {
+            LogTimestamps::Seconds => format!("[{}] ", now.format(TIMESTAMP_FORMAT)),
+            LogTimestamps::Rfc3339 => {
+                let stamp = now.to_rfc3339_opts(SecondsFormat::Millis, true);
+                match self.instance {
+                    Some(instance) => format!("[{}] [{}] ", stamp, instance),
+                    None => format!("[{}] ", stamp),
+                }
+            }
+            LogTimestamps::Off => String::new(),
+        }
+    }
+}
+
 /// Log writer that handles rotation
 pub struct LogWriter {
     path: PathBuf,
     writer: BufWriter<File>,
     config: RotationConfig,
     current_size: u64,
+    /// Only regular files are rotated (not e.g. /dev/null or a FIFO)
+    rotatable: bool,
+    prefix: LinePrefix,
     /// Channel to broadcast new log lines
     broadcast_tx: Option<mpsc::Sender<String>>,
 }
@@ -35,7 +77,8 @@ impl LogWriter {
             .append(true)
             .open(&path)?;
 
-        let current_size = file.metadata()?.len();
+        let metadata = file.metadata()?;
+        let current_size = metadata.len();
         let writer = BufWriter::new(file);
 
         Ok(Self {
@@ -43,10 +86,18 @@ impl LogWriter {
             writer,
             config,
             current_size,
+            rotatable: metadata.is_file(),
+            prefix: LinePrefix::default(),
             broadcast_tx: None,
         })
     }
 
+    /// Write `prefix` in front of each line instead of the default timestamp
+    pub fn with_prefix(mut self, prefix: LinePrefix) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
     /// Set up broadcasting for live log streaming
     pub fn with_broadcast(mut self, tx: mpsc::Sender<String>) -> Self {
         self.broadcast_tx = Some(tx);
@@ -55,8 +106,7 @@ impl LogWriter {
 
     /// Write a line to the log
     pub fn write_line(&mut self, line: &str) -> Result<()> {
-        let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S");
-        let formatted = format!("[{}] {}\n", timestamp, line);
+        let formatted = format!("{}{}\n", self.prefix.format(Utc::now()), line);
         let bytes = formatted.as_bytes();
 
         self.writer.write_all(bytes)?;
@@ -70,7 +120,7 @@ impl LogWriter {
         }
 
         // Check if rotation is needed
-        if self.current_size >= self.config.max_size_bytes {
+        if self.rotatable && self.current_size >= self.config.max_size_bytes {
             self.rotate()?;
         }
 
@@ -90,7 +140,7 @@ impl LogWriter {
             }
         }
 
-        if self.current_size >= self.config.max_size_bytes {
+        if self.rotatable && self.current_size >= self.config.max_size_bytes {
             self.rotate()?;
         }
 
@@ -156,59 +206,187 @@ fn rotated_path(base: &Path, index: usize) -> PathBuf {
 }
 
 /// Async log capture from process stdout/stderr
+/// Called with every captured line, after it is written
+pub type LineTap = Arc<dyn Fn(&str) + Send + Sync>;
+
 pub struct LogCapture {
-    pub stdout_writer: LogWriter,
-    pub stderr_writer: LogWriter,
+    stdout_writer: Arc<Mutex<LogWriter>>,
+    /// The same writer as stdout when both streams go to one file
+    stderr_writer: Arc<Mutex<LogWriter>>,
+    tap: Option<LineTap>,
+    overflow: LogOverflow,
+    dropped: Arc<AtomicU64>,
+    max_line_bytes: usize,
+    raw: bool,
 }
 
 impl LogCapture {
-    pub fn new(app_name: &str, config: RotationConfig) -> Result<Self> {
-        let stdout_path = crate::stdout_path(app_name);
-        let stderr_path = crate::stderr_path(app_name);
+    pub fn new(stdout_path: PathBuf, stderr_path: PathBuf, config: RotationConfig) -> Result<Self> {
+        let stdout_writer = Arc::new(Mutex::new(LogWriter::new(
+            stdout_path.clone(),
+            config.clone(),
+        )?));
+        let stderr_writer = if stderr_path == stdout_path {
+            Arc::clone(&stdout_writer)
+        } else {
+            Arc::new(Mutex::new(LogWriter::new(stderr_path, config)?))
+        };
 
         Ok(Self {
-            stdout_writer: LogWriter::new(stdout_path, config.clone())?,
-            stderr_writer: LogWriter::new(stderr_path, config)?,
+            stdout_writer,
+            stderr_writer,
+            tap: None,
+            overflow: LogOverflow::default(),
+            dropped: Arc::new(AtomicU64::new(0)),
+            max_line_bytes: constants::DEFAULT_LOG_MAX_LINE_BYTES,
+            raw: false,
         })
     }
 
+    /// Write `prefix` in front of every line of both streams
+    pub fn with_prefix(self, prefix: LinePrefix) -> Self {
+        for writer in [&self.stdout_writer, &self.stderr_writer] {
+            writer.lock().unwrap_or_else(|e| e.into_inner()).prefix = prefix;
+        }
+        self
+    }
+
+    /// Also hand every line of both streams to `tap`
+    pub fn with_tap(mut self, tap: LineTap) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    /// What to do when a stream's buffer fills up
+    pub fn with_overflow(mut self, overflow: LogOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Cut lines longer than `max` bytes, or split them when raw
+    pub fn with_max_line_bytes(mut self, max: usize) -> Self {
+        self.max_line_bytes = max.max(1);
+        self
+    }
+
+    /// Write output exactly as printed: no timestamps, truncation or escaping
+    pub fn with_raw(mut self, raw: bool) -> Self {
+        self.raw = raw;
+        self
+    }
+
+    /// Lines dropped so far from both streams
+    pub fn dropped_lines(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.dropped)
+    }
+
     /// Spawn tasks to capture stdout and stderr
     pub fn spawn_capture(
-        mut self,
+        self,
         stdout: Option<ChildStdout>,
         stderr: Option<ChildStderr>,
     ) -> (
         Option<tokio::task::JoinHandle<()>>,
         Option<tokio::task::JoinHandle<()>>,
     ) {
-        let stdout_handle = stdout.map(|out| {
-            tokio::spawn(async move {
-                let reader = BufReader::new(out);
-                let mut lines = reader.lines();
-
-                while let Ok(Some(line)) = lines.next_line().await {
-                    if let Err(e) = self.stdout_writer.write_line(&line) {
-                        warn!("Failed to write stdout: {}", e);
-                    }
-                }
-            })
-        });
+        let buffer = |name| StreamBuffer {
+            overflow: self.overflow,
+            dropped: Arc::clone(&self.dropped),
+            max_line_bytes: self.max_line_bytes,
+            raw: self.raw,
+            name,
+        };
+        let stdout_handle =
+            stdout.map(|out| capture(out, self.stdout_writer, self.tap.clone(), buffer("stdout")));
+        let stderr_handle =
+            stderr.map(|err| capture(err, self.stderr_writer, self.tap.clone(), buffer("stderr")));
 
-        let stderr_handle = stderr.map(|err| {
-            tokio::spawn(async move {
-                let reader = BufReader::new(err);
-                let mut lines = reader.lines();
+        (stdout_handle, stderr_handle)
+    }
+}
 
-                while let Ok(Some(line)) = lines.next_line().await {
-                    if let Err(e) = self.stderr_writer.write_line(&line) {
-                        warn!("Failed to write stderr: {}", e);
+/// How one stream is read and buffered
+struct StreamBuffer {
+    overflow: LogOverflow,
+    dropped: Arc<AtomicU64>,
+    max_line_bytes: usize,
+    raw: bool,
+    name: &'static str,
+}
+
+/// A piece of captured output waiting to be written
+enum Output {
+    Line(String),
+    /// Raw bytes, a line or up to `max_line_bytes` of one
+    Raw(Vec<u8>),
+}
+
+/// Read lines into a bounded queue while writing earlier ones in batches
+fn capture<R: AsyncRead + Unpin + Send + 'static>(
+    stream: R,
+    writer: Arc<Mutex<LogWriter>>,
+    tap: Option<LineTap>,
+    buffer: StreamBuffer,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let queue = LineQueue::new(constants::LOG_BUFFER_LINES, buffer.overflow, buffer.dropped);
+        let name = buffer.name;
+
+        let read = async {
+            let mut reader = BufReader::new(stream);
+            let mut buf = Vec::new();
+            while let Ok(true) = read_chunk(&mut reader, &mut buf, buffer.max_line_bytes).await {
+                if buffer.raw {
+                    queue.push(Output::Raw(std::mem::take(&mut buf))).await;
+                    continue;
+                }
+                let mut line = sanitize(&buf);
+                if buf.len() >= buffer.max_line_bytes && !buf.ends_with(b"\n") {
+                    match skip_line(&mut reader).await {
+                        Ok(0) => {}
+                        Ok(cut) => line = truncated(line, cut),
+                        Err(_) => break,
                     }
                 }
-            })
-        });
+                queue.push(Output::Line(line)).await;
+            }
+            queue.close();
+        };
+
+        let write = async {
+            while let Some(batch) = queue.take().await {
+                let writer = Arc::clone(&writer);
+                let tap = tap.clone();
+                // File writes block; the reader keeps draining the pipe meanwhile
+                let _ =
+                    tokio::task::spawn_blocking(move || {
+                        let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+                        for output in &batch {
+                            let result = match output {
+                                Output::Line(line) => writer.write_line(line),
+                                Output::Raw(bytes) => writer.write_raw(bytes),
+                            };
+                            if let Err(e) = result {
+                                warn!("Failed to write {}: {}", name, e);
+                            }
+                        }
+                        drop(writer);
+                        if let Some(tap) = &tap {
+                            for output in &batch {
+                                match output {
+                                    Output::Line(line) => tap(line),
+                                    Output::Raw(bytes) => tap(String::from_utf8_lossy(bytes)
+                                        .trim_end_matches(['\r', '\n'])),
+                                }
+                            }
+                        }
+                    })
+                    .await;
+            }
+        };
 
-        (stdout_handle, stderr_handle)
-    }
+        tokio::join!(read, write);
+    })
 }
 
 #[cfg(test)]
@@ -239,6 +417,45 @@ mod tests {
         assert!(content.contains("[20")); // Timestamp starts with year
     }
 
+    #[test]
+    fn test_line_prefix() {
+        let now = DateTime::parse_from_rfc3339("2026-10-17T09:00:00.123Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let prefix = |timestamps, instance| {
+            LinePrefix {
+                timestamps,
+                instance,
+            }
+            .format(now)
+        };
+
+        assert_eq!(
+            prefix(LogTimestamps::Seconds, Some(2)),
+            "[2026-10-17 09:00:00] "
+        );
+        assert_eq!(
+            prefix(LogTimestamps::Rfc3339, None),
+            "[2026-10-17T09:00:00.123Z] "
+        );
+        assert_eq!(
+            prefix(LogTimestamps::Rfc3339, Some(2)),
+            "[2026-10-17T09:00:00.123Z] [2] "
+        );
+        assert_eq!(prefix(LogTimestamps::Off, Some(2)), "");
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.log");
+        let mut writer = LogWriter::new(path.clone(), RotationConfig::default())
+            .unwrap()
+            .with_prefix(LinePrefix {
+                timestamps: LogTimestamps::Off,
+                instance: None,
+            });
+        writer.write_line("Hello, world!").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Hello, world!\n");
+    }
+
     #[test]
     fn test_log_rotation() {
         let dir = TempDir::new().unwrap();
@@ -263,6 +480,89 @@ mod tests {
         let _ = rotated_1;
     }
 
+    #[test]
+    fn test_non_regular_file_not_rotated() {
+        let config = RotationConfig::new(10, 3);
+        let mut writer = LogWriter::new(PathBuf::from("/dev/null"), config).unwrap();
+        for i in 0..5 {
+            writer.write_line(&format!("Line {}", i)).unwrap();
+        }
+        assert!(Path::new("/dev/null").exists());
+        assert!(!Path::new("/dev/null.1").exists());
+    }
+
+    #[tokio::test]
+    async fn test_capture_tap() {
+        let dir = TempDir::new().unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let tap_seen = Arc::clone(&seen);
+        let capture = LogCapture::new(
+            dir.path().join("out.log"),
+            dir.path().join("err.log"),
+            RotationConfig::default(),
+        )
+        .unwrap()
+        .with_tap(Arc::new(move |line: &str| {
+            tap_seen.lock().unwrap().push(line.to_string())
+        }));
+
+        let mut child = tokio::process::Command::new("sh")
+            .args(["-c", "echo ready; echo 'panic: boom' >&2"])
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let (out, err) = capture.spawn_capture(child.stdout.take(), child.stderr.take());
+        out.unwrap().await.unwrap();
+        err.unwrap().await.unwrap();
+
+        let mut seen = seen.lock().unwrap().clone();
+        seen.sort();
+        assert_eq!(seen, vec!["panic: boom", "ready"]);
+        assert!(fs::read_to_string(dir.path().join("err.log"))
+            .unwrap()
+            .contains("panic: boom"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_long_and_binary_lines() {
+        async fn run(capture: LogCapture) {
+            let mut child = tokio::process::Command::new("sh")
+                .args(["-c", "printf 'abcdefghijklmnop\\nbin\\377\\000\\ndone\\n'"])
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .unwrap();
+            let (out, _) = capture.spawn_capture(child.stdout.take(), None);
+            out.unwrap().await.unwrap();
+        }
+        let dir = TempDir::new().unwrap();
+        let (out, err) = (dir.path().join("out.log"), dir.path().join("err.log"));
+
+        let capture = LogCapture::new(out.clone(), err.clone(), RotationConfig::default())
+            .unwrap()
+            .with_prefix(LinePrefix {
+                timestamps: LogTimestamps::Off,
+                instance: None,
+            })
+            .with_max_line_bytes(8);
+        run(capture).await;
+        assert_eq!(
+            fs::read_to_string(&out).unwrap(),
+            "abcdefgh... [8 bytes truncated]\nbin\\xff\\x00\ndone\n"
+        );
+
+        fs::remove_file(&out).unwrap();
+        let capture = LogCapture::new(out.clone(), err, RotationConfig::default())
+            .unwrap()
+            .with_max_line_bytes(8)
+            .with_raw(true);
+        run(capture).await;
+        assert_eq!(
+            fs::read(&out).unwrap(),
+            b"abcdefghijklmnop\nbin\xff\x00\ndone\n"
+        );
+    }
+
     #[test]
     fn test_rotated_path() {
         let base = PathBuf::from("/var/log/app.log");
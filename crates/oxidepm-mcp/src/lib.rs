@@ -0,0 +1,229 @@
+//! OxidePM MCP Server
+//!
+//! Lets AI assistants manage processes through the Model Context Protocol,
+//! over stdio or HTTP with server-sent events. Only the tools in the
+//! [`Allowlist`] are offered; by default that is the read-only ones.
+
+pub mod sse;
+pub mod tools;
+
+pub use sse::serve_sse;
+pub use tools::{Allowlist, Tool};
+
+use oxidepm_client::OxideClient;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::{debug, info};
+
+/// MCP revision implemented here (the one that defines the SSE transport)
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Transport-independent MCP request handler
+pub struct McpServer {
+    socket_path: PathBuf,
+    allow: Allowlist,
+}
+
+impl McpServer {
+    pub fn new(socket_path: PathBuf, allow: Allowlist) -> Self {
+        Self { socket_path, allow }
+    }
+
+    /// Handle one raw JSON-RPC message; notifications get no reply
+    pub async fn handle_line(&self, line: &str) -> Option<Value> {
+        match serde_json::from_str::<Value>(line) {
+            Ok(message) => self.handle_message(message).await,
+            Err(e) => Some(error_reply(
+                Value::Null,
+                PARSE_ERROR,
+                format!("Parse error: {}", e),
+            )),
+        }
+    }
+
+    /// Handle one JSON-RPC message; notifications get no reply
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return id.map(|id| error_reply(id, INVALID_REQUEST, "Invalid request".to_string()));
+        };
+        // Notifications (e.g. notifications/initialized) need nothing from us
+        let id = id?;
+        debug!("MCP request: {}", method);
+
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "oxidepm", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({
+                "tools": self.allow.tools().map(|tool| tool.definition()).collect::<Vec<_>>(),
+            })),
+            "tools/call" => self.call_tool(&params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_reply(id, code, message),
+        })
+    }
+
+    async fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let tool = Tool::from_name(name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool: {}", name)))?;
+
+        // Refusals are tool results, so the assistant sees why and can tell the user
+        if !self.allow.allows(tool) {
+            return Ok(tool_result(
+                format!(
+                    "Tool '{}' is not permitted; restart the MCP server with --allow {}",
+                    name, name
+                ),
+                true,
+            ));
+        }
+
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        info!("MCP tool call: {}", name);
+        let outcome = match OxideClient::connect_to(&self.socket_path) {
+            Ok(client) => tool.call(&client, &args).await,
+            Err(e) => Err(e.to_string()),
+        };
+
+        Ok(match outcome {
+            Ok(text) => tool_result(text, false),
+            Err(text) => tool_result(text, true),
+        })
+    }
+
+    /// Serve newline-delimited JSON-RPC on stdin/stdout until stdin closes
+    pub async fn run_stdio(&self) -> std::io::Result<()> {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        let mut stdout = tokio::io::stdout();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(reply) = self.handle_line(&line).await {
+                stdout.write_all(reply.to_string().as_bytes()).await?;
+                stdout.write_all(b"\n").await?;
+                stdout.flush().await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+fn error_reply(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn server(allow: Allowlist) -> McpServer {
+        McpServer::new(PathBuf::from("/nonexistent/daemon.sock"), allow)
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_list_tools() {
+        let server = server(Allowlist::read_only());
+
+        let reply = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
+            .await
+            .unwrap();
+        assert_eq!(reply["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(server
+            .handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await
+            .is_none());
+
+        let reply = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        let names: Vec<_> = reply["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names, vec!["list_processes", "show_process", "tail_logs"]);
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_gating() {
+        let dir = tempdir().unwrap();
+        let server = McpServer::new(dir.path().join("daemon.sock"), Allowlist::read_only());
+
+        let reply = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"stop_process","arguments":{"selector":"all"}}}"#)
+            .await
+            .unwrap();
+        assert_eq!(reply["result"]["isError"], true);
+        assert!(reply["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("not permitted"));
+
+        // Allowed, but the daemon is not running
+        let reply = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"list_processes"}}"#)
+            .await
+            .unwrap();
+        assert_eq!(reply["result"]["isError"], true);
+        assert_eq!(reply["result"]["content"][0]["text"], "Daemon not running");
+
+        let reply = server
+            .handle_line(
+                r#"{"jsonrpc":"2.0","id":5,"method":"tools/call","params":{"name":"rm_rf"}}"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors() {
+        let server = server(Allowlist::all());
+        let reply = server.handle_line("{not json").await.unwrap();
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+
+        let reply = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":6,"method":"resources/list"}"#)
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(reply["id"], 6);
+    }
+}
@@ -0,0 +1,114 @@
+//! HTTP + server-sent events transport
+//!
+//! A client opens `GET /sse` and receives an `endpoint` event naming the URL to
+//! POST its JSON-RPC messages to; replies arrive as `message` events on the
+//! open stream.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Router,
+};
+use futures::{stream, Stream, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, info};
+
+use crate::McpServer;
+
+/// Replies buffered per session before the client reads them
+const SESSION_BUFFER: usize = 32;
+
+type Sessions = Arc<Mutex<HashMap<String, mpsc::Sender<Value>>>>;
+
+#[derive(Clone)]
+struct SseState {
+    server: Arc<McpServer>,
+    sessions: Sessions,
+}
+
+/// Drops the session when its event stream goes away
+struct SessionGuard {
+    id: String,
+    sessions: Sessions,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.sessions.lock().unwrap().remove(&self.id);
+        debug!("MCP session {} closed", self.id);
+    }
+}
+
+fn router(server: Arc<McpServer>) -> Router {
+    let state = SseState {
+        server,
+        sessions: Arc::new(Mutex::new(HashMap::new())),
+    };
+    Router::new()
+        .route("/sse", get(open_session))
+        .route("/message", post(post_message))
+        .with_state(state)
+}
+
+async fn open_session(
+    State(state): State<SseState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = mpsc::channel(SESSION_BUFFER);
+    state.sessions.lock().unwrap().insert(id.clone(), tx);
+    debug!("MCP session {} opened", id);
+
+    let endpoint = Event::default()
+        .event("endpoint")
+        .data(format!("/message?sessionId={}", id));
+    let guard = SessionGuard {
+        id,
+        sessions: state.sessions,
+    };
+    let replies = ReceiverStream::new(rx).map(move |reply| {
+        let _ = &guard;
+        Ok(Event::default().event("message").data(reply.to_string()))
+    });
+
+    Sse::new(stream::once(async move { Ok(endpoint) }).chain(replies))
+        .keep_alive(KeepAlive::default())
+}
+
+async fn post_message(
+    State(state): State<SseState>,
+    Query(query): Query<HashMap<String, String>>,
+    body: String,
+) -> StatusCode {
+    let session = query
+        .get("sessionId")
+        .and_then(|id| state.sessions.lock().unwrap().get(id).cloned());
+    let Some(session) = session else {
+        return StatusCode::NOT_FOUND;
+    };
+
+    if let Some(reply) = state.server.handle_line(&body).await {
+        if session.send(reply).await.is_err() {
+            return StatusCode::GONE;
+        }
+    }
+    StatusCode::ACCEPTED
+}
+
+/// Serve MCP over HTTP + SSE on `bind_addr`
+pub async fn serve_sse(server: McpServer, bind_addr: &str) -> std::io::Result<()> {
+    let app = router(Arc::new(server));
+
+    info!("Starting OxidePM MCP server on http://{}/sse", bind_addr);
+
+    let listener = tokio::net::TcpListener::bind(bind_addr).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! Tools exposed to MCP clients and the allowlist that gates them
+
+use oxidepm_client::{AppMode, AppOpOutcome, AppOpResult, AppSpec, OxideClient, Selector};
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+/// Log lines returned by `tail_logs` when the caller does not ask for a number
+const DEFAULT_TAIL_LINES: usize = 50;
+
+/// Upper bound on `tail_logs` so a single call cannot flood the model's context
+const MAX_TAIL_LINES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tool {
+    ListProcesses,
+    ShowProcess,
+    TailLogs,
+    StartProcess,
+    StopProcess,
+    RestartProcess,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 6] = [
+        Tool::ListProcesses,
+        Tool::ShowProcess,
+        Tool::TailLogs,
+        Tool::StartProcess,
+        Tool::StopProcess,
+        Tool::RestartProcess,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::ListProcesses => "list_processes",
+            Tool::ShowProcess => "show_process",
+            Tool::TailLogs => "tail_logs",
+            Tool::StartProcess => "start_process",
+            Tool::StopProcess => "stop_process",
+            Tool::RestartProcess => "restart_process",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Tool> {
+        Tool::ALL.into_iter().find(|tool| tool.name() == name)
+    }
+
+    /// Whether the tool only reads state
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Tool::ListProcesses | Tool::ShowProcess | Tool::TailLogs
+        )
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Tool::ListProcesses => "List every process managed by OxidePM with its status, PID, CPU, memory and restarts",
+            Tool::ShowProcess => "Show the full configuration and state of one process",
+            Tool::TailLogs => "Return the most recent stdout/stderr lines of a process",
+            Tool::StartProcess => "Start a new process under OxidePM",
+            Tool::StopProcess => "Stop the processes matching a selector",
+            Tool::RestartProcess => "Restart the processes matching a selector",
+        }
+    }
+
+    fn input_schema(&self) -> Value {
+        let selector = json!({
+            "type": "string",
+            "description": "Process name, id, \"all\", \"@tag\" or \"ns:namespace\""
+        });
+        match self {
+            Tool::ListProcesses => json!({ "type": "object", "properties": {} }),
+            Tool::ShowProcess | Tool::StopProcess | Tool::RestartProcess => json!({
+                "type": "object",
+                "properties": { "selector": selector },
+                "required": ["selector"]
+            }),
+            Tool::TailLogs => json!({
+                "type": "object",
+                "properties": {
+                    "selector": selector,
+                    "lines": {
+                        "type": "integer",
+                        "description": format!("Number of lines (default {}, max {})", DEFAULT_TAIL_LINES, MAX_TAIL_LINES)
+                    }
+                },
+                "required": ["selector"]
+            }),
+            Tool::StartProcess => json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Process name" },
+                    "command": { "type": "string", "description": "Script, binary or command to run" },
+                    "args": { "type": "array", "items": { "type": "string" } },
+                    "cwd": { "type": "string", "description": "Working directory (absolute path)" },
+                    "env": { "type": "object", "additionalProperties": { "type": "string" } }
+                },
+                "required": ["name", "command", "cwd"]
+            }),
+        }
+    }
+
+    /// Tool entry for a `tools/list` response
+    pub fn definition(&self) -> Value {
+        json!({
+            "name": self.name(),
+            "description": self.description(),
+            "inputSchema": self.input_schema(),
+        })
+    }
+
+    /// Run the tool against the daemon, returning the text shown to the model
+    pub async fn call(&self, client: &OxideClient, args: &Value) -> Result<String, String> {
+        match self {
+            Tool::ListProcesses => {
+                let apps = client.status().await.map_err(|e| e.to_string())?;
+                let apps: Vec<Value> = apps
+                    .iter()
+                    .map(|app| {
+                        json!({
+                            "id": app.spec.id,
+                            "name": app.spec.name,
+                            "namespace": app.spec.namespace,
+                            "status": app.state.status,
+                            "pid": app.state.pid,
+                            "cpu_percent": app.state.cpu_percent,
+                            "memory_bytes": app.state.memory_bytes,
+                            "restarts": app.state.restarts,
+                            "uptime_secs": app.state.uptime_secs,
+                            "port": app.state.port.or(app.spec.port),
+                        })
+                    })
+                    .collect();
+                to_text(&apps)
+            }
+            Tool::ShowProcess => {
+                let app = client
+                    .show(selector(args)?)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                to_text(&app)
+            }
+            Tool::TailLogs => {
+                let lines = args
+                    .get("lines")
+                    .and_then(Value::as_u64)
+                    .map_or(DEFAULT_TAIL_LINES, |n| (n as usize).min(MAX_TAIL_LINES));
+                let lines = client
+                    .logs(selector(args)?, lines)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if lines.is_empty() {
+                    return Ok("(no log lines)".to_string());
+                }
+                Ok(lines.join("\n"))
+            }
+            Tool::StartProcess => {
+                let spec = start_spec(args)?;
+                let started = client.start(spec).await.map_err(|e| e.to_string())?;
+                Ok(format!("Started {} (id {})", started.name, started.id))
+            }
+            Tool::StopProcess => {
+                let results = client
+                    .stop(selector(args)?)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(describe_results(&results, "stopped"))
+            }
+            Tool::RestartProcess => {
+                let results = client
+                    .restart(selector(args)?)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(describe_results(&results, "restarted"))
+            }
+        }
+    }
+}
+
+fn to_text<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| e.to_string())
+}
+
+fn string_arg<'a>(args: &'a Value, key: &str) -> Result<&'a str, String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| format!("Missing required argument '{}'", key))
+}
+
+fn selector(args: &Value) -> Result<Selector, String> {
+    string_arg(args, "selector").map(Selector::parse)
+}
+
+fn start_spec(args: &Value) -> Result<AppSpec, String> {
+    let name = string_arg(args, "name")?;
+    let command = string_arg(args, "command")?;
+    let cwd = PathBuf::from(string_arg(args, "cwd")?);
+    if !cwd.is_absolute() {
+        return Err("'cwd' must be an absolute path".to_string());
+    }
+
+    let mode = AppMode::detect(&cwd).unwrap_or(AppMode::Cmd);
+    let mut spec = AppSpec::new(name.to_string(), mode, command.to_string(), cwd);
+    if let Some(args) = args.get("args").and_then(Value::as_array) {
+        spec.args = args
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect();
+    }
+    if let Some(env) = args.get("env").and_then(Value::as_object) {
+        spec.env = env
+            .iter()
+            .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+            .collect::<HashMap<_, _>>();
+    }
+    Ok(spec)
+}
+
+fn describe_results(results: &[AppOpResult], verb: &str) -> String {
+    if results.is_empty() {
+        return "No processes matched".to_string();
+    }
+    results
+        .iter()
+        .map(|r| match &r.outcome {
+            AppOpOutcome::Done => format!("{} (id {}): {}", r.name, r.id, verb),
+            AppOpOutcome::Skipped => format!("{} (id {}): nothing to do", r.name, r.id),
+            AppOpOutcome::AlreadyStopped => format!("{} (id {}): already stopped", r.name, r.id),
+            AppOpOutcome::NotFound => format!("{}: not found", r.name),
+            AppOpOutcome::Failed { error } => {
+                format!("{} (id {}): failed: {}", r.name, r.id, error)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Tools an MCP client may call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allowlist(BTreeSet<Tool>);
+
+impl Allowlist {
+    /// Only the tools that read state
+    pub fn read_only() -> Self {
+        Self(Tool::ALL.into_iter().filter(Tool::is_read_only).collect())
+    }
+
+    pub fn all() -> Self {
+        Self(Tool::ALL.into_iter().collect())
+    }
+
+    /// Parse a comma-separated list of tool names, "read-only" or "all"
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut tools = BTreeSet::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry {
+                "all" => tools.extend(Tool::ALL),
+                "read-only" => tools.extend(Self::read_only().0),
+                name => {
+                    let tool = Tool::from_name(name).ok_or_else(|| {
+                        let names: Vec<_> = Tool::ALL.iter().map(Tool::name).collect();
+                        format!(
+                            "Unknown tool '{}' (expected one of: {}, read-only, all)",
+                            name,
+                            names.join(", ")
+                        )
+                    })?;
+                    tools.insert(tool);
+                }
+            }
+        }
+        Ok(Self(tools))
+    }
+
+    pub fn allows(&self, tool: Tool) -> bool {
+        self.0.contains(&tool)
+    }
+
+    pub fn tools(&self) -> impl Iterator<Item = Tool> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl Default for Allowlist {
+    fn default() -> Self {
+        Self::read_only()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist_parse() {
+        let allow = Allowlist::parse("read-only, restart_process").unwrap();
+        assert!(allow.allows(Tool::ListProcesses));
+        assert!(allow.allows(Tool::TailLogs));
+        assert!(allow.allows(Tool::RestartProcess));
+        assert!(!allow.allows(Tool::StopProcess));
+        assert!(!allow.allows(Tool::StartProcess));
+
+        assert_eq!(Allowlist::parse("all").unwrap(), Allowlist::all());
+        assert!(Allowlist::parse("stop_process,rm_rf")
+            .unwrap_err()
+            .contains("rm_rf"));
+        assert!(!Allowlist::default().allows(Tool::StopProcess));
+    }
+
+    #[test]
+    fn test_start_spec_args() {
+        let spec = start_spec(&json!({
+            "name": "api",
+            "command": "server.sh",
+            "cwd": "/srv/api",
+            "args": ["--port", "8080"],
+            "env": { "RUST_LOG": "debug" }
+        }))
+        .unwrap();
+        assert_eq!(spec.args, vec!["--port", "8080"]);
+        assert_eq!(spec.env.get("RUST_LOG").map(String::as_str), Some("debug"));
+
+        let err =
+            start_spec(&json!({ "name": "api", "command": "x", "cwd": "relative" })).unwrap_err();
+        assert!(err.contains("absolute"));
+        assert!(start_spec(&json!({ "name": "api" }))
+            .unwrap_err()
+            .contains("command"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Two-way Telegram bot
+//!
+//! With `interactive = true` the daemon long-polls the bot for messages.
+//! Commands from allowed chats that are on the allowlist are handed back to
+//! the daemon to answer; chats that are not allowed are ignored, and commands
+//! that are not enabled get a short refusal.
+
+use crate::config::TelegramConfig;
+use crate::error::Result;
+use crate::telegram::TelegramNotifier;
+use tracing::{debug, warn};
+
+/// Commands the bot understands, besides /help
+pub const BOT_COMMANDS: &[&str] = &["status", "logs", "restart"];
+
+/// Longest message Telegram accepts
+pub const MAX_MESSAGE_CHARS: usize = 4096;
+
+/// Log lines sent by `/logs <app>` without a count
+const DEFAULT_LOG_LINES: usize = 20;
+
+/// Most log lines `/logs` returns
+const MAX_LOG_LINES: usize = 200;
+
+/// How long each getUpdates call waits for messages
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+/// A command sent to the bot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotCommand {
+    /// `/status`
+    Status,
+    /// `/restart <app>`
+    Restart { app: String },
+    /// `/logs <app> [lines]`
+    Logs { app: String, lines: usize },
+    /// `/help` or `/start`
+    Help,
+}
+
+impl BotCommand {
+    /// Parse a message; `Ok(None)` for messages that are not commands
+    pub fn parse(text: &str) -> std::result::Result<Option<Self>, String> {
+        let mut words = text.split_whitespace();
+        let Some(command) = words.next().and_then(|w| w.strip_prefix('/')) else {
+            return Ok(None);
+        };
+        // In groups commands may be addressed as /status@my_bot
+        let command = command.split('@').next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+
+        let parsed = match (command, args.as_slice()) {
+            ("status", []) => BotCommand::Status,
+            ("restart", [app]) => BotCommand::Restart {
+                app: app.to_string(),
+            },
+            ("logs", [app]) => BotCommand::Logs {
+                app: app.to_string(),
+                lines: DEFAULT_LOG_LINES,
+            },
+            ("logs", [app, lines]) => {
+                let lines: usize = lines
+                    .parse()
+                    .map_err(|_| format!("Invalid line count '{}'", lines))?;
+                BotCommand::Logs {
+                    app: app.to_string(),
+                    lines: lines.clamp(1, MAX_LOG_LINES),
+                }
+            }
+            ("help" | "start", _) => BotCommand::Help,
+            ("status" | "restart" | "logs", _) => {
+                return Err(format!("Usage: {}", usage(command)));
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown command /{}. Send /help for a list",
+                    command
+                ))
+            }
+        };
+        Ok(Some(parsed))
+    }
+
+    /// Name used in the allowlist
+    pub fn name(&self) -> &'static str {
+        match self {
+            BotCommand::Status => "status",
+            BotCommand::Restart { .. } => "restart",
+            BotCommand::Logs { .. } => "logs",
+            BotCommand::Help => "help",
+        }
+    }
+}
+
+fn usage(command: &str) -> &'static str {
+    match command {
+        "status" => "/status - list apps",
+        "restart" => "/restart <app> - restart an app",
+        "logs" => "/logs <app> [lines] - recent log lines",
+        _ => "",
+    }
+}
+
+/// An allowed command and the chat to answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BotRequest {
+    pub chat_id: String,
+    pub command: BotCommand,
+}
+
+/// Long-polling Telegram bot
+pub struct TelegramBot {
+    notifier: TelegramNotifier,
+    config: TelegramConfig,
+    /// Next update to fetch; earlier ones are acknowledged
+    offset: Option<i64>,
+}
+
+impl TelegramBot {
+    /// Create a bot from the Telegram channel settings
+    pub fn new(config: TelegramConfig) -> Self {
+        Self {
+            notifier: TelegramNotifier::new(config.bot_token.clone(), config.chat_id.clone()),
+            config,
+            offset: None,
+        }
+    }
+
+    /// Wait for messages and return the commands the daemon should answer.
+    /// Help, refusals and usage errors are answered here.
+    pub async fn poll(&mut self) -> Result<Vec<BotRequest>> {
+        let updates = self
+            .notifier
+            .get_updates(self.offset, POLL_TIMEOUT_SECS)
+            .await?;
+
+        let mut requests = Vec::new();
+        for update in updates {
+            self.offset = Some(update.update_id + 1);
+            let Some(message) = update.message else {
+                continue;
+            };
+            let Some(text) = message.text else {
+                continue;
+            };
+            let chat_id = message.chat.id.to_string();
+
+            match self.check(&chat_id, &text) {
+                Some(Ok(command)) => requests.push(BotRequest { chat_id, command }),
+                Some(Err(reply)) => self.reply(&chat_id, &reply).await?,
+                None => {}
+            }
+        }
+        Ok(requests)
+    }
+
+    /// Decide what to do with a message: a command to run, a reply to send
+    /// straight away, or nothing
+    fn check(&self, chat_id: &str, text: &str) -> Option<std::result::Result<BotCommand, String>> {
+        if !self.config.is_chat_allowed(chat_id) {
+            warn!(
+                "Ignoring Telegram message from unauthorized chat {}",
+                chat_id
+            );
+            return None;
+        }
+        let command = match BotCommand::parse(text) {
+            Ok(Some(command)) => command,
+            Ok(None) => return None,
+            Err(reply) => return Some(Err(reply)),
+        };
+        debug!("Telegram command /{} from chat {}", command.name(), chat_id);
+
+        if command == BotCommand::Help {
+            return Some(Err(self.help()));
+        }
+        if !self.config.is_command_allowed(command.name()) {
+            return Some(Err(format!(
+                "/{} is not enabled for this bot",
+                command.name()
+            )));
+        }
+        Some(Ok(command))
+    }
+
+    /// Commands this bot answers
+    fn help(&self) -> String {
+        let enabled: Vec<&str> = BOT_COMMANDS
+            .iter()
+            .filter(|c| self.config.is_command_allowed(c))
+            .map(|c| usage(c))
+            .collect();
+        if enabled.is_empty() {
+            "No commands are enabled for this bot".to_string()
+        } else {
+            format!("Commands:\n{}", enabled.join("\n"))
+        }
+    }
+
+    /// Send a plain text reply, cut to fit a single message
+    pub async fn reply(&self, chat_id: &str, text: &str) -> Result<()> {
+        self.notifier
+            .send_to(chat_id, &truncate_message(text))
+            .await
+    }
+}
+
+/// Cut a message to `MAX_MESSAGE_CHARS`, marking where it was cut
+fn truncate_message(text: &str) -> String {
+    const MARKER: &str = "\n...";
+    if text.chars().count() <= MAX_MESSAGE_CHARS {
+        return text.to_string();
+    }
+    let kept: String = text
+        .chars()
+        .take(MAX_MESSAGE_CHARS - MARKER.len())
+        .collect();
+    format!("{}{}", kept, MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::default_bot_commands;
+    use crate::event::Severity;
+
+    fn bot(commands: &[&str]) -> TelegramBot {
+        TelegramBot::new(TelegramConfig {
+            bot_token: "token".to_string(),
+            chat_id: "42".to_string(),
+            min_severity: Severity::Info,
+            interactive: true,
+            allowed_chats: Vec::new(),
+            commands: commands.iter().map(|c| c.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            BotCommand::parse("/status").unwrap(),
+            Some(BotCommand::Status)
+        );
+        assert_eq!(
+            BotCommand::parse("/status@oxidepm_bot").unwrap(),
+            Some(BotCommand::Status)
+        );
+        assert_eq!(
+            BotCommand::parse("/restart api").unwrap(),
+            Some(BotCommand::Restart {
+                app: "api".to_string()
+            })
+        );
+        assert_eq!(
+            BotCommand::parse("/logs api").unwrap(),
+            Some(BotCommand::Logs {
+                app: "api".to_string(),
+                lines: DEFAULT_LOG_LINES
+            })
+        );
+        assert_eq!(
+            BotCommand::parse("/logs api 5000").unwrap(),
+            Some(BotCommand::Logs {
+                app: "api".to_string(),
+                lines: MAX_LOG_LINES
+            })
+        );
+        assert_eq!(BotCommand::parse("hello").unwrap(), None);
+        assert!(BotCommand::parse("/restart").is_err());
+        assert!(BotCommand::parse("/logs api many").is_err());
+        assert!(BotCommand::parse("/shutdown").is_err());
+    }
+
+    #[test]
+    fn test_check_allowlist() {
+        let bot = bot(&["status", "logs"]);
+        assert_eq!(bot.check("42", "/status"), Some(Ok(BotCommand::Status)));
+        assert!(
+            matches!(bot.check("42", "/restart api"), Some(Err(reply)) if reply.contains("not enabled"))
+        );
+        assert_eq!(bot.check("7", "/status"), None);
+        assert_eq!(bot.check("42", "good morning"), None);
+
+        let Some(Err(help)) = bot.check("42", "/help") else {
+            panic!("expected help");
+        };
+        assert!(help.contains("/status"));
+        assert!(!help.contains("/restart"));
+    }
+
+    #[test]
+    fn test_default_commands_are_read_only() {
+        assert_eq!(default_bot_commands(), vec!["status", "logs"]);
+    }
+
+    #[test]
+    fn test_truncate_message() {
+        assert_eq!(truncate_message("short"), "short");
+        let long = "x".repeat(MAX_MESSAGE_CHARS + 10);
+        let cut = truncate_message(&long);
+        assert_eq!(cut.chars().count(), MAX_MESSAGE_CHARS);
+        assert!(cut.ends_with("..."));
+    }
+}
@@ -1,6 +1,9 @@
 //! Configuration types for notification system
 
 use crate::error::{NotifyError, Result};
+use crate::event::Severity;
+use crate::report::ReportsConfig;
+use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use tracing::{debug, info};
@@ -23,15 +26,23 @@ fn ensure_config_dir() -> Result<()> {
 }
 
 /// Notification configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct NotifyConfig {
     /// Telegram notification settings
     pub telegram: Option<TelegramConfig>,
 
     /// Events to notify on (empty = all events)
-    /// Valid values: "start", "stop", "crash", "restart", "memory_limit", "health_check"
+    /// Valid values: "start", "stop", "crash", "restart", "memory_limit", "health_check", "log_match"
     #[serde(default)]
     pub events: Vec<String>,
+
+    /// Window during which only sufficiently severe events are sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+
+    /// Scheduled summary reports (disabled when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reports: Option<ReportsConfig>,
 }
 
 impl NotifyConfig {
@@ -89,7 +100,23 @@ impl NotifyConfig {
 
     /// Configure Telegram notifications
     pub fn set_telegram(&mut self, bot_token: String, chat_id: String) {
-        self.telegram = Some(TelegramConfig { bot_token, chat_id });
+        // Keep the rest of the channel's settings when changing the bot
+        match self.telegram {
+            Some(ref mut telegram) => {
+                telegram.bot_token = bot_token;
+                telegram.chat_id = chat_id;
+            }
+            None => {
+                self.telegram = Some(TelegramConfig {
+                    bot_token,
+                    chat_id,
+                    min_severity: Severity::default(),
+                    interactive: false,
+                    allowed_chats: Vec::new(),
+                    commands: default_bot_commands(),
+                });
+            }
+        }
     }
 
     /// Remove Telegram configuration
@@ -111,6 +138,7 @@ impl NotifyConfig {
             "restart",
             "memory_limit",
             "health_check",
+            "log_match",
         ];
 
         for event in &self.events {
@@ -123,16 +151,153 @@ impl NotifyConfig {
         }
         Ok(())
     }
+
+    /// Set or clear the quiet hours window
+    pub fn set_quiet_hours(&mut self, quiet_hours: Option<QuietHours>) {
+        self.quiet_hours = quiet_hours;
+    }
+
+    /// Enable or disable scheduled summary reports
+    pub fn set_reports(&mut self, reports: Option<ReportsConfig>) {
+        self.reports = reports;
+    }
+
+    /// Validate the report schedule, if any
+    pub fn validate_reports(&self) -> Result<()> {
+        match self.reports {
+            Some(ref reports) => reports.validate(),
+            None => Ok(()),
+        }
+    }
+
+    /// Validate the quiet hours window, if any
+    pub fn validate_quiet_hours(&self) -> Result<()> {
+        if let Some(ref quiet) = self.quiet_hours {
+            quiet.window()?;
+        }
+        Ok(())
+    }
 }
 
 /// Telegram notification configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TelegramConfig {
     /// Bot token from @BotFather
     pub bot_token: String,
 
     /// Chat ID to send messages to (can be user, group, or channel)
     pub chat_id: String,
+
+    /// Minimum severity sent to this channel (default: info)
+    #[serde(default)]
+    pub min_severity: Severity,
+
+    /// Answer commands sent to the bot, polled by the daemon
+    #[serde(default)]
+    pub interactive: bool,
+
+    /// Chats allowed to send commands (empty = only `chat_id`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_chats: Vec<String>,
+
+    /// Commands the bot answers (default: status, logs)
+    /// Valid values: "status", "logs", "restart"
+    #[serde(default = "default_bot_commands")]
+    pub commands: Vec<String>,
+}
+
+/// Commands that only read state are allowed unless configured otherwise
+pub fn default_bot_commands() -> Vec<String> {
+    vec!["status".to_string(), "logs".to_string()]
+}
+
+impl TelegramConfig {
+    /// Check whether a chat may send commands to the bot
+    pub fn is_chat_allowed(&self, chat_id: &str) -> bool {
+        if self.allowed_chats.is_empty() {
+            self.chat_id == chat_id
+        } else {
+            self.allowed_chats.iter().any(|c| c == chat_id)
+        }
+    }
+
+    /// Check whether a command is on the allowlist
+    pub fn is_command_allowed(&self, command: &str) -> bool {
+        self.commands.iter().any(|c| c == command)
+    }
+
+    /// Validate command names
+    pub fn validate_commands(&self) -> Result<()> {
+        for command in &self.commands {
+            if !crate::bot::BOT_COMMANDS.contains(&command.as_str()) {
+                return Err(NotifyError::config(format!(
+                    "Invalid bot command '{}'. Valid commands: {:?}",
+                    command,
+                    crate::bot::BOT_COMMANDS
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Quiet hours configuration
+///
+/// Between `start` and `end` (local time, `HH:MM`) only events at or above
+/// `min_severity` are sent. A window whose start is after its end wraps
+/// around midnight, e.g. `22:00`–`07:00`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Start of the quiet window (e.g. "22:00")
+    pub start: String,
+
+    /// End of the quiet window (e.g. "07:00")
+    pub end: String,
+
+    /// Minimum severity still sent during quiet hours (default: critical)
+    #[serde(default = "default_quiet_severity")]
+    pub min_severity: Severity,
+}
+
+fn default_quiet_severity() -> Severity {
+    Severity::Critical
+}
+
+impl QuietHours {
+    /// Create a quiet hours window that only lets critical events through
+    pub fn new(start: impl Into<String>, end: impl Into<String>) -> Self {
+        Self {
+            start: start.into(),
+            end: end.into(),
+            min_severity: default_quiet_severity(),
+        }
+    }
+
+    /// Parse the start and end times
+    pub fn window(&self) -> Result<(NaiveTime, NaiveTime)> {
+        Ok((parse_time(&self.start)?, parse_time(&self.end)?))
+    }
+
+    /// Check whether a time of day falls inside the quiet window
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let Ok((start, end)) = self.window() else {
+            return false;
+        };
+        if start <= end {
+            time >= start && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| {
+        NotifyError::config(format!(
+            "Invalid quiet hours time '{}'. Expected HH:MM (e.g. 22:00)",
+            value
+        ))
+    })
 }
 
 #[cfg(test)]
@@ -175,6 +340,75 @@ chat_id = "-100123456789"
         assert_eq!(telegram.bot_token, "123456:ABC-DEF");
         assert_eq!(telegram.chat_id, "-100123456789");
         assert_eq!(config.events, vec!["crash", "restart"]);
+        assert!(config.quiet_hours.is_none());
+    }
+
+    #[test]
+    fn test_load_config_with_severity_and_quiet_hours() {
+        let content = r#"
+[telegram]
+bot_token = "123456:ABC-DEF"
+chat_id = "-100123456789"
+min_severity = "warning"
+
+[quiet_hours]
+start = "22:00"
+end = "07:00"
+"#;
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let config = NotifyConfig::load_from(&file.path().to_path_buf()).unwrap();
+
+        assert_eq!(config.telegram.unwrap().min_severity, Severity::Warning);
+        let quiet = config.quiet_hours.unwrap();
+        assert_eq!(quiet.min_severity, Severity::Critical);
+        assert!(quiet.window().is_ok());
+        assert!(config.reports.is_none());
+    }
+
+    #[test]
+    fn test_load_config_with_reports() {
+        let content = r#"
+[reports]
+period = "weekly"
+at = "08:30"
+"#;
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let config = NotifyConfig::load_from(&file.path().to_path_buf()).unwrap();
+
+        let reports = config.reports.clone().unwrap();
+        assert_eq!(reports.period, crate::report::ReportPeriod::Weekly);
+        assert_eq!(reports.at, "08:30");
+        assert_eq!(reports.weekday, "mon");
+        assert_eq!(reports.top, 3);
+        assert!(config.validate_reports().is_ok());
+    }
+
+    #[test]
+    fn test_quiet_hours_contains() {
+        let t = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+        let overnight = QuietHours::new("22:00", "07:00");
+        assert!(overnight.contains(t(23, 30)));
+        assert!(overnight.contains(t(3, 0)));
+        assert!(!overnight.contains(t(7, 0)));
+        assert!(!overnight.contains(t(12, 0)));
+
+        let daytime = QuietHours::new("12:00", "13:00");
+        assert!(daytime.contains(t(12, 30)));
+        assert!(!daytime.contains(t(13, 0)));
+    }
+
+    #[test]
+    fn test_validate_quiet_hours() {
+        let mut config = NotifyConfig::default();
+        assert!(config.validate_quiet_hours().is_ok());
+
+        config.set_quiet_hours(Some(QuietHours::new("22:00", "7am")));
+        assert!(config.validate_quiet_hours().is_err());
     }
 
     #[test]
@@ -199,8 +433,8 @@ chat_id = "-100123456789"
     #[test]
     fn test_validate_events_valid() {
         let config = NotifyConfig {
-            telegram: None,
             events: vec!["crash".to_string(), "restart".to_string()],
+            ..Default::default()
         };
         assert!(config.validate_events().is_ok());
     }
@@ -208,12 +442,40 @@ chat_id = "-100123456789"
     #[test]
     fn test_validate_events_invalid() {
         let config = NotifyConfig {
-            telegram: None,
             events: vec!["invalid_event".to_string()],
+            ..Default::default()
         };
         assert!(config.validate_events().is_err());
     }
 
+    #[test]
+    fn test_telegram_bot_defaults() {
+        let content = r#"
+[telegram]
+bot_token = "123456:ABC-DEF"
+chat_id = "42"
+interactive = true
+"#;
+        let mut file = NamedTempFile::with_suffix(".toml").unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let config = NotifyConfig::load_from(&file.path().to_path_buf()).unwrap();
+        let mut telegram = config.telegram.unwrap();
+
+        assert!(telegram.interactive);
+        assert!(telegram.is_chat_allowed("42"));
+        assert!(!telegram.is_chat_allowed("43"));
+        assert!(telegram.is_command_allowed("status"));
+        assert!(!telegram.is_command_allowed("restart"));
+
+        telegram.allowed_chats = vec!["43".to_string()];
+        assert!(telegram.is_chat_allowed("43"));
+        assert!(!telegram.is_chat_allowed("42"));
+
+        telegram.commands.push("shutdown".to_string());
+        assert!(telegram.validate_commands().is_err());
+    }
+
     #[test]
     fn test_set_and_remove_telegram() {
         let mut config = NotifyConfig::default();
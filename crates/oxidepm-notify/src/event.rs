@@ -1,6 +1,56 @@
 //! Process event types for notifications
 
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How urgent an event is, used for per-channel filtering and quiet hours
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Routine lifecycle events (start, stop, restart)
+    #[default]
+    Info,
+    /// Something needs attention but the process is still running
+    Warning,
+    /// The process is down or failing
+    Critical,
+}
+
+impl Severity {
+    /// Get the severity as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "warning" | "warn" => Ok(Severity::Warning),
+            "critical" | "crit" => Ok(Severity::Critical),
+            other => Err(format!(
+                "Invalid severity '{}'. Valid values: info, warning, critical",
+                other
+            )),
+        }
+    }
+}
 
 /// Events that can trigger notifications
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +90,14 @@ pub enum ProcessEvent {
         id: u32,
         endpoint: String,
     },
+
+    /// A log trigger's pattern matched a line of output
+    LogMatch {
+        name: String,
+        id: u32,
+        pattern: String,
+        line: String,
+    },
 }
 
 impl ProcessEvent {
@@ -52,6 +110,20 @@ impl ProcessEvent {
             ProcessEvent::Restarted { .. } => "restart",
             ProcessEvent::MemoryLimit { .. } => "memory_limit",
             ProcessEvent::HealthCheckFailed { .. } => "health_check",
+            ProcessEvent::LogMatch { .. } => "log_match",
+        }
+    }
+
+    /// Get the severity of the event
+    pub fn severity(&self) -> Severity {
+        match self {
+            ProcessEvent::Started { .. }
+            | ProcessEvent::Stopped { .. }
+            | ProcessEvent::Restarted { .. } => Severity::Info,
+            ProcessEvent::MemoryLimit { .. } | ProcessEvent::LogMatch { .. } => Severity::Warning,
+            ProcessEvent::Crashed { .. } | ProcessEvent::HealthCheckFailed { .. } => {
+                Severity::Critical
+            }
         }
     }
 
@@ -103,6 +175,17 @@ impl ProcessEvent {
                     name, id, endpoint
                 )
             }
+            ProcessEvent::LogMatch {
+                name,
+                id,
+                pattern,
+                line,
+            } => {
+                format!(
+                    "\u{1F50E} Log match: `{}` (id: {})\nPattern: {}\n{}",
+                    name, id, pattern, line
+                )
+            }
         }
     }
 
@@ -114,7 +197,8 @@ impl ProcessEvent {
             | ProcessEvent::Crashed { name, .. }
             | ProcessEvent::Restarted { name, .. }
             | ProcessEvent::MemoryLimit { name, .. }
-            | ProcessEvent::HealthCheckFailed { name, .. } => name,
+            | ProcessEvent::HealthCheckFailed { name, .. }
+            | ProcessEvent::LogMatch { name, .. } => name,
         }
     }
 
@@ -126,7 +210,8 @@ impl ProcessEvent {
             | ProcessEvent::Crashed { id, .. }
             | ProcessEvent::Restarted { id, .. }
             | ProcessEvent::MemoryLimit { id, .. }
-            | ProcessEvent::HealthCheckFailed { id, .. } => *id,
+            | ProcessEvent::HealthCheckFailed { id, .. }
+            | ProcessEvent::LogMatch { id, .. } => *id,
         }
     }
 }
@@ -156,6 +241,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_severity() {
+        let started = ProcessEvent::Started {
+            name: "test".to_string(),
+            id: 1,
+        };
+        let memory = ProcessEvent::MemoryLimit {
+            name: "test".to_string(),
+            id: 1,
+            memory_mb: 512,
+            limit_mb: 256,
+        };
+        let crashed = ProcessEvent::Crashed {
+            name: "test".to_string(),
+            id: 1,
+            error: "err".to_string(),
+        };
+        assert_eq!(started.severity(), Severity::Info);
+        assert_eq!(memory.severity(), Severity::Warning);
+        assert_eq!(crashed.severity(), Severity::Critical);
+        assert!(Severity::Info < Severity::Warning);
+        assert!(Severity::Warning < Severity::Critical);
+    }
+
+    #[test]
+    fn test_severity_parse() {
+        assert_eq!("critical".parse::<Severity>().unwrap(), Severity::Critical);
+        assert_eq!("Warn".parse::<Severity>().unwrap(), Severity::Warning);
+        assert!("loud".parse::<Severity>().is_err());
+    }
+
     #[test]
     fn test_format_message_started() {
         let event = ProcessEvent::Started {
@@ -223,6 +339,20 @@ mod tests {
         assert!(msg.contains("256MB"));
     }
 
+    #[test]
+    fn test_format_message_log_match() {
+        let event = ProcessEvent::LogMatch {
+            name: "api".to_string(),
+            id: 1,
+            pattern: "panic:".to_string(),
+            line: "panic: index out of range".to_string(),
+        };
+        assert_eq!(event.event_type(), "log_match");
+        let msg = event.format_message();
+        assert!(msg.contains("Pattern: panic:"));
+        assert!(msg.contains("index out of range"));
+    }
+
     #[test]
     fn test_serialization() {
         let event = ProcessEvent::Started {
@@ -4,19 +4,24 @@
 //! - Telegram
 //! - (Future: Slack, Discord, Webhooks, etc.)
 
+mod bot;
 pub mod config;
 mod error;
 mod event;
 #[cfg(test)]
 pub mod mock;
+mod report;
 mod telegram;
 
-pub use config::{notify_config_path, NotifyConfig, TelegramConfig};
+pub use bot::{BotCommand, BotRequest, TelegramBot, BOT_COMMANDS, MAX_MESSAGE_CHARS};
+pub use config::{notify_config_path, NotifyConfig, QuietHours, TelegramConfig};
 pub use error::{NotifyError, Result};
-pub use event::ProcessEvent;
+pub use event::{ProcessEvent, Severity};
+pub use report::{AppReport, CrashEntry, Report, ReportPeriod, ReportsConfig};
 pub use telegram::TelegramNotifier;
 
 use async_trait::async_trait;
+use chrono::NaiveTime;
 
 /// Trait for notification backends
 #[async_trait]
@@ -63,12 +68,38 @@ impl NotificationManager {
 
         // Send to Telegram if configured
         if let Some(ref telegram) = self.telegram {
-            telegram.send_process_event(event).await?;
+            if self.channel_accepts(self.config.telegram.as_ref().map(|t| t.min_severity), event) {
+                telegram.send_process_event(event).await?;
+            }
         }
 
         Ok(())
     }
 
+    /// Send a summary report to all configured channels
+    pub async fn send_report(&self, report: &Report) -> Result<()> {
+        self.send_message(&report.format_message()).await
+    }
+
+    /// Bot answering commands, if the Telegram channel is interactive
+    pub fn telegram_bot(&self) -> Option<TelegramBot> {
+        self.config
+            .telegram
+            .as_ref()
+            .filter(|t| t.interactive)
+            .map(|t| TelegramBot::new(t.clone()))
+    }
+
+    /// Settings the manager was created with
+    pub fn config(&self) -> &NotifyConfig {
+        &self.config
+    }
+
+    /// Scheduled report settings, if reports are enabled
+    pub fn reports_config(&self) -> Option<&ReportsConfig> {
+        self.config.reports.as_ref()
+    }
+
     /// Send a plain message to all configured channels
     pub async fn send_message(&self, message: &str) -> Result<()> {
         if let Some(ref telegram) = self.telegram {
@@ -85,8 +116,19 @@ impl NotificationManager {
             .unwrap_or(false)
     }
 
-    /// Check if this event type should trigger a notification
+    /// Check if this event should trigger a notification right now
     fn should_notify(&self, event: &ProcessEvent) -> bool {
+        self.should_notify_at(event, chrono::Local::now().time())
+    }
+
+    /// Check if this event should trigger a notification at the given local time
+    fn should_notify_at(&self, event: &ProcessEvent, now: NaiveTime) -> bool {
+        if let Some(ref quiet) = self.config.quiet_hours {
+            if quiet.contains(now) && event.severity() < quiet.min_severity {
+                return false;
+            }
+        }
+
         if self.config.events.is_empty() {
             // If no events specified, notify all
             return true;
@@ -95,6 +137,11 @@ impl NotificationManager {
         let event_type = event.event_type();
         self.config.events.iter().any(|e| e == event_type)
     }
+
+    /// Check if a channel's minimum severity lets this event through
+    fn channel_accepts(&self, min_severity: Option<Severity>, event: &ProcessEvent) -> bool {
+        event.severity() >= min_severity.unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -114,8 +161,14 @@ mod tests {
             telegram: Some(TelegramConfig {
                 bot_token: "test".to_string(),
                 chat_id: "123".to_string(),
+                min_severity: Severity::Info,
+                interactive: false,
+                allowed_chats: vec![],
+                commands: config::default_bot_commands(),
             }),
             events: vec![],
+            quiet_hours: None,
+            reports: None,
         };
         let manager = NotificationManager::new(config);
 
@@ -132,8 +185,14 @@ mod tests {
             telegram: Some(TelegramConfig {
                 bot_token: "test".to_string(),
                 chat_id: "123".to_string(),
+                min_severity: Severity::Info,
+                interactive: false,
+                allowed_chats: vec![],
+                commands: config::default_bot_commands(),
             }),
             events: vec!["crash".to_string(), "memory_limit".to_string()],
+            quiet_hours: None,
+            reports: None,
         };
         let manager = NotificationManager::new(config);
 
@@ -152,4 +211,42 @@ mod tests {
         };
         assert!(!manager.should_notify(&start_event));
     }
+
+    #[test]
+    fn test_quiet_hours_suppress_low_severity() {
+        let config = NotifyConfig {
+            quiet_hours: Some(QuietHours::new("22:00", "07:00")),
+            ..Default::default()
+        };
+        let manager = NotificationManager::new(config);
+
+        let start_event = ProcessEvent::Started {
+            name: "test".to_string(),
+            id: 1,
+        };
+        let crash_event = ProcessEvent::Crashed {
+            name: "test".to_string(),
+            id: 1,
+            error: "segfault".to_string(),
+        };
+        let night = NaiveTime::from_hms_opt(23, 0, 0).unwrap();
+        let day = NaiveTime::from_hms_opt(10, 0, 0).unwrap();
+
+        assert!(!manager.should_notify_at(&start_event, night));
+        assert!(manager.should_notify_at(&crash_event, night));
+        assert!(manager.should_notify_at(&start_event, day));
+    }
+
+    #[test]
+    fn test_channel_min_severity() {
+        let manager = NotificationManager::new(NotifyConfig::default());
+
+        let restart_event = ProcessEvent::Restarted {
+            name: "test".to_string(),
+            id: 1,
+            restart_count: 1,
+        };
+        assert!(manager.channel_accepts(None, &restart_event));
+        assert!(!manager.channel_accepts(Some(Severity::Warning), &restart_event));
+    }
 }
@@ -0,0 +1,366 @@
+//! Scheduled summary reports
+//!
+//! The daemon compiles a `Report` over the last day or week and sends it
+//! through the configured channels at the time set in `[reports]`.
+
+use crate::error::{NotifyError, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Most crashes listed in one report
+const MAX_CRASHES_LISTED: usize = 10;
+
+/// How often a report is sent, and the span it covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    #[default]
+    Daily,
+    Weekly,
+}
+
+impl ReportPeriod {
+    /// Span of time a report covers
+    pub fn duration(&self) -> Duration {
+        match self {
+            ReportPeriod::Daily => Duration::days(1),
+            ReportPeriod::Weekly => Duration::weeks(1),
+        }
+    }
+
+    /// Get the period as a string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportPeriod::Daily => "daily",
+            ReportPeriod::Weekly => "weekly",
+        }
+    }
+}
+
+/// Summary report configuration (`[reports]` in notify.toml)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportsConfig {
+    /// Send a report every day or every week (default: daily)
+    #[serde(default)]
+    pub period: ReportPeriod,
+
+    /// Local time to send the report at (HH:MM, default: 09:00)
+    #[serde(default = "default_report_at")]
+    pub at: String,
+
+    /// Day of the week for weekly reports (default: mon)
+    #[serde(default = "default_report_weekday")]
+    pub weekday: String,
+
+    /// How many apps to list under top memory and CPU (default: 3)
+    #[serde(default = "default_report_top")]
+    pub top: usize,
+}
+
+fn default_report_at() -> String {
+    "09:00".to_string()
+}
+
+fn default_report_weekday() -> String {
+    "mon".to_string()
+}
+
+fn default_report_top() -> usize {
+    3
+}
+
+impl Default for ReportsConfig {
+    fn default() -> Self {
+        Self {
+            period: ReportPeriod::default(),
+            at: default_report_at(),
+            weekday: default_report_weekday(),
+            top: default_report_top(),
+        }
+    }
+}
+
+impl ReportsConfig {
+    /// Parse the time of day and weekday
+    fn schedule(&self) -> Result<(NaiveTime, Weekday)> {
+        let at = NaiveTime::parse_from_str(self.at.trim(), "%H:%M").map_err(|_| {
+            NotifyError::config(format!(
+                "Invalid report time '{}'. Expected HH:MM (e.g. 09:00)",
+                self.at
+            ))
+        })?;
+        let weekday = self.weekday.trim().parse::<Weekday>().map_err(|_| {
+            NotifyError::config(format!(
+                "Invalid report weekday '{}'. Expected mon, tue, ... sun",
+                self.weekday
+            ))
+        })?;
+        Ok((at, weekday))
+    }
+
+    /// Check that the schedule can be parsed
+    pub fn validate(&self) -> Result<()> {
+        self.schedule().map(|_| ())
+    }
+
+    /// First time a report is due strictly after `now`
+    pub fn next_run<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Result<DateTime<Tz>> {
+        let (at, weekday) = self.schedule()?;
+        let tz = now.timezone();
+        let mut date = now.date_naive();
+        // A week and a day covers every weekday plus a time already passed today
+        for _ in 0..8 {
+            let due_today = match self.period {
+                ReportPeriod::Daily => true,
+                ReportPeriod::Weekly => date.weekday() == weekday,
+            };
+            if due_today {
+                // Skips times that do not exist on a DST change
+                if let Some(candidate) = tz.from_local_datetime(&date.and_time(at)).earliest() {
+                    if candidate > *now {
+                        return Ok(candidate);
+                    }
+                }
+            }
+            date = date
+                .succ_opt()
+                .ok_or_else(|| NotifyError::config("Report date out of range"))?;
+        }
+        Err(NotifyError::config("No upcoming report time"))
+    }
+
+    /// Next report time in local time
+    pub fn next_run_local(&self) -> Result<DateTime<Local>> {
+        self.next_run(&Local::now())
+    }
+}
+
+/// One app's figures over the report period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppReport {
+    pub name: String,
+    /// Share of the period the app was up, 0-100
+    pub uptime_percent: f64,
+    pub restarts: u32,
+    pub crashes: u32,
+    /// Highest memory use seen, if the app was sampled while running
+    pub peak_memory_bytes: Option<u64>,
+    /// Average CPU use while running, if sampled
+    pub avg_cpu_percent: Option<f32>,
+}
+
+/// A crash within the report period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashEntry {
+    pub name: String,
+    pub at: DateTime<Utc>,
+    pub exit_code: Option<i32>,
+    pub reason: Option<String>,
+}
+
+/// Summary of all apps over a day or week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub period: ReportPeriod,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub apps: Vec<AppReport>,
+    /// Oldest first
+    pub crashes: Vec<CrashEntry>,
+    /// How many apps to list under top memory and CPU
+    pub top: usize,
+}
+
+impl Report {
+    /// Format the report as a Markdown message
+    pub fn format_message(&self) -> String {
+        let title = match self.period {
+            ReportPeriod::Daily => "Daily report",
+            ReportPeriod::Weekly => "Weekly report",
+        };
+        let mut lines = vec![format!(
+            "\u{1F4CA} {} ({} - {} UTC)",
+            title,
+            self.since.format("%Y-%m-%d %H:%M"),
+            self.until.format("%Y-%m-%d %H:%M")
+        )];
+
+        if self.apps.is_empty() {
+            lines.push("No apps managed".to_string());
+            return lines.join("\n");
+        }
+
+        let restarts: u32 = self.apps.iter().map(|a| a.restarts).sum();
+        lines.push(format!(
+            "Apps: {}, restarts: {}, crashes: {}",
+            self.apps.len(),
+            restarts,
+            self.crashes.len()
+        ));
+
+        lines.push(String::new());
+        lines.push("*Uptime*".to_string());
+        let mut by_uptime: Vec<&AppReport> = self.apps.iter().collect();
+        by_uptime.sort_by(|a, b| {
+            a.uptime_percent
+                .total_cmp(&b.uptime_percent)
+                .then(a.name.cmp(&b.name))
+        });
+        for app in by_uptime {
+            let mut line = format!("`{}` {:.1}%", app.name, app.uptime_percent);
+            if app.restarts > 0 || app.crashes > 0 {
+                line.push_str(&format!(
+                    " ({} restarts, {} crashes)",
+                    app.restarts, app.crashes
+                ));
+            }
+            lines.push(line);
+        }
+
+        let mut by_memory: Vec<(&str, u64)> = self
+            .apps
+            .iter()
+            .filter_map(|a| a.peak_memory_bytes.map(|m| (a.name.as_str(), m)))
+            .collect();
+        by_memory.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        if !by_memory.is_empty() {
+            lines.push(String::new());
+            lines.push("*Top memory (peak)*".to_string());
+            for (name, bytes) in by_memory.into_iter().take(self.top) {
+                lines.push(format!("`{}` {}MB", name, bytes / (1024 * 1024)));
+            }
+        }
+
+        let mut by_cpu: Vec<(&str, f32)> = self
+            .apps
+            .iter()
+            .filter_map(|a| a.avg_cpu_percent.map(|c| (a.name.as_str(), c)))
+            .collect();
+        by_cpu.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+        if !by_cpu.is_empty() {
+            lines.push(String::new());
+            lines.push("*Top CPU (average)*".to_string());
+            for (name, cpu) in by_cpu.into_iter().take(self.top) {
+                lines.push(format!("`{}` {:.1}%", name, cpu));
+            }
+        }
+
+        if !self.crashes.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("*Crashes ({})*", self.crashes.len()));
+            let skipped = self.crashes.len().saturating_sub(MAX_CRASHES_LISTED);
+            for crash in self.crashes.iter().skip(skipped) {
+                let mut line = format!("`{}` at {}", crash.name, crash.at.format("%m-%d %H:%M"));
+                if let Some(code) = crash.exit_code {
+                    line.push_str(&format!(" - exit code {}", code));
+                }
+                if let Some(ref reason) = crash.reason {
+                    line.push_str(&format!(" ({})", reason));
+                }
+                lines.push(line);
+            }
+            if skipped > 0 {
+                lines.push(format!("...and {} earlier", skipped));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.from_utc_datetime(
+            &NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(h, min, 0)
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_next_run_daily() {
+        let config = ReportsConfig::default();
+        // 2026-10-14 is a Wednesday
+        assert_eq!(
+            config.next_run(&at(2026, 10, 14, 8, 0)).unwrap(),
+            at(2026, 10, 14, 9, 0)
+        );
+        assert_eq!(
+            config.next_run(&at(2026, 10, 14, 9, 0)).unwrap(),
+            at(2026, 10, 15, 9, 0)
+        );
+    }
+
+    #[test]
+    fn test_next_run_weekly() {
+        let config = ReportsConfig {
+            period: ReportPeriod::Weekly,
+            weekday: "mon".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.next_run(&at(2026, 10, 14, 8, 0)).unwrap(),
+            at(2026, 10, 19, 9, 0)
+        );
+        assert_eq!(
+            config.next_run(&at(2026, 10, 19, 10, 0)).unwrap(),
+            at(2026, 10, 26, 9, 0)
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ReportsConfig::default().validate().is_ok());
+        let config = ReportsConfig {
+            weekday: "someday".to_string(),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_format_message() {
+        let report = Report {
+            period: ReportPeriod::Daily,
+            since: at(2026, 10, 13, 9, 0),
+            until: at(2026, 10, 14, 9, 0),
+            apps: vec![
+                AppReport {
+                    name: "api".to_string(),
+                    uptime_percent: 98.5,
+                    restarts: 2,
+                    crashes: 1,
+                    peak_memory_bytes: Some(512 * 1024 * 1024),
+                    avg_cpu_percent: Some(12.0),
+                },
+                AppReport {
+                    name: "worker".to_string(),
+                    uptime_percent: 100.0,
+                    restarts: 0,
+                    crashes: 0,
+                    peak_memory_bytes: Some(128 * 1024 * 1024),
+                    avg_cpu_percent: Some(40.0),
+                },
+            ],
+            crashes: vec![CrashEntry {
+                name: "api".to_string(),
+                at: at(2026, 10, 13, 14, 2),
+                exit_code: Some(1),
+                reason: None,
+            }],
+            top: 1,
+        };
+
+        let msg = report.format_message();
+        assert!(msg.contains("Daily report"));
+        assert!(msg.contains("`api` 98.5% (2 restarts, 1 crashes)"));
+        assert!(msg.contains("*Top memory (peak)*\n`api` 512MB"));
+        assert!(msg.contains("*Top CPU (average)*\n`worker` 40.0%"));
+        assert!(msg.contains("`api` at 10-13 14:02 - exit code 1"));
+    }
+}
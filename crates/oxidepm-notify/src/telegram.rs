@@ -9,9 +9,10 @@ use tracing::{debug, error, info};
 
 /// Telegram API response
 #[derive(Debug, Deserialize)]
-struct TelegramResponse {
+struct TelegramResponse<T> {
     ok: bool,
     description: Option<String>,
+    result: Option<T>,
 }
 
 /// Request body for sendMessage
@@ -19,7 +20,37 @@ struct TelegramResponse {
 struct SendMessageRequest<'a> {
     chat_id: &'a str,
     text: &'a str,
-    parse_mode: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_mode: Option<&'a str>,
+}
+
+/// Request body for getUpdates
+#[derive(Debug, Serialize)]
+struct GetUpdatesRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<i64>,
+    timeout: u64,
+    allowed_updates: [&'static str; 1],
+}
+
+/// An incoming update from getUpdates
+#[derive(Debug, Clone, Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    pub message: Option<Message>,
+}
+
+/// A message sent to the bot
+#[derive(Debug, Clone, Deserialize)]
+pub struct Message {
+    pub chat: Chat,
+    pub text: Option<String>,
+}
+
+/// The chat a message came from
+#[derive(Debug, Clone, Deserialize)]
+pub struct Chat {
+    pub id: i64,
 }
 
 /// Telegram notification backend
@@ -48,41 +79,33 @@ impl TelegramNotifier {
         }
     }
 
-    /// Get the Telegram API URL for sendMessage
-    fn api_url(&self) -> String {
-        format!(
-            "https://api.telegram.org/bot{}/sendMessage",
-            self.bot_token
-        )
+    /// Get the Telegram API URL for a Bot API method
+    fn method_url(&self, method: &str) -> String {
+        format!("https://api.telegram.org/bot{}/{}", self.bot_token, method)
     }
 
-    /// Send a message using the Telegram Bot API
-    async fn send_telegram_message(&self, text: &str) -> Result<()> {
-        if self.bot_token.is_empty() || self.chat_id.is_empty() {
+    /// Call a Bot API method and return its result
+    async fn call<B, T>(&self, method: &str, body: &B) -> Result<Option<T>>
+    where
+        B: Serialize,
+        T: serde::de::DeserializeOwned,
+    {
+        if self.bot_token.is_empty() {
             return Err(NotifyError::NotConfigured);
         }
 
-        let request = SendMessageRequest {
-            chat_id: &self.chat_id,
-            text,
-            parse_mode: "Markdown",
-        };
-
-        debug!("Sending Telegram message to chat {}", self.chat_id);
-
         let response = self
             .client
-            .post(self.api_url())
-            .json(&request)
+            .post(self.method_url(method))
+            .json(body)
             .send()
             .await?;
 
         let status = response.status();
-        let body: TelegramResponse = response.json().await?;
+        let body: TelegramResponse<T> = response.json().await?;
 
         if body.ok {
-            info!("Telegram notification sent successfully");
-            Ok(())
+            Ok(body.result)
         } else {
             let error_msg = body
                 .description
@@ -91,6 +114,49 @@ impl TelegramNotifier {
             Err(NotifyError::telegram(error_msg))
         }
     }
+
+    /// Send a message using the Telegram Bot API
+    async fn send_telegram_message(&self, text: &str) -> Result<()> {
+        if self.bot_token.is_empty() || self.chat_id.is_empty() {
+            return Err(NotifyError::NotConfigured);
+        }
+
+        let request = SendMessageRequest {
+            chat_id: &self.chat_id,
+            text,
+            parse_mode: Some("Markdown"),
+        };
+
+        debug!("Sending Telegram message to chat {}", self.chat_id);
+
+        self.call::<_, serde_json::Value>("sendMessage", &request)
+            .await?;
+        info!("Telegram notification sent successfully");
+        Ok(())
+    }
+
+    /// Send a plain text message to a specific chat, e.g. a reply to a command
+    pub async fn send_to(&self, chat_id: &str, text: &str) -> Result<()> {
+        let request = SendMessageRequest {
+            chat_id,
+            text,
+            parse_mode: None,
+        };
+        debug!("Sending Telegram reply to chat {}", chat_id);
+        self.call::<_, serde_json::Value>("sendMessage", &request)
+            .await?;
+        Ok(())
+    }
+
+    /// Long-poll for messages sent to the bot, waiting up to `timeout_secs`
+    pub async fn get_updates(&self, offset: Option<i64>, timeout_secs: u64) -> Result<Vec<Update>> {
+        let request = GetUpdatesRequest {
+            offset,
+            timeout: timeout_secs,
+            allowed_updates: ["message"],
+        };
+        Ok(self.call("getUpdates", &request).await?.unwrap_or_default())
+    }
 }
 
 #[async_trait]
@@ -135,7 +201,7 @@ mod tests {
     fn test_api_url() {
         let notifier = TelegramNotifier::new("my_bot_token".to_string(), "123".to_string());
         assert_eq!(
-            notifier.api_url(),
+            notifier.method_url("sendMessage"),
             "https://api.telegram.org/botmy_bot_token/sendMessage"
         );
     }
@@ -0,0 +1,200 @@
+//! Prebuilt binary runner - runs an executable built elsewhere (e.g. by CI)
+
+use async_trait::async_trait;
+use oxidepm_core::{AppSpec, BuildInfo, Error, Result};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use tracing::info;
+
+use crate::traits::{PrepareResult, Runner, RunningProcess};
+
+/// Runs an existing executable as is, recording its checksum
+pub struct BinaryRunner;
+
+/// Executable of a binary-mode app, relative paths resolved against its cwd
+pub fn binary_path(spec: &AppSpec) -> PathBuf {
+    spec.cwd.join(&spec.command)
+}
+
+/// Hex-encoded SHA-256 of the file at `path`
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
+#[async_trait]
+impl Runner for BinaryRunner {
+    async fn prepare(&self, spec: &AppSpec) -> Result<PrepareResult> {
+        let path = binary_path(spec);
+        let metadata = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                return Ok(PrepareResult::failure(format!(
+                    "Binary not found: {}",
+                    path.display()
+                )))
+            }
+        };
+        if !metadata.is_file() {
+            return Ok(PrepareResult::failure(format!(
+                "Not a file: {}",
+                path.display()
+            )));
+        }
+        if !is_executable(&metadata) {
+            return Ok(PrepareResult::failure(format!(
+                "Binary is not executable: {} (try chmod +x)",
+                path.display()
+            )));
+        }
+
+        let hashed = path.clone();
+        let sha256 = tokio::task::spawn_blocking(move || sha256_file(&hashed))
+            .await
+            .map_err(|e| Error::ProcessStartFailed(format!("Checksum task failed: {}", e)))??;
+
+        let build = BuildInfo {
+            profile: BuildInfo::PREBUILT.to_string(),
+            binary_path: path.clone(),
+            duration_ms: 0,
+            git_commit: crate::git_commit(&spec.cwd).await,
+            sha256: Some(sha256.clone()),
+        };
+        Ok(PrepareResult::success_with_binary(
+            format!("Using {} (sha256 {})", path.display(), sha256),
+            path,
+        )
+        .with_build(build))
+    }
+
+    async fn start(&self, spec: &AppSpec) -> Result<RunningProcess> {
+        let path = binary_path(spec);
+        info!("Starting prebuilt binary: {}", path.display());
+
+        let mut cmd = Command::new(&path);
+        cmd.args(&spec.args)
+            .current_dir(&spec.cwd)
+            .envs(&spec.env)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(false);
+        crate::limits::apply(&mut cmd, spec)?;
+        crate::sandbox::apply(&mut cmd, spec)?;
+
+        let child = cmd.spawn().map_err(|e| {
+            Error::ProcessStartFailed(format!("Failed to start {}: {}", path.display(), e))
+        })?;
+
+        let pid = child.id().ok_or_else(|| {
+            Error::ProcessStartFailed("Process started but no PID available".to_string())
+        })?;
+
+        info!("Started process {} with PID {}", spec.name, pid);
+        Ok(RunningProcess::new(pid, child))
+    }
+
+    fn argv(&self, spec: &AppSpec) -> Vec<String> {
+        let mut argv = vec![binary_path(spec).to_string_lossy().into_owned()];
+        argv.extend(spec.args.iter().cloned());
+        argv
+    }
+
+    fn mode_name(&self) -> &'static str {
+        "binary"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::AppMode;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn spec(dir: &Path, command: &str) -> AppSpec {
+        AppSpec::new(
+            "artifact".to_string(),
+            AppMode::Binary,
+            command.to_string(),
+            dir.to_path_buf(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_prepare_checks_and_hashes_binary() {
+        let dir = TempDir::new().unwrap();
+        let runner = BinaryRunner;
+
+        let result = runner.prepare(&spec(dir.path(), "bin/app")).await.unwrap();
+        assert!(!result.success);
+        assert!(
+            result.output.contains("Binary not found"),
+            "{}",
+            result.output
+        );
+
+        let path = dir.path().join("app");
+        fs::write(&path, "test").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let result = runner.prepare(&spec(dir.path(), "app")).await.unwrap();
+        assert!(!result.success);
+        assert!(
+            result.output.contains("not executable"),
+            "{}",
+            result.output
+        );
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        let result = runner.prepare(&spec(dir.path(), "app")).await.unwrap();
+        assert!(result.success, "{}", result.output);
+        let build = result.build.unwrap();
+        assert_eq!(build.profile, BuildInfo::PREBUILT);
+        assert_eq!(build.binary_path, path);
+        assert_eq!(
+            build.sha256.as_deref(),
+            Some("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08")
+        );
+    }
+
+    #[test]
+    fn test_argv_resolves_relative_path() {
+        let spec = spec(Path::new("/srv/api"), "dist/api")
+            .with_args(vec!["--port".to_string(), "80".to_string()]);
+        assert_eq!(
+            BinaryRunner.argv(&spec),
+            vec!["/srv/api/dist/api", "--port", "80"]
+        );
+
+        let spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Binary,
+            "/opt/api".to_string(),
+            PathBuf::from("/srv"),
+        );
+        assert_eq!(BinaryRunner.argv(&spec)[0], "/opt/api");
+    }
+}
@@ -1,19 +1,45 @@
 //! Cargo project runner
 
 use async_trait::async_trait;
-use oxidepm_core::{AppSpec, Error, Result};
+use oxidepm_core::{AppSpec, BuildInfo, Error, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Instant, SystemTime};
 use tokio::process::Command;
 use tracing::{info, warn};
 
-use crate::traits::{PrepareResult, Runner, RunningProcess};
+use crate::traits::{PrepareResult, ProgressSink, Runner, RunningProcess};
 
 /// Cargo project runner - builds and runs Rust projects
 pub struct CargoRunner;
 
+/// Project directory and `cargo` arguments of a build
+type BuildKey = (PathBuf, Vec<String>);
+
+/// Builds this process has done, so restarting an unchanged project does not
+/// invoke cargo again
+static BUILD_CACHE: OnceLock<Mutex<HashMap<BuildKey, CachedBuild>>> = OnceLock::new();
+
+struct CachedBuild {
+    build: BuildInfo,
+    /// Newest source modification time when the build started
+    sources_modified: SystemTime,
+}
+
 #[async_trait]
 impl Runner for CargoRunner {
     async fn prepare(&self, spec: &AppSpec) -> Result<PrepareResult> {
+        self.prepare_with_progress(spec, &ProgressSink::none())
+            .await
+    }
+
+    async fn prepare_with_progress(
+        &self,
+        spec: &AppSpec,
+        progress: &ProgressSink,
+    ) -> Result<PrepareResult> {
         // Check if cargo is available
         let cargo_path = match which::which("cargo") {
             Ok(path) => path,
@@ -33,19 +59,38 @@ impl Runner for CargoRunner {
             )));
         }
 
-        info!("Building Cargo project in {}", spec.cwd.display());
+        let args = build_args(spec);
+        let binary_path = binary_path(spec)?;
+        let cache_key = (spec.cwd.clone(), args.clone());
+        let sources_modified = newest_source_mtime(&spec.cwd);
+
+        if let Some(build) = cached_build(&cache_key, sources_modified) {
+            if binary_path.exists() {
+                info!(
+                    "{} is up to date, skipping cargo build",
+                    binary_path.display()
+                );
+                return Ok(
+                    PrepareResult::success_with_binary("Build up to date", binary_path)
+                        .with_build(build),
+                );
+            }
+        }
+
+        info!(
+            "Building Cargo project in {}: cargo {}",
+            spec.cwd.display(),
+            args.join(" ")
+        );
 
-        // Run cargo build --release
         let mut cmd = Command::new(&cargo_path);
-        cmd.arg("build")
-            .arg("--release")
-            .current_dir(&spec.cwd)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+        cmd.args(&args).current_dir(&spec.cwd);
 
-        let output = cmd.output().await.map_err(|e| {
-            Error::BuildFailed(format!("Failed to run cargo build: {}", e))
-        })?;
+        let build_started = Instant::now();
+        let output = crate::output_with_progress(&mut cmd, progress)
+            .await
+            .map_err(|e| Error::BuildFailed(format!("Failed to run cargo build: {}", e)))?;
+        let duration_ms = build_started.elapsed().as_millis() as u64;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -55,28 +100,47 @@ impl Runner for CargoRunner {
             )));
         }
 
-        // Find the binary
-        let binary_name = find_binary_name(&spec.cwd, &spec.command)?;
-        let binary_path = spec.cwd.join("target/release").join(&binary_name);
-
         if !binary_path.exists() {
+            let out_dir = binary_path.parent().unwrap_or(&spec.cwd);
             return Ok(PrepareResult::failure(format!(
                 "Binary not found at {}. Available binaries: {:?}",
                 binary_path.display(),
-                list_release_binaries(&spec.cwd)
+                list_binaries(out_dir)
             )));
         }
 
+        let build = BuildInfo {
+            profile: profile(spec).to_string(),
+            binary_path: binary_path.clone(),
+            duration_ms,
+            git_commit: crate::git_commit(&spec.cwd).await,
+            sha256: None,
+        };
+        if let Some(sources_modified) = sources_modified {
+            build_cache()
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(
+                    cache_key,
+                    CachedBuild {
+                        build: build.clone(),
+                        sources_modified,
+                    },
+                );
+        }
+
         let stdout = String::from_utf8_lossy(&output.stdout);
-        Ok(PrepareResult::success_with_binary(
-            format!("Build successful\n{}", stdout),
-            binary_path,
-        ))
+        Ok(
+            PrepareResult::success_with_binary(
+                format!("Build successful\n{}", stdout),
+                binary_path,
+            )
+            .with_build(build),
+        )
     }
 
     async fn start(&self, spec: &AppSpec) -> Result<RunningProcess> {
-        let binary_name = find_binary_name(&spec.cwd, &spec.command)?;
-        let binary_path = spec.cwd.join("target/release").join(&binary_name);
+        let binary_path = binary_path(spec)?;
 
         if !binary_path.exists() {
             return Err(Error::ProcessStartFailed(format!(
@@ -94,6 +158,8 @@ impl Runner for CargoRunner {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .kill_on_drop(false);
+        crate::limits::apply(&mut cmd, spec)?;
+        crate::sandbox::apply(&mut cmd, spec)?;
 
         let child = cmd.spawn().map_err(|e| {
             Error::ProcessStartFailed(format!(
@@ -111,13 +177,19 @@ impl Runner for CargoRunner {
         Ok(RunningProcess::new(pid, child))
     }
 
-    fn command_string(&self, spec: &AppSpec) -> String {
-        let binary_name = find_binary_name(&spec.cwd, &spec.command).unwrap_or_else(|_| spec.command.clone());
-        let binary_path = spec.cwd.join("target/release").join(&binary_name);
+    fn argv(&self, spec: &AppSpec) -> Vec<String> {
+        let binary_path =
+            binary_path(spec).unwrap_or_else(|_| output_dir(spec).join(&spec.command));
 
-        let mut parts = vec![binary_path.to_string_lossy().to_string()];
-        parts.extend(spec.args.clone());
-        parts.join(" ")
+        let mut argv = vec![binary_path.to_string_lossy().to_string()];
+        argv.extend(spec.args.iter().cloned());
+        argv
+    }
+
+    fn prepare_command(&self, spec: &AppSpec) -> Option<Vec<String>> {
+        let mut command = vec!["cargo".to_string()];
+        command.extend(build_args(spec));
+        Some(command)
     }
 
     fn mode_name(&self) -> &'static str {
@@ -125,6 +197,85 @@ impl Runner for CargoRunner {
     }
 }
 
+/// Cargo profile the app is built with (default: release)
+fn profile(spec: &AppSpec) -> &str {
+    spec.cargo_profile.as_deref().unwrap_or("release")
+}
+
+/// Arguments for `cargo` that build the app
+fn build_args(spec: &AppSpec) -> Vec<String> {
+    let mut args = vec!["build".to_string()];
+    match profile(spec) {
+        "release" => args.push("--release".to_string()),
+        profile => args.extend(["--profile".to_string(), profile.to_string()]),
+    }
+    if !spec.cargo_features.is_empty() {
+        args.extend(["--features".to_string(), spec.cargo_features.join(",")]);
+    }
+    if let Some(target) = &spec.cargo_target {
+        args.extend(["--target".to_string(), target.clone()]);
+    }
+    args
+}
+
+/// Directory cargo puts the app's binaries in, e.g. target/x86_64-unknown-linux-musl/debug
+fn output_dir(spec: &AppSpec) -> PathBuf {
+    // The dev and test profiles share target/debug, bench uses target/release
+    let profile_dir = match profile(spec) {
+        "dev" | "test" => "debug",
+        "bench" => "release",
+        profile => profile,
+    };
+    let mut dir = spec.cwd.join("target");
+    if let Some(target) = &spec.cargo_target {
+        dir.push(target);
+    }
+    dir.join(profile_dir)
+}
+
+/// Path of the binary the app runs
+fn binary_path(spec: &AppSpec) -> Result<PathBuf> {
+    Ok(output_dir(spec).join(find_binary_name(&spec.cwd, &spec.command)?))
+}
+
+fn build_cache() -> &'static Mutex<HashMap<BuildKey, CachedBuild>> {
+    BUILD_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The cached build for `key`, if no source changed since it started
+fn cached_build(key: &BuildKey, sources_modified: Option<SystemTime>) -> Option<BuildInfo> {
+    let sources_modified = sources_modified?;
+    let cache = build_cache().lock().unwrap_or_else(|e| e.into_inner());
+    let cached = cache.get(key)?;
+    (sources_modified <= cached.sources_modified).then(|| cached.build.clone())
+}
+
+/// Newest modification time of the project's files, skipping build output
+/// and VCS directories. Path dependencies outside `dir` are not looked at.
+fn newest_source_mtime(dir: &Path) -> Option<SystemTime> {
+    let mut newest = None;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                if !matches!(name.to_str(), Some("target" | ".git" | "node_modules")) {
+                    pending.push(entry.path());
+                }
+            } else if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                newest = newest.max(Some(modified));
+            }
+        }
+    }
+    newest
+}
+
 /// Find the binary name from Cargo.toml or use the provided name
 fn find_binary_name(cwd: &std::path::Path, hint: &str) -> Result<String> {
     // If hint is not empty and doesn't look like a default, use it
@@ -158,14 +309,13 @@ fn find_binary_name(cwd: &std::path::Path, hint: &str) -> Result<String> {
     ))
 }
 
-/// List available binaries in target/release
-fn list_release_binaries(cwd: &std::path::Path) -> Vec<String> {
-    let release_dir = cwd.join("target/release");
-    if !release_dir.exists() {
+/// List available binaries in a cargo output directory
+fn list_binaries(dir: &Path) -> Vec<String> {
+    if !dir.exists() {
         return vec![];
     }
 
-    std::fs::read_dir(&release_dir)
+    std::fs::read_dir(dir)
         .ok()
         .map(|entries| {
             entries
@@ -229,7 +379,7 @@ path = "src/main.rs"
     #[test]
     fn test_find_binary_name_with_hint() {
         let dir = TempDir::new().unwrap();
-        let result = find_binary_name(&dir.path().to_path_buf(), "myapp").unwrap();
+        let result = find_binary_name(dir.path(), "myapp").unwrap();
         assert_eq!(result, "myapp");
     }
 
@@ -238,7 +388,99 @@ path = "src/main.rs"
         let dir = TempDir::new().unwrap();
         create_cargo_project(&dir, "test-app");
 
-        let result = find_binary_name(&dir.path().to_path_buf(), "").unwrap();
+        let result = find_binary_name(dir.path(), "").unwrap();
         assert_eq!(result, "test-app");
     }
+
+    #[test]
+    fn test_build_args_and_binary_path() {
+        let dir = TempDir::new().unwrap();
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Cargo,
+            "api".to_string(),
+            dir.path().to_path_buf(),
+        );
+
+        assert_eq!(build_args(&spec), vec!["build", "--release"]);
+        assert_eq!(
+            binary_path(&spec).unwrap(),
+            dir.path().join("target/release/api")
+        );
+
+        spec.cargo_profile = Some("dev".to_string());
+        spec.cargo_features = vec!["metrics".to_string(), "tls".to_string()];
+        spec.cargo_target = Some("x86_64-unknown-linux-musl".to_string());
+        assert_eq!(
+            build_args(&spec),
+            vec![
+                "build",
+                "--profile",
+                "dev",
+                "--features",
+                "metrics,tls",
+                "--target",
+                "x86_64-unknown-linux-musl"
+            ]
+        );
+        assert_eq!(
+            binary_path(&spec).unwrap(),
+            dir.path()
+                .join("target/x86_64-unknown-linux-musl/debug/api")
+        );
+
+        spec.cargo_profile = Some("staging".to_string());
+        spec.cargo_target = None;
+        assert_eq!(
+            binary_path(&spec).unwrap(),
+            dir.path().join("target/staging/api")
+        );
+    }
+
+    #[test]
+    fn test_build_cache_invalidated_by_changes() {
+        let dir = TempDir::new().unwrap();
+        create_cargo_project(&dir, "cached");
+        let key = (
+            dir.path().to_path_buf(),
+            vec!["build".to_string(), "--release".to_string()],
+        );
+        let built_at = newest_source_mtime(dir.path());
+        assert!(built_at.is_some());
+
+        build_cache().lock().unwrap().insert(
+            key.clone(),
+            CachedBuild {
+                build: BuildInfo {
+                    profile: "release".to_string(),
+                    binary_path: dir.path().join("target/release/cached"),
+                    duration_ms: 1,
+                    git_commit: None,
+                    sha256: None,
+                },
+                sources_modified: built_at.unwrap(),
+            },
+        );
+        assert!(cached_build(&key, newest_source_mtime(dir.path())).is_some());
+
+        // Output under target/ does not count as a change
+        std::fs::create_dir_all(dir.path().join("target/release")).unwrap();
+        std::fs::write(dir.path().join("target/release/cached"), "").unwrap();
+        let later = built_at.unwrap() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(dir.path().join("target/release/cached"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(cached_build(&key, newest_source_mtime(dir.path())).is_some());
+
+        std::fs::File::options()
+            .write(true)
+            .open(dir.path().join("src/main.rs"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(cached_build(&key, newest_source_mtime(dir.path())).is_none());
+    }
 }
@@ -51,6 +51,8 @@ impl Runner for CmdRunner {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .kill_on_drop(false);
+        crate::limits::apply(&mut cmd, spec)?;
+        crate::sandbox::apply(&mut cmd, spec)?;
 
         let child = cmd.spawn().map_err(|e| {
             Error::ProcessStartFailed(format!("Failed to start '{}': {}", spec.command, e))
@@ -64,12 +66,10 @@ impl Runner for CmdRunner {
         Ok(RunningProcess::new(pid, child))
     }
 
-    fn command_string(&self, spec: &AppSpec) -> String {
-        if spec.args.is_empty() {
-            spec.command.clone()
-        } else {
-            format!("{} {}", spec.command, spec.args.join(" "))
-        }
+    fn argv(&self, spec: &AppSpec) -> Vec<String> {
+        let mut argv = vec![spec.command.clone()];
+        argv.extend(spec.args.iter().cloned());
+        argv
     }
 
     fn mode_name(&self) -> &'static str {
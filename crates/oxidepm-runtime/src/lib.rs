@@ -1,20 +1,32 @@
 //! OxidePM Runtime - Process runners for different languages/modes
 
+pub mod binary;
 pub mod cargo;
 pub mod cmd;
+pub mod limits;
 pub mod node;
 pub mod npm;
+pub mod preflight;
 pub mod rust;
+pub mod sandbox;
 pub mod traits;
+pub mod workspace;
 
+pub use binary::BinaryRunner;
 pub use cargo::CargoRunner;
 pub use cmd::CmdRunner;
 pub use node::NodeRunner;
 pub use npm::NpmRunner;
 pub use rust::RustRunner;
-pub use traits::{PrepareResult, Runner, RunningProcess};
+pub use traits::{PrepareResult, ProgressSink, Runner, RunningProcess};
 
-use oxidepm_core::AppMode;
+use oxidepm_core::{AppMode, AppSpec, Error, Result};
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::{ExitStatus, Output, Stdio};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+use tokio::process::Command;
 
 /// Get the appropriate runner for an app mode
 pub fn get_runner(mode: AppMode) -> Box<dyn Runner> {
@@ -26,5 +38,231 @@ pub fn get_runner(mode: AppMode) -> Box<dyn Runner> {
         AppMode::Yarn => Box::new(NpmRunner::new("yarn")),
         AppMode::Cargo => Box::new(CargoRunner),
         AppMode::Rust => Box::new(RustRunner),
+        AppMode::Binary => Box::new(BinaryRunner),
+    }
+}
+
+/// Command that runs `argv` the way the app's own process runs: in its
+/// working directory and environment, under its resource limits and sandbox
+pub fn app_command(argv: &[String], spec: &AppSpec) -> Result<Command> {
+    let (program, args) = argv
+        .split_first()
+        .ok_or_else(|| Error::ProcessStartFailed("Nothing to run".to_string()))?;
+
+    let mut cmd = Command::new(program);
+    cmd.args(args).current_dir(&spec.cwd).envs(&spec.env);
+    limits::apply(&mut cmd, spec)?;
+    sandbox::apply(&mut cmd, spec)?;
+    Ok(cmd)
+}
+
+/// Command that runs an app attached to the terminal, the way its runner
+/// would launch it under the daemon. `spec` should already be prepared.
+pub fn foreground_command(runner: &dyn Runner, spec: &AppSpec) -> Result<Command> {
+    let mut cmd = app_command(&runner.argv(spec), spec)?;
+    cmd.stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    Ok(cmd)
+}
+
+/// Whether nothing on the host is listening on the port (IPv4 and IPv6)
+pub fn is_port_free(port: u16) -> bool {
+    ["0.0.0.0", "::"]
+        .iter()
+        .all(|host| match TcpListener::bind((*host, port)) {
+            Ok(_) => true,
+            // A host without IPv6 still has the port free
+            Err(e) => e.kind() != ErrorKind::AddrInUse,
+        })
+}
+
+/// Run a build command to completion like `Command::output`, passing each
+/// line it writes to stderr (where cargo and rustc report progress) on to
+/// `progress` as it arrives
+pub(crate) async fn output_with_progress(
+    cmd: &mut Command,
+    progress: &ProgressSink,
+) -> std::io::Result<Output> {
+    // The build gets a process group of its own, so dropping the future
+    // (timeout, cancelled start) can kill it along with rustc and build scripts
+    #[cfg(unix)]
+    cmd.process_group(0);
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut group = BuildGroup(child.id());
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    let read_stdout = async {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).await.map(|_| buf)
+    };
+    let read_stderr = async {
+        let mut lines = BufReader::new(stderr).lines();
+        let mut buf = String::new();
+        while let Some(line) = lines.next_line().await? {
+            progress.send(&line);
+            buf.push_str(&line);
+            buf.push('\n');
+        }
+        Ok::<_, std::io::Error>(buf.into_bytes())
+    };
+    let (stdout, stderr) = tokio::try_join!(read_stdout, read_stderr)?;
+    let status = child.wait().await?;
+    group.0 = None;
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Run a command to completion, passing each line it writes to stdout or
+/// stderr on to `output` as it arrives. Dropping the future kills the
+/// command's process group.
+pub async fn status_with_output(
+    cmd: &mut Command,
+    output: &ProgressSink,
+) -> std::io::Result<ExitStatus> {
+    #[cfg(unix)]
+    cmd.process_group(0);
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut group = BuildGroup(child.id());
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    tokio::try_join!(forward_lines(stdout, output), forward_lines(stderr, output))?;
+    let status = child.wait().await?;
+    group.0 = None;
+    Ok(status)
+}
+
+async fn forward_lines(
+    stream: impl AsyncRead + Unpin,
+    output: &ProgressSink,
+) -> std::io::Result<()> {
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(line) = lines.next_line().await? {
+        output.send(&line);
+    }
+    Ok(())
+}
+
+/// Kills a build's process group unless the build ran to completion
+struct BuildGroup(Option<u32>);
+
+impl Drop for BuildGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.0 {
+            use nix::sys::signal::{killpg, Signal};
+            let _ = killpg(nix::unistd::Pid::from_raw(pgid as i32), Signal::SIGKILL);
+        }
+    }
+}
+
+/// Commit checked out in the git repository containing `dir`, if any
+pub(crate) async fn git_commit(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?;
+    let commit = commit.trim();
+    (output.status.success() && !commit.is_empty()).then(|| commit.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_output_with_progress() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&lines);
+        let progress = ProgressSink::new(move |line| seen.lock().unwrap().push(line.to_string()));
+
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            "echo out; echo 'Compiling a' >&2; echo 'Compiling b' >&2; exit 3",
+        ]);
+        let output = output_with_progress(&mut cmd, &progress).await.unwrap();
+
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"Compiling a\nCompiling b\n");
+        assert_eq!(*lines.lock().unwrap(), vec!["Compiling a", "Compiling b"]);
+    }
+
+    #[tokio::test]
+    async fn test_status_with_output() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&lines);
+        let output = ProgressSink::new(move |line| seen.lock().unwrap().push(line.to_string()));
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo out; echo err >&2; exit 4"]);
+        let status = status_with_output(&mut cmd, &output).await.unwrap();
+
+        assert_eq!(status.code(), Some(4));
+        let mut lines = lines.lock().unwrap().clone();
+        lines.sort();
+        assert_eq!(lines, vec!["err", "out"]);
+    }
+
+    #[tokio::test]
+    async fn test_foreground_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut spec = AppSpec::new(
+            "once".to_string(),
+            AppMode::Cmd,
+            "sh".to_string(),
+            dir.path().to_path_buf(),
+        );
+        spec.args = vec![
+            "-c".to_string(),
+            "test \"$GREETING\" = hi && exit 7".to_string(),
+        ];
+        spec.env.insert("GREETING".to_string(), "hi".to_string());
+
+        let mut cmd = foreground_command(&CmdRunner, &spec).unwrap();
+        let status = cmd.status().await.unwrap();
+        assert_eq!(status.code(), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_git_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(git_commit(dir.path()).await, None);
+        if which::which("git").is_err() {
+            return;
+        }
+
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        let head = String::from_utf8(git(&["rev-parse", "HEAD"]).stdout).unwrap();
+        assert_eq!(git_commit(dir.path()).await.as_deref(), Some(head.trim()));
     }
 }
@@ -0,0 +1,107 @@
+//! Per-app resource limits (setrlimit)
+
+use nix::sys::resource::{getrlimit, setrlimit, Resource};
+use nix::unistd::geteuid;
+use oxidepm_core::{AppSpec, Error, Limits, Result};
+use tokio::process::Command;
+
+/// Apply the spec's resource limits to a command about to be spawned.
+///
+/// The soft limit is set to the configured value. The hard limit is only
+/// raised when it is below that value, which needs root; otherwise the start
+/// fails here with a clear message rather than as an opaque spawn error.
+pub fn apply(cmd: &mut Command, spec: &AppSpec) -> Result<()> {
+    let limits = resolve(&spec.limits)?;
+    if limits.is_empty() {
+        return Ok(());
+    }
+
+    // SAFETY: the hook only calls setrlimit on pre-computed values
+    unsafe {
+        cmd.pre_exec(move || {
+            for &(resource, soft, hard) in &limits {
+                setrlimit(resource, soft, hard)?;
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+/// (resource, soft, hard) for each configured limit
+fn resolve(limits: &Limits) -> Result<Vec<(Resource, u64, u64)>> {
+    [
+        ("nofile", Resource::RLIMIT_NOFILE, limits.nofile),
+        ("nproc", Resource::RLIMIT_NPROC, limits.nproc),
+        ("core", Resource::RLIMIT_CORE, limits.core),
+    ]
+    .into_iter()
+    .filter_map(|(name, resource, value)| value.map(|value| (name, resource, value)))
+    .map(|(name, resource, value)| {
+        let (_, current_hard) = getrlimit(resource)
+            .map_err(|e| Error::ProcessStartFailed(format!("Cannot read {} limit: {}", name, e)))?;
+        // RLIM_INFINITY is u64::MAX, the same as Limits::UNLIMITED
+        if value > current_hard && !geteuid().is_root() {
+            return Err(Error::ProcessStartFailed(format!(
+                "{} limit {} is above the hard limit {}; raising it needs root",
+                name,
+                Limits::format_value(value),
+                Limits::format_value(current_hard)
+            )));
+        }
+        Ok((resource, value, value.max(current_hard)))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::AppMode;
+    use std::path::PathBuf;
+    use std::process::Stdio;
+
+    #[tokio::test]
+    async fn test_limits_applied() {
+        let (soft, _) = getrlimit(Resource::RLIMIT_NOFILE).unwrap();
+        let nofile = soft.min(1024) - 1;
+        let spec = AppSpec::new(
+            "limited".to_string(),
+            AppMode::Cmd,
+            "sh".to_string(),
+            PathBuf::from("/tmp"),
+        )
+        .with_limits(Limits {
+            nofile: Some(nofile),
+            nproc: None,
+            core: Some(0),
+        });
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "ulimit -n; ulimit -c"])
+            .stdout(Stdio::piped());
+        apply(&mut cmd, &spec).unwrap();
+        let output = cmd.output().await.unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("{}\n0\n", nofile)
+        );
+    }
+
+    #[test]
+    fn test_limit_above_hard_limit() {
+        if geteuid().is_root() {
+            return;
+        }
+        let (_, hard) = getrlimit(Resource::RLIMIT_NOFILE).unwrap();
+        if hard == Limits::UNLIMITED {
+            return;
+        }
+        let limits = Limits {
+            nofile: Some(hard + 1),
+            ..Default::default()
+        };
+        let err = resolve(&limits).unwrap_err();
+        assert!(err.to_string().contains("raising it needs root"));
+    }
+}
@@ -74,6 +74,8 @@ impl Runner for NodeRunner {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .kill_on_drop(false);
+        crate::limits::apply(&mut cmd, spec)?;
+        crate::sandbox::apply(&mut cmd, spec)?;
 
         let child = cmd.spawn().map_err(|e| {
             Error::ProcessStartFailed(format!("Failed to start node: {}", e))
@@ -87,10 +89,15 @@ impl Runner for NodeRunner {
         Ok(RunningProcess::new(pid, child))
     }
 
-    fn command_string(&self, spec: &AppSpec) -> String {
-        let mut parts = vec!["node".to_string(), spec.command.clone()];
-        parts.extend(spec.args.clone());
-        parts.join(" ")
+    fn argv(&self, spec: &AppSpec) -> Vec<String> {
+        let script_path = if std::path::Path::new(&spec.command).is_absolute() {
+            spec.command.clone()
+        } else {
+            spec.cwd.join(&spec.command).to_string_lossy().to_string()
+        };
+        let mut argv = vec!["node".to_string(), script_path];
+        argv.extend(spec.args.iter().cloned());
+        argv
     }
 
     fn mode_name(&self) -> &'static str {
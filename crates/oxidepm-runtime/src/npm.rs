@@ -7,6 +7,7 @@ use tokio::process::Command;
 use tracing::info;
 
 use crate::traits::{PrepareResult, Runner, RunningProcess};
+use crate::workspace;
 
 /// npm/pnpm/yarn script runner
 pub struct NpmRunner {
@@ -33,12 +34,21 @@ impl Runner for NpmRunner {
             }
         };
 
+        // In a monorepo the script lives in the workspace package
+        let package_dir = match &spec.workspace {
+            Some(selector) => match workspace::find_package(&spec.cwd, selector) {
+                Ok(package) => package.dir,
+                Err(e) => return Ok(PrepareResult::failure(e.to_string())),
+            },
+            None => spec.cwd.clone(),
+        };
+
         // Check for package.json
-        let package_json = spec.cwd.join("package.json");
+        let package_json = package_dir.join("package.json");
         if !package_json.exists() {
             return Ok(PrepareResult::failure(format!(
                 "package.json not found in {}",
-                spec.cwd.display()
+                package_dir.display()
             )));
         }
 
@@ -78,15 +88,16 @@ impl Runner for NpmRunner {
             spec.cwd.display()
         );
 
-        let mut cmd = Command::new(self.tool);
-        cmd.arg("run")
-            .arg(&spec.command)
-            .args(&spec.args)
+        let argv = self.argv(spec);
+        let mut cmd = Command::new(&argv[0]);
+        cmd.args(&argv[1..])
             .current_dir(&spec.cwd)
             .envs(&spec.env)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .kill_on_drop(false);
+        crate::limits::apply(&mut cmd, spec)?;
+        crate::sandbox::apply(&mut cmd, spec)?;
 
         let child = cmd.spawn().map_err(|e| {
             Error::ProcessStartFailed(format!("Failed to start {}: {}", self.tool, e))
@@ -106,10 +117,23 @@ impl Runner for NpmRunner {
         Ok(RunningProcess::new(pid, child))
     }
 
-    fn command_string(&self, spec: &AppSpec) -> String {
-        let mut parts = vec![self.tool.to_string(), "run".to_string(), spec.command.clone()];
-        parts.extend(spec.args.clone());
-        parts.join(" ")
+    fn argv(&self, spec: &AppSpec) -> Vec<String> {
+        let mut argv = vec![self.tool.to_string()];
+        if let Some(selector) = &spec.workspace {
+            // Filter by package name; fall back to what the user gave if the
+            // workspace can't be read (prepare reports why)
+            let package = workspace::find_package(&spec.cwd, selector)
+                .map(|package| package.name)
+                .unwrap_or_else(|_| selector.clone());
+            match self.tool {
+                "pnpm" => argv.extend(["--filter".to_string(), package]),
+                "yarn" => argv.extend(["workspace".to_string(), package]),
+                _ => argv.push(format!("--workspace={}", package)),
+            }
+        }
+        argv.extend(["run".to_string(), spec.command.clone()]);
+        argv.extend(spec.args.iter().cloned());
+        argv
     }
 
     fn mode_name(&self) -> &'static str {
@@ -200,4 +224,42 @@ mod tests {
         let result = runner.prepare(&spec).await.unwrap();
         assert!(result.success);
     }
+
+    #[test]
+    fn test_workspace_argv() {
+        let root = TempDir::new().unwrap();
+        std::fs::write(
+            root.path().join("pnpm-workspace.yaml"),
+            "packages:\n  - apps/*\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.path().join("apps/web")).unwrap();
+        std::fs::write(
+            root.path().join("apps/web/package.json"),
+            r#"{"name": "@acme/web"}"#,
+        )
+        .unwrap();
+
+        let mut spec = AppSpec::new(
+            "web".to_string(),
+            AppMode::Pnpm,
+            "dev".to_string(),
+            root.path().to_path_buf(),
+        )
+        .with_args(vec!["--host".to_string()]);
+        spec.workspace = Some("apps/web".to_string());
+
+        assert_eq!(
+            NpmRunner::new("pnpm").argv(&spec),
+            vec!["pnpm", "--filter", "@acme/web", "run", "dev", "--host"]
+        );
+        assert_eq!(
+            NpmRunner::new("yarn").argv(&spec),
+            vec!["yarn", "workspace", "@acme/web", "run", "dev", "--host"]
+        );
+        assert_eq!(
+            NpmRunner::new("npm").argv(&spec),
+            vec!["npm", "--workspace=@acme/web", "run", "dev", "--host"]
+        );
+    }
 }
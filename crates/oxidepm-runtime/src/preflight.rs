@@ -0,0 +1,1219 @@
+//! Preflight checks: whether a project is ready to run (manifest, installed
+//! dependencies, lockfile, build output, toolchain version, `.env`),
+//! optionally fixing what can be fixed. Shared by `oxidepm check`,
+//! `oxidepm start` and the daemon, which runs them for starts that come in
+//! over the API. `oxidepm check` also probes the services `.env` points at.
+
+use oxidepm_core::{load_env_file, CheckResult, CheckStatus, FetchFailure, PreflightSummary};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::workspace::runnable_members;
+
+/// `.env` variables whose connection strings `probe_dependencies` tries
+const PROBED_VARS: &[&str] = &["DATABASE_URL", "REDIS_URL"];
+
+/// How long one connect (and the Redis PING answer) may take
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Project type detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectType {
+    NodeJs,
+    Cargo,
+    Generic,
+}
+
+impl ProjectType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectType::NodeJs => "nodejs",
+            ProjectType::Cargo => "cargo",
+            ProjectType::Generic => "generic",
+        }
+    }
+}
+
+/// Run preflight checks on a project directory
+pub fn run_preflight_checks(project_dir: &Path, auto_fix: bool) -> PreflightSummary {
+    let mut results: Vec<CheckResult> = Vec::new();
+
+    // Run checks based on project type
+    match detect_project_type(project_dir) {
+        ProjectType::NodeJs => {
+            check_nodejs_project(project_dir, auto_fix, &mut results);
+        }
+        ProjectType::Cargo => {
+            check_cargo_project(project_dir, auto_fix, &mut results);
+        }
+        ProjectType::Generic => {
+            // Still run generic checks
+        }
+    }
+
+    // Run generic checks for all project types
+    check_env_files(project_dir, auto_fix, &mut results);
+    check_workspace_members(project_dir, &mut results);
+
+    PreflightSummary::new(results)
+}
+
+pub fn detect_project_type(dir: &Path) -> ProjectType {
+    if dir.join("package.json").exists() {
+        ProjectType::NodeJs
+    } else if dir.join("Cargo.toml").exists() {
+        ProjectType::Cargo
+    } else {
+        ProjectType::Generic
+    }
+}
+
+fn check_nodejs_project(dir: &Path, fix: bool, results: &mut Vec<CheckResult>) {
+    let package_json = dir.join("package.json");
+
+    // Check package.json exists
+    if package_json.exists() {
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: "package.json found".to_string(),
+            fix_hint: None,
+        });
+    } else {
+        results.push(CheckResult {
+            status: CheckStatus::Error,
+            message: "package.json not found".to_string(),
+            fix_hint: Some("Run `npm init` to create package.json".to_string()),
+        });
+        return;
+    }
+
+    check_node_version(dir, fix, results);
+
+    // Check node_modules exists
+    let node_modules = dir.join("node_modules");
+    if node_modules.exists() && node_modules.is_dir() {
+        // Count packages
+        let package_count = fs::read_dir(&node_modules)
+            .map(|entries| entries.filter_map(|e| e.ok()).count())
+            .unwrap_or(0);
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: format!("node_modules/ found ({} packages)", package_count),
+            fix_hint: None,
+        });
+    } else if fix {
+        // Attempt to run npm install
+        results.push(CheckResult {
+            status: CheckStatus::Fixed,
+            message: "Running npm install...".to_string(),
+            fix_hint: None,
+        });
+
+        let install_result = run_npm_install(dir);
+        match install_result {
+            Ok(count) => {
+                results.push(CheckResult {
+                    status: CheckStatus::Ok,
+                    message: format!("node_modules/ installed ({} packages)", count),
+                    fix_hint: None,
+                });
+            }
+            Err(output) => {
+                let (message, fix_hint) = match FetchFailure::classify(&output) {
+                    Some(kind) => (
+                        format!("npm install failed [{}]: {}", kind.code(), output),
+                        kind.hint().to_string(),
+                    ),
+                    None => (
+                        format!("npm install failed: {}", output),
+                        "Try running `npm install` manually".to_string(),
+                    ),
+                };
+                results.push(CheckResult {
+                    status: CheckStatus::Error,
+                    message,
+                    fix_hint: Some(fix_hint),
+                });
+            }
+        }
+    } else {
+        results.push(CheckResult {
+            status: CheckStatus::Warn,
+            message: "node_modules/ missing - run `npm install`".to_string(),
+            fix_hint: Some("Use --fix to auto-install dependencies".to_string()),
+        });
+    }
+
+    // Check for lockfile
+    let has_package_lock = dir.join("package-lock.json").exists();
+    let has_yarn_lock = dir.join("yarn.lock").exists();
+    let has_pnpm_lock = dir.join("pnpm-lock.yaml").exists();
+
+    if has_package_lock {
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: "package-lock.json found".to_string(),
+            fix_hint: None,
+        });
+    } else if has_yarn_lock {
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: "yarn.lock found".to_string(),
+            fix_hint: None,
+        });
+    } else if has_pnpm_lock {
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: "pnpm-lock.yaml found".to_string(),
+            fix_hint: None,
+        });
+    } else {
+        results.push(CheckResult {
+            status: CheckStatus::Warn,
+            message: "No lockfile found (package-lock.json, yarn.lock, or pnpm-lock.yaml)"
+                .to_string(),
+            fix_hint: Some("Run `npm install` to generate package-lock.json".to_string()),
+        });
+    }
+}
+
+fn check_cargo_project(dir: &Path, fix: bool, results: &mut Vec<CheckResult>) {
+    let cargo_toml = dir.join("Cargo.toml");
+
+    // Check Cargo.toml exists
+    if cargo_toml.exists() {
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: "Cargo.toml found".to_string(),
+            fix_hint: None,
+        });
+    } else {
+        results.push(CheckResult {
+            status: CheckStatus::Error,
+            message: "Cargo.toml not found".to_string(),
+            fix_hint: Some("Run `cargo init` to create a new Cargo project".to_string()),
+        });
+        return;
+    }
+
+    check_rust_version(dir, fix, results);
+
+    // Check Cargo.lock exists
+    let cargo_lock = dir.join("Cargo.lock");
+    if cargo_lock.exists() {
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: "Cargo.lock found".to_string(),
+            fix_hint: None,
+        });
+    } else {
+        results.push(CheckResult {
+            status: CheckStatus::Warn,
+            message: "Cargo.lock missing - dependencies not locked".to_string(),
+            fix_hint: Some("Run `cargo build` to generate Cargo.lock".to_string()),
+        });
+    }
+
+    // Check target/ directory (build artifacts)
+    let target_dir = dir.join("target");
+    if target_dir.exists() && target_dir.is_dir() {
+        // Check for debug or release builds
+        let has_debug = target_dir.join("debug").exists();
+        let has_release = target_dir.join("release").exists();
+
+        if has_release {
+            results.push(CheckResult {
+                status: CheckStatus::Ok,
+                message: "target/release/ found (release build available)".to_string(),
+                fix_hint: None,
+            });
+        } else if has_debug {
+            results.push(CheckResult {
+                status: CheckStatus::Ok,
+                message: "target/debug/ found (debug build available)".to_string(),
+                fix_hint: None,
+            });
+        } else {
+            results.push(CheckResult {
+                status: CheckStatus::Info,
+                message: "target/ exists but no builds found".to_string(),
+                fix_hint: Some("Run `cargo build` or `cargo build --release`".to_string()),
+            });
+        }
+    } else {
+        results.push(CheckResult {
+            status: CheckStatus::Info,
+            message: "target/ not found - project not built yet".to_string(),
+            fix_hint: Some("Run `cargo build` to build the project".to_string()),
+        });
+    }
+}
+
+/// List the members of a monorepo root that `oxidepm start` would start
+fn check_workspace_members(dir: &Path, results: &mut Vec<CheckResult>) {
+    let members = runnable_members(dir);
+    if members.is_empty() {
+        return;
+    }
+    results.push(CheckResult {
+        status: CheckStatus::Info,
+        message: format!("Monorepo: {} runnable members", members.len()),
+        fix_hint: None,
+    });
+    for member in members {
+        let relative = member.dir.strip_prefix(dir).unwrap_or(&member.dir);
+        let relative = if relative.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            relative.display().to_string()
+        };
+        results.push(CheckResult {
+            status: CheckStatus::Info,
+            message: format!(
+                "Member {} ({}, {})",
+                member.name,
+                relative,
+                member.kind.describe()
+            ),
+            fix_hint: None,
+        });
+    }
+}
+
+/// A `major.minor.patch` version; missing parts of a partial one are `None`
+type Partial = [Option<u64>; 3];
+type Version = (u64, u64, u64);
+
+/// Compare `engines.node` in package.json against `node --version`
+fn check_node_version(dir: &Path, fix: bool, results: &mut Vec<CheckResult>) {
+    let Some(range) = fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|package| package["engines"]["node"].as_str().map(str::to_string))
+    else {
+        return;
+    };
+
+    let Some(installed) = tool_version(dir, "node", &["--version"]) else {
+        results.push(CheckResult {
+            status: CheckStatus::Info,
+            message: format!("node not found - engines.node \"{}\" not checked", range),
+            fix_hint: None,
+        });
+        return;
+    };
+    let shown = format_version(installed);
+
+    match node_range_satisfied(&range, installed) {
+        Some(true) => results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: format!("Node.js {} satisfies engines.node \"{}\"", shown, range),
+            fix_hint: None,
+        }),
+        None => results.push(CheckResult {
+            status: CheckStatus::Warn,
+            message: format!(
+                "engines.node \"{}\" is not a version range oxidepm understands",
+                range
+            ),
+            fix_hint: None,
+        }),
+        Some(false) => {
+            let wanted = node_install_target(&range);
+            let message = format!(
+                "Node.js {} does not satisfy engines.node \"{}\"",
+                shown, range
+            );
+            if fix {
+                match run_nvm_install(&wanted) {
+                    Ok(()) => {
+                        results.push(CheckResult {
+                            status: CheckStatus::Fixed,
+                            message: format!("Installed Node.js {} with nvm", wanted),
+                            fix_hint: None,
+                        });
+                        results.push(CheckResult {
+                            status: CheckStatus::Error,
+                            message,
+                            fix_hint: Some(format!(
+                                "Switch to it with `nvm alias default {}` and restart the daemon",
+                                wanted
+                            )),
+                        });
+                    }
+                    Err(e) => results.push(CheckResult {
+                        status: CheckStatus::Error,
+                        message: format!("{} (nvm install failed: {})", message, e),
+                        fix_hint: Some(format!("Install Node.js {} manually", range)),
+                    }),
+                }
+            } else {
+                results.push(CheckResult {
+                    status: CheckStatus::Error,
+                    message,
+                    fix_hint: Some(format!(
+                        "Use --fix to install Node.js {} with nvm, or `nvm install {}`",
+                        wanted, wanted
+                    )),
+                });
+            }
+        }
+    }
+}
+
+/// Compare `rust-version` in Cargo.toml against `rustc --version`, which
+/// honours rustup overrides of the project directory
+fn check_rust_version(dir: &Path, fix: bool, results: &mut Vec<CheckResult>) {
+    let Some(required) = fs::read_to_string(dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .and_then(|manifest| {
+            let declared = |table: Option<&toml::Value>| {
+                table?.get("rust-version")?.as_str().map(str::to_string)
+            };
+            declared(manifest.get("package"))
+                .or_else(|| declared(manifest.get("workspace").and_then(|w| w.get("package"))))
+        })
+    else {
+        return;
+    };
+    let Some(minimum) = parse_partial(&required).map(|p| lower_bound(&p)) else {
+        results.push(CheckResult {
+            status: CheckStatus::Warn,
+            message: format!("rust-version \"{}\" is not a version", required),
+            fix_hint: None,
+        });
+        return;
+    };
+
+    let Some(mut installed) = tool_version(dir, "rustc", &["--version"]) else {
+        results.push(CheckResult {
+            status: CheckStatus::Info,
+            message: format!("rustc not found - rust-version {} not checked", required),
+            fix_hint: None,
+        });
+        return;
+    };
+
+    if installed < minimum && fix && which::which("rustup").is_ok() {
+        match run_rustup_update(dir) {
+            Ok(()) => {
+                results.push(CheckResult {
+                    status: CheckStatus::Fixed,
+                    message: format!(
+                        "Updated Rust with rustup (was {})",
+                        format_version(installed)
+                    ),
+                    fix_hint: None,
+                });
+                installed = tool_version(dir, "rustc", &["--version"]).unwrap_or(installed);
+            }
+            Err(e) => results.push(CheckResult {
+                status: CheckStatus::Error,
+                message: format!("rustup update failed: {}", e),
+                fix_hint: Some("Try running `rustup update` manually".to_string()),
+            }),
+        }
+    }
+
+    if installed >= minimum {
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: format!(
+                "rustc {} satisfies rust-version {}",
+                format_version(installed),
+                required
+            ),
+            fix_hint: None,
+        });
+    } else {
+        let fix_hint = if !fix {
+            "Use --fix to update Rust with rustup, or run `rustup update`"
+        } else if which::which("rustup").is_err() {
+            "rustup not found - install a newer Rust toolchain manually"
+        } else {
+            "Check for a rust-toolchain file pinning an older toolchain"
+        };
+        results.push(CheckResult {
+            status: CheckStatus::Error,
+            message: format!(
+                "rustc {} is older than rust-version {}",
+                format_version(installed),
+                required
+            ),
+            fix_hint: Some(fix_hint.to_string()),
+        });
+    }
+}
+
+/// Version a `<tool> --version` run in `dir` prints, e.g. `v20.11.1` or
+/// `rustc 1.78.0 (9b00956e5 2024-04-29)`
+fn tool_version(dir: &Path, tool: &str, args: &[&str]) -> Option<Version> {
+    let output = Command::new(tool)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .split_whitespace()
+        .find_map(|word| parse_partial(word).filter(|p| p[1].is_some()))
+        .map(|p| lower_bound(&p))
+}
+
+fn format_version((major, minor, patch): Version) -> String {
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+/// Parse `18`, `v18.2`, `18.x`, `1.70.0-beta.1`; `*`, `x` or an empty string
+/// leave every part open
+fn parse_partial(s: &str) -> Option<Partial> {
+    let s = s.trim().trim_start_matches(['v', 'V', '=']);
+    let s = s.split(['-', '+']).next().unwrap_or(s);
+    let mut parts = [None; 3];
+    if s.is_empty() {
+        return Some(parts);
+    }
+    let mut open = false;
+    for (i, part) in s.split('.').enumerate() {
+        if i == 3 {
+            return None;
+        }
+        match part {
+            "x" | "X" | "*" => open = true,
+            _ if open => return None,
+            _ => parts[i] = Some(part.parse().ok()?),
+        }
+    }
+    Some(parts)
+}
+
+fn lower_bound(p: &Partial) -> Version {
+    (p[0].unwrap_or(0), p[1].unwrap_or(0), p[2].unwrap_or(0))
+}
+
+/// First version past a partial one, e.g. `18.3.0` for `18.2`; `None` for
+/// a full or fully open version
+fn next_after(p: &Partial) -> Option<Version> {
+    match *p {
+        [Some(major), None, _] => Some((major + 1, 0, 0)),
+        [Some(major), Some(minor), None] => Some((major, minor + 1, 0)),
+        _ => None,
+    }
+}
+
+/// Whether `version` is in an npm-style range (`>=18`, `^18.2.0`, `~20.1`,
+/// `18.x`, `16 - 20`, `>=16 <21`, alternatives joined by `||`); `None` if the
+/// range doesn't parse
+fn node_range_satisfied(range: &str, version: Version) -> Option<bool> {
+    let mut satisfied = false;
+    for set in range.split("||") {
+        let bounds = range_bounds(set)?;
+        satisfied |= bounds.iter().all(|(order, bound)| {
+            let actual = version.cmp(bound);
+            order.contains(&actual)
+        });
+    }
+    Some(satisfied)
+}
+
+// Orderings against a range bound a version may have
+const GE: &[Ordering] = &[Ordering::Greater, Ordering::Equal];
+const GT: &[Ordering] = &[Ordering::Greater];
+const LE: &[Ordering] = &[Ordering::Less, Ordering::Equal];
+const LT: &[Ordering] = &[Ordering::Less];
+const EQ: &[Ordering] = &[Ordering::Equal];
+
+/// Comparisons a version must pass for one `||` alternative: each is the
+/// orderings against a bound that are allowed
+fn range_bounds(set: &str) -> Option<Vec<(&'static [Ordering], Version)>> {
+    let mut bounds = Vec::new();
+    if let Some((low, high)) = set.split_once(" - ") {
+        let (low, high) = (parse_partial(low)?, parse_partial(high)?);
+        bounds.push((GE, lower_bound(&low)));
+        match next_after(&high) {
+            Some(next) => bounds.push((LT, next)),
+            None if high[0].is_some() => bounds.push((LE, lower_bound(&high))),
+            None => {}
+        }
+        return Some(bounds);
+    }
+
+    // `>= 18` is the same comparator as `>=18`
+    let mut comparators: Vec<String> = Vec::new();
+    for token in set.split_whitespace() {
+        match comparators.last_mut() {
+            Some(last) if last.chars().all(|c| "<>=^~".contains(c)) => last.push_str(token),
+            _ => comparators.push(token.to_string()),
+        }
+    }
+
+    for comparator in &comparators {
+        let split = comparator
+            .find(|c: char| !"<>=^~".contains(c))
+            .unwrap_or(comparator.len());
+        let (op, version) = comparator.split_at(split);
+        let p = parse_partial(version)?;
+        if p[0].is_none() {
+            // `*`: anything goes
+            continue;
+        }
+        let low = lower_bound(&p);
+        let next = next_after(&p);
+        match op {
+            "" | "=" => match next {
+                Some(next) => bounds.extend([(GE, low), (LT, next)]),
+                None => bounds.push((EQ, low)),
+            },
+            ">=" => bounds.push((GE, low)),
+            ">" => match next {
+                Some(next) => bounds.push((GE, next)),
+                None => bounds.push((GT, low)),
+            },
+            "<" => bounds.push((LT, low)),
+            "<=" => match next {
+                Some(next) => bounds.push((LT, next)),
+                None => bounds.push((LE, low)),
+            },
+            "~" => {
+                let upper = match p[1] {
+                    Some(minor) => (low.0, minor + 1, 0),
+                    None => (low.0 + 1, 0, 0),
+                };
+                bounds.extend([(GE, low), (LT, upper)]);
+            }
+            "^" => {
+                let upper = match p {
+                    [Some(0), Some(0), Some(patch)] => (0, 0, patch + 1),
+                    [Some(0), Some(minor), _] if minor > 0 || p[2].is_none() => (0, minor + 1, 0),
+                    [Some(major), _, _] => (major + 1, 0, 0),
+                    _ => return None,
+                };
+                bounds.extend([(GE, low), (LT, upper)]);
+            }
+            _ => return None,
+        }
+    }
+    Some(bounds)
+}
+
+/// Version to hand `nvm install` for a range: the major of its first lower
+/// bound, or the latest release when it has none
+fn node_install_target(range: &str) -> String {
+    let first = range.split("||").next().unwrap_or(range);
+    range_bounds(first)
+        .and_then(|bounds| {
+            bounds
+                .into_iter()
+                .find(|(order, _)| *order == GE || *order == EQ)
+                .map(|(_, (major, _, _))| major.to_string())
+        })
+        .unwrap_or_else(|| "node".to_string())
+}
+
+/// nvm is a shell function, so source it first
+fn run_nvm_install(version: &str) -> Result<(), String> {
+    let nvm_dir = std::env::var_os("NVM_DIR")
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".nvm")))
+        .ok_or_else(|| "nvm not found".to_string())?;
+    let script = nvm_dir.join("nvm.sh");
+    if !script.is_file() {
+        return Err("nvm not found".to_string());
+    }
+
+    let output = Command::new("bash")
+        .arg("-c")
+        .arg("source \"$0\" && nvm install \"$1\"")
+        .arg(&script)
+        .arg(version)
+        .output()
+        .map_err(|e| format!("bash: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+    Ok(())
+}
+
+fn run_rustup_update(dir: &Path) -> Result<(), String> {
+    let output = Command::new("rustup")
+        .arg("update")
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("rustup: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+    Ok(())
+}
+
+fn check_env_files(dir: &Path, fix: bool, results: &mut Vec<CheckResult>) {
+    let env_file = dir.join(".env");
+    let env_example = dir.join(".env.example");
+    let env_template = dir.join(".env.template");
+    let env_local = dir.join(".env.local");
+
+    // Check if .env exists
+    if env_file.exists() {
+        results.push(CheckResult {
+            status: CheckStatus::Ok,
+            message: ".env found".to_string(),
+            fix_hint: None,
+        });
+    } else {
+        // Check for templates
+        let template_path = if env_example.exists() {
+            Some(env_example.clone())
+        } else if env_template.exists() {
+            Some(env_template.clone())
+        } else {
+            None
+        };
+
+        if let Some(template) = template_path {
+            let template_name = template
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("template");
+
+            if fix {
+                // Copy template to .env
+                match fs::copy(&template, &env_file) {
+                    Ok(_) => {
+                        results.push(CheckResult {
+                            status: CheckStatus::Fixed,
+                            message: format!("Creating .env from {}", template_name),
+                            fix_hint: None,
+                        });
+                        results.push(CheckResult {
+                            status: CheckStatus::Ok,
+                            message: ".env created".to_string(),
+                            fix_hint: None,
+                        });
+                    }
+                    Err(e) => {
+                        results.push(CheckResult {
+                            status: CheckStatus::Error,
+                            message: format!("Failed to create .env: {}", e),
+                            fix_hint: Some(format!("Manually copy {} to .env", template_name)),
+                        });
+                    }
+                }
+            } else {
+                results.push(CheckResult {
+                    status: CheckStatus::Warn,
+                    message: format!(".env missing (template found: {})", template_name),
+                    fix_hint: Some("Use --fix to create .env from template".to_string()),
+                });
+            }
+        } else {
+            // No .env and no template - just info
+            results.push(CheckResult {
+                status: CheckStatus::Info,
+                message: ".env not found (no template available)".to_string(),
+                fix_hint: None,
+            });
+        }
+    }
+
+    // Check .env.local
+    if env_local.exists() {
+        results.push(CheckResult {
+            status: CheckStatus::Info,
+            message: ".env.local found".to_string(),
+            fix_hint: None,
+        });
+    }
+}
+
+/// Try to reach the services `DATABASE_URL` and `REDIS_URL` in `.env` (and
+/// `.env.local`, which overrides it) point at: a TCP connect, plus a PING for
+/// plain `redis://`. Unreachable services are warnings, as they may well come
+/// up before the app needs them.
+pub fn probe_dependencies(dir: &Path) -> Vec<CheckResult> {
+    let mut env = HashMap::new();
+    for file in [".env", ".env.local"] {
+        let path = dir.join(file);
+        if path.is_file() {
+            let _ = load_env_file(&path, &mut env);
+        }
+    }
+
+    let mut results = Vec::new();
+    for var in PROBED_VARS {
+        let Some(url) = env.get(*var).filter(|url| !url.is_empty()) else {
+            continue;
+        };
+        results.push(probe_url(var, url, dir));
+    }
+    results
+}
+
+fn probe_url(var: &str, url: &str, dir: &Path) -> CheckResult {
+    // SQLite needs no server, only its file; `sqlite:data.db` and
+    // `sqlite:///abs/data.db` are both valid
+    if let Some((scheme, rest)) = url.split_once(':') {
+        let scheme = scheme.to_ascii_lowercase();
+        if scheme.starts_with("sqlite") || scheme == "file" {
+            return probe_sqlite_file(var, rest, dir);
+        }
+    }
+
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return CheckResult {
+            status: CheckStatus::Warn,
+            message: format!("{} is not a URL", var),
+            fix_hint: Some(format!("Expected scheme://host:port/... in {}", var)),
+        };
+    };
+    let scheme = scheme.to_ascii_lowercase();
+
+    let Some(addr) = host_port(&scheme, rest) else {
+        return CheckResult {
+            status: CheckStatus::Info,
+            message: format!(
+                "{} not probed (no host or default port for {})",
+                var, scheme
+            ),
+            fix_hint: None,
+        };
+    };
+
+    match connect(&addr) {
+        Ok(mut stream) => {
+            // TLS (`rediss://`) would need a handshake before PING
+            if scheme == "redis" && !redis_answers(&mut stream) {
+                return CheckResult {
+                    status: CheckStatus::Warn,
+                    message: format!(
+                        "{} at {} accepts connections but did not answer PING",
+                        var, addr
+                    ),
+                    fix_hint: Some(format!("Check that {} points at a Redis server", var)),
+                };
+            }
+            CheckResult {
+                status: CheckStatus::Ok,
+                message: format!("{} reachable ({} at {})", var, scheme, addr),
+                fix_hint: None,
+            }
+        }
+        Err(e) => CheckResult {
+            status: CheckStatus::Warn,
+            message: format!("{} unreachable: {} at {} ({})", var, scheme, addr, e),
+            fix_hint: Some(format!("Start the service or fix {} in .env", var)),
+        },
+    }
+}
+
+/// Check the file of a `sqlite:`/`file:` URL, given what follows the scheme
+fn probe_sqlite_file(var: &str, rest: &str, dir: &Path) -> CheckResult {
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    // "//data.db" is relative, "///srv/data.db" absolute
+    let path = path.strip_prefix("//").unwrap_or(path);
+    if path.is_empty() || path == ":memory:" || query.contains("mode=memory") {
+        return CheckResult {
+            status: CheckStatus::Info,
+            message: format!("{} not probed (in-memory database)", var),
+            fix_hint: None,
+        };
+    }
+
+    let path = if Path::new(path).is_absolute() {
+        Path::new(path).to_path_buf()
+    } else {
+        dir.join(path)
+    };
+    if path.exists() {
+        CheckResult {
+            status: CheckStatus::Ok,
+            message: format!("{} found ({})", var, path.display()),
+            fix_hint: None,
+        }
+    } else {
+        CheckResult {
+            status: CheckStatus::Warn,
+            message: format!("{} file missing: {}", var, path.display()),
+            fix_hint: Some("Create the database or fix the path in .env".to_string()),
+        }
+    }
+}
+
+/// `host:port` of a connection string after its `scheme://`, without the
+/// credentials, filling in the scheme's default port
+fn host_port(scheme: &str, rest: &str) -> Option<String> {
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    // Several hosts (MongoDB, Postgres failover): the first one will do
+    let authority = authority.split(',').next().unwrap_or(authority);
+    if authority.is_empty() {
+        return None;
+    }
+
+    let has_port = match authority.rfind(']') {
+        Some(bracket) => authority[bracket..].contains(':'),
+        None => authority.contains(':'),
+    };
+    if has_port {
+        return Some(authority.to_string());
+    }
+    let port = match scheme {
+        "postgres" | "postgresql" => 5432,
+        "mysql" | "mariadb" => 3306,
+        "redis" | "rediss" => 6379,
+        "mongodb" => 27017,
+        "amqp" => 5672,
+        "amqps" => 5671,
+        _ => return None,
+    };
+    Some(format!("{}:{}", authority, port))
+}
+
+fn connect(addr: &str) -> std::io::Result<TcpStream> {
+    let mut last = None;
+    for sock in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&sock, PROBE_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last = Some(e),
+        }
+    }
+    Err(last.unwrap_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses resolved")
+    }))
+}
+
+/// Whether a Redis server answers PING; an error reply such as NOAUTH
+/// still comes from Redis
+fn redis_answers(stream: &mut TcpStream) -> bool {
+    let _ = stream.set_read_timeout(Some(PROBE_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(PROBE_TIMEOUT));
+    if stream.write_all(b"PING\r\n").is_err() {
+        return false;
+    }
+    let mut reply = [0u8; 64];
+    match stream.read(&mut reply) {
+        Ok(n) if n > 0 => matches!(reply[0], b'+' | b'-'),
+        _ => false,
+    }
+}
+
+fn run_npm_install(dir: &Path) -> Result<usize, String> {
+    // Detect which package manager to use
+    let (cmd, args) = if dir.join("pnpm-lock.yaml").exists() {
+        ("pnpm", vec!["install"])
+    } else if dir.join("yarn.lock").exists() {
+        ("yarn", vec!["install"])
+    } else {
+        ("npm", vec!["install"])
+    };
+
+    let output = Command::new(cmd)
+        .args(&args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("{}: {}", cmd, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+
+    // Count installed packages
+    let node_modules = dir.join("node_modules");
+    let count = fs::read_dir(&node_modules)
+        .map(|entries| entries.filter_map(|e| e.ok()).count())
+        .unwrap_or(0);
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_project_type_nodejs() {
+        let dir = TempDir::new().unwrap();
+        File::create(dir.path().join("package.json")).unwrap();
+
+        assert_eq!(detect_project_type(dir.path()), ProjectType::NodeJs);
+    }
+
+    #[test]
+    fn test_detect_project_type_cargo() {
+        let dir = TempDir::new().unwrap();
+        File::create(dir.path().join("Cargo.toml")).unwrap();
+
+        assert_eq!(detect_project_type(dir.path()), ProjectType::Cargo);
+    }
+
+    #[test]
+    fn test_detect_project_type_generic() {
+        let dir = TempDir::new().unwrap();
+
+        assert_eq!(detect_project_type(dir.path()), ProjectType::Generic);
+    }
+
+    #[test]
+    fn test_check_env_files_with_template() {
+        let dir = TempDir::new().unwrap();
+        let mut template = File::create(dir.path().join(".env.example")).unwrap();
+        writeln!(template, "DATABASE_URL=postgres://localhost/mydb").unwrap();
+
+        let mut results = Vec::new();
+        check_env_files(dir.path(), false, &mut results);
+
+        // Should have a warning about missing .env
+        assert!(results.iter().any(|r| r.status == CheckStatus::Warn));
+        assert!(results.iter().any(|r| r.message.contains(".env missing")));
+    }
+
+    #[test]
+    fn test_check_env_files_with_fix() {
+        let dir = TempDir::new().unwrap();
+        let mut template = File::create(dir.path().join(".env.example")).unwrap();
+        writeln!(template, "DATABASE_URL=postgres://localhost/mydb").unwrap();
+
+        let mut results = Vec::new();
+        check_env_files(dir.path(), true, &mut results);
+
+        // Should have created .env
+        assert!(dir.path().join(".env").exists());
+        assert!(results.iter().any(|r| r.status == CheckStatus::Fixed));
+    }
+
+    #[test]
+    fn test_check_nodejs_project_missing_node_modules() {
+        let dir = TempDir::new().unwrap();
+        let mut package = File::create(dir.path().join("package.json")).unwrap();
+        writeln!(package, r#"{{"name": "test"}}"#).unwrap();
+
+        let mut results = Vec::new();
+        check_nodejs_project(dir.path(), false, &mut results);
+
+        assert!(results
+            .iter()
+            .any(|r| r.message.contains("package.json found")));
+        assert!(results
+            .iter()
+            .any(|r| r.message.contains("node_modules/ missing")));
+    }
+
+    #[test]
+    fn test_check_cargo_project() {
+        let dir = TempDir::new().unwrap();
+        let mut cargo_toml = File::create(dir.path().join("Cargo.toml")).unwrap();
+        writeln!(cargo_toml, r#"[package]"#).unwrap();
+        writeln!(cargo_toml, r#"name = "test""#).unwrap();
+
+        let mut results = Vec::new();
+        check_cargo_project(dir.path(), false, &mut results);
+
+        assert!(results
+            .iter()
+            .any(|r| r.message.contains("Cargo.toml found")));
+        assert!(results
+            .iter()
+            .any(|r| r.message.contains("Cargo.lock missing")));
+    }
+
+    #[test]
+    fn test_check_workspace_members() {
+        let dir = TempDir::new().unwrap();
+        let api = dir.path().join("crates/api");
+        std::fs::create_dir_all(api.join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        std::fs::write(api.join("Cargo.toml"), "[package]\nname = \"api\"\n").unwrap();
+        std::fs::write(api.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let mut results = Vec::new();
+        check_workspace_members(dir.path(), &mut results);
+        let messages: Vec<&str> = results.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Monorepo: 1 runnable members",
+                "Member api (crates/api, cargo bin api)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_host_port() {
+        assert_eq!(
+            host_port("postgres", "user:secret@db.local/app?sslmode=disable").as_deref(),
+            Some("db.local:5432")
+        );
+        assert_eq!(
+            host_port("redis", "localhost:6380/0").as_deref(),
+            Some("localhost:6380")
+        );
+        assert_eq!(
+            host_port("mysql", "[::1]/app").as_deref(),
+            Some("[::1]:3306")
+        );
+        assert_eq!(
+            host_port("mongodb", "a.local:27018,b.local:27018/app").as_deref(),
+            Some("a.local:27018")
+        );
+        assert_eq!(host_port("postgres", "/app?host=/var/run/postgresql"), None);
+        assert_eq!(host_port("custom", "svc.local"), None);
+    }
+
+    #[test]
+    fn test_probe_dependencies() {
+        use std::net::TcpListener;
+
+        let dir = TempDir::new().unwrap();
+        let redis = TcpListener::bind("127.0.0.1:0").unwrap();
+        let redis_port = redis.local_addr().unwrap().port();
+        let closed_port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        std::thread::spawn(move || {
+            let (mut conn, _) = redis.accept().unwrap();
+            let mut buf = [0u8; 64];
+            let _ = conn.read(&mut buf);
+            conn.write_all(b"+PONG\r\n").unwrap();
+        });
+
+        let mut env = File::create(dir.path().join(".env")).unwrap();
+        writeln!(
+            env,
+            "DATABASE_URL=postgres://app:pw@127.0.0.1:{}/app",
+            closed_port
+        )
+        .unwrap();
+        writeln!(env, "REDIS_URL=\"redis://127.0.0.1:{}/0\"", redis_port).unwrap();
+
+        let results = probe_dependencies(dir.path());
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].status, CheckStatus::Warn);
+        assert!(results[0].message.starts_with("DATABASE_URL unreachable"));
+        assert!(!results[0].message.contains("pw"));
+        assert_eq!(results[1].status, CheckStatus::Ok);
+        assert!(results[1].message.contains("REDIS_URL reachable"));
+
+        // SQLite URLs, with or without "//", name a file next to .env
+        File::create(dir.path().join("data.db")).unwrap();
+        for (url, status, message) in [
+            ("sqlite:data.db", CheckStatus::Ok, "DB found"),
+            ("sqlite://data.db?mode=rwc", CheckStatus::Ok, "DB found"),
+            ("file:missing.db", CheckStatus::Warn, "DB file missing"),
+            ("sqlite::memory:", CheckStatus::Info, "DB not probed"),
+            ("sqlite://:memory:", CheckStatus::Info, "DB not probed"),
+            (
+                "file:app?mode=memory&cache=shared",
+                CheckStatus::Info,
+                "DB not probed",
+            ),
+        ] {
+            let result = probe_url("DB", url, dir.path());
+            assert_eq!(result.status, status, "{}", url);
+            assert!(
+                result.message.starts_with(message),
+                "{}: {}",
+                url,
+                result.message
+            );
+        }
+        let absolute = format!("sqlite://{}", dir.path().join("data.db").display());
+        assert_eq!(
+            probe_url("DB", &absolute, dir.path()).status,
+            CheckStatus::Ok
+        );
+
+        // Nothing to probe without the variables
+        let empty = TempDir::new().unwrap();
+        assert!(probe_dependencies(empty.path()).is_empty());
+    }
+
+    #[test]
+    fn test_node_range_satisfied() {
+        let v20 = (20, 11, 1);
+        for (range, expected) in [
+            (">=18", true),
+            (">= 18.0.0", true),
+            (">20.11", false),
+            ("^20.2.0", true),
+            ("^18", false),
+            ("~20.11.0", true),
+            ("~20.10", false),
+            ("20.x", true),
+            ("20", true),
+            ("18.x || 20.x", true),
+            ("16 - 19", false),
+            ("16 - 20", true),
+            (">=16 <20", false),
+            ("<=20.11.1", true),
+            ("*", true),
+        ] {
+            assert_eq!(
+                node_range_satisfied(range, v20),
+                Some(expected),
+                "{}",
+                range
+            );
+        }
+        assert_eq!(node_range_satisfied("^0.2.3", (0, 3, 0)), Some(false));
+        assert_eq!(node_range_satisfied("latest", v20), None);
+
+        assert_eq!(node_install_target(">=18 <21"), "18");
+        assert_eq!(node_install_target("^20.2.0 || ^22"), "20");
+        assert_eq!(node_install_target("<16"), "node");
+    }
+
+    #[test]
+    fn test_check_rust_version() {
+        let dir = TempDir::new().unwrap();
+        let manifest = dir.path().join("Cargo.toml");
+
+        // No rust-version, nothing to check
+        fs::write(&manifest, "[package]\nname = \"t\"\n").unwrap();
+        let mut results = Vec::new();
+        check_rust_version(dir.path(), false, &mut results);
+        assert!(results.is_empty());
+
+        // The comparisons need a rustc to compare against
+        if tool_version(dir.path(), "rustc", &["--version"]).is_none() {
+            return;
+        }
+        fs::write(
+            &manifest,
+            "[package]\nname = \"t\"\nrust-version = \"1.0\"\n",
+        )
+        .unwrap();
+        let mut results = Vec::new();
+        check_rust_version(dir.path(), false, &mut results);
+        assert_eq!(results[0].status, CheckStatus::Ok, "{}", results[0].message);
+
+        fs::write(&manifest, "[workspace.package]\nrust-version = \"99.0\"\n").unwrap();
+        let mut results = Vec::new();
+        check_rust_version(dir.path(), false, &mut results);
+        assert_eq!(results[0].status, CheckStatus::Error);
+        assert!(results[0].message.contains("older than rust-version 99.0"));
+        assert!(!PreflightSummary::new(results).can_start());
+    }
+}
@@ -1,13 +1,14 @@
 //! Single-file Rust runner
 
 use async_trait::async_trait;
-use oxidepm_core::{AppSpec, Error, Result};
+use oxidepm_core::{AppSpec, BuildInfo, Error, Result};
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::time::Instant;
 use tokio::process::Command;
 use tracing::info;
 
-use crate::traits::{PrepareResult, Runner, RunningProcess};
+use crate::traits::{PrepareResult, ProgressSink, Runner, RunningProcess};
 
 /// Single-file Rust runner - compiles and runs .rs files
 pub struct RustRunner;
@@ -15,6 +16,15 @@ pub struct RustRunner;
 #[async_trait]
 impl Runner for RustRunner {
     async fn prepare(&self, spec: &AppSpec) -> Result<PrepareResult> {
+        self.prepare_with_progress(spec, &ProgressSink::none())
+            .await
+    }
+
+    async fn prepare_with_progress(
+        &self,
+        spec: &AppSpec,
+        progress: &ProgressSink,
+    ) -> Result<PrepareResult> {
         // Check if rustc is available
         let rustc_path = match which::which("rustc") {
             Ok(path) => path,
@@ -75,13 +85,13 @@ impl Runner for RustRunner {
             .arg("-o")
             .arg(&output_path)
             .arg("-O") // Optimize
-            .current_dir(&spec.cwd)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+            .current_dir(&spec.cwd);
 
-        let output = cmd.output().await.map_err(|e| {
-            Error::BuildFailed(format!("Failed to run rustc: {}", e))
-        })?;
+        let build_started = Instant::now();
+        let output = crate::output_with_progress(&mut cmd, progress)
+            .await
+            .map_err(|e| Error::BuildFailed(format!("Failed to run rustc: {}", e)))?;
+        let duration_ms = build_started.elapsed().as_millis() as u64;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -91,13 +101,21 @@ impl Runner for RustRunner {
             )));
         }
 
+        let build = BuildInfo {
+            profile: "release".to_string(),
+            binary_path: output_path.clone(),
+            duration_ms,
+            git_commit: crate::git_commit(&spec.cwd).await,
+            sha256: None,
+        };
         Ok(PrepareResult::success_with_binary(
             format!(
                 "Compiled {} successfully",
                 source_path.file_name().unwrap().to_string_lossy()
             ),
             output_path,
-        ))
+        )
+        .with_build(build))
     }
 
     async fn start(&self, spec: &AppSpec) -> Result<RunningProcess> {
@@ -130,6 +148,8 @@ impl Runner for RustRunner {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .kill_on_drop(false);
+        crate::limits::apply(&mut cmd, spec)?;
+        crate::sandbox::apply(&mut cmd, spec)?;
 
         let child = cmd.spawn().map_err(|e| {
             Error::ProcessStartFailed(format!(
@@ -147,23 +167,20 @@ impl Runner for RustRunner {
         Ok(RunningProcess::new(pid, child))
     }
 
-    fn command_string(&self, spec: &AppSpec) -> String {
-        let source_path = if std::path::Path::new(&spec.command).is_absolute() {
-            PathBuf::from(&spec.command)
-        } else {
-            spec.cwd.join(&spec.command)
-        };
-
-        let binary_name = source_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("app");
-
-        let binary_path = spec.cwd.join(".oxidepm/bin").join(binary_name);
+    fn argv(&self, spec: &AppSpec) -> Vec<String> {
+        let mut argv = vec![binary_path(spec).to_string_lossy().to_string()];
+        argv.extend(spec.args.iter().cloned());
+        argv
+    }
 
-        let mut parts = vec![binary_path.to_string_lossy().to_string()];
-        parts.extend(spec.args.clone());
-        parts.join(" ")
+    fn prepare_command(&self, spec: &AppSpec) -> Option<Vec<String>> {
+        Some(vec![
+            "rustc".to_string(),
+            source_path(spec).to_string_lossy().to_string(),
+            "-o".to_string(),
+            binary_path(spec).to_string_lossy().to_string(),
+            "-O".to_string(),
+        ])
     }
 
     fn mode_name(&self) -> &'static str {
@@ -171,6 +188,25 @@ impl Runner for RustRunner {
     }
 }
 
+/// Source file named by the app's command
+fn source_path(spec: &AppSpec) -> PathBuf {
+    if std::path::Path::new(&spec.command).is_absolute() {
+        PathBuf::from(&spec.command)
+    } else {
+        spec.cwd.join(&spec.command)
+    }
+}
+
+/// Where `prepare` puts the compiled binary
+fn binary_path(spec: &AppSpec) -> PathBuf {
+    let source_path = source_path(spec);
+    let binary_name = source_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("app");
+    spec.cwd.join(".oxidepm/bin").join(binary_name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,6 +214,32 @@ mod tests {
     use std::io::Write;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_argv_and_prepare_command() {
+        let spec = AppSpec::new(
+            "test".to_string(),
+            AppMode::Rust,
+            "src/app.rs".to_string(),
+            PathBuf::from("/srv"),
+        )
+        .with_args(vec!["--port".to_string(), "8080".to_string()]);
+
+        assert_eq!(
+            RustRunner.argv(&spec),
+            vec!["/srv/.oxidepm/bin/app", "--port", "8080"]
+        );
+        assert_eq!(
+            RustRunner.prepare_command(&spec).unwrap(),
+            vec![
+                "rustc",
+                "/srv/src/app.rs",
+                "-o",
+                "/srv/.oxidepm/bin/app",
+                "-O"
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_prepare_missing_file() {
         let dir = TempDir::new().unwrap();
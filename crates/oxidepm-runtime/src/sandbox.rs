@@ -0,0 +1,509 @@
+//! Namespace sandbox for app processes (Linux)
+//!
+//! Apps with a `sandbox` section start in their own user, mount and PID
+//! namespaces, plus an empty network namespace unless `network = true`. The
+//! app keeps no capabilities on the host: a root daemon drops it to `nobody`
+//! first, and the user namespace maps only the app's own uid, so it loses
+//! its namespace capabilities at exec.
+//!
+//! The namespaces are entered in the child between fork and exec. A PID
+//! namespace only applies to processes forked after it is entered, so the
+//! supervised PID stays behind as a shim that passes signals on to the app
+//! and exits the way it does; inside the namespace a second shim runs as
+//! PID 1, reaping orphans, and the app itself is PID 2.
+
+use oxidepm_core::{AppSpec, Result};
+use tokio::process::Command;
+
+/// Apply the spec's sandbox, if it has one, to a command about to be spawned
+pub fn apply(cmd: &mut Command, spec: &AppSpec) -> Result<()> {
+    match &spec.sandbox {
+        Some(sandbox) => imp::apply(cmd, sandbox, &spec.cwd),
+        None => Ok(()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use nix::errno::Errno;
+    use nix::libc;
+    use nix::mount::{mount, MsFlags};
+    use nix::sched::{unshare, CloneFlags};
+    use nix::sys::prctl;
+    use nix::sys::signal::{
+        kill, sigaction, sigprocmask, SaFlags, SigAction, SigHandler, SigSet, SigmaskHow, Signal,
+    };
+    use nix::sys::statvfs::{statvfs, FsFlags};
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{
+        chdir, fork, getgid, getpid, getuid, setgid, setgroups, setuid, ForkResult, Gid, Uid, User,
+    };
+    use oxidepm_core::{Error, Result, Sandbox};
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use tokio::process::Command;
+
+    /// Account a root daemon runs sandboxed apps as
+    const UNPRIVILEGED_USER: &str = "nobody";
+    /// Its ids when it is missing from the user database
+    const UNPRIVILEGED_ID: u32 = 65534;
+
+    /// Signals the shims pass on to the app
+    const FORWARDED: [Signal; 6] = [
+        Signal::SIGHUP,
+        Signal::SIGINT,
+        Signal::SIGQUIT,
+        Signal::SIGTERM,
+        Signal::SIGUSR1,
+        Signal::SIGUSR2,
+    ];
+
+    /// Everything the child needs, worked out before fork so the pre-exec
+    /// hook only makes syscalls
+    struct Plan {
+        namespaces: CloneFlags,
+        /// Unprivileged ids to switch to before unsharing (root daemon only)
+        run_as: Option<(Uid, Gid)>,
+        /// uid_map and gid_map contents: the app's own ids, nothing else
+        id_maps: (Vec<u8>, Vec<u8>),
+        private_tmp: bool,
+        /// Bind-mounted onto themselves before the read-only mounts, so they
+        /// keep their own writable mount underneath
+        writable: Vec<PathBuf>,
+        /// Paths to remount read-only, with the flags their filesystem is
+        /// mounted with (a remount inside a user namespace must keep them)
+        read_only: Vec<(PathBuf, MsFlags)>,
+        cwd: PathBuf,
+    }
+
+    pub fn apply(cmd: &mut Command, sandbox: &Sandbox, cwd: &Path) -> Result<()> {
+        let plan = Plan::new(sandbox, cwd)?;
+        // SAFETY: the hook only makes syscalls and writes pre-built buffers;
+        // it does not allocate or take locks
+        unsafe {
+            cmd.pre_exec(move || plan.enter().map_err(std::io::Error::from));
+        }
+        Ok(())
+    }
+
+    impl Plan {
+        fn new(sandbox: &Sandbox, cwd: &Path) -> Result<Self> {
+            let cwd = canonical(cwd)?;
+            if sandbox.private_tmp && cwd.starts_with("/tmp") {
+                return Err(Error::ProcessStartFailed(format!(
+                    "sandbox private_tmp would hide the working directory {}; set private_tmp = false or move the app out of /tmp",
+                    cwd.display()
+                )));
+            }
+
+            let mut namespaces =
+                CloneFlags::CLONE_NEWUSER | CloneFlags::CLONE_NEWNS | CloneFlags::CLONE_NEWPID;
+            if !sandbox.network {
+                namespaces |= CloneFlags::CLONE_NEWNET;
+            }
+            let run_as = getuid().is_root().then(unprivileged_ids);
+            let (uid, gid) = run_as.unwrap_or_else(|| (getuid(), getgid()));
+            let id_maps = (
+                format!("{0} {0} 1", uid).into_bytes(),
+                format!("{0} {0} 1", gid).into_bytes(),
+            );
+
+            let writable = sandbox
+                .writable
+                .iter()
+                .map(|path| canonical(&cwd.join(path)))
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut read_only = Vec::new();
+            if sandbox.read_only_cwd {
+                read_only.push(cwd.clone());
+            }
+            for path in &sandbox.read_only {
+                read_only.push(canonical(&cwd.join(path))?);
+            }
+            let read_only = read_only
+                .into_iter()
+                .map(|path| {
+                    let flags = locked_flags(&path)?;
+                    Ok((path, flags))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(Self {
+                namespaces,
+                run_as,
+                id_maps,
+                private_tmp: sandbox.private_tmp,
+                writable,
+                read_only,
+                cwd,
+            })
+        }
+
+        /// Runs in the child after fork; returns in the process that execs the app
+        fn enter(&self) -> nix::Result<()> {
+            if let Some((uid, gid)) = self.run_as {
+                setgroups(&[])?;
+                setgid(gid)?;
+                setuid(uid)?;
+                // Changing uid made the process undumpable, which leaves its
+                // /proc/self files owned by root and the id maps unwritable
+                prctl::set_dumpable(true)?;
+            }
+
+            unshare(self.namespaces)?;
+            let (uid_map, gid_map) = &self.id_maps;
+            write_proc("/proc/self/setgroups", b"deny")?;
+            write_proc("/proc/self/uid_map", uid_map)?;
+            write_proc("/proc/self/gid_map", gid_map)?;
+
+            // Keep our mounts from propagating back to the host
+            mount(
+                None::<&str>,
+                "/",
+                None::<&str>,
+                MsFlags::MS_REC | MsFlags::MS_PRIVATE,
+                None::<&str>,
+            )?;
+
+            if self.private_tmp {
+                mount(
+                    Some("tmpfs"),
+                    "/tmp",
+                    Some("tmpfs"),
+                    MsFlags::MS_NOSUID | MsFlags::MS_NODEV,
+                    Some("mode=1777"),
+                )?;
+            }
+            for path in &self.writable {
+                bind(path)?;
+            }
+            for (path, locked) in &self.read_only {
+                bind(path)?;
+                mount(
+                    None::<&str>,
+                    path,
+                    None::<&str>,
+                    MsFlags::MS_BIND | MsFlags::MS_REMOUNT | MsFlags::MS_RDONLY | *locked,
+                    None::<&str>,
+                )?;
+            }
+
+            // Only children join the PID namespace: the supervised process
+            // shims for its child, PID 1, which shims for the app as PID 2.
+            // The forwarding handler is installed before forking, as PID 1
+            // drops signals from outside that it has no handler for, and the
+            // signals stay blocked until each shim knows its child
+            let forwarded: SigSet = FORWARDED.into_iter().collect();
+            sigprocmask(SigmaskHow::SIG_BLOCK, Some(&forwarded), None)?;
+            set_handlers(SigHandler::Handler(forward_signal));
+            shim_for_child()?;
+            // PID 1 going away (when the outer shim is killed) ends the namespace
+            prctl::set_pdeathsig(Signal::SIGKILL)?;
+            shim_for_child()?;
+            mount(
+                Some("proc"),
+                "/proc",
+                Some("proc"),
+                MsFlags::MS_NOSUID | MsFlags::MS_NODEV | MsFlags::MS_NOEXEC,
+                None::<&str>,
+            )?;
+
+            // The working directory was entered before the new mounts existed
+            chdir(&self.cwd)?;
+            set_handlers(SigHandler::SigDfl);
+            sigprocmask(SigmaskHow::SIG_UNBLOCK, Some(&forwarded), None)
+        }
+    }
+
+    fn set_handlers(handler: SigHandler) {
+        let action = SigAction::new(handler, SaFlags::SA_RESTART, SigSet::empty());
+        for signal in FORWARDED {
+            // SAFETY: the only handler set here is `forward_signal`, which only calls kill
+            let _ = unsafe { sigaction(signal, &action) };
+        }
+    }
+
+    /// The child a shim passes signals on to; each process has its own copy
+    static SHIM_CHILD: AtomicI32 = AtomicI32::new(0);
+
+    extern "C" fn forward_signal(signal: libc::c_int) {
+        let child = SHIM_CHILD.load(Ordering::Relaxed);
+        if child > 0 {
+            // SAFETY: kill is async-signal-safe
+            unsafe { libc::kill(child, signal) };
+        }
+    }
+
+    /// Fork: the child returns, with the forwarded signals still blocked, and
+    /// the parent stays behind passing them on to it and reaping, until it
+    /// exits the way the child did
+    fn shim_for_child() -> nix::Result<()> {
+        // SAFETY: both sides only make syscalls until the child execs
+        let child = match unsafe { fork() }? {
+            ForkResult::Child => return Ok(()),
+            ForkResult::Parent { child } => child,
+        };
+        SHIM_CHILD.store(child.as_raw(), Ordering::Relaxed);
+        close_inherited_fds();
+        let forwarded: SigSet = FORWARDED.into_iter().collect();
+        let _ = sigprocmask(SigmaskHow::SIG_UNBLOCK, Some(&forwarded), None);
+
+        loop {
+            match waitpid(None, None) {
+                Ok(WaitStatus::Exited(pid, code)) if pid == child => exit(code),
+                Ok(WaitStatus::Signaled(pid, signal, _)) if pid == child => die_by(signal),
+                Ok(_) | Err(Errno::EINTR) => continue,
+                Err(_) => exit(1),
+            }
+        }
+    }
+
+    /// Close everything but stdio in a shim. Among the inherited descriptors
+    /// is the pipe the spawning side reads until exec closes it, and a shim
+    /// never execs.
+    fn close_inherited_fds() {
+        // SAFETY: close_range and close only close descriptors
+        unsafe {
+            if libc::syscall(libc::SYS_close_range, 3u32, u32::MAX, 0u32) == 0 {
+                return;
+            }
+            // Kernels before 5.9
+            let max = match libc::sysconf(libc::_SC_OPEN_MAX) {
+                max if max > 3 => max as libc::c_int,
+                _ => 1024,
+            };
+            for fd in 3..max {
+                libc::close(fd);
+            }
+        }
+    }
+
+    /// End with the child's signal; PID 1 cannot signal itself, so it falls
+    /// back to the shell's `128 + signal` exit code
+    fn die_by(signal: Signal) -> ! {
+        let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+        // SAFETY: restoring the default disposition
+        let _ = unsafe { sigaction(signal, &default) };
+        let _ = kill(getpid(), signal);
+        exit(128 + signal as i32)
+    }
+
+    fn exit(code: i32) -> ! {
+        // SAFETY: ends the forked shim without running the parent's destructors
+        unsafe { libc::_exit(code) }
+    }
+
+    pub(super) fn unprivileged_ids() -> (Uid, Gid) {
+        match User::from_name(UNPRIVILEGED_USER) {
+            Ok(Some(user)) => (user.uid, user.gid),
+            _ => (
+                Uid::from_raw(UNPRIVILEGED_ID),
+                Gid::from_raw(UNPRIVILEGED_ID),
+            ),
+        }
+    }
+
+    fn bind(path: &Path) -> nix::Result<()> {
+        mount(
+            Some(path),
+            path,
+            None::<&str>,
+            MsFlags::MS_BIND | MsFlags::MS_REC,
+            None::<&str>,
+        )
+    }
+
+    fn write_proc(path: &str, contents: &[u8]) -> nix::Result<()> {
+        let to_errno =
+            |e: std::io::Error| nix::errno::Errno::from_raw(e.raw_os_error().unwrap_or(0));
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|mut file| file.write_all(contents))
+            .map_err(to_errno)
+    }
+
+    fn canonical(path: &Path) -> Result<PathBuf> {
+        path.canonicalize().map_err(|e| {
+            Error::ProcessStartFailed(format!("sandbox path {}: {}", path.display(), e))
+        })
+    }
+
+    /// Mount flags of the filesystem holding `path` that a read-only remount must repeat
+    fn locked_flags(path: &Path) -> Result<MsFlags> {
+        let stat = statvfs(path).map_err(|e| {
+            Error::ProcessStartFailed(format!("sandbox path {}: {}", path.display(), e))
+        })?;
+        let fs_flags = stat.flags();
+        let mut flags = MsFlags::empty();
+        for (fs_flag, ms_flag) in [
+            (FsFlags::ST_NOSUID, MsFlags::MS_NOSUID),
+            (FsFlags::ST_NODEV, MsFlags::MS_NODEV),
+            (FsFlags::ST_NOEXEC, MsFlags::MS_NOEXEC),
+            (FsFlags::ST_NOATIME, MsFlags::MS_NOATIME),
+            (FsFlags::ST_NODIRATIME, MsFlags::MS_NODIRATIME),
+            (FsFlags::ST_RELATIME, MsFlags::MS_RELATIME),
+        ] {
+            if fs_flags.contains(fs_flag) {
+                flags |= ms_flag;
+            }
+        }
+        Ok(flags)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    use oxidepm_core::{Error, Result, Sandbox};
+    use std::path::Path;
+    use tokio::process::Command;
+
+    pub fn apply(_cmd: &mut Command, _sandbox: &Sandbox, _cwd: &Path) -> Result<()> {
+        Err(Error::ProcessStartFailed(
+            "sandbox is only supported on Linux".to_string(),
+        ))
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::{getuid, Pid};
+    use oxidepm_core::{AppMode, Sandbox};
+    use std::path::{Path, PathBuf};
+    use std::process::Stdio;
+    use tempfile::tempdir;
+
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::ExitStatus;
+
+    async fn run_sandboxed(spec: &AppSpec, script: &str) -> (ExitStatus, String) {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", script])
+            .current_dir(&spec.cwd)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        apply(&mut cmd, spec).unwrap();
+        let output = cmd.output().await.unwrap();
+        (
+            output.status,
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+        )
+    }
+
+    /// A project directory the app can use even when a root daemon drops it to `nobody`
+    fn project_dir(dir: &Path) -> PathBuf {
+        let project = dir.canonicalize().unwrap();
+        std::fs::set_permissions(&project, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::create_dir(project.join("out")).unwrap();
+        std::fs::set_permissions(project.join("out"), std::fs::Permissions::from_mode(0o777))
+            .unwrap();
+        project
+    }
+
+    fn spec_in(project: &Path) -> AppSpec {
+        // tempdir() lives under /tmp, which a private /tmp would hide
+        let sandbox = Sandbox {
+            private_tmp: false,
+            writable: vec!["out".into()],
+            ..Default::default()
+        };
+        AppSpec::new(
+            "sandboxed".to_string(),
+            AppMode::Cmd,
+            "sh".to_string(),
+            project.to_path_buf(),
+        )
+        .with_sandbox(sandbox)
+    }
+
+    #[tokio::test]
+    #[ignore = "needs user namespaces, which restricted containers and CI runners may not allow"]
+    async fn test_sandbox_mounts() {
+        let dir = tempdir().unwrap();
+        let project = project_dir(dir.path());
+
+        let script =
+            "touch app.txt 2>/dev/null && echo cwd-writable; touch out/ok && echo out-writable";
+        let (status, stdout) = run_sandboxed(&spec_in(&project), script).await;
+        assert!(status.success());
+        assert_eq!(stdout.trim(), "out-writable");
+        assert!(project.join("out/ok").exists());
+        assert!(!project.join("app.txt").exists());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs user namespaces, which restricted containers and CI runners may not allow"]
+    async fn test_sandbox_isolates_processes_and_privileges() {
+        let dir = tempdir().unwrap();
+        let project = project_dir(dir.path());
+
+        let script = "echo pid=$$; echo uid=$(id -u); grep CapEff /proc/self/status; ls /proc | grep -c '^[0-9]'; exit 3";
+        let (status, stdout) = run_sandboxed(&spec_in(&project), script).await;
+        let lines: Vec<&str> = stdout.lines().collect();
+        // The app is PID 2 under the namespace's init, and sees only its own processes
+        assert_eq!(lines[0], "pid=2");
+        let uid = if getuid().is_root() {
+            imp::unprivileged_ids().0
+        } else {
+            getuid()
+        };
+        assert_eq!(lines[1], format!("uid={}", uid));
+        assert!(lines[2].ends_with("0000000000000000"), "{}", lines[2]);
+        assert!(
+            lines[3].parse::<u32>().unwrap() <= 4,
+            "{} processes visible",
+            lines[3]
+        );
+        // The shims exit with the app's code
+        assert_eq!(status.code(), Some(3));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs user namespaces, which restricted containers and CI runners may not allow"]
+    async fn test_sandbox_forwards_signals() {
+        let dir = tempdir().unwrap();
+        let project = project_dir(dir.path());
+
+        let mut cmd = Command::new("sh");
+        cmd.args([
+            "-c",
+            "trap 'echo got-term; exit 0' TERM; echo ready; while :; do sleep 0.1; done",
+        ])
+        .current_dir(&project)
+        .stdout(Stdio::piped());
+        apply(&mut cmd, &spec_in(&project)).unwrap();
+        let mut child = cmd.spawn().unwrap();
+        let mut stdout = tokio::io::BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        tokio::io::AsyncBufReadExt::read_line(&mut stdout, &mut line)
+            .await
+            .unwrap();
+        assert_eq!(line.trim(), "ready");
+
+        kill(Pid::from_raw(child.id().unwrap() as i32), Signal::SIGTERM).unwrap();
+        line.clear();
+        tokio::io::AsyncBufReadExt::read_line(&mut stdout, &mut line)
+            .await
+            .unwrap();
+        assert_eq!(line.trim(), "got-term");
+        assert_eq!(child.wait().await.unwrap().code(), Some(0));
+    }
+
+    #[test]
+    fn test_private_tmp_rejects_cwd_under_tmp() {
+        let spec = AppSpec::new(
+            "sandboxed".to_string(),
+            AppMode::Cmd,
+            "sh".to_string(),
+            "/tmp".into(),
+        )
+        .with_sandbox(Sandbox::default());
+        let mut cmd = Command::new("sh");
+        let err = apply(&mut cmd, &spec).unwrap_err();
+        assert!(err.to_string().contains("private_tmp"));
+    }
+}
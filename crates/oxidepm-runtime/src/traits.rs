@@ -1,8 +1,9 @@
 //! Runner trait and common types
 
 use async_trait::async_trait;
-use oxidepm_core::{AppSpec, Result};
+use oxidepm_core::{AppSpec, BuildInfo, Result};
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::process::Child;
 
 /// Result of the prepare phase (build/validate)
@@ -11,6 +12,8 @@ pub struct PrepareResult {
     pub success: bool,
     pub output: String,
     pub binary_path: Option<PathBuf>,
+    /// Set when prepare compiled the binary
+    pub build: Option<BuildInfo>,
 }
 
 impl PrepareResult {
@@ -19,6 +22,7 @@ impl PrepareResult {
             success: true,
             output: output.into(),
             binary_path: None,
+            build: None,
         }
     }
 
@@ -27,14 +31,67 @@ impl PrepareResult {
             success: true,
             output: output.into(),
             binary_path: Some(path),
+            build: None,
         }
     }
 
+    pub fn with_build(mut self, build: BuildInfo) -> Self {
+        self.build = Some(build);
+        self
+    }
+
     pub fn failure<S: Into<String>>(output: S) -> Self {
         Self {
             success: false,
             output: output.into(),
             binary_path: None,
+            build: None,
+        }
+    }
+}
+
+type ProgressCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
+type StepCallback = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Receives output lines while a runner prepares an app, e.g. the
+/// `Compiling ...` lines of a cargo build, and how far a request over
+/// several apps has got
+#[derive(Clone, Default)]
+pub struct ProgressSink {
+    callback: Option<ProgressCallback>,
+    steps: Option<StepCallback>,
+}
+
+impl ProgressSink {
+    /// A sink that drops every line
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn new(callback: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Some(Arc::new(callback)),
+            steps: None,
+        }
+    }
+
+    /// Also receive `(done, total)` as the apps of a request finish
+    pub fn with_steps(mut self, steps: impl Fn(usize, usize) + Send + Sync + 'static) -> Self {
+        self.steps = Some(Arc::new(steps));
+        self
+    }
+
+    pub fn send(&self, line: &str) {
+        if let Some(callback) = &self.callback {
+            callback(line);
+        }
+    }
+
+    /// Report that `done` of `total` steps have finished
+    pub fn step(&self, done: usize, total: usize) {
+        if let Some(steps) = &self.steps {
+            steps(done, total);
         }
     }
 }
@@ -57,11 +114,31 @@ pub trait Runner: Send + Sync {
     /// Prepare the process (build for Rust, validate for Node)
     async fn prepare(&self, spec: &AppSpec) -> Result<PrepareResult>;
 
+    /// Like `prepare`, streaming build output to `progress` as it happens.
+    /// Runners that do not build anything just call `prepare`.
+    async fn prepare_with_progress(
+        &self,
+        spec: &AppSpec,
+        _progress: &ProgressSink,
+    ) -> Result<PrepareResult> {
+        self.prepare(spec).await
+    }
+
     /// Start the process and return the child handle
     async fn start(&self, spec: &AppSpec) -> Result<RunningProcess>;
 
+    /// Exact argv `start` executes: the program followed by its arguments
+    fn argv(&self, spec: &AppSpec) -> Vec<String>;
+
+    /// Build command `prepare` runs, for modes that compile
+    fn prepare_command(&self, _spec: &AppSpec) -> Option<Vec<String>> {
+        None
+    }
+
     /// Get the command that will be executed (for display)
-    fn command_string(&self, spec: &AppSpec) -> String;
+    fn command_string(&self, spec: &AppSpec) -> String {
+        self.argv(spec).join(" ")
+    }
 
     /// Get the mode name
     fn mode_name(&self) -> &'static str;
@@ -0,0 +1,444 @@
+//! Package lookup in pnpm/yarn/npm workspace monorepos, and the members of
+//! cargo and npm workspaces that can run as apps of their own
+
+use oxidepm_core::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// A package inside a workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspacePackage {
+    /// `name` from the package's package.json
+    pub name: String,
+    /// Directory holding the package's package.json
+    pub dir: PathBuf,
+}
+
+/// A monorepo member that can run as an app of its own
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnableMember {
+    /// App name: the binary, or the last segment of the package name (the
+    /// whole package name, `acme-web`, when that is taken by another member)
+    pub name: String,
+    /// Directory of the member
+    pub dir: PathBuf,
+    pub kind: MemberKind,
+}
+
+/// How a runnable member is started from the monorepo root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemberKind {
+    /// A binary of a cargo workspace member, built from the root
+    CargoBin(String),
+    /// A workspace package with a `start` script, run through the package manager
+    NpmPackage(String),
+}
+
+impl MemberKind {
+    pub fn describe(&self) -> String {
+        match self {
+            MemberKind::CargoBin(bin) => format!("cargo bin {}", bin),
+            MemberKind::NpmPackage(package) => format!("package {}", package),
+        }
+    }
+}
+
+/// Members of the cargo workspace and the npm workspaces rooted at `root`
+/// that can run as apps: cargo members with a binary target, packages with a
+/// `start` script. Empty when `root` is not a monorepo root. Names can still
+/// repeat when two cargo members run binaries of the same name.
+pub fn runnable_members(root: &Path) -> Vec<RunnableMember> {
+    let mut members: Vec<RunnableMember> = cargo_members(root)
+        .into_iter()
+        .filter_map(|dir| {
+            let bin = cargo_bin(&dir)?;
+            Some(RunnableMember {
+                name: bin.clone(),
+                dir,
+                kind: MemberKind::CargoBin(bin),
+            })
+        })
+        .collect();
+
+    for package in list_packages(root).unwrap_or_default() {
+        if !has_start_script(&package.dir) {
+            continue;
+        }
+        // "@acme/web" -> "web"
+        let name = package.name.rsplit('/').next().unwrap_or(&package.name);
+        members.push(RunnableMember {
+            name: name.to_string(),
+            dir: package.dir,
+            kind: MemberKind::NpmPackage(package.name),
+        });
+    }
+
+    // A package sharing its short name with another member goes by its full
+    // name instead: "@acme/web" -> "acme-web"
+    let taken = |name: &str, members: &[RunnableMember]| {
+        members.iter().filter(|member| member.name == name).count() > 1
+    };
+    let renamed: Vec<Option<String>> = members
+        .iter()
+        .map(|member| match &member.kind {
+            MemberKind::NpmPackage(package) if taken(&member.name, &members) => {
+                Some(package.trim_start_matches('@').replace('/', "-"))
+            }
+            _ => None,
+        })
+        .collect();
+    for (member, name) in members.iter_mut().zip(renamed) {
+        if let Some(name) = name {
+            member.name = name;
+        }
+    }
+    members
+}
+
+/// Member directories of the cargo workspace declared in `root`/Cargo.toml
+/// (`members` globs minus `exclude`), including the root package if there
+/// is one; sorted by directory
+fn cargo_members(root: &Path) -> Vec<PathBuf> {
+    let Some(manifest) = read_cargo_manifest(root) else {
+        return Vec::new();
+    };
+    let Some(workspace) = manifest.get("workspace") else {
+        return Vec::new();
+    };
+    let globs = |key: &str| {
+        string_list(workspace.get(key).and_then(|v| v.as_array()), |v| {
+            v.as_str()
+        })
+    };
+    let excluded: Vec<PathBuf> = globs("exclude").iter().map(|e| root.join(e)).collect();
+
+    let mut members = Vec::new();
+    if manifest.get("package").is_some() {
+        members.push(root.to_path_buf());
+    }
+    for pattern in globs("members") {
+        let full = format!(
+            "{}/{}",
+            glob::Pattern::escape(&root.to_string_lossy()),
+            pattern.trim_end_matches('/')
+        );
+        let Ok(paths) = glob::glob(&full) else {
+            continue;
+        };
+        for dir in paths.filter_map(|p| p.ok()) {
+            if dir.join("Cargo.toml").is_file()
+                && !excluded.contains(&dir)
+                && !members.contains(&dir)
+            {
+                members.push(dir);
+            }
+        }
+    }
+    members.sort();
+    members
+}
+
+fn read_cargo_manifest(dir: &Path) -> Option<toml::Table> {
+    let content = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    content.parse().ok()
+}
+
+/// Binary a cargo member runs: its `default-run`, the package's own
+/// `src/main.rs`, else its first `[[bin]]`
+fn cargo_bin(dir: &Path) -> Option<String> {
+    let manifest = read_cargo_manifest(dir)?;
+    let package = manifest.get("package")?;
+    if let Some(default_run) = package.get("default-run").and_then(|d| d.as_str()) {
+        return Some(default_run.to_string());
+    }
+    let name = package.get("name").and_then(|n| n.as_str());
+    if let Some(name) = name.filter(|_| dir.join("src/main.rs").is_file()) {
+        return Some(name.to_string());
+    }
+    manifest
+        .get("bin")
+        .and_then(|b| b.as_array())
+        .and_then(|bins| bins.first())
+        .and_then(|bin| bin.get("name"))
+        .and_then(|n| n.as_str())
+        .map(String::from)
+}
+
+fn has_start_script(dir: &Path) -> bool {
+    std::fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .is_some_and(|json| json["scripts"]["start"].is_string())
+}
+
+/// Find a workspace package of the monorepo rooted at `root`, by package
+/// name (`@acme/web`), directory relative to the root (`apps/web`), or
+/// directory name (`web`)
+pub fn find_package(root: &Path, selector: &str) -> Result<WorkspacePackage> {
+    let packages = list_packages(root)?;
+    let selector_path = Path::new(selector.trim_end_matches('/'));
+
+    let found = packages
+        .iter()
+        .find(|package| package.name == selector)
+        .or_else(|| {
+            packages.iter().find(|package| {
+                package
+                    .dir
+                    .strip_prefix(root)
+                    .is_ok_and(|rel| rel == selector_path)
+            })
+        })
+        .or_else(|| {
+            packages
+                .iter()
+                .find(|package| package.dir.file_name().is_some_and(|name| name == selector))
+        });
+
+    found.cloned().ok_or_else(|| {
+        let available: Vec<&str> = packages
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect();
+        Error::config(format!(
+            "Workspace package '{}' not found in {} (available: {})",
+            selector,
+            root.display(),
+            available.join(", ")
+        ))
+    })
+}
+
+/// All packages matched by the workspace globs, sorted by directory
+pub fn list_packages(root: &Path) -> Result<Vec<WorkspacePackage>> {
+    let globs = workspace_globs(root)?;
+    if globs.is_empty() {
+        return Err(Error::config(format!(
+            "No workspaces declared in {} (expected pnpm-workspace.yaml or a \"workspaces\" field in package.json)",
+            root.display()
+        )));
+    }
+
+    let (excluded, included): (Vec<&String>, Vec<&String>) =
+        globs.iter().partition(|g| g.starts_with('!'));
+    let excluded: Vec<glob::Pattern> = excluded
+        .iter()
+        .filter_map(|g| glob::Pattern::new(&g[1..]).ok())
+        .collect();
+
+    let mut packages = Vec::new();
+    for pattern in included {
+        let full = format!(
+            "{}/{}",
+            glob::Pattern::escape(&root.to_string_lossy()),
+            pattern.trim_end_matches('/')
+        );
+        let Ok(paths) = glob::glob(&full) else {
+            continue;
+        };
+        for dir in paths.filter_map(|p| p.ok()) {
+            let relative = dir.strip_prefix(root).unwrap_or(&dir);
+            if excluded.iter().any(|ex| ex.matches_path(relative)) {
+                continue;
+            }
+            let Some(name) = package_name(&dir) else {
+                continue;
+            };
+            if !packages.iter().any(|p: &WorkspacePackage| p.dir == dir) {
+                packages.push(WorkspacePackage { name, dir });
+            }
+        }
+    }
+
+    packages.sort_by(|a, b| a.dir.cmp(&b.dir));
+    Ok(packages)
+}
+
+/// Package globs from pnpm-workspace.yaml, else package.json `workspaces`
+fn workspace_globs(root: &Path) -> Result<Vec<String>> {
+    let pnpm_workspace = root.join("pnpm-workspace.yaml");
+    if pnpm_workspace.exists() {
+        let content = std::fs::read_to_string(&pnpm_workspace)?;
+        let yaml: serde_yaml::Value = serde_yaml::from_str(&content)
+            .map_err(|e| Error::config(format!("Invalid pnpm-workspace.yaml: {}", e)))?;
+        return Ok(string_list(
+            yaml.get("packages").and_then(|p| p.as_sequence()),
+            |v| v.as_str(),
+        ));
+    }
+
+    let package_json = root.join("package.json");
+    if !package_json.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&package_json)?;
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| Error::config(format!("Invalid package.json: {}", e)))?;
+    // Either ["packages/*"] or { "packages": ["packages/*"] } (yarn classic)
+    let workspaces = json.get("workspaces");
+    let list = workspaces.and_then(|w| w.as_array()).or_else(|| {
+        workspaces
+            .and_then(|w| w.get("packages"))
+            .and_then(|p| p.as_array())
+    });
+    Ok(string_list(list, |v| v.as_str()))
+}
+
+fn string_list<T>(items: Option<&Vec<T>>, as_str: impl Fn(&T) -> Option<&str>) -> Vec<String> {
+    items
+        .into_iter()
+        .flatten()
+        .filter_map(as_str)
+        .map(String::from)
+        .collect()
+}
+
+/// `name` from the package.json in `dir`, if it has one
+fn package_name(dir: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(dir.join("package.json")).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    json.get("name")?.as_str().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn add_package(root: &Path, dir: &str, name: &str) {
+        let dir = root.join(dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("package.json"),
+            format!(r#"{{"name": "{}", "scripts": {{"dev": "vite"}}}}"#, name),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_find_package_pnpm() {
+        let root = TempDir::new().unwrap();
+        let root = root.path();
+        fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages:\n  - 'apps/*'\n  - 'packages/*'\n  - '!packages/legacy'\n",
+        )
+        .unwrap();
+        add_package(root, "apps/web", "@acme/web");
+        add_package(root, "packages/ui", "@acme/ui");
+        add_package(root, "packages/legacy", "@acme/legacy");
+
+        let names: Vec<String> = list_packages(root)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["@acme/web", "@acme/ui"]);
+
+        let by_name = find_package(root, "@acme/web").unwrap();
+        assert_eq!(by_name.dir, root.join("apps/web"));
+        assert_eq!(find_package(root, "apps/web").unwrap(), by_name);
+        assert_eq!(find_package(root, "web").unwrap(), by_name);
+
+        let err = find_package(root, "@acme/legacy").unwrap_err().to_string();
+        assert!(err.contains("available: @acme/web, @acme/ui"), "{}", err);
+    }
+
+    #[test]
+    fn test_find_package_json_workspaces() {
+        let root = TempDir::new().unwrap();
+        let root = root.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{"private": true, "workspaces": {"packages": ["services/*"]}}"#,
+        )
+        .unwrap();
+        add_package(root, "services/api", "api");
+
+        assert_eq!(
+            find_package(root, "api").unwrap().dir,
+            root.join("services/api")
+        );
+    }
+
+    #[test]
+    fn test_no_workspaces() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("package.json"), r#"{"name": "single"}"#).unwrap();
+
+        let err = find_package(root.path(), "api").unwrap_err().to_string();
+        assert!(err.contains("No workspaces declared"), "{}", err);
+    }
+
+    #[test]
+    fn test_runnable_members() {
+        let root = TempDir::new().unwrap();
+        let root = root.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/old\"]\n",
+        )
+        .unwrap();
+        let add_crate = |dir: &str, manifest: &str, main: bool| {
+            let dir = root.join(dir);
+            fs::create_dir_all(dir.join("src")).unwrap();
+            fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+            if main {
+                fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+            }
+        };
+        add_crate("crates/api", "[package]\nname = \"api\"\n", true);
+        add_crate("crates/core", "[package]\nname = \"core\"\n", false);
+        add_crate(
+            "crates/tools",
+            "[package]\nname = \"tools\"\n\n[[bin]]\nname = \"migrate\"\npath = \"src/migrate.rs\"\n",
+            false,
+        );
+        add_crate("crates/old", "[package]\nname = \"old\"\n", true);
+
+        fs::write(
+            root.join("package.json"),
+            r#"{"private": true, "workspaces": ["apps/*"]}"#,
+        )
+        .unwrap();
+        add_package(root, "apps/docs", "@acme/docs");
+        let web = root.join("apps/web");
+        fs::create_dir_all(&web).unwrap();
+        fs::write(
+            web.join("package.json"),
+            r#"{"name": "@acme/web", "scripts": {"start": "next start"}}"#,
+        )
+        .unwrap();
+
+        let members = runnable_members(root);
+        let found: Vec<(&str, MemberKind)> = members
+            .iter()
+            .map(|m| (m.name.as_str(), m.kind.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("api", MemberKind::CargoBin("api".into())),
+                ("migrate", MemberKind::CargoBin("migrate".into())),
+                ("web", MemberKind::NpmPackage("@acme/web".into())),
+            ]
+        );
+        assert_eq!(members[2].dir, web);
+
+        // A package named like a cargo binary falls back to its full name
+        add_crate("crates/web", "[package]\nname = \"web\"\n", true);
+        let names: Vec<String> = runnable_members(root).into_iter().map(|m| m.name).collect();
+        assert_eq!(names, vec!["api", "migrate", "web", "acme-web"]);
+
+        // A single crate is no monorepo
+        let single = TempDir::new().unwrap();
+        add_crate_at(single.path());
+        assert!(runnable_members(single.path()).is_empty());
+    }
+
+    fn add_crate_at(dir: &Path) {
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"single\"\n").unwrap();
+        fs::write(dir.join("src/main.rs"), "fn main() {}").unwrap();
+    }
+}
@@ -17,10 +17,124 @@ use ratatui::{
     widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Table, Tabs},
     Frame, Terminal,
 };
+use std::cmp::Reverse;
+use std::collections::HashSet;
 use std::io;
 use std::path::PathBuf;
 use std::time::Duration;
 
+/// Sort order for the process list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    #[default]
+    Id,
+    Cpu,
+    Memory,
+    Uptime,
+    Restarts,
+}
+
+impl SortKey {
+    /// Next sort key in the cycle
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Id => SortKey::Cpu,
+            SortKey::Cpu => SortKey::Memory,
+            SortKey::Memory => SortKey::Uptime,
+            SortKey::Uptime => SortKey::Restarts,
+            SortKey::Restarts => SortKey::Id,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortKey::Id => "id",
+            SortKey::Cpu => "cpu",
+            SortKey::Memory => "mem",
+            SortKey::Uptime => "uptime",
+            SortKey::Restarts => "restarts",
+        }
+    }
+
+    /// Sort processes in place (metrics descending, id ascending)
+    fn sort(&self, apps: &mut [&AppInfo]) {
+        match self {
+            SortKey::Id => apps.sort_by_key(|a| a.spec.id),
+            SortKey::Cpu => apps.sort_by(|a, b| {
+                b.state
+                    .cpu_percent
+                    .partial_cmp(&a.state.cpu_percent)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }),
+            SortKey::Memory => apps.sort_by_key(|a| Reverse(a.state.memory_bytes)),
+            SortKey::Uptime => apps.sort_by_key(|a| Reverse(a.state.uptime_secs)),
+            SortKey::Restarts => apps.sort_by_key(|a| Reverse(a.state.restarts)),
+        }
+    }
+}
+
+/// Case-insensitive fuzzy (subsequence) match
+fn fuzzy_match(haystack: &str, needle: &str) -> bool {
+    let mut haystack = haystack.chars().flat_map(char::to_lowercase);
+    needle
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| haystack.any(|h| h == c))
+}
+
+/// Check if a process matches the filter on name, tag or status
+fn matches_filter(info: &AppInfo, filter: &str) -> bool {
+    filter.is_empty()
+        || fuzzy_match(&info.spec.name, filter)
+        || info.spec.tags.iter().any(|t| fuzzy_match(t, filter))
+        || fuzzy_match(info.state.status.as_str(), filter)
+}
+
+/// Cluster base name for an instance ("api-3" -> "api"), None for standalone apps
+fn cluster_name(info: &AppInfo) -> Option<&str> {
+    let instance_id = info.state.instance_id?;
+    info.spec.name.strip_suffix(&format!("-{}", instance_id))
+}
+
+/// A row in the process list
+enum ListRow<'a> {
+    /// Standalone app
+    App(&'a AppInfo),
+    /// Cluster parent row aggregating its instances
+    Cluster {
+        name: String,
+        parent: Option<&'a AppInfo>,
+        instances: Vec<&'a AppInfo>,
+        expanded: bool,
+    },
+    /// Cluster instance shown under an expanded parent
+    Instance(&'a AppInfo),
+}
+
+impl<'a> ListRow<'a> {
+    /// App to use for details/logs when this row is selected
+    fn app(&self) -> Option<&'a AppInfo> {
+        match self {
+            ListRow::App(info) | ListRow::Instance(info) => Some(info),
+            ListRow::Cluster {
+                parent, instances, ..
+            } => parent.or_else(|| instances.first().copied()),
+        }
+    }
+
+    /// App ids targeted by stop/restart on this row
+    fn target_ids(&self) -> Vec<u32> {
+        match self {
+            ListRow::App(info) | ListRow::Instance(info) => vec![info.spec.id],
+            ListRow::Cluster {
+                parent: Some(parent),
+                ..
+            } => vec![parent.spec.id],
+            ListRow::Cluster { instances, .. } => instances.iter().map(|i| i.spec.id).collect(),
+        }
+    }
+}
+
 /// TUI Application state
 pub struct App {
     client: IpcClient,
@@ -30,32 +144,173 @@ pub struct App {
     logs: Vec<String>,
     should_quit: bool,
     last_error: Option<String>,
+    filter: String,
+    filter_editing: bool,
+    sort_key: SortKey,
+    supervision_paused: bool,
+    expanded_clusters: HashSet<String>,
 }
 
 impl App {
     pub fn new(socket_path: PathBuf) -> Self {
         Self {
-            client: IpcClient::new(socket_path),
+            client: IpcClient::new(socket_path)
+                .with_client_name("tui")
+                .with_connection_reuse(),
             processes: Vec::new(),
             selected_index: 0,
             tab_index: 0,
             logs: Vec::new(),
             should_quit: false,
             last_error: None,
+            filter: String::new(),
+            filter_editing: false,
+            sort_key: SortKey::default(),
+            supervision_paused: false,
+            expanded_clusters: HashSet::new(),
         }
     }
 
+    /// Processes after applying the filter and sort order
+    fn visible(&self) -> Vec<&AppInfo> {
+        let mut apps: Vec<&AppInfo> = self
+            .processes
+            .iter()
+            .filter(|info| matches_filter(info, &self.filter))
+            .collect();
+        self.sort_key.sort(&mut apps);
+        apps
+    }
+
+    /// Visible processes with cluster instances grouped under a parent row
+    fn rows(&self) -> Vec<ListRow<'_>> {
+        let visible = self.visible();
+
+        // Group instances by cluster, keeping the order of first appearance
+        let mut clusters: Vec<(&str, Vec<&AppInfo>)> = Vec::new();
+        for info in &visible {
+            if let Some(name) = cluster_name(info) {
+                match clusters.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, members)) => members.push(info),
+                    None => clusters.push((name, vec![info])),
+                }
+            }
+        }
+
+        let mut rows = Vec::new();
+        let mut emitted: HashSet<&str> = HashSet::new();
+        for info in &visible {
+            let name = match cluster_name(info) {
+                Some(name) => name,
+                None if clusters.iter().any(|(n, _)| *n == info.spec.name) => &info.spec.name,
+                None => {
+                    rows.push(ListRow::App(info));
+                    continue;
+                }
+            };
+            if !emitted.insert(name) {
+                continue;
+            }
+
+            let instances = clusters
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, members)| members.clone())
+                .unwrap_or_default();
+            let parent = self
+                .processes
+                .iter()
+                .find(|p| p.spec.name == name && p.state.instance_id.is_none());
+            let expanded = self.expanded_clusters.contains(name);
+
+            rows.push(ListRow::Cluster {
+                name: name.to_string(),
+                parent,
+                instances: instances.clone(),
+                expanded,
+            });
+            if expanded {
+                rows.extend(instances.into_iter().map(ListRow::Instance));
+            }
+        }
+        rows
+    }
+
+    /// Currently selected process in the visible list
+    fn selected(&self) -> Option<&AppInfo> {
+        self.rows().get(self.selected_index).and_then(ListRow::app)
+    }
+
+    /// Expand or collapse the selected cluster row
+    fn toggle_selected_cluster(&mut self) {
+        let name = match self.rows().get(self.selected_index) {
+            Some(ListRow::Cluster { name, .. }) => name.clone(),
+            _ => return,
+        };
+        if !self.expanded_clusters.remove(&name) {
+            self.expanded_clusters.insert(name);
+        }
+    }
+
+    /// Keep the selection inside the visible list
+    fn clamp_selection(&mut self) {
+        let len = self.rows().len();
+        if self.selected_index >= len {
+            self.selected_index = len.saturating_sub(1);
+        }
+    }
+
+    fn cycle_sort(&mut self) {
+        self.sort_key = self.sort_key.next();
+        self.selected_index = 0;
+    }
+
+    fn push_filter_char(&mut self, c: char) {
+        self.filter.push(c);
+        self.selected_index = 0;
+    }
+
+    fn pop_filter_char(&mut self) {
+        self.filter.pop();
+        self.clamp_selection();
+    }
+
+    fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.filter_editing = false;
+        self.clamp_selection();
+    }
+
+    /// Filter/sort summary shown in the title bar
+    fn title(&self) -> String {
+        let mut title = String::from("OxidePM Monitor");
+        if self.supervision_paused {
+            title.push_str(" | SUPERVISION PAUSED");
+        }
+        if !self.filter.is_empty() || self.filter_editing {
+            title.push_str(&format!(" | filter: /{}", self.filter));
+            if self.filter_editing {
+                title.push('_');
+            }
+        }
+        if self.sort_key != SortKey::Id {
+            title.push_str(&format!(" | sort: {}", self.sort_key.as_str()));
+        }
+        title
+    }
+
     async fn refresh(&mut self) {
         match self.client.send(&Request::Status).await {
-            Ok(Response::Status { apps }) => {
+            Ok(Response::Status {
+                apps,
+                supervision_paused,
+            }) => {
                 self.processes = apps;
+                self.supervision_paused = supervision_paused;
                 self.last_error = None;
-                // Adjust selection if needed
-                if self.selected_index >= self.processes.len() && !self.processes.is_empty() {
-                    self.selected_index = self.processes.len() - 1;
-                }
+                self.clamp_selection();
             }
-            Ok(Response::Error { message }) => {
+            Ok(Response::Error { message, .. }) => {
                 self.last_error = Some(message);
             }
             Err(e) => {
@@ -66,36 +321,41 @@ impl App {
     }
 
     async fn refresh_logs(&mut self) {
-        if self.processes.is_empty() {
+        let Some(app) = self.selected() else {
             return;
-        }
-
-        let app = &self.processes[self.selected_index];
+        };
         let selector = oxidepm_core::Selector::ById(app.spec.id);
 
-        if let Ok(Response::LogLines { lines }) = self.client.send(&Request::Logs {
-            selector,
-            lines: 50,
-            follow: false,
-            stdout: true,
-            stderr: true,
-        }).await {
+        if let Ok(Response::LogLines { lines }) = self
+            .client
+            .send(&Request::Logs {
+                selector,
+                lines: 50,
+                follow: false,
+                stdout: true,
+                stderr: true,
+                since_secs: None,
+            })
+            .await
+        {
             self.logs = lines;
         }
     }
 
     fn next(&mut self) {
-        if !self.processes.is_empty() {
-            self.selected_index = (self.selected_index + 1) % self.processes.len();
+        let len = self.rows().len();
+        if len > 0 {
+            self.selected_index = (self.selected_index + 1) % len;
         }
     }
 
     fn previous(&mut self) {
-        if !self.processes.is_empty() {
+        let len = self.rows().len();
+        if len > 0 {
             self.selected_index = if self.selected_index > 0 {
                 self.selected_index - 1
             } else {
-                self.processes.len() - 1
+                len - 1
             };
         }
     }
@@ -113,26 +373,42 @@ impl App {
     }
 
     async fn stop_selected(&mut self) {
-        if self.processes.is_empty() {
-            return;
-        }
-
-        let app = &self.processes[self.selected_index];
-        let selector = oxidepm_core::Selector::ById(app.spec.id);
+        let ids = match self.rows().get(self.selected_index) {
+            Some(row) => row.target_ids(),
+            None => return,
+        };
 
-        let _ = self.client.send(&Request::Stop { selector }).await;
+        for id in ids {
+            let selector = oxidepm_core::Selector::ById(id);
+            let _ = self
+                .client
+                .send(&Request::Stop {
+                    selector,
+                    if_exists: false,
+                })
+                .await;
+        }
         self.refresh().await;
     }
 
     async fn restart_selected(&mut self) {
-        if self.processes.is_empty() {
-            return;
-        }
-
-        let app = &self.processes[self.selected_index];
-        let selector = oxidepm_core::Selector::ById(app.spec.id);
+        let ids = match self.rows().get(self.selected_index) {
+            Some(row) => row.target_ids(),
+            None => return,
+        };
 
-        let _ = self.client.send(&Request::Restart { selector }).await;
+        for id in ids {
+            let selector = oxidepm_core::Selector::ById(id);
+            let _ = self
+                .client
+                .send(&Request::Restart {
+                    selector,
+                    reset_counter: false,
+                    bulk: Default::default(),
+                    if_exists: false,
+                })
+                .await;
+        }
         self.refresh().await;
     }
 }
@@ -163,8 +439,17 @@ pub async fn run(socket_path: PathBuf) -> io::Result<()> {
 
         if crossterm::event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
+                if key.kind == KeyEventKind::Press && app.filter_editing {
                     match key.code {
+                        KeyCode::Esc => app.clear_filter(),
+                        KeyCode::Enter => app.filter_editing = false,
+                        KeyCode::Backspace => app.pop_filter_char(),
+                        KeyCode::Char(c) => app.push_filter_char(c),
+                        _ => {}
+                    }
+                } else if key.kind == KeyEventKind::Press {
+                    match key.code {
+                        KeyCode::Esc if !app.filter.is_empty() => app.clear_filter(),
                         KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
                         KeyCode::Down | KeyCode::Char('j') => app.next(),
                         KeyCode::Up | KeyCode::Char('k') => app.previous(),
@@ -172,6 +457,12 @@ pub async fn run(socket_path: PathBuf) -> io::Result<()> {
                         KeyCode::BackTab => app.previous_tab(),
                         KeyCode::Char('s') => app.stop_selected().await,
                         KeyCode::Char('r') => app.restart_selected().await,
+                        KeyCode::Char('/') => {
+                            app.filter_editing = true;
+                            app.tab_index = 0; // Filter applies to the process list
+                        }
+                        KeyCode::Char('o') => app.cycle_sort(),
+                        KeyCode::Enter => app.toggle_selected_cluster(),
                         KeyCode::Char('l') => {
                             app.refresh_logs().await;
                             app.tab_index = 2; // Switch to logs tab
@@ -218,7 +509,7 @@ fn ui(f: &mut Frame, app: &App) {
     // Tabs
     let tab_titles = vec!["Processes", "Details", "Logs"];
     let tabs = Tabs::new(tab_titles)
-        .block(Block::default().borders(Borders::ALL).title("OxidePM Monitor"))
+        .block(Block::default().borders(Borders::ALL).title(app.title()))
         .select(app.tab_index)
         .style(Style::default().fg(Color::White))
         .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
@@ -234,7 +525,8 @@ fn ui(f: &mut Frame, app: &App) {
 
     // Help bar
     let help_text = match app.tab_index {
-        0 => "↑/↓: Select | s: Stop | r: Restart | l: Logs | Tab: Switch | q: Quit",
+        _ if app.filter_editing => "Type to filter by name/tag/status | Enter: Apply | Esc: Clear",
+        0 => "↑/↓: Select | Enter: Expand | s: Stop | r: Restart | l: Logs | /: Filter | o: Sort | Tab: Switch | q: Quit",
         1 => "↑/↓: Select | Tab: Switch | q: Quit",
         2 => "↑/↓: Scroll | Tab: Switch | q: Quit",
         _ => "",
@@ -246,41 +538,44 @@ fn ui(f: &mut Frame, app: &App) {
 }
 
 fn render_processes(f: &mut Frame, app: &App, area: Rect) {
-    let header_cells = ["ID", "Name", "Mode", "PID", "↺", "Status", "CPU", "Mem", "Uptime", "Port"]
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+    let header_cells = [
+        "ID", "Name", "Mode", "PID", "↺", "Status", "CPU", "Mem", "Uptime", "Port",
+    ]
+    .iter()
+    .map(|h| {
+        Cell::from(*h).style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+    });
     let header = Row::new(header_cells).height(1).bottom_margin(1);
 
-    let rows: Vec<Row> = app.processes.iter().enumerate().map(|(i, info)| {
-        let status_color = match info.state.status {
-            AppStatus::Running => Color::Green,
-            AppStatus::Stopped => Color::Red,
-            AppStatus::Errored => Color::Red,
-            AppStatus::Starting | AppStatus::Building => Color::Yellow,
-            AppStatus::Stopping => Color::Yellow,
-        };
-
-        let cells = vec![
-            Cell::from(info.spec.id.to_string()),
-            Cell::from(info.spec.name.clone()),
-            Cell::from(info.spec.mode.to_string()),
-            Cell::from(info.state.pid.map(|p| p.to_string()).unwrap_or("-".to_string())),
-            Cell::from(info.state.restarts.to_string()),
-            Cell::from(info.state.status.as_str()).style(Style::default().fg(status_color)),
-            Cell::from(format!("{:.1}%", info.state.cpu_percent)),
-            Cell::from(format_bytes(info.state.memory_bytes)),
-            Cell::from(format_duration(info.state.uptime_secs)),
-            Cell::from(info.state.port.map(|p| p.to_string()).unwrap_or("-".to_string())),
-        ];
+    let list_rows = app.rows();
+    let rows: Vec<Row> = list_rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let cells = match row {
+                ListRow::App(info) => app_cells(info, info.spec.qualified_name()),
+                ListRow::Instance(info) => app_cells(info, format!("  └ {}", info.spec.name)),
+                ListRow::Cluster {
+                    name,
+                    parent,
+                    instances,
+                    expanded,
+                } => cluster_cells(name, *parent, instances, *expanded),
+            };
 
-        let style = if i == app.selected_index {
-            Style::default().bg(Color::DarkGray)
-        } else {
-            Style::default()
-        };
+            let style = if i == app.selected_index {
+                Style::default().bg(Color::DarkGray)
+            } else {
+                Style::default()
+            };
 
-        Row::new(cells).style(style)
-    }).collect();
+            Row::new(cells).style(style)
+        })
+        .collect();
 
     let table = Table::new(
         rows,
@@ -298,25 +593,116 @@ fn render_processes(f: &mut Frame, app: &App, area: Rect) {
         ],
     )
     .header(header)
-    .block(Block::default().borders(Borders::ALL).title("Processes"));
+    .block(Block::default().borders(Borders::ALL).title(format!(
+        "Processes ({}/{})",
+        app.visible().len(),
+        app.processes.len()
+    )));
 
     f.render_widget(table, area);
 }
 
+fn status_color(status: AppStatus) -> Color {
+    match status {
+        AppStatus::Running => Color::Green,
+        AppStatus::Stopped => Color::Red,
+        AppStatus::Errored => Color::Red,
+        AppStatus::Starting | AppStatus::Building => Color::Yellow,
+        AppStatus::Stopping => Color::Yellow,
+        AppStatus::Paused => Color::Blue,
+    }
+}
+
+fn app_cells(info: &AppInfo, name: String) -> Vec<Cell<'static>> {
+    vec![
+        Cell::from(info.spec.id.to_string()),
+        Cell::from(name),
+        Cell::from(info.spec.mode.to_string()),
+        Cell::from(
+            info.state
+                .pid
+                .map(|p| p.to_string())
+                .unwrap_or("-".to_string()),
+        ),
+        Cell::from(info.state.restarts.to_string()),
+        Cell::from(info.state.status.as_str())
+            .style(Style::default().fg(status_color(info.state.status))),
+        Cell::from(format!("{:.1}%", info.state.cpu_percent)),
+        Cell::from(format_bytes(info.state.memory_bytes)),
+        Cell::from(format_duration(info.state.uptime_secs)),
+        Cell::from(
+            info.state
+                .port
+                .map(|p| p.to_string())
+                .unwrap_or("-".to_string()),
+        ),
+    ]
+}
+
+/// Cells for a cluster parent row with aggregate CPU/memory across instances
+fn cluster_cells(
+    name: &str,
+    parent: Option<&AppInfo>,
+    instances: &[&AppInfo],
+    expanded: bool,
+) -> Vec<Cell<'static>> {
+    let online = instances
+        .iter()
+        .filter(|i| i.state.status.is_running())
+        .count();
+    let status_color = if online == instances.len() {
+        Color::Green
+    } else if online > 0 {
+        Color::Yellow
+    } else {
+        Color::Red
+    };
+    let cpu: f32 = instances.iter().map(|i| i.state.cpu_percent).sum();
+    let memory: u64 = instances.iter().map(|i| i.state.memory_bytes).sum();
+    let restarts: u32 = instances.iter().map(|i| i.state.restarts).sum();
+    let uptime = instances
+        .iter()
+        .map(|i| i.state.uptime_secs)
+        .max()
+        .unwrap_or(0);
+    let marker = if expanded { "▾" } else { "▸" };
+
+    vec![
+        Cell::from(
+            parent
+                .map(|p| p.spec.id.to_string())
+                .unwrap_or("-".to_string()),
+        ),
+        Cell::from(format!("{} {} ({})", marker, name, instances.len())),
+        Cell::from(
+            instances
+                .first()
+                .map(|i| i.spec.mode.to_string())
+                .unwrap_or_default(),
+        ),
+        Cell::from("-"),
+        Cell::from(restarts.to_string()),
+        Cell::from(format!("{}/{} up", online, instances.len()))
+            .style(Style::default().fg(status_color)),
+        Cell::from(format!("{:.1}%", cpu)),
+        Cell::from(format_bytes(memory)),
+        Cell::from(format_duration(uptime)),
+        Cell::from("-"),
+    ]
+}
+
 fn render_details(f: &mut Frame, app: &App, area: Rect) {
-    if app.processes.is_empty() {
+    let Some(info) = app.selected() else {
         let paragraph = Paragraph::new("No processes")
             .block(Block::default().borders(Borders::ALL).title("Details"));
         f.render_widget(paragraph, area);
         return;
-    }
-
-    let info = &app.processes[app.selected_index];
+    };
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(8),  // Info
+            Constraint::Length(10), // Info
             Constraint::Length(4),  // CPU gauge
             Constraint::Length(4),  // Memory gauge
             Constraint::Min(5),     // Environment
@@ -349,6 +735,20 @@ fn render_details(f: &mut Frame, app: &App, area: Rect) {
             Span::raw("Instances: "),
             Span::raw(info.spec.instances.to_string()),
         ]),
+        Line::from(vec![
+            Span::raw("Threads: "),
+            Span::raw(format_optional(info.state.threads)),
+            Span::raw("  FDs: "),
+            Span::raw(format_optional(info.state.open_fds)),
+        ]),
+        Line::from(vec![
+            Span::raw("Disk: "),
+            Span::raw(format!(
+                "{}/s read, {}/s write",
+                format_bytes(info.state.disk_read_bytes_per_sec),
+                format_bytes(info.state.disk_write_bytes_per_sec)
+            )),
+        ]),
     ];
     let info_paragraph = Paragraph::new(info_text)
         .block(Block::default().borders(Borders::ALL).title("Info"));
@@ -389,10 +789,9 @@ fn render_logs(f: &mut Frame, app: &App, area: Rect) {
         .map(|line| Line::from(line.as_str()))
         .collect();
 
-    let title = if app.processes.is_empty() {
-        "Logs".to_string()
-    } else {
-        format!("Logs - {}", app.processes[app.selected_index].spec.name)
+    let title = match app.selected() {
+        Some(info) => format!("Logs - {}", info.spec.name),
+        None => "Logs".to_string(),
     };
 
     let paragraph = Paragraph::new(logs_text)
@@ -412,6 +811,12 @@ fn format_bytes(bytes: u64) -> String {
     }
 }
 
+fn format_optional(value: Option<u32>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
 fn format_duration(secs: u64) -> String {
     if secs >= 86400 {
         format!("{}d", secs / 86400)
@@ -436,6 +841,103 @@ mod tests {
         assert_eq!(format_bytes(2_000_000_000), "1.9G");
     }
 
+    fn make_app(id: u32, name: &str, cpu: f32, memory_bytes: u64) -> AppInfo {
+        let mut spec = oxidepm_core::AppSpec::new(
+            name.to_string(),
+            oxidepm_core::AppMode::Cmd,
+            "true".to_string(),
+            PathBuf::from("/tmp"),
+        );
+        spec.id = id;
+        let mut state = oxidepm_core::RunState::new(id);
+        state.cpu_percent = cpu;
+        state.memory_bytes = memory_bytes;
+        AppInfo { spec, state }
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("api-gateway", "agw"));
+        assert!(fuzzy_match("API-Gateway", "api"));
+        assert!(!fuzzy_match("worker", "wx"));
+        assert!(fuzzy_match("anything", ""));
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let mut app = App::new(PathBuf::from("/nonexistent.sock"));
+        app.processes = vec![
+            make_app(1, "api", 5.0, 100),
+            make_app(2, "worker", 50.0, 10),
+            make_app(3, "api-admin", 1.0, 1000),
+        ];
+
+        app.filter = "api".to_string();
+        let ids: Vec<u32> = app.visible().iter().map(|a| a.spec.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+
+        app.cycle_sort(); // cpu
+        let ids: Vec<u32> = app.visible().iter().map(|a| a.spec.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+
+        app.cycle_sort(); // memory
+        let ids: Vec<u32> = app.visible().iter().map(|a| a.spec.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+        assert!(app.title().contains("filter: /api"));
+        assert!(app.title().contains("sort: mem"));
+
+        app.selected_index = 1;
+        app.clear_filter();
+        assert_eq!(app.visible().len(), 3);
+    }
+
+    fn make_instance(id: u32, base: &str, instance_id: u32, cpu: f32) -> AppInfo {
+        let mut info = make_app(id, &format!("{}-{}", base, instance_id), cpu, 100);
+        info.state.instance_id = Some(instance_id);
+        info.state.status = AppStatus::Running;
+        info
+    }
+
+    #[test]
+    fn test_cluster_grouping() {
+        let mut app = App::new(PathBuf::from("/nonexistent.sock"));
+        app.processes = vec![
+            make_app(1, "web", 0.0, 0),
+            make_instance(2, "web", 0, 10.0),
+            make_instance(3, "web", 1, 20.0),
+            make_app(4, "worker", 1.0, 10),
+        ];
+
+        // Collapsed: one cluster row plus the standalone worker
+        let rows = app.rows();
+        assert_eq!(rows.len(), 2);
+        match &rows[0] {
+            ListRow::Cluster {
+                name,
+                parent,
+                instances,
+                expanded,
+            } => {
+                assert_eq!(name, "web");
+                assert_eq!(parent.map(|p| p.spec.id), Some(1));
+                assert_eq!(instances.len(), 2);
+                assert!(!expanded);
+            }
+            _ => panic!("expected cluster row"),
+        }
+        assert_eq!(rows[0].target_ids(), vec![1]);
+
+        // Expanded: instances follow the parent row
+        app.selected_index = 0;
+        app.toggle_selected_cluster();
+        let rows = app.rows();
+        assert_eq!(rows.len(), 4);
+        assert!(matches!(rows[1], ListRow::Instance(info) if info.spec.id == 2));
+
+        app.toggle_selected_cluster();
+        assert_eq!(app.rows().len(), 2);
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(30), "30s");
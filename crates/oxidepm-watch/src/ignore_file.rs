@@ -0,0 +1,106 @@
+//! Ignore patterns from `.gitignore` / `.oxidepmignore` files
+
+use std::path::Path;
+use tracing::debug;
+
+/// Ignore patterns from the ignore files (`WATCH_IGNORE_FILES`) in `dir`,
+/// in the form `WatchConfig::ignore` expects
+pub fn ignore_file_patterns(dir: &Path) -> Vec<String> {
+    oxidepm_core::WATCH_IGNORE_FILES
+        .iter()
+        .filter_map(|name| {
+            let content = std::fs::read_to_string(dir.join(name)).ok()?;
+            debug!(
+                "Loading watch ignore patterns from {}",
+                dir.join(name).display()
+            );
+            Some(parse_ignore_file(&content, dir))
+        })
+        .flatten()
+        .collect()
+}
+
+/// Convert gitignore-style lines to watch ignore patterns.
+///
+/// Bare names (`dist`, `*.log`) match at any depth. Entries with a slash
+/// (`/build`, `src/generated/`) are anchored to `root`, matching the entry
+/// and everything below it. Negations (`!keep.log`) are not supported and
+/// are skipped.
+pub fn parse_ignore_file(content: &str, root: &Path) -> Vec<String> {
+    let root = glob::Pattern::escape(&root.to_string_lossy());
+    let mut patterns = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('!') {
+            debug!("Skipping negated ignore pattern '{}'", line);
+            continue;
+        }
+
+        let entry = line.trim_end_matches('/');
+        if entry.is_empty() {
+            continue;
+        }
+        if entry.contains('/') {
+            let entry = entry.trim_start_matches('/');
+            patterns.push(format!("{}/{}", root, entry));
+            patterns.push(format!("{}/{}/**", root, entry));
+        } else {
+            patterns.push(entry.to_string());
+        }
+    }
+
+    patterns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FileWatcher, WatchConfig};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_ignore_file() {
+        let content = "# build output\ndist/\n*.log\n\n/build\nsrc/generated/\n!keep.log\n";
+        let patterns = parse_ignore_file(content, Path::new("/srv/app"));
+
+        assert_eq!(
+            patterns,
+            vec![
+                "dist",
+                "*.log",
+                "/srv/app/build",
+                "/srv/app/build/**",
+                "/srv/app/src/generated",
+                "/srv/app/src/generated/**",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignore_file_patterns_are_applied() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".gitignore"), "dist\n/build\n").unwrap();
+        fs::write(dir.path().join(".oxidepmignore"), "*.tmp\n").unwrap();
+
+        let mut ignore = WatchConfig::default().ignore;
+        ignore.extend(ignore_file_patterns(dir.path()));
+        let watcher = FileWatcher::new(WatchConfig {
+            ignore,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let root = dir.path();
+        assert!(watcher.should_ignore(&root.join("web/dist/bundle.js")));
+        assert!(watcher.should_ignore(&root.join("build/out.o")));
+        assert!(watcher.should_ignore(&root.join("cache.tmp")));
+        assert!(watcher.should_ignore(&root.join("node_modules/x/index.js")));
+        assert!(!watcher.should_ignore(&root.join("src/build/mod.rs")));
+        assert!(!watcher.should_ignore(&root.join("src/main.rs")));
+    }
+}
@@ -1,7 +1,9 @@
 //! OxidePM Watch - Filesystem watcher for watch mode
 
 mod debounce;
+mod ignore_file;
 mod watcher;
 
 pub use debounce::Debouncer;
+pub use ignore_file::{ignore_file_patterns, parse_ignore_file};
 pub use watcher::{FileWatcher, WatchConfig, WatchEvent};
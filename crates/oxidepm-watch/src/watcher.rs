@@ -1,6 +1,6 @@
 //! Filesystem watcher using notify
 
-use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as NotifyWatcher};
+use notify::{Event, PollWatcher, RecursiveMode, Watcher as NotifyWatcher};
 use oxidepm_core::{Error, Result};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver};
@@ -16,6 +16,10 @@ pub struct WatchConfig {
     pub ignore: Vec<String>,
     /// Debounce time in milliseconds
     pub debounce_ms: u64,
+    /// Only report files with these extensions (without the dot; empty reports any file)
+    pub extensions: Vec<String>,
+    /// Poll at this interval instead of using native file events
+    pub poll_interval: Option<Duration>,
 }
 
 impl Default for WatchConfig {
@@ -26,6 +30,8 @@ impl Default for WatchConfig {
                 .map(|s| s.to_string())
                 .collect(),
             debounce_ms: oxidepm_core::DEFAULT_DEBOUNCE_MS,
+            extensions: Vec::new(),
+            poll_interval: None,
         }
     }
 }
@@ -59,9 +65,10 @@ impl From<notify::EventKind> for WatchEventKind {
 
 /// File watcher for watch mode
 pub struct FileWatcher {
-    watcher: RecommendedWatcher,
+    watcher: Box<dyn NotifyWatcher + Send>,
     rx: Receiver<notify::Result<Event>>,
     ignore_patterns: Vec<glob::Pattern>,
+    extensions: Vec<String>,
     debouncer: Debouncer,
     watched_paths: Vec<PathBuf>,
 }
@@ -71,19 +78,31 @@ impl FileWatcher {
     pub fn new(config: WatchConfig) -> Result<Self> {
         let (tx, rx) = mpsc::channel();
 
-        let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        let handler = move |res: notify::Result<Event>| {
             if let Err(e) = tx.send(res) {
                 warn!("Failed to send watch event: {}", e);
             }
-        })
-        .map_err(|e| Error::ConfigError(format!("Failed to create watcher: {}", e)))?;
+        };
+        let watcher: Box<dyn NotifyWatcher + Send> = match config.poll_interval {
+            Some(interval) => {
+                let poll_config = notify::Config::default().with_poll_interval(interval);
+                Box::new(PollWatcher::new(handler, poll_config).map_err(|e| {
+                    Error::ConfigError(format!("Failed to create polling watcher: {}", e))
+                })?)
+            }
+            None => Box::new(
+                notify::recommended_watcher(handler)
+                    .map_err(|e| Error::ConfigError(format!("Failed to create watcher: {}", e)))?,
+            ),
+        };
 
         let ignore_patterns: Vec<glob::Pattern> = config
             .ignore
             .iter()
             .filter_map(|p| {
-                // Convert simple patterns to glob patterns
-                let pattern = if p.contains('*') || p.contains('?') {
+                // Convert simple patterns to glob patterns; absolute ones
+                // (from ignore files) are already anchored
+                let pattern = if p.contains('*') || p.contains('?') || Path::new(p).is_absolute() {
                     p.clone()
                 } else {
                     format!("**/{}", p)
@@ -100,16 +119,23 @@ impl FileWatcher {
 
         let debouncer = Debouncer::new(Duration::from_millis(config.debounce_ms));
 
+        let extensions = config
+            .extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect();
+
         Ok(Self {
             watcher,
             rx,
             ignore_patterns,
+            extensions,
             debouncer,
             watched_paths: Vec::new(),
         })
     }
 
-    /// Watch a directory recursively
+    /// Watch a directory recursively, or a single file
     pub fn watch(&mut self, path: &Path) -> Result<()> {
         let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
 
@@ -117,7 +143,7 @@ impl FileWatcher {
             .watch(&path, RecursiveMode::Recursive)
             .map_err(|e| Error::ConfigError(format!("Failed to watch {}: {}", path.display(), e)))?;
 
-        info!("Watching directory: {}", path.display());
+        info!("Watching: {}", path.display());
         self.watched_paths.push(path);
         Ok(())
     }
@@ -144,7 +170,7 @@ impl FileWatcher {
                     let paths: Vec<PathBuf> = event
                         .paths
                         .into_iter()
-                        .filter(|p| !self.should_ignore(p))
+                        .filter(|p| self.matches_extension(p) && !self.should_ignore(p))
                         .collect();
 
                     if paths.is_empty() {
@@ -188,8 +214,22 @@ impl FileWatcher {
         }
     }
 
+    /// Check a path against the extension filter
+    fn matches_extension(&self, path: &Path) -> bool {
+        if self.extensions.is_empty() {
+            return true;
+        }
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                self.extensions
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(ext))
+            })
+    }
+
     /// Check if a path should be ignored
-    fn should_ignore(&self, path: &Path) -> bool {
+    pub(crate) fn should_ignore(&self, path: &Path) -> bool {
         let path_str = path.to_string_lossy();
 
         for pattern in &self.ignore_patterns {
@@ -238,6 +278,7 @@ mod tests {
         let config = WatchConfig {
             ignore: vec!["target".to_string(), "node_modules".to_string(), "*.swp".to_string()],
             debounce_ms: 200,
+            ..Default::default()
         };
 
         let watcher = FileWatcher::new(config).unwrap();
@@ -264,6 +305,7 @@ mod tests {
         let config = WatchConfig {
             ignore: vec![],
             debounce_ms: 50,
+            ..Default::default()
         };
         let mut watcher = FileWatcher::new(config).unwrap();
         watcher.watch(dir.path()).unwrap();
@@ -281,4 +323,45 @@ mod tests {
             assert!(!event.paths.is_empty());
         }
     }
+
+    #[test]
+    fn test_matches_extension() {
+        let config = WatchConfig {
+            extensions: vec!["rs".to_string(), ".TOML".to_string()],
+            ..Default::default()
+        };
+        let watcher = FileWatcher::new(config).unwrap();
+
+        assert!(watcher.matches_extension(Path::new("/project/src/main.rs")));
+        assert!(watcher.matches_extension(Path::new("/project/Cargo.toml")));
+        assert!(!watcher.matches_extension(Path::new("/project/README.md")));
+        assert!(!watcher.matches_extension(Path::new("/project/src")));
+
+        let any = FileWatcher::new(WatchConfig::default()).unwrap();
+        assert!(any.matches_extension(Path::new("/project/README.md")));
+    }
+
+    #[test]
+    fn test_poll_backend_sees_changes() {
+        let dir = TempDir::new().unwrap();
+        let config = WatchConfig {
+            ignore: vec![],
+            debounce_ms: 0,
+            extensions: vec!["js".to_string()],
+            poll_interval: Some(Duration::from_millis(50)),
+        };
+        let mut watcher = FileWatcher::new(config).unwrap();
+        watcher.watch(dir.path()).unwrap();
+
+        fs::write(dir.path().join("notes.txt"), "skip").unwrap();
+        fs::write(dir.path().join("server.js"), "hello").unwrap();
+
+        let event = watcher
+            .wait(Duration::from_secs(2))
+            .expect("poll watcher reported no change");
+        assert!(event
+            .paths
+            .iter()
+            .all(|p| p.extension().is_some_and(|ext| ext == "js")));
+    }
 }
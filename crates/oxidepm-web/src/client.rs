@@ -0,0 +1,192 @@
+//! Daemon client shared by the HTTP handlers
+//!
+//! Handlers run concurrently, so the client keeps a few connections open
+//! between requests. While a daemon is down, its circuit breaker fails
+//! requests at once instead of letting each one wait out the IPC timeouts.
+
+use oxidepm_core::{Error, ErrorCode, Result};
+use oxidepm_ipc::{Endpoint, IpcClient, Request, Response};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Idle daemon connections kept per daemon
+const POOL_SIZE: usize = 8;
+
+/// Failed requests in a row that open the breaker
+const BREAKER_THRESHOLD: u32 = 3;
+
+/// How long an open breaker fails requests before letting one through again
+const BREAKER_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// IPC client of one daemon, with pooled connections and a circuit breaker
+pub struct DaemonClient {
+    ipc: IpcClient,
+    breaker: CircuitBreaker,
+}
+
+impl DaemonClient {
+    /// Long requests that outlast the daemon's budget come back as
+    /// operations to poll, rather than holding the HTTP request open
+    pub fn new(ipc: IpcClient) -> Self {
+        Self {
+            ipc: ipc
+                .with_connection_pool(POOL_SIZE)
+                .with_background_operations(),
+            breaker: CircuitBreaker::default(),
+        }
+    }
+
+    pub fn endpoint(&self) -> &Endpoint {
+        self.ipc.endpoint()
+    }
+
+    /// Send a request, or fail at once while the daemon is known to be down
+    pub async fn send(&self, request: &Request) -> Result<Response> {
+        // Held until the request is done, so a cancelled probe frees the slot
+        let _probe = match self.breaker.admit() {
+            Ok(probe) => probe,
+            Err(retry_in) => {
+                return Err(Error::IpcConnectionFailed(format!(
+                    "{} is not answering; trying again in {}s",
+                    self.endpoint(),
+                    retry_in.as_secs().max(1)
+                )))
+            }
+        };
+        let result = self.ipc.send(request).await;
+        match &result {
+            Err(e) if is_unavailable(e) => self.breaker.record_failure(),
+            _ => self.breaker.record_success(),
+        }
+        result
+    }
+}
+
+/// Whether a failed request means the daemon is down or hung, rather than
+/// that it refused the request
+pub fn is_unavailable(err: &Error) -> bool {
+    err.kind() == ErrorCode::DaemonUnreachable
+        || matches!(
+            err,
+            Error::IpcError(_) | Error::IpcConnectionFailed(_) | Error::Timeout(_)
+        )
+}
+
+/// Opens after `BREAKER_THRESHOLD` failures in a row and stays open for
+/// `BREAKER_COOLDOWN`; after that a single request probes the daemon while
+/// the others keep failing fast, and a failed probe opens it again at once
+#[derive(Default)]
+struct CircuitBreaker {
+    failures: AtomicU32,
+    open_until: Mutex<Option<Instant>>,
+    /// A request is probing the daemon after the cooldown
+    probing: AtomicBool,
+}
+
+/// The half-open breaker's one request in flight; dropping it lets the next
+/// request probe unless the outcome has closed or reopened the breaker
+#[derive(Debug)]
+struct Probe<'a>(&'a AtomicBool);
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl CircuitBreaker {
+    /// Whether a request may go through: the probe to hold while half-open,
+    /// else the time left while open (zero while another request probes)
+    fn admit(&self) -> std::result::Result<Option<Probe<'_>>, Duration> {
+        let Some(open_until) = self.open_until.lock().ok().and_then(|open| *open) else {
+            return Ok(None);
+        };
+        if let Some(left) = open_until.checked_duration_since(Instant::now()) {
+            return Err(left);
+        }
+        self.probing
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| Some(Probe(&self.probing)))
+            .map_err(|_| Duration::ZERO)
+    }
+
+    fn record_failure(&self) {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= BREAKER_THRESHOLD {
+            if let Ok(mut open_until) = self.open_until.lock() {
+                *open_until = Some(Instant::now() + BREAKER_COOLDOWN);
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+        if let Ok(mut open_until) = self.open_until.lock() {
+            *open_until = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::default();
+        for _ in 1..BREAKER_THRESHOLD {
+            breaker.record_failure();
+            assert!(matches!(breaker.admit(), Ok(None)));
+        }
+        breaker.record_failure();
+        assert!(breaker.admit().unwrap_err() <= BREAKER_COOLDOWN);
+
+        breaker.record_success();
+        assert!(matches!(breaker.admit(), Ok(None)));
+        // The streak starts over after a success
+        breaker.record_failure();
+        assert!(matches!(breaker.admit(), Ok(None)));
+    }
+
+    #[test]
+    fn test_circuit_breaker_single_probe() {
+        let breaker = CircuitBreaker::default();
+        for _ in 0..BREAKER_THRESHOLD {
+            breaker.record_failure();
+        }
+        // Cooldown over
+        *breaker.open_until.lock().unwrap() = Some(Instant::now());
+
+        let probe = breaker.admit().unwrap().expect("first request probes");
+        assert_eq!(breaker.admit().unwrap_err(), Duration::ZERO);
+        // A probe dropped without an outcome lets the next request try
+        drop(probe);
+        let probe = breaker.admit().unwrap().expect("next request probes");
+
+        breaker.record_failure();
+        drop(probe);
+        assert!(breaker.admit().unwrap_err() > Duration::ZERO);
+
+        *breaker.open_until.lock().unwrap() = Some(Instant::now());
+        let probe = breaker.admit().unwrap();
+        breaker.record_success();
+        drop(probe);
+        assert!(matches!(breaker.admit(), Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn test_fails_fast_while_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = DaemonClient::new(IpcClient::with_endpoint(Endpoint::Unix(
+            dir.path().join("missing.sock"),
+        )));
+        for _ in 0..BREAKER_THRESHOLD {
+            let err = client.send(&Request::Status).await.unwrap_err();
+            assert!(matches!(err, Error::DaemonNotRunning));
+        }
+        let err = client.send(&Request::Status).await.unwrap_err();
+        assert!(err.to_string().contains("trying again in"));
+        assert!(is_unavailable(&err));
+    }
+}
@@ -3,29 +3,49 @@
 //! Provides REST API and WebSocket support for remote process management.
 
 use axum::{
-    extract::{Path, Request as AxumRequest, State, WebSocketUpgrade},
-    http::{header::HeaderValue, Method, StatusCode},
+    async_trait,
+    extract::{
+        FromRequestParts, Path, Query, RawPathParams, Request as AxumRequest, State,
+        WebSocketUpgrade,
+    },
+    http::{
+        header::{self, HeaderValue},
+        request::Parts,
+        Method, StatusCode,
+    },
     middleware::{self, Next},
     response::{IntoResponse, Json, Response as AxumResponse},
     routing::{delete, get, post},
     Router,
 };
 use futures::{SinkExt, StreamExt};
-use oxidepm_core::{AppInfo, AppSpec, Selector};
-use oxidepm_ipc::{IpcClient, Request, Response};
+use oxidepm_core::{
+    constants, AppInfo, AppSpec, Error, LogStream, OperationInfo, PreflightSummary, Selector,
+};
+use oxidepm_ipc::{Endpoint, IpcClient, Request, Response};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 use tracing::info;
 
+mod client;
+
+pub use client::DaemonClient;
+
 /// API response wrapper
 #[derive(Serialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
@@ -34,6 +54,8 @@ impl<T: Serialize> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
+            hint: None,
         }
     }
 
@@ -42,14 +64,36 @@ impl<T: Serialize> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message.into()),
+            code: None,
+            hint: None,
+        }
+    }
+
+    /// Error response carrying the daemon's error code and remediation hint
+    pub fn err_with(
+        message: impl Into<String>,
+        code: Option<String>,
+        hint: Option<String>,
+    ) -> ApiResponse<()> {
+        ApiResponse {
+            success: false,
+            data: None,
+            error: Some(message.into()),
+            code,
+            hint,
         }
     }
 }
 
+/// How long the combined views wait for each host
+const HOST_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
-    client: Arc<IpcClient>,
+    client: Arc<DaemonClient>,
+    /// Remote daemons served under `/api/hosts/:host`
+    hosts: Arc<BTreeMap<String, Arc<DaemonClient>>>,
     event_tx: broadcast::Sender<WebEvent>,
     #[allow(dead_code)]
     api_key: Option<String>,
@@ -59,11 +103,87 @@ impl AppState {
     pub fn new(socket_path: std::path::PathBuf, api_key: Option<String>) -> Self {
         let (event_tx, _) = broadcast::channel(100);
         Self {
-            client: Arc::new(IpcClient::new(socket_path)),
+            client: Arc::new(DaemonClient::new(
+                IpcClient::new(socket_path).with_client_name("web"),
+            )),
+            hosts: Arc::new(BTreeMap::new()),
             event_tx,
             api_key,
         }
     }
+
+    /// Also serve these remote daemons, keyed by host name
+    pub fn with_hosts(mut self, hosts: impl IntoIterator<Item = (String, Endpoint)>) -> Self {
+        self.hosts = Arc::new(
+            hosts
+                .into_iter()
+                .map(|(name, endpoint)| {
+                    (
+                        name,
+                        Arc::new(DaemonClient::new(
+                            IpcClient::with_endpoint(endpoint).with_client_name("web"),
+                        )),
+                    )
+                })
+                .collect(),
+        );
+        self
+    }
+
+    /// Client for a host name; "local" is the daemon on this machine
+    fn client_for(&self, host: &str) -> Option<Arc<DaemonClient>> {
+        if host == constants::LOCAL_HOST {
+            return Some(self.client.clone());
+        }
+        self.hosts.get(host).cloned()
+    }
+
+    /// Every daemon served, local first
+    fn targets(&self) -> Vec<(String, Arc<DaemonClient>)> {
+        std::iter::once((constants::LOCAL_HOST.to_string(), self.client.clone()))
+            .chain(
+                self.hosts
+                    .iter()
+                    .map(|(name, client)| (name.clone(), client.clone())),
+            )
+            .collect()
+    }
+}
+
+/// The daemon a request is for: the local one, or `:host` under `/api/hosts/:host`
+pub struct Daemon(pub Arc<DaemonClient>);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Daemon {
+    type Rejection = AxumResponse;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let Some((_, host)) = params.iter().find(|(key, _)| *key == "host") else {
+            return Ok(Daemon(state.client.clone()));
+        };
+        state.client_for(host).map(Daemon).ok_or_else(|| {
+            let message = format!("Unknown host: {}", host);
+            (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::err(message))).into_response()
+        })
+    }
+}
+
+/// Selector in a process route (nested routes also carry `:host`)
+#[derive(Deserialize)]
+pub struct SelectorPath {
+    pub selector: String,
+}
+
+/// Operation id in an operation route
+#[derive(Deserialize)]
+pub struct OperationPath {
+    pub id: u64,
 }
 
 /// API key authentication middleware
@@ -121,6 +241,79 @@ pub struct StartRequest {
     pub instances: Option<u32>,
     pub watch: Option<bool>,
     pub port: Option<u16>,
+    pub namespace: Option<String>,
+    /// Start even if the preflight checks find blocking issues
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Query parameters for the process list
+#[derive(Deserialize, Default)]
+pub struct ListQuery {
+    /// Only return apps in this namespace
+    pub namespace: Option<String>,
+}
+
+/// Query parameters for deleting apps
+#[derive(Deserialize, Default)]
+pub struct DeleteQuery {
+    /// Also remove logs and run/metrics history
+    #[serde(default)]
+    pub purge: bool,
+}
+
+/// A daemon in the combined views
+#[derive(Serialize)]
+pub struct HostSummary {
+    pub name: String,
+    pub location: String,
+    pub online: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processes: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A process tagged with the daemon that runs it
+#[derive(Serialize)]
+pub struct HostedApp {
+    pub host: String,
+    #[serde(flatten)]
+    pub app: AppInfo,
+}
+
+/// Processes of every daemon, plus the daemons that could not be reached
+#[derive(Serialize)]
+pub struct ClusterProcesses {
+    pub processes: Vec<HostedApp>,
+    pub unreachable: Vec<HostSummary>,
+}
+
+/// Query parameters for the event log
+#[derive(Deserialize, Default)]
+pub struct EventsQuery {
+    /// Number of most recent events (default: 50)
+    pub last: Option<usize>,
+}
+
+/// Query parameters for the logs endpoint
+#[derive(Deserialize, Default)]
+pub struct LogsQuery {
+    /// Number of lines per page (default: 100)
+    pub lines: Option<usize>,
+    /// Log stream to read, `out` or `err` (default: out)
+    pub stream: Option<LogStream>,
+    /// Cursor from a previous page's `older`
+    pub before: Option<String>,
+    /// Cursor from a previous page's `newer`
+    pub after: Option<String>,
+}
+
+/// Query parameters for the audit log
+#[derive(Deserialize, Default)]
+pub struct AuditQuery {
+    /// Number of most recent entries (default: 100)
+    pub last: Option<usize>,
 }
 
 /// Create the API router
@@ -146,18 +339,14 @@ pub fn create_router_with_cors(state: AppState, cors_origin: Option<String>) ->
 
     // Routes that require authentication
     let protected_routes = Router::new()
-        // Process management
-        .route("/api/processes", get(list_processes))
-        .route("/api/processes", post(start_process))
-        .route("/api/processes/:selector", get(get_process))
-        .route("/api/processes/:selector", delete(delete_process))
-        .route("/api/processes/:selector/stop", post(stop_process))
-        .route("/api/processes/:selector/restart", post(restart_process))
-        .route("/api/processes/:selector/logs", get(get_logs))
-        // System (except health)
-        .route("/api/ping", get(ping_daemon))
-        .route("/api/save", post(save_processes))
-        .route("/api/resurrect", post(resurrect_processes))
+        // The local daemon at /api/..., any served daemon at /api/hosts/:host/...
+        .nest("/api", daemon_routes())
+        .nest("/api/hosts/:host", daemon_routes())
+        // Every daemon at once
+        .route("/api/hosts", get(list_hosts))
+        .route("/api/cluster/processes", get(list_cluster_processes))
+        // Prometheus text exposition
+        .route("/metrics", get(prometheus_metrics))
         // WebSocket for real-time updates
         .route("/ws", get(websocket_handler))
         .route_layer(middleware::from_fn_with_state(state.clone(), api_key_auth));
@@ -174,6 +363,29 @@ pub fn create_router_with_cors(state: AppState, cors_origin: Option<String>) ->
         .with_state(state)
 }
 
+/// Process management and system routes, served for each daemon
+fn daemon_routes() -> Router<AppState> {
+    Router::new()
+        // Process management
+        .route("/processes", get(list_processes))
+        .route("/processes", post(start_process))
+        .route("/processes/:selector", get(get_process))
+        .route("/processes/:selector", delete(delete_process))
+        .route("/processes/:selector/stop", post(stop_process))
+        .route("/processes/:selector/restart", post(restart_process))
+        .route("/processes/:selector/logs", get(get_logs))
+        .route("/processes/:selector/events", get(get_events))
+        .route("/processes/:selector/ready", get(get_readiness))
+        // System (except health)
+        .route("/ping", get(ping_daemon))
+        .route("/system", get(get_system_info))
+        .route("/audit", get(get_audit))
+        .route("/operations", get(list_operations))
+        .route("/operations/:id", get(get_operation))
+        .route("/save", post(save_processes))
+        .route("/resurrect", post(resurrect_processes))
+}
+
 /// Start the web server
 pub async fn start_server(
     bind_addr: &str,
@@ -190,8 +402,16 @@ pub async fn start_server_with_cors(
     api_key: Option<String>,
     cors_origin: Option<String>,
 ) -> std::io::Result<()> {
-    let state = AppState::new(socket_path, api_key);
-    let app = create_router_with_cors(state.clone(), cors_origin);
+    serve(bind_addr, AppState::new(socket_path, api_key), cors_origin).await
+}
+
+/// Start the web server with prepared state (e.g. with remote hosts)
+pub async fn serve(
+    bind_addr: &str,
+    state: AppState,
+    cors_origin: Option<String>,
+) -> std::io::Result<()> {
+    let app = create_router_with_cors(state, cors_origin);
 
     info!("Starting OxidePM Web API on {}", bind_addr);
 
@@ -203,6 +423,46 @@ pub async fn start_server_with_cors(
 
 // === API Handlers ===
 
+/// Response for a request the daemon did not answer: 503 with a "daemon
+/// unavailable" payload when it is down or hung, so callers can back off
+fn ipc_failure(err: &Error) -> AxumResponse {
+    if client::is_unavailable(err) {
+        let body = ApiResponse::<()>::err_with(
+            format!("Daemon unavailable: {}", err),
+            Some(oxidepm_core::ErrorCode::DaemonUnreachable.to_string()),
+            Some("Check that oxidepmd is running with `oxidepm ping`".to_string()),
+        );
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response();
+    }
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiResponse::<()>::err(err.to_string())),
+    )
+        .into_response()
+}
+
+/// Response for a request that carries on in the daemon: 202 with the
+/// operation to poll under `/api/operations/:id`
+fn operation_pending(operation: OperationInfo) -> AxumResponse {
+    (StatusCode::ACCEPTED, Json(ApiResponse::ok(operation))).into_response()
+}
+
+/// A start refused by its preflight checks: 422 with the check results as data
+fn preflight_failed(summary: PreflightSummary) -> AxumResponse {
+    let issues: Vec<&str> = summary.issues().map(|r| r.message.as_str()).collect();
+    let body = ApiResponse {
+        success: false,
+        error: Some(format!("Preflight checks failed: {}", issues.join("; "))),
+        data: Some(summary),
+        code: Some("preflight_failed".to_string()),
+        hint: Some(
+            "Fix the project (`oxidepm check <dir> --fix`) or pass \"force\": true to start anyway"
+                .to_string(),
+        ),
+    };
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+}
+
 async fn health_check() -> impl IntoResponse {
     Json(ApiResponse::ok(serde_json::json!({
         "status": "healthy",
@@ -210,51 +470,154 @@ async fn health_check() -> impl IntoResponse {
     })))
 }
 
-async fn ping_daemon(State(state): State<AppState>) -> impl IntoResponse {
-    match state.client.send(&Request::Ping).await {
-        Ok(Response::Pong) => Json(ApiResponse::ok("pong")).into_response(),
-        Ok(Response::Error { message }) => {
-            (StatusCode::SERVICE_UNAVAILABLE, Json(ApiResponse::<()>::err(message))).into_response()
-        }
-        Err(e) => {
-            (StatusCode::SERVICE_UNAVAILABLE, Json(ApiResponse::<()>::err(e.to_string()))).into_response()
-        }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err("Unexpected response"))).into_response()
+async fn ping_daemon(Daemon(client): Daemon) -> impl IntoResponse {
+    match client.send(&Request::Ping).await {
+        Ok(Response::Pong { .. }) => Json(ApiResponse::ok("pong")).into_response(),
+        Ok(Response::Error { message, .. }) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::<()>::err(message)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
-async fn list_processes(State(state): State<AppState>) -> impl IntoResponse {
-    match state.client.send(&Request::Status).await {
-        Ok(Response::Status { apps }) => Json(ApiResponse::ok(apps)).into_response(),
-        Ok(Response::Error { message }) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<Vec<AppInfo>>::err(message))).into_response()
+async fn list_processes(
+    Daemon(client): Daemon,
+    Query(query): Query<ListQuery>,
+) -> impl IntoResponse {
+    match client.send(&Request::Status).await {
+        Ok(Response::Status { mut apps, .. }) => {
+            if let Some(namespace) = &query.namespace {
+                apps.retain(|app| app.spec.namespace == *namespace);
+            }
+            Json(ApiResponse::ok(apps)).into_response()
         }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<Vec<AppInfo>>::err(e.to_string()))).into_response()
+        Ok(Response::Error { message, .. }) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<Vec<AppInfo>>::err(message)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<Vec<AppInfo>>::err("Unexpected response")),
+        )
+            .into_response(),
+    }
+}
+
+/// Ask every served daemon for its processes at once
+async fn status_of_all(state: &AppState) -> Vec<(String, String, Result<Vec<AppInfo>, String>)> {
+    let queries: Vec<_> = state
+        .targets()
+        .into_iter()
+        .map(|(name, client)| {
+            let location = client.endpoint().to_string();
+            let query = tokio::spawn(async move {
+                match tokio::time::timeout(HOST_TIMEOUT, client.send(&Request::Status)).await {
+                    Ok(Ok(Response::Status { apps, .. })) => Ok(apps),
+                    Ok(Ok(Response::Error { message, .. })) => Err(message),
+                    Ok(Ok(_)) => Err("Unexpected response".to_string()),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(_) => Err("No answer".to_string()),
+                }
+            });
+            (name, location, query)
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(queries.len());
+    for (name, location, query) in queries {
+        let result = query.await.unwrap_or_else(|e| Err(e.to_string()));
+        results.push((name, location, result));
+    }
+    results
+}
+
+fn host_summary(
+    name: String,
+    location: String,
+    result: &Result<Vec<AppInfo>, String>,
+) -> HostSummary {
+    HostSummary {
+        name,
+        location,
+        online: result.is_ok(),
+        processes: result.as_ref().ok().map(Vec::len),
+        error: result.as_ref().err().cloned(),
+    }
+}
+
+async fn list_hosts(State(state): State<AppState>) -> impl IntoResponse {
+    let hosts: Vec<HostSummary> = status_of_all(&state)
+        .await
+        .into_iter()
+        .map(|(name, location, result)| host_summary(name, location, &result))
+        .collect();
+    Json(ApiResponse::ok(hosts))
+}
+
+async fn list_cluster_processes(
+    State(state): State<AppState>,
+    Query(query): Query<ListQuery>,
+) -> impl IntoResponse {
+    let mut cluster = ClusterProcesses {
+        processes: Vec::new(),
+        unreachable: Vec::new(),
+    };
+    for (name, location, result) in status_of_all(&state).await {
+        match result {
+            Ok(apps) => cluster.processes.extend(
+                apps.into_iter()
+                    .filter(|app| {
+                        query
+                            .namespace
+                            .as_ref()
+                            .map_or(true, |ns| app.spec.namespace == *ns)
+                    })
+                    .map(|app| HostedApp {
+                        host: name.clone(),
+                        app,
+                    }),
+            ),
+            Err(e) => cluster
+                .unreachable
+                .push(host_summary(name, location, &Err(e))),
         }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<Vec<AppInfo>>::err("Unexpected response"))).into_response()
     }
+    Json(ApiResponse::ok(cluster))
 }
 
 async fn get_process(
-    State(state): State<AppState>,
-    Path(selector): Path<String>,
+    Daemon(client): Daemon,
+    Path(SelectorPath { selector }): Path<SelectorPath>,
 ) -> impl IntoResponse {
     let selector = Selector::parse(&selector);
-    match state.client.send(&Request::Show { selector }).await {
+    match client.send(&Request::Show { selector }).await {
         Ok(Response::Show { app }) => Json(ApiResponse::ok(app)).into_response(),
-        Ok(Response::Error { message }) => {
-            (StatusCode::NOT_FOUND, Json(ApiResponse::<AppInfo>::err(message))).into_response()
-        }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<AppInfo>::err(e.to_string()))).into_response()
-        }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<AppInfo>::err("Unexpected response"))).into_response()
+        Ok(Response::Error { message, .. }) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<AppInfo>::err(message)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<AppInfo>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
 async fn start_process(
     State(state): State<AppState>,
+    Daemon(client): Daemon,
     Json(req): Json<StartRequest>,
 ) -> impl IntoResponse {
     use oxidepm_core::AppMode;
@@ -280,115 +643,540 @@ async fn start_process(
     if let Some(port) = req.port {
         spec.port = Some(port);
     }
+    if let Some(namespace) = req.namespace {
+        spec.namespace = namespace;
+    }
 
-    match state.client.send(&Request::Start { spec }).await {
-        Ok(Response::Started { id, name }) => {
-            let _ = state.event_tx.send(WebEvent::ProcessStarted { id, name: name.clone() });
-            Json(ApiResponse::ok(serde_json::json!({ "id": id, "name": name }))).into_response()
-        }
-        Ok(Response::Error { message }) => {
-            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::err(message))).into_response()
+    // The same checks `oxidepm start` runs, in the app's cwd on the daemon's host
+    if !req.force {
+        let check = Request::Check {
+            dir: spec.cwd.clone(),
+            fix: false,
+        };
+        match client.send(&check).await {
+            Ok(Response::Checked { summary }) if !summary.can_start() => {
+                return preflight_failed(summary)
+            }
+            Ok(Response::Checked { .. }) => {}
+            Ok(Response::Error {
+                message,
+                code,
+                hint,
+            }) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::err_with(message, code, hint)),
+                )
+                    .into_response()
+            }
+            Err(e) => return ipc_failure(&e),
+            _ => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::err("Unexpected response")),
+                )
+                    .into_response()
+            }
         }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(e.to_string()))).into_response()
+    }
+
+    match client
+        .send(&Request::Start {
+            spec,
+            progress: false,
+        })
+        .await
+    {
+        Ok(Response::Started { id, name }) => {
+            let _ = state.event_tx.send(WebEvent::ProcessStarted {
+                id,
+                name: name.clone(),
+            });
+            Json(ApiResponse::ok(
+                serde_json::json!({ "id": id, "name": name }),
+            ))
+            .into_response()
         }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err("Unexpected response"))).into_response()
+        Ok(Response::OperationPending { operation }) => operation_pending(operation),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
 async fn stop_process(
-    State(state): State<AppState>,
-    Path(selector): Path<String>,
+    Daemon(client): Daemon,
+    Path(SelectorPath { selector }): Path<SelectorPath>,
 ) -> impl IntoResponse {
     let selector = Selector::parse(&selector);
-    match state.client.send(&Request::Stop { selector }).await {
-        Ok(Response::Stopped { count }) => Json(ApiResponse::ok(serde_json::json!({ "stopped": count }))).into_response(),
-        Ok(Response::Error { message }) => {
-            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::err(message))).into_response()
-        }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(e.to_string()))).into_response()
-        }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err("Unexpected response"))).into_response()
+    match client
+        .send(&Request::Stop {
+            selector,
+            if_exists: false,
+        })
+        .await
+    {
+        Ok(Response::Stopped { count, results }) => Json(ApiResponse::ok(
+            serde_json::json!({ "stopped": count, "results": results }),
+        ))
+        .into_response(),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
 async fn restart_process(
-    State(state): State<AppState>,
-    Path(selector): Path<String>,
+    Daemon(client): Daemon,
+    Path(SelectorPath { selector }): Path<SelectorPath>,
 ) -> impl IntoResponse {
     let selector = Selector::parse(&selector);
-    match state.client.send(&Request::Restart { selector }).await {
-        Ok(Response::Restarted { count }) => Json(ApiResponse::ok(serde_json::json!({ "restarted": count }))).into_response(),
-        Ok(Response::Error { message }) => {
-            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::err(message))).into_response()
-        }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(e.to_string()))).into_response()
-        }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err("Unexpected response"))).into_response()
+    match client
+        .send(&Request::Restart {
+            selector,
+            reset_counter: false,
+            bulk: Default::default(),
+            if_exists: false,
+        })
+        .await
+    {
+        Ok(Response::Restarted { count, results }) => Json(ApiResponse::ok(
+            serde_json::json!({ "restarted": count, "results": results }),
+        ))
+        .into_response(),
+        Ok(Response::OperationPending { operation }) => operation_pending(operation),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
 async fn delete_process(
-    State(state): State<AppState>,
-    Path(selector): Path<String>,
+    Daemon(client): Daemon,
+    Path(SelectorPath { selector }): Path<SelectorPath>,
+    Query(query): Query<DeleteQuery>,
 ) -> impl IntoResponse {
     let selector = Selector::parse(&selector);
-    match state.client.send(&Request::Delete { selector }).await {
-        Ok(Response::Deleted { count }) => Json(ApiResponse::ok(serde_json::json!({ "deleted": count }))).into_response(),
-        Ok(Response::Error { message }) => {
-            (StatusCode::BAD_REQUEST, Json(ApiResponse::<()>::err(message))).into_response()
-        }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(e.to_string()))).into_response()
-        }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err("Unexpected response"))).into_response()
+    match client
+        .send(&Request::Delete {
+            selector,
+            purge: query.purge,
+        })
+        .await
+    {
+        Ok(Response::Deleted { count, results }) => Json(ApiResponse::ok(
+            serde_json::json!({ "deleted": count, "results": results }),
+        ))
+        .into_response(),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
 async fn get_logs(
-    State(state): State<AppState>,
-    Path(selector): Path<String>,
+    Daemon(client): Daemon,
+    Path(SelectorPath { selector }): Path<SelectorPath>,
+    Query(query): Query<LogsQuery>,
+) -> impl IntoResponse {
+    let request = Request::LogPage {
+        selector: Selector::parse(&selector),
+        stream: query.stream.unwrap_or_default(),
+        lines: query.lines.unwrap_or(constants::DEFAULT_LOG_PAGE_LINES),
+        before: query.before,
+        after: query.after,
+    };
+    match client.send(&request).await {
+        Ok(Response::LogPage { page }) => Json(ApiResponse::ok(page)).into_response(),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
+    }
+}
+
+async fn get_events(
+    Daemon(client): Daemon,
+    Path(SelectorPath { selector }): Path<SelectorPath>,
+    Query(query): Query<EventsQuery>,
 ) -> impl IntoResponse {
     let selector = Selector::parse(&selector);
-    match state.client.send(&Request::Logs { selector, lines: 100, follow: false, stdout: true, stderr: true }).await {
-        Ok(Response::LogLines { lines }) => Json(ApiResponse::ok(lines)).into_response(),
-        Ok(Response::Error { message }) => {
-            (StatusCode::BAD_REQUEST, Json(ApiResponse::<Vec<String>>::err(message))).into_response()
-        }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<Vec<String>>::err(e.to_string()))).into_response()
-        }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<Vec<String>>::err("Unexpected response"))).into_response()
+    let limit = query.last.unwrap_or(constants::DEFAULT_EVENTS_LIMIT);
+    match client.send(&Request::Events { selector, limit }).await {
+        Ok(Response::Events { events }) => Json(ApiResponse::ok(events)).into_response(),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
-async fn save_processes(State(state): State<AppState>) -> impl IntoResponse {
-    match state.client.send(&Request::Save).await {
-        Ok(Response::Saved { count, path }) => {
-            Json(ApiResponse::ok(serde_json::json!({ "saved": count, "path": path }))).into_response()
-        }
-        Ok(Response::Error { message }) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(message))).into_response()
-        }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(e.to_string()))).into_response()
+/// Readiness for load balancers: 200 when every selected app is running and
+/// ready, 503 otherwise
+async fn get_readiness(
+    Daemon(client): Daemon,
+    Path(SelectorPath { selector }): Path<SelectorPath>,
+) -> impl IntoResponse {
+    let selector = Selector::parse(&selector);
+    match client.send(&Request::Status).await {
+        Ok(Response::Status { apps, .. }) => {
+            let apps: Vec<serde_json::Value> = apps
+                .iter()
+                .filter(|app| selector.matches(&app.spec))
+                .map(|app| {
+                    serde_json::json!({
+                        "id": app.spec.id,
+                        "name": app.spec.qualified_name(),
+                        "ready": app.state.ready,
+                    })
+                })
+                .collect();
+            if apps.is_empty() {
+                let message = oxidepm_core::Error::AppNotFound(selector.to_string()).to_string();
+                return (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::err(message)))
+                    .into_response();
+            }
+            let ready = apps.iter().all(|app| app["ready"] == true);
+            let status = if ready {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            (
+                status,
+                Json(ApiResponse::ok(
+                    serde_json::json!({ "ready": ready, "apps": apps }),
+                )),
+            )
+                .into_response()
         }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err("Unexpected response"))).into_response()
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
+    }
+}
+
+async fn get_audit(Daemon(client): Daemon, Query(query): Query<AuditQuery>) -> impl IntoResponse {
+    let limit = query.last.unwrap_or(constants::DEFAULT_AUDIT_LIMIT);
+    match client.send(&Request::Audit { limit }).await {
+        Ok(Response::Audit { entries }) => Json(ApiResponse::ok(entries)).into_response(),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
+    }
+}
+
+async fn list_operations(Daemon(client): Daemon) -> impl IntoResponse {
+    match client.send(&Request::Operations).await {
+        Ok(Response::Operations { operations }) => Json(ApiResponse::ok(operations)).into_response(),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
+    }
+}
+
+/// An operation, with the response of its request once it has finished
+async fn get_operation(
+    Daemon(client): Daemon,
+    Path(OperationPath { id }): Path<OperationPath>,
+) -> impl IntoResponse {
+    match client.send(&Request::OperationStatus { id }).await {
+        Ok(Response::Operation { operation, result }) => Json(ApiResponse::ok(
+            serde_json::json!({ "operation": operation, "result": result }),
+        ))
+        .into_response(),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
+    }
+}
+
+async fn get_system_info(Daemon(client): Daemon) -> impl IntoResponse {
+    match client.send(&Request::SystemInfo).await {
+        Ok(Response::SystemInfo { info }) => Json(ApiResponse::ok(info)).into_response(),
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err_with(message, code, hint)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
-async fn resurrect_processes(State(state): State<AppState>) -> impl IntoResponse {
-    match state.client.send(&Request::Resurrect).await {
-        Ok(Response::Resurrected { count }) => Json(ApiResponse::ok(serde_json::json!({ "resurrected": count }))).into_response(),
-        Ok(Response::Error { message }) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(message))).into_response()
+async fn prometheus_metrics(Daemon(client): Daemon) -> impl IntoResponse {
+    match client.send(&Request::Status).await {
+        Ok(Response::Status { apps, .. }) => (
+            [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+            render_prometheus(&apps),
+        )
+            .into_response(),
+        Ok(Response::Error { message, .. }) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, message).into_response()
         }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err(e.to_string()))).into_response()
+        Err(e) => (StatusCode::SERVICE_UNAVAILABLE, e.to_string()).into_response(),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected response").into_response(),
+    }
+}
+
+/// Per-process gauges in the Prometheus text format
+fn render_prometheus(apps: &[AppInfo]) -> String {
+    type Sample = fn(&AppInfo) -> Option<f64>;
+    let metrics: [(&str, &str, Sample); 11] = [
+        ("oxidepm_up", "Whether the process is running", |a| {
+            Some(if a.state.status.is_running() {
+                1.0
+            } else {
+                0.0
+            })
+        }),
+        ("oxidepm_cpu_percent", "CPU usage in percent", |a| {
+            Some(a.state.cpu_percent as f64)
+        }),
+        ("oxidepm_memory_bytes", "Resident memory in bytes", |a| {
+            Some(a.state.memory_bytes as f64)
+        }),
+        (
+            "oxidepm_restarts",
+            "Restarts since the daemon started",
+            |a| Some(a.state.restarts as f64),
+        ),
+        (
+            "oxidepm_uptime_seconds",
+            "Seconds since the process started",
+            |a| Some(a.state.uptime_secs as f64),
+        ),
+        ("oxidepm_processes", "Processes in the app's tree", |a| {
+            a.state.processes.map(f64::from)
+        }),
+        ("oxidepm_open_fds", "Open file descriptors", |a| {
+            a.state.open_fds.map(f64::from)
+        }),
+        ("oxidepm_threads", "OS threads", |a| {
+            a.state.threads.map(f64::from)
+        }),
+        (
+            "oxidepm_disk_read_bytes_per_second",
+            "Disk read rate",
+            |a| Some(a.state.disk_read_bytes_per_sec as f64),
+        ),
+        (
+            "oxidepm_disk_write_bytes_per_second",
+            "Disk write rate",
+            |a| Some(a.state.disk_write_bytes_per_sec as f64),
+        ),
+        (
+            "oxidepm_log_lines_dropped",
+            "Output lines dropped because the log writer fell behind",
+            |a| Some(a.state.log_lines_dropped as f64),
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, help, sample) in metrics {
+        out.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} gauge\n",
+            name, help, name
+        ));
+        for app in apps {
+            if let Some(value) = sample(app) {
+                out.push_str(&format!(
+                    "{}{{id=\"{}\",name=\"{}\",namespace=\"{}\"}} {}\n",
+                    name,
+                    app.spec.id,
+                    escape_label(&app.spec.name),
+                    escape_label(&app.spec.namespace),
+                    value
+                ));
+            }
         }
-        _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::<()>::err("Unexpected response"))).into_response()
+    }
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+async fn save_processes(Daemon(client): Daemon) -> impl IntoResponse {
+    match client.send(&Request::Save { output: None }).await {
+        Ok(Response::Saved { count, path }) => Json(ApiResponse::ok(
+            serde_json::json!({ "saved": count, "path": path }),
+        ))
+        .into_response(),
+        Ok(Response::Error { message, .. }) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(message)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
+    }
+}
+
+async fn resurrect_processes(Daemon(client): Daemon) -> impl IntoResponse {
+    match client
+        .send(&Request::Resurrect {
+            file: None,
+            all: false,
+        })
+        .await
+    {
+        Ok(Response::Resurrected {
+            count,
+            stopped,
+            warnings,
+        }) => Json(ApiResponse::ok(serde_json::json!({
+            "resurrected": count,
+            "stopped": stopped,
+            "warnings": warnings,
+        })))
+        .into_response(),
+        Ok(Response::OperationPending { operation }) => operation_pending(operation),
+        Ok(Response::Error { message, .. }) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err(message)),
+        )
+            .into_response(),
+        Err(e) => ipc_failure(&e),
+        _ => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::err("Unexpected response")),
+        )
+            .into_response(),
     }
 }
 
@@ -437,6 +1225,9 @@ async fn handle_websocket(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::body::Body;
+    use oxidepm_ipc::IpcServer;
+    use tower::Service;
 
     #[test]
     fn test_api_response_ok() {
@@ -453,4 +1244,264 @@ mod tests {
         assert!(resp.data.is_none());
         assert_eq!(resp.error, Some("error message".to_string()));
     }
+
+    #[test]
+    fn test_render_prometheus() {
+        let spec = AppSpec::new(
+            "api".to_string(),
+            oxidepm_core::AppMode::Node,
+            "app.js".to_string(),
+            std::path::PathBuf::from("/"),
+        );
+        let mut state = oxidepm_core::RunState::running(3, 100);
+        state.open_fds = Some(12);
+        state.disk_read_bytes_per_sec = 2048;
+        state.log_lines_dropped = 7;
+        let running = AppInfo::new(
+            AppSpec {
+                id: 3,
+                ..spec.clone()
+            },
+            state,
+        );
+        let stopped = AppInfo::new(
+            AppSpec {
+                id: 4,
+                name: "worker".to_string(),
+                ..spec
+            },
+            oxidepm_core::RunState::new(4),
+        );
+
+        let out = render_prometheus(&[running, stopped]);
+        assert!(out.contains("# TYPE oxidepm_up gauge"));
+        assert!(out.contains("oxidepm_up{id=\"3\",name=\"api\",namespace=\"default\"} 1\n"));
+        assert!(out.contains("oxidepm_up{id=\"4\",name=\"worker\",namespace=\"default\"} 0\n"));
+        assert!(out.contains("oxidepm_open_fds{id=\"3\",name=\"api\",namespace=\"default\"} 12\n"));
+        assert!(!out.contains("oxidepm_open_fds{id=\"4\""));
+        assert!(out.contains("oxidepm_disk_read_bytes_per_second{id=\"3\",name=\"api\",namespace=\"default\"} 2048\n"));
+        assert!(out.contains(
+            "oxidepm_log_lines_dropped{id=\"3\",name=\"api\",namespace=\"default\"} 7\n"
+        ));
+    }
+
+    async fn get_json(router: &Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let request = AxumRequest::builder().uri(uri).body(Body::empty()).unwrap();
+        // Routers are always ready, so no poll_ready first
+        let response = router.clone().call(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_host_routes() {
+        let dir = tempfile::tempdir().unwrap();
+        let remote_socket = dir.path().join("web1.sock");
+        let server = IpcServer::bind(&remote_socket).await.unwrap();
+        let mut state = AppState::new(dir.path().join("local.sock"), None)
+            .with_hosts([("web1".to_string(), Endpoint::Unix(remote_socket))]);
+        // Never start a real daemon from a test
+        state.client = Arc::new(DaemonClient::new(IpcClient::with_endpoint(Endpoint::Unix(
+            dir.path().join("local.sock"),
+        ))));
+        let router = create_router(state);
+
+        // The remote daemon answers a show for the selector in the nested route
+        let daemon = tokio::spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            let envelope = conn.read_request().await.unwrap().unwrap();
+            assert_eq!(envelope.client.as_deref(), Some("web"));
+            let Request::Show { selector } = envelope.message else {
+                panic!("expected show")
+            };
+            assert_eq!(selector, Selector::ByName("api".to_string()));
+            conn.send_response(
+                envelope.id,
+                &Response::from_error(&oxidepm_core::Error::AppNotFound("api".into())),
+            )
+            .await
+            .unwrap();
+            server
+        });
+        let (status, body) = get_json(&router, "/api/hosts/web1/processes/api").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "App not found: api");
+        let server = daemon.await.unwrap();
+
+        let (status, body) = get_json(&router, "/api/hosts/db1/processes").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Unknown host: db1");
+
+        // Combined view: web1 answers, the local daemon is not running
+        let daemon = tokio::spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            let envelope = conn.read_request().await.unwrap().unwrap();
+            let spec = AppSpec::new(
+                "api".into(),
+                oxidepm_core::AppMode::Cmd,
+                "srv".into(),
+                "/".into(),
+            );
+            let app = AppInfo::new(spec, oxidepm_core::RunState::new(1));
+            let status = Response::Status {
+                apps: vec![app],
+                supervision_paused: false,
+            };
+            conn.send_response(envelope.id, &status).await.unwrap();
+        });
+        let (status, body) = get_json(&router, "/api/cluster/processes").await;
+        daemon.await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["processes"][0]["host"], "web1");
+        assert_eq!(body["data"]["processes"][0]["spec"]["name"], "api");
+        assert_eq!(body["data"]["unreachable"][0]["name"], "local");
+        assert_eq!(body["data"]["unreachable"][0]["online"], false);
+    }
+
+    #[tokio::test]
+    async fn test_daemon_unavailable() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::new(dir.path().join("oxidepm.sock"), None);
+        state.client = Arc::new(DaemonClient::new(IpcClient::with_endpoint(Endpoint::Unix(
+            dir.path().join("oxidepm.sock"),
+        ))));
+        let router = create_router(state);
+
+        // Down at first, and still answered at once after the breaker opens
+        for _ in 0..5 {
+            let (status, body) = get_json(&router, "/api/processes").await;
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(body["code"], "daemon_unreachable");
+            assert!(body["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Daemon unavailable"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readiness_route() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("oxidepm.sock");
+        let server = IpcServer::bind(&socket).await.unwrap();
+        let mut state = AppState::new(socket.clone(), None);
+        state.client = Arc::new(DaemonClient::new(IpcClient::with_endpoint(Endpoint::Unix(
+            socket,
+        ))));
+        let router = create_router(state);
+
+        let daemon = tokio::spawn(async move {
+            loop {
+                let mut conn = server.accept().await.unwrap();
+                let envelope = conn.read_request().await.unwrap().unwrap();
+                let app = |id: u32, name: &str, ready: bool| {
+                    let spec = AppSpec::new(
+                        name.into(),
+                        oxidepm_core::AppMode::Cmd,
+                        "srv".into(),
+                        "/".into(),
+                    );
+                    let mut state = oxidepm_core::RunState::running(id, 100 + id);
+                    state.ready = ready;
+                    AppInfo::new(AppSpec { id, ..spec }, state)
+                };
+                let status = Response::Status {
+                    apps: vec![app(1, "api", true), app(2, "worker", false)],
+                    supervision_paused: false,
+                };
+                conn.send_response(envelope.id, &status).await.unwrap();
+            }
+        });
+
+        let (status, body) = get_json(&router, "/api/processes/api/ready").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["ready"], true);
+        assert_eq!(body["data"]["apps"][0]["name"], "api");
+
+        let (status, body) = get_json(&router, "/api/processes/all/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["data"]["apps"][1]["ready"], false);
+
+        let (status, _) = get_json(&router, "/api/processes/nope/ready").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        daemon.abort();
+    }
+
+    #[tokio::test]
+    async fn test_start_runs_preflight() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("oxidepm.sock");
+        let server = IpcServer::bind(&socket).await.unwrap();
+        let mut state = AppState::new(socket.clone(), None);
+        state.client = Arc::new(DaemonClient::new(IpcClient::with_endpoint(Endpoint::Unix(
+            socket,
+        ))));
+        let router = create_router(state);
+
+        // node_modules is missing; forced starts skip the check
+        let daemon = tokio::spawn(async move {
+            loop {
+                let mut conn = server.accept().await.unwrap();
+                let envelope = conn.read_request().await.unwrap().unwrap();
+                let response = match envelope.message {
+                    Request::Check { dir, fix } => {
+                        assert_eq!(dir, std::path::PathBuf::from("/srv/api"));
+                        assert!(!fix);
+                        Response::Checked {
+                            summary: PreflightSummary::new(vec![oxidepm_core::CheckResult {
+                                status: oxidepm_core::CheckStatus::Warn,
+                                message: "node_modules/ missing - run `npm install`".into(),
+                                fix_hint: None,
+                            }]),
+                        }
+                    }
+                    Request::Start { spec, .. } => Response::Started {
+                        id: 1,
+                        name: spec.name,
+                    },
+                    other => panic!("unexpected request {:?}", other.action()),
+                };
+                conn.send_response(envelope.id, &response).await.unwrap();
+            }
+        });
+
+        let start = |body: serde_json::Value| {
+            let request = AxumRequest::builder()
+                .method("POST")
+                .uri("/api/processes")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let router = router.clone();
+            async move {
+                let response = router.clone().call(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+                )
+            }
+        };
+        let request =
+            serde_json::json!({ "name": "api", "command": "server.js", "cwd": "/srv/api" });
+
+        let (status, body) = start(request.clone()).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "preflight_failed");
+        assert_eq!(body["data"]["has_blocking_issues"], true);
+        assert!(body["error"].as_str().unwrap().contains("node_modules"));
+
+        let mut forced = request;
+        forced["force"] = true.into();
+        let (status, body) = start(forced).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["name"], "api");
+        daemon.abort();
+    }
 }
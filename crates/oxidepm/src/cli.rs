@@ -14,53 +14,80 @@ pub struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count, global = true)]
     pub verbose: u8,
 
-    /// Number of instances for clustering
-    #[arg(short, long, default_value = "1", global = true)]
-    pub instances: u32,
-
-    /// Port assignment for the process
-    #[arg(long, global = true)]
-    pub port: Option<u16>,
-
-    /// HTTP health check URL endpoint
-    #[arg(long, global = true)]
-    pub health_check: Option<String>,
-
     /// Output in JSON format instead of tables
     #[arg(long, global = true)]
     pub json: bool,
+
+    /// Talk to a remote daemon from ~/.oxidepm/hosts.toml instead of the local one
+    #[arg(long, global = true, value_name = "NAME")]
+    pub host: Option<String>,
+
+    /// Use this home directory (socket, database, logs) instead of ~/.oxidepm [env: OXIDEPM_HOME]
+    #[arg(long, global = true, value_name = "DIR")]
+    pub home: Option<PathBuf>,
+
+    /// Use the daemon of a named profile, with its home in ~/.oxidepm-<NAME>
+    #[arg(long, global = true, value_name = "NAME", conflicts_with = "home")]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     /// Start a process
     Start(StartArgs),
 
+    /// Run an app in the foreground without the daemon
+    ///
+    /// Resolves, builds and configures the app like `start`, but keeps it
+    /// attached to the terminal and does not register or restart it.
+    Run(StartArgs),
+
     /// Stop process(es)
     Stop {
-        /// Process name, id, or "all"
-        selector: String,
+        #[command(flatten)]
+        target: TargetArgs,
+
+        /// Succeed when the selector matches no app, e.g. in scripts
+        #[arg(long)]
+        if_exists: bool,
     },
 
     /// Restart process(es)
     Restart {
-        /// Process name, id, or "all"
-        selector: String,
+        #[command(flatten)]
+        target: TargetArgs,
+
+        /// Clear the restart count and crash-loop streak
+        #[arg(long)]
+        reset_counter: bool,
+
+        /// Apps restarted at once (default: 16)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        parallel: Option<u64>,
+
+        /// Keep restarting the other apps after one fails
+        #[arg(long)]
+        continue_on_error: bool,
+
+        /// Succeed when the selector matches no app, e.g. in scripts
+        #[arg(long)]
+        if_exists: bool,
     },
 
     /// Remove process(es) from list
     Delete {
-        /// Process name, id, or "all"
+        /// Process name, id, @tag, ns:namespace, or "all"
         selector: String,
-    },
 
-    /// Show process status table
-    Status {
-        /// Show extended info (cwd, port)
+        /// Also remove the app's logs, hook log and run/metrics history
         #[arg(long)]
-        more: bool,
+        purge: bool,
     },
 
+    /// Show process status table
+    Status(StatusArgs),
+
     /// Show detailed info for a process
     Show {
         /// Process name or id
@@ -74,10 +101,22 @@ pub enum Commands {
     Ping,
 
     /// Save current process list
-    Save,
+    Save {
+        /// Write to this file (e.g. a backup) instead of ~/.oxidepm/saved.json
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
 
     /// Restore saved processes
-    Resurrect,
+    Resurrect {
+        /// Restore from this file instead of ~/.oxidepm/saved.json
+        #[arg(long, short)]
+        file: Option<PathBuf>,
+
+        /// Also start apps that were stopped when the list was saved
+        #[arg(long)]
+        all: bool,
+    },
 
     /// Stop daemon and all processes
     Kill,
@@ -95,22 +134,40 @@ pub enum Commands {
     /// Start Web API server
     Web(WebArgs),
 
+    /// Start gRPC API server
+    Grpc(GrpcArgs),
+
+    /// Serve the Model Context Protocol so AI assistants can manage processes
+    Mcp(McpArgs),
+
     /// Graceful restart of process(es)
-    Reload {
-        /// Process name, id, or "all"
-        selector: String,
-    },
+    Reload(TargetArgs),
 
-    /// Clear/truncate log files for process(es)
+    /// Clear/truncate log files for process(es), or prune them by age/size
     Flush {
         /// Process name, id, or "all"
         selector: String,
+
+        /// Instead of truncating, delete rotated logs not written for this long (e.g. "7d")
+        #[arg(long, value_parser = parse_duration)]
+        older_than: Option<u64>,
+
+        /// Instead of truncating, delete the oldest rotated logs until each app's logs fit (e.g. "100M")
+        #[arg(long, value_parser = parse_size)]
+        keep: Option<u64>,
     },
 
     /// Show what command would run without starting
+    ///
+    /// Takes a registered app, or a file, directory or config file together
+    /// with the same options as `start`.
     Describe {
-        /// Process name, id, or target file/directory
-        target: String,
+        #[command(flatten)]
+        start: StartArgs,
+
+        /// Show secret values instead of masking them
+        #[arg(long)]
+        show_secrets: bool,
     },
 
     /// Configure notifications (Telegram, etc.)
@@ -118,6 +175,396 @@ pub enum Commands {
 
     /// Check if a project is ready to run (dependencies, configs, env)
     Check(CheckArgs),
+
+    /// Suspend auto-restarts, health actions and watch restarts (maintenance mode)
+    PauseSupervision,
+
+    /// Resume supervision after pause-supervision
+    ResumeSupervision,
+
+    /// Make the daemon re-read notify.toml and daemon.toml (like SIGHUP)
+    DaemonReload,
+
+    /// Take apps out of rotation before maintenance
+    ///
+    /// Drained apps report not ready, so load balancers polling
+    /// `/api/processes/<selector>/ready` stop sending them traffic, and their
+    /// log triggers hold off. They stop once --timeout passes.
+    Drain {
+        /// Process name, id, @tag, ns:namespace, or "all"
+        selector: String,
+
+        /// How long drained apps keep running before they stop (e.g. "30s", "5m")
+        #[arg(long, value_parser = parse_duration, default_value = "30s")]
+        timeout: u64,
+    },
+
+    /// End a drain, starting the apps it stopped
+    Undrain {
+        /// Process name, id, @tag, ns:namespace, or "all"
+        selector: String,
+    },
+
+    /// Freeze process(es) with SIGSTOP until resumed
+    Pause(TargetArgs),
+
+    /// Continue paused process(es) with SIGCONT
+    Resume(TargetArgs),
+
+    /// Send a signal (e.g. SIGUSR2 to reopen logs) to process(es)
+    Signal {
+        /// Process name, id, @tag, ns:namespace, or "all"
+        selector: String,
+
+        /// SIGHUP, SIGINT, SIGQUIT, SIGUSR1, SIGUSR2, SIGALRM, SIGTERM or SIGWINCH
+        signal: oxidepm_core::AppSignal,
+
+        /// Signal every process the app started, not just its main process
+        #[arg(long)]
+        tree: bool,
+    },
+
+    /// Import apps from PM2 or Docker Compose
+    Import(ImportArgs),
+
+    /// Make the running apps match a config file (start new, reload changed)
+    Apply(ApplyArgs),
+
+    /// Config file tools
+    Config(ConfigArgs),
+
+    /// Show an app's environment, or persist overrides with `set`/`unset`
+    Env(EnvArgs),
+
+    /// Run a command in an app's working directory, environment and user
+    ///
+    /// The daemon runs it, so it works against remote hosts too, e.g.
+    /// `oxidepm exec api -- npm run migrate`.
+    Exec(ExecArgs),
+
+    /// Rename an app, moving its log files to the new name
+    ///
+    /// A running app is stopped while its logs move and started again.
+    Rename {
+        /// Process name or id
+        selector: String,
+
+        /// New name
+        name: String,
+    },
+
+    /// Change settings in an app's stored spec
+    ///
+    /// Running apps keep their settings until restarted; use --restart or
+    /// --reload to apply them now.
+    Update(UpdateArgs),
+
+    /// Manage encrypted secrets referenced as `${secret:NAME}`
+    Secret(SecretArgs),
+
+    /// Show recent health check results
+    Health(HealthArgs),
+
+    /// Show the supervisor event log (starts, stops, crashes, restarts)
+    Events(EventsArgs),
+
+    /// Show the audit log of control requests (who started, stopped, changed what)
+    Audit(AuditArgs),
+
+    /// Start, stop or restart a group of apps in batches
+    Group(GroupArgs),
+
+    /// List long-running requests (builds, rollouts, resurrect), or show or wait for one
+    Operations(OperationsArgs),
+
+    /// Show a daemon and host overview (process counts, usage, load)
+    Top,
+
+    /// List ports held by running apps
+    Ports,
+
+    /// List the remote daemons in ~/.oxidepm/hosts.toml and whether they answer
+    Hosts,
+
+    /// Diagnose common problems with the local daemon (stale socket, hung daemon, DB lock, permissions)
+    Doctor {
+        /// Remove a stale socket left by a daemon that is gone
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct GroupArgs {
+    #[command(subcommand)]
+    pub command: GroupCommand,
+}
+
+#[derive(Subcommand)]
+pub enum GroupCommand {
+    /// Start the apps of a group
+    Start(RolloutArgs),
+    /// Stop the apps of a group
+    Stop(RolloutArgs),
+    /// Restart the apps of a group
+    Restart(RolloutArgs),
+}
+
+#[derive(Args)]
+pub struct RolloutArgs {
+    /// Process name, id, @tag, ns:namespace, or "all"
+    pub selector: String,
+
+    /// One app at a time (same as --batch 1)
+    #[arg(long, conflicts_with = "batch")]
+    pub serial: bool,
+
+    /// Apps handled per batch (default: all at once)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub batch: Option<u64>,
+
+    /// Wait for each batch to be running and healthy before the next one,
+    /// halting the rollout when it is not
+    #[arg(long)]
+    pub wait_healthy: bool,
+
+    /// Seconds to wait for a batch to become healthy
+    #[arg(long, value_name = "SECS", requires = "wait_healthy")]
+    pub health_timeout: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct EventsArgs {
+    /// Process name, id, @tag, ns:namespace, or "all"
+    #[arg(default_value = "all")]
+    pub selector: String,
+
+    /// Number of most recent events to show
+    #[arg(long, default_value = "50")]
+    pub last: usize,
+}
+
+#[derive(Args)]
+pub struct AuditArgs {
+    /// Number of most recent entries to show
+    #[arg(long, default_value = "100")]
+    pub last: usize,
+}
+
+#[derive(Args)]
+pub struct OperationsArgs {
+    /// Operation id; every recent operation is listed when left out
+    pub id: Option<u64>,
+
+    /// Wait for the operation to finish, showing its progress
+    #[arg(long, requires = "id")]
+    pub wait: bool,
+}
+
+#[derive(Args)]
+pub struct HealthArgs {
+    /// Process name, id, @tag, ns:namespace, or "all"
+    pub selector: String,
+
+    /// Number of recent checks to show
+    #[arg(short = 'n', long, default_value = "10")]
+    pub lines: usize,
+}
+
+#[derive(Args)]
+pub struct SecretArgs {
+    #[command(subcommand)]
+    pub command: SecretCommand,
+}
+
+#[derive(Subcommand)]
+pub enum SecretCommand {
+    /// Store a secret (value is prompted for, or read from stdin)
+    Set {
+        /// Secret name
+        name: String,
+    },
+    /// List stored secret names
+    List,
+    /// Delete a secret
+    Rm {
+        /// Secret name
+        name: String,
+    },
+}
+
+#[derive(Args)]
+pub struct EnvArgs {
+    /// Process name or id
+    pub selector: String,
+
+    #[command(subcommand)]
+    pub command: Option<EnvCommand>,
+
+    /// Show secret values instead of masking them
+    #[arg(long)]
+    pub show_secrets: bool,
+
+    /// Also list variables inherited from the daemon's environment
+    #[arg(long)]
+    pub inherited: bool,
+}
+
+#[derive(Args)]
+pub struct UpdateArgs {
+    /// Process name, id, @tag, ns:namespace, or "all"
+    pub selector: String,
+
+    /// Restart the app when its memory goes over this size, in MB unless
+    /// given a unit (e.g. 512, 1G); 0 removes the limit
+    #[arg(long, value_name = "SIZE", value_parser = parse_memory_mb)]
+    pub max_memory: Option<u64>,
+
+    /// Maximum uptime before auto-restart (e.g., "1h", "24h", "30m")
+    #[arg(long, value_parser = parse_duration)]
+    pub max_uptime: Option<u64>,
+
+    /// Enable watch mode
+    #[arg(long, conflicts_with = "no_watch")]
+    pub watch: bool,
+
+    /// Disable watch mode
+    #[arg(long)]
+    pub no_watch: bool,
+
+    /// Enable auto-restart
+    #[arg(long, conflicts_with = "no_autorestart")]
+    pub autorestart: bool,
+
+    /// Disable auto-restart
+    #[arg(long)]
+    pub no_autorestart: bool,
+
+    /// Max restarts before errored
+    #[arg(long)]
+    pub max_restarts: Option<u32>,
+
+    /// Restart delay in ms
+    #[arg(long)]
+    pub restart_delay: Option<u64>,
+
+    /// Kill timeout in ms
+    #[arg(long)]
+    pub kill_timeout: Option<u64>,
+
+    /// Port for the app, passed to it as PORT
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Health check: an http(s):// URL to poll, or a script that must exit 0
+    #[arg(long, value_name = "URL|SCRIPT")]
+    pub health_check: Option<String>,
+
+    /// Replace the app's tags (repeatable)
+    #[arg(long)]
+    pub tag: Vec<String>,
+
+    /// Restart running apps so the change takes effect now
+    #[arg(long, conflicts_with = "reload")]
+    pub restart: bool,
+
+    /// Gracefully reload running apps so the change takes effect now
+    #[arg(long)]
+    pub reload: bool,
+}
+
+#[derive(Args)]
+pub struct ExecArgs {
+    /// Process name or id
+    pub selector: String,
+
+    /// Command to run, after `--`
+    #[arg(last = true, required = true)]
+    pub command: Vec<String>,
+}
+
+#[derive(Subcommand)]
+pub enum EnvCommand {
+    /// Set variables (KEY=VALUE) in the stored spec
+    Set {
+        #[arg(required = true, value_parser = parse_env)]
+        vars: Vec<(String, String)>,
+
+        /// Restart the app so the change takes effect now
+        #[arg(long)]
+        restart: bool,
+    },
+    /// Remove variables from the stored spec
+    Unset {
+        #[arg(required = true)]
+        keys: Vec<String>,
+
+        /// Restart the app so the change takes effect now
+        #[arg(long)]
+        restart: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Check a config file for syntax errors, unknown keys and conflicting settings
+    Validate {
+        /// Config file (.toml, .yaml, .yml or .json)
+        file: PathBuf,
+
+        /// Treat warnings as errors
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct ApplyArgs {
+    /// Config file (.toml, .yaml, .yml or .json)
+    pub config: PathBuf,
+
+    /// Delete apps that are no longer declared in the config file
+    #[arg(long)]
+    pub prune: bool,
+
+    /// Show what would change without touching any process
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    #[command(subcommand)]
+    pub command: ImportCommand,
+
+    /// Start the imported apps instead of only listing them
+    #[arg(long, global = true)]
+    pub start: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ImportCommand {
+    /// Import a PM2 ecosystem file (.js/.cjs/.json) or dump.pm2
+    Pm2 {
+        /// Ecosystem file or dump (default: ./ecosystem.config.js, then ~/.pm2/dump.pm2)
+        path: Option<PathBuf>,
+
+        /// Apply the env_<name> profile, like `pm2 start --env <name>`
+        #[arg(long)]
+        env: Option<String>,
+    },
+
+    /// Import services from a Docker Compose file
+    Compose {
+        /// Compose file (default: ./compose.yaml or ./docker-compose.yml)
+        path: Option<PathBuf>,
+    },
 }
 
 #[derive(Args)]
@@ -137,6 +584,10 @@ pub enum NotifyCommand {
         /// Chat ID to send messages to
         #[arg(long)]
         chat: String,
+
+        /// Minimum severity to send (info, warning, critical)
+        #[arg(long)]
+        min_severity: Option<String>,
     },
 
     /// Remove notification configuration
@@ -147,11 +598,68 @@ pub enum NotifyCommand {
 
     /// Set which events to notify on
     Events {
-        /// Events to notify (comma-separated: start,stop,crash,restart,memory_limit,health_check)
+        /// Events to notify (comma-separated: start,stop,crash,restart,memory_limit,health_check,log_match)
         #[arg(long)]
         set: String,
     },
 
+    /// Set quiet hours during which only severe events are sent
+    Quiet {
+        /// Start of the quiet window (HH:MM, local time)
+        #[arg(long, required_unless_present = "off")]
+        from: Option<String>,
+
+        /// End of the quiet window (HH:MM, local time)
+        #[arg(long, required_unless_present = "off")]
+        to: Option<String>,
+
+        /// Minimum severity still sent during quiet hours (default: critical)
+        #[arg(long)]
+        min_severity: Option<String>,
+
+        /// Disable quiet hours
+        #[arg(long, conflicts_with_all = ["from", "to", "min_severity"])]
+        off: bool,
+    },
+
+    /// Let the Telegram bot answer commands (/status, /logs, /restart)
+    Bot {
+        /// Commands to answer (comma-separated: status,logs,restart)
+        #[arg(long)]
+        commands: Option<String>,
+
+        /// Chat allowed to send commands (repeatable; default: the notification chat)
+        #[arg(long = "allow-chat")]
+        allow_chats: Vec<String>,
+
+        /// Stop answering commands
+        #[arg(long, conflicts_with_all = ["commands", "allow_chats"])]
+        off: bool,
+    },
+
+    /// Schedule daily or weekly summary reports
+    Reports {
+        /// Report period (daily, weekly)
+        #[arg(long, default_value = "daily")]
+        period: String,
+
+        /// Local time to send the report at (HH:MM)
+        #[arg(long, default_value = "09:00")]
+        at: String,
+
+        /// Day of the week for weekly reports (mon..sun)
+        #[arg(long, default_value = "mon")]
+        weekday: String,
+
+        /// How many apps to list under top memory and CPU
+        #[arg(long, default_value_t = 3)]
+        top: usize,
+
+        /// Disable summary reports
+        #[arg(long)]
+        off: bool,
+    },
+
     /// Show current notification configuration
     Status,
 
@@ -159,6 +667,39 @@ pub enum NotifyCommand {
     Test,
 }
 
+/// Process selection for stop/restart/reload
+#[derive(Args)]
+pub struct TargetArgs {
+    /// Process name (or namespace/name), id, @tag, ns:namespace, or "all"
+    #[arg(required_unless_present = "config")]
+    pub selector: Option<String>,
+
+    /// Act on the apps declared in a config file instead of a selector
+    #[arg(long, conflicts_with = "selector")]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub selection: AppSelection,
+}
+
+/// Narrow the apps taken from a config file or a monorepo
+#[derive(Args, Default)]
+pub struct AppSelection {
+    /// Only these apps from the config file or monorepo (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    pub only: Vec<String>,
+
+    /// Skip these apps from the config file or monorepo (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    pub except: Vec<String>,
+}
+
+impl AppSelection {
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.except.is_empty()
+    }
+}
+
 #[derive(Args)]
 pub struct StartArgs {
     /// Target: file, directory, or config file (optional if --git is used)
@@ -192,6 +733,29 @@ pub struct StartArgs {
     #[arg(long)]
     pub env_file: Option<PathBuf>,
 
+    /// Restart the app when the env file changes (`reload`: replace it gracefully instead)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "ACTION",
+        num_args = 0..=1,
+        default_missing_value = "restart",
+        requires = "env_file"
+    )]
+    pub watch_env_file: Option<EnvFileWatch>,
+
+    /// Write stdout to this file instead of ~/.oxidepm/logs (/dev/null discards it)
+    #[arg(long)]
+    pub out_file: Option<PathBuf>,
+
+    /// Write stderr to this file instead of ~/.oxidepm/logs
+    #[arg(long)]
+    pub err_file: Option<PathBuf>,
+
+    /// Write stderr into the stdout log
+    #[arg(long)]
+    pub merge_logs: bool,
+
     /// Enable watch mode
     #[arg(long)]
     pub watch: bool,
@@ -200,6 +764,18 @@ pub struct StartArgs {
     #[arg(long)]
     pub ignore: Vec<String>,
 
+    /// Watch only this file or directory, relative to cwd (repeatable)
+    #[arg(long)]
+    pub watch_path: Vec<PathBuf>,
+
+    /// Only restart on changes to files with these extensions (comma-separated or repeatable)
+    #[arg(long, value_delimiter = ',')]
+    pub watch_ext: Vec<String>,
+
+    /// Poll for changes every MS milliseconds (default 1000) instead of native file events, for NFS/Docker volumes
+    #[arg(long, value_name = "MS", num_args = 0..=1, default_missing_value = "1000")]
+    pub watch_poll: Option<u64>,
+
     /// Restart delay in ms
     #[arg(long, default_value = "500")]
     pub restart_delay: u64,
@@ -212,11 +788,15 @@ pub struct StartArgs {
     #[arg(long, default_value = "3000")]
     pub kill_timeout: u64,
 
+    /// Abandon the start if building takes longer than this many seconds (default: 1800)
+    #[arg(long, value_name = "SECS")]
+    pub build_timeout: Option<u64>,
+
     /// Disable auto-restart
     #[arg(long)]
     pub no_autorestart: bool,
 
-    /// Force mode: rust, cargo, node, npm, pnpm, yarn, cmd
+    /// Force mode: rust, cargo, node, npm, pnpm, yarn, cmd, binary
     #[arg(long)]
     pub mode: Option<String>,
 
@@ -224,14 +804,59 @@ pub struct StartArgs {
     #[arg(long)]
     pub script: Option<String>,
 
+    /// Run the script in this workspace package (name or path) of a pnpm/yarn/npm monorepo
+    #[arg(long)]
+    pub workspace: Option<String>,
+
     /// Binary name for cargo mode
     #[arg(long)]
     pub bin: Option<String>,
 
+    /// Cargo features to build with (comma-separated or repeatable)
+    #[arg(long, value_delimiter = ',')]
+    pub cargo_features: Vec<String>,
+
+    /// Cargo profile to build with: dev, release (default) or a custom profile
+    #[arg(long)]
+    pub cargo_profile: Option<String>,
+
+    /// Target triple to build for, e.g. x86_64-unknown-linux-musl
+    #[arg(long)]
+    pub cargo_target: Option<String>,
+
+    /// Binary mode: restart when the executable is replaced on disk (default: only warn)
+    #[arg(long)]
+    pub reload_on_change: bool,
+
     /// Tag for process grouping (repeatable, use @tag to select)
     #[arg(long)]
     pub tag: Vec<String>,
 
+    /// Namespace to start the app(s) in (select with ns:<namespace>)
+    #[arg(long)]
+    pub namespace: Option<String>,
+
+    /// Number of instances for clustering
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub instances: u32,
+
+    /// Port for the app, passed to it as PORT (instances get consecutive ports)
+    #[arg(long, conflicts_with = "auto_port")]
+    pub port: Option<u16>,
+
+    /// Let the daemon pick a free port and pass it to the app as PORT
+    #[arg(long)]
+    pub auto_port: bool,
+
+    /// Health check: an http(s):// URL to poll, or a script that must exit 0
+    #[arg(long, value_name = "URL|SCRIPT")]
+    pub health_check: Option<String>,
+
+    /// Restart the app when its memory goes over this size, in MB unless
+    /// given a unit (e.g. 512, 1G)
+    #[arg(long, value_name = "SIZE", value_parser = parse_memory_mb)]
+    pub max_memory: Option<u64>,
+
     /// Maximum uptime before auto-restart (e.g., "1h", "24h", "30m")
     #[arg(long, value_parser = parse_duration)]
     pub max_uptime: Option<u64>,
@@ -240,6 +865,19 @@ pub struct StartArgs {
     #[arg(long = "delay")]
     pub startup_delay: Option<u64>,
 
+    /// Wait for a service or file before launching: tcp://host:port, an
+    /// http(s) URL answering 2xx, or a path (repeatable)
+    #[arg(long, value_name = "TARGET", value_parser = parse_wait_target)]
+    pub wait_for: Vec<String>,
+
+    /// Give up on --wait-for after this long (e.g. "30s", "5m"; default: 60s)
+    #[arg(long, value_parser = parse_duration, requires = "wait_for")]
+    pub wait_for_timeout: Option<u64>,
+
+    /// Start band on resurrect: lower priorities start first (default: 0)
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub priority: i32,
+
     /// Inherit environment variables from parent process
     #[arg(long)]
     pub env_inherit: bool,
@@ -260,6 +898,18 @@ pub struct StartArgs {
     #[arg(long)]
     pub on_crash: Option<String>,
 
+    /// Script to run during a reload, before the old process is stopped
+    #[arg(long)]
+    pub on_pre_reload: Option<String>,
+
+    /// Script to run after a reload has completed
+    #[arg(long)]
+    pub on_post_reload: Option<String>,
+
+    /// User to run hooks as (default: the daemon's user)
+    #[arg(long)]
+    pub hook_user: Option<String>,
+
     /// Auto-setup: install dependencies, create .env from template before starting
     #[arg(long)]
     pub setup: bool,
@@ -268,6 +918,13 @@ pub struct StartArgs {
     #[arg(long)]
     pub no_check: bool,
 
+    /// Print the app spec that would be sent to the daemon instead of starting anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    #[command(flatten)]
+    pub selection: AppSelection,
+
     /// Additional arguments passed to process
     #[arg(last = true)]
     pub args: Vec<String>,
@@ -282,9 +939,13 @@ pub struct LogsArgs {
     #[arg(short, long)]
     pub follow: bool,
 
-    /// Number of lines to show
-    #[arg(long, default_value = "15")]
-    pub lines: usize,
+    /// Number of lines to show (default: 15, or all with --since)
+    #[arg(long)]
+    pub lines: Option<usize>,
+
+    /// Show lines written within this long (e.g. 30m, 2h, 1d), including rotated files
+    #[arg(long, value_parser = parse_duration)]
+    pub since: Option<u64>,
 
     /// Show only stdout
     #[arg(long)]
@@ -299,6 +960,70 @@ pub struct LogsArgs {
     pub grep: Option<String>,
 }
 
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Show extended info (cwd, port)
+    #[arg(long)]
+    pub more: bool,
+
+    /// Filter rows by KEY=VALUE (status, name, mode), repeatable
+    #[arg(long = "filter", value_name = "KEY=VALUE", value_parser = parse_status_filter)]
+    pub filters: Vec<StatusFilter>,
+
+    /// Only show apps with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Only show apps in this namespace
+    #[arg(long)]
+    pub namespace: Option<String>,
+
+    /// Sort rows
+    #[arg(long, value_enum)]
+    pub sort: Option<StatusSort>,
+
+    /// Refresh the table every N seconds (default 2) until interrupted
+    #[arg(long, value_name = "SECS", num_args = 0..=1, default_missing_value = "2")]
+    pub watch: Option<u64>,
+
+    /// Query the local daemon and every configured host, merging the results
+    #[arg(long)]
+    pub all_hosts: bool,
+}
+
+/// Status table filter
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusFilter {
+    Status(String),
+    Name(String),
+    Mode(String),
+}
+
+/// Status table sort order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatusSort {
+    Cpu,
+    Mem,
+    Uptime,
+    Name,
+}
+
+/// What to do when a watched env file changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EnvFileWatch {
+    Restart,
+    Reload,
+}
+
+impl From<EnvFileWatch> for oxidepm_core::EnvFileAction {
+    fn from(watch: EnvFileWatch) -> Self {
+        match watch {
+            EnvFileWatch::Restart => Self::Restart,
+            EnvFileWatch::Reload => Self::Reload,
+        }
+    }
+}
+
 #[derive(Clone, Copy, ValueEnum)]
 pub enum StartupTarget {
     Systemd,
@@ -314,6 +1039,38 @@ pub struct WebArgs {
     /// API key for authentication (optional)
     #[arg(long)]
     pub api_key: Option<String>,
+
+    /// Also serve these hosts from hosts.toml under /api/hosts/<name> ("all" for every host)
+    #[arg(long, value_name = "NAMES")]
+    pub hosts: Option<String>,
+}
+
+#[derive(Args)]
+pub struct GrpcArgs {
+    /// Port for the gRPC API server
+    #[arg(short, long, default_value = "50051")]
+    pub port: u16,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum McpTransport {
+    Stdio,
+    Sse,
+}
+
+#[derive(Args)]
+pub struct McpArgs {
+    /// How MCP clients connect
+    #[arg(long, value_enum, default_value = "stdio")]
+    pub transport: McpTransport,
+
+    /// Port for the SSE transport (bound to localhost)
+    #[arg(short, long, default_value = "9616")]
+    pub port: u16,
+
+    /// Tools the assistant may call: names, "read-only" and/or "all", comma separated
+    #[arg(long, default_value = "read-only", value_parser = parse_allowlist)]
+    pub allow: oxidepm_mcp::Allowlist,
 }
 
 #[derive(Args)]
@@ -321,13 +1078,18 @@ pub struct CheckArgs {
     /// Target: file or directory to check
     pub target: String,
 
-    /// Auto-fix issues (run npm install, create .env from template)
+    /// Auto-fix issues (run npm install, create .env from template, install
+    /// a matching Node.js with nvm or update Rust with rustup)
     #[arg(long)]
     pub fix: bool,
 
     /// Set environment variable (can be used multiple times, KEY=VALUE format)
     #[arg(long = "set-env", value_parser = parse_env)]
     pub set_envs: Vec<(String, String)>,
+
+    /// Don't try to reach the services DATABASE_URL and REDIS_URL in .env point at
+    #[arg(long)]
+    pub no_probe: bool,
 }
 
 fn parse_env(s: &str) -> Result<(String, String), String> {
@@ -335,6 +1097,48 @@ fn parse_env(s: &str) -> Result<(String, String), String> {
     Ok((s[..pos].to_string(), s[pos + 1..].to_string()))
 }
 
+/// Parse a status filter like "status=running"
+fn parse_status_filter(s: &str) -> Result<StatusFilter, String> {
+    let (key, value) = parse_env(s)?;
+    let value = value.to_lowercase();
+    match key.to_lowercase().as_str() {
+        "status" => Ok(StatusFilter::Status(value)),
+        "name" => Ok(StatusFilter::Name(value)),
+        "mode" => Ok(StatusFilter::Mode(value)),
+        other => Err(format!(
+            "Unknown filter key '{}' (expected status, name or mode)",
+            other
+        )),
+    }
+}
+
+/// Parse an MCP tool allowlist like "read-only,restart_process"
+fn parse_allowlist(s: &str) -> Result<oxidepm_mcp::Allowlist, String> {
+    oxidepm_mcp::Allowlist::parse(s)
+}
+
+/// Parse sizes like "100M", "1G" or a plain number of bytes
+fn parse_size(s: &str) -> Result<u64, String> {
+    oxidepm_core::constants::parse_size(s)
+        .ok_or_else(|| format!("Invalid size '{}' (e.g. 500K, 100M, 1G)", s))
+}
+
+/// Parse a memory limit into MB: a plain number is MB, "1G" or "512M" is
+/// rounded up to whole MB
+fn parse_memory_mb(s: &str) -> Result<u64, String> {
+    if let Ok(mb) = s.trim().parse::<u64>() {
+        return Ok(mb);
+    }
+    parse_size(s).map(|bytes| bytes.div_ceil(1024 * 1024))
+}
+
+/// Check a `--wait-for` target, keeping it as given
+fn parse_wait_target(s: &str) -> Result<String, String> {
+    s.parse::<oxidepm_core::WaitTarget>()
+        .map(|_| s.trim().to_string())
+        .map_err(|e| e.to_string())
+}
+
 /// Parse duration strings like "1h", "30m", "2d", "24h30m" into seconds
 fn parse_duration(s: &str) -> Result<u64, String> {
     let s = s.trim().to_lowercase();
@@ -387,6 +1191,494 @@ fn parse_duration(s: &str) -> Result<u64, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_target_args() {
+        let cli = Cli::try_parse_from([
+            "oxidepm",
+            "stop",
+            "--config",
+            "apps.toml",
+            "--only",
+            "api,web",
+        ])
+        .unwrap();
+        let Commands::Stop { target, if_exists } = cli.command else {
+            panic!("expected stop")
+        };
+        assert!(target.selector.is_none());
+        assert_eq!(target.selection.only, vec!["api", "web"]);
+        assert!(!if_exists);
+
+        let cli = Cli::try_parse_from(["oxidepm", "stop", "api", "--if-exists"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Stop {
+                if_exists: true,
+                ..
+            }
+        ));
+
+        assert!(Cli::try_parse_from(["oxidepm", "restart"]).is_err());
+        let cli = Cli::try_parse_from(["oxidepm", "restart", "api", "--reset-counter"]).unwrap();
+        let Commands::Restart {
+            target,
+            reset_counter,
+            ..
+        } = cli.command
+        else {
+            panic!("expected restart")
+        };
+        assert_eq!(target.selector.as_deref(), Some("api"));
+        assert!(reset_counter);
+
+        let cli = Cli::try_parse_from([
+            "oxidepm",
+            "restart",
+            "@web",
+            "--parallel",
+            "4",
+            "--continue-on-error",
+        ])
+        .unwrap();
+        let Commands::Restart {
+            parallel,
+            continue_on_error,
+            ..
+        } = cli.command
+        else {
+            panic!("expected restart")
+        };
+        assert_eq!(parallel, Some(4));
+        assert!(continue_on_error);
+        assert!(Cli::try_parse_from(["oxidepm", "restart", "@web", "--parallel", "0"]).is_err());
+
+        assert!(
+            Cli::try_parse_from(["oxidepm", "restart", "api", "--config", "apps.toml"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_start_dry_run_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "start", "app.js", "--dry-run"]).unwrap();
+        let Commands::Start(args) = cli.command else {
+            panic!("expected start")
+        };
+        assert!(args.dry_run);
+        assert_eq!(args.target.as_deref(), Some("app.js"));
+    }
+
+    #[test]
+    fn test_start_cluster_args() {
+        let cli = Cli::try_parse_from([
+            "oxidepm",
+            "start",
+            "./server",
+            "-i",
+            "4",
+            "--port",
+            "3000",
+            "--health-check",
+            "http://localhost:3000/health",
+            "--max-memory",
+            "512M",
+        ])
+        .unwrap();
+        let Commands::Start(args) = cli.command else {
+            panic!("expected start")
+        };
+        assert_eq!(args.instances, 4);
+        assert_eq!(args.port, Some(3000));
+        assert_eq!(args.max_memory, Some(512));
+
+        assert!(Cli::try_parse_from(["oxidepm", "start", "app.js", "-i", "0"]).is_err());
+        assert!(
+            Cli::try_parse_from(["oxidepm", "start", "app.js", "--port", "80", "--auto-port"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_home_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "status", "--profile", "work"]).unwrap();
+        assert_eq!(cli.profile.as_deref(), Some("work"));
+        let cli = Cli::try_parse_from(["oxidepm", "--home", "/srv/pm", "ping"]).unwrap();
+        assert_eq!(cli.home, Some(PathBuf::from("/srv/pm")));
+
+        assert!(
+            Cli::try_parse_from(["oxidepm", "ping", "--home", "/srv/pm", "--profile", "work"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_flush_args() {
+        let cli = Cli::try_parse_from([
+            "oxidepm",
+            "flush",
+            "all",
+            "--older-than",
+            "7d",
+            "--keep",
+            "100M",
+        ])
+        .unwrap();
+        let Commands::Flush {
+            selector,
+            older_than,
+            keep,
+        } = cli.command
+        else {
+            panic!("expected flush")
+        };
+        assert_eq!(selector, "all");
+        assert_eq!(older_than, Some(7 * 86400));
+        assert_eq!(keep, Some(100 * 1024 * 1024));
+
+        assert!(Cli::try_parse_from(["oxidepm", "flush", "all", "--keep", "lots"]).is_err());
+    }
+
+    #[test]
+    fn test_mcp_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "mcp"]).unwrap();
+        let Commands::Mcp(args) = cli.command else {
+            panic!("expected mcp")
+        };
+        assert!(matches!(args.transport, McpTransport::Stdio));
+        assert_eq!(args.allow, oxidepm_mcp::Allowlist::read_only());
+
+        let cli = Cli::try_parse_from([
+            "oxidepm",
+            "mcp",
+            "--transport",
+            "sse",
+            "--allow",
+            "read-only,restart_process",
+        ])
+        .unwrap();
+        let Commands::Mcp(args) = cli.command else {
+            panic!("expected mcp")
+        };
+        assert!(matches!(args.transport, McpTransport::Sse));
+        assert!(args.allow.allows(oxidepm_mcp::Tool::RestartProcess));
+        assert!(!args.allow.allows(oxidepm_mcp::Tool::StopProcess));
+
+        assert!(Cli::try_parse_from(["oxidepm", "mcp", "--allow", "shell"]).is_err());
+    }
+
+    #[test]
+    fn test_host_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "status", "--all-hosts"]).unwrap();
+        assert!(cli.host.is_none());
+        let Commands::Status(args) = cli.command else {
+            panic!("expected status")
+        };
+        assert!(args.all_hosts);
+
+        // --host is global, so it may follow the subcommand
+        let cli = Cli::try_parse_from(["oxidepm", "restart", "api", "--host", "web1"]).unwrap();
+        assert_eq!(cli.host.as_deref(), Some("web1"));
+        assert!(matches!(cli.command, Commands::Restart { .. }));
+
+        assert!(Cli::try_parse_from(["oxidepm", "hosts"]).is_ok());
+        assert!(matches!(
+            Cli::try_parse_from(["oxidepm", "save", "--output", "backup.json"]).unwrap().command,
+            Commands::Save { output: Some(path) } if path.as_os_str() == "backup.json"
+        ));
+        assert!(matches!(
+            Cli::try_parse_from(["oxidepm", "resurrect", "--all"])
+                .unwrap()
+                .command,
+            Commands::Resurrect {
+                file: None,
+                all: true
+            }
+        ));
+        assert!(matches!(
+            Cli::try_parse_from(["oxidepm", "doctor", "--fix"])
+                .unwrap()
+                .command,
+            Commands::Doctor { fix: true }
+        ));
+
+        let cli = Cli::try_parse_from([
+            "oxidepm",
+            "group",
+            "restart",
+            "@web",
+            "--batch",
+            "2",
+            "--wait-healthy",
+        ])
+        .unwrap();
+        let Commands::Group(GroupArgs {
+            command: GroupCommand::Restart(args),
+        }) = cli.command
+        else {
+            panic!("expected group restart")
+        };
+        assert_eq!(args.selector, "@web");
+        assert_eq!(args.batch, Some(2));
+        assert!(args.wait_healthy && !args.serial);
+        assert!(Cli::try_parse_from([
+            "oxidepm", "group", "stop", "@web", "--serial", "--batch", "2"
+        ])
+        .is_err());
+        assert!(
+            Cli::try_parse_from(["oxidepm", "group", "start", "@web", "--batch", "0"]).is_err()
+        );
+        assert!(Cli::try_parse_from([
+            "oxidepm",
+            "group",
+            "start",
+            "@web",
+            "--health-timeout",
+            "5"
+        ])
+        .is_err());
+
+        let cli = Cli::try_parse_from(["oxidepm", "web", "--hosts", "web1,web2"]).unwrap();
+        let Commands::Web(args) = cli.command else {
+            panic!("expected web")
+        };
+        assert_eq!(args.hosts.as_deref(), Some("web1,web2"));
+    }
+
+    #[test]
+    fn test_drain_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "drain", "@web"]).unwrap();
+        let Commands::Drain { selector, timeout } = cli.command else {
+            panic!("expected drain")
+        };
+        assert_eq!(selector, "@web");
+        assert_eq!(timeout, oxidepm_core::constants::DEFAULT_DRAIN_TIMEOUT_SECS);
+
+        let cli = Cli::try_parse_from(["oxidepm", "drain", "api", "--timeout", "5m"]).unwrap();
+        let Commands::Drain { timeout, .. } = cli.command else {
+            panic!("expected drain")
+        };
+        assert_eq!(timeout, 300);
+    }
+
+    #[test]
+    fn test_pause_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "pause", "api"]).unwrap();
+        let Commands::Pause(args) = cli.command else {
+            panic!("expected pause")
+        };
+        assert_eq!(args.selector.as_deref(), Some("api"));
+
+        let cli =
+            Cli::try_parse_from(["oxidepm", "resume", "--config", "app.toml", "--only", "api"])
+                .unwrap();
+        let Commands::Resume(args) = cli.command else {
+            panic!("expected resume")
+        };
+        assert_eq!(args.selection.only, vec!["api"]);
+
+        // Daemon-wide supervision keeps its own commands
+        let cli = Cli::try_parse_from(["oxidepm", "pause-supervision"]).unwrap();
+        assert!(matches!(cli.command, Commands::PauseSupervision));
+        let cli = Cli::try_parse_from(["oxidepm", "daemon-reload"]).unwrap();
+        assert!(matches!(cli.command, Commands::DaemonReload));
+    }
+
+    #[test]
+    fn test_signal_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "signal", "api", "usr2", "--tree"]).unwrap();
+        let Commands::Signal {
+            selector,
+            signal,
+            tree,
+        } = cli.command
+        else {
+            panic!("expected signal")
+        };
+        assert_eq!(selector, "api");
+        assert_eq!(signal, oxidepm_core::AppSignal::Sigusr2);
+        assert!(tree);
+
+        assert!(Cli::try_parse_from(["oxidepm", "signal", "api", "SIGKILL"]).is_err());
+        assert!(Cli::try_parse_from(["oxidepm", "signal", "api", "SIGNOPE"]).is_err());
+    }
+
+    #[test]
+    fn test_exec_args() {
+        let cli =
+            Cli::try_parse_from(["oxidepm", "exec", "api", "--", "npm", "run", "migrate"]).unwrap();
+        let Commands::Exec(args) = cli.command else {
+            panic!("expected exec")
+        };
+        assert_eq!(args.selector, "api");
+        assert_eq!(args.command, vec!["npm", "run", "migrate"]);
+
+        assert!(Cli::try_parse_from(["oxidepm", "exec", "api"]).is_err());
+    }
+
+    #[test]
+    fn test_env_args() {
+        let cli =
+            Cli::try_parse_from(["oxidepm", "env", "api", "set", "A=1", "B=x=y", "--restart"])
+                .unwrap();
+        let Commands::Env(args) = cli.command else {
+            panic!("expected env")
+        };
+        assert_eq!(args.selector, "api");
+        let Some(EnvCommand::Set { vars, restart }) = args.command else {
+            panic!("expected set")
+        };
+        assert_eq!(
+            vars,
+            vec![("A".into(), "1".into()), ("B".into(), "x=y".into())]
+        );
+        assert!(restart);
+
+        let cli = Cli::try_parse_from(["oxidepm", "env", "api", "--show-secrets"]).unwrap();
+        let Commands::Env(args) = cli.command else {
+            panic!("expected env")
+        };
+        assert!(args.command.is_none() && args.show_secrets);
+
+        assert!(Cli::try_parse_from(["oxidepm", "env", "api", "set", "NOEQUALS"]).is_err());
+    }
+
+    #[test]
+    fn test_update_args() {
+        let cli = Cli::try_parse_from([
+            "oxidepm",
+            "update",
+            "api",
+            "--max-memory",
+            "512",
+            "--watch",
+            "--tag",
+            "web",
+            "--reload",
+        ])
+        .unwrap();
+        let Commands::Update(args) = cli.command else {
+            panic!("expected update")
+        };
+        assert_eq!(args.selector, "api");
+        assert_eq!(args.max_memory, Some(512));
+        assert!(args.watch && !args.no_watch);
+        assert_eq!(args.tag, vec!["web".to_string()]);
+        assert!(args.reload && !args.restart);
+
+        assert_eq!(parse_memory_mb("1G"), Ok(1024));
+        assert_eq!(parse_memory_mb("1500K"), Ok(2));
+        assert!(
+            Cli::try_parse_from(["oxidepm", "update", "api", "--watch", "--no-watch"]).is_err()
+        );
+        assert!(
+            Cli::try_parse_from(["oxidepm", "update", "api", "--restart", "--reload"]).is_err()
+        );
+
+        let cli = Cli::try_parse_from(["oxidepm", "rename", "api", "gateway"]).unwrap();
+        assert!(
+            matches!(cli.command, Commands::Rename { selector, name } if selector == "api" && name == "gateway")
+        );
+        assert!(Cli::try_parse_from(["oxidepm", "rename", "api"]).is_err());
+    }
+
+    #[test]
+    fn test_secret_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "secret", "set", "DB_PASSWORD"]).unwrap();
+        let Commands::Secret(args) = cli.command else {
+            panic!("expected secret")
+        };
+        assert!(matches!(args.command, SecretCommand::Set { name } if name == "DB_PASSWORD"));
+
+        assert!(Cli::try_parse_from(["oxidepm", "secret", "list"]).is_ok());
+        assert!(Cli::try_parse_from(["oxidepm", "secret", "set"]).is_err());
+    }
+
+    #[test]
+    fn test_health_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "health", "api", "-n", "3"]).unwrap();
+        let Commands::Health(args) = cli.command else {
+            panic!("expected health")
+        };
+        assert_eq!(args.selector, "api");
+        assert_eq!(args.lines, 3);
+
+        let cli = Cli::try_parse_from(["oxidepm", "health", "all"]).unwrap();
+        let Commands::Health(args) = cli.command else {
+            panic!("expected health")
+        };
+        assert_eq!(args.lines, 10);
+    }
+
+    #[test]
+    fn test_logs_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "logs", "api", "--since", "2h"]).unwrap();
+        let Commands::Logs(args) = cli.command else {
+            panic!("expected logs")
+        };
+        assert_eq!(args.since, Some(2 * 3600));
+        assert_eq!(args.lines, None);
+
+        assert!(Cli::try_parse_from(["oxidepm", "logs", "api", "--since", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_events_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "events", "api", "--last", "5"]).unwrap();
+        let Commands::Events(args) = cli.command else {
+            panic!("expected events")
+        };
+        assert_eq!(args.selector, "api");
+        assert_eq!(args.last, 5);
+
+        let cli = Cli::try_parse_from(["oxidepm", "events"]).unwrap();
+        let Commands::Events(args) = cli.command else {
+            panic!("expected events")
+        };
+        assert_eq!(args.selector, "all");
+        assert_eq!(args.last, oxidepm_core::constants::DEFAULT_EVENTS_LIMIT);
+    }
+
+    #[test]
+    fn test_audit_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "audit", "--last", "20"]).unwrap();
+        let Commands::Audit(args) = cli.command else {
+            panic!("expected audit")
+        };
+        assert_eq!(args.last, 20);
+
+        let cli = Cli::try_parse_from(["oxidepm", "audit"]).unwrap();
+        let Commands::Audit(args) = cli.command else {
+            panic!("expected audit")
+        };
+        assert_eq!(args.last, oxidepm_core::constants::DEFAULT_AUDIT_LIMIT);
+    }
+
+    #[test]
+    fn test_operations_args() {
+        let cli = Cli::try_parse_from(["oxidepm", "operations", "3", "--wait"]).unwrap();
+        let Commands::Operations(args) = cli.command else {
+            panic!("expected operations")
+        };
+        assert_eq!(args.id, Some(3));
+        assert!(args.wait);
+
+        // Waiting needs an operation to wait for
+        assert!(Cli::try_parse_from(["oxidepm", "operations", "--wait"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_status_filter() {
+        assert_eq!(
+            parse_status_filter("status=Running").unwrap(),
+            StatusFilter::Status("running".to_string())
+        );
+        assert_eq!(
+            parse_status_filter("name=api").unwrap(),
+            StatusFilter::Name("api".to_string())
+        );
+        assert!(parse_status_filter("cpu=10").is_err());
+        assert!(parse_status_filter("status").is_err());
+    }
+
     #[test]
     fn test_parse_duration_hours() {
         assert_eq!(parse_duration("1h").unwrap(), 3600);
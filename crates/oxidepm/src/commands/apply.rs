@@ -0,0 +1,93 @@
+//! Apply command implementation
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use oxidepm_core::{ApplyReport, ConfigFile};
+use oxidepm_ipc::{Request, Response};
+use std::path::Path;
+
+use crate::cli::ApplyArgs;
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_success};
+
+pub async fn execute(args: ApplyArgs) -> Result<()> {
+    let path = args.config.canonicalize().unwrap_or(args.config.clone());
+    let config = ConfigFile::load(&path)?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let specs = config.into_specs(base_dir)?;
+
+    let client = super::get_client();
+    let response = client
+        .send(&Request::Apply {
+            specs,
+            prune: args.prune,
+            dry_run: args.dry_run,
+        })
+        .await?;
+
+    match response {
+        Response::Applied { report } => {
+            print_report(&report);
+            if report.failed.is_empty() {
+                Ok(())
+            } else {
+                bail!("{} app(s) failed to apply", report.failed.len())
+            }
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
+
+fn print_report(report: &ApplyReport) {
+    if is_json_mode() {
+        if let Ok(json) = serde_json::to_string_pretty(report) {
+            println!("{}", json);
+        }
+        return;
+    }
+
+    let (start, reload, delete) = if report.dry_run {
+        ("would start", "would reload", "would delete")
+    } else {
+        ("started", "reloaded", "deleted")
+    };
+    for name in &report.started {
+        println!("  {} {} {}", "+".green(), name, start.dimmed());
+    }
+    for name in &report.reloaded {
+        println!("  {} {} {}", "~".yellow(), name, reload.dimmed());
+    }
+    for name in &report.deleted {
+        println!("  {} {} {}", "-".red(), name, delete.dimmed());
+    }
+    for name in &report.unchanged {
+        println!("  {} {} {}", "=".dimmed(), name, "unchanged".dimmed());
+    }
+    for failure in &report.failed {
+        print_error(failure);
+    }
+
+    if !report.has_changes() {
+        print_success("Everything is up to date");
+    } else if report.dry_run {
+        println!("\nDry run: no changes made");
+    } else {
+        print_success(&format!(
+            "Applied: {} started, {} reloaded, {} deleted",
+            report.started.len(),
+            report.reloaded.len(),
+            report.deleted.len()
+        ));
+    }
+}
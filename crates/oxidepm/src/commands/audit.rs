@@ -0,0 +1,65 @@
+//! Audit command implementation - show who changed what on the daemon
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use oxidepm_core::AuditEntry;
+use oxidepm_ipc::{Request, Response};
+
+use crate::cli::AuditArgs;
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_info};
+
+pub async fn execute(args: AuditArgs) -> Result<()> {
+    let client = super::get_client();
+
+    match client.send(&Request::Audit { limit: args.last }).await? {
+        Response::Audit { entries } => {
+            if is_json_mode() {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else if entries.is_empty() {
+                print_info("No control requests recorded yet");
+            } else {
+                for entry in &entries {
+                    print_entry(entry);
+                }
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
+
+fn print_entry(entry: &AuditEntry) {
+    let time = entry
+        .timestamp
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M:%S");
+    let actor = match &entry.client {
+        Some(client) => format!("{} via {}", entry.actor, client),
+        None => entry.actor.clone(),
+    };
+    let result = if entry.success {
+        entry.result.green()
+    } else {
+        entry.result.red()
+    };
+    println!(
+        "{} {} {} {} {}",
+        time.to_string().dimmed(),
+        actor,
+        entry.action.bold(),
+        entry.target.as_deref().unwrap_or("-"),
+        result
+    );
+}
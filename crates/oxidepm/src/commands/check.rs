@@ -4,115 +4,14 @@ use anyhow::Result;
 use colored::Colorize;
 use serde::Serialize;
 use std::fs;
-use std::net::TcpListener;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+
+use oxidepm_core::{CheckResult, CheckStatus};
+use oxidepm_runtime::preflight::{detect_project_type, probe_dependencies, run_preflight_checks};
 
 use crate::cli::CheckArgs;
 use crate::output::is_json_mode;
 
-/// Summary of preflight check results - used by start command
-#[derive(Debug)]
-pub struct PreflightSummary {
-    pub results: Vec<CheckResult>,
-    pub warnings: usize,
-    pub errors: usize,
-    pub has_blocking_issues: bool,
-}
-
-impl PreflightSummary {
-    /// Check if there are issues that would prevent starting
-    pub fn can_start(&self) -> bool {
-        !self.has_blocking_issues
-    }
-
-    /// Get a user-friendly error message for blocking issues
-    pub fn blocking_message(&self) -> String {
-        let blocking: Vec<&CheckResult> = self.results.iter()
-            .filter(|r| r.status == CheckStatus::Warn || r.status == CheckStatus::Error)
-            .collect();
-
-        let mut msg = String::from("Cannot start - missing dependencies:\n");
-        for result in blocking {
-            msg.push_str(&format!("  - {}\n", result.message));
-        }
-        msg
-    }
-}
-
-/// Run preflight checks on a project directory (called by both check and start commands)
-pub fn run_preflight_checks(project_dir: &Path, auto_fix: bool) -> PreflightSummary {
-    let mut results: Vec<CheckResult> = Vec::new();
-
-    // Detect project type
-    let project_type = detect_project_type(project_dir);
-
-    // Run checks based on project type
-    match project_type {
-        ProjectType::NodeJs => {
-            check_nodejs_project(project_dir, auto_fix, &mut results);
-        }
-        ProjectType::Cargo => {
-            check_cargo_project(project_dir, auto_fix, &mut results);
-        }
-        ProjectType::Generic => {
-            // Still run generic checks
-        }
-    }
-
-    // Run generic checks for all project types
-    check_env_files(project_dir, auto_fix, &mut results);
-
-    // Calculate statistics
-    let warnings = results.iter().filter(|r| r.status == CheckStatus::Warn).count();
-    let errors = results.iter().filter(|r| r.status == CheckStatus::Error).count();
-
-    // Blocking issues are warnings about missing deps (node_modules) or errors
-    let has_blocking_issues = results.iter().any(|r| {
-        r.status == CheckStatus::Error ||
-        (r.status == CheckStatus::Warn && r.message.contains("node_modules"))
-    });
-
-    PreflightSummary {
-        results,
-        warnings,
-        errors,
-        has_blocking_issues,
-    }
-}
-
-/// Result of a single check
-#[derive(Debug, Clone, Serialize)]
-pub struct CheckResult {
-    pub status: CheckStatus,
-    pub message: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fix_hint: Option<String>,
-}
-
-/// Status of a single check result
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
-#[serde(rename_all = "lowercase")]
-pub enum CheckStatus {
-    Ok,
-    Warn,
-    Error,
-    Info,
-    Fixed,
-}
-
-impl CheckStatus {
-    fn prefix(&self) -> String {
-        match self {
-            CheckStatus::Ok => format!("[{}]", "OK".green()),
-            CheckStatus::Warn => format!("[{}]", "WARN".yellow()),
-            CheckStatus::Error => format!("[{}]", "ERROR".red()),
-            CheckStatus::Info => format!("[{}]", "INFO".cyan()),
-            CheckStatus::Fixed => format!("[{}]", "FIX".blue()),
-        }
-    }
-}
-
 /// JSON output for check results
 #[derive(Serialize)]
 struct CheckOutput {
@@ -124,24 +23,6 @@ struct CheckOutput {
     fixed: usize,
 }
 
-/// Project type detected
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ProjectType {
-    NodeJs,
-    Cargo,
-    Generic,
-}
-
-impl ProjectType {
-    fn as_str(&self) -> &'static str {
-        match self {
-            ProjectType::NodeJs => "nodejs",
-            ProjectType::Cargo => "cargo",
-            ProjectType::Generic => "generic",
-        }
-    }
-}
-
 pub async fn execute(args: CheckArgs) -> Result<()> {
     let target_path = PathBuf::from(&args.target).canonicalize().unwrap_or_else(|_| {
         PathBuf::from(&args.target)
@@ -159,8 +40,6 @@ pub async fn execute(args: CheckArgs) -> Result<()> {
         std::process::exit(1);
     }
 
-    let mut results: Vec<CheckResult> = Vec::new();
-
     // Detect project type
     let project_type = detect_project_type(&project_dir);
 
@@ -168,21 +47,8 @@ pub async fn execute(args: CheckArgs) -> Result<()> {
         println!("Checking project: {}\n", project_dir.display().to_string().cyan());
     }
 
-    // Run checks based on project type
-    match project_type {
-        ProjectType::NodeJs => {
-            check_nodejs_project(&project_dir, args.fix, &mut results);
-        }
-        ProjectType::Cargo => {
-            check_cargo_project(&project_dir, args.fix, &mut results);
-        }
-        ProjectType::Generic => {
-            // Still run generic checks
-        }
-    }
-
-    // Run generic checks for all project types
-    check_env_files(&project_dir, args.fix, &mut results);
+    // Project and .env checks, as run before a start, then the extras
+    let mut results = run_preflight_checks(&project_dir, args.fix).results;
     check_config_files(&project_dir, &mut results);
 
     // Handle --set-env
@@ -190,6 +56,11 @@ pub async fn execute(args: CheckArgs) -> Result<()> {
         handle_set_env(&project_dir, &args.set_envs, &mut results);
     }
 
+    // Databases and caches the app will connect to
+    if !args.no_probe {
+        results.extend(probe_dependencies(&project_dir));
+    }
+
     // Calculate statistics
     let warnings = results.iter().filter(|r| r.status == CheckStatus::Warn).count();
     let errors = results.iter().filter(|r| r.status == CheckStatus::Error).count();
@@ -208,7 +79,7 @@ pub async fn execute(args: CheckArgs) -> Result<()> {
         println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
         for result in &results {
-            println!("{} {}", result.status.prefix(), result.message);
+            println!("{} {}", status_prefix(result.status), result.message);
             if let Some(hint) = &result.fix_hint {
                 if result.status == CheckStatus::Warn || result.status == CheckStatus::Error {
                     println!("       {}", hint.dimmed());
@@ -239,257 +110,14 @@ pub async fn execute(args: CheckArgs) -> Result<()> {
     Ok(())
 }
 
-fn detect_project_type(dir: &Path) -> ProjectType {
-    if dir.join("package.json").exists() {
-        ProjectType::NodeJs
-    } else if dir.join("Cargo.toml").exists() {
-        ProjectType::Cargo
-    } else {
-        ProjectType::Generic
-    }
-}
-
-fn check_nodejs_project(dir: &Path, fix: bool, results: &mut Vec<CheckResult>) {
-    let package_json = dir.join("package.json");
-
-    // Check package.json exists
-    if package_json.exists() {
-        results.push(CheckResult {
-            status: CheckStatus::Ok,
-            message: "package.json found".to_string(),
-            fix_hint: None,
-        });
-    } else {
-        results.push(CheckResult {
-            status: CheckStatus::Error,
-            message: "package.json not found".to_string(),
-            fix_hint: Some("Run `npm init` to create package.json".to_string()),
-        });
-        return;
-    }
-
-    // Check node_modules exists
-    let node_modules = dir.join("node_modules");
-    if node_modules.exists() && node_modules.is_dir() {
-        // Count packages
-        let package_count = fs::read_dir(&node_modules)
-            .map(|entries| entries.filter_map(|e| e.ok()).count())
-            .unwrap_or(0);
-        results.push(CheckResult {
-            status: CheckStatus::Ok,
-            message: format!("node_modules/ found ({} packages)", package_count),
-            fix_hint: None,
-        });
-    } else if fix {
-        // Attempt to run npm install
-        results.push(CheckResult {
-            status: CheckStatus::Fixed,
-            message: "Running npm install...".to_string(),
-            fix_hint: None,
-        });
-
-        let install_result = run_npm_install(dir);
-        match install_result {
-            Ok(count) => {
-                results.push(CheckResult {
-                    status: CheckStatus::Ok,
-                    message: format!("node_modules/ installed ({} packages)", count),
-                    fix_hint: None,
-                });
-            }
-            Err(e) => {
-                results.push(CheckResult {
-                    status: CheckStatus::Error,
-                    message: format!("npm install failed: {}", e),
-                    fix_hint: Some("Try running `npm install` manually".to_string()),
-                });
-            }
-        }
-    } else {
-        results.push(CheckResult {
-            status: CheckStatus::Warn,
-            message: "node_modules/ missing - run `npm install`".to_string(),
-            fix_hint: Some("Use --fix to auto-install dependencies".to_string()),
-        });
-    }
-
-    // Check for lockfile
-    let has_package_lock = dir.join("package-lock.json").exists();
-    let has_yarn_lock = dir.join("yarn.lock").exists();
-    let has_pnpm_lock = dir.join("pnpm-lock.yaml").exists();
-
-    if has_package_lock {
-        results.push(CheckResult {
-            status: CheckStatus::Ok,
-            message: "package-lock.json found".to_string(),
-            fix_hint: None,
-        });
-    } else if has_yarn_lock {
-        results.push(CheckResult {
-            status: CheckStatus::Ok,
-            message: "yarn.lock found".to_string(),
-            fix_hint: None,
-        });
-    } else if has_pnpm_lock {
-        results.push(CheckResult {
-            status: CheckStatus::Ok,
-            message: "pnpm-lock.yaml found".to_string(),
-            fix_hint: None,
-        });
-    } else {
-        results.push(CheckResult {
-            status: CheckStatus::Warn,
-            message: "No lockfile found (package-lock.json, yarn.lock, or pnpm-lock.yaml)".to_string(),
-            fix_hint: Some("Run `npm install` to generate package-lock.json".to_string()),
-        });
-    }
-}
-
-fn check_cargo_project(dir: &Path, _fix: bool, results: &mut Vec<CheckResult>) {
-    let cargo_toml = dir.join("Cargo.toml");
-
-    // Check Cargo.toml exists
-    if cargo_toml.exists() {
-        results.push(CheckResult {
-            status: CheckStatus::Ok,
-            message: "Cargo.toml found".to_string(),
-            fix_hint: None,
-        });
-    } else {
-        results.push(CheckResult {
-            status: CheckStatus::Error,
-            message: "Cargo.toml not found".to_string(),
-            fix_hint: Some("Run `cargo init` to create a new Cargo project".to_string()),
-        });
-        return;
-    }
-
-    // Check Cargo.lock exists
-    let cargo_lock = dir.join("Cargo.lock");
-    if cargo_lock.exists() {
-        results.push(CheckResult {
-            status: CheckStatus::Ok,
-            message: "Cargo.lock found".to_string(),
-            fix_hint: None,
-        });
-    } else {
-        results.push(CheckResult {
-            status: CheckStatus::Warn,
-            message: "Cargo.lock missing - dependencies not locked".to_string(),
-            fix_hint: Some("Run `cargo build` to generate Cargo.lock".to_string()),
-        });
-    }
-
-    // Check target/ directory (build artifacts)
-    let target_dir = dir.join("target");
-    if target_dir.exists() && target_dir.is_dir() {
-        // Check for debug or release builds
-        let has_debug = target_dir.join("debug").exists();
-        let has_release = target_dir.join("release").exists();
-
-        if has_release {
-            results.push(CheckResult {
-                status: CheckStatus::Ok,
-                message: "target/release/ found (release build available)".to_string(),
-                fix_hint: None,
-            });
-        } else if has_debug {
-            results.push(CheckResult {
-                status: CheckStatus::Ok,
-                message: "target/debug/ found (debug build available)".to_string(),
-                fix_hint: None,
-            });
-        } else {
-            results.push(CheckResult {
-                status: CheckStatus::Info,
-                message: "target/ exists but no builds found".to_string(),
-                fix_hint: Some("Run `cargo build` or `cargo build --release`".to_string()),
-            });
-        }
-    } else {
-        results.push(CheckResult {
-            status: CheckStatus::Info,
-            message: "target/ not found - project not built yet".to_string(),
-            fix_hint: Some("Run `cargo build` to build the project".to_string()),
-        });
-    }
-}
-
-fn check_env_files(dir: &Path, fix: bool, results: &mut Vec<CheckResult>) {
-    let env_file = dir.join(".env");
-    let env_example = dir.join(".env.example");
-    let env_template = dir.join(".env.template");
-    let env_local = dir.join(".env.local");
-
-    // Check if .env exists
-    if env_file.exists() {
-        results.push(CheckResult {
-            status: CheckStatus::Ok,
-            message: ".env found".to_string(),
-            fix_hint: None,
-        });
-    } else {
-        // Check for templates
-        let template_path = if env_example.exists() {
-            Some(env_example.clone())
-        } else if env_template.exists() {
-            Some(env_template.clone())
-        } else {
-            None
-        };
-
-        if let Some(template) = template_path {
-            let template_name = template.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("template");
-
-            if fix {
-                // Copy template to .env
-                match fs::copy(&template, &env_file) {
-                    Ok(_) => {
-                        results.push(CheckResult {
-                            status: CheckStatus::Fixed,
-                            message: format!("Creating .env from {}", template_name),
-                            fix_hint: None,
-                        });
-                        results.push(CheckResult {
-                            status: CheckStatus::Ok,
-                            message: ".env created".to_string(),
-                            fix_hint: None,
-                        });
-                    }
-                    Err(e) => {
-                        results.push(CheckResult {
-                            status: CheckStatus::Error,
-                            message: format!("Failed to create .env: {}", e),
-                            fix_hint: Some(format!("Manually copy {} to .env", template_name)),
-                        });
-                    }
-                }
-            } else {
-                results.push(CheckResult {
-                    status: CheckStatus::Warn,
-                    message: format!(".env missing (template found: {})", template_name),
-                    fix_hint: Some("Use --fix to create .env from template".to_string()),
-                });
-            }
-        } else {
-            // No .env and no template - just info
-            results.push(CheckResult {
-                status: CheckStatus::Info,
-                message: ".env not found (no template available)".to_string(),
-                fix_hint: None,
-            });
-        }
-    }
-
-    // Check .env.local
-    if env_local.exists() {
-        results.push(CheckResult {
-            status: CheckStatus::Info,
-            message: ".env.local found".to_string(),
-            fix_hint: None,
-        });
+/// Colored `[OK]`/`[WARN]`/... tag a check result is printed with
+pub fn status_prefix(status: CheckStatus) -> String {
+    match status {
+        CheckStatus::Ok => format!("[{}]", "OK".green()),
+        CheckStatus::Warn => format!("[{}]", "WARN".yellow()),
+        CheckStatus::Error => format!("[{}]", "ERROR".red()),
+        CheckStatus::Info => format!("[{}]", "INFO".cyan()),
+        CheckStatus::Fixed => format!("[{}]", "FIX".blue()),
     }
 }
 
@@ -596,46 +224,6 @@ fn handle_set_env(dir: &Path, envs: &[(String, String)], results: &mut Vec<Check
     }
 }
 
-fn run_npm_install(dir: &Path) -> Result<usize> {
-    // Detect which package manager to use
-    let (cmd, args) = if dir.join("pnpm-lock.yaml").exists() {
-        ("pnpm", vec!["install"])
-    } else if dir.join("yarn.lock").exists() {
-        ("yarn", vec!["install"])
-    } else {
-        ("npm", vec!["install"])
-    };
-
-    let output = Command::new(cmd)
-        .args(&args)
-        .current_dir(dir)
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("{}", stderr.trim());
-    }
-
-    // Count installed packages
-    let node_modules = dir.join("node_modules");
-    let count = fs::read_dir(&node_modules)
-        .map(|entries| entries.filter_map(|e| e.ok()).count())
-        .unwrap_or(0);
-
-    Ok(count)
-}
-
-/// Result of port conflict check
-#[derive(Debug)]
-pub struct PortCheckResult {
-    /// The port the app wants to use
-    pub desired_port: u16,
-    /// Whether the port is currently in use
-    pub is_in_use: bool,
-    /// The next available port (if desired port is in use)
-    pub available_port: Option<u16>,
-}
-
 /// Detect the port a project wants to use
 pub fn detect_project_port(dir: &Path) -> Option<u16> {
     // 1. Check .env file for PORT
@@ -736,48 +324,6 @@ fn parse_port_from_script(script: &str) -> Option<u16> {
     None
 }
 
-/// Check if a port is currently in use
-pub fn is_port_in_use(port: u16) -> bool {
-    // Check both IPv4 and IPv6 to catch all cases
-    // Try binding to 0.0.0.0 (all IPv4 interfaces) first
-    if TcpListener::bind(("0.0.0.0", port)).is_err() {
-        return true;
-    }
-    // Also check IPv6 on all interfaces
-    if TcpListener::bind(("::", port)).is_err() {
-        return true;
-    }
-    false
-}
-
-/// Find the next available port starting from the given port
-pub fn find_available_port(start_port: u16) -> Option<u16> {
-    for port in start_port..=65535 {
-        if !is_port_in_use(port) {
-            return Some(port);
-        }
-    }
-    None
-}
-
-/// Check for port conflicts and return information
-pub fn check_port_conflict(dir: &Path) -> Option<PortCheckResult> {
-    let desired_port = detect_project_port(dir)?;
-    let is_in_use = is_port_in_use(desired_port);
-
-    let available_port = if is_in_use {
-        find_available_port(desired_port + 1)
-    } else {
-        None
-    };
-
-    Some(PortCheckResult {
-        desired_port,
-        is_in_use,
-        available_port,
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -785,57 +331,6 @@ mod tests {
     use std::fs::File;
     use std::io::Write;
 
-    #[test]
-    fn test_detect_project_type_nodejs() {
-        let dir = TempDir::new().unwrap();
-        File::create(dir.path().join("package.json")).unwrap();
-
-        assert_eq!(detect_project_type(dir.path()), ProjectType::NodeJs);
-    }
-
-    #[test]
-    fn test_detect_project_type_cargo() {
-        let dir = TempDir::new().unwrap();
-        File::create(dir.path().join("Cargo.toml")).unwrap();
-
-        assert_eq!(detect_project_type(dir.path()), ProjectType::Cargo);
-    }
-
-    #[test]
-    fn test_detect_project_type_generic() {
-        let dir = TempDir::new().unwrap();
-
-        assert_eq!(detect_project_type(dir.path()), ProjectType::Generic);
-    }
-
-    #[test]
-    fn test_check_env_files_with_template() {
-        let dir = TempDir::new().unwrap();
-        let mut template = File::create(dir.path().join(".env.example")).unwrap();
-        writeln!(template, "DATABASE_URL=postgres://localhost/mydb").unwrap();
-
-        let mut results = Vec::new();
-        check_env_files(dir.path(), false, &mut results);
-
-        // Should have a warning about missing .env
-        assert!(results.iter().any(|r| r.status == CheckStatus::Warn));
-        assert!(results.iter().any(|r| r.message.contains(".env missing")));
-    }
-
-    #[test]
-    fn test_check_env_files_with_fix() {
-        let dir = TempDir::new().unwrap();
-        let mut template = File::create(dir.path().join(".env.example")).unwrap();
-        writeln!(template, "DATABASE_URL=postgres://localhost/mydb").unwrap();
-
-        let mut results = Vec::new();
-        check_env_files(dir.path(), true, &mut results);
-
-        // Should have created .env
-        assert!(dir.path().join(".env").exists());
-        assert!(results.iter().any(|r| r.status == CheckStatus::Fixed));
-    }
-
     #[test]
     fn test_handle_set_env_new_file() {
         let dir = TempDir::new().unwrap();
@@ -871,33 +366,6 @@ mod tests {
         assert!(!content.contains("old_value"));
     }
 
-    #[test]
-    fn test_check_nodejs_project_missing_node_modules() {
-        let dir = TempDir::new().unwrap();
-        let mut package = File::create(dir.path().join("package.json")).unwrap();
-        writeln!(package, r#"{{"name": "test"}}"#).unwrap();
-
-        let mut results = Vec::new();
-        check_nodejs_project(dir.path(), false, &mut results);
-
-        assert!(results.iter().any(|r| r.message.contains("package.json found")));
-        assert!(results.iter().any(|r| r.message.contains("node_modules/ missing")));
-    }
-
-    #[test]
-    fn test_check_cargo_project() {
-        let dir = TempDir::new().unwrap();
-        let mut cargo_toml = File::create(dir.path().join("Cargo.toml")).unwrap();
-        writeln!(cargo_toml, r#"[package]"#).unwrap();
-        writeln!(cargo_toml, r#"name = "test""#).unwrap();
-
-        let mut results = Vec::new();
-        check_cargo_project(dir.path(), false, &mut results);
-
-        assert!(results.iter().any(|r| r.message.contains("Cargo.toml found")));
-        assert!(results.iter().any(|r| r.message.contains("Cargo.lock missing")));
-    }
-
     #[test]
     fn test_check_config_files() {
         let dir = TempDir::new().unwrap();
@@ -913,10 +381,10 @@ mod tests {
 
     #[test]
     fn test_check_status_prefix() {
-        assert!(CheckStatus::Ok.prefix().contains("OK"));
-        assert!(CheckStatus::Warn.prefix().contains("WARN"));
-        assert!(CheckStatus::Error.prefix().contains("ERROR"));
-        assert!(CheckStatus::Info.prefix().contains("INFO"));
-        assert!(CheckStatus::Fixed.prefix().contains("FIX"));
+        assert!(status_prefix(CheckStatus::Ok).contains("OK"));
+        assert!(status_prefix(CheckStatus::Warn).contains("WARN"));
+        assert!(status_prefix(CheckStatus::Error).contains("ERROR"));
+        assert!(status_prefix(CheckStatus::Info).contains("INFO"));
+        assert!(status_prefix(CheckStatus::Fixed).contains("FIX"));
     }
 }
@@ -0,0 +1,96 @@
+//! Config command implementation
+
+use anyhow::Result;
+use colored::Colorize;
+use oxidepm_core::{validate_config, ConfigFormat, ConfigIssue, Error, ErrorCode, Severity};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::cli::{ConfigArgs, ConfigCommand};
+use crate::output::{is_json_mode, print_success};
+
+#[derive(Serialize)]
+struct ValidationJson<'a> {
+    file: String,
+    valid: bool,
+    issues: &'a [ConfigIssue],
+}
+
+pub fn execute(args: ConfigArgs) -> Result<()> {
+    match args.command {
+        ConfigCommand::Validate { file, strict } => validate(&file, strict),
+    }
+}
+
+fn validate(path: &Path, strict: bool) -> Result<()> {
+    if !path.exists() {
+        return Err(Error::ConfigNotFound(path.to_path_buf()).into());
+    }
+    let format = ConfigFormat::from_path(path).ok_or_else(|| {
+        Error::ConfigError(format!(
+            "Unsupported config file extension: {}. Expected .toml, .yaml, .yml, or .json",
+            path.display()
+        ))
+    })?;
+    let content = std::fs::read_to_string(path)?;
+
+    let issues = validate_config(&content, format);
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.is_error() || strict)
+        .count();
+
+    if is_json_mode() {
+        let report = ValidationJson {
+            file: path.display().to_string(),
+            valid: errors == 0,
+            issues: &issues,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if errors > 0 {
+            // The report already is the machine-readable result
+            std::process::exit(ErrorCode::ConfigError.exit_code());
+        }
+    } else {
+        for issue in &issues {
+            print_issue(path, issue);
+        }
+        if errors == 0 {
+            print_success(&format!("{} is valid", path.display()));
+        }
+    }
+
+    if errors > 0 {
+        return Err(Error::ConfigError(format!(
+            "{} problem(s) found in {}",
+            errors,
+            path.display()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Print an issue as `file:line: severity: [app] message`
+fn print_issue(path: &Path, issue: &ConfigIssue) {
+    let location = match issue.line {
+        Some(line) => format!("{}:{}", path.display(), line),
+        None => path.display().to_string(),
+    };
+    let severity = match issue.severity {
+        Severity::Error => "error".red().bold(),
+        Severity::Warning => "warning".yellow().bold(),
+    };
+    let app = issue
+        .app
+        .as_ref()
+        .map(|app| format!("[{}] ", app))
+        .unwrap_or_default();
+    eprintln!(
+        "{}: {}: {}{}",
+        location.bold(),
+        severity,
+        app,
+        issue.message
+    );
+}
@@ -0,0 +1,44 @@
+//! Daemon-reload command implementation
+
+use anyhow::{bail, Result};
+use oxidepm_ipc::{Request, Response};
+
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_info, print_success_json};
+
+/// The web API runs in its own process, which a daemon reload does not reach
+const WEB_NOTE: &str = "Web API settings are not reloaded; restart `oxidepm web` to change them";
+
+pub async fn execute() -> Result<()> {
+    let client = super::get_client();
+    match client.send(&Request::ReloadDaemonConfig).await? {
+        Response::DaemonConfigReloaded { changes } => {
+            print_success_json(
+                "Reloaded daemon config",
+                Some(serde_json::json!({ "changes": changes, "note": WEB_NOTE })),
+            );
+            if !is_json_mode() {
+                if changes.is_empty() {
+                    print_info("Nothing changed");
+                }
+                for change in &changes {
+                    println!("  {}", change);
+                }
+                print_info(WEB_NOTE);
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
@@ -4,26 +4,37 @@ use anyhow::{bail, Result};
 use oxidepm_core::Selector;
 use oxidepm_ipc::{Request, Response};
 
-use crate::output::{print_error, print_success};
+use crate::commands::DaemonError;
+use crate::output::{count_failed, print_error, print_op_results, print_success_json};
 
-pub async fn execute(selector: &str) -> Result<()> {
+pub async fn execute(selector: &str, purge: bool) -> Result<()> {
     let client = super::get_client();
     let selector = Selector::parse(selector);
 
-    let response = client.send(&Request::Delete { selector }).await?;
+    let response = client.send(&Request::Delete { selector, purge }).await?;
 
     match response {
-        Response::Deleted { count } => {
+        Response::Deleted { count, results } => {
+            print_op_results(&results);
             if count > 0 {
-                print_success(&format!("Deleted {} process(es)", count));
+                print_success_json(&format!("Deleted {} process(es)", count), Some(&results));
             } else {
-                print_success("No processes to delete");
+                print_success_json("No processes to delete", Some(&results));
+            }
+
+            let failed = count_failed(&results);
+            if failed > 0 {
+                bail!("{} process(es) failed to delete", failed);
             }
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
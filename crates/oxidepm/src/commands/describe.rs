@@ -1,17 +1,60 @@
 //! Describe command implementation - shows what command would run without starting
 
 use anyhow::{bail, Result};
-use oxidepm_core::Selector;
+use colored::Colorize;
+use oxidepm_core::env::mask_value;
+use oxidepm_core::{EnvSource, Selector};
 use oxidepm_ipc::{Request, Response};
+use std::path::Path;
 
-use crate::output::print_error;
+use crate::cli::StartArgs;
+use crate::commands::start::{build_app_spec, config_specs, is_config_target};
+use crate::commands::DaemonError;
+use crate::output::{format_build, print_error};
 
-pub async fn execute(target: &str) -> Result<()> {
+pub async fn execute(args: StartArgs, show_secrets: bool) -> Result<()> {
     let client = super::get_client();
-    let selector = Selector::parse(target);
+    let Some(target) = args.target.clone() else {
+        bail!("No target specified. Give an app name, id, file or directory");
+    };
+    if args.git.is_some() {
+        bail!(
+            "--git cannot be used with describe; clone the repository and describe its directory"
+        );
+    }
+    if args.dry_run {
+        bail!("--dry-run cannot be used with describe; use `oxidepm start --dry-run`");
+    }
+    let target_path = Path::new(&target);
 
-    let response = client.send(&Request::Describe { selector }).await?;
+    if is_config_target(&target) {
+        for (index, spec) in config_specs(target_path, &args)?.into_iter().enumerate() {
+            if index > 0 {
+                println!("{}", "─".repeat(50));
+            }
+            print_described(
+                client.send(&Request::DescribeSpec { spec }).await?,
+                show_secrets,
+            )?;
+        }
+        return Ok(());
+    }
 
+    let selector = Selector::parse(&target);
+    let response = match client.send(&Request::Describe { selector }).await? {
+        // A path that is not a registered app: describe it as `start` would run it
+        Response::Error {
+            code: Some(code), ..
+        } if code == "app_not_found" && target_path.exists() => {
+            let spec = build_app_spec(&args)?;
+            client.send(&Request::DescribeSpec { spec }).await?
+        }
+        response => response,
+    };
+    print_described(response, show_secrets)
+}
+
+fn print_described(response: Response, show_secrets: bool) -> Result<()> {
     match response {
         Response::Described {
             name,
@@ -20,6 +63,10 @@ pub async fn execute(target: &str) -> Result<()> {
             cwd,
             env,
             mode,
+            build,
+            argv,
+            prepare,
+            env_sources,
         } => {
             println!("Process: {}", name);
             println!("Mode: {}", mode);
@@ -31,11 +78,24 @@ pub async fn execute(target: &str) -> Result<()> {
             }
             println!();
             println!("Full Command:");
-            if args.is_empty() {
+            if !argv.is_empty() {
+                println!("  {}", shell_join(&argv));
+            } else if args.is_empty() {
                 println!("  {}", command);
             } else {
                 println!("  {} {}", command, args.join(" "));
             }
+            if let Some(prepare) = prepare {
+                println!();
+                println!("Prepare:");
+                println!("  {}", shell_join(&prepare));
+            }
+
+            if let Some(build) = build {
+                println!();
+                println!("Build: {}", format_build(&build));
+                println!("Binary: {}", build.binary_path.display());
+            }
 
             if !env.is_empty() {
                 println!();
@@ -43,15 +103,34 @@ pub async fn execute(target: &str) -> Result<()> {
                 let mut sorted_env: Vec<_> = env.iter().collect();
                 sorted_env.sort_by(|a, b| a.0.cmp(b.0));
                 for (key, value) in sorted_env {
-                    println!("  {}={}", key, value);
+                    let value = if show_secrets {
+                        value.clone()
+                    } else {
+                        mask_value(key, value)
+                    };
+                    // Replies from older daemons carry no provenance
+                    let source = env_sources
+                        .get(key)
+                        .map(|source| format!("  ({})", source))
+                        .unwrap_or_default();
+                    let line = format!("  {}={}{}", key, value, source.dimmed());
+                    if env_sources.get(key) == Some(&EnvSource::Inherited) {
+                        println!("{}", line.dimmed());
+                    } else {
+                        println!("{}", line);
+                    }
                 }
             }
 
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
@@ -59,3 +138,35 @@ pub async fn execute(target: &str) -> Result<()> {
         }
     }
 }
+
+/// Join argv for display, quoting arguments a shell would split or expand
+fn shell_join(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c));
+            if plain {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_join() {
+        let argv = ["node", "/srv/app/server.js", "--name", "my app", "", "it's"].map(String::from);
+        assert_eq!(
+            shell_join(&argv),
+            r"node /srv/app/server.js --name 'my app' '' 'it'\''s'"
+        );
+    }
+}
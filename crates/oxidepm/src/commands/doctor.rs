@@ -0,0 +1,365 @@
+//! Doctor command implementation - diagnoses common problems with the local daemon
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use oxidepm_core::{constants, CheckResult, CheckStatus, Heartbeat};
+use oxidepm_ipc::{Endpoint, IpcClient, Request, Response};
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::commands::check::status_prefix;
+use crate::output::{format_duration, is_json_mode};
+
+/// How long a daemon that accepts connections may take to answer a ping
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long to wait for the database write lock before calling it stuck
+const DB_LOCK_WAIT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+struct DoctorOutput {
+    checks: Vec<CheckResult>,
+    errors: usize,
+    warnings: usize,
+}
+
+pub async fn execute(fix: bool) -> Result<()> {
+    let mut results = Vec::new();
+    check_home(&mut results);
+    check_daemon(fix, &mut results).await;
+    check_database(&mut results).await;
+    check_logs_dir(&mut results);
+
+    let errors = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Error)
+        .count();
+    let warnings = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Warn)
+        .count();
+
+    if is_json_mode() {
+        let output = DoctorOutput {
+            checks: results,
+            errors,
+            warnings,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        for result in &results {
+            println!("{} {}", status_prefix(result.status), result.message);
+            if let Some(hint) = &result.fix_hint {
+                println!("       {}", hint.dimmed());
+            }
+        }
+        println!();
+        if errors == 0 && warnings == 0 {
+            println!("{}", "No problems found".green());
+        }
+    }
+
+    if errors > 0 {
+        bail!("{} problem(s) found", errors);
+    }
+    Ok(())
+}
+
+fn result(status: CheckStatus, message: String, fix_hint: Option<String>) -> CheckResult {
+    CheckResult {
+        status,
+        message,
+        fix_hint,
+    }
+}
+
+/// The data directory must exist, be ours and be writable
+fn check_home(results: &mut Vec<CheckResult>) {
+    let home = constants::oxidepm_home();
+    if !home.exists() {
+        results.push(result(
+            CheckStatus::Info,
+            format!(
+                "{} does not exist yet (created when the daemon first starts)",
+                home.display()
+            ),
+            None,
+        ));
+        return;
+    }
+
+    match probe_writable(&home) {
+        Ok(()) => results.push(result(
+            CheckStatus::Ok,
+            format!("{} is writable", home.display()),
+            None,
+        )),
+        Err(e) => {
+            results.push(permission_problem(&home, &e));
+            return;
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = std::fs::metadata(&home) {
+            let mode = meta.permissions().mode() & 0o777;
+            if mode & 0o022 != 0 {
+                results.push(result(
+                    CheckStatus::Warn,
+                    format!(
+                        "{} is writable by other users (mode {:o})",
+                        home.display(),
+                        mode
+                    ),
+                    Some(format!("chmod 700 {}", home.display())),
+                ));
+            }
+        }
+    }
+}
+
+/// Socket, daemon answer and heartbeat
+async fn check_daemon(fix: bool, results: &mut Vec<CheckResult>) {
+    let socket = constants::socket_path();
+    let heartbeat = read_heartbeat(&constants::heartbeat_path());
+    let live_heartbeat = heartbeat.as_ref().filter(|h| !h.is_stale());
+
+    if !socket.exists() {
+        results.push(match live_heartbeat {
+            Some(h) => result(
+                CheckStatus::Error,
+                format!(
+                    "Daemon (pid {}) is running but its socket {} is missing",
+                    h.pid,
+                    socket.display()
+                ),
+                Some(format!(
+                    "Restart it: kill {} and run any oxidepm command",
+                    h.pid
+                )),
+            ),
+            None => result(
+                CheckStatus::Info,
+                "Daemon is not running (it starts with the next command)".to_string(),
+                None,
+            ),
+        });
+        return;
+    }
+
+    if let Err(e) = tokio::net::UnixStream::connect(&socket).await {
+        results.push(match e.kind() {
+            ErrorKind::ConnectionRefused | ErrorKind::NotFound if fix => {
+                match std::fs::remove_file(&socket) {
+                    Ok(()) => result(
+                        CheckStatus::Fixed,
+                        format!("Removed stale socket {}", socket.display()),
+                        None,
+                    ),
+                    Err(e) => permission_problem(&socket, &e),
+                }
+            }
+            ErrorKind::ConnectionRefused | ErrorKind::NotFound => result(
+                CheckStatus::Error,
+                format!("Stale socket {}: no daemon is listening", socket.display()),
+                Some("Run `oxidepm doctor --fix` to remove it".to_string()),
+            ),
+            _ => permission_problem(&socket, &e),
+        });
+        return;
+    }
+
+    let client = IpcClient::with_endpoint(Endpoint::Unix(socket));
+    match tokio::time::timeout(PING_TIMEOUT, client.send(&Request::Ping)).await {
+        Ok(Ok(Response::Pong { heartbeat: Some(h) })) => check_heartbeat(&h, results),
+        Ok(Ok(Response::Pong { heartbeat: None })) => results.push(result(
+            CheckStatus::Warn,
+            "Daemon answers but predates heartbeats".to_string(),
+            Some("Restart the daemon to run the installed version: oxidepm kill".to_string()),
+        )),
+        Ok(Ok(_)) => results.push(result(
+            CheckStatus::Error,
+            "Daemon sent an unexpected reply to ping".to_string(),
+            None,
+        )),
+        Ok(Err(e)) => results.push(result(
+            CheckStatus::Error,
+            format!("Daemon did not answer: {}", e),
+            None,
+        )),
+        Err(_) => {
+            let last = heartbeat
+                .as_ref()
+                .map(|h| format!(" (last heartbeat {}s ago, pid {})", h.age_secs(), h.pid))
+                .unwrap_or_default();
+            results.push(result(
+                CheckStatus::Error,
+                format!("Daemon accepts connections but does not answer{}", last),
+                Some(
+                    "The daemon looks hung; kill it and run any oxidepm command to start a new one"
+                        .to_string(),
+                ),
+            ));
+        }
+    }
+}
+
+fn check_heartbeat(h: &Heartbeat, results: &mut Vec<CheckResult>) {
+    results.push(result(
+        CheckStatus::Ok,
+        format!(
+            "Daemon pid {} v{} up {}, {} app(s) ({} running)",
+            h.pid,
+            h.version,
+            format_duration(h.uptime_secs),
+            h.managed,
+            h.running
+        ),
+        None,
+    ));
+
+    if h.version != env!("CARGO_PKG_VERSION") {
+        results.push(result(
+            CheckStatus::Warn,
+            format!(
+                "Daemon is v{} but the CLI is v{}",
+                h.version,
+                env!("CARGO_PKG_VERSION")
+            ),
+            Some("Restart the daemon after upgrading: oxidepm kill".to_string()),
+        ));
+    }
+    if h.stalls > 0 {
+        results.push(result(
+            CheckStatus::Warn,
+            format!(
+                "Daemon event loop stalled {} time(s), longest {}ms",
+                h.stalls, h.max_stall_ms
+            ),
+            Some("Look for slow hooks, a slow disk or an overloaded machine".to_string()),
+        ));
+    }
+}
+
+/// The database must open read-write and not be held locked
+async fn check_database(results: &mut Vec<CheckResult>) {
+    let db_path = constants::db_path();
+    if !db_path.exists() {
+        return;
+    }
+
+    results.push(match oxidepm_db::check_writable(&db_path, DB_LOCK_WAIT).await {
+        Ok(()) => result(CheckStatus::Ok, format!("Database {} is writable", db_path.display()), None),
+        Err(e) if e.to_string().contains("locked") => result(
+            CheckStatus::Error,
+            format!("Database {} is locked by another process", db_path.display()),
+            Some("Another oxidepmd may be using the same data directory; check with `pgrep -a oxidepmd`".to_string()),
+        ),
+        Err(e) => result(
+            CheckStatus::Error,
+            format!("Database {} cannot be opened for writing: {}", db_path.display(), e),
+            Some(format!("Check the ownership and permissions of {}", db_path.display())),
+        ),
+    });
+}
+
+fn check_logs_dir(results: &mut Vec<CheckResult>) {
+    let logs = constants::logs_dir();
+    if !logs.exists() {
+        return;
+    }
+    match probe_writable(&logs) {
+        Ok(()) => results.push(result(
+            CheckStatus::Ok,
+            format!("{} is writable", logs.display()),
+            None,
+        )),
+        Err(e) => results.push(permission_problem(&logs, &e)),
+    }
+}
+
+/// Create and remove a scratch file in `dir`
+fn probe_writable(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".doctor-{}", std::process::id()));
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)
+}
+
+fn permission_problem(path: &Path, e: &std::io::Error) -> CheckResult {
+    let hint = (e.kind() == ErrorKind::PermissionDenied).then(|| {
+        format!(
+            "{} may belong to another user; fix with chown -R $USER {}",
+            path.display(),
+            path.display()
+        )
+    });
+    result(
+        CheckStatus::Error,
+        format!("Cannot use {}: {}", path.display(), e),
+        hint,
+    )
+}
+
+fn read_heartbeat(path: &Path) -> Option<Heartbeat> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn heartbeat() -> Heartbeat {
+        Heartbeat {
+            pid: 42,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: 3700,
+            managed: 3,
+            running: 2,
+            at: Utc::now(),
+            stalls: 0,
+            max_stall_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_check_heartbeat() {
+        let mut results = Vec::new();
+        check_heartbeat(&heartbeat(), &mut results);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].status, CheckStatus::Ok);
+        assert!(results[0].message.contains("1h 1m"));
+
+        let mut results = Vec::new();
+        let stalled = Heartbeat {
+            version: "0.0.1".to_string(),
+            stalls: 2,
+            max_stall_ms: 1800,
+            ..heartbeat()
+        };
+        check_heartbeat(&stalled, &mut results);
+        let warnings: Vec<_> = results
+            .iter()
+            .filter(|r| r.status == CheckStatus::Warn)
+            .collect();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].message.contains("1800ms"));
+    }
+
+    #[test]
+    fn test_read_heartbeat() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("heartbeat.json");
+        assert!(read_heartbeat(&path).is_none());
+
+        let written = heartbeat();
+        std::fs::write(&path, serde_json::to_string(&written).unwrap()).unwrap();
+        assert_eq!(read_heartbeat(&path), Some(written));
+        assert!(probe_writable(dir.path()).is_ok());
+    }
+}
@@ -0,0 +1,73 @@
+//! Drain/undrain command implementation - takes apps out of rotation for maintenance
+
+use anyhow::{bail, Result};
+use oxidepm_core::Selector;
+use oxidepm_ipc::{Request, Response};
+
+use crate::commands::DaemonError;
+use crate::output::{format_duration, print_error, print_success_json};
+
+pub async fn drain(selector: &str, timeout_secs: u64) -> Result<()> {
+    let request = Request::Drain {
+        selector: Selector::parse(selector),
+        timeout_secs,
+    };
+    match send(request).await? {
+        Response::Drained {
+            count,
+            timeout_secs,
+        } => {
+            print_success_json(
+                &format!(
+                    "Draining {} app(s); they stop in {}",
+                    count,
+                    format_duration(timeout_secs)
+                ),
+                Some(serde_json::json!({ "drained": count, "timeout_secs": timeout_secs })),
+            );
+            Ok(())
+        }
+        _ => unexpected(),
+    }
+}
+
+pub async fn undrain(selector: &str) -> Result<()> {
+    let request = Request::Undrain {
+        selector: Selector::parse(selector),
+    };
+    match send(request).await? {
+        Response::Undrained { count, started } => {
+            let message = if started > 0 {
+                format!("Undrained {} app(s), started {} again", count, started)
+            } else {
+                format!("Undrained {} app(s)", count)
+            };
+            print_success_json(
+                &message,
+                Some(serde_json::json!({ "undrained": count, "started": started })),
+            );
+            Ok(())
+        }
+        _ => unexpected(),
+    }
+}
+
+/// Send the request, turning an error response into an error
+async fn send(request: Request) -> Result<Response> {
+    match super::get_client().send(&request).await? {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        response => Ok(response),
+    }
+}
+
+fn unexpected() -> Result<()> {
+    print_error("Unexpected response from daemon");
+    bail!("Unexpected response")
+}
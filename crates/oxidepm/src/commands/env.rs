@@ -0,0 +1,129 @@
+//! Env command implementation - inspect and override app environments
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use oxidepm_core::env::mask_value;
+use oxidepm_core::Selector;
+use oxidepm_ipc::{Request, Response};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::cli::{EnvArgs, EnvCommand};
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_info, print_success_json};
+
+pub async fn execute(args: EnvArgs) -> Result<()> {
+    let selector = Selector::parse(&args.selector);
+    let request = match args.command {
+        None => return show(selector, args.show_secrets, args.inherited).await,
+        Some(EnvCommand::Set { vars, restart }) => Request::SetEnv {
+            selector,
+            set: vars.into_iter().collect(),
+            unset: Vec::new(),
+            restart,
+        },
+        Some(EnvCommand::Unset { keys, restart }) => Request::SetEnv {
+            selector,
+            set: HashMap::new(),
+            unset: keys,
+            restart,
+        },
+    };
+    let restart = matches!(request, Request::SetEnv { restart: true, .. });
+
+    let client = super::get_client();
+    match client.send(&request).await? {
+        Response::EnvUpdated { count, restarted } => {
+            print_success_json(
+                &format!("Updated environment of {} app(s)", count),
+                Some(serde_json::json!({ "updated": count, "restarted": restarted })),
+            );
+            if !is_json_mode() {
+                if restarted > 0 {
+                    print_info(&format!("Restarted {} app(s)", restarted));
+                } else if !restart {
+                    print_info(
+                        "Running processes keep their environment until restarted (use --restart)",
+                    );
+                }
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
+
+async fn show(selector: Selector, show_secrets: bool, include_inherited: bool) -> Result<()> {
+    let client = super::get_client();
+    match client.send(&Request::Env { selector }).await? {
+        Response::Env {
+            name,
+            env,
+            inherited,
+        } => {
+            let env = display_env(env, show_secrets);
+            let inherited = if include_inherited {
+                display_env(inherited, show_secrets)
+            } else {
+                BTreeMap::new()
+            };
+
+            if is_json_mode() {
+                let json = serde_json::json!({ "name": name, "env": env, "inherited": inherited });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+                return Ok(());
+            }
+
+            if env.is_empty() {
+                println!("{} has no environment variables of its own", name.cyan());
+            }
+            for (key, value) in &env {
+                println!("{}={}", key.bold(), value);
+            }
+            if !inherited.is_empty() {
+                println!();
+                println!("{}", "Inherited from the daemon:".dimmed());
+                for (key, value) in &inherited {
+                    println!("{}", format!("{}={}", key, value).dimmed());
+                }
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
+
+/// Sort variables and mask secret values unless asked not to
+fn display_env(env: HashMap<String, String>, show_secrets: bool) -> BTreeMap<String, String> {
+    env.into_iter()
+        .map(|(key, value)| {
+            let value = if show_secrets {
+                value
+            } else {
+                mask_value(&key, &value)
+            };
+            (key, value)
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+//! Events command implementation - show the supervisor event log
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use oxidepm_core::{AppEvent, EventKind, Selector};
+use oxidepm_ipc::{Request, Response};
+
+use crate::cli::EventsArgs;
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_info};
+
+pub async fn execute(args: EventsArgs) -> Result<()> {
+    let selector = Selector::parse(&args.selector);
+    let client = super::get_client();
+
+    match client
+        .send(&Request::Events {
+            selector,
+            limit: args.last,
+        })
+        .await?
+    {
+        Response::Events { events } => {
+            if is_json_mode() {
+                println!("{}", serde_json::to_string_pretty(&events)?);
+            } else if events.is_empty() {
+                print_info(&format!("No events recorded for {}", args.selector));
+            } else {
+                for event in &events {
+                    print_event(event);
+                }
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
+
+fn print_event(event: &AppEvent) {
+    let time = event
+        .timestamp
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M:%S");
+    let kind = format!("{:<9}", event.kind.as_str());
+    let kind = match event.kind {
+        EventKind::Started | EventKind::Reloaded | EventKind::Ready => kind.green(),
+        EventKind::Stopped | EventKind::NotReady => kind.yellow(),
+        EventKind::Crashed | EventKind::Unhealthy => kind.red(),
+        EventKind::Restarted => kind.cyan(),
+        EventKind::Paused | EventKind::Resumed | EventKind::Signalled => kind.blue(),
+    };
+
+    let mut details = Vec::new();
+    if let Some(reason) = &event.reason {
+        details.push(reason.clone());
+    }
+    if let Some(code) = event.exit_code {
+        details.push(format!("exit code {}", code));
+    }
+    println!(
+        "{} {} {} {}",
+        time.to_string().dimmed(),
+        kind,
+        event.name.bold(),
+        details.join(", ").dimmed()
+    );
+}
@@ -0,0 +1,59 @@
+//! Exec command implementation - runs a command in a managed app's context
+
+use anyhow::{bail, Result};
+use oxidepm_core::Selector;
+use oxidepm_ipc::{Request, Response};
+
+use crate::cli::ExecArgs;
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_hint, print_success_json};
+
+pub async fn execute(args: ExecArgs) -> Result<()> {
+    let client = super::get_client();
+    let request = Request::Exec {
+        selector: Selector::parse(&args.selector),
+        command: args.command,
+    };
+
+    // The daemon runs the command, so its output arrives line by line
+    let mut output = Vec::new();
+    let response = client
+        .send_with_progress(&request, |line| {
+            if is_json_mode() {
+                output.push(line.to_string());
+            } else {
+                println!("{}", line);
+            }
+        })
+        .await?;
+
+    match response {
+        Response::Executed { exit_code } => {
+            if is_json_mode() {
+                print_success_json(
+                    &format!("Command exited with code {}", exit_code),
+                    Some(serde_json::json!({ "exit_code": exit_code, "output": output })),
+                );
+            }
+            if exit_code != 0 {
+                std::process::exit(exit_code);
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            if let Some(hint) = &hint {
+                print_hint(hint);
+            }
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
@@ -1,29 +1,48 @@
-//! Flush command implementation - clears/truncates log files
+//! Flush command implementation - clears/truncates log files, or prunes them by age/size
 
 use anyhow::{bail, Result};
 use oxidepm_core::Selector;
 use oxidepm_ipc::{Request, Response};
 
-use crate::output::{print_error, print_success};
+use crate::commands::DaemonError;
+use crate::output::{format_bytes, print_error, print_success};
 
-pub async fn execute(selector: &str) -> Result<()> {
+pub async fn execute(
+    selector: &str,
+    older_than_secs: Option<u64>,
+    keep_bytes: Option<u64>,
+) -> Result<()> {
     let client = super::get_client();
     let selector = Selector::parse(selector);
 
-    let response = client.send(&Request::Flush { selector }).await?;
+    let response = client
+        .send(&Request::Flush {
+            selector,
+            older_than_secs,
+            keep_bytes,
+        })
+        .await?;
 
     match response {
-        Response::Flushed { count } => {
+        Response::Flushed { count, freed_bytes } => {
             if count == 0 {
                 print_error("No matching processes found");
             } else {
-                print_success(&format!("Flushed logs for {} process(es)", count));
+                print_success(&format!(
+                    "Flushed logs for {} process(es), freed {}",
+                    count,
+                    format_bytes(freed_bytes)
+                ));
             }
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
@@ -0,0 +1,86 @@
+//! Group command implementation
+
+use anyhow::{bail, Result};
+use oxidepm_core::{AppOpResult, GroupAction, Rollout, Selector};
+use oxidepm_ipc::{Request, Response};
+
+use crate::cli::{GroupArgs, GroupCommand, RolloutArgs};
+use crate::commands::DaemonError;
+use crate::output::{count_failed, print_error, print_op_results, print_success_json};
+
+pub async fn execute(args: GroupArgs) -> Result<()> {
+    let (action, args) = match args.command {
+        GroupCommand::Start(args) => (GroupAction::Start, args),
+        GroupCommand::Stop(args) => (GroupAction::Stop, args),
+        GroupCommand::Restart(args) => (GroupAction::Restart, args),
+    };
+
+    let client = super::get_client();
+    let request = Request::Group {
+        selector: Selector::parse(&args.selector),
+        action,
+        rollout: rollout(&args),
+    };
+
+    let label = format!("{} {}", action, args.selector);
+    let (results, batches, halted) = match super::send_tracked(&client, &request, &label).await? {
+        Response::GroupDone {
+            results,
+            batches,
+            halted,
+            ..
+        } => (results, batches, halted),
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            return Err(DaemonError::new(message, code, hint).into());
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    };
+
+    print_op_results(&results);
+    if let Some(reason) = halted {
+        print_error(&format!("Rollout halted: {}", reason));
+        bail!("Rollout halted");
+    }
+
+    let failed = count_failed(&results);
+    let done = AppOpResult::count_done(&results);
+    let verb = match action {
+        GroupAction::Start => "Started",
+        GroupAction::Stop => "Stopped",
+        GroupAction::Restart => "Restarted",
+    };
+    if results.is_empty() {
+        print_success_json(&format!("No processes to {}", action), Some(&results));
+    } else {
+        print_success_json(
+            &format!("{} {} process(es) in {} batch(es)", verb, done, batches),
+            Some(&results),
+        );
+    }
+
+    if failed > 0 {
+        bail!("{} process(es) failed to {}", failed, action);
+    }
+    Ok(())
+}
+
+fn rollout(args: &RolloutArgs) -> Rollout {
+    let batch_size = if args.serial {
+        1
+    } else {
+        args.batch.unwrap_or(0) as usize
+    };
+    Rollout {
+        batch_size,
+        wait_healthy: args.wait_healthy,
+        health_timeout_secs: args.health_timeout,
+    }
+}
@@ -0,0 +1,95 @@
+//! Health command implementation - show recent health check results
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use oxidepm_core::{AppHealth, Probe, Selector};
+use oxidepm_ipc::{Request, Response};
+
+use crate::cli::HealthArgs;
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_info};
+
+pub async fn execute(args: HealthArgs) -> Result<()> {
+    let selector = Selector::parse(&args.selector);
+    let client = super::get_client();
+
+    match client.send(&Request::Health { selector }).await? {
+        Response::Health { mut apps } => {
+            for app in &mut apps {
+                let skip = app.history.len().saturating_sub(args.lines);
+                app.history.drain(..skip);
+            }
+
+            if is_json_mode() {
+                println!("{}", serde_json::to_string_pretty(&apps)?);
+                return Ok(());
+            }
+
+            if apps.is_empty() {
+                print_info(&format!(
+                    "No health checks configured for {}",
+                    args.selector
+                ));
+                return Ok(());
+            }
+            for (i, app) in apps.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                print_app_health(app);
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
+
+fn print_app_health(app: &AppHealth) {
+    let status = match (app.probe, app.healthy) {
+        (Probe::Liveness, true) => "healthy".green(),
+        (Probe::Liveness, false) => "unhealthy".red(),
+        (Probe::Readiness, true) => "ready".green(),
+        (Probe::Readiness, false) => "not ready".yellow(),
+    };
+    println!(
+        "{} (id: {}) {} {} - {}",
+        app.name.cyan().bold(),
+        app.id,
+        app.probe,
+        status,
+        app.endpoint.dimmed()
+    );
+    println!("Consecutive failures: {}", app.consecutive_failures);
+
+    if app.history.is_empty() {
+        println!("{}", "No checks have run yet".dimmed());
+        return;
+    }
+    for record in app.history.iter().rev() {
+        let mark = if record.healthy {
+            "✓".green()
+        } else {
+            "✗".red()
+        };
+        let time = record
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S");
+        let message = record.message.as_deref().unwrap_or("");
+        println!(
+            "  {} {} {:>6}ms  {}",
+            mark, time, record.duration_ms, message
+        );
+    }
+}
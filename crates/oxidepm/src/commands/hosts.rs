@@ -0,0 +1,86 @@
+//! Hosts command implementation - list configured remote daemons
+
+use anyhow::Result;
+use colored::Colorize;
+use oxidepm_core::{constants, HostsFile};
+use oxidepm_ipc::{Endpoint, IpcClient};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::output::{is_json_mode, print_info};
+
+/// How long to wait for each host to answer a ping
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Serialize)]
+struct HostJson {
+    name: String,
+    location: String,
+    reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub async fn execute() -> Result<()> {
+    let hosts = HostsFile::load()?;
+    if hosts.hosts.is_empty() && !is_json_mode() {
+        print_info(&format!(
+            "No hosts configured in {}",
+            constants::hosts_path().display()
+        ));
+        return Ok(());
+    }
+
+    // Ping every host at once
+    let checks: Vec<_> = hosts
+        .hosts
+        .iter()
+        .map(|(name, host)| {
+            let endpoint = Endpoint::for_host(name, host);
+            tokio::spawn(async move {
+                let client = IpcClient::with_endpoint(endpoint?);
+                match tokio::time::timeout(PING_TIMEOUT, client.ping()).await {
+                    Ok(Ok(true)) => Ok(()),
+                    Ok(Ok(false)) => Err(oxidepm_core::Error::DaemonNotRunning),
+                    Ok(Err(e)) => Err(e),
+                    Err(_) => Err(oxidepm_core::Error::Timeout("no answer".to_string())),
+                }
+            })
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    for ((name, host), check) in hosts.hosts.iter().zip(checks) {
+        let error = match check.await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        rows.push(HostJson {
+            name: name.clone(),
+            location: host.location(),
+            reachable: error.is_none(),
+            error,
+        });
+    }
+
+    if is_json_mode() {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<16} {:<32} {}",
+        "HOST".bold(),
+        "LOCATION".bold(),
+        "STATUS".bold()
+    );
+    for row in &rows {
+        let status = match &row.error {
+            None => "online".green().to_string(),
+            Some(e) => format!("{} ({})", "unreachable".red(), e),
+        };
+        println!("{:<16} {:<32} {}", row.name, row.location, status);
+    }
+    Ok(())
+}
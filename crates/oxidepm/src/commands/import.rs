@@ -0,0 +1,163 @@
+//! Import command implementation
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use oxidepm_core::import::{compose, pm2, ImportResult};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::{ImportArgs, ImportCommand};
+use crate::commands::start::start_specs;
+use crate::output::{is_json_mode, print_info, print_success_json, print_warning};
+
+#[derive(Serialize)]
+struct ImportJson<'a> {
+    apps: &'a [oxidepm_core::AppSpec],
+    warnings: &'a [String],
+}
+
+pub async fn execute(args: ImportArgs) -> Result<()> {
+    let (source, result) = match args.command {
+        ImportCommand::Pm2 { path, env } => import_pm2(path, env.as_deref())?,
+        ImportCommand::Compose { path } => import_compose(path)?,
+    };
+
+    if result.specs.is_empty() {
+        bail!("No apps found in {}", source.display());
+    }
+
+    if is_json_mode() && !args.start {
+        let data = ImportJson {
+            apps: &result.specs,
+            warnings: &result.warnings,
+        };
+        print_success_json(&format!("Imported {} apps", result.specs.len()), Some(data));
+        return Ok(());
+    }
+
+    print_summary(&source, &result);
+
+    if !args.start {
+        print_info("Run again with --start to start these apps");
+        return Ok(());
+    }
+
+    start_specs(&super::get_client(), result.specs).await
+}
+
+/// Locate and convert a PM2 ecosystem file or dump
+fn import_pm2(path: Option<PathBuf>, env: Option<&str>) -> Result<(PathBuf, ImportResult)> {
+    let path = match path {
+        Some(path) => path,
+        None => find_pm2_source()?,
+    };
+    if !path.exists() {
+        bail!("File not found: {}", path.display());
+    }
+    let path = path.canonicalize().unwrap_or(path);
+
+    let json = match path.extension().and_then(|e| e.to_str()) {
+        Some("js") | Some("cjs") => eval_ecosystem_js(&path)?,
+        _ => std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+    };
+
+    let apps = pm2::parse(&json)?;
+    let base_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let result = pm2::into_specs(apps, &base_dir, env);
+    Ok((path, result))
+}
+
+/// Ecosystem file in the current directory, falling back to PM2's dump
+fn find_pm2_source() -> Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    pm2::ECOSYSTEM_FILES
+        .iter()
+        .map(|name| cwd.join(name))
+        .chain(std::iter::once(pm2::dump_path()))
+        .find(|path| path.exists())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No PM2 config found. Expected one of {:?} or {}",
+                pm2::ECOSYSTEM_FILES,
+                pm2::dump_path().display()
+            )
+        })
+}
+
+/// Convert a Docker Compose file
+fn import_compose(path: Option<PathBuf>) -> Result<(PathBuf, ImportResult)> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let cwd = std::env::current_dir()?;
+            compose::COMPOSE_FILES
+                .iter()
+                .map(|name| cwd.join(name))
+                .find(|path| path.exists())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "No compose file found. Expected one of {:?}",
+                        compose::COMPOSE_FILES
+                    )
+                })?
+        }
+    };
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let path = path.canonicalize().unwrap_or(path);
+
+    let file = compose::parse(&content)?;
+    let base_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let result = compose::into_specs(file, &base_dir);
+    Ok((path, result))
+}
+
+/// Evaluate an ecosystem.config.js with node and return its exports as JSON
+fn eval_ecosystem_js(path: &Path) -> Result<String> {
+    let output = Command::new("node")
+        .arg("-e")
+        .arg("process.stdout.write(JSON.stringify(require(process.argv[1])))")
+        .arg(path)
+        .current_dir(path.parent().unwrap_or(Path::new(".")))
+        .output()
+        .context("Failed to run node (required to read ecosystem .js files)")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("Failed to evaluate {}: {}", path.display(), stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn print_summary(source: &Path, result: &ImportResult) {
+    println!(
+        "Imported {} apps from {}:",
+        result.specs.len(),
+        source.display()
+    );
+    for spec in &result.specs {
+        let command = if spec.args.is_empty() {
+            spec.command.clone()
+        } else {
+            format!("{} {}", spec.command, spec.args.join(" "))
+        };
+        let mut details = vec![spec.mode.to_string()];
+        if spec.instances > 1 {
+            details.push(format!("{} instances", spec.instances));
+        }
+        if let Some(mb) = spec.max_memory_mb {
+            details.push(format!("max {}MB", mb));
+        }
+        println!(
+            "  {} {} ({})",
+            spec.name.cyan(),
+            command.dimmed(),
+            details.join(", ")
+        );
+    }
+    for warning in &result.warnings {
+        print_warning(warning);
+    }
+}
@@ -1,8 +1,9 @@
 //! Kill command implementation
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 use oxidepm_ipc::{Request, Response};
 
+use crate::commands::DaemonError;
 use crate::output::{print_error, print_success};
 
 pub async fn execute() -> Result<()> {
@@ -13,9 +14,13 @@ pub async fn execute() -> Result<()> {
             print_success(&message);
             Ok(())
         }
-        Ok(Response::Error { message }) => {
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         Ok(_) => {
             print_success("Daemon killed");
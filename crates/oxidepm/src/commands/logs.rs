@@ -6,8 +6,12 @@ use oxidepm_ipc::{Request, Response};
 use regex::Regex;
 
 use crate::cli::LogsArgs;
+use crate::commands::DaemonError;
 use crate::output::{print_error, print_logs};
 
+/// Lines shown when neither --lines nor --since is given
+const DEFAULT_LINES: usize = 15;
+
 pub async fn execute(args: LogsArgs) -> Result<()> {
     let client = super::get_client();
     let selector = Selector::parse(&args.selector);
@@ -22,10 +26,14 @@ pub async fn execute(args: LogsArgs) -> Result<()> {
     let response = client
         .send(&Request::Logs {
             selector,
-            lines: args.lines,
+            lines: args.lines.unwrap_or(match args.since {
+                Some(_) => usize::MAX,
+                None => DEFAULT_LINES,
+            }),
             follow: args.follow,
             stdout: args.out,
             stderr: args.err,
+            since_secs: args.since,
         })
         .await?;
 
@@ -47,9 +55,13 @@ pub async fn execute(args: LogsArgs) -> Result<()> {
 
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
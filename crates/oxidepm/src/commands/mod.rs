@@ -1,26 +1,203 @@
 //! Command implementations
 
+pub mod apply;
+pub mod audit;
 pub mod check;
+pub mod config;
+pub mod daemon_reload;
 pub mod delete;
 pub mod describe;
+pub mod doctor;
+pub mod drain;
+pub mod env;
+pub mod events;
+pub mod exec;
 pub mod flush;
+pub mod group;
+pub mod health;
+pub mod hosts;
+pub mod import;
 pub mod kill;
 pub mod logs;
 pub mod notify;
+pub mod operations;
+pub mod pause;
 pub mod ping;
+pub mod ports;
+pub mod rename;
 pub mod restart;
 pub mod resurrect;
+pub mod run;
 pub mod save;
+pub mod secret;
 pub mod show;
+pub mod signal;
 pub mod start;
 pub mod startup;
 pub mod status;
 pub mod stop;
+pub mod supervision;
+pub mod top;
+pub mod update;
+pub mod wizard;
 
-use oxidepm_core::constants;
-use oxidepm_ipc::IpcClient;
+use oxidepm_core::{constants, ConfigFile, ErrorCode, HostsFile, Selector};
+use oxidepm_ipc::{Endpoint, IpcClient, Request, Response};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::cli::TargetArgs;
+use crate::output::{is_json_mode, Spinner};
+
+/// Daemon chosen with --host; unset means the local one
+static ENDPOINT: OnceLock<Endpoint> = OnceLock::new();
 
 /// Get the IPC client
 pub fn get_client() -> IpcClient {
-    IpcClient::new(constants::socket_path())
+    match ENDPOINT.get() {
+        Some(endpoint) => IpcClient::with_endpoint(endpoint.clone()),
+        None => IpcClient::new(constants::socket_path()),
+    }
+    .with_client_name("cli")
+}
+
+/// Point every command at the named host from hosts.toml
+pub fn select_host(name: Option<&str>) -> anyhow::Result<()> {
+    let Some(name) = name else {
+        return Ok(());
+    };
+    let hosts = HostsFile::load()?;
+    let endpoint = Endpoint::for_host(name, hosts.get(name)?)?;
+    let _ = ENDPOINT.set(endpoint);
+    Ok(())
+}
+
+/// Point the CLI, and any daemon it starts, at another home directory
+pub fn select_home(home: Option<&Path>, profile: Option<&str>) -> anyhow::Result<()> {
+    let home = match (home, profile) {
+        (Some(home), _) => std::env::current_dir()?.join(home),
+        (None, Some(profile)) => constants::profile_home(profile)?,
+        (None, None) => return Ok(()),
+    };
+    // Read by every path lookup and inherited by an auto-started daemon
+    std::env::set_var(constants::HOME_ENV, home);
+    Ok(())
+}
+
+/// Endpoints of the hosts.toml entries named in a comma-separated list ("all" for every host)
+pub fn host_endpoints(names: &str) -> anyhow::Result<Vec<(String, Endpoint)>> {
+    let hosts = HostsFile::load()?;
+    let selected = hosts.select(names)?;
+    selected
+        .into_iter()
+        .map(|(name, host)| Ok((name.to_string(), Endpoint::for_host(name, host)?)))
+        .collect()
+}
+
+/// Send a request that may run for a while, behind a spinner on a terminal
+/// that shows its latest output and, once it carries on in the background,
+/// a progress bar
+pub async fn send_tracked(
+    client: &IpcClient,
+    request: &Request,
+    label: &str,
+) -> anyhow::Result<Response> {
+    if is_json_mode() || !atty::is(atty::Stream::Stderr) {
+        return Ok(client.send(request).await?);
+    }
+
+    let spinner = Spinner::start(label);
+    let response = client
+        .send_with_operation(
+            request,
+            |line| spinner.set_message(&format!("{}: {}", label, line.trim())),
+            |operation| spinner.set_percent(operation.percent),
+        )
+        .await;
+    spinner.finish();
+    Ok(response?)
+}
+
+/// Turn stop/restart targets into selectors, one per config app when --config is used
+pub fn resolve_selectors(args: &TargetArgs) -> anyhow::Result<Vec<Selector>> {
+    let Some(path) = &args.config else {
+        if !args.selection.is_empty() {
+            anyhow::bail!("--only/--except require --config");
+        }
+        let selector = args.selector.as_deref().unwrap_or("all");
+        return Ok(vec![Selector::parse(selector)]);
+    };
+
+    let config = ConfigFile::load(path)?.select(&args.selection.only, &args.selection.except)?;
+    Ok(config
+        .apps
+        .into_iter()
+        .map(|app| Selector::ByName(app.name))
+        .collect())
+}
+
+/// Error reported by the daemon, keeping its machine-readable code
+#[derive(Debug)]
+pub struct DaemonError {
+    pub message: String,
+    pub code: Option<String>,
+    pub hint: Option<String>,
+}
+
+impl DaemonError {
+    pub fn new(message: String, code: Option<String>, hint: Option<String>) -> Self {
+        Self {
+            message,
+            code,
+            hint,
+        }
+    }
+}
+
+impl std::fmt::Display for DaemonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DaemonError {}
+
+/// Classify a command failure: (specific code string, taxonomy class)
+pub fn classify_error(err: &anyhow::Error) -> (String, ErrorCode) {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<DaemonError>() {
+            return match &e.code {
+                Some(code) => (code.clone(), ErrorCode::from_code(code)),
+                None => (ErrorCode::General.to_string(), ErrorCode::General),
+            };
+        }
+        if let Some(e) = cause.downcast_ref::<oxidepm_core::Error>() {
+            return (e.code().to_string(), e.kind());
+        }
+    }
+    (ErrorCode::General.to_string(), ErrorCode::General)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_error() {
+        let err: anyhow::Error = DaemonError::new(
+            "App not found: api".to_string(),
+            Some("app_not_found".to_string()),
+            None,
+        )
+        .into();
+        let (code, kind) = classify_error(&err);
+        assert_eq!(code, "app_not_found");
+        assert_eq!(kind.exit_code(), 3);
+
+        let err: anyhow::Error = oxidepm_core::Error::DaemonNotRunning.into();
+        assert_eq!(classify_error(&err).1, ErrorCode::DaemonUnreachable);
+
+        let err = anyhow::anyhow!("something else");
+        assert_eq!(classify_error(&err).1.exit_code(), 1);
+    }
 }
@@ -1,24 +1,59 @@
 //! Notification configuration command
 
 use anyhow::{bail, Result};
-use oxidepm_notify::{NotificationManager, NotifyConfig, ProcessEvent};
+use oxidepm_notify::{
+    NotificationManager, NotifyConfig, ProcessEvent, QuietHours, ReportPeriod, ReportsConfig,
+    Severity,
+};
 
 use crate::cli::{NotifyArgs, NotifyCommand};
 use crate::output::{print_error, print_info, print_success};
 
 pub async fn execute(args: NotifyArgs) -> Result<()> {
     match args.command {
-        NotifyCommand::Telegram { token, chat } => configure_telegram(token, chat).await,
+        NotifyCommand::Telegram {
+            token,
+            chat,
+            min_severity,
+        } => configure_telegram(token, chat, min_severity).await,
         NotifyCommand::Remove { channel } => remove_channel(&channel).await,
         NotifyCommand::Events { set } => set_events(&set).await,
+        NotifyCommand::Quiet {
+            from,
+            to,
+            min_severity,
+            off,
+        } => set_quiet_hours(from, to, min_severity, off).await,
+        NotifyCommand::Bot {
+            commands,
+            allow_chats,
+            off,
+        } => configure_bot(commands, allow_chats, off).await,
+        NotifyCommand::Reports {
+            period,
+            at,
+            weekday,
+            top,
+            off,
+        } => set_reports(&period, at, weekday, top, off).await,
         NotifyCommand::Status => show_status().await,
         NotifyCommand::Test => test_notification().await,
     }
 }
 
-async fn configure_telegram(token: String, chat: String) -> Result<()> {
+async fn configure_telegram(
+    token: String,
+    chat: String,
+    min_severity: Option<String>,
+) -> Result<()> {
     let mut config = NotifyConfig::load().unwrap_or_default();
     config.set_telegram(token, chat);
+    if let Some(min_severity) = min_severity {
+        let severity = parse_severity(&min_severity)?;
+        if let Some(ref mut telegram) = config.telegram {
+            telegram.min_severity = severity;
+        }
+    }
     config.save()?;
 
     print_success("Telegram notifications configured successfully");
@@ -80,6 +115,184 @@ async fn set_events(events_str: &str) -> Result<()> {
     Ok(())
 }
 
+async fn set_quiet_hours(
+    from: Option<String>,
+    to: Option<String>,
+    min_severity: Option<String>,
+    off: bool,
+) -> Result<()> {
+    let mut config = NotifyConfig::load().unwrap_or_default();
+
+    if off {
+        config.set_quiet_hours(None);
+        config.save()?;
+        print_success("Quiet hours disabled");
+        return Ok(());
+    }
+
+    let (Some(from), Some(to)) = (from, to) else {
+        bail!("Both --from and --to are required");
+    };
+    let mut quiet = QuietHours::new(from, to);
+    if let Some(min_severity) = min_severity {
+        quiet.min_severity = parse_severity(&min_severity)?;
+    }
+    config.set_quiet_hours(Some(quiet.clone()));
+
+    if let Err(e) = config.validate_quiet_hours() {
+        print_error(&format!("{}", e));
+        bail!(e);
+    }
+
+    config.save()?;
+
+    print_success(&format!(
+        "Quiet hours set to {}-{} (only {} and above)",
+        quiet.start, quiet.end, quiet.min_severity
+    ));
+
+    Ok(())
+}
+
+async fn configure_bot(
+    commands: Option<String>,
+    allow_chats: Vec<String>,
+    off: bool,
+) -> Result<()> {
+    let mut config = NotifyConfig::load().unwrap_or_default();
+
+    let Some(ref mut telegram) = config.telegram else {
+        print_error("Telegram is not configured");
+        print_info("Run 'oxidepm notify telegram --token <TOKEN> --chat <CHAT_ID>' first");
+        bail!("Not configured");
+    };
+
+    if off {
+        telegram.interactive = false;
+        config.save()?;
+        print_success("Telegram bot commands disabled");
+        print_info("Restart the daemon to apply: oxidepm kill");
+        return Ok(());
+    }
+
+    telegram.interactive = true;
+    if let Some(commands) = commands {
+        telegram.commands = commands
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+    }
+    if !allow_chats.is_empty() {
+        telegram.allowed_chats = allow_chats;
+    }
+
+    if let Err(e) = telegram.validate_commands() {
+        print_error(&format!("{}", e));
+        bail!(e);
+    }
+
+    let summary = format!(
+        "Telegram bot answering {} from {}",
+        describe_commands(&telegram.commands),
+        describe_chats(telegram)
+    );
+    config.save()?;
+
+    print_success(&summary);
+    print_info("Restart the daemon to apply: oxidepm kill");
+
+    Ok(())
+}
+
+fn describe_commands(commands: &[String]) -> String {
+    if commands.is_empty() {
+        "no commands".to_string()
+    } else {
+        commands
+            .iter()
+            .map(|c| format!("/{}", c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn describe_chats(telegram: &oxidepm_notify::TelegramConfig) -> String {
+    if telegram.allowed_chats.is_empty() {
+        format!("chat {}", telegram.chat_id)
+    } else {
+        format!("chats {}", telegram.allowed_chats.join(", "))
+    }
+}
+
+async fn set_reports(
+    period: &str,
+    at: String,
+    weekday: String,
+    top: usize,
+    off: bool,
+) -> Result<()> {
+    let mut config = NotifyConfig::load().unwrap_or_default();
+
+    if off {
+        config.set_reports(None);
+        config.save()?;
+        print_success("Summary reports disabled");
+        return Ok(());
+    }
+
+    let period = match period.to_lowercase().as_str() {
+        "daily" => ReportPeriod::Daily,
+        "weekly" => ReportPeriod::Weekly,
+        other => {
+            print_error(&format!("Invalid report period: {}", other));
+            bail!(
+                "Invalid report period '{}'. Valid values: daily, weekly",
+                other
+            );
+        }
+    };
+    config.set_reports(Some(ReportsConfig {
+        period,
+        at,
+        weekday,
+        top,
+    }));
+
+    if let Err(e) = config.validate_reports() {
+        print_error(&format!("{}", e));
+        bail!(e);
+    }
+
+    config.save()?;
+
+    print_success(&format!(
+        "Summary reports: {}",
+        describe_reports(config.reports.as_ref())
+    ));
+    print_info("Restart the daemon to apply: oxidepm kill");
+
+    Ok(())
+}
+
+/// One-line description of the report schedule
+fn describe_reports(reports: Option<&ReportsConfig>) -> String {
+    match reports {
+        None => "off".to_string(),
+        Some(r) => match r.period {
+            ReportPeriod::Daily => format!("daily at {}", r.at),
+            ReportPeriod::Weekly => format!("weekly on {} at {}", r.weekday, r.at),
+        },
+    }
+}
+
+fn parse_severity(value: &str) -> Result<Severity> {
+    value.parse::<Severity>().map_err(|e| {
+        print_error(&e);
+        anyhow::anyhow!(e)
+    })
+}
+
 async fn show_status() -> Result<()> {
     let config = NotifyConfig::load().unwrap_or_default();
 
@@ -96,6 +309,16 @@ async fn show_status() -> Result<()> {
             &telegram.bot_token[..8.min(telegram.bot_token.len())],
             &telegram.bot_token[telegram.bot_token.len().saturating_sub(4)..]
         );
+        println!("  Min severity: {}", telegram.min_severity);
+        if telegram.interactive {
+            println!(
+                "  Bot commands: {} from {}",
+                describe_commands(&telegram.commands),
+                describe_chats(telegram)
+            );
+        } else {
+            println!("  Bot commands: off");
+        }
     } else {
         println!("Telegram: not configured");
     }
@@ -108,6 +331,17 @@ async fn show_status() -> Result<()> {
         println!("Events: {}", config.events.join(", "));
     }
 
+    // Quiet hours
+    if let Some(ref quiet) = config.quiet_hours {
+        println!(
+            "Quiet hours: {}-{} (only {} and above)",
+            quiet.start, quiet.end, quiet.min_severity
+        );
+    } else {
+        println!("Quiet hours: off");
+    }
+    println!("Reports: {}", describe_reports(config.reports.as_ref()));
+
     // Config file location
     println!();
     println!(
@@ -0,0 +1,142 @@
+//! Operations command implementation - long requests the daemon tracks
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use colored::Colorize;
+use oxidepm_core::{OperationInfo, OperationState};
+use oxidepm_ipc::{IpcClient, Request, Response};
+
+use crate::cli::OperationsArgs;
+use crate::commands::DaemonError;
+use crate::output::{
+    format_duration, is_json_mode, print_error, print_info, print_success, progress_bar, Spinner,
+};
+
+pub async fn execute(args: OperationsArgs) -> Result<()> {
+    let client = super::get_client();
+
+    let Some(id) = args.id else {
+        let operations = match client.send(&Request::Operations).await? {
+            Response::Operations { operations } => operations,
+            response => return unexpected(response),
+        };
+        if is_json_mode() {
+            println!("{}", serde_json::to_string_pretty(&operations)?);
+        } else if operations.is_empty() {
+            print_info("No operations in the last hour");
+        } else {
+            for operation in &operations {
+                print_operation(operation);
+            }
+        }
+        return Ok(());
+    };
+
+    let (operation, result) = match client.send(&Request::OperationStatus { id }).await? {
+        Response::Operation { operation, .. } if args.wait && !operation.is_finished() => {
+            wait(&client, operation).await?
+        }
+        Response::Operation { operation, result } => (operation, result),
+        response => return unexpected(response),
+    };
+
+    if is_json_mode() {
+        let json = serde_json::json!({ "operation": operation, "result": result });
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        print_operation(&operation);
+        for line in &operation.log {
+            println!("  {}", line.dimmed());
+        }
+    }
+    match operation.state {
+        OperationState::Failed { error } => {
+            if !is_json_mode() {
+                print_error(&error);
+            }
+            bail!("Operation {} failed", id)
+        }
+        OperationState::Succeeded if !is_json_mode() => {
+            print_success(&format!("Operation {} succeeded", id));
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Follow an operation to its end, behind a progress bar on a terminal
+async fn wait(
+    client: &IpcClient,
+    operation: OperationInfo,
+) -> Result<(OperationInfo, Option<Box<Response>>)> {
+    let spinner = (!is_json_mode() && atty::is(atty::Stream::Stderr)).then(|| {
+        Spinner::start(&format!(
+            "Operation {} ({})",
+            operation.id, operation.action
+        ))
+    });
+    let (id, seen_lines) = (operation.id, operation.log_lines);
+    let mut last = operation;
+    let response = client
+        .follow_operation(
+            id,
+            seen_lines,
+            |line| {
+                if let Some(spinner) = &spinner {
+                    spinner.set_message(&format!("Operation {}: {}", id, line.trim()));
+                }
+            },
+            |operation| {
+                if let Some(spinner) = &spinner {
+                    spinner.set_percent(operation.percent);
+                }
+                last = operation.clone();
+            },
+        )
+        .await;
+    if let Some(spinner) = spinner {
+        spinner.finish();
+    }
+    Ok((last, Some(Box::new(response?))))
+}
+
+fn print_operation(operation: &OperationInfo) {
+    let state = match &operation.state {
+        OperationState::Running => "running".yellow(),
+        OperationState::Succeeded => "succeeded".green(),
+        OperationState::Failed { .. } => "failed".red(),
+    };
+    let elapsed = (operation.finished_at.unwrap_or_else(Utc::now) - operation.started_at)
+        .num_seconds()
+        .max(0) as u64;
+    let progress = operation
+        .percent
+        .map(|percent| format!(" {}", progress_bar(percent, 20)))
+        .unwrap_or_default();
+    println!(
+        "{} {} {} {} {}{}",
+        format!("#{}", operation.id).bold(),
+        operation.action,
+        operation.target.as_deref().unwrap_or("-"),
+        state,
+        format_duration(elapsed).dimmed(),
+        progress
+    );
+}
+
+fn unexpected(response: Response) -> Result<()> {
+    match response {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Pause/resume command implementation - freezes apps with SIGSTOP/SIGCONT
+
+use anyhow::{bail, Result};
+use oxidepm_core::{AppOpResult, Selector};
+use oxidepm_ipc::{Request, Response};
+
+use crate::cli::TargetArgs;
+use crate::commands::DaemonError;
+use crate::output::{count_failed, print_error, print_op_results, print_success_json};
+
+pub async fn pause(args: &TargetArgs) -> Result<()> {
+    let (total, results) = send_all(args, |selector| Request::Pause { selector }).await?;
+    finish(total, &results, "Paused", "No running processes to pause")
+}
+
+pub async fn resume(args: &TargetArgs) -> Result<()> {
+    let (total, results) = send_all(args, |selector| Request::Resume { selector }).await?;
+    finish(total, &results, "Resumed", "No paused processes to resume")
+}
+
+/// Send one request per selector, collecting the per-app outcomes
+async fn send_all(
+    args: &TargetArgs,
+    request: impl Fn(Selector) -> Request,
+) -> Result<(usize, Vec<AppOpResult>)> {
+    let client = super::get_client();
+    let mut total = 0;
+    let mut results = Vec::new();
+
+    for selector in super::resolve_selectors(args)? {
+        match client.send(&request(selector)).await? {
+            Response::Paused {
+                count,
+                results: done,
+            }
+            | Response::Resumed {
+                count,
+                results: done,
+            } => {
+                total += count;
+                results.extend(done);
+            }
+            Response::Error {
+                message,
+                code,
+                hint,
+            } => {
+                print_error(&message);
+                return Err(DaemonError::new(message, code, hint).into());
+            }
+            _ => {
+                print_error("Unexpected response from daemon");
+                bail!("Unexpected response")
+            }
+        }
+    }
+    Ok((total, results))
+}
+
+fn finish(total: usize, results: &[AppOpResult], verb: &str, none: &str) -> Result<()> {
+    print_op_results(results);
+    if total > 0 {
+        print_success_json(&format!("{} {} process(es)", verb, total), Some(results));
+    } else {
+        print_success_json(none, Some(results));
+    }
+
+    let failed = count_failed(results);
+    if failed > 0 {
+        bail!("{} process(es) failed", failed);
+    }
+    Ok(())
+}
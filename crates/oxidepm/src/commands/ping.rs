@@ -3,19 +3,38 @@
 use anyhow::{bail, Result};
 use oxidepm_ipc::{Request, Response};
 
-use crate::output::{print_error, print_success};
+use crate::commands::DaemonError;
+use crate::output::{format_duration, is_json_mode, print_error, print_success};
 
 pub async fn execute() -> Result<()> {
     let client = super::get_client();
 
     match client.send(&Request::Ping).await {
-        Ok(Response::Pong) => {
-            print_success("Daemon is alive");
+        Ok(Response::Pong { heartbeat }) => {
+            if is_json_mode() {
+                println!("{}", serde_json::to_string_pretty(&heartbeat)?);
+                return Ok(());
+            }
+            match heartbeat {
+                Some(h) => print_success(&format!(
+                    "Daemon is alive (pid {}, v{}, up {}, {} app(s), {} running)",
+                    h.pid,
+                    h.version,
+                    format_duration(h.uptime_secs),
+                    h.managed,
+                    h.running
+                )),
+                None => print_success("Daemon is alive"),
+            }
             Ok(())
         }
-        Ok(Response::Error { message }) => {
+        Ok(Response::Error {
+            message,
+            code,
+            hint,
+        }) => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         Ok(_) => {
             print_error("Unexpected response from daemon");
@@ -0,0 +1,40 @@
+//! Ports command implementation - list ports held by running apps
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use oxidepm_ipc::{Request, Response};
+
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_info};
+
+pub async fn execute() -> Result<()> {
+    let client = super::get_client();
+
+    match client.send(&Request::Ports).await? {
+        Response::Ports { ports } => {
+            if is_json_mode() {
+                println!("{}", serde_json::to_string_pretty(&ports)?);
+            } else if ports.is_empty() {
+                print_info("No ports held by running apps");
+            } else {
+                println!("{:<7} {:<5} {}", "PORT".bold(), "ID".bold(), "APP".bold());
+                for mapping in &ports {
+                    println!("{:<7} {:<5} {}", mapping.port, mapping.app_id, mapping.name);
+                }
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Rename command implementation
+
+use anyhow::{bail, Result};
+use oxidepm_core::Selector;
+use oxidepm_ipc::{Request, Response};
+
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_info, print_success_json};
+
+pub async fn execute(selector: &str, name: &str) -> Result<()> {
+    let request = Request::Rename {
+        selector: Selector::parse(selector),
+        name: name.to_string(),
+    };
+
+    let client = super::get_client();
+    match client.send(&request).await? {
+        Response::Renamed {
+            id,
+            from,
+            to,
+            restarted,
+        } => {
+            print_success_json(
+                &format!("Renamed {} to {}", from, to),
+                Some(serde_json::json!({
+                    "id": id,
+                    "from": from,
+                    "to": to,
+                    "restarted": restarted,
+                })),
+            );
+            if restarted && !is_json_mode() {
+                print_info(&format!("Restarted {} under its new name", to));
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
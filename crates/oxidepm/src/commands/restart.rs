@@ -1,33 +1,117 @@
 //! Restart command implementation
 
 use anyhow::{bail, Result};
-use oxidepm_core::Selector;
-use oxidepm_ipc::{Request, Response};
+use oxidepm_core::{AppOpResult, BulkOptions};
+use oxidepm_ipc::{IpcClient, Request, Response};
 
-use crate::output::{print_error, print_success};
+use crate::cli::TargetArgs;
+use crate::commands::DaemonError;
+use crate::output::{
+    count_failed, is_json_mode, nothing_done_message, print_error, print_info, print_op_results,
+    print_success_json, Spinner,
+};
 
-pub async fn execute(selector: &str) -> Result<()> {
+pub async fn execute(
+    args: &TargetArgs,
+    reset_counter: bool,
+    parallel: Option<u64>,
+    continue_on_error: bool,
+    if_exists: bool,
+) -> Result<()> {
     let client = super::get_client();
-    let selector = Selector::parse(selector);
+    let bulk = BulkOptions {
+        parallel: parallel.unwrap_or(0) as usize,
+        halt_on_error: !continue_on_error,
+        progress: !is_json_mode(),
+    };
+    let mut total = 0;
+    let mut results = Vec::new();
 
-    let response = client.send(&Request::Restart { selector }).await?;
-
-    match response {
-        Response::Restarted { count } => {
-            if count > 0 {
-                print_success(&format!("Restarted {} process(es)", count));
-            } else {
-                print_success("No processes to restart");
+    for selector in super::resolve_selectors(args)? {
+        let label = format!("Restarting {}", selector);
+        let request = Request::Restart {
+            selector,
+            reset_counter,
+            bulk: bulk.clone(),
+            if_exists,
+        };
+        match send_restart(&client, &request, &label).await? {
+            Response::Restarted {
+                count,
+                results: restarted,
+            } => {
+                total += count;
+                results.extend(restarted);
+            }
+            Response::Error {
+                message,
+                code,
+                hint,
+            } => {
+                print_error(&message);
+                return Err(DaemonError::new(message, code, hint).into());
+            }
+            _ => {
+                print_error("Unexpected response from daemon");
+                bail!("Unexpected response")
             }
-            Ok(())
         }
-        Response::Error { message } => {
-            print_error(&message);
-            bail!(message)
+        if bulk.halt_on_error && count_failed(&results) > 0 {
+            break;
         }
-        _ => {
-            print_error("Unexpected response from daemon");
-            bail!("Unexpected response")
+    }
+
+    print_op_results(&results);
+    if total > 0 {
+        print_success_json(&format!("Restarted {} process(es)", total), Some(&results));
+    } else {
+        print_success_json(
+            &nothing_done_message(&results, "No processes to restart"),
+            Some(&results),
+        );
+    }
+
+    let failed = count_failed(&results);
+    if failed > 0 {
+        if bulk.halt_on_error && !is_json_mode() {
+            print_halted(&results);
         }
+        bail!("{} process(es) failed to restart", failed);
+    }
+    Ok(())
+}
+
+/// Send a restart, showing each app as the daemon finishes it: behind a
+/// spinner on a terminal, as plain lines otherwise
+async fn send_restart(client: &IpcClient, request: &Request, label: &str) -> Result<Response> {
+    if is_json_mode() {
+        return Ok(client.send(request).await?);
+    }
+    if !atty::is(atty::Stream::Stderr) {
+        return Ok(client
+            .send_with_progress(request, |line| eprintln!("  {}", line))
+            .await?);
+    }
+
+    let spinner = Spinner::start(label);
+    let response = client
+        .send_with_operation(
+            request,
+            |line| spinner.set_message(&format!("{}: {}", label, line)),
+            |operation| spinner.set_percent(operation.percent),
+        )
+        .await;
+    spinner.finish();
+    Ok(response?)
+}
+
+/// Point out that apps were left alone because of a failure
+fn print_halted(results: &[AppOpResult]) {
+    let skipped = results.len() - AppOpResult::count_done(results) - count_failed(results);
+    if skipped > 0 {
+        print_info(&format!(
+            "Stopped after a failure; {} app(s) were not restarted (use --continue-on-error to restart them anyway)",
+            skipped
+        ));
     }
 }
@@ -2,26 +2,51 @@
 
 use anyhow::{bail, Result};
 use oxidepm_ipc::{Request, Response};
+use std::path::PathBuf;
 
-use crate::output::{print_error, print_info, print_success};
+use crate::commands::DaemonError;
+use crate::output::{print_error, print_info, print_success, print_warning};
 
-pub async fn execute() -> Result<()> {
+pub async fn execute(file: Option<PathBuf>, all: bool) -> Result<()> {
     let client = super::get_client();
 
-    let response = client.send(&Request::Resurrect).await?;
+    // The daemon resolves paths against its own working directory
+    let file = match file {
+        Some(path) => Some(std::env::current_dir()?.join(path)),
+        None => None,
+    };
+    let request = Request::Resurrect { file, all };
+    let response = super::send_tracked(&client, &request, "Resurrecting").await?;
 
     match response {
-        Response::Resurrected { count } => {
+        Response::Resurrected {
+            count,
+            stopped,
+            warnings,
+        } => {
+            for warning in &warnings {
+                print_warning(warning);
+            }
             if count > 0 {
                 print_success(&format!("Resurrected {} processes", count));
-            } else {
+            } else if stopped == 0 {
                 print_info("No saved processes to resurrect");
             }
+            if stopped > 0 {
+                print_info(&format!(
+                    "{} app(s) were stopped when saved and stay stopped (use --all to start them)",
+                    stopped
+                ));
+            }
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
@@ -0,0 +1,252 @@
+//! Run command implementation - runs an app in the foreground without the daemon
+//!
+//! The app is resolved, prepared and given its port and environment exactly
+//! as `start` would, but it runs attached to the terminal and is never
+//! registered, so nothing restarts it once it exits.
+
+use anyhow::{bail, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use colored::Colorize;
+use oxidepm_core::secrets::SecretStore;
+use oxidepm_core::{constants, AppSpec, PortConflictPolicy};
+use oxidepm_runtime::{foreground_command, get_runner, is_port_free, ProgressSink};
+use std::path::Path;
+
+use crate::cli::StartArgs;
+use crate::commands::start::{
+    build_app_spec, config_specs, detect_port, is_config_target, preflight, project_dir,
+};
+use crate::output::print_error;
+
+/// `matches` are the `run` subcommand's, which tell typed flags from defaults
+pub async fn execute(args: StartArgs, matches: &ArgMatches) -> Result<()> {
+    let Some(target) = args.target.clone() else {
+        bail!("No target specified. Give a file, directory or config file");
+    };
+    if args.git.is_some() {
+        bail!("--git cannot be used with run; clone the repository and run its directory");
+    }
+    if args.dry_run {
+        bail!("--dry-run cannot be used with run; use `oxidepm start --dry-run`");
+    }
+    if args.instances > 1 {
+        bail!(
+            "run starts a single instance; use `oxidepm start -i {}`",
+            args.instances
+        );
+    }
+    let unsupported = daemon_only_flags(&args, matches);
+    if !unsupported.is_empty() {
+        bail!(
+            "{} only apply to apps the daemon manages; use `oxidepm start` instead of run",
+            unsupported.join(", ")
+        );
+    }
+    let target_path = Path::new(&target);
+
+    let mut spec = if is_config_target(&target) {
+        let mut specs = config_specs(target_path, &args)?;
+        if specs.len() != 1 {
+            bail!(
+                "run takes a single app, but {} selects {}; pick one with --only <name>",
+                target,
+                specs.len()
+            );
+        }
+        specs.remove(0)
+    } else {
+        if !args.selection.is_empty() {
+            bail!("--only/--except can only be used when running from a config file");
+        }
+        let project_dir = project_dir(target_path);
+        preflight(project_dir, &target, &args, "run")?;
+        let mut spec = build_app_spec(&args)?;
+        detect_port(&mut spec, &args, project_dir);
+        spec
+    };
+
+    let runner = get_runner(spec.mode);
+    let progress = ProgressSink::new(|line| eprintln!("{}", line));
+    let prepared = runner.prepare_with_progress(&spec, &progress).await?;
+    if !prepared.success {
+        print_error(&format!("Prepare failed for {}", spec.name));
+        eprintln!("{}", prepared.output.trim_end());
+        bail!("Prepare failed");
+    }
+
+    reserve_port(&mut spec)?;
+    let launch_spec = SecretStore::open_default()
+        .resolve_spec(&spec)?
+        .for_launch();
+    let mut child = foreground_command(runner.as_ref(), &launch_spec)?.spawn()?;
+
+    // Ctrl-C reaches the app too; stay around until it has finished shutting down
+    let status = loop {
+        tokio::select! {
+            status = child.wait() => break status?,
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    };
+
+    #[cfg(unix)]
+    let code = {
+        use std::os::unix::process::ExitStatusExt;
+        status
+            .code()
+            .or_else(|| status.signal().map(|signal| 128 + signal))
+    };
+    #[cfg(not(unix))]
+    let code = status.code();
+    std::process::exit(code.unwrap_or(1));
+}
+
+/// The start flags that were set but mean nothing to a foreground run:
+/// restarts, watching, health checks, limits, hooks and log files are all
+/// the daemon's job
+fn daemon_only_flags(args: &StartArgs, matches: &ArgMatches) -> Vec<&'static str> {
+    // Flags with a default are set when typed, even if to the default
+    let typed = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    let set = [
+        ("--branch", args.branch.is_some()),
+        ("--clone-dir", args.clone_dir.is_some()),
+        ("--watch-env-file", args.watch_env_file.is_some()),
+        ("--out-file", args.out_file.is_some()),
+        ("--err-file", args.err_file.is_some()),
+        ("--merge-logs", args.merge_logs),
+        ("--watch", args.watch),
+        ("--ignore", !args.ignore.is_empty()),
+        ("--watch-path", !args.watch_path.is_empty()),
+        ("--watch-ext", !args.watch_ext.is_empty()),
+        ("--watch-poll", args.watch_poll.is_some()),
+        ("--restart-delay", typed("restart_delay")),
+        ("--max-restarts", typed("max_restarts")),
+        ("--kill-timeout", typed("kill_timeout")),
+        ("--build-timeout", args.build_timeout.is_some()),
+        ("--no-autorestart", args.no_autorestart),
+        ("--reload-on-change", args.reload_on_change),
+        ("--tag", !args.tag.is_empty()),
+        ("--namespace", args.namespace.is_some()),
+        ("--health-check", args.health_check.is_some()),
+        ("--max-memory", args.max_memory.is_some()),
+        ("--max-uptime", args.max_uptime.is_some()),
+        ("--delay", args.startup_delay.is_some()),
+        ("--wait-for", !args.wait_for.is_empty()),
+        ("--priority", typed("priority")),
+        ("--on-start", args.on_start.is_some()),
+        ("--on-stop", args.on_stop.is_some()),
+        ("--on-restart", args.on_restart.is_some()),
+        ("--on-crash", args.on_crash.is_some()),
+        ("--on-pre-reload", args.on_pre_reload.is_some()),
+        ("--on-post-reload", args.on_post_reload.is_some()),
+        ("--hook-user", args.hook_user.is_some()),
+    ];
+    set.into_iter()
+        .filter(|(_, is_set)| *is_set)
+        .map(|(flag, _)| flag)
+        .collect()
+}
+
+/// Give the app its port the way the daemon would, minus the registry of
+/// managed apps: an auto port is the first free one in range, and a taken
+/// port moves only when the spec allows it
+fn reserve_port(spec: &mut AppSpec) -> Result<()> {
+    let port = if spec.auto_port {
+        let (start, end) = spec.port_range.unwrap_or_else(constants::auto_port_range);
+        let Some(port) = spec
+            .requested_port()
+            .filter(|&port| is_port_free(port))
+            .or_else(|| (start..=end).find(|&port| is_port_free(port)))
+        else {
+            bail!(
+                "no free port left in the auto range {}-{} (widen port_range or {})",
+                start,
+                end,
+                constants::AUTO_PORT_RANGE_ENV
+            );
+        };
+        port
+    } else {
+        let Some(port) = spec.requested_port() else {
+            return Ok(());
+        };
+        if is_port_free(port) {
+            return Ok(());
+        }
+        let free = (port.saturating_add(1)..=u16::MAX).find(|&port| is_port_free(port));
+        match (spec.on_port_conflict, free) {
+            (PortConflictPolicy::Reassign, Some(free)) => {
+                eprintln!(
+                    "{} port {} is in use, using port {} instead",
+                    "[WARN]".yellow(),
+                    port,
+                    free
+                );
+                free
+            }
+            (_, Some(free)) => bail!(
+                "port {} is in use by another process; port {} is free",
+                port,
+                free
+            ),
+            (_, None) => bail!(
+                "port {} is in use by another process and no free port was found",
+                port
+            ),
+        }
+    };
+
+    spec.port = Some(port);
+    spec.env.insert("PORT".to_string(), port.to_string());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Commands};
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn daemon_only(argv: &[&str]) -> Vec<&'static str> {
+        let matches = Cli::command()
+            .try_get_matches_from(["oxidepm", "run"].iter().chain(argv))
+            .unwrap();
+        let Commands::Run(args) = Cli::from_arg_matches(&matches).unwrap().command else {
+            panic!("expected run");
+        };
+        daemon_only_flags(&args, matches.subcommand_matches("run").unwrap())
+    }
+
+    #[test]
+    fn test_daemon_only_flags() {
+        let unsupported = daemon_only(&[
+            "app.js",
+            "--port",
+            "3000",
+            "--env",
+            "A=1",
+            "--",
+            "--verbose",
+        ]);
+        assert!(unsupported.is_empty());
+
+        let unsupported = daemon_only(&[
+            "app.js",
+            "--watch",
+            "--max-memory",
+            "512",
+            "--health-check",
+            "http://localhost/health",
+            "--priority",
+            "-1",
+        ]);
+        assert_eq!(
+            unsupported,
+            vec!["--watch", "--health-check", "--max-memory", "--priority"]
+        );
+
+        // Typing a default still asks for something run does not do
+        let unsupported = daemon_only(&["app.js", "--max-restarts", "15", "--priority", "0"]);
+        assert_eq!(unsupported, vec!["--max-restarts", "--priority"]);
+    }
+}
@@ -2,22 +2,33 @@
 
 use anyhow::{bail, Result};
 use oxidepm_ipc::{Request, Response};
+use std::path::PathBuf;
 
+use crate::commands::DaemonError;
 use crate::output::{print_error, print_success};
 
-pub async fn execute() -> Result<()> {
+pub async fn execute(output: Option<PathBuf>) -> Result<()> {
     let client = super::get_client();
 
-    let response = client.send(&Request::Save).await?;
+    // The daemon resolves paths against its own working directory
+    let output = match output {
+        Some(path) => Some(std::env::current_dir()?.join(path)),
+        None => None,
+    };
+    let response = client.send(&Request::Save { output }).await?;
 
     match response {
         Response::Saved { count, path } => {
             print_success(&format!("Saved {} processes to {}", count, path));
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
@@ -0,0 +1,69 @@
+//! Secret command implementation - manage the encrypted secret store
+
+use anyhow::{bail, Result};
+use dialoguer::Password;
+use oxidepm_core::secrets::SecretStore;
+use std::io::Read;
+
+use crate::cli::{SecretArgs, SecretCommand};
+use crate::output::{is_json_mode, print_info, print_success_json};
+
+pub async fn execute(args: SecretArgs) -> Result<()> {
+    let store = SecretStore::open_default();
+    match args.command {
+        SecretCommand::Set { name } => {
+            let value = read_value(&name)?;
+            store.set(&name, &value)?;
+            print_success_json(
+                &format!("Stored secret {}", name),
+                Some(serde_json::json!({ "name": name })),
+            );
+            if !is_json_mode() {
+                print_info(&format!(
+                    "Reference it as ${{secret:{}}} in env values or args",
+                    name
+                ));
+            }
+        }
+        SecretCommand::List => {
+            let names = store.names()?;
+            if is_json_mode() {
+                println!("{}", serde_json::to_string_pretty(&names)?);
+            } else if names.is_empty() {
+                print_info("No secrets stored");
+            } else {
+                for name in names {
+                    println!("{}", name);
+                }
+            }
+        }
+        SecretCommand::Rm { name } => {
+            if !store.remove(&name)? {
+                bail!("Secret not found: {}", name);
+            }
+            print_success_json(
+                &format!("Removed secret {}", name),
+                Some(serde_json::json!({ "name": name })),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Prompt on a terminal, otherwise read the value from stdin
+fn read_value(name: &str) -> Result<String> {
+    if atty::is(atty::Stream::Stdin) {
+        return Ok(Password::new()
+            .with_prompt(format!("Value for {}", name))
+            .interact()?);
+    }
+
+    let mut value = String::new();
+    std::io::stdin().read_to_string(&mut value)?;
+    let value = value.strip_suffix('\n').unwrap_or(&value);
+    let value = value.strip_suffix('\r').unwrap_or(value);
+    if value.is_empty() {
+        bail!("No value provided on stdin");
+    }
+    Ok(value.to_string())
+}
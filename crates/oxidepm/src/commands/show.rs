@@ -4,6 +4,7 @@ use anyhow::{bail, Result};
 use oxidepm_core::Selector;
 use oxidepm_ipc::{Request, Response};
 
+use crate::commands::DaemonError;
 use crate::output::{print_app_detail, print_error};
 
 pub async fn execute(selector: &str) -> Result<()> {
@@ -17,9 +18,13 @@ pub async fn execute(selector: &str) -> Result<()> {
             print_app_detail(&app);
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
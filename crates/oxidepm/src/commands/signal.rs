@@ -0,0 +1,49 @@
+//! Signal command implementation - passes a signal through to apps
+
+use anyhow::{bail, Result};
+use oxidepm_core::{AppSignal, Selector};
+use oxidepm_ipc::{Request, Response};
+
+use crate::commands::DaemonError;
+use crate::output::{count_failed, print_error, print_op_results, print_success_json};
+
+pub async fn execute(selector: &str, signal: AppSignal, tree: bool) -> Result<()> {
+    let client = super::get_client();
+    let request = Request::Signal {
+        selector: Selector::parse(selector),
+        signal,
+        tree,
+    };
+
+    match client.send(&request).await? {
+        Response::Signalled { count, results } => {
+            print_op_results(&results);
+            if count > 0 {
+                print_success_json(
+                    &format!("Sent {} to {} process(es)", signal, count),
+                    Some(&results),
+                );
+            } else {
+                print_success_json("No running processes to signal", Some(&results));
+            }
+
+            let failed = count_failed(&results);
+            if failed > 0 {
+                bail!("{} process(es) could not be signalled", failed);
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
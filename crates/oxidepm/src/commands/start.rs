@@ -2,16 +2,23 @@
 
 use anyhow::{bail, Result};
 use colored::Colorize;
-use dialoguer::Confirm;
-use oxidepm_core::{AppMode, AppSpec, ConfigFile, RestartPolicy, constants};
+use dialoguer::{Confirm, MultiSelect};
+use oxidepm_core::env::mask_value;
+use oxidepm_core::{
+    constants, start_order, AppMode, AppSpec, CheckStatus, ConfigFile, EnvSource, HealthCheck,
+    PortConflictPolicy, RestartPolicy,
+};
 use oxidepm_ipc::{Request, Response};
-use std::collections::HashMap;
+use oxidepm_runtime::preflight::run_preflight_checks;
+use oxidepm_runtime::workspace::{runnable_members, MemberKind, RunnableMember};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::cli::StartArgs;
-use crate::commands::check::{run_preflight_checks, check_port_conflict, CheckStatus};
-use crate::output::{print_error, print_success};
+use crate::commands::check::detect_project_port;
+use crate::commands::DaemonError;
+use crate::output::{is_json_mode, print_error, print_hint, print_success, print_warning, Spinner};
 
 pub async fn execute(mut args: StartArgs) -> Result<()> {
     let client = super::get_client();
@@ -25,6 +32,20 @@ pub async fn execute(mut args: StartArgs) -> Result<()> {
         args.setup = true;
     }
 
+    if let Some(namespace) = &args.namespace {
+        if !oxidepm_core::validate_app_name(namespace) {
+            bail!(
+                "Invalid namespace '{}': only alphanumeric characters, underscores, and hyphens are allowed",
+                namespace
+            );
+        }
+    }
+
+    // No target in a terminal: ask what to start
+    if super::wizard::is_available(&args) && !args.dry_run {
+        return super::wizard::execute(&client, &args).await;
+    }
+
     // Ensure we have a target
     let target = args.target.as_ref().ok_or_else(|| {
         anyhow::anyhow!("No target specified. Use a file/directory path or --git <url>")
@@ -34,136 +55,74 @@ pub async fn execute(mut args: StartArgs) -> Result<()> {
     let target_path = Path::new(target);
 
     // Check if it's a config file
-    if target_path.is_file()
-        && (target.ends_with(".toml") || target.ends_with(".json"))
-    {
+    if is_config_target(target) {
+        if args.dry_run {
+            return print_specs(&config_specs(target_path, &args)?);
+        }
         // Load config file and start all apps
         return start_from_config(&client, target_path, &args).await;
     }
 
-    // Determine project directory for preflight checks
-    let project_dir = if target_path.is_file() {
-        target_path.parent().unwrap_or(Path::new("."))
-    } else if target_path.is_dir() {
-        target_path
-    } else {
-        Path::new(".")
-    };
-
-    // Run preflight checks unless --no-check is specified
-    if !args.no_check {
-        let summary = run_preflight_checks(project_dir, args.setup);
+    // A monorepo root: one app per runnable member
+    if let Some(members) = monorepo_members(target_path, &args) {
+        return start_monorepo(&client, target, members, &args).await;
+    }
 
-        // Print check results if there are issues
-        if summary.warnings > 0 || summary.errors > 0 {
-            if !args.setup {
-                // Show what's wrong and suggest fix
-                eprintln!("{}", "Cannot start - preflight checks failed:".red().bold());
-                eprintln!();
-                for result in &summary.results {
-                    if result.status == CheckStatus::Warn || result.status == CheckStatus::Error {
-                        let prefix = if result.status == CheckStatus::Error {
-                            "[ERROR]".red()
-                        } else {
-                            "[WARN]".yellow()
-                        };
-                        eprintln!("  {} {}", prefix, result.message);
-                    }
-                }
-                eprintln!();
-                eprintln!("Fix with: {} {} {}",
-                    "oxidepm start".cyan(),
-                    target.cyan(),
-                    "--setup".cyan()
-                );
-                eprintln!("Or run:   {} {} {}",
-                    "oxidepm check".cyan(),
-                    target.cyan(),
-                    "--fix".cyan()
-                );
-                bail!("Preflight checks failed");
-            } else {
-                // --setup was provided, show what was fixed
-                println!("{}", "Setting up project...".cyan());
-                for result in &summary.results {
-                    if result.status == CheckStatus::Fixed {
-                        println!("  {} {}", "[FIX]".blue(), result.message);
-                    }
-                }
-                // Re-check after fixes
-                let recheck = run_preflight_checks(project_dir, false);
-                if recheck.has_blocking_issues {
-                    eprintln!();
-                    eprintln!("{}", "Setup incomplete - some issues could not be fixed:".red());
-                    for result in &recheck.results {
-                        if result.status == CheckStatus::Error {
-                            eprintln!("  {} {}", "[ERROR]".red(), result.message);
-                        }
-                    }
-                    bail!("Setup failed");
-                }
-                println!("{}", "Setup complete!".green());
-                println!();
-            }
-        }
+    if !args.selection.is_empty() {
+        bail!("--only/--except can only be used when starting from a config file or a monorepo");
+    }
 
-        // Check for port conflicts (skip if user explicitly provided PORT)
-        let user_provided_port = args.envs.iter().any(|(k, _)| k == "PORT");
-        if !user_provided_port {
-            if let Some(port_check) = check_port_conflict(project_dir) {
-                if port_check.is_in_use {
-                eprintln!("{} Port {} is already in use", "[WARN]".yellow(), port_check.desired_port);
-
-                if let Some(available) = port_check.available_port {
-                    // Try interactive prompt, fall back to suggesting command if not a terminal
-                    let use_alternative = if atty::is(atty::Stream::Stdin) {
-                        Confirm::new()
-                            .with_prompt(format!("Would you like to run on port {} instead?", available))
-                            .default(true)
-                            .interact()
-                            .unwrap_or(false)
-                    } else {
-                        // Non-interactive: suggest command and exit
-                        eprintln!();
-                        eprintln!("To use port {} instead, run:", available);
-                        eprintln!("  {} {} {} {}",
-                            "oxidepm start".cyan(),
-                            target.cyan(),
-                            "--env".cyan(),
-                            format!("PORT={}", available).cyan()
-                        );
-                        bail!("Port {} is in use. Use --env PORT={} to use an alternative port",
-                              port_check.desired_port, available);
-                    };
-
-                    if use_alternative {
-                        // Add PORT env var to use the alternative port
-                        args.envs.push(("PORT".to_string(), available.to_string()));
-                        println!("{} Using port {}", "[OK]".green(), available);
-                    } else {
-                        bail!("Port {} is in use. Free the port or specify a different one with --env PORT=<port>", port_check.desired_port);
-                    }
-                } else {
-                    bail!("Port {} is in use and no available ports found", port_check.desired_port);
-                }
-            }
-        }
-        }
+    let project_dir = project_dir(target_path);
+    if !args.dry_run {
+        preflight(project_dir, target, &args, "start")?;
     }
 
     // Single app start
-    let spec = build_app_spec(&args)?;
+    let mut spec = build_app_spec(&args)?;
+
+    detect_port(&mut spec, &args, project_dir);
+
+    if args.dry_run {
+        return print_specs(std::slice::from_ref(&spec));
+    }
 
-    let response = client.send(&Request::Start { spec: spec.clone() }).await?;
+    let mut response = send_start(&client, spec.clone()).await?;
+
+    if let Response::Error {
+        code: Some(code),
+        message,
+        ..
+    } = &response
+    {
+        if code == "port_conflict" && atty::is(atty::Stream::Stdin) {
+            eprintln!("{} {}", "[WARN]".yellow(), message);
+            let reassign = Confirm::new()
+                .with_prompt("Start on a free port instead?")
+                .default(true)
+                .interact()
+                .unwrap_or(false);
+            if reassign {
+                spec.on_port_conflict = PortConflictPolicy::Reassign;
+                response = send_start(&client, spec).await?;
+            }
+        }
+    }
 
     match response {
         Response::Started { id, name } => {
             print_success(&format!("Started {} (id: {})", name, id));
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            if let Some(hint) = &hint {
+                print_hint(hint);
+            }
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
@@ -270,35 +229,426 @@ fn extract_repo_name(url: &str) -> Result<String> {
     Ok(name.to_string())
 }
 
-async fn start_from_config(
-    client: &oxidepm_ipc::IpcClient,
-    config_path: &Path,
-    _args: &StartArgs,
+/// Directory a file or directory target belongs to, where preflight checks
+/// and port detection look
+pub(crate) fn project_dir(target_path: &Path) -> &Path {
+    if target_path.is_file() {
+        target_path.parent().unwrap_or(Path::new("."))
+    } else if target_path.is_dir() {
+        target_path
+    } else {
+        Path::new(".")
+    }
+}
+
+/// Run preflight checks unless --no-check is specified, fixing what they
+/// can with --setup. `command` is the subcommand the hints suggest re-running.
+pub(crate) fn preflight(
+    project_dir: &Path,
+    target: &str,
+    args: &StartArgs,
+    command: &str,
 ) -> Result<()> {
-    let config = ConfigFile::load(config_path)?;
+    if !args.no_check {
+        let summary = run_preflight_checks(project_dir, args.setup);
+
+        // Print check results if there are issues
+        if summary.warnings > 0 || summary.errors > 0 {
+            if !args.setup {
+                // Show what's wrong and suggest fix
+                eprintln!(
+                    "{}",
+                    format!("Cannot {} - preflight checks failed:", command)
+                        .red()
+                        .bold()
+                );
+                eprintln!();
+                for result in &summary.results {
+                    if result.status == CheckStatus::Warn || result.status == CheckStatus::Error {
+                        let prefix = if result.status == CheckStatus::Error {
+                            "[ERROR]".red()
+                        } else {
+                            "[WARN]".yellow()
+                        };
+                        eprintln!("  {} {}", prefix, result.message);
+                    }
+                }
+                eprintln!();
+                eprintln!(
+                    "Fix with: {} {} {}",
+                    format!("oxidepm {}", command).cyan(),
+                    target.cyan(),
+                    "--setup".cyan()
+                );
+                eprintln!(
+                    "Or run:   {} {} {}",
+                    "oxidepm check".cyan(),
+                    target.cyan(),
+                    "--fix".cyan()
+                );
+                bail!("Preflight checks failed");
+            } else {
+                // --setup was provided, show what was fixed
+                println!("{}", "Setting up project...".cyan());
+                for result in &summary.results {
+                    if result.status == CheckStatus::Fixed {
+                        println!("  {} {}", "[FIX]".blue(), result.message);
+                    }
+                }
+                // Re-check after fixes
+                let recheck = run_preflight_checks(project_dir, false);
+                if !recheck.can_start() {
+                    eprintln!();
+                    eprintln!(
+                        "{}",
+                        "Setup incomplete - some issues could not be fixed:".red()
+                    );
+                    // Failed fix attempts (e.g. dependency downloads) carry the classified cause
+                    for result in summary.results.iter().chain(&recheck.results) {
+                        if result.status == CheckStatus::Error {
+                            eprintln!("  {} {}", "[ERROR]".red(), result.message);
+                            if let Some(hint) = &result.fix_hint {
+                                print_hint(hint);
+                            }
+                        }
+                    }
+                    bail!("Setup failed");
+                }
+                println!("{}", "Setup complete!".green());
+                println!();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Use the project's own port unless the spec picks one or PORT was given explicitly
+pub(crate) fn detect_port(spec: &mut AppSpec, args: &StartArgs, project_dir: &Path) {
+    if !spec.auto_port && spec.port.is_none() && !args.envs.iter().any(|(k, _)| k == "PORT") {
+        spec.port = detect_project_port(project_dir);
+    }
+}
+
+/// Whether a start target names a config file rather than an app
+pub(crate) fn is_config_target(target: &str) -> bool {
+    Path::new(target).is_file() && (target.ends_with(".toml") || target.ends_with(".json"))
+}
+
+/// Apps a config file declares, narrowed by --only/--except
+pub(crate) fn config_specs(config_path: &Path, args: &StartArgs) -> Result<Vec<AppSpec>> {
+    let config =
+        ConfigFile::load(config_path)?.select(&args.selection.only, &args.selection.except)?;
     let base_dir = config_path.parent().unwrap_or(Path::new("."));
 
-    let specs = config.into_specs(base_dir)?;
+    let mut specs = config.into_specs(base_dir)?;
+    if let Some(namespace) = &args.namespace {
+        for spec in &mut specs {
+            spec.namespace = namespace.clone();
+        }
+    }
+    Ok(specs)
+}
 
+async fn start_from_config(
+    client: &oxidepm_ipc::IpcClient,
+    config_path: &Path,
+    args: &StartArgs,
+) -> Result<()> {
+    let specs = config_specs(config_path, args)?;
     if specs.is_empty() {
-        print_error("No apps defined in config file");
+        if args.selection.is_empty() {
+            print_error("No apps defined in config file");
+        } else {
+            print_error("No apps left after --only/--except");
+        }
         bail!("No apps in config");
     }
 
+    start_specs(client, specs).await
+}
+
+/// Runnable members of a monorepo root target, unless a flag picks a single
+/// app out of it (--workspace, --bin, --script, --mode, --name)
+fn monorepo_members(target_path: &Path, args: &StartArgs) -> Option<Vec<RunnableMember>> {
+    let single = args.workspace.is_some()
+        || args.bin.is_some()
+        || args.script.is_some()
+        || args.mode.is_some()
+        || args.name.is_some();
+    if single || !target_path.is_dir() {
+        return None;
+    }
+    let members = runnable_members(target_path);
+    // A crate whose workspace only holds libraries is still a single app
+    let root = target_path.canonicalize().ok();
+    let monorepo = members
+        .iter()
+        .any(|member| member.dir.canonicalize().ok() != root);
+    monorepo.then_some(members)
+}
+
+async fn start_monorepo(
+    client: &oxidepm_ipc::IpcClient,
+    target: &str,
+    members: Vec<RunnableMember>,
+    args: &StartArgs,
+) -> Result<()> {
+    let root = Path::new(target);
+    let members = select_members(members, args)?;
+    if members.is_empty() {
+        print_error("No members selected");
+        bail!("No apps to start");
+    }
+    if args.port.is_some() && members.len() > 1 {
+        bail!(
+            "--port can't be shared by {} apps; use --auto-port, or --only to start one",
+            members.len()
+        );
+    }
+
+    if !args.dry_run {
+        preflight(root, target, args, "start")?;
+    }
+    let specs = members
+        .iter()
+        .map(|member| member_spec(args, root, member))
+        .collect::<Result<Vec<_>>>()?;
+    if args.dry_run {
+        return print_specs(&specs);
+    }
+    start_specs(client, specs).await
+}
+
+/// Members narrowed by --only/--except, else picked on a terminal (all by
+/// default), else all of them
+fn select_members(members: Vec<RunnableMember>, args: &StartArgs) -> Result<Vec<RunnableMember>> {
+    for (index, member) in members.iter().enumerate() {
+        if let Some(other) = members[index + 1..].iter().find(|m| m.name == member.name) {
+            bail!(
+                "Monorepo members {} and {} would both be named '{}'; start them one at a time with --bin or --workspace and --name",
+                member.kind.describe(),
+                other.kind.describe(),
+                member.name
+            );
+        }
+    }
+    let (only, except) = (&args.selection.only, &args.selection.except);
+    if !args.selection.is_empty() {
+        for name in only.iter().chain(except) {
+            if !members.iter().any(|member| &member.name == name) {
+                let available: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+                bail!(
+                    "'{}' is not a runnable member of this monorepo (available: {})",
+                    name,
+                    available.join(", ")
+                );
+            }
+        }
+        return Ok(members
+            .into_iter()
+            .filter(|m| (only.is_empty() || only.contains(&m.name)) && !except.contains(&m.name))
+            .collect());
+    }
+
+    if args.dry_run || is_json_mode() || !atty::is(atty::Stream::Stdin) {
+        return Ok(members);
+    }
+    let items: Vec<String> = members
+        .iter()
+        .map(|member| format!("{} ({})", member.name, member.kind.describe()))
+        .collect();
+    let chosen = MultiSelect::new()
+        .with_prompt("Monorepo found - start which members?")
+        .items(&items)
+        .defaults(&vec![true; items.len()])
+        .interact()?;
+    Ok(members
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| chosen.contains(index))
+        .map(|(_, member)| member)
+        .collect())
+}
+
+/// App spec of one monorepo member, run from the root with the flags given
+/// for the whole monorepo
+fn member_spec(args: &StartArgs, root: &Path, member: &RunnableMember) -> Result<AppSpec> {
+    let mut spec = build_app_spec(args)?;
+    spec.name = member.name.clone();
+    match &member.kind {
+        MemberKind::CargoBin(bin) => {
+            spec.mode = AppMode::Cargo;
+            spec.command = bin.clone();
+        }
+        MemberKind::NpmPackage(package) => {
+            spec.mode = node_tool(root);
+            spec.command = "start".to_string();
+            spec.workspace = Some(package.clone());
+        }
+    }
+    detect_port(&mut spec, args, &member.dir);
+    Ok(spec)
+}
+
+/// Print the specs a start would send, for --dry-run: as JSON (an object for
+/// one app, an array for a config file) or readable with secrets masked
+fn print_specs(specs: &[AppSpec]) -> Result<()> {
+    if is_json_mode() {
+        let json = match specs {
+            [spec] => serde_json::to_string_pretty(spec)?,
+            specs => serde_json::to_string_pretty(specs)?,
+        };
+        println!("{}", json);
+        return Ok(());
+    }
+
+    for (index, spec) in specs.iter().enumerate() {
+        if index > 0 {
+            println!("{}", "─".repeat(50));
+        }
+        print_spec(spec);
+    }
+    Ok(())
+}
+
+fn print_spec(spec: &AppSpec) {
+    println!("Process: {}", spec.qualified_name());
+    println!("Mode: {}", spec.mode);
+    println!("Working Directory: {}", spec.cwd.display());
+    println!("Command: {}", spec.command);
+    if !spec.args.is_empty() {
+        println!("Arguments: {}", spec.args.join(" "));
+    }
+    if spec.instances > 1 {
+        println!("Instances: {}", spec.instances);
+    }
+    if let Some(port) = spec.port {
+        println!("Port: {}", port);
+    } else if spec.auto_port {
+        println!("Port: picked by the daemon");
+    }
+    if let Some(check) = &spec.health_check {
+        println!("Health Check: {}", check.endpoint());
+    }
+    if let Some(max_memory_mb) = spec.max_memory_mb {
+        println!("Max Memory: {} MB", max_memory_mb);
+    }
+    if !spec.tags.is_empty() {
+        println!("Tags: {}", spec.tags.join(", "));
+    }
+
+    let policy = &spec.restart_policy;
+    println!();
+    println!("Restart Policy:");
+    println!("  auto restart: {}", if policy.auto_restart { "yes" } else { "no" });
+    println!("  max restarts: {} within {}s", policy.max_restarts, policy.crash_window_secs);
+    println!("  restart delay: {}ms", policy.restart_delay_ms);
+    println!("  kill timeout: {}ms", spec.kill_timeout_ms);
+
+    println!();
+    println!("Watch: {}", if spec.watch { "yes" } else { "no" });
+    if !spec.watch_paths.is_empty() {
+        let paths: Vec<_> = spec.watch_paths.iter().map(|p| p.display().to_string()).collect();
+        println!("  paths: {}", paths.join(", "));
+    }
+    if !spec.watch_extensions.is_empty() {
+        println!("  extensions: {}", spec.watch_extensions.join(", "));
+    }
+    println!("  ignore: {}", spec.ignore_patterns.join(", "));
+
+    if !spec.env.is_empty() {
+        println!();
+        println!("Environment Variables:");
+        let mut env: Vec<_> = spec.env.iter().collect();
+        env.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, value) in env {
+            let line = format!(
+                "  {}={}{}",
+                key,
+                mask_value(key, value),
+                format!("  ({})", spec.env_source(key)).dimmed()
+            );
+            if spec.env_source(key) == EnvSource::Inherited {
+                println!("{}", line.dimmed());
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+    if let Some(env_file) = &spec.env_file {
+        println!("Env File: {}", env_file.display());
+    }
+}
+
+/// Send a start request, showing the build output the daemon streams back
+/// while it prepares the app: behind a spinner on a terminal, as plain lines
+/// otherwise
+async fn send_start(client: &oxidepm_ipc::IpcClient, spec: AppSpec) -> Result<Response> {
+    if is_json_mode() {
+        return Ok(client
+            .send(&Request::Start {
+                spec,
+                progress: false,
+            })
+            .await?);
+    }
+
+    let label = format!("Starting {}", spec.name);
+    let request = Request::Start {
+        spec,
+        progress: true,
+    };
+    if !atty::is(atty::Stream::Stderr) {
+        return Ok(client
+            .send_with_progress(&request, |line| eprintln!("  {}", line))
+            .await?);
+    }
+
+    let spinner = Spinner::start(&label);
+    let response = client
+        .send_with_operation(
+            &request,
+            |line| spinner.set_message(&format!("{}: {}", label, line.trim())),
+            |operation| spinner.set_percent(operation.percent),
+        )
+        .await;
+    spinner.finish();
+    Ok(response?)
+}
+
+/// Start a list of apps one by one in `start_order` (priority bands, then
+/// dependencies), reporting each result
+pub(crate) async fn start_specs(
+    client: &oxidepm_ipc::IpcClient,
+    specs: Vec<AppSpec>,
+) -> Result<()> {
     let mut started = 0;
     let mut failed = 0;
 
-    for spec in specs {
+    let mut warnings = Vec::new();
+    let mut ordered: Vec<_> = start_order(&specs, &mut warnings)
+        .into_iter()
+        .zip(specs)
+        .collect();
+    for warning in &warnings {
+        print_warning(warning);
+    }
+    ordered.sort_by_key(|(order, _)| *order);
+
+    for (_, spec) in ordered {
         let name = spec.name.clone();
-        let response = client.send(&Request::Start { spec }).await?;
+        let response = send_start(client, spec).await?;
 
         match response {
             Response::Started { id, name } => {
                 print_success(&format!("Started {} (id: {})", name, id));
                 started += 1;
             }
-            Response::Error { message } => {
+            Response::Error { message, hint, .. } => {
                 print_error(&format!("Failed to start {}: {}", name, message));
+                if let Some(hint) = hint {
+                    print_hint(&hint);
+                }
                 failed += 1;
             }
             _ => {
@@ -317,7 +667,28 @@ async fn start_from_config(
     Ok(())
 }
 
-fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
+/// Package manager a Node.js project or monorepo root uses, judged by its lockfile
+pub(crate) fn node_tool(root: &Path) -> AppMode {
+    if root.join("pnpm-workspace.yaml").exists() || root.join("pnpm-lock.yaml").exists() {
+        AppMode::Pnpm
+    } else if root.join("yarn.lock").exists() {
+        AppMode::Yarn
+    } else {
+        AppMode::Npm
+    }
+}
+
+/// Health check for --health-check: a URL is polled, anything else is run
+/// as a script
+pub(crate) fn health_check(check: &str) -> HealthCheck {
+    if check.starts_with("http://") || check.starts_with("https://") {
+        HealthCheck::http(check)
+    } else {
+        HealthCheck::script(check)
+    }
+}
+
+pub(crate) fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
     let target = args.target.as_ref().ok_or_else(|| {
         anyhow::anyhow!("No target specified")
     })?;
@@ -326,6 +697,9 @@ fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
     // Determine mode
     let mode = if let Some(mode_str) = &args.mode {
         mode_str.parse::<AppMode>()?
+    } else if args.workspace.is_some() {
+        // Monorepos are run with the package manager the root uses
+        node_tool(target_path)
     } else if args.script.is_some() {
         // npm/pnpm/yarn mode
         AppMode::Npm
@@ -342,7 +716,7 @@ fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
         cwd.canonicalize().unwrap_or_else(|_| cwd.clone())
     } else if target_path.is_dir() {
         target_path.canonicalize().unwrap_or_else(|_| target_path.to_path_buf())
-    } else if let Some(parent) = target_path.parent() {
+    } else if let Some(parent) = target_path.parent().filter(|p| !p.as_os_str().is_empty()) {
         parent.canonicalize().unwrap_or_else(|_| parent.to_path_buf())
     } else {
         std::env::current_dir()?
@@ -373,7 +747,15 @@ fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
 
     // Determine name
     let name = args.name.clone().unwrap_or_else(|| {
-        if target_path.is_dir() {
+        if let Some(workspace) = &args.workspace {
+            // "@acme/web" or "apps/web" -> "web"
+            workspace
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(workspace)
+                .to_string()
+        } else if target_path.is_dir() {
             target_path
                 .file_name()
                 .and_then(|n| n.to_str())
@@ -388,12 +770,14 @@ fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
         }
     });
 
-    // Build environment
+    // Build environment, remembering where each variable came from
     let mut env: HashMap<String, String> = HashMap::new();
+    let mut env_sources: BTreeMap<String, EnvSource> = BTreeMap::new();
 
     // If env_inherit is set, start with parent process environment
     if args.env_inherit {
         for (key, value) in std::env::vars() {
+            env_sources.insert(key.clone(), EnvSource::Inherited);
             env.insert(key, value);
         }
     }
@@ -410,6 +794,7 @@ fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
                 if let Some(pos) = line.find('=') {
                     let key = line[..pos].trim().to_string();
                     let value = line[pos + 1..].trim().trim_matches('"').to_string();
+                    env_sources.insert(key.clone(), EnvSource::EnvFile);
                     env.insert(key, value);
                 }
             }
@@ -418,6 +803,7 @@ fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
 
     // Finally overlay with explicit --env arguments (highest priority)
     for (key, value) in args.envs.iter().cloned() {
+        env_sources.insert(key.clone(), EnvSource::Flag);
         env.insert(key, value);
     }
 
@@ -436,44 +822,89 @@ fn build_app_spec(args: &StartArgs) -> Result<AppSpec> {
         crash_window_secs: 60,
     };
 
+    let invoked_from = std::env::current_dir()?;
+    // The daemon re-reads the file, from its own working directory
+    let env_file = args.env_file.as_ref().map(|file| invoked_from.join(file));
+
     Ok(AppSpec {
         id: 0, // Will be assigned by daemon
         name,
+        namespace: args
+            .namespace
+            .clone()
+            .unwrap_or_else(|| oxidepm_core::DEFAULT_NAMESPACE.to_string()),
         mode,
         command,
         args: args.args.clone(),
         cwd,
+        workspace: args.workspace.clone(),
+        cargo_features: args.cargo_features.clone(),
+        cargo_profile: args.cargo_profile.clone(),
+        cargo_target: args.cargo_target.clone(),
         env,
+        env_sources,
+        env_file,
+        watch_env_file: args.watch_env_file.map(Into::into),
         watch: args.watch,
         ignore_patterns,
+        watch_paths: args.watch_path.clone(),
+        watch_extensions: oxidepm_core::normalize_extensions(args.watch_ext.clone()),
+        watch_poll_ms: args.watch_poll,
+        reload_on_change: args.reload_on_change,
+        build_timeout_secs: args.build_timeout,
         restart_policy,
         kill_timeout_ms: args.kill_timeout,
         created_at: chrono::Utc::now(),
         // Clustering
-        instances: 1,
+        instances: args.instances,
         instance_id: None,
         // Port management
-        port: None,
+        port: args.port,
         port_range: None,
-        // Health checks
-        health_check: None,
-        // Memory limit
-        max_memory_mb: None,
+        on_port_conflict: Default::default(),
+        auto_port: args.auto_port,
+        // Health checks (readiness checks and warm-ups are only configurable in config files)
+        health_check: args.health_check.as_deref().map(health_check),
+        readiness_check: None,
+        warmup: None,
+        // Services and files to wait for before launch
+        wait_for: args.wait_for.clone(),
+        wait_for_timeout_secs: args.wait_for_timeout,
+        // Memory limit, in MB
+        max_memory_mb: args.max_memory,
         // Startup delay
         startup_delay_ms: args.startup_delay,
         // Environment inheritance flag (for reference)
         env_inherit: args.env_inherit,
         // Event hooks
         hooks: oxidepm_core::Hooks {
-            on_start: args.on_start.clone(),
-            on_stop: args.on_stop.clone(),
-            on_restart: args.on_restart.clone(),
-            on_crash: args.on_crash.clone(),
+            on_start: args.on_start.clone().map(oxidepm_core::Hook::from),
+            on_stop: args.on_stop.clone().map(oxidepm_core::Hook::from),
+            on_restart: args.on_restart.clone().map(oxidepm_core::Hook::from),
+            on_crash: args.on_crash.clone().map(oxidepm_core::Hook::from),
             on_error: None,
+            on_pre_reload: args.on_pre_reload.clone().map(oxidepm_core::Hook::from),
+            on_post_reload: args.on_post_reload.clone().map(oxidepm_core::Hook::from),
         },
+        hook_user: args.hook_user.clone(),
         // Process tags for grouping
         tags: args.tag.clone(),
+        // Dependencies are only configurable in config files
+        depends_on: Vec::new(),
+        priority: args.priority,
         // Maximum uptime before auto-restart
         max_uptime_secs: args.max_uptime,
+        // Namespace isolation is only configurable in config files
+        sandbox: None,
+        limits: Default::default(),
+        // Custom log destinations, relative to where the command is run
+        logs: oxidepm_core::LogSettings {
+            out: args.out_file.as_ref().map(|p| invoked_from.join(p)),
+            err: args.err_file.as_ref().map(|p| invoked_from.join(p)),
+            merge: args.merge_logs,
+            ..Default::default()
+        },
+        // Log triggers are only configurable in config files
+        triggers: Vec::new(),
     })
 }
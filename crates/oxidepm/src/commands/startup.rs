@@ -1,25 +1,18 @@
 //! Startup command implementation
 
 use anyhow::Result;
+use oxidepm_core::constants;
 
 use crate::cli::StartupTarget;
 use crate::output::{print_info, print_success};
 
 pub fn execute(target: Option<StartupTarget>) -> Result<()> {
-    let target = target.unwrap_or_else(|| {
-        #[cfg(target_os = "macos")]
-        {
-            StartupTarget::Launchd
-        }
-        #[cfg(target_os = "linux")]
-        {
-            StartupTarget::Systemd
-        }
-        #[cfg(not(any(target_os = "macos", target_os = "linux")))]
-        {
-            StartupTarget::Systemd
-        }
-    });
+    #[cfg(target_os = "macos")]
+    let default_target = StartupTarget::Launchd;
+    #[cfg(not(target_os = "macos"))]
+    let default_target = StartupTarget::Systemd;
+
+    let target = target.unwrap_or(default_target);
 
     match target {
         StartupTarget::Systemd => print_systemd_instructions(),
@@ -45,7 +38,7 @@ User={user}
 ExecStart={binary} daemon
 Restart=on-failure
 RestartSec=10
-Environment=HOME={home}
+Environment=HOME={home}{oxidepm_home}
 
 [Install]
 WantedBy=multi-user.target
@@ -53,6 +46,7 @@ WantedBy=multi-user.target
         user = user,
         binary = binary.display(),
         home = home.display(),
+        oxidepm_home = home_environment(),
     );
 
     print_info("Systemd unit file:");
@@ -66,6 +60,18 @@ WantedBy=multi-user.target
     println!("  4. sudo systemctl start oxidepmd");
 }
 
+/// `Environment=OXIDEPM_HOME=...` when the CLI runs against a non-default home
+fn home_environment() -> String {
+    match std::env::var_os(constants::HOME_ENV) {
+        Some(_) => format!(
+            "\nEnvironment={}={}",
+            constants::HOME_ENV,
+            constants::oxidepm_home().display()
+        ),
+        None => String::new(),
+    }
+}
+
 fn print_launchd_instructions() {
     let home = dirs::home_dir().unwrap_or_default();
     let binary = std::env::current_exe().unwrap_or_default();
@@ -89,14 +95,20 @@ fn print_launchd_instructions() {
     <key>WorkingDirectory</key>
     <string>{home}</string>
     <key>StandardErrorPath</key>
-    <string>{home}/.oxidepm/daemon.err.log</string>
+    <string>{oxidepm_home}/daemon.err.log</string>
     <key>StandardOutPath</key>
-    <string>{home}/.oxidepm/daemon.out.log</string>
+    <string>{oxidepm_home}/daemon.out.log</string>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>OXIDEPM_HOME</key>
+        <string>{oxidepm_home}</string>
+    </dict>
 </dict>
 </plist>
 "#,
         binary = binary.display(),
         home = home.display(),
+        oxidepm_home = constants::oxidepm_home().display(),
     );
 
     print_info("Launchd plist file:");
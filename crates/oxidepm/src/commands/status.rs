@@ -1,27 +1,74 @@
 //! Status command implementation
 
 use anyhow::{bail, Result};
-use oxidepm_ipc::{Request, Response};
+use oxidepm_core::{constants, AppInfo, AppStatus, HostsFile};
+use oxidepm_ipc::{Endpoint, IpcClient, Request, Response};
+use std::time::Duration;
 
-use crate::output::{print_error, print_status_table, print_status_table_extended};
+use crate::cli::{StatusArgs, StatusFilter, StatusSort};
+use crate::commands::DaemonError;
+use crate::output::{
+    is_json_mode, print_error, print_host_status_table, print_status_table,
+    print_status_table_extended, print_warning,
+};
+
+/// How long to wait for each host in `--all-hosts`
+const HOST_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub async fn execute(args: StatusArgs) -> Result<()> {
+    let Some(secs) = args.watch else {
+        return print_once(&args).await;
+    };
+
+    let interval = Duration::from_secs(secs.max(1));
+    loop {
+        if !is_json_mode() {
+            // Clear screen and move cursor home
+            print!("\x1B[2J\x1B[H");
+            println!(
+                "Every {}s: oxidepm status (Ctrl-C to exit)\n",
+                interval.as_secs()
+            );
+        }
+        print_once(&args).await?;
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn print_once(args: &StatusArgs) -> Result<()> {
+    if args.all_hosts {
+        return print_all_hosts(args).await;
+    }
 
-pub async fn execute(show_more: bool) -> Result<()> {
     let client = super::get_client();
 
     let response = client.send(&Request::Status).await?;
 
     match response {
-        Response::Status { apps } => {
-            if show_more {
+        Response::Status {
+            apps,
+            supervision_paused,
+        } => {
+            if supervision_paused && !is_json_mode() {
+                print_warning(
+                    "Supervision is paused: auto-restarts, health actions and watch restarts are suspended (run `oxidepm resume-supervision`)",
+                );
+            }
+            let apps = filter_and_sort(apps, args);
+            if args.more {
                 print_status_table_extended(&apps);
             } else {
                 print_status_table(&apps);
             }
             Ok(())
         }
-        Response::Error { message } => {
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
             print_error(&message);
-            bail!(message)
+            Err(DaemonError::new(message, code, hint).into())
         }
         _ => {
             print_error("Unexpected response from daemon");
@@ -29,3 +76,187 @@ pub async fn execute(show_more: bool) -> Result<()> {
         }
     }
 }
+
+/// Query the local daemon and every configured host at once and merge the results
+async fn print_all_hosts(args: &StatusArgs) -> Result<()> {
+    let hosts = HostsFile::load()?;
+
+    let mut targets = Vec::new();
+    if constants::socket_path().exists() {
+        targets.push((
+            constants::LOCAL_HOST.to_string(),
+            Endpoint::Unix(constants::socket_path()),
+        ));
+    }
+    for (name, host) in &hosts.hosts {
+        match Endpoint::for_host(name, host) {
+            Ok(endpoint) => targets.push((name.clone(), endpoint)),
+            Err(e) => print_warning(&format!("{}: {}", name, e)),
+        }
+    }
+    if targets.is_empty() {
+        bail!(
+            "No daemons to query: the local daemon is not running and no hosts are configured in {}",
+            constants::hosts_path().display()
+        );
+    }
+
+    let queries: Vec<_> = targets
+        .into_iter()
+        .map(|(name, endpoint)| {
+            let query = tokio::spawn(async move {
+                let client = IpcClient::with_endpoint(endpoint);
+                match tokio::time::timeout(HOST_TIMEOUT, client.send(&Request::Status)).await {
+                    Ok(Ok(Response::Status { apps, .. })) => Ok(apps),
+                    Ok(Ok(Response::Error { message, .. })) => Err(message),
+                    Ok(Ok(_)) => Err("unexpected response".to_string()),
+                    Ok(Err(e)) => Err(e.to_string()),
+                    Err(_) => Err("no answer".to_string()),
+                }
+            });
+            (name, query)
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    let mut reached = 0;
+    for (name, query) in queries {
+        match query.await.unwrap_or_else(|e| Err(e.to_string())) {
+            Ok(apps) => {
+                reached += 1;
+                rows.extend(
+                    filter_and_sort(apps, args)
+                        .into_iter()
+                        .map(|app| (name.clone(), app)),
+                );
+            }
+            Err(e) => print_warning(&format!("{}: unreachable ({})", name, e)),
+        }
+    }
+    if reached == 0 {
+        bail!("None of the configured daemons could be reached");
+    }
+
+    print_host_status_table(&rows);
+    Ok(())
+}
+
+/// Apply --filter/--tag/--namespace/--sort to the app list
+fn filter_and_sort(apps: Vec<AppInfo>, args: &StatusArgs) -> Vec<AppInfo> {
+    let mut apps: Vec<AppInfo> = apps
+        .into_iter()
+        .filter(|app| args.filters.iter().all(|f| matches_filter(app, f)))
+        .filter(|app| {
+            args.tag
+                .as_ref()
+                .map_or(true, |tag| app.spec.tags.contains(tag))
+        })
+        .filter(|app| {
+            args.namespace
+                .as_ref()
+                .map_or(true, |ns| app.spec.namespace == *ns)
+        })
+        .collect();
+
+    match args.sort {
+        Some(StatusSort::Cpu) => apps.sort_by(|a, b| {
+            b.state
+                .cpu_percent
+                .partial_cmp(&a.state.cpu_percent)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        }),
+        Some(StatusSort::Mem) => apps.sort_by_key(|a| std::cmp::Reverse(a.state.memory_bytes)),
+        Some(StatusSort::Uptime) => apps.sort_by_key(|a| std::cmp::Reverse(a.state.uptime_secs)),
+        Some(StatusSort::Name) => apps.sort_by(|a, b| a.spec.name.cmp(&b.spec.name)),
+        None => {}
+    }
+
+    apps
+}
+
+fn matches_filter(app: &AppInfo, filter: &StatusFilter) -> bool {
+    match filter {
+        // "online" is what the table shows for running apps
+        StatusFilter::Status(status) => {
+            app.state.status.as_str() == status
+                || (status == "online" && app.state.status == AppStatus::Running)
+        }
+        StatusFilter::Name(name) => app.spec.name.to_lowercase().contains(name.as_str()),
+        StatusFilter::Mode(mode) => app.spec.mode.as_str() == mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::{AppMode, AppSpec, RunState};
+    use std::path::PathBuf;
+
+    fn app(id: u32, name: &str, status: AppStatus, memory_bytes: u64, tags: &[&str]) -> AppInfo {
+        let mut spec = AppSpec::new(
+            name.to_string(),
+            AppMode::Node,
+            "app.js".to_string(),
+            PathBuf::from("/"),
+        );
+        spec.id = id;
+        spec.tags = tags.iter().map(|t| t.to_string()).collect();
+        let mut state = RunState::new(id);
+        state.status = status;
+        state.memory_bytes = memory_bytes;
+        AppInfo::new(spec, state)
+    }
+
+    fn args() -> StatusArgs {
+        StatusArgs {
+            more: false,
+            filters: Vec::new(),
+            tag: None,
+            namespace: None,
+            sort: None,
+            watch: None,
+            all_hosts: false,
+        }
+    }
+
+    fn names(apps: &[AppInfo]) -> Vec<&str> {
+        apps.iter().map(|a| a.spec.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let apps = vec![
+            app(1, "api", AppStatus::Running, 100, &["web"]),
+            app(2, "worker", AppStatus::Stopped, 300, &[]),
+            app(3, "admin", AppStatus::Running, 200, &["web"]),
+        ];
+
+        let mut a = args();
+        a.filters = vec![StatusFilter::Status("online".to_string())];
+        assert_eq!(
+            names(&filter_and_sort(apps.clone(), &a)),
+            vec!["api", "admin"]
+        );
+
+        let mut a = args();
+        a.tag = Some("web".to_string());
+        a.sort = Some(StatusSort::Name);
+        assert_eq!(
+            names(&filter_and_sort(apps.clone(), &a)),
+            vec!["admin", "api"]
+        );
+
+        let mut a = args();
+        a.sort = Some(StatusSort::Mem);
+        assert_eq!(
+            names(&filter_and_sort(apps.clone(), &a)),
+            vec!["worker", "admin", "api"]
+        );
+
+        let mut apps = apps;
+        apps[1].spec.namespace = "jobs".to_string();
+        let mut a = args();
+        a.namespace = Some("jobs".to_string());
+        assert_eq!(names(&filter_and_sort(apps, &a)), vec!["worker"]);
+    }
+}
@@ -1,33 +1,62 @@
 //! Stop command implementation
 
 use anyhow::{bail, Result};
-use oxidepm_core::Selector;
 use oxidepm_ipc::{Request, Response};
 
-use crate::output::{print_error, print_success};
+use crate::cli::TargetArgs;
+use crate::commands::DaemonError;
+use crate::output::{
+    count_failed, nothing_done_message, print_error, print_op_results, print_success_json,
+};
 
-pub async fn execute(selector: &str) -> Result<()> {
+pub async fn execute(args: &TargetArgs, if_exists: bool) -> Result<()> {
     let client = super::get_client();
-    let selector = Selector::parse(selector);
+    let mut total = 0;
+    let mut results = Vec::new();
 
-    let response = client.send(&Request::Stop { selector }).await?;
-
-    match response {
-        Response::Stopped { count } => {
-            if count > 0 {
-                print_success(&format!("Stopped {} process(es)", count));
-            } else {
-                print_success("No running processes to stop");
+    for selector in super::resolve_selectors(args)? {
+        match client
+            .send(&Request::Stop {
+                selector,
+                if_exists,
+            })
+            .await?
+        {
+            Response::Stopped {
+                count,
+                results: stopped,
+            } => {
+                total += count;
+                results.extend(stopped);
+            }
+            Response::Error {
+                message,
+                code,
+                hint,
+            } => {
+                print_error(&message);
+                return Err(DaemonError::new(message, code, hint).into());
+            }
+            _ => {
+                print_error("Unexpected response from daemon");
+                bail!("Unexpected response")
             }
-            Ok(())
-        }
-        Response::Error { message } => {
-            print_error(&message);
-            bail!(message)
-        }
-        _ => {
-            print_error("Unexpected response from daemon");
-            bail!("Unexpected response")
         }
     }
+
+    print_op_results(&results);
+    if total > 0 {
+        print_success_json(&format!("Stopped {} process(es)", total), Some(&results));
+    } else {
+        print_success_json(
+            &nothing_done_message(&results, "No running processes to stop"),
+            Some(&results),
+        );
+    }
+
+    let failed = count_failed(&results);
+    if failed > 0 {
+        bail!("{} process(es) failed to stop", failed);
+    }
+    Ok(())
 }
@@ -0,0 +1,40 @@
+//! Pause/resume supervision command implementation
+
+use anyhow::{bail, Result};
+use oxidepm_ipc::{Request, Response};
+
+use crate::commands::DaemonError;
+use crate::output::{print_error, print_success};
+
+pub async fn pause() -> Result<()> {
+    send(Request::PauseSupervision).await
+}
+
+pub async fn resume() -> Result<()> {
+    send(Request::ResumeSupervision).await
+}
+
+async fn send(request: Request) -> Result<()> {
+    let client = super::get_client();
+
+    let response = client.send(&request).await?;
+
+    match response {
+        Response::Ok { message } => {
+            print_success(&message);
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
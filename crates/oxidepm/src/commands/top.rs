@@ -0,0 +1,134 @@
+//! Top command implementation - daemon and host overview
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+use colored::Colorize;
+use oxidepm_core::SystemInfo;
+use oxidepm_ipc::{Request, Response};
+
+use crate::commands::DaemonError;
+use crate::output::{format_bytes, format_duration, is_json_mode, print_error};
+
+pub async fn execute() -> Result<()> {
+    let client = super::get_client();
+
+    match client.send(&Request::SystemInfo).await? {
+        Response::SystemInfo { info } => {
+            if is_json_mode() {
+                println!("{}", serde_json::to_string_pretty(&info)?);
+            } else {
+                print_system_info(&info);
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
+
+fn print_system_info(info: &SystemInfo) {
+    let host = &info.host;
+    let statuses = if info.by_status.is_empty() {
+        "-".to_string()
+    } else {
+        info.by_status
+            .iter()
+            .map(|(status, count)| format!("{} {}", count, status))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    println!("{}", "─".repeat(50));
+    println!(
+        "  {} │ v{} (pid {})",
+        "Daemon".bold(),
+        info.version,
+        info.daemon_pid
+    );
+    println!(
+        "  {} │ {}",
+        "Uptime".bold(),
+        format_duration(info.daemon_uptime_secs)
+    );
+    println!(
+        "  {} │ {} ({})",
+        "Processes".bold(),
+        info.total_processes,
+        statuses
+    );
+    println!("  {} │ {:.1}%", "CPU".bold(), info.cpu_percent);
+    println!(
+        "  {} │ {}",
+        "Memory".bold(),
+        format_bytes(info.memory_bytes)
+    );
+    println!("{}", "─".repeat(50));
+    if let Some(hostname) = &host.hostname {
+        println!("  {} │ {}", "Host".bold(), hostname);
+    }
+    println!(
+        "  {} │ {:.2} {:.2} {:.2} ({} CPUs)",
+        "Load".bold(),
+        host.load_average[0],
+        host.load_average[1],
+        host.load_average[2],
+        host.cpu_count
+    );
+    println!(
+        "  {} │ {} / {}",
+        "Memory".bold(),
+        format_bytes(host.used_memory_bytes),
+        format_bytes(host.total_memory_bytes)
+    );
+    println!(
+        "  {} │ {}",
+        "Uptime".bold(),
+        format_duration(host.uptime_secs)
+    );
+    println!("{}", "─".repeat(50));
+    if info.db.as_ref().is_some_and(|db| db.backend == "memory") {
+        println!(
+            "  {} │ in memory (ephemeral, nothing is persisted)",
+            "Database".bold()
+        );
+        println!("{}", "─".repeat(50));
+    } else if let Some(db) = &info.db {
+        let vacuumed = match db.last_vacuum {
+            Some(at) => format!(
+                "{} ago",
+                format_duration((Utc::now() - at).num_seconds().max(0) as u64)
+            ),
+            None => "never".to_string(),
+        };
+        match &db.journal_mode {
+            Some(journal_mode) => println!(
+                "  {} │ {} {} ({} wal, {} free, {})",
+                "Database".bold(),
+                db.backend,
+                format_bytes(db.size_bytes),
+                format_bytes(db.wal_bytes),
+                format_bytes(db.free_bytes),
+                journal_mode
+            ),
+            None => println!(
+                "  {} │ {} {} ({})",
+                "Database".bold(),
+                db.backend,
+                format_bytes(db.size_bytes),
+                db.location
+            ),
+        }
+        println!("  {} │ {}", "Vacuumed".bold(), vacuumed);
+        println!("{}", "─".repeat(50));
+    }
+}
@@ -0,0 +1,92 @@
+//! Update command implementation - change fields of stored app specs
+
+use anyhow::{bail, Result};
+use oxidepm_core::{Selector, SpecUpdate};
+use oxidepm_ipc::{Request, Response};
+
+use crate::cli::UpdateArgs;
+use crate::commands::start::health_check;
+use crate::commands::DaemonError;
+use crate::output::{
+    count_failed, is_json_mode, print_error, print_info, print_op_results, print_success_json,
+};
+
+pub async fn execute(args: UpdateArgs) -> Result<()> {
+    let update = spec_update(&args);
+    if update.is_empty() {
+        bail!("Nothing to update (see oxidepm update --help)");
+    }
+    let request = Request::UpdateSpec {
+        selector: Selector::parse(&args.selector),
+        update,
+        restart: args.restart,
+        reload: args.reload,
+    };
+
+    let client = super::get_client();
+    match client.send(&request).await? {
+        Response::SpecUpdated {
+            count,
+            restarted,
+            results,
+        } => {
+            print_op_results(&results);
+            print_success_json(
+                &format!("Updated {} app(s)", count),
+                Some(serde_json::json!({
+                    "updated": count,
+                    "restarted": restarted,
+                    "results": results,
+                })),
+            );
+            if !is_json_mode() {
+                let verb = if args.reload { "Reloaded" } else { "Restarted" };
+                if restarted > 0 {
+                    print_info(&format!("{} {} app(s)", verb, restarted));
+                } else if !args.restart && !args.reload {
+                    print_info(
+                        "Running processes keep their settings until restarted (use --restart or --reload)",
+                    );
+                }
+            }
+            let failed = count_failed(&results);
+            if failed > 0 {
+                bail!("{} app(s) failed to update", failed);
+            }
+            Ok(())
+        }
+        Response::Error {
+            message,
+            code,
+            hint,
+        } => {
+            print_error(&message);
+            Err(DaemonError::new(message, code, hint).into())
+        }
+        _ => {
+            print_error("Unexpected response from daemon");
+            bail!("Unexpected response")
+        }
+    }
+}
+
+/// The fields the flags set
+fn spec_update(args: &UpdateArgs) -> SpecUpdate {
+    let flag = |on: bool, off: bool| match (on, off) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    };
+    SpecUpdate {
+        max_memory_mb: args.max_memory,
+        max_uptime_secs: args.max_uptime,
+        watch: flag(args.watch, args.no_watch),
+        auto_restart: flag(args.autorestart, args.no_autorestart),
+        max_restarts: args.max_restarts,
+        restart_delay_ms: args.restart_delay,
+        kill_timeout_ms: args.kill_timeout,
+        port: args.port,
+        health_check: args.health_check.as_deref().map(health_check),
+        tags: (!args.tag.is_empty()).then(|| args.tag.clone()),
+    }
+}
@@ -0,0 +1,430 @@
+//! Interactive `oxidepm start` wizard for when no target is given
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use dialoguer::{Confirm, Input, Select};
+use oxidepm_core::{validate_app_name, AppMode, ConfigFile, PortSetting};
+use oxidepm_ipc::IpcClient;
+use std::path::Path;
+
+use crate::cli::StartArgs;
+use crate::commands::check::detect_project_port;
+use crate::commands::start::{node_tool, start_specs};
+use crate::output::{print_info, print_success};
+
+/// File the wizard offers to write
+const WIZARD_CONFIG_FILE: &str = "oxidepm.toml";
+
+/// Choice offered when none of the detected entry points fit
+const OTHER_COMMAND: &str = "Other command...";
+
+/// Whether `start` should fall back to the wizard: no target and a terminal on both ends
+pub(crate) fn is_available(args: &StartArgs) -> bool {
+    args.target.is_none()
+        && args.git.is_none()
+        && atty::is(atty::Stream::Stdin)
+        && atty::is(atty::Stream::Stdout)
+}
+
+/// Answers collected by the wizard
+#[derive(Debug, Clone, PartialEq)]
+struct WizardConfig {
+    name: String,
+    mode: AppMode,
+    /// Script file, npm script, cargo binary or command, depending on mode
+    entry: String,
+    args: Vec<String>,
+    instances: u32,
+    port: Option<PortSetting>,
+    watch: bool,
+    env_file: Option<String>,
+}
+
+impl WizardConfig {
+    /// Render as an oxidepm.toml with a single app
+    fn to_toml(&self) -> String {
+        let quote = |value: &str| toml::Value::from(value).to_string();
+
+        let mut out = String::from("[[apps]]\n");
+        out.push_str(&format!("name = {}\n", quote(&self.name)));
+        out.push_str(&format!("mode = {}\n", quote(self.mode.as_str())));
+        match self.mode {
+            AppMode::Cargo => out.push_str(&format!("bin = {}\n", quote(&self.entry))),
+            _ => out.push_str(&format!("script = {}\n", quote(&self.entry))),
+        }
+        if !self.args.is_empty() {
+            let args: Vec<String> = self.args.iter().map(|arg| quote(arg)).collect();
+            out.push_str(&format!("args = [{}]\n", args.join(", ")));
+        }
+        if self.instances > 1 {
+            out.push_str(&format!("instances = {}\n", self.instances));
+        }
+        match self.port {
+            Some(PortSetting::Fixed(port)) => out.push_str(&format!("port = {}\n", port)),
+            Some(PortSetting::Auto) => out.push_str("port = \"auto\"\n"),
+            None => {}
+        }
+        if self.watch {
+            out.push_str("watch = true\n");
+        }
+        if let Some(env_file) = &self.env_file {
+            out.push_str(&format!("env_file = {}\n", quote(env_file)));
+        }
+        out
+    }
+}
+
+/// Walk the user through describing the app in the current directory, then start it
+pub async fn execute(client: &IpcClient, args: &StartArgs) -> Result<()> {
+    let dir = std::env::current_dir()?;
+    println!(
+        "{}",
+        "No target given - let's set up an app from this directory.".bold()
+    );
+    println!();
+
+    let config = prompt(&dir)?;
+    let content = config.to_toml();
+
+    println!();
+    println!("{}", content.trim_end().dimmed());
+    println!();
+
+    let config_path = dir.join(WIZARD_CONFIG_FILE);
+    let write = Confirm::new()
+        .with_prompt(format!("Write this to {}?", WIZARD_CONFIG_FILE))
+        .default(true)
+        .interact()?;
+    let write = write
+        && (!config_path.exists()
+            || Confirm::new()
+                .with_prompt(format!(
+                    "{} already exists. Overwrite it?",
+                    WIZARD_CONFIG_FILE
+                ))
+                .default(false)
+                .interact()?);
+    if write {
+        std::fs::write(&config_path, &content)?;
+        print_success(&format!("Wrote {}", config_path.display()));
+        print_info(&format!(
+            "Next time, run: oxidepm start {}",
+            WIZARD_CONFIG_FILE
+        ));
+    }
+
+    let mut specs = ConfigFile::from_toml(&content)?.into_specs(&dir)?;
+    if let Some(namespace) = &args.namespace {
+        for spec in &mut specs {
+            spec.namespace = namespace.clone();
+        }
+    }
+    start_specs(client, specs).await
+}
+
+fn prompt(dir: &Path) -> Result<WizardConfig> {
+    let (mode, entry, args) = prompt_entry(dir)?;
+
+    let default_name = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| validate_app_name(n))
+        .unwrap_or("app")
+        .to_string();
+    let name: String = Input::new()
+        .with_prompt("App name")
+        .default(default_name)
+        .validate_with(|name: &String| {
+            if validate_app_name(name) {
+                Ok(())
+            } else {
+                Err("only alphanumeric characters, underscores, and hyphens are allowed")
+            }
+        })
+        .interact_text()?;
+
+    let instances: u32 = Input::new()
+        .with_prompt("Instances")
+        .default(1)
+        .validate_with(|n: &u32| {
+            if *n >= 1 {
+                Ok(())
+            } else {
+                Err("at least one instance is required")
+            }
+        })
+        .interact_text()?;
+
+    let detected_port = detect_project_port(dir)
+        .map(|port| port.to_string())
+        .unwrap_or_default();
+    let port: String = Input::new()
+        .with_prompt("Port (number, \"auto\", or empty for none)")
+        .default(detected_port)
+        .allow_empty(true)
+        .validate_with(|input: &String| parse_port(input).map(|_| ()))
+        .interact_text()?;
+    let port = parse_port(&port).map_err(anyhow::Error::msg)?;
+
+    let watch = Confirm::new()
+        .with_prompt("Restart on file changes (watch)?")
+        .default(false)
+        .interact()?;
+
+    let env_file = if dir.join(".env").exists() {
+        Confirm::new()
+            .with_prompt("Load environment from .env?")
+            .default(true)
+            .interact()?
+            .then(|| ".env".to_string())
+    } else {
+        let path: String = Input::new()
+            .with_prompt("Env file (empty for none)")
+            .allow_empty(true)
+            .interact_text()?;
+        Some(path.trim().to_string()).filter(|path| !path.is_empty())
+    };
+
+    Ok(WizardConfig {
+        name,
+        mode,
+        entry,
+        args,
+        instances,
+        port,
+        watch,
+        env_file,
+    })
+}
+
+/// Detect the project type and let the user pick what to run
+fn prompt_entry(dir: &Path) -> Result<(AppMode, String, Vec<String>)> {
+    let (mode, mut choices) = detect_entries(dir);
+    match mode {
+        Some(AppMode::Cargo) => println!("Detected a Cargo project"),
+        Some(mode) => println!("Detected a Node.js project ({})", mode),
+        None if !choices.is_empty() => println!("Found script files in {}", dir.display()),
+        None => println!("No project detected in {}", dir.display()),
+    }
+
+    let prompt = match mode {
+        Some(AppMode::Cargo) => "Binary to run",
+        Some(_) => "Script to run",
+        None => "What should be started?",
+    };
+    choices.push(OTHER_COMMAND.to_string());
+    let picked = if choices.len() == 1 {
+        0
+    } else {
+        Select::new()
+            .with_prompt(prompt)
+            .items(&choices)
+            .default(0)
+            .interact()?
+    };
+
+    if picked + 1 < choices.len() {
+        let entry = choices.swap_remove(picked);
+        let mode = mode
+            .or_else(|| AppMode::detect(&dir.join(&entry)))
+            .unwrap_or(AppMode::Cmd);
+        return Ok((mode, entry, Vec::new()));
+    }
+
+    let command: String = Input::new().with_prompt("Command to run").interact_text()?;
+    let mut words = command.split_whitespace().map(String::from);
+    let Some(program) = words.next() else {
+        bail!("No command given");
+    };
+    Ok((AppMode::Cmd, program, words.collect()))
+}
+
+/// Project mode (if any) and the entry points it offers, most likely first
+fn detect_entries(dir: &Path) -> (Option<AppMode>, Vec<String>) {
+    match AppMode::detect(dir) {
+        Some(AppMode::Cargo) => (Some(AppMode::Cargo), cargo_bins(dir)),
+        Some(_) => (Some(node_tool(dir)), npm_scripts(dir)),
+        None => (None, script_files(dir)),
+    }
+}
+
+/// Binary targets declared by a Cargo.toml
+fn cargo_bins(dir: &Path) -> Vec<String> {
+    let Ok(content) = std::fs::read_to_string(dir.join("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let Ok(manifest) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+
+    let mut bins = Vec::new();
+    let package = manifest
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str());
+    if let Some(package) = package {
+        if dir.join("src/main.rs").exists() {
+            bins.push(package.to_string());
+        }
+    }
+    let declared = manifest.get("bin").and_then(|b| b.as_array());
+    for bin in declared.into_iter().flatten() {
+        if let Some(name) = bin.get("name").and_then(|n| n.as_str()) {
+            if !bins.iter().any(|b| b == name) {
+                bins.push(name.to_string());
+            }
+        }
+    }
+    bins
+}
+
+/// Scripts from package.json, with "start" first
+fn npm_scripts(dir: &Path) -> Vec<String> {
+    let scripts = std::fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|json| json.get("scripts")?.as_object().cloned())
+        .unwrap_or_default();
+
+    let mut names: Vec<String> = scripts.keys().cloned().collect();
+    names.sort_by_key(|name| name != "start");
+    names
+}
+
+/// Node and Rust source files at the top of the directory
+fn script_files(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter_map(|e| e.file_name().to_str().map(String::from))
+        .filter(|name| AppMode::detect(Path::new(name)).is_some())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Parse the port answer: a number, "auto", or empty for none
+fn parse_port(input: &str) -> std::result::Result<Option<PortSetting>, String> {
+    match input.trim() {
+        "" => Ok(None),
+        "auto" => Ok(Some(PortSetting::Auto)),
+        port => port
+            .parse::<u16>()
+            .ok()
+            .filter(|port| *port > 0)
+            .map(|port| Some(PortSetting::Fixed(port)))
+            .ok_or_else(|| format!("\"{}\" is not a port number or \"auto\"", port)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_cargo_bins() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"api\"\n\n[[bin]]\nname = \"worker\"\npath = \"src/worker.rs\"\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let (mode, entries) = detect_entries(dir.path());
+        assert_eq!(mode, Some(AppMode::Cargo));
+        assert_eq!(entries, vec!["api", "worker"]);
+    }
+
+    #[test]
+    fn test_detect_npm_scripts() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"build": "tsc", "dev": "vite", "start": "node server.js"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("yarn.lock"), "").unwrap();
+
+        let (mode, entries) = detect_entries(dir.path());
+        assert_eq!(mode, Some(AppMode::Yarn));
+        assert_eq!(entries, vec!["start", "build", "dev"]);
+    }
+
+    #[test]
+    fn test_detect_script_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("server.js"), "").unwrap();
+        fs::write(dir.path().join("main.rs"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+
+        let (mode, entries) = detect_entries(dir.path());
+        assert_eq!(mode, None);
+        assert_eq!(entries, vec!["main.rs", "server.js"]);
+    }
+
+    #[test]
+    fn test_parse_port() {
+        assert_eq!(parse_port(""), Ok(None));
+        assert_eq!(parse_port("auto"), Ok(Some(PortSetting::Auto)));
+        assert_eq!(parse_port(" 3000 "), Ok(Some(PortSetting::Fixed(3000))));
+        assert!(parse_port("0").is_err());
+        assert!(parse_port("http").is_err());
+    }
+
+    #[test]
+    fn test_to_toml_round_trips() {
+        let dir = TempDir::new().unwrap();
+        let config = WizardConfig {
+            name: "web".to_string(),
+            mode: AppMode::Npm,
+            entry: "start".to_string(),
+            args: vec!["--verbose".to_string()],
+            instances: 2,
+            port: Some(PortSetting::Fixed(3000)),
+            watch: true,
+            env_file: Some(".env".to_string()),
+        };
+
+        let content = config.to_toml();
+        assert!(
+            content.starts_with("[[apps]]\nname = \"web\"\nmode = \"npm\"\nscript = \"start\"\n")
+        );
+
+        let specs = ConfigFile::from_toml(&content)
+            .unwrap()
+            .into_specs(dir.path())
+            .unwrap();
+        assert_eq!(specs.len(), 1);
+        assert_eq!(specs[0].name, "web");
+        assert_eq!(specs[0].mode, AppMode::Npm);
+        assert_eq!(specs[0].command, "start");
+        assert_eq!(specs[0].args, vec!["--verbose"]);
+        assert_eq!(specs[0].instances, 2);
+        assert!(specs[0].watch);
+    }
+
+    #[test]
+    fn test_to_toml_cargo_uses_bin() {
+        let config = WizardConfig {
+            name: "api".to_string(),
+            mode: AppMode::Cargo,
+            entry: "api".to_string(),
+            args: Vec::new(),
+            instances: 1,
+            port: Some(PortSetting::Auto),
+            watch: false,
+            env_file: None,
+        };
+        assert_eq!(
+            config.to_toml(),
+            "[[apps]]\nname = \"api\"\nmode = \"cargo\"\nbin = \"api\"\nport = \"auto\"\n"
+        );
+    }
+}
@@ -1,7 +1,7 @@
 //! OxidePM CLI - PM2-like process manager for Rust and Node.js
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use oxidepm_core::constants::socket_path;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -15,7 +15,8 @@ use commands::*;
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging based on verbosity
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     // Set JSON output mode if requested
     output::set_json_mode(cli.json);
@@ -32,43 +33,138 @@ async fn main() -> Result<()> {
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| format!("oxidepm={}", log_level).into()),
         )
-        .with(tracing_subscriber::fmt::layer().without_time())
+        // Keep stdout for command output (and the MCP stdio transport)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .without_time()
+                .with_writer(std::io::stderr),
+        )
         .init();
 
+    if let Err(e) = commands::select_home(cli.home.as_deref(), cli.profile.as_deref()) {
+        exit_with_error(e);
+    }
+    if let Err(e) = commands::select_host(cli.host.as_deref()) {
+        exit_with_error(e);
+    }
+
     // Handle commands
     let result = match cli.command {
         Commands::Start(args) => start::execute(args).await,
-        Commands::Stop { selector } => stop::execute(&selector).await,
-        Commands::Restart { selector } => restart::execute(&selector).await,
-        Commands::Delete { selector } => delete::execute(&selector).await,
-        Commands::Status { more } => status::execute(more).await,
+        Commands::Run(args) => {
+            let run_matches = matches.subcommand_matches("run").expect("run was parsed");
+            run::execute(args, run_matches).await
+        }
+        Commands::Stop { target, if_exists } => stop::execute(&target, if_exists).await,
+        Commands::Restart {
+            target,
+            reset_counter,
+            parallel,
+            continue_on_error,
+            if_exists,
+        } => {
+            restart::execute(&target, reset_counter, parallel, continue_on_error, if_exists).await
+        }
+        Commands::Delete { selector, purge } => delete::execute(&selector, purge).await,
+        Commands::Status(args) => status::execute(args).await,
         Commands::Show { selector } => show::execute(&selector).await,
         Commands::Logs(args) => logs::execute(args).await,
         Commands::Ping => ping::execute().await,
-        Commands::Save => save::execute().await,
-        Commands::Resurrect => resurrect::execute().await,
+        Commands::Save { output } => save::execute(output).await,
+        Commands::Resurrect { file, all } => resurrect::execute(file, all).await,
         Commands::Kill => kill::execute().await,
         Commands::Startup { target } => startup::execute(target),
-        Commands::Monit => {
-            oxidepm_tui::run(socket_path()).await.map_err(|e| anyhow::anyhow!(e))
-        }
+        Commands::Monit => oxidepm_tui::run(socket_path())
+            .await
+            .map_err(|e| anyhow::anyhow!(e)),
         Commands::Web(args) => {
+            async {
+                let bind_addr = format!("0.0.0.0:{}", args.port);
+                let hosts = commands::host_endpoints(args.hosts.as_deref().unwrap_or_default())?;
+                let state =
+                    oxidepm_web::AppState::new(socket_path(), args.api_key).with_hosts(hosts);
+                oxidepm_web::serve(&bind_addr, state, None)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))
+            }
+            .await
+        }
+        Commands::Grpc(args) => {
             let bind_addr = format!("0.0.0.0:{}", args.port);
-            oxidepm_web::start_server(&bind_addr, socket_path(), args.api_key)
+            oxidepm_grpc::start_server(&bind_addr, socket_path())
                 .await
                 .map_err(|e| anyhow::anyhow!(e))
         }
-        Commands::Reload { selector } => restart::execute(&selector).await, // Graceful restart uses same logic
+        Commands::Mcp(args) => {
+            let server = oxidepm_mcp::McpServer::new(socket_path(), args.allow);
+            let result = match args.transport {
+                cli::McpTransport::Stdio => server.run_stdio().await,
+                cli::McpTransport::Sse => {
+                    oxidepm_mcp::serve_sse(server, &format!("127.0.0.1:{}", args.port)).await
+                }
+            };
+            result.map_err(|e| anyhow::anyhow!(e))
+        }
+        Commands::Reload(args) => restart::execute(&args, false, None, true, false).await, // Graceful restart uses same logic
         Commands::Notify(args) => notify::execute(args).await,
-        Commands::Flush { selector } => flush::execute(&selector).await,
-        Commands::Describe { target } => describe::execute(&target).await,
+        Commands::Flush {
+            selector,
+            older_than,
+            keep,
+        } => flush::execute(&selector, older_than, keep).await,
+        Commands::Describe {
+            start,
+            show_secrets,
+        } => describe::execute(start, show_secrets).await,
         Commands::Check(args) => check::execute(args).await,
+        Commands::PauseSupervision => supervision::pause().await,
+        Commands::ResumeSupervision => supervision::resume().await,
+        Commands::DaemonReload => daemon_reload::execute().await,
+        Commands::Drain { selector, timeout } => drain::drain(&selector, timeout).await,
+        Commands::Undrain { selector } => drain::undrain(&selector).await,
+        Commands::Pause(args) => pause::pause(&args).await,
+        Commands::Resume(args) => pause::resume(&args).await,
+        Commands::Signal {
+            selector,
+            signal,
+            tree,
+        } => signal::execute(&selector, signal, tree).await,
+        Commands::Import(args) => import::execute(args).await,
+        Commands::Apply(args) => apply::execute(args).await,
+        Commands::Config(args) => config::execute(args),
+        Commands::Env(args) => env::execute(args).await,
+        Commands::Exec(args) => exec::execute(args).await,
+        Commands::Rename { selector, name } => rename::execute(&selector, &name).await,
+        Commands::Update(args) => update::execute(args).await,
+        Commands::Secret(args) => secret::execute(args).await,
+        Commands::Health(args) => health::execute(args).await,
+        Commands::Events(args) => events::execute(args).await,
+        Commands::Audit(args) => audit::execute(args).await,
+        Commands::Group(args) => group::execute(args).await,
+        Commands::Operations(args) => operations::execute(args).await,
+        Commands::Top => top::execute().await,
+        Commands::Ports => ports::execute().await,
+        Commands::Hosts => hosts::execute().await,
+        Commands::Doctor { fix } => doctor::execute(fix).await,
     };
 
     if let Err(e) = result {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+        exit_with_error(e);
     }
 
     Ok(())
 }
+
+/// Report a failed command (as JSON with --json) and exit with its class's code
+fn exit_with_error(e: anyhow::Error) -> ! {
+    let (code, kind) = commands::classify_error(&e);
+    if output::is_json_mode() {
+        let hint = e
+            .downcast_ref::<commands::DaemonError>()
+            .and_then(|d| d.hint.clone());
+        output::print_error_payload(&e.to_string(), &code, kind.exit_code(), hint);
+    } else {
+        eprintln!("Error: {}", e);
+    }
+    std::process::exit(kind.exit_code());
+}
@@ -1,9 +1,13 @@
 //! PM2-style output formatting
 
 use colored::Colorize;
-use oxidepm_core::{AppInfo, AppStatus};
+use oxidepm_core::{AppInfo, AppOpOutcome, AppOpResult, AppStatus, BuildInfo, Limits};
 use serde::Serialize;
+use std::io::Write;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
 use tabled::{
     settings::{object::Columns, Alignment, Modify, Style},
     Table, Tabled,
@@ -22,6 +26,104 @@ pub fn is_json_mode() -> bool {
     JSON_MODE.load(Ordering::SeqCst)
 }
 
+/// One-line progress display on stderr: a spinner followed by a progress bar
+/// once the percentage is known and the latest message, cleared when finished
+pub struct Spinner {
+    message: Arc<Mutex<String>>,
+    percent: Arc<Mutex<Option<u8>>>,
+    done: Arc<AtomicBool>,
+    ticker: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+    /// Width of the progress bar, in characters
+    const BAR_WIDTH: usize = 20;
+
+    pub fn start(message: &str) -> Self {
+        let message = Arc::new(Mutex::new(message.to_string()));
+        let percent = Arc::new(Mutex::new(None));
+        let done = Arc::new(AtomicBool::new(false));
+        let ticker = {
+            let message = Arc::clone(&message);
+            let percent = Arc::clone(&percent);
+            let done = Arc::clone(&done);
+            std::thread::spawn(move || {
+                let term = console::Term::stderr();
+                let mut frame = 0;
+                while !done.load(Ordering::SeqCst) {
+                    let width = term.size().1 as usize;
+                    let bar = match *percent.lock().unwrap() {
+                        Some(percent) => format!("{} ", progress_bar(percent, Self::BAR_WIDTH)),
+                        None => String::new(),
+                    };
+                    let line = format!(
+                        "{} {}{}",
+                        Self::FRAMES[frame % Self::FRAMES.len()],
+                        bar,
+                        message.lock().unwrap()
+                    );
+                    let _ = term.clear_line();
+                    let _ = write!(
+                        &term,
+                        "{}",
+                        console::truncate_str(&line, width.saturating_sub(1), "…")
+                    );
+                    frame += 1;
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                let _ = term.clear_line();
+            })
+        };
+        Self {
+            message,
+            percent,
+            done,
+            ticker: Some(ticker),
+        }
+    }
+
+    pub fn set_message(&self, message: &str) {
+        *self.message.lock().unwrap() = message.to_string();
+    }
+
+    /// Show a progress bar at `percent`, or none when unknown
+    pub fn set_percent(&self, percent: Option<u8>) {
+        *self.percent.lock().unwrap() = percent;
+    }
+
+    /// Stop the spinner and clear its line
+    pub fn finish(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        if let Some(ticker) = self.ticker.take() {
+            let _ = ticker.join();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A bar like `[#####.....]  50%`, `width` characters between the brackets
+pub fn progress_bar(percent: u8, width: usize) -> String {
+    let percent = percent.min(100);
+    let filled = width * percent as usize / 100;
+    format!(
+        "[{}{}] {:>3}%",
+        "#".repeat(filled),
+        ".".repeat(width - filled),
+        percent
+    )
+}
+
 #[derive(Tabled, Serialize)]
 pub struct StatusRow {
     #[tabled(rename = "id")]
@@ -50,6 +152,7 @@ pub struct StatusRow {
 pub struct StatusJson {
     pub id: u32,
     pub name: String,
+    pub namespace: String,
     pub mode: String,
     pub pid: Option<u32>,
     pub restarts: u32,
@@ -66,6 +169,7 @@ impl From<&AppInfo> for StatusJson {
         StatusJson {
             id: info.spec.id,
             name: info.spec.name.clone(),
+            namespace: info.spec.namespace.clone(),
             mode: info.spec.mode.to_string(),
             pid: info.state.pid,
             restarts: info.state.restarts,
@@ -87,11 +191,12 @@ impl From<&AppInfo> for StatusRow {
             AppStatus::Starting => "starting".yellow().to_string(),
             AppStatus::Stopping => "stopping".yellow().to_string(),
             AppStatus::Building => "building".cyan().to_string(),
+            AppStatus::Paused => "paused".blue().to_string(),
         };
 
         StatusRow {
             id: info.spec.id,
-            name: info.spec.name.clone(),
+            name: info.spec.qualified_name(),
             mode: info.spec.mode.to_string(),
             pid: info
                 .state
@@ -132,6 +237,60 @@ pub fn print_status_table(apps: &[AppInfo]) {
     println!("{}", table);
 }
 
+/// Status row tagged with the daemon it came from (`status --all-hosts`)
+#[derive(Tabled)]
+pub struct HostStatusRow {
+    #[tabled(rename = "host")]
+    pub host: String,
+    #[tabled(inline)]
+    pub row: StatusRow,
+}
+
+#[derive(Serialize)]
+pub struct HostStatusJson {
+    pub host: String,
+    #[serde(flatten)]
+    pub app: StatusJson,
+}
+
+/// Print apps from several daemons as `(host, app)` pairs
+pub fn print_host_status_table(apps: &[(String, AppInfo)]) {
+    if is_json_mode() {
+        let json_apps: Vec<HostStatusJson> = apps
+            .iter()
+            .map(|(host, info)| HostStatusJson {
+                host: host.clone(),
+                app: StatusJson::from(info),
+            })
+            .collect();
+        match serde_json::to_string_pretty(&json_apps) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Error serializing to JSON: {}", e),
+        }
+        return;
+    }
+
+    if apps.is_empty() {
+        println!("No processes running");
+        return;
+    }
+
+    let rows: Vec<HostStatusRow> = apps
+        .iter()
+        .map(|(host, info)| HostStatusRow {
+            host: host.clone(),
+            row: StatusRow::from(info),
+        })
+        .collect();
+
+    let table = Table::new(rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::single(1)).with(Alignment::right()))
+        .to_string();
+
+    println!("{}", table);
+}
+
 /// Extended status row with cwd and port
 #[derive(Tabled, Serialize)]
 pub struct StatusRowExtended {
@@ -165,6 +324,7 @@ impl From<&AppInfo> for StatusRowExtended {
             AppStatus::Starting => "starting".yellow().to_string(),
             AppStatus::Stopping => "stopping".yellow().to_string(),
             AppStatus::Building => "building".cyan().to_string(),
+            AppStatus::Paused => "paused".blue().to_string(),
         };
 
         // Get port from state first (actual running port), fall back to spec
@@ -180,7 +340,7 @@ impl From<&AppInfo> for StatusRowExtended {
 
         StatusRowExtended {
             id: info.spec.id,
-            name: info.spec.name.clone(),
+            name: info.spec.qualified_name(),
             mode: info.spec.mode.to_string(),
             pid: info
                 .state
@@ -246,13 +406,22 @@ pub fn print_status_table_extended(apps: &[AppInfo]) {
 pub struct AppDetailJson {
     pub id: u32,
     pub name: String,
+    pub namespace: String,
     pub mode: String,
     pub status: String,
     pub pid: Option<u32>,
     pub restarts: u32,
+    pub restarts_in_window: u32,
+    pub next_restart_at: Option<String>,
     pub uptime_secs: u64,
     pub cpu_percent: f32,
     pub memory_bytes: u64,
+    pub processes: Option<u32>,
+    pub open_fds: Option<u32>,
+    pub threads: Option<u32>,
+    pub disk_read_bytes_per_sec: u64,
+    pub disk_write_bytes_per_sec: u64,
+    pub log_lines_dropped: u64,
     pub command: String,
     pub cwd: String,
     pub args: Vec<String>,
@@ -262,7 +431,17 @@ pub struct AppDetailJson {
     pub tags: Vec<String>,
     pub max_memory_mb: Option<u64>,
     pub max_uptime_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Limits::is_empty")]
+    pub limits: Limits,
     pub healthy: bool,
+    pub ready: bool,
+    pub total_restarts: u32,
+    pub crashes: u32,
+    pub last_crash_at: Option<String>,
+    pub downtime_secs: u64,
+    pub mtbf_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildInfo>,
 }
 
 impl From<&AppInfo> for AppDetailJson {
@@ -270,13 +449,22 @@ impl From<&AppInfo> for AppDetailJson {
         AppDetailJson {
             id: info.spec.id,
             name: info.spec.name.clone(),
+            namespace: info.spec.namespace.clone(),
             mode: info.spec.mode.to_string(),
             status: info.state.status.as_str().to_string(),
             pid: info.state.pid,
             restarts: info.state.restarts,
+            restarts_in_window: info.state.restarts_in_window,
+            next_restart_at: info.state.next_restart_at.map(|t| t.to_rfc3339()),
             uptime_secs: info.state.uptime_secs,
             cpu_percent: info.state.cpu_percent,
             memory_bytes: info.state.memory_bytes,
+            processes: info.state.processes,
+            open_fds: info.state.open_fds,
+            threads: info.state.threads,
+            disk_read_bytes_per_sec: info.state.disk_read_bytes_per_sec,
+            disk_write_bytes_per_sec: info.state.disk_write_bytes_per_sec,
+            log_lines_dropped: info.state.log_lines_dropped,
             command: info.spec.command.clone(),
             cwd: info.spec.cwd.display().to_string(),
             args: info.spec.args.clone(),
@@ -285,11 +473,72 @@ impl From<&AppInfo> for AppDetailJson {
             tags: info.spec.tags.clone(),
             max_memory_mb: info.spec.max_memory_mb,
             max_uptime_secs: info.spec.max_uptime_secs,
+            limits: info.spec.limits.clone(),
             healthy: info.state.healthy,
+            ready: info.state.ready,
+            total_restarts: info.state.stats.total_restarts,
+            crashes: info.state.stats.crashes,
+            last_crash_at: info.state.stats.last_crash_at.map(|t| t.to_rfc3339()),
+            downtime_secs: info.state.stats.downtime_secs,
+            mtbf_secs: info.state.stats.mtbf_secs,
+            build: info.state.build.clone(),
         }
     }
 }
 
+/// Crash-loop streak and the next scheduled restart, if there is a streak
+fn format_backoff(info: &AppInfo) -> Option<String> {
+    let policy = &info.spec.restart_policy;
+    let state = &info.state;
+    if state.restarts_in_window == 0 && state.next_restart_at.is_none() {
+        return None;
+    }
+
+    let streak = format!(
+        "{}/{} restarts within {}",
+        state.restarts_in_window,
+        policy.max_restarts,
+        format_duration(policy.crash_window_secs)
+    );
+    if let Some(at) = state.next_restart_at {
+        let wait = (at - chrono::Utc::now()).num_seconds().max(0) as u64;
+        return Some(format!(
+            "{}, next attempt at {} (in {})",
+            streak,
+            at.with_timezone(&chrono::Local).format("%H:%M:%S"),
+            format_duration(wait)
+        ));
+    }
+    if state.status == AppStatus::Errored && state.restarts_exhausted(policy) {
+        return Some(format!(
+            "{}, gave up (clear with `oxidepm restart {} --reset-counter`)",
+            streak, info.spec.name
+        ));
+    }
+    Some(streak)
+}
+
+/// Profile, build time and commit of a build, e.g. "release, built in 4s, commit 0123456789ab"
+pub fn format_build(build: &BuildInfo) -> String {
+    let took = if build.duration_ms < 1000 {
+        format!("{}ms", build.duration_ms)
+    } else {
+        format_duration(build.duration_ms / 1000)
+    };
+    let mut text = if build.profile == BuildInfo::PREBUILT {
+        build.profile.clone()
+    } else {
+        format!("{}, built in {}", build.profile, took)
+    };
+    if let Some(sha256) = build.short_sha256() {
+        text.push_str(&format!(", sha256 {}", sha256));
+    }
+    if let Some(commit) = build.short_commit() {
+        text.push_str(&format!(", commit {}", commit));
+    }
+    text
+}
+
 pub fn print_app_detail(info: &AppInfo) {
     if is_json_mode() {
         let json_detail = AppDetailJson::from(info);
@@ -302,9 +551,34 @@ pub fn print_app_detail(info: &AppInfo) {
 
     println!("{}", "─".repeat(50));
     println!("  {} │ {}", "Name".bold(), info.spec.name);
+    println!("  {} │ {}", "Namespace".bold(), info.spec.namespace);
     println!("  {} │ {}", "ID".bold(), info.spec.id);
     println!("  {} │ {}", "Mode".bold(), info.spec.mode);
-    println!("  {} │ {}", "Status".bold(), format_status(info.state.status));
+    println!(
+        "  {} │ {}",
+        "Status".bold(),
+        format_status(info.state.status)
+    );
+    let gate = match (&info.spec.readiness_check, &info.spec.warmup) {
+        (Some(readiness), _) => Some(readiness.endpoint()),
+        (None, Some(_)) => Some("after warm-up".to_string()),
+        (None, None) => None,
+    };
+    if let Some(gate) = gate {
+        let ready = if info.state.ready {
+            "yes".green()
+        } else {
+            "no".yellow()
+        };
+        println!("  {} │ {} ({})", "Ready".bold(), ready, gate);
+    }
+    if info.state.draining {
+        println!(
+            "  {} │ {}",
+            "Draining".bold(),
+            "yes (undrain to put it back)".yellow()
+        );
+    }
     println!(
         "  {} │ {}",
         "PID".bold(),
@@ -314,6 +588,9 @@ pub fn print_app_detail(info: &AppInfo) {
             .unwrap_or_else(|| "-".to_string())
     );
     println!("  {} │ {}", "Restarts".bold(), info.state.restarts);
+    if let Some(backoff) = format_backoff(info) {
+        println!("  {} │ {}", "Backoff".bold(), backoff);
+    }
     println!("  {} │ {}", "Uptime".bold(), format_duration(info.state.uptime_secs));
     println!(
         "  {} │ {:.1}%",
@@ -325,9 +602,47 @@ pub fn print_app_detail(info: &AppInfo) {
         "Memory".bold(),
         format_bytes(info.state.memory_bytes)
     );
+    if let Some(processes) = info.state.processes {
+        println!("  {} │ {}", "Processes".bold(), processes);
+    }
+    if let Some(threads) = info.state.threads {
+        println!("  {} │ {}", "Threads".bold(), threads);
+    }
+    if let Some(fds) = info.state.open_fds {
+        println!("  {} │ {}", "Open FDs".bold(), fds);
+    }
+    println!(
+        "  {} │ {}/s read, {}/s write",
+        "Disk I/O".bold(),
+        format_bytes(info.state.disk_read_bytes_per_sec),
+        format_bytes(info.state.disk_write_bytes_per_sec)
+    );
     println!("{}", "─".repeat(50));
     println!("  {} │ {}", "Command".bold(), info.spec.command);
     println!("  {} │ {}", "CWD".bold(), info.spec.cwd.display());
+    println!(
+        "  {} │ {}",
+        "Out Log".bold(),
+        info.spec.stdout_log_path().display()
+    );
+    if !info.spec.logs.merge {
+        println!(
+            "  {} │ {}",
+            "Err Log".bold(),
+            info.spec.stderr_log_path().display()
+        );
+    }
+    if info.state.log_lines_dropped > 0 {
+        println!(
+            "  {} │ {}",
+            "Log Drops".bold(),
+            format!(
+                "{} lines (writer fell behind)",
+                info.state.log_lines_dropped
+            )
+            .yellow()
+        );
+    }
     if !info.spec.args.is_empty() {
         println!("  {} │ {:?}", "Args".bold(), info.spec.args);
     }
@@ -343,9 +658,41 @@ pub fn print_app_detail(info: &AppInfo) {
     if let Some(max_uptime) = info.spec.max_uptime_secs {
         println!("  {} │ {}", "Max Uptime".bold(), format_duration(max_uptime));
     }
+    if !info.spec.limits.is_empty() {
+        let limits: Vec<String> = info
+            .spec
+            .limits
+            .entries()
+            .map(|(name, value)| format!("{}={}", name, Limits::format_value(value)))
+            .collect();
+        println!("  {} │ {}", "Limits".bold(), limits.join(" "));
+    }
     if let Some(code) = info.state.last_exit_code {
         println!("  {} │ {}", "Last Exit".bold(), code);
     }
+    if let Some(build) = &info.state.build {
+        println!("  {} │ {}", "Build".bold(), format_build(build));
+        println!("  {} │ {}", "Binary".bold(), build.binary_path.display());
+    }
+    println!("{}", "─".repeat(50));
+    let stats = &info.state.stats;
+    println!("  {} │ {}", "Total Restarts".bold(), stats.total_restarts);
+    println!("  {} │ {}", "Crashes".bold(), stats.crashes);
+    if let Some(at) = stats.last_crash_at {
+        println!(
+            "  {} │ {}",
+            "Last Crash".bold(),
+            at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    println!(
+        "  {} │ {}",
+        "Downtime".bold(),
+        format_duration(stats.downtime_secs)
+    );
+    if let Some(mtbf) = stats.mtbf_secs {
+        println!("  {} │ {}", "MTBF".bold(), format_duration(mtbf));
+    }
     println!("{}", "─".repeat(50));
 }
 
@@ -357,6 +704,7 @@ fn format_status(status: AppStatus) -> String {
         AppStatus::Starting => "starting".yellow().to_string(),
         AppStatus::Stopping => "stopping".yellow().to_string(),
         AppStatus::Building => "building".cyan().to_string(),
+        AppStatus::Paused => "paused".blue().to_string(),
     }
 }
 
@@ -400,10 +748,68 @@ pub fn print_error(message: &str) {
     eprintln!("{} {}", "✗".red(), message);
 }
 
+pub fn print_warning(message: &str) {
+    eprintln!("{} {}", "⚠".yellow().bold(), message);
+}
+
+/// Print a remediation hint below an error
+pub fn print_hint(hint: &str) {
+    eprintln!("  {} {}", "hint:".yellow(), hint);
+}
+
 pub fn print_info(message: &str) {
     println!("{} {}", "ℹ".blue(), message);
 }
 
+/// Print what a stop/restart/delete did to each app, when it touched more
+/// than one or something failed
+pub fn print_op_results(results: &[AppOpResult]) {
+    let any_failed = results
+        .iter()
+        .any(|r| matches!(r.outcome, AppOpOutcome::Failed { .. }));
+    if is_json_mode() || (results.len() < 2 && !any_failed) {
+        return;
+    }
+
+    println!(
+        "{:<5} {:<24} {}",
+        "ID".bold(),
+        "APP".bold(),
+        "RESULT".bold()
+    );
+    for result in results {
+        let outcome = match &result.outcome {
+            AppOpOutcome::Done => "ok".green().to_string(),
+            AppOpOutcome::Skipped => "skipped".dimmed().to_string(),
+            AppOpOutcome::AlreadyStopped => "already stopped".dimmed().to_string(),
+            AppOpOutcome::NotFound => "not found".yellow().to_string(),
+            AppOpOutcome::Failed { error } => format!("{} {}", "failed:".red(), error),
+        };
+        println!("{:<5} {:<24} {}", result.id, result.name, outcome);
+    }
+}
+
+/// Summary of a stop/restart that did nothing: why, when a single app or
+/// selector was involved
+pub fn nothing_done_message(results: &[AppOpResult], fallback: &str) -> String {
+    match results {
+        [result] => match result.outcome {
+            AppOpOutcome::AlreadyStopped => format!("{} is already stopped", result.name),
+            AppOpOutcome::NotFound => format!("{} not found, nothing to do", result.name),
+            _ => fallback.to_string(),
+        },
+        _ => fallback.to_string(),
+    }
+}
+
+/// Number of failed results in a stop/restart/delete
+pub fn count_failed(results: &[AppOpResult]) -> usize {
+    results
+        .iter()
+        .filter(|r| matches!(r.outcome, AppOpOutcome::Failed { .. }))
+        .count()
+}
+
 /// Print logs in JSON format if enabled
 pub fn print_logs(lines: &[String]) {
     if is_json_mode() {
@@ -463,6 +869,31 @@ pub fn print_error_json(message: &str) {
     }
 }
 
+/// Structured failure payload printed in JSON mode
+#[derive(Serialize)]
+struct ErrorJson<'a> {
+    success: bool,
+    error: &'a str,
+    error_code: &'a str,
+    exit_code: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+}
+
+/// Print a command failure as JSON on stdout
+pub fn print_error_payload(message: &str, error_code: &str, exit_code: i32, hint: Option<String>) {
+    let payload = ErrorJson {
+        success: false,
+        error: message,
+        error_code,
+        exit_code,
+        hint,
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&payload) {
+        println!("{}", json);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -534,6 +965,48 @@ mod tests {
         assert_eq!(json_detail.max_uptime_secs, Some(86400));
     }
 
+    #[test]
+    fn test_format_backoff() {
+        let mut info = create_test_app_info();
+        assert_eq!(format_backoff(&info), None);
+
+        info.spec.restart_policy.max_restarts = 3;
+        info.spec.restart_policy.crash_window_secs = 60;
+        info.state.restarts_in_window = 1;
+        assert_eq!(format_backoff(&info).unwrap(), "1/3 restarts within 1m 0s");
+
+        info.state.next_restart_at = Some(chrono::Utc::now() + chrono::Duration::seconds(30));
+        assert!(format_backoff(&info).unwrap().contains("next attempt at"));
+
+        info.state.next_restart_at = None;
+        info.state.restarts_in_window = 3;
+        info.state.status = AppStatus::Errored;
+        assert!(format_backoff(&info).unwrap().contains("--reset-counter"));
+    }
+
+    #[test]
+    fn test_format_build() {
+        let mut build = BuildInfo {
+            profile: "release".to_string(),
+            binary_path: PathBuf::from("/app/target/release/app"),
+            duration_ms: 61_500,
+            git_commit: Some("0123456789abcdef0123".to_string()),
+            sha256: None,
+        };
+        assert_eq!(
+            format_build(&build),
+            "release, built in 1m 1s, commit 0123456789ab"
+        );
+        build.git_commit = None;
+        assert_eq!(format_build(&build), "release, built in 1m 1s");
+        build.duration_ms = 118;
+        assert_eq!(format_build(&build), "release, built in 118ms");
+
+        build.profile = BuildInfo::PREBUILT.to_string();
+        build.sha256 = Some("9f86d081884c7d659a2feaa0c55ad015".to_string());
+        assert_eq!(format_build(&build), "prebuilt, sha256 9f86d081884c");
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(0), "0s");
@@ -555,4 +1028,11 @@ mod tests {
         assert_eq!(format_bytes(1024 * 1024 * 1024), "1.0G");
         assert_eq!(format_bytes(128 * 1024 * 1024), "128.0M");
     }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(progress_bar(0, 4), "[....]   0%");
+        assert_eq!(progress_bar(50, 4), "[##..]  50%");
+        assert_eq!(progress_bar(120, 4), "[####] 100%");
+    }
 }
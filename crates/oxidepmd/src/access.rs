@@ -0,0 +1,117 @@
+//! Who may use the daemon socket, from `OXIDEPM_SOCKET_MODE`,
+//! `OXIDEPM_SOCKET_GROUP`, `OXIDEPM_ALLOWED_UIDS` and `OXIDEPM_ALLOWED_GIDS`
+
+use nix::unistd::{Group, User};
+use oxidepm_core::{constants, Error, Result};
+use oxidepm_ipc::SocketAccess;
+
+/// Socket permissions and peer allowlist from the environment
+pub fn socket_access() -> Result<SocketAccess> {
+    let var = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    };
+    Ok(SocketAccess {
+        mode: var(constants::SOCKET_MODE_ENV)
+            .map(|v| parse_mode(&v))
+            .transpose()?,
+        group: var(constants::SOCKET_GROUP_ENV)
+            .map(|v| resolve_group(v.trim()))
+            .transpose()?,
+        uids: var(constants::ALLOWED_UIDS_ENV)
+            .map(|v| parse_list(&v, resolve_user))
+            .transpose()?
+            .unwrap_or_default(),
+        gids: var(constants::ALLOWED_GIDS_ENV)
+            .map(|v| parse_list(&v, resolve_group))
+            .transpose()?
+            .unwrap_or_default(),
+    })
+}
+
+/// Octal file mode, with or without a leading 0 ("660", "0660", "0o660")
+fn parse_mode(value: &str) -> Result<u32> {
+    let digits = value.trim().trim_start_matches("0o");
+    u32::from_str_radix(digits, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+        .ok_or_else(|| {
+            Error::ConfigError(format!(
+                "{} must be an octal mode like 0660, not '{}'",
+                constants::SOCKET_MODE_ENV,
+                value
+            ))
+        })
+}
+
+fn parse_list(value: &str, resolve: fn(&str) -> Result<u32>) -> Result<Vec<u32>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(resolve)
+        .collect()
+}
+
+fn resolve_user(name: &str) -> Result<u32> {
+    if let Ok(uid) = name.parse() {
+        return Ok(uid);
+    }
+    match User::from_name(name) {
+        Ok(Some(user)) => Ok(user.uid.as_raw()),
+        _ => Err(Error::ConfigError(format!(
+            "Unknown user '{}' in {}",
+            name,
+            constants::ALLOWED_UIDS_ENV
+        ))),
+    }
+}
+
+fn resolve_group(name: &str) -> Result<u32> {
+    if let Ok(gid) = name.parse() {
+        return Ok(gid);
+    }
+    match Group::from_name(name) {
+        Ok(Some(group)) => Ok(group.gid.as_raw()),
+        _ => Err(Error::ConfigError(format!("Unknown group '{}'", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_ipc::PeerCred;
+
+    #[test]
+    fn test_parse_access() {
+        assert_eq!(parse_mode("0660").unwrap(), 0o660);
+        assert_eq!(parse_mode("0o600").unwrap(), 0o600);
+        assert!(parse_mode("rw-rw----").is_err());
+        assert!(parse_mode("1777").is_err());
+
+        assert_eq!(
+            parse_list("1001, 1002,,", resolve_user).unwrap(),
+            vec![1001, 1002]
+        );
+        assert_eq!(parse_list("root", resolve_user).unwrap(), vec![0]);
+        assert_eq!(parse_list("root", resolve_group).unwrap(), vec![0]);
+        assert!(parse_list("no-such-user-here", resolve_user).is_err());
+
+        let access = SocketAccess {
+            uids: vec![1001],
+            gids: vec![2000],
+            ..Default::default()
+        };
+        let peer = |uid, gid| PeerCred {
+            uid,
+            gid,
+            pid: None,
+        };
+        assert!(access.allows(500, &peer(500, 500)));
+        assert!(access.allows(500, &peer(0, 0)));
+        assert!(access.allows(500, &peer(1001, 1001)));
+        assert!(access.allows(500, &peer(1003, 2000)));
+        assert!(!access.allows(500, &peer(1003, 1003)));
+    }
+}
@@ -0,0 +1,94 @@
+//! Audit log of control requests
+//!
+//! Every request that changes state is recorded with who sent it and how it
+//! went, both in `audit.log` (one JSON object per line, append-only) and in
+//! the database, where `oxidepm audit` and `/api/audit` read it back.
+
+use nix::unistd::{Uid, User};
+use oxidepm_core::{constants, AuditEntry, Result};
+use oxidepm_db::AuditStore;
+use oxidepm_ipc::PeerCred;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Where audit entries go
+pub struct AuditLog {
+    store: Arc<dyn AuditStore>,
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(store: Arc<dyn AuditStore>) -> Self {
+        Self {
+            store,
+            path: constants::audit_path(),
+        }
+    }
+
+    /// Record an entry; failures are logged so they never fail the request
+    pub async fn record(&self, entry: AuditEntry) {
+        if let Err(e) = self.append(&entry) {
+            warn!("Failed to write audit log {}: {}", self.path.display(), e);
+        }
+        if let Err(e) = self.store.insert(&entry).await {
+            warn!("Failed to store audit entry: {}", e);
+        }
+    }
+
+    /// Latest `limit` entries, oldest first
+    pub async fn recent(&self, limit: usize) -> Result<Vec<AuditEntry>> {
+        self.store.recent(limit).await
+    }
+
+    fn append(&self, entry: &AuditEntry) -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        options.open(&self.path)?.write_all(line.as_bytes())
+    }
+}
+
+/// "uid 1000 (alice)" for a local client
+pub fn describe_peer(peer: Option<PeerCred>) -> String {
+    let Some(peer) = peer else {
+        return "unknown".to_string();
+    };
+    match User::from_uid(Uid::from_raw(peer.uid)) {
+        Ok(Some(user)) => format!("uid {} ({})", peer.uid, user.name),
+        _ => format!("uid {}", peer.uid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_peer() {
+        assert_eq!(
+            describe_peer(Some(PeerCred {
+                uid: 0,
+                gid: 0,
+                pid: None
+            })),
+            "uid 0 (root)"
+        );
+        assert_eq!(
+            describe_peer(Some(PeerCred {
+                uid: 4_000_000,
+                gid: 0,
+                pid: None
+            })),
+            "uid 4000000"
+        );
+        assert_eq!(describe_peer(None), "unknown");
+    }
+}
@@ -0,0 +1,253 @@
+//! Telegram bot commands
+//!
+//! When the Telegram channel is `interactive`, a background task long-polls
+//! the bot and turns `/status`, `/restart` and `/logs` into the same requests
+//! the CLI sends. Restarts wait their turn behind other mutations of the
+//! same apps and are recorded in the audit log as `telegram <chat id>`.
+
+use oxidepm_core::{AppInfo, AppOpOutcome, Selector};
+use oxidepm_ipc::{Request, Response};
+use oxidepm_notify::{BotCommand, BotRequest, MAX_MESSAGE_CHARS};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::daemon::Daemon;
+use crate::handlers::RequestHandler;
+use crate::locks::MutationLocks;
+
+/// Longest wait between polls after the Telegram API fails
+const MAX_BACKOFF_SECS: u64 = 60;
+
+/// Answer bot commands when the Telegram channel is interactive
+pub fn spawn(handler: Arc<RequestHandler>, mutations: Arc<MutationLocks>) {
+    let Some(mut bot) = handler.supervisor().notifier().telegram_bot() else {
+        return;
+    };
+    info!("Telegram bot: answering commands");
+
+    tokio::spawn(async move {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            let requests = match bot.poll().await {
+                Ok(requests) => {
+                    backoff = Duration::from_secs(1);
+                    requests
+                }
+                Err(e) => {
+                    warn!("Telegram bot: {}", e);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(Duration::from_secs(MAX_BACKOFF_SECS));
+                    continue;
+                }
+            };
+
+            for BotRequest { chat_id, command } in requests {
+                let response = Daemon::execute(
+                    &handler,
+                    &mutations,
+                    to_request(&command),
+                    format!("telegram {}", chat_id),
+                    Some("telegram".to_string()),
+                )
+                .await;
+                if let Err(e) = bot
+                    .reply(&chat_id, &format_response(&command, response))
+                    .await
+                {
+                    warn!("Telegram bot: failed to reply to chat {}: {}", chat_id, e);
+                }
+            }
+        }
+    });
+}
+
+/// The IPC request a command stands for
+fn to_request(command: &BotCommand) -> Request {
+    match command {
+        // Help is answered by the bot itself
+        BotCommand::Status | BotCommand::Help => Request::Status,
+        BotCommand::Restart { app } => Request::Restart {
+            selector: Selector::parse(app),
+            reset_counter: false,
+            bulk: Default::default(),
+            if_exists: false,
+        },
+        BotCommand::Logs { app, lines } => Request::Logs {
+            selector: Selector::parse(app),
+            lines: *lines,
+            follow: false,
+            stdout: false,
+            stderr: false,
+            since_secs: None,
+        },
+    }
+}
+
+/// Plain text answer to a command
+fn format_response(command: &BotCommand, response: Response) -> String {
+    match response {
+        Response::Error { message, .. } => format!("Error: {}", message),
+        Response::Status {
+            apps,
+            supervision_paused,
+        } => {
+            let mut text = format_status(&apps);
+            if supervision_paused {
+                text.push_str("\n\nSupervision is paused");
+            }
+            text
+        }
+        Response::Restarted { results, .. } => {
+            if results.is_empty() {
+                return "No matching apps".to_string();
+            }
+            results
+                .iter()
+                .map(|r| match &r.outcome {
+                    AppOpOutcome::Done => format!("Restarted {}", r.name),
+                    AppOpOutcome::Skipped => format!("Skipped {}", r.name),
+                    AppOpOutcome::AlreadyStopped => format!("{} is already stopped", r.name),
+                    AppOpOutcome::NotFound => format!("{} not found", r.name),
+                    AppOpOutcome::Failed { error } => {
+                        format!("Failed to restart {}: {}", r.name, error)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Response::LogLines { lines } => {
+            let BotCommand::Logs { app, .. } = command else {
+                return lines.join("\n");
+            };
+            if lines.is_empty() {
+                return format!("No logs for {}", app);
+            }
+            // Keep the newest lines that fit in one message
+            let mut kept = Vec::new();
+            let mut size = 0;
+            for line in lines.iter().rev() {
+                size += line.chars().count() + 1;
+                if size > MAX_MESSAGE_CHARS - 100 {
+                    break;
+                }
+                kept.push(line.as_str());
+            }
+            kept.reverse();
+            format!("Last {} lines of {}:\n{}", kept.len(), app, kept.join("\n"))
+        }
+        other => other.kind(),
+    }
+}
+
+/// One line per app: name, status, CPU, memory and restarts
+fn format_status(apps: &[AppInfo]) -> String {
+    if apps.is_empty() {
+        return "No apps managed".to_string();
+    }
+    let running = apps.iter().filter(|a| a.state.status.is_running()).count();
+    let mut lines = vec![format!("{} apps, {} running", apps.len(), running)];
+    for app in apps {
+        let mut line = format!(
+            "{} [{}] {}",
+            app.spec.id,
+            app.state.status,
+            app.spec.qualified_name()
+        );
+        if app.state.status.is_running() {
+            line.push_str(&format!(
+                " - cpu {:.1}%, mem {}MB",
+                app.state.cpu_percent,
+                app.state.memory_bytes / (1024 * 1024)
+            ));
+        }
+        if app.state.restarts > 0 {
+            line.push_str(&format!(", {} restarts", app.state.restarts));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::{AppMode, AppOpResult, AppSpec, AppStatus, RunState};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_to_request() {
+        let request = to_request(&BotCommand::Restart {
+            app: "api".to_string(),
+        });
+        assert!(
+            matches!(request, Request::Restart { selector: Selector::ByName(ref name), reset_counter: false, .. } if name == "api")
+        );
+        assert!(!request.is_read_only());
+        assert!(to_request(&BotCommand::Status).is_read_only());
+        assert!(to_request(&BotCommand::Logs {
+            app: "api".to_string(),
+            lines: 20
+        })
+        .is_read_only());
+    }
+
+    #[test]
+    fn test_format_status() {
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Cmd,
+            "sleep".to_string(),
+            PathBuf::from("/"),
+        );
+        spec.id = 1;
+        let mut state = RunState::new(1);
+        state.status = AppStatus::Running;
+        state.memory_bytes = 64 * 1024 * 1024;
+        state.restarts = 2;
+
+        let text = format_response(
+            &BotCommand::Status,
+            Response::Status {
+                apps: vec![AppInfo::new(spec, state)],
+                supervision_paused: false,
+            },
+        );
+        assert!(text.starts_with("1 apps, 1 running"));
+        assert!(text.contains("1 [running] api - cpu 0.0%, mem 64MB, 2 restarts"));
+    }
+
+    #[test]
+    fn test_format_restart_and_logs() {
+        let text = format_response(
+            &BotCommand::Restart {
+                app: "api".to_string(),
+            },
+            Response::Restarted {
+                count: 1,
+                results: vec![AppOpResult {
+                    id: 1,
+                    name: "api".to_string(),
+                    outcome: AppOpOutcome::Done,
+                }],
+            },
+        );
+        assert_eq!(text, "Restarted api");
+
+        let logs = BotCommand::Logs {
+            app: "api".to_string(),
+            lines: 2,
+        };
+        let text = format_response(
+            &logs,
+            Response::LogLines {
+                lines: vec!["one".to_string(), "two".to_string()],
+            },
+        );
+        assert_eq!(text, "Last 2 lines of api:\none\ntwo");
+
+        let long = vec!["x".repeat(1000); 10];
+        let text = format_response(&logs, Response::LogLines { lines: long });
+        assert!(text.chars().count() <= MAX_MESSAGE_CHARS);
+    }
+}
@@ -1,110 +1,453 @@
 //! Main daemon orchestration
 
-use oxidepm_core::{constants, Result};
+use chrono::Utc;
+use oxidepm_core::{
+    constants, AppOpOutcome, AppOpResult, AuditEntry, BulkOptions, Error, Result, Selector,
+};
 use oxidepm_db::Database;
-use oxidepm_ipc::{IpcServer, Request, Response};
+use oxidepm_ipc::{Envelope, IpcConnection, IpcServer, Request, Response, TcpIpcServer};
+use oxidepm_runtime::ProgressSink;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{error, info};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, error, info, warn};
 
+use crate::access;
+use crate::audit;
+use crate::bot;
 use crate::handlers::RequestHandler;
-use crate::supervisor::Supervisor;
+use crate::locks::{LockScope, MutationLocks};
+use crate::reports;
+use crate::supervisor::{Supervisor, SupervisorAction};
+use crate::watchdog;
 
 /// Main daemon struct
 pub struct Daemon {
     server: IpcServer,
-    handler: Arc<RwLock<RequestHandler>>,
+    /// Remote clients, when `OXIDEPM_TCP_LISTEN` is set
+    tcp_server: Option<TcpIpcServer>,
+    handler: Arc<RequestHandler>,
+    /// Held while a state-changing request runs, so two never touch the same app at once
+    mutations: Arc<MutationLocks>,
 }
 
 impl Daemon {
     /// Create a new daemon instance
     pub async fn new() -> Result<Self> {
         // Initialize database
-        let db_path = constants::db_path();
-        let db = Database::new(&db_path).await?;
-        info!("Database initialized at {}", db_path.display());
+        let ephemeral = constants::ephemeral();
+        let db = match constants::database_url() {
+            _ if ephemeral => {
+                info!("Ephemeral mode: keeping state in memory");
+                Database::memory()
+            }
+            Some(url) => {
+                // Several hosts can share one database; each keeps to its own apps
+                let host = sysinfo::System::host_name().unwrap_or_else(|| "localhost".to_string());
+                let db = Database::connect(&url, &host).await?;
+                info!(
+                    "Database initialized at {} (host {})",
+                    oxidepm_db::redact_url(&url),
+                    host
+                );
+                db
+            }
+            None => {
+                let db_path = constants::db_path();
+                let db = Database::new(&db_path).await?;
+                info!("Database initialized at {}", db_path.display());
+                db
+            }
+        };
 
         // Create supervisor
         let supervisor = Supervisor::new(db).await?;
 
-        // Resurrect any saved processes
-        let count = supervisor.resurrect().await?;
-        if count > 0 {
-            info!("Resurrected {} saved processes", count);
+        // Resurrect any saved processes; an ephemeral daemon starts empty
+        if !ephemeral {
+            let report = supervisor.resurrect(None, false).await?;
+            if report.started > 0 {
+                info!("Resurrected {} saved processes", report.started);
+            }
         }
 
         // Create request handler
-        let handler = RequestHandler::new(supervisor);
+        let actions = supervisor.take_actions();
+        let handler = Arc::new(RequestHandler::new(supervisor));
+        if let Some(actions) = actions {
+            Self::spawn_action_runner(Arc::clone(&handler), actions);
+        }
+        watchdog::spawn(Arc::clone(&handler));
+        Self::spawn_config_reload(Arc::clone(&handler))?;
+        reports::spawn(Arc::clone(&handler));
+        let mutations = Arc::new(MutationLocks::default());
+        bot::spawn(Arc::clone(&handler), Arc::clone(&mutations));
+        if let Some(minutes) = constants::autosave_minutes().filter(|_| !ephemeral) {
+            info!(
+                "Auto-saving the process list every {} minute(s) and on topology changes",
+                minutes
+            );
+            Self::spawn_autosave(Arc::clone(&handler), minutes);
+        }
 
         // Create IPC server
         let socket_path = constants::socket_path();
-        let server = IpcServer::bind(&socket_path).await?;
+        let server = IpcServer::bind_with_access(&socket_path, access::socket_access()?).await?;
         info!("IPC server listening on {}", socket_path.display());
 
+        let tcp_server = match constants::tcp_listen() {
+            Some((addr, Some(token))) => Some(TcpIpcServer::bind(&addr, token).await?),
+            Some((addr, None)) => {
+                return Err(Error::ConfigError(format!(
+                    "{} is set to {} but {} is not; refusing unauthenticated remote clients",
+                    constants::TCP_LISTEN_ENV,
+                    addr,
+                    constants::TCP_TOKEN_ENV
+                )))
+            }
+            None => None,
+        };
+
         Ok(Self {
             server,
-            handler: Arc::new(RwLock::new(handler)),
+            tcp_server,
+            handler,
+            mutations,
         })
     }
 
+    /// Stop every app, for an ephemeral daemon on its way out
+    pub async fn stop_all(&self) {
+        let _serialized = self.mutations.lock(LockScope::Daemon).await;
+        self.handler.stop(Selector::All, false).await;
+    }
+
+    /// Re-read the daemon config on SIGHUP instead of exiting
+    fn spawn_config_reload(handler: Arc<RequestHandler>) -> Result<()> {
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                info!("Received SIGHUP, reloading daemon config");
+                handler.reload_daemon_config();
+            }
+        });
+        Ok(())
+    }
+
+    /// Save the process list every `minutes`
+    fn spawn_autosave(handler: Arc<RequestHandler>, minutes: u64) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(minutes * 60));
+            // The first tick completes immediately; nothing changed yet
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                handler.autosave().await;
+            }
+        });
+    }
+
+    /// Run restarts/stops requested by the supervisor's background tasks
+    fn spawn_action_runner(
+        handler: Arc<RequestHandler>,
+        mut actions: mpsc::UnboundedReceiver<SupervisorAction>,
+    ) {
+        tokio::spawn(async move {
+            while let Some(action) = actions.recv().await {
+                let handler = Arc::clone(&handler);
+                tokio::spawn(async move {
+                    handler.supervisor().run_action(action).await;
+                });
+            }
+        });
+    }
+
     /// Run the daemon main loop
     pub async fn run(&self) -> Result<()> {
         info!("Daemon running, waiting for connections...");
 
         loop {
-            match self.server.accept().await {
-                Ok(mut conn) => {
-                    let handler = Arc::clone(&self.handler);
-
-                    tokio::spawn(async move {
-                        loop {
-                            match conn.read_request().await {
-                                Ok(Some(request)) => {
-                                    let response = Self::handle_request(&handler, request).await;
-
-                                    if let Err(e) = conn.send_response(&response).await {
-                                        error!("Failed to send response: {}", e);
-                                        break;
-                                    }
-
-                                    // Check if this was a kill request
-                                    if matches!(response, Response::Ok { .. })
-                                        && matches!(response, Response::Ok { message } if message.contains("Daemon shutting down"))
-                                    {
-                                        // Don't break here, let the main loop handle shutdown
-                                    }
-                                }
-                                Ok(None) => {
-                                    // Connection closed
-                                    break;
-                                }
-                                Err(e) => {
-                                    error!("Error reading request: {}", e);
-                                    break;
-                                }
+            tokio::select! {
+                accepted = self.server.accept() => match accepted {
+                    Ok(conn) => {
+                        let handler = Arc::clone(&self.handler);
+                        let mutations = Arc::clone(&self.mutations);
+                        let actor = audit::describe_peer(conn.peer());
+                        tokio::spawn(Self::serve_connection(handler, mutations, conn, actor));
+                    }
+                    Err(e) => {
+                        error!("Failed to accept connection: {}", e);
+                    }
+                },
+                accepted = Self::accept_tcp(self.tcp_server.as_ref()) => match accepted {
+                    Ok((mut conn, peer)) => {
+                        let handler = Arc::clone(&self.handler);
+                        let mutations = Arc::clone(&self.mutations);
+                        let token = self.tcp_server.as_ref().map(|s| s.token().to_string()).unwrap_or_default();
+                        tokio::spawn(async move {
+                            if let Err(e) = conn.authenticate(&token).await {
+                                warn!("Rejected TCP client {}: {}", peer, e);
+                                return;
                             }
-                        }
-                    });
+                            debug!("Authenticated TCP client {}", peer);
+                            Self::serve_connection(handler, mutations, conn, format!("tcp {}", peer)).await;
+                        });
+                    }
+                    Err(e) => {
+                        error!("Failed to accept TCP connection: {}", e);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Next TCP client, or never when not listening on TCP
+    async fn accept_tcp(
+        server: Option<&TcpIpcServer>,
+    ) -> Result<(IpcConnection, std::net::SocketAddr)> {
+        match server {
+            Some(server) => server.accept().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Serve one client connection
+    ///
+    /// Each request runs in its own task and its response is written back with
+    /// the request's id as soon as it is ready, so a slow request does not hold
+    /// up the ones behind it. When the client disconnects, read-only requests
+    /// still in flight are dropped; mutations run to completion so no app is
+    /// left half started or stopped, and are recorded in the audit log under
+    /// `actor`.
+    async fn serve_connection(
+        handler: Arc<RequestHandler>,
+        mutations: Arc<MutationLocks>,
+        conn: IpcConnection,
+        actor: String,
+    ) {
+        let (mut reader, mut writer) = conn.into_split();
+        let (responses_tx, mut responses_rx) = mpsc::unbounded_channel::<(Option<u64>, Response)>();
+        // Never written to; dropping the sender tells request tasks the client is gone
+        let (connected_tx, connected_rx) = watch::channel(());
+
+        tokio::spawn(async move {
+            while let Some((id, response)) = responses_rx.recv().await {
+                if let Err(e) = writer.send_response(id, &response).await {
+                    error!("Failed to send response: {}", e);
+                    break;
                 }
+            }
+        });
+
+        loop {
+            let Envelope {
+                id,
+                message: request,
+                client,
+            } = match reader.read_request().await {
+                Ok(Some(envelope)) => envelope,
+                Ok(None) => break, // Connection closed
                 Err(e) => {
-                    error!("Failed to accept connection: {}", e);
+                    error!("Error reading request: {}", e);
+                    break;
                 }
+            };
+
+            // Build output, per-app restart results and exec output go out
+            // as they happen, ahead of the final response
+            let progress = match request {
+                Request::Start { progress: true, .. }
+                | Request::Restart {
+                    bulk: BulkOptions { progress: true, .. },
+                    ..
+                }
+                | Request::Exec { .. } => {
+                    let responses_tx = responses_tx.clone();
+                    ProgressSink::new(move |line| {
+                        let _ = responses_tx.send((
+                            id,
+                            Response::BuildProgress {
+                                line: line.to_string(),
+                            },
+                        ));
+                    })
+                }
+                _ => ProgressSink::none(),
+            };
+
+            let handler = Arc::clone(&handler);
+            let mutations = Arc::clone(&mutations);
+            let responses_tx = responses_tx.clone();
+            let mut connected = connected_rx.clone();
+            let actor = actor.clone();
+            tokio::spawn(async move {
+                let response = if request.is_read_only() {
+                    tokio::select! {
+                        response = Self::handle_request(&handler, request, progress) => response,
+                        _ = connected.changed() => {
+                            debug!("Client disconnected, cancelled request {:?}", id);
+                            return;
+                        }
+                    }
+                } else {
+                    let starting = match &request {
+                        Request::Start { spec, .. } => Some(spec.clone()),
+                        _ => None,
+                    };
+                    // Long requests are tracked as operations; past its
+                    // budget one carries on in the background and the client
+                    // gets the operation id to poll
+                    let operation = handler.budget(&request).map(|budget| {
+                        let operations = handler.operations();
+                        (budget, operations.begin(request.action(), request.target()))
+                    });
+                    let progress = match operation {
+                        Some((_, id)) => handler.operations().sink(id, progress),
+                        None => progress,
+                    };
+                    let work = Self::mutate(
+                        Arc::clone(&handler),
+                        mutations,
+                        request,
+                        progress,
+                        actor,
+                        client,
+                    );
+                    let response = async {
+                        match operation {
+                            Some((budget, id)) => handler.operations().run(id, budget, work).await,
+                            None => work.await,
+                        }
+                    };
+                    tokio::pin!(response);
+                    match starting {
+                        // Nobody is waiting for a start whose client left, so
+                        // stop building; a start that got past its build still
+                        // completes
+                        Some(spec) => tokio::select! {
+                            response = &mut response => response,
+                            _ = connected.changed() => {
+                                handler.cancel_start(&spec);
+                                response.await
+                            }
+                        },
+                        None => response.await,
+                    }
+                };
+                let _ = responses_tx.send((id, response));
+            });
+        }
+
+        drop(connected_tx);
+    }
+
+    /// Run a mutation under its locks, then auto-save and audit it
+    async fn mutate(
+        handler: Arc<RequestHandler>,
+        mutations: Arc<MutationLocks>,
+        request: Request,
+        progress: ProgressSink,
+        actor: String,
+        client: Option<String>,
+    ) -> Response {
+        // Builds hold the app's lock until they finish; stopping the app
+        // cancels them instead of queueing behind them
+        let cancelled = match &request {
+            Request::Stop { selector, .. } | Request::Delete { selector, .. } => {
+                handler.cancel_builds(selector).await
             }
+            _ => Vec::new(),
+        };
+        let _serialized = mutations.lock(handler.lock_scope(&request).await).await;
+        let changes_topology = request.changes_topology();
+        let (action, target) = (request.action(), request.target());
+        let response = Self::handle_request(&handler, request, progress).await;
+        let response = count_cancelled_builds(response, &cancelled);
+        if changes_topology && !matches!(response, Response::Error { .. }) {
+            handler.autosave().await;
         }
+        Self::audit(&handler, actor, client, action, target, &response).await;
+        response
     }
 
-    async fn handle_request(
-        handler: &Arc<RwLock<RequestHandler>>,
+    /// Run a request on behalf of something other than a client connection,
+    /// such as the Telegram bot. Mutations are locked and audited the same
+    /// way as those from clients.
+    pub async fn execute(
+        handler: &RequestHandler,
+        mutations: &MutationLocks,
         request: Request,
+        actor: String,
+        client: Option<String>,
     ) -> Response {
-        let mut h = handler.write().await;
+        if request.is_read_only() {
+            return Self::handle_request(handler, request, ProgressSink::none()).await;
+        }
+        let _serialized = mutations.lock(handler.lock_scope(&request).await).await;
+        let changes_topology = request.changes_topology();
+        let (action, target) = (request.action(), request.target());
+        let response = Self::handle_request(handler, request, ProgressSink::none()).await;
+        if changes_topology && !matches!(response, Response::Error { .. }) {
+            handler.autosave().await;
+        }
+        Self::audit(handler, actor, client, action, target, &response).await;
+        response
+    }
 
+    /// Record a mutation and how it went in the audit log
+    async fn audit(
+        handler: &RequestHandler,
+        actor: String,
+        client: Option<String>,
+        action: String,
+        target: Option<String>,
+        response: &Response,
+    ) {
+        let (success, result) = match response {
+            Response::Error { message, .. } => (false, message.clone()),
+            response => (true, response.kind()),
+        };
+        handler
+            .audit_log()
+            .record(AuditEntry {
+                timestamp: Utc::now(),
+                actor,
+                client,
+                action,
+                target,
+                success,
+                result,
+            })
+            .await;
+    }
+
+    async fn handle_request(
+        h: &RequestHandler,
+        request: Request,
+        progress: ProgressSink,
+    ) -> Response {
         match request {
-            Request::Ping => Response::Pong,
-            Request::Start { spec } => h.start(spec).await,
-            Request::Stop { selector } => h.stop(selector).await,
-            Request::Restart { selector } => h.restart(selector).await,
-            Request::Delete { selector } => h.delete(selector).await,
+            Request::Ping => h.ping(),
+            Request::Start { spec, .. } => h.start(spec, &progress).await,
+            Request::Stop {
+                selector,
+                if_exists,
+            } => h.stop(selector, if_exists).await,
+            Request::Restart {
+                selector,
+                reset_counter,
+                bulk,
+                if_exists,
+            } => {
+                h.restart(selector, reset_counter, bulk, if_exists, &progress)
+                    .await
+            }
+            Request::Group {
+                selector,
+                action,
+                rollout,
+            } => h.group(selector, action, rollout, &progress).await,
+            Request::Delete { selector, purge } => h.delete(selector, purge).await,
             Request::Status => h.status().await,
             Request::Show { selector } => h.show(selector).await,
             Request::Logs {
@@ -113,17 +456,90 @@ impl Daemon {
                 follow: _,
                 stdout,
                 stderr,
-            } => h.logs(selector, lines, stdout, stderr).await,
-            Request::Save => h.save().await,
-            Request::Resurrect => h.resurrect().await,
-            Request::Reload { selector } => h.reload(selector).await,
-            Request::Flush { selector } => h.flush(selector).await,
+                since_secs,
+            } => h.logs(selector, lines, stdout, stderr, since_secs).await,
+            Request::LogPage {
+                selector,
+                stream,
+                lines,
+                before,
+                after,
+            } => h.log_page(selector, stream, lines, before, after).await,
+            Request::Save { output } => h.save(output).await,
+            Request::Resurrect { file, all } => h.resurrect(file, all, &progress).await,
+            Request::Reload { selector } => h.reload(selector, &progress).await,
+            Request::Flush {
+                selector,
+                older_than_secs,
+                keep_bytes,
+            } => h.flush(selector, older_than_secs, keep_bytes).await,
             Request::Describe { selector } => h.describe(selector).await,
+            Request::DescribeSpec { spec } => h.describe_spec(spec),
+            Request::PauseSupervision => h.set_supervision_paused(true),
+            Request::ResumeSupervision => h.set_supervision_paused(false),
+            Request::Drain {
+                selector,
+                timeout_secs,
+            } => h.drain(selector, timeout_secs).await,
+            Request::Undrain { selector } => h.undrain(selector).await,
+            Request::Pause { selector } => h.pause(selector).await,
+            Request::Resume { selector } => h.resume(selector).await,
+            Request::Signal {
+                selector,
+                signal,
+                tree,
+            } => h.signal(selector, signal, tree).await,
+            Request::Apply {
+                specs,
+                prune,
+                dry_run,
+            } => h.apply(specs, prune, dry_run).await,
+            Request::Env { selector } => h.env(selector).await,
+            Request::Exec { selector, command } => h.exec(selector, command, &progress).await,
+            Request::Health { selector } => h.health(selector).await,
+            Request::Events { selector, limit } => h.events(selector, limit).await,
+            Request::Audit { limit } => h.audit(limit).await,
+            Request::OperationStatus { id } => h.operation_status(id),
+            Request::Operations => h.list_operations(),
+            Request::SetEnv {
+                selector,
+                set,
+                unset,
+                restart,
+            } => h.set_env(selector, set, unset, restart).await,
+            Request::ReloadDaemonConfig => h.reload_daemon_config(),
+            Request::Check { dir, fix } => h.check(dir, fix).await,
+            Request::Rename { selector, name } => h.rename(selector, name).await,
+            Request::UpdateSpec {
+                selector,
+                update,
+                restart,
+                reload,
+            } => h.update_spec(selector, update, restart, reload).await,
+            Request::SystemInfo => h.system_info().await,
+            Request::Ports => h.ports(),
             Request::Kill => {
                 // Save before killing
-                let _ = h.save().await;
+                let _ = h.save(None).await;
                 Response::ok("Daemon shutting down")
             }
         }
     }
 }
+
+/// Apps whose build a stop cancelled count as stopped, even though no
+/// process was left to stop by the time the stop ran
+fn count_cancelled_builds(response: Response, cancelled: &[u32]) -> Response {
+    match response {
+        Response::Stopped { mut results, .. } if !cancelled.is_empty() => {
+            for result in results.iter_mut().filter(|r| cancelled.contains(&r.id)) {
+                result.outcome = AppOpOutcome::Done;
+            }
+            Response::Stopped {
+                count: AppOpResult::count_done(&results),
+                results,
+            }
+        }
+        response => response,
+    }
+}
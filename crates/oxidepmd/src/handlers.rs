@@ -1,104 +1,540 @@
 //! IPC request handlers
 
-use oxidepm_core::{constants, AppSpec, Result, Selector};
-use oxidepm_ipc::Response;
-use oxidepm_logs::{stderr_path, stdout_path};
+use oxidepm_core::{
+    constants, AppOpOutcome, AppOpResult, AppSignal, AppSpec, BuildInfo, BulkOptions, DaemonConfig,
+    DesiredState, Error, GroupAction, Heartbeat, LogStream, Result, Rollout, Selector, SpecUpdate,
+};
+use oxidepm_ipc::{Request, Response};
+use oxidepm_notify::{notify_config_path, NotifyConfig};
+use oxidepm_runtime::preflight::run_preflight_checks;
+use oxidepm_runtime::ProgressSink;
+use parking_lot::RwLock;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::OpenOptions;
-use tracing::{error, info, warn};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, error, info, warn};
 
+use crate::audit::AuditLog;
+use crate::locks::LockScope;
+use crate::operations::Operations;
 use crate::supervisor::Supervisor;
+use crate::watchdog::Watchdog;
 
 /// Request handler for IPC commands
 pub struct RequestHandler {
     supervisor: Supervisor,
+    watchdog: Watchdog,
+    audit: AuditLog,
+    /// Save the process list after topology changes (`OXIDEPM_AUTOSAVE`)
+    autosave: bool,
+    /// daemon.toml as last loaded; re-read on SIGHUP
+    config: RwLock<DaemonConfig>,
+    operations: Arc<Operations>,
 }
 
 impl RequestHandler {
     pub fn new(supervisor: Supervisor) -> Self {
-        Self { supervisor }
+        let config = DaemonConfig::load().unwrap_or_else(|e| {
+            warn!(
+                "Ignoring {}: {}",
+                constants::daemon_config_path().display(),
+                e
+            );
+            DaemonConfig::default()
+        });
+        if let Some(budget) = config.log_budget() {
+            info!("Log disk budget: {} bytes", budget);
+        }
+        supervisor.set_log_budget(config.log_budget());
+        Self {
+            audit: AuditLog::new(supervisor.audit_store()),
+            supervisor,
+            watchdog: Watchdog::new(),
+            autosave: constants::autosave_minutes().is_some() && !constants::ephemeral(),
+            config: RwLock::new(config),
+            operations: Arc::new(Operations::default()),
+        }
+    }
+
+    pub fn supervisor(&self) -> &Supervisor {
+        &self.supervisor
+    }
+
+    pub fn watchdog(&self) -> &Watchdog {
+        &self.watchdog
+    }
+
+    pub fn audit_log(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    pub fn operations(&self) -> &Arc<Operations> {
+        &self.operations
+    }
+
+    /// How long the client of `request` waits before it carries on in the
+    /// background as an operation. Only requests that can run for minutes
+    /// have a budget; exec streams its output to a client that stays for it.
+    pub fn budget(&self, request: &Request) -> Option<Duration> {
+        match request {
+            Request::Start { .. }
+            | Request::Restart { .. }
+            | Request::Group { .. }
+            | Request::Reload { .. }
+            | Request::Resurrect { .. }
+            | Request::Apply { .. } => self.config.read().request_budget(),
+            _ => None,
+        }
+    }
+
+    /// Current liveness figures
+    pub fn heartbeat(&self) -> Heartbeat {
+        let (managed, running) = self.supervisor.process_counts();
+        self.watchdog.heartbeat(managed, running)
+    }
+
+    /// Handle ping request
+    pub fn ping(&self) -> Response {
+        Response::Pong {
+            heartbeat: Some(self.heartbeat()),
+        }
     }
 
     /// Handle start request
-    pub async fn start(&mut self, spec: AppSpec) -> Response {
+    pub async fn start(&self, spec: AppSpec, progress: &ProgressSink) -> Response {
         info!("Handling start request for: {}", spec.name);
 
-        match self.supervisor.start(spec.clone()).await {
+        match self
+            .supervisor
+            .start_with_progress(spec.clone(), progress)
+            .await
+        {
             Ok(id) => Response::Started {
                 id,
-                name: spec.name,
+                name: spec.qualified_name(),
             },
             Err(e) => {
                 error!("Start failed: {}", e);
-                Response::error(e.to_string())
+                Response::from_error(&e)
+            }
+        }
+    }
+
+    /// Cancel builds in progress for the selected apps, so a stop or delete
+    /// does not wait behind a start that is still building. Returns the ids
+    /// of the apps whose build was cancelled.
+    pub async fn cancel_builds(&self, selector: &Selector) -> Vec<u32> {
+        let Ok(ids) = self.supervisor.resolve_selector(selector).await else {
+            return Vec::new();
+        };
+        let cancelled = self.supervisor.cancel_builds(&ids).await;
+        if !cancelled.is_empty() {
+            info!(
+                "Cancelled the build of {} app(s) for {}",
+                cancelled.len(),
+                selector
+            );
+        }
+        cancelled
+    }
+
+    /// What a mutation must have to itself: the apps it names, or the whole
+    /// daemon. A selector that matches nothing locks nothing; the request
+    /// fails on its own.
+    pub async fn lock_scope(&self, request: &Request) -> LockScope {
+        let selector = match request {
+            Request::Start { spec, .. } => {
+                return LockScope::Apps(BTreeSet::from([spec.qualified_name()]))
             }
+            Request::Stop { selector, .. }
+            | Request::Restart { selector, .. }
+            | Request::Group { selector, .. }
+            | Request::Delete { selector, .. }
+            | Request::Reload { selector }
+            | Request::Flush { selector, .. }
+            | Request::SetEnv { selector, .. }
+            | Request::UpdateSpec { selector, .. }
+            | Request::Drain { selector, .. }
+            | Request::Undrain { selector }
+            | Request::Pause { selector }
+            | Request::Resume { selector }
+            | Request::Signal { selector, .. } => selector,
+            // Runs next to the app without changing it; only daemon-wide
+            // requests wait for it. So do preflight fixes, which only touch
+            // the project directory.
+            Request::Exec { .. } | Request::Check { .. } => return LockScope::Apps(BTreeSet::new()),
+            _ => return LockScope::Daemon,
+        };
+        if *selector == Selector::All {
+            return LockScope::Daemon;
         }
+        LockScope::Apps(
+            self.supervisor
+                .resolve_names(selector)
+                .await
+                .unwrap_or_default(),
+        )
     }
 
-    /// Handle stop request
-    pub async fn stop(&mut self, selector: Selector) -> Response {
+    /// Cancel the build of a start request whose client went away
+    pub fn cancel_start(&self, spec: &AppSpec) {
+        if self.supervisor.cancel_builds_of(spec) > 0 {
+            info!(
+                "Client disconnected, cancelled the build of {}",
+                spec.qualified_name()
+            );
+        }
+    }
+
+    /// Handle stop request. Apps that were not running are reported as
+    /// already stopped.
+    pub async fn stop(&self, selector: Selector, if_exists: bool) -> Response {
         info!("Handling stop request for: {}", selector);
 
-        match self.supervisor.resolve_selector(&selector).await {
-            Ok(ids) => {
-                let mut count = 0;
-                for id in ids {
-                    match self.supervisor.stop(id).await {
-                        Ok(true) => count += 1,
-                        Ok(false) => {}
-                        Err(e) => error!("Error stopping {}: {}", id, e),
+        let ids = match self.supervisor.resolve_selector(&selector).await {
+            Ok(ids) => ids,
+            Err(Error::AppNotFound(_)) if if_exists => {
+                return Response::Stopped {
+                    count: 0,
+                    results: vec![AppOpResult::not_found(&selector)],
+                }
+            }
+            Err(e) => return Response::from_error(&e),
+        };
+        let stop = |id| async move {
+            let stopped = self.supervisor.stop(id).await?;
+            // Stopped on purpose: keep it stopped across resurrect
+            self.supervisor
+                .set_desired_state(id, DesiredState::Stopped)
+                .await?;
+            Ok(stopped)
+        };
+        match self.supervisor.run_bulk(ids, stop).await {
+            Ok(mut results) => {
+                for result in &mut results {
+                    if result.outcome == AppOpOutcome::Skipped {
+                        result.outcome = AppOpOutcome::AlreadyStopped;
                     }
                 }
-                Response::Stopped { count }
+                Response::Stopped {
+                    count: AppOpResult::count_done(&results),
+                    results,
+                }
             }
-            Err(e) => Response::error(e.to_string()),
+            Err(e) => Response::from_error(&e),
         }
     }
 
     /// Handle restart request
-    pub async fn restart(&mut self, selector: Selector) -> Response {
-        info!("Handling restart request for: {}", selector);
+    pub async fn restart(
+        &self,
+        selector: Selector,
+        reset_counter: bool,
+        bulk: BulkOptions,
+        if_exists: bool,
+        progress: &ProgressSink,
+    ) -> Response {
+        info!("Handling restart request for: {} ({:?})", selector, bulk);
 
-        match self.supervisor.resolve_selector(&selector).await {
-            Ok(ids) => {
-                let mut count = 0;
-                for id in ids {
-                    match self.supervisor.restart(id).await {
-                        Ok(true) => count += 1,
-                        Ok(false) => {}
-                        Err(e) => error!("Error restarting {}: {}", id, e),
-                    }
+        let ids = match self.supervisor.resolve_selector(&selector).await {
+            Ok(ids) => ids,
+            Err(Error::AppNotFound(_)) if if_exists => {
+                return Response::Restarted {
+                    count: 0,
+                    results: vec![AppOpResult::not_found(&selector)],
                 }
-                Response::Restarted { count }
             }
-            Err(e) => Response::error(e.to_string()),
+            Err(e) => return Response::from_error(&e),
+        };
+        let restart = |id| async move {
+            let restarted = self.supervisor.restart(id, reset_counter).await?;
+            // A stopped app that is restarted runs again after resurrect too
+            self.supervisor
+                .set_desired_state(id, DesiredState::Running)
+                .await?;
+            Ok(restarted)
+        };
+        match self
+            .supervisor
+            .run_bulk_with(ids, &bulk, progress, restart)
+            .await
+        {
+            Ok(results) => Response::Restarted {
+                count: AppOpResult::count_done(&results),
+                results,
+            },
+            Err(e) => Response::from_error(&e),
         }
     }
 
-    /// Handle delete request
-    pub async fn delete(&mut self, selector: Selector) -> Response {
-        info!("Handling delete request for: {}", selector);
+    /// Handle group request: run the action over the selected apps batch by
+    /// batch, optionally waiting for each batch to be healthy. A batch that
+    /// fails or does not become healthy halts the rollout, so the rest of the
+    /// group keeps serving.
+    pub async fn group(
+        &self,
+        selector: Selector,
+        action: GroupAction,
+        rollout: Rollout,
+        progress: &ProgressSink,
+    ) -> Response {
+        info!(
+            "Handling group {} for: {} ({:?})",
+            action, selector, rollout
+        );
 
-        match self.supervisor.resolve_selector(&selector).await {
-            Ok(ids) => {
-                let mut count = 0;
-                for id in ids {
-                    match self.supervisor.delete(id).await {
-                        Ok(true) => count += 1,
-                        Ok(false) => {}
-                        Err(e) => error!("Error deleting {}: {}", id, e),
+        let ids = match self.supervisor.resolve_selector(&selector).await {
+            Ok(ids) => self.supervisor.expand_clusters(&ids),
+            Err(e) => return Response::from_error(&e),
+        };
+        let health_timeout = Duration::from_secs(
+            rollout
+                .health_timeout_secs
+                .unwrap_or(constants::DEFAULT_ROLLOUT_HEALTH_TIMEOUT_SECS),
+        );
+
+        let op = |id| async move {
+            match action {
+                GroupAction::Start => {
+                    let started = self.supervisor.start_by_id(id).await?;
+                    self.supervisor
+                        .set_desired_state(id, DesiredState::Running)
+                        .await?;
+                    Ok(started)
+                }
+                GroupAction::Stop => {
+                    let stopped = self.supervisor.stop(id).await?;
+                    self.supervisor
+                        .set_desired_state(id, DesiredState::Stopped)
+                        .await?;
+                    Ok(stopped)
+                }
+                GroupAction::Restart => self.supervisor.restart(id, false).await,
+            }
+        };
+
+        let batches = rollout.batches(&ids);
+        let mut results: Vec<AppOpResult> = Vec::with_capacity(ids.len());
+        let mut halted = None;
+        let mut ran = 0;
+        for (index, batch) in batches.iter().enumerate() {
+            ran += 1;
+            let mut batch_results = match self.supervisor.run_bulk(batch.to_vec(), op).await {
+                Ok(batch_results) => batch_results,
+                Err(e) => return Response::from_error(&e),
+            };
+
+            if rollout.wait_healthy && action != GroupAction::Stop {
+                for result in batch_results
+                    .iter_mut()
+                    .filter(|r| r.outcome == AppOpOutcome::Done)
+                {
+                    if let Err(e) = self
+                        .supervisor
+                        .wait_healthy(result.id, health_timeout)
+                        .await
+                    {
+                        result.outcome = AppOpOutcome::Failed {
+                            error: e.to_string(),
+                        };
                     }
                 }
-                Response::Deleted { count }
             }
-            Err(e) => Response::error(e.to_string()),
+
+            let failed: Vec<&str> = batch_results
+                .iter()
+                .filter(|r| matches!(r.outcome, AppOpOutcome::Failed { .. }))
+                .map(|r| r.name.as_str())
+                .collect();
+            if !failed.is_empty() && index + 1 < batches.len() {
+                halted = Some(format!(
+                    "batch {} of {} failed ({}); the remaining apps were left as they were",
+                    index + 1,
+                    batches.len(),
+                    failed.join(", ")
+                ));
+            }
+            results.extend(batch_results);
+            progress.send(&format!(
+                "[batch {}/{}] {} of {} apps done",
+                index + 1,
+                batches.len(),
+                results.len(),
+                ids.len()
+            ));
+            progress.step(results.len(), ids.len());
+            if halted.is_some() {
+                break;
+            }
+        }
+
+        if halted.is_some() {
+            let remaining: Vec<u32> = ids
+                .iter()
+                .copied()
+                .filter(|id| !results.iter().any(|r| r.id == *id))
+                .collect();
+            match self
+                .supervisor
+                .run_bulk(remaining, |_| async { Ok(false) })
+                .await
+            {
+                Ok(skipped) => results.extend(skipped),
+                Err(e) => warn!("Failed to list apps left out of the rollout: {}", e),
+            }
         }
+
+        Response::GroupDone {
+            action,
+            results,
+            batches: ran,
+            halted,
+        }
+    }
+
+    /// Handle delete request
+    pub async fn delete(&self, selector: Selector, purge: bool) -> Response {
+        info!(
+            "Handling delete request for: {} (purge: {})",
+            selector, purge
+        );
+
+        let delete = |id| async move {
+            if purge {
+                self.supervisor.purge(id).await
+            } else {
+                self.supervisor.delete(id).await
+            }
+        };
+        match self.bulk(&selector, delete).await {
+            Ok(results) => Response::Deleted {
+                count: AppOpResult::count_done(&results),
+                results,
+            },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Run `op` concurrently on every app the selector matches
+    async fn bulk<'a, F, Fut>(&'a self, selector: &Selector, op: F) -> Result<Vec<AppOpResult>>
+    where
+        F: Fn(u32) -> Fut,
+        Fut: Future<Output = Result<bool>> + 'a,
+    {
+        let ids = self.supervisor.resolve_selector(selector).await?;
+        self.supervisor.run_bulk(ids, op).await
     }
 
     /// Handle status request
     pub async fn status(&self) -> Response {
         match self.supervisor.status().await {
-            Ok(apps) => Response::Status { apps },
-            Err(e) => Response::error(e.to_string()),
+            Ok(apps) => Response::Status {
+                apps,
+                supervision_paused: self.supervisor.is_supervision_paused(),
+            },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle system info request
+    pub async fn system_info(&self) -> Response {
+        match self.supervisor.system_info().await {
+            Ok(info) => Response::SystemInfo { info },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle ports request
+    pub fn ports(&self) -> Response {
+        Response::Ports {
+            ports: self.supervisor.ports(),
+        }
+    }
+
+    /// Handle pause/resume supervision request
+    pub fn set_supervision_paused(&self, paused: bool) -> Response {
+        let changed = self.supervisor.set_supervision_paused(paused);
+        let message = match (paused, changed) {
+            (true, true) => "Supervision paused",
+            (true, false) => "Supervision already paused",
+            (false, true) => "Supervision resumed",
+            (false, false) => "Supervision is not paused",
+        };
+        Response::ok(message)
+    }
+
+    /// Handle drain request
+    pub async fn drain(&self, selector: Selector, timeout_secs: u64) -> Response {
+        info!("Handling drain request for: {}", selector);
+
+        match self.supervisor.resolve_selector(&selector).await {
+            Ok(ids) => Response::Drained {
+                count: self
+                    .supervisor
+                    .drain(&ids, Duration::from_secs(timeout_secs)),
+                timeout_secs,
+            },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle undrain request
+    pub async fn undrain(&self, selector: Selector) -> Response {
+        info!("Handling undrain request for: {}", selector);
+
+        let result = async {
+            let ids = self.supervisor.resolve_selector(&selector).await?;
+            self.supervisor.undrain(&ids).await
+        }
+        .await;
+        match result {
+            Ok((count, started)) => Response::Undrained { count, started },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle pause request
+    pub async fn pause(&self, selector: Selector) -> Response {
+        info!("Handling pause request for: {}", selector);
+
+        let pause = |id| async move { self.supervisor.pause(id) };
+        match self.bulk(&selector, pause).await {
+            Ok(results) => Response::Paused {
+                count: AppOpResult::count_done(&results),
+                results,
+            },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle resume request
+    pub async fn resume(&self, selector: Selector) -> Response {
+        info!("Handling resume request for: {}", selector);
+
+        let resume = |id| async move { self.supervisor.resume(id) };
+        match self.bulk(&selector, resume).await {
+            Ok(results) => Response::Resumed {
+                count: AppOpResult::count_done(&results),
+                results,
+            },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle signal request
+    pub async fn signal(&self, selector: Selector, signal: AppSignal, tree: bool) -> Response {
+        info!("Handling {} request for: {}", signal, selector);
+
+        let send = |id| async move { self.supervisor.signal(id, signal, tree) };
+        match self.bulk(&selector, send).await {
+            Ok(results) => Response::Signalled {
+                count: AppOpResult::count_done(&results),
+                results,
+            },
+            Err(e) => Response::from_error(&e),
         }
     }
 
@@ -106,8 +542,8 @@ impl RequestHandler {
     pub async fn show(&self, selector: Selector) -> Response {
         match self.supervisor.show(&selector).await {
             Ok(Some(app)) => Response::Show { app },
-            Ok(None) => Response::error("App not found"),
-            Err(e) => Response::error(e.to_string()),
+            Ok(None) => Response::from_error(&Error::AppNotFound(selector.to_string())),
+            Err(e) => Response::from_error(&e),
         }
     }
 
@@ -118,108 +554,223 @@ impl RequestHandler {
         lines: usize,
         stdout: bool,
         stderr: bool,
+        since_secs: Option<u64>,
     ) -> Response {
-        match self.supervisor.logs(&selector, lines, stdout, stderr).await {
+        let since = since_secs.map(Duration::from_secs);
+        match self
+            .supervisor
+            .logs(&selector, lines, stdout, stderr, since)
+            .await
+        {
             Ok(log_lines) => Response::LogLines { lines: log_lines },
-            Err(e) => Response::error(e.to_string()),
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle log page request
+    pub async fn log_page(
+        &self,
+        selector: Selector,
+        stream: LogStream,
+        lines: usize,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Response {
+        match self
+            .supervisor
+            .log_page(
+                &selector,
+                stream,
+                lines,
+                before.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        {
+            Ok(page) => Response::LogPage { page },
+            Err(e) => Response::from_error(&e),
         }
     }
 
     /// Handle save request
-    pub async fn save(&self) -> Response {
-        match self.supervisor.save().await {
-            Ok(count) => Response::Saved {
+    pub async fn save(&self, output: Option<PathBuf>) -> Response {
+        match self.supervisor.save(output.as_deref()).await {
+            Ok((count, path)) => Response::Saved {
                 count,
-                path: constants::saved_path().to_string_lossy().to_string(),
+                path: path.to_string_lossy().to_string(),
             },
-            Err(e) => Response::error(e.to_string()),
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Save the default process list if auto-save is on
+    pub async fn autosave(&self) {
+        if !self.autosave {
+            return;
+        }
+        match self.supervisor.save(None).await {
+            Ok((count, _)) => debug!("Auto-saved {} apps", count),
+            Err(e) => warn!("Auto-save failed: {}", e),
         }
     }
 
     /// Handle resurrect request
-    pub async fn resurrect(&mut self) -> Response {
-        match self.supervisor.resurrect().await {
-            Ok(count) => Response::Resurrected { count },
-            Err(e) => Response::error(e.to_string()),
+    pub async fn resurrect(
+        &self,
+        file: Option<PathBuf>,
+        all: bool,
+        progress: &ProgressSink,
+    ) -> Response {
+        match self
+            .supervisor
+            .resurrect_with_progress(file.as_deref(), all, progress)
+            .await
+        {
+            Ok(report) => Response::Resurrected {
+                count: report.started,
+                stopped: report.stopped,
+                warnings: report.warnings,
+            },
+            Err(e) => Response::from_error(&e),
         }
     }
 
     /// Handle reload request (graceful zero-downtime restart)
-    pub async fn reload(&mut self, selector: Selector) -> Response {
+    pub async fn reload(&self, selector: Selector, progress: &ProgressSink) -> Response {
         info!("Handling reload request for: {}", selector);
 
         match self.supervisor.resolve_selector(&selector).await {
             Ok(ids) => {
                 let mut count = 0;
-                for id in ids {
-                    match self.supervisor.reload(id).await {
-                        Ok(true) => count += 1,
-                        Ok(false) => {}
-                        Err(e) => error!("Error reloading {}: {}", id, e),
-                    }
+                let total = ids.len();
+                for (index, id) in ids.into_iter().enumerate() {
+                    let name = match self.supervisor.get_spec(id).await {
+                        Ok(Some(spec)) => spec.qualified_name(),
+                        _ => id.to_string(),
+                    };
+                    let status = match self.supervisor.reload(id).await {
+                        Ok(true) => {
+                            count += 1;
+                            "ok".to_string()
+                        }
+                        Ok(false) => "skipped".to_string(),
+                        Err(e) => {
+                            error!("Error reloading {}: {}", id, e);
+                            format!("failed: {}", e)
+                        }
+                    };
+                    progress.send(&format!("[{}/{}] {} {}", index + 1, total, name, status));
+                    progress.step(index + 1, total);
                 }
                 Response::Reloaded { count }
             }
-            Err(e) => Response::error(e.to_string()),
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle apply request (declarative reconcile)
+    pub async fn apply(&self, specs: Vec<AppSpec>, prune: bool, dry_run: bool) -> Response {
+        info!(
+            "Handling apply request for {} apps (prune: {}, dry run: {})",
+            specs.len(),
+            prune,
+            dry_run
+        );
+
+        match self.supervisor.apply(specs, prune, dry_run).await {
+            Ok(report) => Response::Applied { report },
+            Err(e) => Response::from_error(&e),
         }
     }
 
     /// Handle flush request (truncate log files)
-    pub async fn flush(&self, selector: Selector) -> Response {
+    pub async fn flush(
+        &self,
+        selector: Selector,
+        older_than_secs: Option<u64>,
+        keep_bytes: Option<u64>,
+    ) -> Response {
         info!("Handling flush request for: {}", selector);
 
+        let older_than = older_than_secs.map(Duration::from_secs);
         match self.supervisor.resolve_selector(&selector).await {
             Ok(ids) => {
                 let mut count = 0;
+                let mut freed_bytes = 0;
                 for id in ids {
-                    match self.flush_logs_for_app(id).await {
-                        Ok(true) => count += 1,
-                        Ok(false) => {}
+                    match self.flush_logs_for_app(id, older_than, keep_bytes).await {
+                        Ok(Some(freed)) => {
+                            count += 1;
+                            freed_bytes += freed;
+                        }
+                        Ok(None) => {}
                         Err(e) => error!("Error flushing logs for {}: {}", id, e),
                     }
                 }
-                Response::Flushed { count }
+                Response::Flushed { count, freed_bytes }
             }
-            Err(e) => Response::error(e.to_string()),
+            Err(e) => Response::from_error(&e),
         }
     }
 
-    /// Flush logs for a single app
-    async fn flush_logs_for_app(&self, id: u32) -> Result<bool> {
-        // Get app info to get the name
-        match self.supervisor.show(&Selector::ById(id)).await? {
-            Some(app_info) => {
-                let name = &app_info.spec.name;
+    /// Flush logs for a single app, returning the bytes freed (None if the app is gone)
+    ///
+    /// Without options the active files are truncated. With `older_than` and/or
+    /// `keep_bytes` the active and rotated files are pruned instead.
+    async fn flush_logs_for_app(
+        &self,
+        id: u32,
+        older_than: Option<Duration>,
+        keep_bytes: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let Some(app_info) = self.supervisor.show(&Selector::ById(id)).await? else {
+            return Ok(None);
+        };
+        let name = &app_info.spec.log_name();
+        let mut paths = vec![
+            app_info.spec.stdout_log_path(),
+            app_info.spec.stderr_log_path(),
+        ];
+        paths.dedup();
 
-                // Truncate stdout log
-                let stdout = stdout_path(name);
-                if stdout.exists() {
-                    if let Err(e) = OpenOptions::new()
-                        .write(true)
-                        .truncate(true)
-                        .open(&stdout)
-                    {
-                        warn!("Failed to truncate stdout log for {}: {}", name, e);
-                    }
+        let mut freed = 0;
+        if older_than.is_none() && keep_bytes.is_none() {
+            for path in paths {
+                // Only regular files: truncating /dev/null or a FIFO makes no sense
+                let Ok(metadata) = std::fs::metadata(&path) else {
+                    continue;
+                };
+                if !metadata.is_file() {
+                    continue;
                 }
-
-                // Truncate stderr log
-                let stderr = stderr_path(name);
-                if stderr.exists() {
-                    if let Err(e) = OpenOptions::new()
-                        .write(true)
-                        .truncate(true)
-                        .open(&stderr)
-                    {
-                        warn!("Failed to truncate stderr log for {}: {}", name, e);
-                    }
+                match OpenOptions::new().write(true).truncate(true).open(&path) {
+                    Ok(_) => freed += metadata.len(),
+                    Err(e) => warn!(
+                        "Failed to truncate log {} for {}: {}",
+                        path.display(),
+                        name,
+                        e
+                    ),
                 }
-
-                info!("Flushed logs for {}", name);
-                Ok(true)
             }
-            None => Ok(false),
+        } else {
+            let mut files: Vec<_> = paths
+                .iter()
+                .flat_map(|path| oxidepm_logs::log_files(path))
+                .collect();
+            if let Some(age) = older_than {
+                let cutoff = SystemTime::now()
+                    .checked_sub(age)
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                freed += oxidepm_logs::remove_older_than(&mut files, cutoff);
+            }
+            if let Some(max_bytes) = keep_bytes {
+                freed += oxidepm_logs::trim_to_size(&mut files, max_bytes);
+            }
         }
+
+        info!("Flushed logs for {} ({} bytes freed)", name, freed);
+        Ok(Some(freed))
     }
 
     /// Handle describe request (show what command would run)
@@ -227,19 +778,468 @@ impl RequestHandler {
         info!("Handling describe request for: {}", selector);
 
         match self.supervisor.show(&selector).await {
-            Ok(Some(app_info)) => {
-                let spec = app_info.spec;
-                Response::Described {
-                    name: spec.name,
-                    command: spec.command,
-                    args: spec.args,
-                    cwd: spec.cwd.to_string_lossy().to_string(),
+            Ok(Some(app_info)) => described(app_info.spec, app_info.state.build),
+            Ok(None) => Response::from_error(&Error::AppNotFound(selector.to_string())),
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle describe request for an app that is not registered
+    pub fn describe_spec(&self, spec: AppSpec) -> Response {
+        info!("Handling describe request for new app: {}", spec.name);
+        described(spec, None)
+    }
+
+    /// Handle env request
+    pub async fn env(&self, selector: Selector) -> Response {
+        info!("Handling env request for: {}", selector);
+
+        match self.single_spec(&selector).await {
+            Ok(spec) => {
+                // Spawned processes inherit the daemon's environment
+                let inherited = std::env::vars()
+                    .filter(|(key, _)| !spec.env.contains_key(key))
+                    .collect();
+                Response::Env {
+                    name: spec.qualified_name(),
                     env: spec.env,
-                    mode: spec.mode.to_string(),
+                    inherited,
                 }
             }
-            Ok(None) => Response::error("App not found"),
-            Err(e) => Response::error(e.to_string()),
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle exec request
+    pub async fn exec(
+        &self,
+        selector: Selector,
+        command: Vec<String>,
+        output: &ProgressSink,
+    ) -> Response {
+        info!("Handling exec request for {}: {:?}", selector, command);
+
+        let result = async {
+            let spec = self.single_spec(&selector).await?;
+            self.supervisor.exec(&spec, &command, output).await
+        }
+        .await;
+
+        match result {
+            Ok(exit_code) => Response::Executed { exit_code },
+            Err(e) => Response::from_error(&e),
         }
     }
+
+    /// Spec of the one app `selector` picks
+    async fn single_spec(&self, selector: &Selector) -> Result<AppSpec> {
+        let ids = self.supervisor.resolve_selector(selector).await?;
+        let [id] = ids[..] else {
+            return Err(Error::InvalidSelector(format!(
+                "'{}' matches {} apps; select a single app",
+                selector,
+                ids.len()
+            )));
+        };
+        self.supervisor
+            .get_spec(id)
+            .await?
+            .ok_or_else(|| Error::AppNotFound(selector.to_string()))
+    }
+
+    /// Handle health history request
+    pub async fn health(&self, selector: Selector) -> Response {
+        info!("Handling health request for: {}", selector);
+
+        match self.supervisor.resolve_selector(&selector).await {
+            Ok(ids) => Response::Health {
+                apps: self.supervisor.health(&ids),
+            },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle events request
+    pub async fn events(&self, selector: Selector, limit: usize) -> Response {
+        info!("Handling events request for: {}", selector);
+
+        let result = async {
+            let ids = self.supervisor.resolve_selector(&selector).await?;
+            self.supervisor.events(&ids, limit).await
+        }
+        .await;
+
+        match result {
+            Ok(events) => Response::Events { events },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle audit log request
+    pub async fn audit(&self, limit: usize) -> Response {
+        match self.audit.recent(limit).await {
+            Ok(entries) => Response::Audit { entries },
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    /// Handle operation status request
+    pub fn operation_status(&self, id: u64) -> Response {
+        self.operations.status(id)
+    }
+
+    /// Handle operations request
+    pub fn list_operations(&self) -> Response {
+        Response::Operations {
+            operations: self.operations.list(),
+        }
+    }
+
+    /// Handle set env request
+    pub async fn set_env(
+        &self,
+        selector: Selector,
+        set: HashMap<String, String>,
+        unset: Vec<String>,
+        restart: bool,
+    ) -> Response {
+        info!("Handling set env request for: {}", selector);
+
+        let ids = match self.supervisor.resolve_selector(&selector).await {
+            Ok(ids) => ids,
+            Err(e) => return Response::from_error(&e),
+        };
+
+        let mut count = 0;
+        let mut restarted = 0;
+        for id in ids {
+            match self.supervisor.set_env(id, &set, &unset).await {
+                Ok(running) => {
+                    count += 1;
+                    if restart && running {
+                        match self.supervisor.restart(id, false).await {
+                            Ok(true) => restarted += 1,
+                            Ok(false) => {}
+                            Err(e) => error!("Error restarting {}: {}", id, e),
+                        }
+                    }
+                }
+                Err(e) => return Response::from_error(&e),
+            }
+        }
+        Response::EnvUpdated { count, restarted }
+    }
+
+    /// Re-read notify.toml and daemon.toml and apply them without a restart,
+    /// logging each change. A file that fails to load leaves every setting
+    /// as it was.
+    pub fn reload_daemon_config(&self) -> Response {
+        self.reload_daemon_config_from(&notify_config_path(), &constants::daemon_config_path())
+    }
+
+    /// Re-read notify.toml and daemon.toml from the given paths; either
+    /// failing to load leaves every setting as it was
+    fn reload_daemon_config_from(&self, notify_path: &PathBuf, daemon_path: &Path) -> Response {
+        let notify = match NotifyConfig::load_from(notify_path) {
+            Ok(config) => config,
+            Err(e) => {
+                let message = format!("Failed to load notify config: {}", e);
+                warn!("{}; keeping the current settings", message);
+                return Response::from_error(&Error::config(message));
+            }
+        };
+        let config = match DaemonConfig::load_from(daemon_path) {
+            Ok(config) => config,
+            Err(e) => {
+                let message = format!("Failed to load {}: {}", daemon_path.display(), e);
+                warn!("{}; keeping the current settings", message);
+                return Response::from_error(&Error::config(message));
+            }
+        };
+
+        let mut changes = notify_changes(self.supervisor.notifier().config(), &notify);
+        {
+            let mut current = self.config.write();
+            let describe =
+                |bytes: Option<u64>| bytes.map_or("none".to_string(), |b| format!("{} bytes", b));
+            if current.log_budget() != config.log_budget() {
+                changes.push(format!(
+                    "log budget: {} -> {}",
+                    describe(current.log_budget()),
+                    describe(config.log_budget())
+                ));
+            }
+            let describe = |budget: Option<Duration>| {
+                budget.map_or("none".to_string(), |d| format!("{}s", d.as_secs()))
+            };
+            if current.request_budget() != config.request_budget() {
+                changes.push(format!(
+                    "request budget: {} -> {}",
+                    describe(current.request_budget()),
+                    describe(config.request_budget())
+                ));
+            }
+            self.supervisor.set_log_budget(config.log_budget());
+            *current = config;
+        }
+        self.supervisor.set_notify_config(notify);
+
+        if changes.is_empty() {
+            info!("Reloaded daemon config, nothing changed");
+        }
+        for change in &changes {
+            info!("Reloaded daemon config: {}", change);
+        }
+        Response::DaemonConfigReloaded { changes }
+    }
+
+    /// Run the preflight checks in `dir` on this host, so starts that come
+    /// in over the API are checked like CLI starts
+    pub async fn check(&self, dir: PathBuf, fix: bool) -> Response {
+        info!("Handling check request for: {}", dir.display());
+        if !dir.is_dir() {
+            return Response::from_error(&Error::FileNotFound(dir));
+        }
+
+        // A fix may run npm install
+        match tokio::task::spawn_blocking(move || run_preflight_checks(&dir, fix)).await {
+            Ok(summary) => Response::Checked { summary },
+            Err(e) => Response::error(format!("Preflight checks failed to run: {}", e)),
+        }
+    }
+
+    /// Rename the one app `selector` matches
+    pub async fn rename(&self, selector: Selector, name: String) -> Response {
+        info!("Handling rename request for: {} -> {}", selector, name);
+
+        let ids = match self.supervisor.resolve_selector(&selector).await {
+            Ok(ids) => ids,
+            Err(e) => return Response::from_error(&e),
+        };
+        let [id] = ids[..] else {
+            return Response::from_error(&Error::InvalidSelector(format!(
+                "'{}' matches {} apps; rename one at a time",
+                selector,
+                ids.len()
+            )));
+        };
+        let from = match self.supervisor.get_spec(id).await {
+            Ok(Some(spec)) => spec.qualified_name(),
+            Ok(None) => return Response::from_error(&Error::AppNotFound(selector.to_string())),
+            Err(e) => return Response::from_error(&e),
+        };
+
+        match self.supervisor.rename(id, &name).await {
+            Ok(restarted) => {
+                let to = match self.supervisor.get_spec(id).await {
+                    Ok(Some(spec)) => spec.qualified_name(),
+                    _ => name,
+                };
+                Response::Renamed {
+                    id,
+                    from,
+                    to,
+                    restarted,
+                }
+            }
+            Err(e) => Response::from_error(&e),
+        }
+    }
+
+    pub async fn update_spec(
+        &self,
+        selector: Selector,
+        update: SpecUpdate,
+        restart: bool,
+        reload: bool,
+    ) -> Response {
+        info!("Handling update spec request for: {}", selector);
+
+        if update.is_empty() {
+            return Response::from_error(&Error::config("Nothing to update"));
+        }
+        let ids = match self.supervisor.resolve_selector(&selector).await {
+            Ok(ids) => ids,
+            Err(e) => return Response::from_error(&e),
+        };
+
+        // Every app gets a result: an update failing halfway leaves the
+        // apps before it changed, which the caller must be able to see
+        let mut count = 0;
+        let mut restarted = 0;
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            let name = match self.supervisor.get_spec(id).await {
+                Ok(Some(spec)) => spec.qualified_name(),
+                _ => id.to_string(),
+            };
+            let running = match self.supervisor.update_spec(id, &update).await {
+                Ok(running) => running,
+                Err(e) => {
+                    error!("Error updating {}: {}", name, e);
+                    results.push(AppOpResult {
+                        id,
+                        name,
+                        outcome: AppOpOutcome::Failed {
+                            error: e.to_string(),
+                        },
+                    });
+                    continue;
+                }
+            };
+            count += 1;
+
+            let mut outcome = AppOpOutcome::Done;
+            if running && (restart || reload) {
+                let (verb, result) = if reload {
+                    ("reload", self.supervisor.reload(id).await)
+                } else {
+                    ("restart", self.supervisor.restart(id, false).await)
+                };
+                match result {
+                    Ok(true) => restarted += 1,
+                    Ok(false) => {}
+                    Err(e) => {
+                        error!("Error applying the update to {}: {}", name, e);
+                        outcome = AppOpOutcome::Failed {
+                            error: format!("updated, but the {} failed: {}", verb, e),
+                        };
+                    }
+                }
+            }
+            results.push(AppOpResult { id, name, outcome });
+        }
+        Response::SpecUpdated {
+            count,
+            restarted,
+            results,
+        }
+    }
+}
+
+/// What differs between two notify configs, section by section. The command
+/// bot and report schedule are set up once, so changes to them wait for a
+/// daemon restart.
+fn notify_changes(old: &NotifyConfig, new: &NotifyConfig) -> Vec<String> {
+    let mut changes = Vec::new();
+    if old.telegram != new.telegram {
+        let interactive =
+            |config: &NotifyConfig| config.telegram.as_ref().is_some_and(|t| t.interactive);
+        changes.push(if interactive(old) || interactive(new) {
+            "notify: telegram settings (the command bot picks them up after a daemon restart)"
+                .to_string()
+        } else {
+            "notify: telegram settings".to_string()
+        });
+    }
+    if old.events != new.events {
+        changes.push(format!(
+            "notify: events {} -> {}",
+            event_list(&old.events),
+            event_list(&new.events)
+        ));
+    }
+    if old.quiet_hours != new.quiet_hours {
+        changes.push("notify: quiet hours".to_string());
+    }
+    if old.reports != new.reports {
+        changes.push("notify: reports (take effect after a daemon restart)".to_string());
+    }
+    changes
+}
+
+/// Events a notify config sends, for the log
+fn event_list(events: &[String]) -> String {
+    if events.is_empty() {
+        "all".to_string()
+    } else {
+        events.join(",")
+    }
+}
+
+/// Describe reply for `spec`: the argv its runner executes and the environment
+/// the process starts with
+fn described(spec: AppSpec, build: Option<BuildInfo>) -> Response {
+    let runner = oxidepm_runtime::get_runner(spec.mode);
+    let argv = runner.argv(&spec);
+    let prepare = runner.prepare_command(&spec);
+
+    // Spawned processes inherit the daemon's environment
+    let (env, env_sources): (HashMap<_, _>, BTreeMap<_, _>) = spec
+        .resolved_env(std::env::vars())
+        .into_iter()
+        .map(|(key, (value, source))| ((key.clone(), value), (key, source)))
+        .unzip();
+    let cwd = spec.cwd.canonicalize().unwrap_or_else(|_| spec.cwd.clone());
+
+    Response::Described {
+        name: spec.name,
+        command: spec.command,
+        args: spec.args,
+        cwd: cwd.to_string_lossy().to_string(),
+        env,
+        mode: spec.mode.to_string(),
+        build,
+        argv,
+        prepare,
+        env_sources,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_db::Database;
+
+    #[tokio::test]
+    async fn test_reload_daemon_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).await.unwrap();
+        let handler = RequestHandler::new(Supervisor::new(db).await.unwrap());
+        let notify_path = dir.path().join("notify.toml");
+        let daemon_path = dir.path().join("daemon.toml");
+        *handler.config.write() = DaemonConfig::default();
+
+        std::fs::write(&notify_path, "events = [\"crash\"]\n").unwrap();
+        std::fs::write(&daemon_path, "request_budget_secs = 90\n").unwrap();
+        let Response::DaemonConfigReloaded { changes } =
+            handler.reload_daemon_config_from(&notify_path, &daemon_path)
+        else {
+            panic!("reload failed");
+        };
+        assert_eq!(
+            changes,
+            vec!["notify: events all -> crash", "request budget: 60s -> 90s"]
+        );
+
+        // Nothing new the second time
+        let Response::DaemonConfigReloaded { changes } =
+            handler.reload_daemon_config_from(&notify_path, &daemon_path)
+        else {
+            panic!("reload failed");
+        };
+        assert!(changes.is_empty(), "{:?}", changes);
+
+        // A file that fails to parse keeps the settings in place
+        std::fs::write(&daemon_path, "request_budget_secs = \"soon\"\n").unwrap();
+        let response = handler.reload_daemon_config_from(&notify_path, &daemon_path);
+        assert!(matches!(response, Response::Error { .. }));
+        assert_eq!(handler.config.read().request_budget_secs, Some(90));
+        assert_eq!(handler.supervisor.notifier().config().events, vec!["crash"]);
+    }
+
+    #[test]
+    fn test_notify_changes() {
+        let old = NotifyConfig::default();
+        assert!(notify_changes(&old, &old).is_empty());
+
+        let new = NotifyConfig {
+            events: vec!["crash".to_string(), "restart".to_string()],
+            ..NotifyConfig::default()
+        };
+        assert_eq!(
+            notify_changes(&old, &new),
+            vec!["notify: events all -> crash,restart"]
+        );
+    }
 }
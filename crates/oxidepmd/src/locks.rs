@@ -0,0 +1,108 @@
+//! Locks that keep state-changing requests from stepping on each other
+//!
+//! A request for particular apps locks just those apps, so a long build or
+//! health wait of one app does not hold up requests for the others. Requests
+//! for the whole daemon (apply, resurrect, kill, a selector of `all`) wait
+//! until no app is locked and then run alone.
+
+use parking_lot::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::{
+    Mutex as AppLock, OwnedMutexGuard, OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock,
+};
+
+/// What a mutation has to have to itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockScope {
+    /// These apps, by qualified name
+    Apps(BTreeSet<String>),
+    /// Every app
+    Daemon,
+}
+
+/// Per-app locks, under a daemon-wide one
+#[derive(Debug, Default)]
+pub struct MutationLocks {
+    daemon: Arc<RwLock<()>>,
+    apps: Mutex<HashMap<String, Arc<AppLock<()>>>>,
+}
+
+/// Held while a mutation runs; dropping it lets the next one in
+#[derive(Debug)]
+pub enum MutationGuard {
+    Apps {
+        _daemon: OwnedRwLockReadGuard<()>,
+        _apps: Vec<OwnedMutexGuard<()>>,
+    },
+    Daemon {
+        _daemon: OwnedRwLockWriteGuard<()>,
+    },
+}
+
+impl MutationLocks {
+    /// Wait until nothing else holds any part of `scope`
+    pub async fn lock(&self, scope: LockScope) -> MutationGuard {
+        let names = match scope {
+            LockScope::Daemon => {
+                return MutationGuard::Daemon {
+                    _daemon: Arc::clone(&self.daemon).write_owned().await,
+                }
+            }
+            LockScope::Apps(names) => names,
+        };
+
+        let daemon = Arc::clone(&self.daemon).read_owned().await;
+        let locks: Vec<Arc<AppLock<()>>> = {
+            let mut apps = self.apps.lock();
+            // Forget locks nobody holds or waits for
+            apps.retain(|_, lock| Arc::strong_count(lock) > 1);
+            names
+                .into_iter()
+                .map(|name| Arc::clone(apps.entry(name).or_default()))
+                .collect()
+        };
+        // Taken in name order, so requests over overlapping apps cannot deadlock
+        let mut guards = Vec::with_capacity(locks.len());
+        for lock in locks {
+            guards.push(lock.lock_owned().await);
+        }
+        MutationGuard::Apps {
+            _daemon: daemon,
+            _apps: guards,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn apps(names: &[&str]) -> LockScope {
+        LockScope::Apps(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    async fn blocks(locks: &MutationLocks, scope: LockScope) -> bool {
+        tokio::time::timeout(Duration::from_millis(50), locks.lock(scope))
+            .await
+            .is_err()
+    }
+
+    #[tokio::test]
+    async fn test_app_locks() {
+        let locks = MutationLocks::default();
+
+        let api = locks.lock(apps(&["api"])).await;
+        assert!(!blocks(&locks, apps(&["web"])).await);
+        assert!(blocks(&locks, apps(&["web", "api"])).await);
+        assert!(blocks(&locks, LockScope::Daemon).await);
+        drop(api);
+
+        let daemon = locks.lock(LockScope::Daemon).await;
+        assert!(blocks(&locks, apps(&["web"])).await);
+        drop(daemon);
+        assert!(!blocks(&locks, apps(&["web", "api"])).await);
+        assert!(locks.apps.lock().len() <= 2);
+    }
+}
@@ -2,12 +2,24 @@
 
 use anyhow::Result;
 use oxidepm_core::constants;
+use std::path::PathBuf;
 use tracing::{error, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod access;
+mod audit;
+mod bot;
 mod daemon;
 mod handlers;
+mod locks;
+mod operations;
+mod ports;
+mod process_table;
+mod reports;
+mod snapshot;
 mod supervisor;
+mod triggers;
+mod watchdog;
 
 use daemon::Daemon;
 
@@ -17,16 +29,28 @@ async fn main() -> Result<()> {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "oxidepmd=info,oxidepm_db=info".into()),
+                .unwrap_or_else(|_| "oxidepmd=info,oxidepm_db=info,oxidepm_ipc=warn".into()),
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // Set before anything reads the home directory; children inherit them, so
+    // an `oxidepm` they run finds this daemon
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(home) = home_from_args(&args)? {
+        std::env::set_var(constants::HOME_ENV, home);
+    }
+    if args.iter().any(|arg| arg == "--ephemeral") {
+        std::env::set_var(constants::EPHEMERAL_ENV, "1");
+    }
+    let ephemeral = constants::ephemeral();
+
     info!("OxidePM Daemon starting...");
 
     // Ensure home directory exists
     let home = constants::oxidepm_home();
-    if !home.exists() {
+    let created_home = !home.exists();
+    if created_home {
         std::fs::create_dir_all(&home)?;
         info!("Created OxidePM home directory: {}", home.display());
     }
@@ -70,6 +94,36 @@ async fn main() -> Result<()> {
         }
     }
 
+    if ephemeral {
+        // Nothing outlives an ephemeral daemon: not its apps, not its files
+        daemon.stop_all().await;
+        if created_home {
+            if let Err(e) = std::fs::remove_dir_all(&home) {
+                error!("Failed to remove {}: {}", home.display(), e);
+            }
+        }
+    }
+
     info!("Daemon shutdown complete");
     Ok(())
 }
+
+/// Home directory from `--home <dir>` or `--profile <name>`
+fn home_from_args(args: &[String]) -> Result<Option<PathBuf>> {
+    let value = |flag: &str| {
+        let prefix = format!("{}=", flag);
+        args.iter()
+            .enumerate()
+            .find_map(|(i, arg)| match arg.strip_prefix(&prefix) {
+                Some(value) => Some(value.to_string()),
+                None if arg == flag => args.get(i + 1).cloned(),
+                None => None,
+            })
+    };
+    match (value("--home"), value("--profile")) {
+        (Some(_), Some(_)) => anyhow::bail!("--home and --profile cannot be used together"),
+        (Some(home), None) => Ok(Some(std::env::current_dir()?.join(home))),
+        (None, Some(profile)) => Ok(Some(constants::profile_home(&profile)?)),
+        (None, None) => Ok(None),
+    }
+}
@@ -0,0 +1,276 @@
+//! Long requests, tracked as operations
+//!
+//! A request that can run for minutes (a start whose build is slow, a
+//! rollout, a resurrect) is tracked by id with its progress and latest
+//! output, and gets a time budget. When the budget runs out its client is
+//! answered with the operation id instead, and the request carries on;
+//! `OperationStatus` polls it and hands back its response once it is done.
+
+use chrono::Utc;
+use oxidepm_core::{constants, ErrorCode, OperationInfo, OperationState};
+use oxidepm_ipc::Response;
+use oxidepm_runtime::ProgressSink;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinError;
+use tracing::{error, info};
+
+/// Running and recently finished operations, with the response of each one
+/// that has finished
+#[derive(Debug, Default)]
+pub struct Operations {
+    last_id: AtomicU64,
+    operations: Mutex<BTreeMap<u64, (OperationInfo, Option<Response>)>>,
+}
+
+impl Operations {
+    /// Track a new operation, returning its id
+    pub fn begin(&self, action: String, target: Option<String>) -> u64 {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let operation = OperationInfo {
+            id,
+            action,
+            target,
+            started_at: Utc::now(),
+            finished_at: None,
+            state: OperationState::Running,
+            percent: None,
+            log: Vec::new(),
+            log_lines: 0,
+        };
+        let mut operations = self.operations.lock();
+        prune(&mut operations);
+        operations.insert(id, (operation, None));
+        id
+    }
+
+    /// Sink that records output and steps in operation `id`, passing lines
+    /// on to `client`
+    pub fn sink(self: &Arc<Self>, id: u64, client: ProgressSink) -> ProgressSink {
+        let (lines, steps) = (Arc::clone(self), Arc::clone(self));
+        ProgressSink::new(move |line| {
+            lines.update(id, |operation| {
+                if operation.log.len() == constants::OPERATION_LOG_LINES {
+                    operation.log.remove(0);
+                }
+                operation.log.push(line.to_string());
+                operation.log_lines += 1;
+            });
+            client.send(line);
+        })
+        .with_steps(move |done, total| {
+            if let Some(percent) = (done.min(total) * 100).checked_div(total) {
+                steps.update(id, |operation| operation.percent = Some(percent as u8));
+            }
+        })
+    }
+
+    /// Run operation `id` and wait up to `budget` for its response. Past the
+    /// budget it carries on and `OperationPending` is returned.
+    pub async fn run<F>(self: &Arc<Self>, id: u64, budget: Duration, work: F) -> Response
+    where
+        F: Future<Output = Response> + Send + 'static,
+    {
+        let mut task = tokio::spawn(work);
+        if let Ok(joined) = tokio::time::timeout(budget, &mut task).await {
+            let response = joined_response(joined);
+            self.finish(id, response.clone());
+            return response;
+        }
+
+        let Some(operation) = self.get(id) else {
+            return joined_response(task.await);
+        };
+        info!(
+            "{} outlasted its {}s budget, continuing as operation {}",
+            operation.action,
+            budget.as_secs(),
+            id
+        );
+        let operations = Arc::clone(self);
+        tokio::spawn(async move {
+            operations.finish(id, joined_response(task.await));
+        });
+        Response::OperationPending { operation }
+    }
+
+    /// Record the response of a finished operation
+    fn finish(&self, id: u64, response: Response) {
+        let mut operations = self.operations.lock();
+        if let Some((operation, result)) = operations.get_mut(&id) {
+            operation.finished_at = Some(Utc::now());
+            operation.state = match &response {
+                Response::Error { message, .. } => OperationState::Failed {
+                    error: message.clone(),
+                },
+                _ => {
+                    if operation.percent.is_some() {
+                        operation.percent = Some(100);
+                    }
+                    OperationState::Succeeded
+                }
+            };
+            *result = Some(response);
+        }
+    }
+
+    fn get(&self, id: u64) -> Option<OperationInfo> {
+        let operations = self.operations.lock();
+        operations.get(&id).map(|(operation, _)| operation.clone())
+    }
+
+    fn update(&self, id: u64, change: impl FnOnce(&mut OperationInfo)) {
+        if let Some((operation, _)) = self.operations.lock().get_mut(&id) {
+            change(operation);
+        }
+    }
+
+    /// Where an operation is, with its response once it has finished
+    pub fn status(&self, id: u64) -> Response {
+        match self.operations.lock().get(&id) {
+            Some((operation, result)) => Response::Operation {
+                operation: operation.clone(),
+                result: result.clone().map(Box::new),
+            },
+            None => Response::Error {
+                message: format!("Operation {} not found", id),
+                code: Some(ErrorCode::InvalidInput.to_string()),
+                hint: Some(
+                    "Finished operations are kept for an hour and do not survive a daemon restart"
+                        .to_string(),
+                ),
+            },
+        }
+    }
+
+    /// Running and recently finished operations, oldest first
+    pub fn list(&self) -> Vec<OperationInfo> {
+        let mut operations = self.operations.lock();
+        prune(&mut operations);
+        operations
+            .values()
+            .map(|(operation, _)| operation.clone())
+            .collect()
+    }
+}
+
+/// Forget operations that finished long ago, and the oldest finished ones
+/// past `MAX_FINISHED_OPERATIONS`
+fn prune(operations: &mut BTreeMap<u64, (OperationInfo, Option<Response>)>) {
+    let cutoff = Utc::now() - chrono::Duration::seconds(constants::OPERATION_RETENTION_SECS as i64);
+    operations.retain(|_, (operation, _)| operation.finished_at.map_or(true, |at| at > cutoff));
+
+    let finished: Vec<u64> = operations
+        .iter()
+        .filter(|(_, (operation, _))| operation.is_finished())
+        .map(|(id, _)| *id)
+        .collect();
+    let excess = finished
+        .len()
+        .saturating_sub(constants::MAX_FINISHED_OPERATIONS);
+    for id in &finished[..excess] {
+        operations.remove(id);
+    }
+}
+
+/// Response of a finished request task, or an error if it panicked
+fn joined_response(joined: Result<Response, JoinError>) -> Response {
+    joined.unwrap_or_else(|e| {
+        error!("Request task failed: {}", e);
+        Response::error(format!("Request failed: {}", e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_operation_outlasts_budget() {
+        let operations = Arc::new(Operations::default());
+
+        // Done within the budget: answered directly
+        let id = operations.begin("start".into(), None);
+        let response = operations
+            .run(id, Duration::from_secs(5), async { Response::ok("quick") })
+            .await;
+        assert!(matches!(response, Response::Ok { .. }));
+        assert!(operations.get(id).unwrap().is_finished());
+
+        let (finish_tx, finish_rx) = tokio::sync::oneshot::channel::<()>();
+        let id = operations.begin("start".into(), Some("api".into()));
+        let response = operations
+            .run(id, Duration::from_millis(10), async move {
+                let _ = finish_rx.await;
+                Response::ok("slow")
+            })
+            .await;
+        let Response::OperationPending { operation } = response else {
+            panic!("Expected a pending operation");
+        };
+        assert_eq!(operation.target.as_deref(), Some("api"));
+        assert!(matches!(
+            operations.status(id),
+            Response::Operation { result: None, .. }
+        ));
+
+        finish_tx.send(()).unwrap();
+        for _ in 0..100 {
+            if let Response::Operation {
+                operation,
+                result: Some(result),
+            } = operations.status(id)
+            {
+                assert_eq!(operation.state, OperationState::Succeeded);
+                assert!(matches!(*result, Response::Ok { message } if message == "slow"));
+                assert_eq!(operations.list().len(), 2);
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("The operation never finished");
+    }
+
+    #[test]
+    fn test_operation_progress() {
+        let operations = Arc::new(Operations::default());
+        let id = operations.begin("resurrect".into(), None);
+        let sink = operations.sink(id, ProgressSink::none());
+        for done in 1..=constants::OPERATION_LOG_LINES + 5 {
+            sink.send(&format!("line {}", done));
+        }
+        sink.step(1, 4);
+
+        let operation = operations.get(id).unwrap();
+        assert_eq!(operation.percent, Some(25));
+        assert_eq!(operation.log.len(), constants::OPERATION_LOG_LINES);
+        assert_eq!(operation.log_lines, constants::OPERATION_LOG_LINES + 5);
+        assert_eq!(operation.log.last().map(String::as_str), Some("line 25"));
+
+        operations.finish(id, Response::ok("done"));
+        assert_eq!(operations.get(id).unwrap().percent, Some(100));
+    }
+
+    #[test]
+    fn test_prunes_finished_operations() {
+        let operations = Operations::default();
+        let running = operations.begin("start".into(), None);
+        for _ in 0..constants::MAX_FINISHED_OPERATIONS + 10 {
+            let id = operations.begin("start".into(), None);
+            operations.finish(id, Response::ok("done"));
+        }
+        let listed = operations.list();
+        assert_eq!(listed.len(), constants::MAX_FINISHED_OPERATIONS + 1);
+        assert_eq!(listed[0].id, running);
+    }
+
+    #[test]
+    fn test_unknown_operation() {
+        let operations = Operations::default();
+        assert!(operations.status(42).is_error());
+    }
+}
@@ -0,0 +1,162 @@
+//! Port registry - which managed app holds which port
+//!
+//! Apps claim their port right before launch, so two apps started at the
+//! same time cannot both pass the check. A claim only counts while its owner
+//! is active; the supervisor decides what active means.
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// An app's hold on a port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortClaim {
+    pub app_id: u32,
+    pub name: String,
+    /// Set while the owner is being reloaded so its replacement can take over
+    handover: bool,
+}
+
+/// Ports claimed by managed apps
+#[derive(Debug, Default)]
+pub struct PortRegistry {
+    claims: BTreeMap<u16, PortClaim>,
+}
+
+impl PortRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claim `port` for an app, returning the current holder if another
+    /// active app has it. Claims of inactive apps are replaced.
+    pub fn claim(
+        &mut self,
+        port: u16,
+        app_id: u32,
+        name: &str,
+        is_active: impl Fn(u32) -> bool,
+    ) -> Result<(), PortClaim> {
+        if let Some(holder) = self.claims.get(&port) {
+            if holder.app_id != app_id && !holder.handover && is_active(holder.app_id) {
+                return Err(holder.clone());
+            }
+        }
+        self.claims.insert(
+            port,
+            PortClaim {
+                app_id,
+                name: name.to_string(),
+                handover: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// Let whichever app claims next take over this app's ports (graceful reload)
+    pub fn hand_over(&mut self, app_id: u32) {
+        for claim in self.claims.values_mut().filter(|c| c.app_id == app_id) {
+            claim.handover = true;
+        }
+    }
+
+    /// Drop every claim held by an app
+    pub fn release(&mut self, app_id: u32) {
+        self.claims.retain(|_, claim| claim.app_id != app_id);
+    }
+
+    /// Active holder of a port, if any
+    pub fn holder(&self, port: u16, is_active: impl Fn(u32) -> bool) -> Option<&PortClaim> {
+        self.claims
+            .get(&port)
+            .filter(|claim| is_active(claim.app_id))
+    }
+
+    /// Whether `claim` would succeed without taking the port from anyone
+    pub fn can_claim(&self, port: u16, app_id: u32, is_active: impl Fn(u32) -> bool) -> bool {
+        self.claims.get(&port).map_or(true, |holder| {
+            holder.app_id == app_id || holder.handover || !is_active(holder.app_id)
+        })
+    }
+
+    /// First port in `range` that no active app holds and nothing is bound to
+    pub fn next_free(
+        &self,
+        range: RangeInclusive<u16>,
+        is_active: impl Fn(u32) -> bool,
+        is_bindable: impl Fn(u16) -> bool,
+    ) -> Option<u16> {
+        range
+            .into_iter()
+            .find(|&port| self.holder(port, &is_active).is_none() && is_bindable(port))
+    }
+
+    /// All claims in port order
+    pub fn claims(&self) -> impl Iterator<Item = (u16, &PortClaim)> {
+        self.claims.iter().map(|(port, claim)| (*port, claim))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claim_and_conflict() {
+        let mut registry = PortRegistry::new();
+        let active = |_: u32| true;
+
+        registry.claim(3000, 1, "api", active).unwrap();
+        // Re-claiming your own port is fine (restarts)
+        registry.claim(3000, 1, "api", active).unwrap();
+
+        let holder = registry.claim(3000, 2, "web", active).unwrap_err();
+        assert_eq!(holder.app_id, 1);
+        assert_eq!(holder.name, "api");
+
+        // A stopped owner no longer blocks the port
+        registry.claim(3000, 2, "web", |id| id != 1).unwrap();
+        assert_eq!(registry.holder(3000, active).unwrap().app_id, 2);
+    }
+
+    #[test]
+    fn test_hand_over_and_release() {
+        let mut registry = PortRegistry::new();
+        let active = |_: u32| true;
+        registry.claim(3000, 1, "api", active).unwrap();
+
+        assert!(!registry.can_claim(3000, 5, active));
+        registry.hand_over(1);
+        assert!(registry.can_claim(3000, 5, active));
+        registry.claim(3000, 5, "api-reload", active).unwrap();
+        // The old instance's release must not drop the new claim
+        registry.release(1);
+        assert_eq!(registry.holder(3000, active).unwrap().app_id, 5);
+
+        registry.release(5);
+        assert!(registry.holder(3000, active).is_none());
+    }
+
+    #[test]
+    fn test_next_free() {
+        let mut registry = PortRegistry::new();
+        let active = |_: u32| true;
+        registry.claim(3000, 1, "api", active).unwrap();
+        registry.claim(3001, 2, "web", active).unwrap();
+
+        assert_eq!(
+            registry.next_free(3000..=u16::MAX, active, |_| true),
+            Some(3002)
+        );
+        assert_eq!(
+            registry.next_free(3000..=u16::MAX, active, |p| p != 3002),
+            Some(3003)
+        );
+        assert_eq!(
+            registry.next_free(3000..=u16::MAX, |id| id != 1, |_| true),
+            Some(3000)
+        );
+        assert_eq!(registry.next_free(3000..=3001, active, |_| true), None);
+        let ports: Vec<u16> = registry.claims().map(|(port, _)| port).collect();
+        assert_eq!(ports, vec![3000, 3001]);
+    }
+}
@@ -0,0 +1,197 @@
+//! The supervisor's table of supervised apps
+//!
+//! Every app has a lock of its own, and the index only says which apps are
+//! in the table, so work on one app never waits for another. After each
+//! change an app publishes a copy of its spec and state; status queries
+//! read those copies and never wait for an app's lock at all, however long
+//! an operation holds it.
+
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The parts of an entry that status queries read
+pub trait Publish {
+    type Snapshot: Clone;
+
+    fn snapshot(&self) -> Self::Snapshot;
+}
+
+/// Entries by app id, each behind a lock of its own
+pub struct ProcessTable<T: Publish> {
+    slots: RwLock<HashMap<u32, Arc<Slot<T>>>>,
+}
+
+struct Slot<T: Publish> {
+    /// `None` once removed, for callers that looked the slot up before
+    entry: RwLock<Option<T>>,
+    /// Copy of the entry as of its last change
+    published: Mutex<T::Snapshot>,
+}
+
+impl<T: Publish> Default for ProcessTable<T> {
+    fn default() -> Self {
+        Self {
+            slots: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Publish> ProcessTable<T> {
+    fn slot(&self, id: u32) -> Option<Arc<Slot<T>>> {
+        self.slots.read().get(&id).cloned()
+    }
+
+    fn slots(&self) -> Vec<(u32, Arc<Slot<T>>)> {
+        let mut slots: Vec<_> = self
+            .slots
+            .read()
+            .iter()
+            .map(|(id, slot)| (*id, Arc::clone(slot)))
+            .collect();
+        slots.sort_by_key(|(id, _)| *id);
+        slots
+    }
+
+    /// Add or replace the entry for `id`, returning the one it replaced
+    pub fn insert(&self, id: u32, entry: T) -> Option<T> {
+        let published = Mutex::new(entry.snapshot());
+        let slot = Arc::new(Slot {
+            entry: RwLock::new(Some(entry)),
+            published,
+        });
+        let previous = self.slots.write().insert(id, slot)?;
+        let entry = previous.entry.write().take();
+        entry
+    }
+
+    /// Take the entry for `id` out of the table
+    pub fn remove(&self, id: u32) -> Option<T> {
+        let slot = self.slots.write().remove(&id)?;
+        let entry = slot.entry.write().take();
+        entry
+    }
+
+    /// Read the entry for `id`
+    pub fn with<R>(&self, id: u32, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let slot = self.slot(id)?;
+        let entry = slot.entry.read();
+        entry.as_ref().map(f)
+    }
+
+    /// Change the entry for `id`, publishing the result
+    pub fn with_mut<R>(&self, id: u32, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let slot = self.slot(id)?;
+        let mut entry = slot.entry.write();
+        let entry = entry.as_mut()?;
+        let result = f(entry);
+        *slot.published.lock() = entry.snapshot();
+        Some(result)
+    }
+
+    /// Change every entry, one at a time, in id order
+    pub fn for_each_mut(&self, mut f: impl FnMut(u32, &mut T)) {
+        for (id, slot) in self.slots() {
+            let mut entry = slot.entry.write();
+            if let Some(entry) = entry.as_mut() {
+                f(id, entry);
+                *slot.published.lock() = entry.snapshot();
+            }
+        }
+    }
+
+    /// What the entry for `id` published last, without waiting for its lock
+    pub fn published(&self, id: u32) -> Option<T::Snapshot> {
+        let slot = self.slot(id)?;
+        let published = slot.published.lock().clone();
+        Some(published)
+    }
+
+    /// What every entry published last, without waiting for their locks
+    pub fn published_all(&self) -> HashMap<u32, T::Snapshot> {
+        self.slots()
+            .into_iter()
+            .map(|(id, slot)| (id, slot.published.lock().clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Counter(u32);
+
+    impl Publish for Counter {
+        type Snapshot = u32;
+
+        fn snapshot(&self) -> u32 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_entries() {
+        let table = ProcessTable::default();
+        assert!(table.published_all().is_empty());
+        assert_eq!(table.insert(2, Counter(0)), None);
+        table.insert(1, Counter(5));
+
+        assert_eq!(
+            table.with_mut(2, |c| {
+                c.0 += 1;
+                c.0
+            }),
+            Some(1)
+        );
+        assert_eq!(table.with(2, |c| c.0), Some(1));
+        assert_eq!(table.published(2), Some(1));
+        assert_eq!(table.with(3, |c| c.0), None);
+
+        let mut seen = Vec::new();
+        table.for_each_mut(|id, c| {
+            c.0 *= 10;
+            seen.push((id, c.0));
+        });
+        assert_eq!(seen, vec![(1, 50), (2, 10)]);
+        assert_eq!(table.published_all(), HashMap::from([(1, 50), (2, 10)]));
+
+        assert_eq!(table.insert(1, Counter(7)), Some(Counter(50)));
+        assert_eq!(table.remove(1), Some(Counter(7)));
+        assert_eq!(table.published(1), None);
+        assert_eq!(table.with(1, |c| c.0), None);
+    }
+
+    #[test]
+    fn test_locked_entry_blocks_no_one_else() {
+        let table = Arc::new(ProcessTable::default());
+        table.insert(1, Counter(1));
+        table.insert(2, Counter(2));
+
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let stuck = std::thread::spawn({
+            let table = Arc::clone(&table);
+            move || {
+                table.with_mut(1, |c| {
+                    locked_tx.send(()).unwrap();
+                    std::thread::sleep(Duration::from_millis(300));
+                    c.0 = 10;
+                });
+            }
+        });
+        locked_rx.recv().unwrap();
+
+        // Entry 1 is held, yet the rest of the table answers at once
+        let started = std::time::Instant::now();
+        assert_eq!(table.with_mut(2, |c| c.0), Some(2));
+        assert_eq!(table.published(1), Some(1));
+        assert_eq!(table.published_all().len(), 2);
+        table.insert(3, Counter(3));
+        assert!(started.elapsed() < Duration::from_millis(200));
+
+        stuck.join().unwrap();
+        assert_eq!(table.published(1), Some(10));
+    }
+}
@@ -0,0 +1,342 @@
+//! Scheduled summary reports
+//!
+//! When `[reports]` is set in notify.toml, one task samples the CPU and
+//! memory of running apps every `REPORT_SAMPLE_INTERVAL_SECS` and another
+//! sends a report at the configured time. Restarts, crashes and uptime come
+//! from the event log; usage figures only cover what was sampled since the
+//! previous report or daemon start, as metrics are not persisted.
+
+use chrono::{DateTime, Local, Utc};
+use oxidepm_core::{constants, AppEvent, AppInfo, EventKind, Result};
+use oxidepm_notify::{AppReport, CrashEntry, Report, ReportsConfig};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::handlers::RequestHandler;
+
+/// Usage samples of one app since the last report
+#[derive(Debug, Default, Clone)]
+struct Usage {
+    peak_memory_bytes: u64,
+    cpu_total: f64,
+    samples: u64,
+}
+
+/// CPU and memory samples per app id
+#[derive(Default)]
+struct UsageTracker {
+    apps: Mutex<HashMap<u32, Usage>>,
+}
+
+impl UsageTracker {
+    /// Record the current usage of running apps
+    fn sample(&self, apps: &[AppInfo]) {
+        let mut usage = self.apps.lock();
+        for app in apps.iter().filter(|a| a.state.status.is_running()) {
+            let entry = usage.entry(app.spec.id).or_default();
+            entry.peak_memory_bytes = entry.peak_memory_bytes.max(app.state.memory_bytes);
+            entry.cpu_total += app.state.cpu_percent as f64;
+            entry.samples += 1;
+        }
+    }
+
+    /// Samples so far, starting over for the next report
+    fn take(&self) -> HashMap<u32, Usage> {
+        std::mem::take(&mut *self.apps.lock())
+    }
+}
+
+/// Start the sampler and scheduler when reports are enabled
+pub fn spawn(handler: Arc<RequestHandler>) {
+    let notifier = handler.supervisor().notifier();
+    let Some(config) = notifier.reports_config().cloned() else {
+        return;
+    };
+    if !notifier.is_configured() {
+        warn!("Summary reports are enabled but no notification channel is configured");
+        return;
+    }
+    if let Err(e) = config.validate() {
+        warn!("Summary reports disabled: {}", e);
+        return;
+    }
+    info!(
+        "Sending {} summary reports at {}",
+        config.period.as_str(),
+        config.at
+    );
+
+    let usage = Arc::new(UsageTracker::default());
+
+    let sampler = Arc::clone(&usage);
+    let sampled = Arc::clone(&handler);
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(constants::REPORT_SAMPLE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match sampled.supervisor().status().await {
+                Ok(apps) => sampler.sample(&apps),
+                Err(e) => warn!("Summary reports: failed to sample usage: {}", e),
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            let next = match config.next_run_local() {
+                Ok(next) => next,
+                Err(e) => {
+                    warn!("Summary reports stopped: {}", e);
+                    return;
+                }
+            };
+            let wait = (next - Local::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            let until = Utc::now();
+            let since = until - config.period.duration();
+            let report = match compile(&handler, &config, &usage.take(), since, until).await {
+                Ok(report) => report,
+                Err(e) => {
+                    warn!("Summary reports: failed to compile report: {}", e);
+                    continue;
+                }
+            };
+            match notifier.send_report(&report).await {
+                Ok(()) => info!("Sent {} summary report", config.period.as_str()),
+                Err(e) => warn!("Failed to send summary report: {}", e),
+            }
+        }
+    });
+}
+
+/// Gather apps and events and build the report
+async fn compile(
+    handler: &RequestHandler,
+    config: &ReportsConfig,
+    usage: &HashMap<u32, Usage>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Report> {
+    let supervisor = handler.supervisor();
+    let apps = supervisor.status().await?;
+    let ids: Vec<u32> = apps.iter().map(|a| a.spec.id).collect();
+    let events = supervisor
+        .events(&ids, constants::EVENTS_RETENTION * ids.len().max(1))
+        .await?;
+    Ok(build_report(config, &apps, &events, usage, since, until))
+}
+
+/// Build a report from apps, their events (oldest first) and usage samples
+fn build_report(
+    config: &ReportsConfig,
+    apps: &[AppInfo],
+    events: &[AppEvent],
+    usage: &HashMap<u32, Usage>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Report {
+    let in_window = |e: &AppEvent| e.timestamp >= since && e.timestamp < until;
+
+    let app_reports = apps
+        .iter()
+        .map(|app| {
+            let id = app.spec.id;
+            let app_events: Vec<&AppEvent> = events.iter().filter(|e| e.app_id == id).collect();
+            let count = |kind: EventKind| {
+                app_events
+                    .iter()
+                    .filter(|e| in_window(e) && e.kind == kind)
+                    .count() as u32
+            };
+            let usage = usage.get(&id).filter(|u| u.samples > 0);
+            AppReport {
+                name: app.spec.qualified_name(),
+                uptime_percent: uptime_percent(
+                    &app_events,
+                    app.state.status.is_running(),
+                    since,
+                    until,
+                ),
+                restarts: count(EventKind::Restarted),
+                crashes: count(EventKind::Crashed),
+                peak_memory_bytes: usage.map(|u| u.peak_memory_bytes),
+                avg_cpu_percent: usage.map(|u| (u.cpu_total / u.samples as f64) as f32),
+            }
+        })
+        .collect();
+
+    let crashes = events
+        .iter()
+        .filter(|e| in_window(e) && e.kind == EventKind::Crashed)
+        .map(|e| CrashEntry {
+            name: e.name.clone(),
+            at: e.timestamp,
+            exit_code: e.exit_code,
+            reason: e.reason.clone(),
+        })
+        .collect();
+
+    Report {
+        period: config.period,
+        since,
+        until,
+        apps: app_reports,
+        crashes,
+        top: config.top,
+    }
+}
+
+/// Whether an event brings the app up or down, if either
+fn transition(kind: EventKind) -> Option<bool> {
+    match kind {
+        EventKind::Started => Some(true),
+        EventKind::Stopped | EventKind::Crashed => Some(false),
+        _ => None,
+    }
+}
+
+/// Share of `since..until` the app was up, from its events (oldest first)
+fn uptime_percent(
+    events: &[&AppEvent],
+    running_now: bool,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> f64 {
+    let total = (until - since).num_milliseconds();
+    if total <= 0 {
+        return 0.0;
+    }
+    let changes: Vec<(DateTime<Utc>, bool)> = events
+        .iter()
+        .filter(|e| e.timestamp < until)
+        .filter_map(|e| transition(e.kind).map(|up| (e.timestamp, up)))
+        .collect();
+
+    // State at the start of the window: the last change before it, else the
+    // opposite of the first change in it, else what the app is doing now
+    let mut up = match changes.iter().rev().find(|(at, _)| *at < since) {
+        Some(&(_, up)) => up,
+        None => changes.first().map(|&(_, up)| !up).unwrap_or(running_now),
+    };
+    let mut from = since;
+    let mut up_ms = 0;
+    for &(at, next) in changes.iter().filter(|(at, _)| *at >= since) {
+        if up {
+            up_ms += (at - from).num_milliseconds();
+        }
+        from = at;
+        up = next;
+    }
+    if up {
+        up_ms += (until - from).num_milliseconds();
+    }
+    (up_ms as f64 / total as f64 * 100.0).clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use oxidepm_core::{AppMode, AppSpec, AppStatus, RunState};
+    use std::path::PathBuf;
+
+    fn app(id: u32, name: &str, running: bool) -> AppInfo {
+        let mut spec = AppSpec::new(
+            name.to_string(),
+            AppMode::Cmd,
+            "sleep".to_string(),
+            PathBuf::from("/"),
+        );
+        spec.id = id;
+        let mut state = RunState::new(id);
+        if running {
+            state.status = AppStatus::Running;
+        }
+        AppInfo::new(spec, state)
+    }
+
+    #[test]
+    fn test_build_report() {
+        let until = Utc::now();
+        let since = until - ChronoDuration::hours(10);
+        let event = |id, name: &str, kind, hours_in: i64| {
+            let mut event = AppEvent::new(id, name, kind);
+            event.timestamp = since + ChronoDuration::hours(hours_in);
+            event
+        };
+        let events = vec![
+            event(1, "api", EventKind::Started, -5),
+            event(1, "api", EventKind::Crashed, 2),
+            event(1, "api", EventKind::Restarted, 3),
+            event(1, "api", EventKind::Started, 3),
+            // Stopped with no earlier events: up from the start of the window
+            event(2, "worker", EventKind::Stopped, 5),
+        ];
+        let apps = vec![
+            app(1, "api", true),
+            app(2, "worker", false),
+            app(3, "cron", true),
+        ];
+        let mut usage = HashMap::new();
+        usage.insert(
+            1,
+            Usage {
+                peak_memory_bytes: 64 * 1024 * 1024,
+                cpu_total: 30.0,
+                samples: 3,
+            },
+        );
+
+        let report = build_report(
+            &ReportsConfig::default(),
+            &apps,
+            &events,
+            &usage,
+            since,
+            until,
+        );
+
+        let api = &report.apps[0];
+        assert!(
+            (api.uptime_percent - 90.0).abs() < 0.01,
+            "uptime was {}",
+            api.uptime_percent
+        );
+        assert_eq!((api.restarts, api.crashes), (1, 1));
+        assert_eq!(api.peak_memory_bytes, Some(64 * 1024 * 1024));
+        assert_eq!(api.avg_cpu_percent, Some(10.0));
+
+        let worker = &report.apps[1];
+        assert!((worker.uptime_percent - 50.0).abs() < 0.01);
+        assert_eq!(worker.peak_memory_bytes, None);
+
+        // No events at all: whatever the app is doing now
+        assert_eq!(report.apps[2].uptime_percent, 100.0);
+
+        assert_eq!(report.crashes.len(), 1);
+        assert_eq!(report.crashes[0].name, "api");
+    }
+
+    #[test]
+    fn test_usage_tracker() {
+        let tracker = UsageTracker::default();
+        let mut api = app(1, "api", true);
+        api.state.memory_bytes = 100;
+        api.state.cpu_percent = 20.0;
+        tracker.sample(&[api.clone(), app(2, "worker", false)]);
+        api.state.memory_bytes = 50;
+        api.state.cpu_percent = 10.0;
+        tracker.sample(&[api]);
+
+        let usage = tracker.take();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[&1].peak_memory_bytes, 100);
+        assert_eq!(usage[&1].samples, 2);
+        assert!(tracker.take().is_empty());
+    }
+}
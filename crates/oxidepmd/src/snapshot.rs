@@ -0,0 +1,123 @@
+//! Compact binary snapshot of saved apps
+//!
+//! MessagePack-encoded `SavedState` behind a small magic/version header.
+//! Much smaller and faster to decode than the JSON save file for large
+//! process sets; the JSON file is still written for humans and older
+//! releases.
+
+use oxidepm_core::{Error, Result, SavedState};
+use std::path::Path;
+
+/// File magic for snapshot files
+const MAGIC: &[u8; 4] = b"OPMS";
+
+/// Current snapshot format version
+const VERSION: u8 = 2;
+
+/// Encode saved apps into snapshot bytes
+pub fn encode(state: &SavedState) -> Result<Vec<u8>> {
+    let body = rmp_serde::to_vec_named(state)
+        .map_err(|e| Error::config(format!("Failed to encode snapshot: {}", e)))?;
+
+    let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + body.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&body);
+    Ok(bytes)
+}
+
+/// Decode saved apps from snapshot bytes
+pub fn decode(bytes: &[u8]) -> Result<SavedState> {
+    let header_len = MAGIC.len() + 1;
+    if bytes.len() < header_len || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(Error::config("Invalid snapshot: bad header"));
+    }
+
+    let version = bytes[MAGIC.len()];
+    if version != VERSION {
+        return Err(Error::config(format!(
+            "Unsupported snapshot version {} (expected {})",
+            version, VERSION
+        )));
+    }
+
+    rmp_serde::from_slice(&bytes[header_len..])
+        .map_err(|e| Error::config(format!("Invalid snapshot: {}", e)))
+}
+
+/// Write a snapshot atomically (temp file + rename)
+pub fn write(path: &Path, state: &SavedState) -> Result<()> {
+    let bytes = encode(state)?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Read a snapshot file
+pub fn read(path: &Path) -> Result<SavedState> {
+    decode(&std::fs::read(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::{AppMode, AppSpec, DesiredState, HealthCheck};
+    use std::path::PathBuf;
+
+    fn sample_apps() -> Vec<AppSpec> {
+        (0..3)
+            .map(|i| {
+                AppSpec::new(
+                    format!("app-{}", i),
+                    AppMode::Node,
+                    "server.js".to_string(),
+                    PathBuf::from("/srv/app"),
+                )
+                .with_args(vec!["--port".to_string(), (3000 + i).to_string()])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut apps = sample_apps();
+        apps[1].health_check = Some(HealthCheck::http("http://localhost:3001/health"));
+        apps[2].tags = vec!["api".to_string()];
+
+        let stopped = vec![apps[2].qualified_name()];
+        let state = SavedState::new(apps).with_stopped(stopped);
+
+        let decoded = decode(&encode(&state).unwrap()).unwrap();
+        assert_eq!(decoded.apps.len(), 3);
+        assert_eq!(decoded.apps[0].args, vec!["--port", "3000"]);
+        assert!(decoded.apps[1].health_check.is_some());
+        assert_eq!(decoded.apps[2].tags, vec!["api"]);
+        assert_eq!(
+            decoded.desired_state(&decoded.apps[2]),
+            DesiredState::Stopped
+        );
+    }
+
+    #[test]
+    fn test_smaller_than_json() {
+        let state = SavedState::new(sample_apps());
+        let json = serde_json::to_vec_pretty(&state).unwrap();
+        assert!(encode(&state).unwrap().len() < json.len());
+    }
+
+    #[test]
+    fn test_rejects_bad_header() {
+        assert!(decode(b"[]").is_err());
+        assert!(decode(b"OPMS\x09\x90").is_err());
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("saved.snapshot");
+        write(&path, &SavedState::new(sample_apps())).unwrap();
+        assert_eq!(read(&path).unwrap().apps.len(), 3);
+        assert!(!path.with_extension("tmp").exists());
+    }
+}
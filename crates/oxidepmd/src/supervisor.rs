@@ -1,21 +1,50 @@
 //! Process supervisor - manages running processes
 
-use oxidepm_core::{constants, AppInfo, AppSpec, AppStatus, Error, HookEvent, Hooks, Result, RunState, Selector};
-use oxidepm_db::Database;
-use oxidepm_health::HealthMonitor;
-use oxidepm_logs::{LogCapture, LogReader, RotationConfig};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use oxidepm_core::secrets::SecretStore;
+use oxidepm_core::{
+    constants, load_env_file, start_order, AppEvent, AppHealth, AppInfo, AppMode, AppOpOutcome,
+    AppOpResult, AppSignal, AppSpec, AppStats, AppStatus, ApplyReport, BuildInfo, BulkOptions,
+    DesiredState, EnvFileAction, Error, EventKind, HealthCheck, HealthRecord, Hook, HookEvent,
+    HookSpec, HostInfo, LogPage, LogStream, LogTrigger, PortConflictPolicy, PortMapping, Probe,
+    Result, RunState, SavedState, Selector, SpecUpdate, SystemInfo, TriggerAction, UnhealthyAction,
+    WaitTarget,
+};
+use oxidepm_db::{AuditStore, Database, EventStore};
+use oxidepm_health::{HealthCheckResult, HealthChecker, HealthMonitor};
+use oxidepm_logs::{LinePrefix, LogCapture, LogReader, LogWriter, RotationConfig};
 use oxidepm_notify::{NotificationManager, NotifyConfig, ProcessEvent};
-use oxidepm_runtime::get_runner;
-use oxidepm_watch::{FileWatcher, WatchConfig};
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use oxidepm_runtime::{binary, get_runner, is_port_free, PrepareResult, ProgressSink, Runner};
+use oxidepm_watch::{ignore_file_patterns, FileWatcher, WatchConfig};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use sysinfo::{Pid, System};
+use sysinfo::{Pid, ProcessRefreshKind, System};
 use tokio::process::Child;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc, Notify};
 use tracing::{debug, error, info, warn};
 
+use crate::ports::PortRegistry;
+use crate::process_table::{ProcessTable, Publish};
+use crate::snapshot;
+use crate::triggers;
+
+/// How often a warming app is checked on: for health before the warm-up,
+/// for readiness once it ends
+const WARMUP_POLL: Duration = Duration::from_millis(500);
+
+/// How long a reload or scale-up waits for each health/readiness check of a
+/// new instance; a warm-up gets its own timeout on top
+const RELOAD_HEALTH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often `wait_for` targets are polled, and how long each attempt may take
+const WAIT_FOR_POLL: Duration = Duration::from_secs(1);
+
 /// Supervised process state
 pub struct SupervisedProcess {
     pub spec: AppSpec,
@@ -33,43 +62,424 @@ pub struct SupervisedProcess {
     /// Parent ID if this is a cluster instance
     #[allow(dead_code)]
     pub parent_id: Option<u32>,
+    /// Output lines the log capture has dropped
+    pub log_lines_dropped: Option<Arc<AtomicU64>>,
+}
+
+/// What status queries see of a supervised process, as of its last change
+#[derive(Debug, Clone)]
+pub struct PublishedProcess {
+    pub spec: AppSpec,
+    pub state: RunState,
+    pub cluster_instance_ids: Vec<u32>,
+}
+
+impl Publish for SupervisedProcess {
+    type Snapshot = PublishedProcess;
+
+    fn snapshot(&self) -> PublishedProcess {
+        PublishedProcess {
+            spec: self.spec.clone(),
+            state: self.state.clone(),
+            cluster_instance_ids: self.cluster_instance_ids.clone(),
+        }
+    }
+}
+
+/// Supervised processes by app id, each locked on its own
+type Processes = Arc<ProcessTable<SupervisedProcess>>;
+
+/// A prepare step in progress, which `stop`/`delete` or a disconnecting
+/// client can cancel
+struct PendingBuild {
+    /// `build_group` of the app being built
+    group: String,
+    cancel: Arc<Notify>,
+}
+
+/// Work a background task needs the supervisor to carry out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupervisorAction {
+    /// Restart an unhealthy app; `attempt` counts consecutive health restarts
+    Restart { id: u32, attempt: u32 },
+    /// Restart an app that crashed; `attempt` counts consecutive crash restarts
+    CrashRestart { id: u32, attempt: u32 },
+    /// Stop an unhealthy app
+    Stop { id: u32 },
+    /// Restart an app after a watched file changed; `prebuilt` is set when the
+    /// watch task already rebuilt it, so the new process starts without building
+    WatchRestart {
+        id: u32,
+        prebuilt: Option<BuildInfo>,
+    },
+    /// Re-read an app's watched env file, which changed, and restart or
+    /// reload the app with the new variables
+    EnvFileChanged { id: u32 },
+    /// Carry out a log trigger that matched a line from process `pid`
+    LogTrigger {
+        id: u32,
+        pid: u32,
+        trigger: LogTrigger,
+        line: String,
+    },
+    /// Stop an app whose drain, begun at `since`, timed out
+    DrainTimeout { id: u32, since: DateTime<Utc> },
+}
+
+/// An app drained for maintenance
+#[derive(Debug, Clone, Copy)]
+struct Drain {
+    /// When the drain began; a timeout only acts on the drain that set it
+    since: DateTime<Utc>,
+    /// The drain timeout stopped the app
+    stopped: bool,
+}
+
+/// Recent health check results per app id and check, kept across restarts
+type HealthHistory = Arc<RwLock<HashMap<(u32, Probe), VecDeque<HealthRecord>>>>;
+
+/// Outcome of `Supervisor::resurrect`
+#[derive(Debug, Default)]
+pub struct ResurrectReport {
+    /// Apps started
+    pub started: usize,
+    /// Saved apps left stopped because they were stopped on purpose
+    pub stopped: usize,
+    /// Saved apps that could not be read
+    pub warnings: Vec<String>,
 }
 
 /// Process supervisor
 pub struct Supervisor {
     db: Database,
-    processes: Arc<RwLock<HashMap<u32, SupervisedProcess>>>,
+    /// Every app's live state, locked per app; status queries read what
+    /// each app last published, so an app stuck in an operation holds up
+    /// nothing but itself
+    processes: Processes,
+    health_history: HealthHistory,
+    /// Drained apps by id (cluster instances included), kept across restarts
+    drains: Arc<RwLock<HashMap<u32, Drain>>>,
+    /// Apps still running their warm-up, by id, with the pid it is for
+    warming: Arc<RwLock<HashMap<u32, u32>>>,
     shutdown_tx: broadcast::Sender<()>,
-    system: Arc<RwLock<System>>,
-    notifier: Arc<NotificationManager>,
+    /// Host totals from the metrics collector's last sample
+    host_usage: Arc<RwLock<HostUsage>>,
+    /// Swapped when the daemon config is reloaded
+    notifier: Arc<RwLock<Arc<NotificationManager>>>,
+    /// Global disk budget for app logs, bytes
+    log_budget: Arc<RwLock<Option<u64>>>,
+    /// When set, auto-restarts, health-driven actions and watch restarts are suspended
+    supervision_paused: Arc<AtomicBool>,
+    actions_tx: mpsc::UnboundedSender<SupervisorAction>,
+    actions_rx: Mutex<Option<mpsc::UnboundedReceiver<SupervisorAction>>>,
+    started_at: Instant,
+    ports: Mutex<PortRegistry>,
+    builds: Mutex<HashMap<u32, PendingBuild>>,
+    /// Log trigger cooldowns, shared by all processes of an app
+    trigger_cooldowns: triggers::Cooldowns,
+    /// Held while saved state is written, so saves never share a temp file
+    saving: Mutex<()>,
 }
 
 impl Supervisor {
     /// Create a new supervisor
     pub async fn new(db: Database) -> Result<Self> {
         let (shutdown_tx, _) = broadcast::channel(16);
+        let (actions_tx, actions_rx) = mpsc::unbounded_channel();
 
         // Load notification config
         let notify_config = NotifyConfig::load().unwrap_or_default();
-        let notifier = Arc::new(NotificationManager::new(notify_config));
+        let notifier = Arc::new(RwLock::new(Arc::new(NotificationManager::new(
+            notify_config,
+        ))));
 
         let supervisor = Self {
             db,
-            processes: Arc::new(RwLock::new(HashMap::new())),
+            processes: Arc::new(ProcessTable::default()),
+            health_history: Arc::new(RwLock::new(HashMap::new())),
+            drains: Arc::new(RwLock::new(HashMap::new())),
+            warming: Arc::new(RwLock::new(HashMap::new())),
             shutdown_tx,
-            system: Arc::new(RwLock::new(System::new_all())),
+            host_usage: Arc::new(RwLock::new(HostUsage::current())),
             notifier,
+            log_budget: Arc::new(RwLock::new(None)),
+            supervision_paused: Arc::new(AtomicBool::new(false)),
+            actions_tx,
+            actions_rx: Mutex::new(Some(actions_rx)),
+            started_at: Instant::now(),
+            ports: Mutex::new(PortRegistry::new()),
+            builds: Mutex::new(HashMap::new()),
+            saving: Mutex::new(()),
+            trigger_cooldowns: triggers::Cooldowns::default(),
         };
 
         // Start metrics collector
         supervisor.spawn_metrics_collector();
+        supervisor.spawn_log_vacuum();
+        supervisor.spawn_db_maintenance();
 
         Ok(supervisor)
     }
 
+    /// Receiver for actions requested by background tasks; the daemon feeds
+    /// them back into `run_action`. Can only be taken once.
+    pub fn take_actions(&self) -> Option<mpsc::UnboundedReceiver<SupervisorAction>> {
+        self.actions_rx.lock().take()
+    }
+
+    /// Carry out an action requested by a background task
+    pub async fn run_action(&self, action: SupervisorAction) {
+        let result = match action.clone() {
+            SupervisorAction::Restart { id, attempt } => self.restart_unhealthy(id, attempt).await,
+            SupervisorAction::CrashRestart { id, attempt } => {
+                self.restart_crashed(id, attempt).await
+            }
+            SupervisorAction::Stop { id } => self.stop_unhealthy(id).await,
+            SupervisorAction::WatchRestart { id, prebuilt } => {
+                self.restart_watched(id, prebuilt).await
+            }
+            SupervisorAction::EnvFileChanged { id } => self.apply_env_file(id).await,
+            SupervisorAction::LogTrigger {
+                id,
+                pid,
+                trigger,
+                line,
+            } => self.fire_trigger(id, pid, trigger, line).await,
+            SupervisorAction::DrainTimeout { id, since } => self.finish_drain(id, since).await,
+        };
+        if let Err(e) = result {
+            error!("Failed to run {:?}: {}", action, e);
+        }
+    }
+
+    /// Pause or resume supervision activity; returns false if already in that state
+    pub fn set_supervision_paused(&self, paused: bool) -> bool {
+        let previous = self.supervision_paused.swap(paused, Ordering::SeqCst);
+        if previous != paused {
+            if paused {
+                warn!("Supervision paused: auto-restarts, health actions and watch restarts suspended");
+            } else {
+                info!("Supervision resumed");
+            }
+        }
+        previous != paused
+    }
+
+    /// Check if supervision is currently paused
+    pub fn is_supervision_paused(&self) -> bool {
+        self.supervision_paused.load(Ordering::SeqCst)
+    }
+
+    /// Drain apps for maintenance: they (and their cluster instances) report
+    /// not ready and their log triggers hold off. Apps still draining after
+    /// `timeout` are stopped. Returns how many apps were drained.
+    pub fn drain(&self, ids: &[u32], timeout: Duration) -> usize {
+        let since = Utc::now();
+        let instances = self.expand_clusters(ids);
+        {
+            let mut drains = self.drains.write();
+            for &id in ids.iter().chain(&instances) {
+                drains.insert(
+                    id,
+                    Drain {
+                        since,
+                        stopped: false,
+                    },
+                );
+            }
+        }
+        info!(
+            "Draining {} app(s), stopping them in {}s",
+            ids.len(),
+            timeout.as_secs()
+        );
+
+        let actions = self.actions_tx.clone();
+        let ids = ids.to_vec();
+        let count = ids.len();
+        tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            for id in ids {
+                let _ = actions.send(SupervisorAction::DrainTimeout { id, since });
+            }
+        });
+        count
+    }
+
+    /// Stop an app once its drain timed out, unless it was undrained or
+    /// drained again since
+    async fn finish_drain(&self, id: u32, since: DateTime<Utc>) -> Result<()> {
+        let current = self
+            .drains
+            .read()
+            .get(&id)
+            .is_some_and(|drain| drain.since == since);
+        if !current {
+            return Ok(());
+        }
+
+        info!("Drain of app {} timed out, stopping it", id);
+        let stopped = self.stop(id).await?;
+        if stopped {
+            // Stays down across resurrect until undrained
+            self.set_desired_state(id, DesiredState::Stopped).await?;
+        }
+        if let Some(drain) = self.drains.write().get_mut(&id) {
+            drain.stopped = stopped;
+        }
+        Ok(())
+    }
+
+    /// End the drain of apps, starting the ones the drain stopped again.
+    /// Returns how many apps were draining and how many were started.
+    pub async fn undrain(&self, ids: &[u32]) -> Result<(usize, usize)> {
+        let instances = self.expand_clusters(ids);
+        let undrained: Vec<(u32, Drain)> = {
+            let mut drains = self.drains.write();
+            let undrained = ids
+                .iter()
+                .filter_map(|&id| drains.remove(&id).map(|drain| (id, drain)))
+                .collect();
+            for id in &instances {
+                drains.remove(id);
+            }
+            undrained
+        };
+
+        let mut started = 0;
+        for (id, drain) in &undrained {
+            if drain.stopped && self.start_by_id(*id).await? {
+                started += 1;
+            }
+        }
+        Ok((undrained.len(), started))
+    }
+
+    /// Freeze a running app (every instance of a cluster) with SIGSTOP on its
+    /// whole process tree. Returns whether anything was paused.
+    pub fn pause(&self, id: u32) -> Result<bool> {
+        self.set_paused(id, true)
+    }
+
+    /// Continue a paused app with SIGCONT. Returns whether anything was resumed.
+    pub fn resume(&self, id: u32) -> Result<bool> {
+        self.set_paused(id, false)
+    }
+
+    /// `id` followed by its cluster instances, if the app is tracked
+    fn app_and_instances(&self, id: u32) -> Option<Vec<u32>> {
+        let instances = self
+            .processes
+            .with(id, |proc| proc.cluster_instance_ids.clone())?;
+        Some(std::iter::once(id).chain(instances).collect())
+    }
+
+    fn set_paused(&self, id: u32, pause: bool) -> Result<bool> {
+        let (from, to, kind) = if pause {
+            (AppStatus::Running, AppStatus::Paused, EventKind::Paused)
+        } else {
+            (AppStatus::Paused, AppStatus::Running, EventKind::Resumed)
+        };
+
+        let Some(ids) = self.app_and_instances(id) else {
+            return Ok(false);
+        };
+        let changed: Vec<(u32, String, Option<u32>)> = ids
+            .into_iter()
+            .filter_map(|app_id| {
+                self.processes.with_mut(app_id, |proc| {
+                    if proc.state.status != from {
+                        return None;
+                    }
+                    proc.state.status = to;
+                    Some((app_id, proc.spec.qualified_name(), proc.state.pid))
+                })?
+            })
+            .collect();
+
+        #[cfg(unix)]
+        {
+            use nix::sys::signal::Signal;
+            let signal = if pause {
+                Signal::SIGSTOP
+            } else {
+                Signal::SIGCONT
+            };
+            for pid in changed.iter().filter_map(|(_, _, pid)| *pid) {
+                signal_tree(pid, signal);
+            }
+        }
+
+        for (app_id, name, _) in &changed {
+            info!("{} app {}", if pause { "Paused" } else { "Resumed" }, name);
+            self.record_event(AppEvent::new(*app_id, name.clone(), kind));
+        }
+        Ok(!changed.is_empty())
+    }
+
+    /// Pass a signal to a running app (every instance of a cluster), or to
+    /// its whole process tree with `tree`. Returns whether anything was
+    /// signalled.
+    pub fn signal(&self, id: u32, signal: AppSignal, tree: bool) -> Result<bool> {
+        let Some(ids) = self.app_and_instances(id) else {
+            return Ok(false);
+        };
+        let targets: Vec<(u32, String, u32)> = ids
+            .into_iter()
+            .filter_map(|app_id| {
+                self.processes.with(app_id, |proc| {
+                    let pid = proc.state.pid.filter(|_| proc.state.status.is_running())?;
+                    Some((app_id, proc.spec.qualified_name(), pid))
+                })?
+            })
+            .collect();
+
+        #[cfg(unix)]
+        {
+            use nix::sys::signal::{kill, Signal};
+            use nix::unistd::Pid as NixPid;
+
+            let signal: Signal = signal
+                .as_str()
+                .parse()
+                .map_err(|_| Error::InvalidSignal(signal.to_string()))?;
+            for (_, name, pid) in &targets {
+                if tree {
+                    signal_tree(*pid, signal);
+                } else if let Err(e) = kill(NixPid::from_raw(*pid as i32), signal) {
+                    return Err(Error::ProcessNotRunning(format!("{}: {}", name, e)));
+                }
+            }
+        }
+
+        for (app_id, name, _) in &targets {
+            info!("Sent {} to app {}", signal, name);
+            self.record_event(
+                AppEvent::new(*app_id, name.clone(), EventKind::Signalled)
+                    .with_reason(signal.as_str()),
+            );
+        }
+        Ok(!targets.is_empty())
+    }
+
+    /// Notification channels, shared with the report scheduler
+    pub fn notifier(&self) -> Arc<NotificationManager> {
+        Arc::clone(&self.notifier.read())
+    }
+
+    /// Send notifications through channels built from `config` from now on
+    pub fn set_notify_config(&self, config: NotifyConfig) {
+        *self.notifier.write() = Arc::new(NotificationManager::new(config));
+    }
+
+    /// Keep app logs within `budget` bytes from now on (None: no budget)
+    pub fn set_log_budget(&self, budget: Option<u64>) {
+        *self.log_budget.write() = budget;
+    }
+
     /// Send a notification for a process event (non-blocking)
     fn notify_event(&self, event: ProcessEvent) {
-        let notifier = Arc::clone(&self.notifier);
+        let notifier = self.notifier();
         tokio::spawn(async move {
             if let Err(e) = notifier.notify(&event).await {
                 warn!("Failed to send notification: {}", e);
@@ -77,16 +487,44 @@ impl Supervisor {
         });
     }
 
+    /// Append to the event log without waiting for the write
+    fn record_event(&self, event: AppEvent) {
+        spawn_record_event(self.db.events(), event);
+    }
+
     /// Start an application
-    pub async fn start(&self, mut spec: AppSpec) -> Result<u32> {
-        // Check if app already exists with this name
-        if let Some(existing) = self.db.apps().get_by_name(&spec.name).await? {
+    pub async fn start(&self, spec: AppSpec) -> Result<u32> {
+        self.start_with_progress(spec, &ProgressSink::none()).await
+    }
+
+    /// Start an application, streaming its build output to `progress`
+    pub async fn start_with_progress(
+        &self,
+        mut spec: AppSpec,
+        progress: &ProgressSink,
+    ) -> Result<u32> {
+        // The namespace ends up in log file names
+        if !oxidepm_core::validate_app_name(&spec.namespace) {
+            return Err(Error::config(format!(
+                "Invalid namespace '{}'",
+                spec.namespace
+            )));
+        }
+        spec.logs.validate()?;
+
+        // Check if app already exists with this name in its namespace
+        if let Some(existing) = self
+            .db
+            .apps()
+            .get_by_name(&spec.namespace, &spec.name)
+            .await?
+        {
             // Check if it's already running
-            let processes = self.processes.read();
-            if let Some(proc) = processes.get(&existing.id) {
-                if proc.state.status.is_running() {
-                    return Err(Error::AppAlreadyExists(spec.name));
-                }
+            let running = self
+                .processes
+                .with(existing.id, |proc| proc.state.status.is_running());
+            if running == Some(true) {
+                return Err(Error::AppAlreadyExists(spec.qualified_name()));
             }
             // Use existing ID
             spec.id = existing.id;
@@ -95,20 +533,25 @@ impl Supervisor {
             let id = self.db.apps().insert(&spec).await?;
             spec.id = id;
         }
+        // Starting an app means it should be running, including after resurrect
+        self.db
+            .apps()
+            .set_desired_state(spec.id, DesiredState::Running)
+            .await?;
 
         info!("Starting app: {} (id: {})", spec.name, spec.id);
 
         // Handle clustering: if instances > 1, spawn multiple processes
         if spec.instances > 1 && spec.instance_id.is_none() {
-            return self.start_cluster(spec).await;
+            return self.start_cluster(spec, progress).await;
         }
 
         // Single instance or cluster child - start normally
-        self.start_single(spec).await
+        self.start_single(spec, progress).await
     }
 
     /// Start a cluster of instances
-    async fn start_cluster(&self, spec: AppSpec) -> Result<u32> {
+    async fn start_cluster(&self, spec: AppSpec, progress: &ProgressSink) -> Result<u32> {
         let instance_count = spec.instances;
         let base_name = spec.name.clone();
         let parent_id = spec.id;
@@ -128,7 +571,7 @@ impl Supervisor {
             let instance_spec = spec.for_instance(i, port);
 
             // Start the instance
-            match self.start_single(instance_spec).await {
+            match self.start_single(instance_spec, progress).await {
                 Ok(id) => {
                     instance_ids.push(id);
                     info!("Started instance {}-{} (id: {}, port: {:?})", base_name, i, id, port);
@@ -152,16 +595,29 @@ impl Supervisor {
                 pid: None,
                 status: AppStatus::Running,
                 restarts: 0,
+                restarts_in_window: 0,
+                next_restart_at: None,
                 uptime_secs: 0,
                 cpu_percent: 0.0,
                 memory_bytes: 0,
+                processes: None,
+                open_fds: None,
+                threads: None,
+                disk_read_bytes_per_sec: 0,
+                disk_write_bytes_per_sec: 0,
+                log_lines_dropped: 0,
                 last_exit_code: None,
                 started_at: Some(chrono::Utc::now()),
                 healthy: true,
                 last_health_check: None,
                 health_check_failures: 0,
+                ready: true,
+                readiness_failures: 0,
+                draining: false,
                 port: None,
                 instance_id: None,
+                stats: AppStats::default(),
+                build: None,
             },
             child: None,
             restart_count: 0,
@@ -170,9 +626,13 @@ impl Supervisor {
             health_monitor: None,
             cluster_instance_ids: instance_ids,
             parent_id: None,
+            log_lines_dropped: None,
         };
 
-        self.processes.write().insert(parent_id, parent_supervised);
+        self.processes.insert(parent_id, parent_supervised);
+        if spec.watch_env_file.is_some() {
+            self.spawn_env_file_task(parent_id);
+        }
 
         info!(
             "Cluster '{}' started with {} instances",
@@ -183,6 +643,11 @@ impl Supervisor {
 
     /// Calculate port for a cluster instance
     fn calculate_instance_port(&self, spec: &AppSpec, instance_index: u32) -> Option<u16> {
+        // Auto ports are picked per instance at launch
+        if spec.auto_port {
+            return None;
+        }
+
         // Priority 1: Use port_range if specified
         if let Some((start, end)) = spec.port_range {
             let port = start + instance_index as u16;
@@ -205,13 +670,176 @@ impl Supervisor {
     }
 
     /// Start a single process (internal)
-    async fn start_single(&self, mut spec: AppSpec) -> Result<u32> {
+    async fn start_single(&self, mut spec: AppSpec, progress: &ProgressSink) -> Result<u32> {
         // For cluster instances, we need a new ID
         if spec.instance_id.is_some() {
             let id = self.db.apps().insert(&spec).await?;
             spec.id = id;
         }
 
+        self.launch_prepared(spec, None, progress).await
+    }
+
+    /// Build, spawn and track a process under the spec's existing id
+    async fn launch(&self, spec: AppSpec) -> Result<u32> {
+        self.launch_prepared(spec, None, &ProgressSink::none())
+            .await
+    }
+
+    /// Like `launch`, but skips the build when `prepared` holds a successful
+    /// one, and streams build output to `progress`
+    async fn launch_prepared(
+        &self,
+        mut spec: AppSpec,
+        prepared: Option<PrepareResult>,
+        progress: &ProgressSink,
+    ) -> Result<u32> {
+        self.reserve_port(&mut spec)?;
+        let id = spec.id;
+        let result = self.spawn_tracked(spec, prepared, progress).await;
+        if result.is_err() {
+            self.ports.lock().release(id);
+        }
+        result
+    }
+
+    /// Claim the spec's port, moving to a free one when the spec allows it.
+    /// A port counts as taken while the app holding it is running, or if
+    /// something outside the daemon is listening on it.
+    fn reserve_port(&self, spec: &mut AppSpec) -> Result<()> {
+        if spec.auto_port {
+            return self.reserve_auto_port(spec);
+        }
+        let Some(port) = spec.requested_port() else {
+            return Ok(());
+        };
+
+        let mut ports = self.ports.lock();
+        // Apps that claimed a port but are not tracked yet are mid-launch
+        let is_active = |id: u32| {
+            self.processes
+                .published(id)
+                .map_or(true, |p| p.state.status.is_running())
+        };
+
+        // The host check is skipped when a managed app holds the port: it is
+        // either ours to take over (reload) or a conflict either way
+        let conflict = if ports.holder(port, is_active).is_none() && !is_port_free(port) {
+            Some(format!("port {} is in use by another process", port))
+        } else {
+            ports
+                .claim(port, spec.id, &spec.name, is_active)
+                .err()
+                .map(|holder| {
+                    format!(
+                        "port {} is used by {} (id: {})",
+                        port, holder.name, holder.app_id
+                    )
+                })
+        };
+        let Some(conflict) = conflict else {
+            return Ok(());
+        };
+
+        let free = ports.next_free(port.saturating_add(1)..=u16::MAX, is_active, is_port_free);
+        match (spec.on_port_conflict, free) {
+            (PortConflictPolicy::Reassign, Some(free)) => {
+                info!(
+                    "{} for {}, using port {} instead",
+                    conflict, spec.name, free
+                );
+                // next_free only returns ports nobody active holds
+                let _ = ports.claim(free, spec.id, &spec.name, is_active);
+                spec.port = Some(free);
+                spec.env.insert("PORT".to_string(), free.to_string());
+                Ok(())
+            }
+            (_, Some(free)) => Err(Error::PortConflict(format!(
+                "{}; port {} is free",
+                conflict, free
+            ))),
+            (_, None) => Err(Error::PortConflict(format!(
+                "{} and no free port was found",
+                conflict
+            ))),
+        }
+    }
+
+    /// Pick a port for a `port = "auto"` app from its `port_range` (or the
+    /// daemon-wide auto range), keeping the port it had last time if it is
+    /// still free. The pick is written into the spec, so restarts ask for it again.
+    fn reserve_auto_port(&self, spec: &mut AppSpec) -> Result<()> {
+        let (start, end) = spec.port_range.unwrap_or_else(constants::auto_port_range);
+
+        let mut ports = self.ports.lock();
+        let is_active = |id: u32| {
+            self.processes
+                .published(id)
+                .map_or(true, |p| p.state.status.is_running())
+        };
+
+        // A port handed over on reload is still bound by the old instance
+        let previous = spec
+            .requested_port()
+            .filter(|&port| match ports.holder(port, is_active) {
+                Some(_) => ports.can_claim(port, spec.id, is_active),
+                None => is_port_free(port),
+            });
+        let port = previous
+            .or_else(|| ports.next_free(start..=end, is_active, is_port_free))
+            .ok_or_else(|| {
+                Error::PortConflict(format!(
+                    "no free port left in the auto range {}-{} (widen port_range or {})",
+                    start,
+                    end,
+                    constants::AUTO_PORT_RANGE_ENV
+                ))
+            })?;
+
+        // Both candidates were checked against the registry above
+        let _ = ports.claim(port, spec.id, &spec.name, is_active);
+        if previous.is_none() {
+            info!("Assigned port {} to {}", port, spec.name);
+        }
+        spec.port = Some(port);
+        spec.env.insert("PORT".to_string(), port.to_string());
+        Ok(())
+    }
+
+    /// Tracked apps and how many of them are running, without touching the database
+    pub fn process_counts(&self) -> (usize, usize) {
+        let processes = self.processes.published_all();
+        let running = processes
+            .values()
+            .filter(|p| p.state.status.is_running())
+            .count();
+        (processes.len(), running)
+    }
+
+    /// Ports held by running apps
+    pub fn ports(&self) -> Vec<PortMapping> {
+        let ports = self.ports.lock();
+        let processes = self.processes.published_all();
+        ports
+            .claims()
+            .filter_map(|(port, claim)| {
+                let proc = processes.get(&claim.app_id)?;
+                proc.state.status.is_running().then(|| PortMapping {
+                    port,
+                    app_id: claim.app_id,
+                    name: proc.spec.qualified_name(),
+                    status: proc.state.status,
+                })
+            })
+            .collect()
+    }
+
+    async fn spawn_tracked(
+        &self,
+        spec: AppSpec,
+        prepared: Option<PrepareResult>,
+        progress: &ProgressSink,
+    ) -> Result<u32> {
         // Apply startup delay if configured
         if let Some(delay_ms) = spec.startup_delay_ms {
             if delay_ms > 0 {
@@ -223,26 +851,67 @@ impl Supervisor {
         // Get appropriate runner
         let runner = get_runner(spec.mode);
 
-        // Prepare (build if needed)
-        info!("Preparing {} ({})...", spec.name, spec.mode);
-        let prepare_result = runner.prepare(&spec).await?;
+        // Prepare (build if needed), unless a watch rebuild already did
+        let prepare_result = match prepared {
+            Some(prepared) if prepared.success => prepared,
+            _ => {
+                info!("Preparing {} ({})...", spec.name, spec.mode);
+                let prepare_result = self
+                    .prepare_tracked(&spec, runner.as_ref(), progress)
+                    .await?;
+
+                if !prepare_result.success {
+                    error!(
+                        "Prepare failed for {}: {}",
+                        spec.name, prepare_result.output
+                    );
+                    return Err(Error::from_build_output(prepare_result.output));
+                }
 
-        if !prepare_result.success {
-            error!("Prepare failed for {}: {}", spec.name, prepare_result.output);
-            return Err(Error::BuildFailed(prepare_result.output));
-        }
+                info!("Prepare successful for {}", spec.name);
+                prepare_result
+            }
+        };
 
-        info!("Prepare successful for {}", spec.name);
+        // Wait for the services and files the app depends on
+        if !spec.wait_for.is_empty() {
+            self.wait_for_targets(&spec).await?;
+        }
 
-        // Start process
-        let running = runner.start(&spec).await?;
+        // Start process. Secret references are resolved only for the launch;
+        // the tracked and persisted spec keeps `${secret:NAME}` placeholders.
+        let launch_spec = SecretStore::open_default()
+            .resolve_spec(&spec)?
+            .for_launch();
+        let running = runner.start(&launch_spec).await?;
         let pid = running.pid;
 
         info!("Started {} with PID {}", spec.name, pid);
 
         // Set up log capture
         oxidepm_logs::ensure_log_dir()?;
-        let log_capture = LogCapture::new(&spec.name, RotationConfig::default())?;
+        let mut log_capture = LogCapture::new(
+            spec.stdout_log_path(),
+            spec.stderr_log_path(),
+            RotationConfig::default(),
+        )?
+        .with_prefix(LinePrefix::for_app(&spec))
+        .with_overflow(spec.logs.overflow)
+        .with_max_line_bytes(
+            spec.logs
+                .max_line_bytes
+                .unwrap_or(constants::DEFAULT_LOG_MAX_LINE_BYTES),
+        )
+        .with_raw(spec.logs.raw);
+        let log_lines_dropped = log_capture.dropped_lines();
+        if let Some(tap) = triggers::tap(
+            &spec,
+            pid,
+            self.trigger_cooldowns.clone(),
+            self.actions_tx.clone(),
+        ) {
+            log_capture = log_capture.with_tap(tap);
+        }
 
         // Take ownership of child's stdout/stderr
         let mut child = running.child;
@@ -263,16 +932,29 @@ impl Supervisor {
                 pid: Some(pid),
                 status: AppStatus::Running,
                 restarts: 0,
+                restarts_in_window: 0,
+                next_restart_at: None,
                 uptime_secs: 0,
                 cpu_percent: 0.0,
                 memory_bytes: 0,
+                processes: None,
+                open_fds: None,
+                threads: None,
+                disk_read_bytes_per_sec: 0,
+                disk_write_bytes_per_sec: 0,
+                log_lines_dropped: 0,
                 last_exit_code: None,
                 started_at: Some(chrono::Utc::now()),
                 healthy: true,
                 last_health_check: None,
                 health_check_failures: 0,
-                port: spec.port,
+                ready: spec.readiness_check.is_none() && spec.warmup.is_none(),
+                readiness_failures: 0,
+                draining: false,
+                port: spec.requested_port(),
                 instance_id: spec.instance_id,
+                stats: AppStats::default(),
+                build: prepare_result.build,
             },
             child: Some(child),
             restart_count: 0,
@@ -281,26 +963,32 @@ impl Supervisor {
             health_monitor,
             cluster_instance_ids: Vec::new(),
             parent_id: None,
+            log_lines_dropped: Some(log_lines_dropped),
         };
 
+        if let Err(e) = self.db.runs().insert(spec.id, &supervised.state).await {
+            warn!("Failed to record run for {}: {}", spec.name, e);
+        }
+
         // Track process
-        self.processes.write().insert(spec.id, supervised);
+        self.processes.insert(spec.id, supervised);
+        if spec.warmup.is_some() {
+            self.warming.write().insert(spec.id, pid);
+        }
 
         // Send notification for process started
         self.notify_event(ProcessEvent::Started {
             name: spec.name.clone(),
             id: spec.id,
         });
+        self.record_event(AppEvent::new(
+            spec.id,
+            spec.qualified_name(),
+            EventKind::Started,
+        ));
 
         // Run on_start hook if configured
-        self.run_hook(
-            &spec.hooks,
-            HookEvent::Start,
-            spec.id,
-            &spec.name,
-            Some(pid),
-            None,
-        );
+        self.run_hook(&spec, HookEvent::Start, spec.id, Some(pid), None);
 
         // Spawn supervision task
         self.spawn_supervision_task(spec.id);
@@ -309,38 +997,202 @@ impl Supervisor {
         if spec.health_check.is_some() {
             self.spawn_health_check_task(spec.id);
         }
+        if spec.readiness_check.is_some() {
+            self.spawn_readiness_task(spec.id);
+        }
+        if spec.warmup.is_some() {
+            self.spawn_warmup_task(spec.id);
+        }
 
         // Set up watch if enabled
         if spec.watch {
             self.spawn_watch_task(spec.id);
         }
 
+        if spec.mode == AppMode::Binary {
+            self.spawn_binary_check_task(spec.id);
+        }
+
+        // A cluster's env file is watched once, for the whole cluster
+        if spec.watch_env_file.is_some() && spec.instance_id.is_none() {
+            self.spawn_env_file_task(spec.id);
+        }
+
         Ok(spec.id)
     }
 
-    /// Stop an application
-    pub async fn stop(&self, id: u32) -> Result<bool> {
-        // Extract what we need without holding the lock across await
-        let (name, kill_timeout_ms, child, pid, hooks) = {
-            let mut processes = self.processes.write();
+    /// Poll the app's `wait_for` targets until all of them are up, failing
+    /// once `wait_for_timeout_secs` elapse
+    async fn wait_for_targets(&self, spec: &AppSpec) -> Result<()> {
+        let mut pending = spec.wait_targets()?;
+        let timeout_secs = spec
+            .wait_for_timeout_secs
+            .unwrap_or(constants::DEFAULT_WAIT_FOR_TIMEOUT_SECS);
+        let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+        let checker = HealthChecker::new();
+        info!(
+            "Waiting for {} before starting {}...",
+            join_targets(&pending),
+            spec.name
+        );
 
-            if let Some(proc) = processes.get_mut(&id) {
-                if !proc.state.status.is_running() {
-                    return Ok(false);
+        loop {
+            let mut down = Vec::new();
+            for target in pending {
+                if !checker.reachable(&target, &spec.cwd, WAIT_FOR_POLL).await {
+                    down.push(target);
                 }
+            }
+            pending = down;
+            if pending.is_empty() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout(format!(
+                    "{} still waiting for {} after {}s (see wait_for_timeout_secs)",
+                    spec.name,
+                    join_targets(&pending),
+                    timeout_secs
+                )));
+            }
+            tokio::time::sleep(WAIT_FOR_POLL).await;
+        }
+    }
 
-                info!("Stopping app {} (id: {})", proc.spec.name, id);
-                proc.state.status = AppStatus::Stopping;
+    /// Run the prepare step with the app listed as building, bounded by its
+    /// build timeout and cancellable through `cancel_builds`
+    async fn prepare_tracked(
+        &self,
+        spec: &AppSpec,
+        runner: &dyn Runner,
+        progress: &ProgressSink,
+    ) -> Result<PrepareResult> {
+        let cancel = Arc::new(Notify::new());
+        self.builds.lock().insert(
+            spec.id,
+            PendingBuild {
+                group: build_group(spec),
+                cancel: Arc::clone(&cancel),
+            },
+        );
 
-                let child = proc.child.take();
-                let pid = proc.state.pid;
-                let name = proc.spec.name.clone();
-                let timeout = proc.spec.kill_timeout_ms;
-                let hooks = proc.spec.hooks.clone();
-                (name, timeout, child, pid, hooks)
-            } else {
-                return Ok(false);
+        let mut state = RunState::new(spec.id);
+        state.status = AppStatus::Building;
+        state.started_at = Some(chrono::Utc::now());
+        state.port = spec.requested_port();
+        state.instance_id = spec.instance_id;
+        let building = SupervisedProcess {
+            spec: spec.clone(),
+            state,
+            child: None,
+            restart_count: 0,
+            last_restart: None,
+            started_at: None,
+            health_monitor: None,
+            cluster_instance_ids: Vec::new(),
+            parent_id: None,
+            log_lines_dropped: None,
+        };
+        let previous = self.processes.insert(spec.id, building);
+
+        let timeout_secs = spec
+            .build_timeout_secs
+            .unwrap_or(constants::DEFAULT_BUILD_TIMEOUT_SECS);
+        let result = tokio::select! {
+            result = tokio::time::timeout(Duration::from_secs(timeout_secs), prepare_logged(runner, spec, progress)) => {
+                result.unwrap_or_else(|_| {
+                    Err(Error::Timeout(format!(
+                        "Build of {} did not finish within {}s (see build_timeout_secs)",
+                        spec.name, timeout_secs
+                    )))
+                })
+            }
+            _ = cancel.notified() => Err(Error::BuildFailed(format!("Build of {} was cancelled", spec.name))),
+        };
+
+        self.builds.lock().remove(&spec.id);
+        // The launched process replaces this entry; until then, and after a
+        // failed build, the app is back to how it was
+        match previous {
+            Some(previous) => self.processes.insert(spec.id, previous),
+            None => self.processes.remove(spec.id),
+        };
+        result
+    }
+
+    /// Cancel builds in progress for the given apps, including the instances
+    /// of a cluster. Returns the ids among `ids` that had a build cancelled.
+    pub async fn cancel_builds(&self, ids: &[u32]) -> Vec<u32> {
+        let mut cancelled = Vec::new();
+        if self.builds.lock().is_empty() {
+            return cancelled;
+        }
+        for id in ids {
+            if let Ok(Some(spec)) = self.db.apps().get_by_id(*id).await {
+                if self.cancel_build_groups(&HashSet::from([build_group(&spec)])) > 0 {
+                    cancelled.push(*id);
+                }
+            }
+        }
+        cancelled
+    }
+
+    /// Cancel the builds a start request for `spec` set off
+    pub fn cancel_builds_of(&self, spec: &AppSpec) -> usize {
+        self.cancel_build_groups(&HashSet::from([build_group(spec)]))
+    }
+
+    fn cancel_build_groups(&self, groups: &HashSet<String>) -> usize {
+        let builds = self.builds.lock();
+        let mut cancelled = 0;
+        for build in builds
+            .values()
+            .filter(|build| groups.contains(&build.group))
+        {
+            info!("Cancelling build of {}", build.group);
+            build.cancel.notify_one();
+            cancelled += 1;
+        }
+        cancelled
+    }
+
+    /// Stop an application
+    pub async fn stop(&self, id: u32) -> Result<bool> {
+        // Extract what we need without holding the lock across await
+        let stopping = self.processes.with_mut(id, |proc| {
+            if !proc.state.status.is_running() {
+                return Err(false);
             }
+            // Nothing runs yet; the build's cleanup restores the entry
+            if proc.state.status == AppStatus::Building {
+                if let Some(build) = self.builds.lock().get(&id) {
+                    build.cancel.notify_one();
+                }
+                return Err(true);
+            }
+
+            info!("Stopping app {} (id: {})", proc.spec.name, id);
+            let paused = proc.state.status == AppStatus::Paused;
+            proc.state.status = AppStatus::Stopping;
+
+            let child = proc.child.take();
+            let pid = proc.state.pid;
+            let name = proc.spec.name.clone();
+            let timeout = proc.spec.kill_timeout_ms;
+            Ok((
+                name,
+                proc.spec.qualified_name(),
+                timeout,
+                child,
+                pid,
+                proc.spec.clone(),
+                paused,
+            ))
+        });
+        let (name, qualified_name, kill_timeout_ms, child, pid, spec, paused) = match stopping {
+            Some(Ok(stopping)) => stopping,
+            Some(Err(acted)) => return Ok(acted),
+            None => return Ok(false),
         };
 
         if let Some(mut child) = child {
@@ -352,6 +1204,10 @@ impl Supervisor {
 
                 if let Some(pid) = pid {
                     let _ = kill(NixPid::from_raw(pid as i32), Signal::SIGTERM);
+                    // A frozen tree only acts on the SIGTERM once continued
+                    if paused {
+                        signal_tree(pid, Signal::SIGCONT);
+                    }
                 }
             }
 
@@ -377,22 +1233,27 @@ impl Supervisor {
             };
 
             // Update state after async operations complete
+            self.processes.with_mut(id, |proc| {
+                proc.state.last_exit_code = exit_code;
+                proc.state.status = AppStatus::Stopped;
+                proc.state.pid = None;
+                proc.started_at = None;
+            });
+            if let Err(e) = self
+                .db
+                .runs()
+                .finish_latest(id, AppStatus::Stopped, exit_code)
+                .await
             {
-                let mut processes = self.processes.write();
-                if let Some(proc) = processes.get_mut(&id) {
-                    proc.state.last_exit_code = exit_code;
-                    proc.state.status = AppStatus::Stopped;
-                    proc.state.pid = None;
-                    proc.started_at = None;
-                }
+                warn!("Failed to record end of run for app {}: {}", id, e);
             }
         }
 
         // Get exit code for hook
-        let exit_code = {
-            let processes = self.processes.read();
-            processes.get(&id).and_then(|p| p.state.last_exit_code)
-        };
+        let exit_code = self
+            .processes
+            .with(id, |p| p.state.last_exit_code)
+            .flatten();
 
         // Send notification for process stopped
         self.notify_event(ProcessEvent::Stopped {
@@ -400,125 +1261,663 @@ impl Supervisor {
             id,
             exit_code,
         });
+        self.record_event(
+            AppEvent::new(id, qualified_name, EventKind::Stopped).with_exit_code(exit_code),
+        );
 
         // Run on_stop hook if configured
-        self.run_hook(&hooks, HookEvent::Stop, id, &name, pid, exit_code);
+        self.run_hook(&spec, HookEvent::Stop, id, pid, exit_code);
 
         info!("Stopped app {}", name);
         Ok(true)
     }
 
     /// Restart an application
-    pub async fn restart(&self, id: u32) -> Result<bool> {
+    ///
+    /// The restart count and crash-loop streak carry over to the new process
+    /// unless `reset_counter` is set, which clears them. A stopped app is
+    /// started.
+    pub async fn restart(&self, id: u32, reset_counter: bool) -> Result<bool> {
+        self.restart_for(id, reset_counter, "manual").await
+    }
+
+    /// Restart an application, recording `reason` on its restart event
+    async fn restart_for(&self, id: u32, reset_counter: bool, reason: &str) -> Result<bool> {
         // Get the spec first
-        let spec = {
-            let processes = self.processes.read();
-            processes.get(&id).map(|p| p.spec.clone())
-        };
+        let tracked = self.processes.with(id, |p| {
+            (p.spec.clone(), p.restart_count, p.state.restarts_in_window)
+        });
 
-        if let Some(spec) = spec {
+        if let Some((spec, restart_count, restarts_in_window)) = tracked {
             // Run on_restart hook if configured (before stop/start)
-            self.run_hook(&spec.hooks, HookEvent::Restart, id, &spec.name, None, None);
+            self.run_hook(&spec, HookEvent::Restart, id, None, None);
+            self.record_event(
+                AppEvent::new(id, spec.qualified_name(), EventKind::Restarted).with_reason(reason),
+            );
 
             self.stop(id).await?;
             tokio::time::sleep(Duration::from_millis(100)).await;
             self.start(spec).await?;
+
+            let (restart_count, restarts_in_window) = if reset_counter {
+                info!("Reset restart counters for app {}", id);
+                (0, 0)
+            } else {
+                (restart_count, restarts_in_window)
+            };
+            self.processes.with_mut(id, |proc| {
+                proc.restart_count = restart_count;
+                proc.state.restarts = restart_count;
+                proc.state.restarts_in_window = restarts_in_window;
+            });
             Ok(true)
         } else {
-            Ok(false)
+            // Not started since the daemon came up: start it from its saved spec
+            self.start_by_id(id).await
         }
     }
 
-    /// Delete an application
-    pub async fn delete(&self, id: u32) -> Result<bool> {
-        // Stop first if running
-        self.stop(id).await?;
-
-        // Remove from processes
-        self.processes.write().remove(&id);
+    /// Restart an app whose health checks failed, keeping its id and counters
+    async fn restart_unhealthy(&self, id: u32, attempt: u32) -> Result<()> {
+        // Skip if it was stopped or restarted by hand in the meantime
+        let unhealthy = self
+            .processes
+            .with(id, |proc| {
+                (proc.state.status.is_running() && !proc.state.healthy)
+                    .then(|| (proc.spec.clone(), proc.restart_count))
+            })
+            .flatten();
+        let Some((spec, restart_count)) = unhealthy else {
+            self.clear_next_restart(id);
+            return Ok(());
+        };
+        if self.is_supervision_paused() {
+            info!(
+                "Supervision paused, not restarting unhealthy app {}",
+                spec.name
+            );
+            self.clear_next_restart(id);
+            return Ok(());
+        }
 
-        // Delete from database
-        self.db.apps().delete(id).await?;
+        info!(
+            "Restarting unhealthy app {} (id: {}, attempt {})",
+            spec.name,
+            id,
+            attempt + 1
+        );
+        self.run_hook(&spec, HookEvent::Restart, id, None, None);
+        self.record_event(
+            AppEvent::new(id, spec.qualified_name(), EventKind::Restarted).with_reason("health"),
+        );
+        self.stop(id).await?;
+        self.launch(spec.clone()).await?;
+
+        let restart_count = restart_count + 1;
+        self.processes.with_mut(id, |proc| {
+            proc.restart_count = restart_count;
+            proc.state.restarts = restart_count;
+            proc.last_restart = Some(Instant::now());
+            proc.state.restarts_in_window = attempt + 1;
+            proc.state.next_restart_at = None;
+        });
 
-        info!("Deleted app (id: {})", id);
-        Ok(true)
+        self.notify_event(ProcessEvent::Restarted {
+            name: spec.name,
+            id,
+            restart_count,
+        });
+        Ok(())
     }
 
-    /// Get status of all apps
-    pub async fn status(&self) -> Result<Vec<AppInfo>> {
-        let apps = self.db.apps().get_all().await?;
-        let processes = self.processes.read();
-
+    /// Restart an app that crashed, keeping its id and counters. Nothing
+    /// happens if it was started or stopped meanwhile, or supervision was
+    /// paused during the delay.
+    async fn restart_crashed(&self, id: u32, attempt: u32) -> Result<()> {
+        let crashed = self
+            .processes
+            .with(id, |proc| {
+                (proc.state.status == AppStatus::Errored && proc.child.is_none())
+                    .then(|| (proc.spec.clone(), proc.restart_count))
+            })
+            .flatten();
+        let Some((spec, restart_count)) = crashed else {
+            self.clear_next_restart(id);
+            return Ok(());
+        };
+        if self.is_supervision_paused() {
+            info!(
+                "Supervision paused, not restarting crashed app {}",
+                spec.name
+            );
+            self.clear_next_restart(id);
+            return Ok(());
+        }
+
+        info!(
+            "Restarting crashed app {} (id: {}, attempt {})",
+            spec.name,
+            id,
+            attempt + 1
+        );
+        self.run_hook(&spec, HookEvent::Restart, id, None, None);
+        self.record_event(
+            AppEvent::new(id, spec.qualified_name(), EventKind::Restarted).with_reason("crash"),
+        );
+        self.launch(spec.clone()).await?;
+
+        let restart_count = restart_count + 1;
+        self.processes.with_mut(id, |proc| {
+            proc.restart_count = restart_count;
+            proc.state.restarts = restart_count;
+            proc.last_restart = Some(Instant::now());
+            proc.state.restarts_in_window = attempt + 1;
+            proc.state.next_restart_at = None;
+        });
+
+        self.notify_event(ProcessEvent::Restarted {
+            name: spec.name,
+            id,
+            restart_count,
+        });
+        Ok(())
+    }
+
+    /// Restart an app after a watched file changed, keeping its id and counters
+    async fn restart_watched(&self, id: u32, prebuilt: Option<BuildInfo>) -> Result<()> {
+        let watched = self
+            .processes
+            .with(id, |proc| {
+                proc.state
+                    .status
+                    .is_running()
+                    .then(|| (proc.spec.clone(), proc.restart_count))
+            })
+            .flatten();
+        let Some((spec, restart_count)) = watched else {
+            return Ok(());
+        };
+        if self.is_supervision_paused() {
+            info!(
+                "Supervision paused, not restarting {} for a file change",
+                spec.name
+            );
+            return Ok(());
+        }
+
+        info!("Restarting {} (id: {}) after a file change", spec.name, id);
+        self.run_hook(&spec, HookEvent::Restart, id, None, None);
+        self.record_event(
+            AppEvent::new(id, spec.qualified_name(), EventKind::Restarted).with_reason("watch"),
+        );
+        self.stop(id).await?;
+        let prepared = prebuilt.map(|build| PrepareResult::success("").with_build(build));
+        self.launch_prepared(spec.clone(), prepared, &ProgressSink::none())
+            .await?;
+
+        let restart_count = restart_count + 1;
+        self.processes.with_mut(id, |proc| {
+            proc.restart_count = restart_count;
+            proc.state.restarts = restart_count;
+            proc.last_restart = Some(Instant::now());
+        });
+
+        self.notify_event(ProcessEvent::Restarted {
+            name: spec.name,
+            id,
+            restart_count,
+        });
+        Ok(())
+    }
+
+    /// Re-read a changed env file into the app's (and its cluster instances')
+    /// environment, persist it, and restart or reload the app when a variable
+    /// actually changed
+    async fn apply_env_file(&self, id: u32) -> Result<()> {
+        let tracked = self
+            .processes
+            .with(id, |proc| {
+                proc.state
+                    .status
+                    .is_running()
+                    .then(|| (proc.spec.clone(), proc.restart_count))
+            })
+            .flatten();
+        let Some((spec, restart_count)) = tracked else {
+            return Ok(());
+        };
+        let (Some(path), Some(action)) = (spec.env_file.clone(), spec.watch_env_file) else {
+            return Ok(());
+        };
+        if self.is_supervision_paused() {
+            info!(
+                "Supervision paused, not applying the changed env file of {}",
+                spec.name
+            );
+            return Ok(());
+        }
+
+        let mut vars = HashMap::new();
+        load_env_file(&path, &mut vars)?;
+        if !spec.clone().apply_env_file(vars.clone()) {
+            debug!(
+                "Env file of {} changed, but none of its variables did",
+                spec.name
+            );
+            return Ok(());
+        }
+        let envs: Vec<(u32, HashMap<String, String>)> = self
+            .app_and_instances(id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|app_id| {
+                self.processes.with_mut(app_id, |proc| {
+                    proc.spec.apply_env_file(vars.clone());
+                    (app_id, proc.spec.env.clone())
+                })
+            })
+            .collect();
+        for (app_id, env) in envs {
+            self.db.apps().update_env(app_id, &env).await?;
+        }
+
+        match action {
+            EnvFileAction::Restart => {
+                info!(
+                    "Env file {} of {} changed, restarting",
+                    path.display(),
+                    spec.name
+                );
+                // The tracked spec already carries the new variables
+                self.restart_for(id, false, "env_file").await?;
+
+                let restart_count = restart_count + 1;
+                self.processes.with_mut(id, |proc| {
+                    proc.restart_count = restart_count;
+                    proc.state.restarts = restart_count;
+                    proc.last_restart = Some(Instant::now());
+                });
+                self.notify_event(ProcessEvent::Restarted {
+                    name: spec.name,
+                    id,
+                    restart_count,
+                });
+            }
+            EnvFileAction::Reload => {
+                info!(
+                    "Env file {} of {} changed, reloading",
+                    path.display(),
+                    spec.name
+                );
+                self.reload(id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Carry out a log trigger, unless the process that printed the line is gone
+    async fn fire_trigger(
+        &self,
+        id: u32,
+        pid: u32,
+        trigger: LogTrigger,
+        line: String,
+    ) -> Result<()> {
+        let matched = self
+            .processes
+            .with(id, |proc| {
+                (proc.state.pid == Some(pid) && proc.state.status.is_running())
+                    .then(|| (proc.spec.clone(), proc.restart_count))
+            })
+            .flatten();
+        let Some((spec, restart_count)) = matched else {
+            return Ok(());
+        };
+        if self.drains.read().contains_key(&id) {
+            debug!(
+                "Log trigger '{}' matched for {}, which is draining",
+                trigger.pattern, spec.name
+            );
+            return Ok(());
+        }
+        info!(
+            "Log trigger '{}' matched for {} (id: {}), action: {}",
+            trigger.pattern, spec.name, id, trigger.action
+        );
+
+        match trigger.action {
+            TriggerAction::Notify => {
+                self.notify_event(ProcessEvent::LogMatch {
+                    name: spec.name,
+                    id,
+                    pattern: trigger.pattern,
+                    line,
+                });
+            }
+            TriggerAction::Hook => {
+                let Some(script) = trigger.run else {
+                    return Ok(());
+                };
+                tokio::spawn(async move {
+                    let extra = [
+                        ("log_line", line.as_str()),
+                        ("trigger_pattern", trigger.pattern.as_str()),
+                    ];
+                    let call = HookCall {
+                        app_id: id,
+                        app_name: &spec.name,
+                        event: "log_match",
+                        pid: Some(pid),
+                        exit_code: None,
+                        extra: &extra,
+                    };
+                    let result =
+                        run_hook_script(&Hook::Shell(script), &call, &HookEnv::of(&spec)).await;
+                    if let Err(e) = result {
+                        error!("Log trigger hook failed for {}: {}", spec.name, e);
+                    }
+                });
+            }
+            TriggerAction::Restart => {
+                if self.is_supervision_paused() {
+                    info!(
+                        "Supervision paused, not restarting {} for log trigger '{}'",
+                        spec.name, trigger.pattern
+                    );
+                    return Ok(());
+                }
+                self.run_hook(&spec, HookEvent::Restart, id, None, None);
+                self.record_event(
+                    AppEvent::new(id, spec.qualified_name(), EventKind::Restarted)
+                        .with_reason(format!("log trigger: {}", trigger.pattern)),
+                );
+                self.stop(id).await?;
+                self.launch(spec.clone()).await?;
+
+                let restart_count = restart_count + 1;
+                self.processes.with_mut(id, |proc| {
+                    proc.restart_count = restart_count;
+                    proc.state.restarts = restart_count;
+                    proc.last_restart = Some(Instant::now());
+                });
+                self.notify_event(ProcessEvent::Restarted {
+                    name: spec.name,
+                    id,
+                    restart_count,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Forget a scheduled restart that is not going to happen
+    fn clear_next_restart(&self, id: u32) {
+        self.processes.with_mut(id, |proc| {
+            proc.state.next_restart_at = None;
+        });
+    }
+
+    /// Stop an app whose health checks failed and leave it errored
+    async fn stop_unhealthy(&self, id: u32) -> Result<()> {
+        let unhealthy = self
+            .processes
+            .with(id, |proc| {
+                proc.state.status.is_running() && !proc.state.healthy
+            })
+            .unwrap_or(false);
+        if !unhealthy || self.is_supervision_paused() {
+            return Ok(());
+        }
+
+        info!("Stopping unhealthy app {}", id);
+        self.stop(id).await?;
+        self.processes.with_mut(id, |proc| {
+            proc.state.status = AppStatus::Errored;
+        });
+        Ok(())
+    }
+
+    /// Delete an application
+    pub async fn delete(&self, id: u32) -> Result<bool> {
+        self.remove(id, false).await
+    }
+
+    /// Delete an application along with its logs, hook log and history
+    pub async fn purge(&self, id: u32) -> Result<bool> {
+        self.remove(id, true).await
+    }
+
+    async fn remove(&self, id: u32, purge: bool) -> Result<bool> {
+        // A cluster goes with its instances
+        let instances = self
+            .processes
+            .with(id, |proc| proc.cluster_instance_ids.clone())
+            .unwrap_or_default();
+        for instance_id in instances {
+            self.remove_one(instance_id, purge).await?;
+        }
+        self.remove_one(id, purge).await
+    }
+
+    async fn remove_one(&self, id: u32, purge: bool) -> Result<bool> {
+        // Stop first if running
+        self.stop(id).await?;
+
+        // The tracked spec knows about custom log paths
+        let tracked = self.processes.remove(id).map(|proc| proc.spec);
+        let spec = match tracked {
+            Some(spec) => Some(spec),
+            None => self.db.apps().get_by_id(id).await?,
+        };
+        self.health_history
+            .write()
+            .retain(|(app_id, _), _| *app_id != id);
+        self.drains.write().remove(&id);
+        self.ports.lock().release(id);
+
+        // Delete from database
+        if !purge {
+            self.db.apps().delete(id).await?;
+            info!("Deleted app (id: {})", id);
+            return Ok(true);
+        }
+
+        self.db.apps().delete_with_history(id).await?;
+        if let Some(spec) = spec {
+            let mut paths: Vec<PathBuf> = [spec.stdout_log_path(), spec.stderr_log_path()]
+                .iter()
+                .flat_map(|path| oxidepm_logs::log_files(path))
+                .map(|file| file.path)
+                .collect();
+            paths.push(hook_log_path(&spec.log_name()));
+            paths.sort();
+            paths.dedup();
+            for path in paths {
+                match std::fs::remove_file(&path) {
+                    Ok(()) => debug!("Removed {}", path.display()),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => warn!("Failed to remove {}: {}", path.display(), e),
+                }
+            }
+        }
+        info!("Deleted app (id: {}) with its logs and history", id);
+        Ok(true)
+    }
+
+    /// Get status of all apps
+    pub async fn status(&self) -> Result<Vec<AppInfo>> {
+        let apps = self.db.apps().get_all().await?;
+        let processes = self.processes.published_all();
+        let drains = self.drains.read();
+
         let mut result = Vec::new();
         for spec in apps {
-            let state = if let Some(proc) = processes.get(&spec.id) {
-                proc.state.clone()
+            // Tracked processes carry the full spec (tags, health check, ...)
+            // which the database does not persist
+            let info = if let Some(proc) = processes.get(&spec.id) {
+                AppInfo::new(proc.spec.clone(), reported_state(&processes, &drains, proc))
             } else {
-                RunState::new(spec.id)
+                let state = RunState::new(spec.id);
+                AppInfo::new(spec, state)
             };
-            result.push(AppInfo::new(spec, state));
+            result.push(info);
         }
 
         Ok(result)
     }
 
+    /// Daemon-wide overview: app counts, combined usage and host figures
+    pub async fn system_info(&self) -> Result<SystemInfo> {
+        let apps = self.status().await?;
+        let mut by_status = BTreeMap::new();
+        let mut cpu_percent = 0.0;
+        let mut memory_bytes = 0;
+        for app in &apps {
+            *by_status
+                .entry(app.state.status.as_str().to_string())
+                .or_insert(0) += 1;
+            cpu_percent += app.state.cpu_percent;
+            memory_bytes += app.state.memory_bytes;
+        }
+        let db = match self.db.health().await {
+            Ok(health) => Some(health),
+            Err(e) => {
+                warn!("Failed to read database health: {}", e);
+                None
+            }
+        };
+
+        // The metrics collector keeps this refreshed
+        let usage = *self.host_usage.read();
+        let load = System::load_average();
+        let host = HostInfo {
+            hostname: System::host_name(),
+            cpu_count: usage.cpu_count,
+            load_average: [load.one, load.five, load.fifteen],
+            total_memory_bytes: usage.total_memory_bytes,
+            used_memory_bytes: usage.used_memory_bytes,
+            uptime_secs: System::uptime(),
+        };
+
+        Ok(SystemInfo {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            daemon_pid: std::process::id(),
+            daemon_uptime_secs: self.started_at.elapsed().as_secs(),
+            total_processes: apps.len(),
+            by_status,
+            cpu_percent,
+            memory_bytes,
+            host,
+            db,
+        })
+    }
+
     /// Get info for a single app
     pub async fn show(&self, selector: &Selector) -> Result<Option<AppInfo>> {
         let spec = match selector {
             Selector::All => return Ok(None),
             Selector::ById(id) => self.db.apps().get_by_id(*id).await?,
-            Selector::ByName(name) => self.db.apps().get_by_name(name).await?,
-            Selector::ByTag(tag) => {
-                // For tags, return the first matching app
+            Selector::ByName(name) => self.find_by_name(name).await?,
+            Selector::ByTag(_) | Selector::ByNamespace(_) => {
+                // For groups, return the first matching app
                 let apps = self.db.apps().get_all().await?;
-                apps.into_iter().find(|app| app.tags.contains(tag))
+                apps.into_iter().find(|app| selector.matches(app))
             }
         };
 
         if let Some(spec) = spec {
-            let processes = self.processes.read();
-            let state = if let Some(proc) = processes.get(&spec.id) {
-                proc.state.clone()
+            let stats = self.db.stats().get(spec.id).await?;
+            let last_build = self
+                .db
+                .runs()
+                .get_latest(spec.id)
+                .await?
+                .and_then(|run| run.build);
+            let processes = self.processes.published_all();
+            let drains = self.drains.read();
+            // The tracked spec carries fields the database does not store (log paths etc.)
+            let (spec, mut state) = if let Some(proc) = processes.get(&spec.id) {
+                (proc.spec.clone(), reported_state(&processes, &drains, proc))
             } else {
-                RunState::new(spec.id)
+                let id = spec.id;
+                (spec, RunState::new(id))
             };
+            state.stats = stats;
+            state.build = state.build.or(last_build);
             Ok(Some(AppInfo::new(spec, state)))
         } else {
             Ok(None)
         }
     }
 
-    /// Get logs for an app
+    /// Spec of the app whose logs a selector refers to
+    async fn log_spec(&self, selector: &Selector) -> Result<AppSpec> {
+        let spec = match selector {
+            Selector::All => return Err(Error::InvalidSelector("Cannot get logs for 'all'".into())),
+            Selector::ById(id) => self.db.apps().get_by_id(*id).await?,
+            Selector::ByName(name) => self.find_by_name(name).await?,
+            Selector::ByTag(_) | Selector::ByNamespace(_) => {
+                // For groups, return logs from first matching app
+                let apps = self.db.apps().get_all().await?;
+                apps.into_iter().find(|app| selector.matches(app))
+            }
+        };
+
+        let spec = spec.ok_or_else(|| Error::AppNotFound(selector.to_string()))?;
+        // Log paths can be customised; the database row does not carry them
+        Ok(self.processes.published(spec.id).map_or(spec, |p| p.spec))
+    }
+
+    /// Get a page of one of an app's log streams, across rotated files
+    pub async fn log_page(
+        &self,
+        selector: &Selector,
+        stream: LogStream,
+        lines: usize,
+        before: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<LogPage> {
+        let spec = self.log_spec(selector).await?;
+        let path = match stream {
+            LogStream::Out => spec.stdout_log_path(),
+            LogStream::Err => spec.stderr_log_path(),
+        };
+        let lines = lines.clamp(1, constants::MAX_LOG_PAGE_LINES);
+        oxidepm_logs::read_page(&path, stream, lines, before, after)
+    }
+
+    /// Get logs for an app, the last `lines` or those written within `since`
     pub async fn logs(
         &self,
         selector: &Selector,
         lines: usize,
         stdout: bool,
         stderr: bool,
+        since: Option<Duration>,
     ) -> Result<Vec<String>> {
-        let spec = match selector {
-            Selector::All => return Err(Error::InvalidSelector("Cannot get logs for 'all'".into())),
-            Selector::ById(id) => self.db.apps().get_by_id(*id).await?,
-            Selector::ByName(name) => self.db.apps().get_by_name(name).await?,
-            Selector::ByTag(tag) => {
-                // For tags, return logs from first matching app
-                let apps = self.db.apps().get_all().await?;
-                apps.into_iter().find(|app| app.tags.contains(tag))
+        let spec = self.log_spec(selector).await?;
+        let (stdout_path, stderr_path) = (spec.stdout_log_path(), spec.stderr_log_path());
+        // An age reaching past the epoch covers everything
+        let since = since
+            .and_then(|age| chrono::Duration::from_std(age).ok())
+            .and_then(|age| chrono::Utc::now().checked_sub_signed(age));
+        let read = |path: PathBuf| {
+            let reader = LogReader::new(path);
+            match since {
+                Some(since) => reader.since(since, lines),
+                None => reader.tail(lines),
             }
         };
 
-        let spec = spec.ok_or_else(|| Error::AppNotFound(selector.to_string()))?;
-
         let mut all_lines = Vec::new();
 
         if stdout || !stderr {
-            let reader = LogReader::new(oxidepm_logs::stdout_path(&spec.name));
-            all_lines.extend(reader.tail(lines)?);
+            all_lines.extend(read(stdout_path.clone())?);
         }
 
-        if stderr || !stdout {
-            let reader = LogReader::new(oxidepm_logs::stderr_path(&spec.name));
-            all_lines.extend(reader.tail(lines)?);
+        // A merged log already holds stderr
+        if (stderr || !stdout) && stderr_path != stdout_path {
+            all_lines.extend(read(stderr_path)?);
         }
 
         // Sort by timestamp if we have both
@@ -535,47 +1934,342 @@ impl Supervisor {
         Ok(all_lines)
     }
 
-    /// Save current process list
-    pub async fn save(&self) -> Result<usize> {
-        let apps = self.db.apps().get_all().await?;
-        let path = constants::saved_path();
+    /// Save current process list, to `output` or the default saved state
+    pub async fn save(&self, output: Option<&Path>) -> Result<(usize, PathBuf)> {
+        let apps = self.saved_specs().await?;
+        let desired = self.db.apps().desired_states().await?;
+        let stopped = apps
+            .iter()
+            .filter(|spec| desired.get(&spec.id) == Some(&DesiredState::Stopped))
+            .map(AppSpec::qualified_name)
+            .collect();
+        let count = apps.len();
+        let state = SavedState::new(apps).with_stopped(stopped);
+        let path = output.map_or_else(constants::saved_path, Path::to_path_buf);
+        let _saving = self.saving.lock();
+
+        // Compact snapshot for the fast resurrect path; only the default
+        // saved state has one
+        if output.is_none() {
+            if let Err(e) = snapshot::write(&constants::snapshot_path(), &state) {
+                warn!("Failed to write snapshot: {}", e);
+            }
+        }
+
+        // Write then rename, so a crash mid-save never leaves a torn file
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, state.to_json()?)?;
+        std::fs::rename(&tmp, &path)?;
+
+        info!("Saved {} apps to {}", count, path.display());
+        Ok((count, path))
+    }
+
+    /// The specs `save` writes. The registry does not store every field (health
+    /// checks, hooks, sandbox, limits, ports, instances, ...), so tracked apps
+    /// are saved as they run and only untracked ones come from the registry.
+    /// Cluster instances are left out: their parent's spec recreates them.
+    async fn saved_specs(&self) -> Result<Vec<AppSpec>> {
+        let registered = self.db.apps().get_all().await?;
+        let mut processes = self.processes.published_all();
+        Ok(registered
+            .into_iter()
+            .filter_map(|spec| match processes.remove(&spec.id) {
+                Some(proc) if proc.spec.instance_id.is_some() => None,
+                Some(proc) => Some(proc.spec),
+                None => Some(spec),
+            })
+            .collect())
+    }
+
+    /// Load saved apps from `file`, or from the default saved state
+    /// (preferring the binary snapshot over JSON)
+    fn load_saved(&self, file: Option<&Path>) -> Result<Option<(SavedState, Vec<String>)>> {
+        let path = match file {
+            Some(file) if !file.exists() => return Err(Error::FileNotFound(file.to_path_buf())),
+            Some(file) => file.to_path_buf(),
+            None => {
+                let snapshot_path = constants::snapshot_path();
+                if snapshot_path.exists() {
+                    match snapshot::read(&snapshot_path) {
+                        Ok(state) => return Ok(Some((state, Vec::new()))),
+                        Err(e) => {
+                            warn!("Ignoring unreadable snapshot, falling back to JSON: {}", e)
+                        }
+                    }
+                }
+                let path = constants::saved_path();
+                if !path.exists() {
+                    return Ok(None);
+                }
+                path
+            }
+        };
 
-        let json = serde_json::to_string_pretty(&apps)?;
-        std::fs::write(&path, json)?;
+        let (state, warnings) = SavedState::parse(&std::fs::read_to_string(&path)?)?;
+        for warning in &warnings {
+            warn!("{}: {}", path.display(), warning);
+        }
+        Ok(Some((state, warnings)))
+    }
 
-        info!("Saved {} apps to {}", apps.len(), path.display());
-        Ok(apps.len())
+    /// Record whether an app should be running
+    pub async fn set_desired_state(&self, id: u32, state: DesiredState) -> Result<()> {
+        self.db.apps().set_desired_state(id, state).await?;
+        Ok(())
     }
 
     /// Resurrect saved processes
-    pub async fn resurrect(&self) -> Result<usize> {
-        let path = constants::saved_path();
-        if !path.exists() {
-            return Ok(0);
+    ///
+    /// Saved apps missing from the database are inserted in one transaction,
+    /// then every saved app that is not running is started in `start_order`:
+    /// lower priority bands first, and within a band an app starts only once
+    /// the apps it `depends_on` have. The apps of a tier start concurrently
+    /// (bounded). Apps saved as stopped stay registered but stopped unless
+    /// `all` is set.
+    pub async fn resurrect(&self, file: Option<&Path>, all: bool) -> Result<ResurrectReport> {
+        self.resurrect_with_progress(file, all, &ProgressSink::none())
+            .await
+    }
+
+    /// Resurrect saved processes, with a line and a step on `progress` as
+    /// each app started or failed to
+    pub async fn resurrect_with_progress(
+        &self,
+        file: Option<&Path>,
+        all: bool,
+        progress: &ProgressSink,
+    ) -> Result<ResurrectReport> {
+        let Some((saved, warnings)) = self.load_saved(file)? else {
+            return Ok(ResurrectReport::default());
+        };
+        let mut report = ResurrectReport {
+            warnings,
+            ..Default::default()
+        };
+
+        let mut registered: HashMap<String, u32> = self
+            .db
+            .apps()
+            .get_all()
+            .await?
+            .iter()
+            .map(|spec| (spec.qualified_name(), spec.id))
+            .collect();
+        let new_apps: Vec<AppSpec> = saved
+            .apps
+            .iter()
+            .filter(|spec| !registered.contains_key(&spec.qualified_name()))
+            .cloned()
+            .collect();
+        let ids = self.db.apps().insert_many(&new_apps).await?;
+        registered.extend(new_apps.iter().map(AppSpec::qualified_name).zip(ids));
+
+        let running: HashSet<u32> = self
+            .processes
+            .published_all()
+            .into_iter()
+            .filter(|(_, proc)| proc.state.status.is_running())
+            .map(|(id, _)| id)
+            .collect();
+        let keep_stopped: Vec<bool> = saved
+            .apps
+            .iter()
+            .map(|spec| !all && saved.desired_state(spec) == DesiredState::Stopped)
+            .collect();
+
+        let order = start_order(&saved.apps, &mut report.warnings);
+
+        let mut pending: BTreeMap<(i32, usize), Vec<AppSpec>> = BTreeMap::new();
+        for ((mut spec, keep_stopped), tier) in saved.apps.into_iter().zip(keep_stopped).zip(order)
+        {
+            spec.id = registered[&spec.qualified_name()];
+            if running.contains(&spec.id) {
+                continue;
+            }
+            if keep_stopped {
+                self.db
+                    .apps()
+                    .set_desired_state(spec.id, DesiredState::Stopped)
+                    .await?;
+                report.stopped += 1;
+                continue;
+            }
+            pending.entry(tier).or_default().push(spec);
         }
 
-        let content = std::fs::read_to_string(&path)?;
-        let apps: Vec<AppSpec> = serde_json::from_str(&content)?;
+        let total = pending.values().map(Vec::len).sum();
+        let finished = &AtomicUsize::new(0);
+        for tier in pending.into_values() {
+            report.started += stream::iter(tier)
+                .map(|spec| async move {
+                    let name = spec.qualified_name();
+                    let started = match self.start(spec).await {
+                        Ok(_) => true,
+                        Err(e) => {
+                            warn!("Failed to resurrect {}: {}", name, e);
+                            false
+                        }
+                    };
+                    let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                    let status = if started { "ok" } else { "failed" };
+                    progress.send(&format!("[{}/{}] {} {}", done, total, name, status));
+                    progress.step(done, total);
+                    started
+                })
+                .buffer_unordered(constants::RESURRECT_CONCURRENCY)
+                .filter(|started| std::future::ready(*started))
+                .count()
+                .await;
+        }
 
-        let mut count = 0;
-        for spec in apps {
-            // Check if already in database
-            if self.db.apps().get_by_name(&spec.name).await?.is_none() {
-                // Insert into database
-                let mut new_spec = spec.clone();
-                new_spec.id = self.db.apps().insert(&spec).await?;
-
-                // Start the process
-                if let Err(e) = self.start(new_spec).await {
-                    warn!("Failed to resurrect {}: {}", spec.name, e);
-                } else {
-                    count += 1;
+        info!(
+            "Resurrected {} processes ({} left stopped)",
+            report.started, report.stopped
+        );
+        Ok(report)
+    }
+
+    /// Run `op` on each app, a bounded number at a time, and report what it
+    /// did to each one (in `ids` order)
+    pub async fn run_bulk<'a, F, Fut>(&'a self, ids: Vec<u32>, op: F) -> Result<Vec<AppOpResult>>
+    where
+        F: Fn(u32) -> Fut,
+        Fut: Future<Output = Result<bool>> + 'a,
+    {
+        self.run_bulk_with(ids, &BulkOptions::default(), &ProgressSink::none(), op)
+            .await
+    }
+
+    /// `run_bulk` with the options of the request: how many apps at once,
+    /// whether a failure stops the apps not yet begun (they are reported as
+    /// skipped), and a `[done/total] name outcome` line on `progress` as each
+    /// app finishes
+    pub async fn run_bulk_with<'a, F, Fut>(
+        &'a self,
+        ids: Vec<u32>,
+        options: &BulkOptions,
+        progress: &ProgressSink,
+        op: F,
+    ) -> Result<Vec<AppOpResult>>
+    where
+        F: Fn(u32) -> Fut,
+        Fut: Future<Output = Result<bool>> + 'a,
+    {
+        // Look names up front: a delete removes the app
+        let names: HashMap<u32, String> = self
+            .db
+            .apps()
+            .get_all()
+            .await?
+            .iter()
+            .map(|spec| (spec.id, spec.qualified_name()))
+            .collect();
+
+        let total = ids.len();
+        let finished = AtomicUsize::new(0);
+        let halted = AtomicBool::new(false);
+        let (names, finished, halted) = (&names, &finished, &halted);
+        Ok(stream::iter(ids)
+            .map(|id| {
+                let op = op(id);
+                async move {
+                    let name = names.get(&id).cloned().unwrap_or_else(|| id.to_string());
+                    if halted.load(Ordering::Relaxed) {
+                        return AppOpResult {
+                            id,
+                            name,
+                            outcome: AppOpOutcome::Skipped,
+                        };
+                    }
+                    let outcome = match op.await {
+                        Ok(true) => AppOpOutcome::Done,
+                        Ok(false) => AppOpOutcome::Skipped,
+                        Err(e) => {
+                            error!("Operation failed for {}: {}", name, e);
+                            if options.halt_on_error {
+                                halted.store(true, Ordering::Relaxed);
+                            }
+                            AppOpOutcome::Failed {
+                                error: e.to_string(),
+                            }
+                        }
+                    };
+                    let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                    let status = match &outcome {
+                        AppOpOutcome::Done => "ok".to_string(),
+                        AppOpOutcome::Skipped => "skipped".to_string(),
+                        AppOpOutcome::AlreadyStopped => "already stopped".to_string(),
+                        AppOpOutcome::NotFound => "not found".to_string(),
+                        AppOpOutcome::Failed { error } => format!("failed: {}", error),
+                    };
+                    progress.send(&format!("[{}/{}] {} {}", done, total, name, status));
+                    progress.step(done, total);
+                    AppOpResult { id, name, outcome }
+                }
+            })
+            .buffered(options.concurrency())
+            .collect()
+            .await)
+    }
+
+    /// Start an app that is not running from its spec; false when it already runs
+    pub async fn start_by_id(&self, id: u32) -> Result<bool> {
+        let tracked = match self.processes.published(id) {
+            Some(proc) if proc.state.status.is_running() => return Ok(false),
+            Some(proc) => Some(proc.spec),
+            None => None,
+        };
+        let spec = match tracked {
+            Some(spec) => spec,
+            None => self
+                .db
+                .apps()
+                .get_by_id(id)
+                .await?
+                .ok_or_else(|| Error::AppNotFound(id.to_string()))?,
+        };
+        self.start(spec).await?;
+        Ok(true)
+    }
+
+    /// Wait until the app runs and, if it has a health check, passed one
+    /// since it started (otherwise stayed up for `ROLLOUT_SETTLE_MS`), and is
+    /// ready if it has a readiness check or a warm-up. Gives up when the app stops running
+    /// or `timeout` elapses; the error says why.
+    pub async fn wait_healthy(&self, id: u32, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let verdict = match self.processes.published(id) {
+                Some(proc) if proc.state.status == AppStatus::Running => {
+                    let healthy = passed_health_check(&proc);
+                    let ready = (proc.spec.readiness_check.is_none() && proc.spec.warmup.is_none())
+                        || proc.state.ready;
+                    if healthy && ready {
+                        Some(Ok(()))
+                    } else {
+                        None
+                    }
                 }
+                Some(proc) if proc.state.status.is_running() => None,
+                Some(proc) => Some(Err(Error::ProcessNotRunning(format!(
+                    "{} is {}",
+                    proc.spec.qualified_name(),
+                    proc.state.status
+                )))),
+                None => Some(Err(Error::AppNotFound(id.to_string()))),
+            };
+            if let Some(verdict) = verdict {
+                return verdict;
             }
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout(format!(
+                    "not healthy after {}s",
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
         }
-
-        info!("Resurrected {} processes", count);
-        Ok(count)
     }
 
     /// Resolve selector to app IDs
@@ -593,12 +2287,25 @@ impl Supervisor {
                 }
             }
             Selector::ByName(name) => {
-                if let Some(app) = self.db.apps().get_by_name(name).await? {
+                if let Some(app) = self.find_by_name(name).await? {
                     Ok(vec![app.id])
                 } else {
                     Err(Error::AppNotFound(name.clone()))
                 }
             }
+            Selector::ByNamespace(namespace) => {
+                let apps = self.db.apps().get_all().await?;
+                let matching: Vec<u32> = apps
+                    .into_iter()
+                    .filter(|app| app.namespace == *namespace)
+                    .map(|app| app.id)
+                    .collect();
+                if matching.is_empty() {
+                    Err(Error::AppNotFound(format!("ns:{}", namespace)))
+                } else {
+                    Ok(matching)
+                }
+            }
             Selector::ByTag(tag) => {
                 let apps = self.db.apps().get_all().await?;
                 let matching: Vec<u32> = apps
@@ -615,77 +2322,612 @@ impl Supervisor {
         }
     }
 
-    /// Graceful reload - start new instance, wait for healthy, then stop old
-    pub async fn reload(&self, id: u32) -> Result<bool> {
-        // Get the current spec
-        let (spec, is_cluster) = {
-            let processes = self.processes.read();
-            match processes.get(&id) {
-                Some(proc) => {
-                    let is_cluster = !proc.cluster_instance_ids.is_empty();
-                    (proc.spec.clone(), is_cluster)
-                }
-                None => return Ok(false),
-            }
-        };
-
-        info!("Starting graceful reload for {} (id: {})", spec.name, id);
+    /// Qualified names of the apps `selector` picks
+    pub async fn resolve_names(&self, selector: &Selector) -> Result<BTreeSet<String>> {
+        let ids: HashSet<u32> = self.resolve_selector(selector).await?.into_iter().collect();
+        Ok(self
+            .db
+            .apps()
+            .get_all()
+            .await?
+            .iter()
+            .filter(|spec| ids.contains(&spec.id))
+            .map(AppSpec::qualified_name)
+            .collect())
+    }
 
-        // Handle cluster reload differently
-        if is_cluster {
-            return self.reload_cluster(id).await;
+    /// Look up an app by `name` or `namespace/name`
+    ///
+    /// A bare name prefers the default namespace, then falls back to the only
+    /// namespace that has an app with that name.
+    async fn find_by_name(&self, name: &str) -> Result<Option<AppSpec>> {
+        if let Some((namespace, name)) = name.split_once('/') {
+            return self.db.apps().get_by_name(namespace, name).await;
+        }
+        if let Some(app) = self
+            .db
+            .apps()
+            .get_by_name(constants::DEFAULT_NAMESPACE, name)
+            .await?
+        {
+            return Ok(Some(app));
         }
 
-        // Single instance reload
-        self.reload_single(id, spec).await
+        let mut apps = self.db.apps().find_by_name(name).await?;
+        if apps.len() > 1 {
+            let candidates: Vec<String> = apps.iter().map(AppSpec::qualified_name).collect();
+            return Err(Error::InvalidSelector(format!(
+                "'{}' exists in several namespaces ({}); use <namespace>/{}",
+                name,
+                candidates.join(", "),
+                name
+            )));
+        }
+        Ok(apps.pop())
     }
 
-    /// Reload a single instance with zero-downtime
-    async fn reload_single(&self, old_id: u32, spec: AppSpec) -> Result<bool> {
-        // Create a temporary spec with a new name for the new instance
-        let mut new_spec = spec.clone();
-        new_spec.name = format!("{}-reload", spec.name);
-        new_spec.id = 0; // Will get new ID
-
-        // Start the new instance
-        info!("Starting new instance for reload: {}", new_spec.name);
-        let new_id = match self.start_single(new_spec.clone()).await {
-            Ok(id) => id,
-            Err(e) => {
-                error!("Failed to start new instance for reload: {}", e);
-                return Err(e);
-            }
+    /// Reconcile running apps with a declared app list
+    ///
+    /// New apps are started, apps whose definition changed are replaced with
+    /// the new spec, and with `prune` apps missing from the list are deleted.
+    /// Declared apps that exist unchanged but are not running are started.
+    pub async fn apply(
+        &self,
+        specs: Vec<AppSpec>,
+        prune: bool,
+        dry_run: bool,
+    ) -> Result<ApplyReport> {
+        let current: HashMap<String, AppInfo> = self
+            .status()
+            .await?
+            .into_iter()
+            .map(|info| (info.spec.qualified_name(), info))
+            .collect();
+        let declared: HashSet<String> = specs.iter().map(AppSpec::qualified_name).collect();
+
+        let mut report = ApplyReport {
+            dry_run,
+            ..Default::default()
         };
+        let mut to_start = Vec::new();
+        let mut to_replace = Vec::new();
+
+        for spec in specs.iter().cloned() {
+            let name = spec.qualified_name();
+            match current.get(&name) {
+                None => {
+                    report.started.push(name);
+                    to_start.push(spec);
+                }
+                Some(info) if info.spec.definition_eq(&spec) && self.is_app_running(info) => {
+                    report.unchanged.push(name);
+                }
+                Some(info) if info.spec.definition_eq(&spec) => {
+                    report.started.push(name);
+                    to_start.push(spec);
+                }
+                Some(info) => {
+                    report.reloaded.push(name);
+                    to_replace.push((info.spec.id, spec));
+                }
+            }
+        }
 
-        // Wait for health check if configured
-        if spec.health_check.is_some() {
-            info!("Waiting for new instance to become healthy...");
-            if !self.wait_for_healthy(new_id, Duration::from_secs(30)).await {
-                error!("New instance failed health check, aborting reload");
-                let _ = self.stop(new_id).await;
-                let _ = self.delete(new_id).await;
-                return Err(Error::HealthCheckFailed);
+        let mut to_delete = Vec::new();
+        if prune {
+            // Only prune namespaces the declaration covers, so projects
+            // sharing a daemon don't delete each other's apps
+            let namespaces: HashSet<&str> =
+                specs.iter().map(|spec| spec.namespace.as_str()).collect();
+            // Cluster instances go away with their parent
+            let mut stale: Vec<&AppInfo> = current
+                .values()
+                .filter(|info| info.spec.instance_id.is_none())
+                .filter(|info| namespaces.contains(info.spec.namespace.as_str()))
+                .filter(|info| !declared.contains(&info.spec.qualified_name()))
+                .collect();
+            stale.sort_by_key(|info| info.spec.id);
+            for info in stale {
+                report.deleted.push(info.spec.qualified_name());
+                to_delete.push(info.spec.id);
             }
-            info!("New instance is healthy");
-        } else {
-            // No health check, wait a brief moment for startup
-            tokio::time::sleep(Duration::from_millis(500)).await;
         }
 
-        // Stop the old instance
-        info!("Stopping old instance (id: {})", old_id);
-        self.stop(old_id).await?;
+        if dry_run {
+            return Ok(report);
+        }
 
-        // Rename new instance to original name
-        {
-            let mut processes = self.processes.write();
-            if let Some(proc) = processes.get_mut(&new_id) {
-                proc.spec.name = spec.name.clone();
+        for id in to_delete {
+            if let Err(e) = self.remove_app(id).await {
+                error!("Apply: failed to delete app {}: {}", id, e);
             }
         }
 
-        // Update database
+        for (id, spec) in to_replace {
+            let name = spec.qualified_name();
+            let result = match self.remove_app(id).await {
+                Ok(()) => self.start(spec).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                report.reloaded.retain(|n| n != &name);
+                report.failed.push(format!("{}: {}", name, e));
+            }
+        }
+
+        for spec in to_start {
+            let name = spec.qualified_name();
+            if let Err(e) = self.start(spec).await {
+                report.started.retain(|n| n != &name);
+                report.failed.push(format!("{}: {}", name, e));
+            }
+        }
+
+        info!(
+            "Apply finished: {} started, {} reloaded, {} deleted, {} failed",
+            report.started.len(),
+            report.reloaded.len(),
+            report.deleted.len(),
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    /// Spec an app runs with: the tracked one when available, else the stored one
+    /// Ids with cluster parents replaced by their instances
+    pub fn expand_clusters(&self, ids: &[u32]) -> Vec<u32> {
+        let processes = self.processes.published_all();
+        ids.iter()
+            .flat_map(|id| match processes.get(id) {
+                Some(proc) if !proc.cluster_instance_ids.is_empty() => {
+                    proc.cluster_instance_ids.clone()
+                }
+                _ => vec![*id],
+            })
+            .collect()
+    }
+
+    /// Where the audit log is stored
+    pub fn audit_store(&self) -> Arc<dyn AuditStore> {
+        self.db.audit()
+    }
+
+    /// Latest events for the given apps (including cluster instances), oldest first
+    pub async fn events(&self, ids: &[u32], limit: usize) -> Result<Vec<AppEvent>> {
+        let mut ids = ids.to_vec();
+        ids.extend(self.expand_clusters(&ids));
+        ids.sort_unstable();
+        ids.dedup();
+        self.db.events().get_by_apps(&ids, limit).await
+    }
+
+    /// Health state and history of the given apps, one entry per liveness or
+    /// readiness check; cluster parents expand to their instances
+    pub fn health(&self, ids: &[u32]) -> Vec<AppHealth> {
+        let ids = self.expand_clusters(ids);
+        let processes = self.processes.published_all();
+        let history = self.health_history.read();
+
+        let mut apps = Vec::new();
+        for id in ids {
+            let Some(proc) = processes.get(&id) else {
+                continue;
+            };
+            let checks = [
+                (
+                    Probe::Liveness,
+                    &proc.spec.health_check,
+                    proc.state.healthy,
+                    proc.state.health_check_failures,
+                ),
+                (
+                    Probe::Readiness,
+                    &proc.spec.readiness_check,
+                    proc.state.ready,
+                    proc.state.readiness_failures,
+                ),
+            ];
+            for (probe, check, passing, failures) in checks {
+                let Some(check) = check else { continue };
+                apps.push(AppHealth {
+                    id,
+                    name: proc.spec.qualified_name(),
+                    probe,
+                    endpoint: check.endpoint(),
+                    healthy: passing,
+                    consecutive_failures: failures,
+                    history: history
+                        .get(&(id, probe))
+                        .map(|h| h.iter().cloned().collect())
+                        .unwrap_or_default(),
+                });
+            }
+        }
+        apps
+    }
+
+    pub async fn get_spec(&self, id: u32) -> Result<Option<AppSpec>> {
+        let tracked = self.processes.published(id).map(|proc| proc.spec);
+        match tracked {
+            Some(spec) => Ok(Some(spec)),
+            None => self.db.apps().get_by_id(id).await,
+        }
+    }
+
+    /// Run `command` in the app's working directory and environment, as the
+    /// user and under the limits its process runs with, passing its output to
+    /// `output`. Returns the exit code, 128 + the signal if it was killed.
+    pub async fn exec(
+        &self,
+        spec: &AppSpec,
+        command: &[String],
+        output: &ProgressSink,
+    ) -> Result<i32> {
+        let launch_spec = SecretStore::open_default().resolve_spec(spec)?.for_launch();
+        let mut cmd = oxidepm_runtime::app_command(command, &launch_spec)?;
+        let status = oxidepm_runtime::status_with_output(&mut cmd, output)
+            .await
+            .map_err(|e| {
+                Error::ProcessStartFailed(format!("Failed to run '{}': {}", command.join(" "), e))
+            })?;
+
+        #[cfg(unix)]
+        let code = {
+            use std::os::unix::process::ExitStatusExt;
+            status
+                .code()
+                .or_else(|| status.signal().map(|signal| 128 + signal))
+        };
+        #[cfg(not(unix))]
+        let code = status.code();
+        Ok(code.unwrap_or(1))
+    }
+
+    /// Apply environment overrides to an app (and its cluster instances) and
+    /// persist them. Running processes keep their environment until restarted.
+    ///
+    /// Returns whether the app is currently running.
+    pub async fn set_env(
+        &self,
+        id: u32,
+        set: &HashMap<String, String>,
+        unset: &[String],
+    ) -> Result<bool> {
+        if let Some(key) = set
+            .keys()
+            .chain(unset)
+            .find(|key| key.is_empty() || key.contains('=') || key.contains('\0'))
+        {
+            return Err(Error::config(format!(
+                "Invalid environment variable name '{}'",
+                key
+            )));
+        }
+        let apply = |spec: &mut AppSpec| {
+            for key in unset.iter().chain(set.keys()) {
+                spec.env.remove(key);
+                spec.env_sources.remove(key);
+            }
+            spec.env
+                .extend(set.iter().map(|(k, v)| (k.clone(), v.clone())));
+        };
+
+        let (ids, running) = {
+            let mut ids = vec![id];
+            let mut running = false;
+            self.processes.with_mut(id, |proc| {
+                apply(&mut proc.spec);
+                running = proc.state.status.is_running();
+                ids.extend(proc.cluster_instance_ids.iter().copied());
+            });
+            for &instance_id in &ids[1..] {
+                self.processes.with_mut(instance_id, |instance| {
+                    apply(&mut instance.spec);
+                    running |= instance.state.status.is_running();
+                });
+            }
+            (ids, running)
+        };
+
+        for app_id in ids {
+            let Some(mut stored) = self.db.apps().get_by_id(app_id).await? else {
+                if app_id == id {
+                    return Err(Error::AppNotFound(id.to_string()));
+                }
+                continue;
+            };
+            apply(&mut stored);
+            self.db.apps().update_env(app_id, &stored.env).await?;
+        }
+
+        info!(
+            "Updated environment for app {} ({} set, {} unset)",
+            id,
+            set.len(),
+            unset.len()
+        );
+        Ok(running)
+    }
+
+    /// Apply `update` to an app's stored spec, and to its cluster instances
+    /// (which keep their own ports). Returns whether the app is running; it
+    /// runs with the old settings until restarted or reloaded.
+    pub async fn update_spec(&self, id: u32, update: &SpecUpdate) -> Result<bool> {
+        let instance_update = SpecUpdate {
+            port: None,
+            ..update.clone()
+        };
+
+        let (ids, running) = {
+            let mut ids = vec![id];
+            let mut running = false;
+            self.processes.with_mut(id, |proc| {
+                update.apply(&mut proc.spec);
+                running = proc.state.status.is_running();
+                ids.extend(proc.cluster_instance_ids.iter().copied());
+            });
+            for &instance_id in &ids[1..] {
+                self.processes.with_mut(instance_id, |instance| {
+                    instance_update.apply(&mut instance.spec);
+                    running |= instance.state.status.is_running();
+                });
+            }
+            (ids, running)
+        };
+
+        for app_id in ids {
+            let Some(mut stored) = self.db.apps().get_by_id(app_id).await? else {
+                if app_id == id {
+                    return Err(Error::AppNotFound(id.to_string()));
+                }
+                continue;
+            };
+            if app_id == id {
+                update.apply(&mut stored);
+            } else {
+                instance_update.apply(&mut stored);
+            }
+            self.db.apps().update(&stored).await?;
+        }
+
+        info!("Updated spec of app {}", id);
+        Ok(running)
+    }
+
+    /// Rename an app, moving its log files (rotated copies, cluster
+    /// instances and the hook log included) to the new name. A running app
+    /// is stopped while they move and started again; returns whether it was.
+    pub async fn rename(&self, id: u32, new_name: &str) -> Result<bool> {
+        if !oxidepm_core::validate_app_name(new_name) {
+            return Err(Error::config(format!(
+                "Invalid app name '{}': only alphanumeric characters, underscores, and hyphens are allowed",
+                new_name
+            )));
+        }
+        let spec = self
+            .get_spec(id)
+            .await?
+            .ok_or_else(|| Error::AppNotFound(id.to_string()))?;
+        if spec.instance_id.is_some() {
+            return Err(Error::config(format!(
+                "'{}' is a cluster instance; rename its cluster instead",
+                spec.name
+            )));
+        }
+        if spec.name == new_name {
+            return Ok(false);
+        }
+        let mut renamed = spec.clone();
+        renamed.name = new_name.to_string();
+        if self.db.apps().exists(&spec.namespace, new_name).await? {
+            return Err(Error::AppAlreadyExists(renamed.qualified_name()));
+        }
+        // Check every destination before touching anything
+        let moves = log_moves(&spec, &renamed);
+        if let Some((_, to)) = moves.iter().find(|(_, to)| to.exists()) {
+            return Err(Error::config(format!(
+                "Cannot move logs: {} already exists",
+                to.display()
+            )));
+        }
+
+        let (running, instance_ids) = self
+            .processes
+            .with(id, |proc| {
+                (
+                    proc.state.status.is_running(),
+                    proc.cluster_instance_ids.clone(),
+                )
+            })
+            .unwrap_or_default();
+        // Instances are named after the cluster; it starts fresh ones
+        for instance_id in instance_ids {
+            self.remove_one(instance_id, false).await?;
+        }
+        self.stop(id).await?;
+
+        let mut moved = Vec::new();
+        let renamed_stored = async {
+            for (from, to) in &moves {
+                std::fs::rename(from, to).map_err(|e| {
+                    Error::config(format!(
+                        "Failed to move {} to {}: {}",
+                        from.display(),
+                        to.display(),
+                        e
+                    ))
+                })?;
+                moved.push((from, to));
+            }
+
+            let mut stored = self
+                .db
+                .apps()
+                .get_by_id(id)
+                .await?
+                .ok_or_else(|| Error::AppNotFound(id.to_string()))?;
+            stored.name = new_name.to_string();
+            self.db.apps().update(&stored).await
+        }
+        .await;
+        // Leave the app under its old name, as it was before
+        if let Err(e) = renamed_stored {
+            for (from, to) in moved.into_iter().rev() {
+                if let Err(e) = std::fs::rename(to, from) {
+                    warn!(
+                        "Failed to move {} back to {}: {}",
+                        to.display(),
+                        from.display(),
+                        e
+                    );
+                }
+            }
+            if running {
+                if let Err(e) = self.start_by_id(id).await {
+                    warn!("Failed to restart {} after a failed rename: {}", spec.name, e);
+                }
+            }
+            return Err(e);
+        }
+        self.processes.with_mut(id, |proc| {
+            proc.spec.name = new_name.to_string();
+            proc.cluster_instance_ids.clear();
+            if running {
+                proc.state.status = AppStatus::Stopped;
+            }
+        });
+        info!(
+            "Renamed app {} to {} (id: {})",
+            spec.qualified_name(),
+            renamed.qualified_name(),
+            id
+        );
+
+        if running {
+            self.start_by_id(id).await?;
+        }
+        Ok(running)
+    }
+
+    /// Whether an app (or any of its cluster instances) is running
+    fn is_app_running(&self, info: &AppInfo) -> bool {
+        if info.state.status.is_running() {
+            return true;
+        }
+        let processes = self.processes.published_all();
+        processes.get(&info.spec.id).is_some_and(|proc| {
+            proc.cluster_instance_ids.iter().any(|id| {
+                processes
+                    .get(id)
+                    .is_some_and(|instance| instance.state.status.is_running())
+            })
+        })
+    }
+
+    /// Delete an app together with its cluster instances
+    async fn remove_app(&self, id: u32) -> Result<()> {
+        let instance_ids = self
+            .processes
+            .with(id, |proc| proc.cluster_instance_ids.clone())
+            .unwrap_or_default();
+        for instance_id in instance_ids {
+            self.delete(instance_id).await?;
+        }
+        self.delete(id).await?;
+        Ok(())
+    }
+
+    /// Graceful reload - start new instance, wait for healthy, then stop old
+    pub async fn reload(&self, id: u32) -> Result<bool> {
+        // Get the current spec
+        let tracked = self.processes.with(id, |proc| {
+            let is_cluster = !proc.cluster_instance_ids.is_empty();
+            (proc.spec.clone(), is_cluster)
+        });
+        let Some((spec, is_cluster)) = tracked else {
+            return Ok(false);
+        };
+
+        info!("Starting graceful reload for {} (id: {})", spec.name, id);
+
+        // Handle cluster reload differently
+        if is_cluster {
+            return self.reload_cluster(id).await;
+        }
+
+        // Single instance reload
+        self.reload_single(id, spec).await
+    }
+
+    /// Reload a single instance with zero-downtime
+    async fn reload_single(&self, old_id: u32, spec: AppSpec) -> Result<bool> {
+        // Create a temporary spec with a new name for the new instance
+        let mut new_spec = spec.clone();
+        new_spec.name = format!("{}-reload", spec.name);
+        new_spec.id = 0; // Will get new ID
+
+        // Start the new instance; it takes over the old one's port
+        info!("Starting new instance for reload: {}", new_spec.name);
+        self.ports.lock().hand_over(old_id);
+        let new_id = match self
+            .start_single(new_spec.clone(), &ProgressSink::none())
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                error!("Failed to start new instance for reload: {}", e);
+                return Err(e);
+            }
+        };
+
+        // Wait for health and readiness checks and the warm-up if configured
+        if spec.health_check.is_some() || spec.readiness_check.is_some() || spec.warmup.is_some() {
+            info!("Waiting for new instance to become healthy...");
+            if !self.wait_for_healthy(new_id, RELOAD_HEALTH_TIMEOUT).await {
+                error!("New instance failed health check, aborting reload");
+                let _ = self.stop(new_id).await;
+                let _ = self.delete(new_id).await;
+                return Err(Error::HealthCheckFailed);
+            }
+            info!("New instance is healthy");
+        } else {
+            // No health check, wait a brief moment for startup
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+
+        // Let the old instance drain before it is stopped
+        self.run_reload_hook(
+            &spec,
+            old_id,
+            &spec.name,
+            self.pid_of(old_id),
+            self.pid_of(new_id),
+        )
+        .await;
+
+        // Stop the old instance
+        info!("Stopping old instance (id: {})", old_id);
+        self.stop(old_id).await?;
+
+        // Rename new instance to original name
+        self.processes.with_mut(new_id, |proc| {
+            proc.spec.name = spec.name.clone();
+        });
+
+        // Update database
         self.db.apps().delete(old_id).await?;
+        self.record_event(AppEvent::new(
+            new_id,
+            spec.qualified_name(),
+            EventKind::Reloaded,
+        ));
+        self.run_hook(
+            &spec,
+            HookEvent::PostReload,
+            new_id,
+            self.pid_of(new_id),
+            None,
+        );
 
         info!("Graceful reload completed for {}", spec.name);
         Ok(true)
@@ -693,12 +2935,11 @@ impl Supervisor {
 
     /// Reload a cluster with rolling restart
     async fn reload_cluster(&self, parent_id: u32) -> Result<bool> {
-        let (spec, instance_ids) = {
-            let processes = self.processes.read();
-            match processes.get(&parent_id) {
-                Some(proc) => (proc.spec.clone(), proc.cluster_instance_ids.clone()),
-                None => return Ok(false),
-            }
+        let tracked = self.processes.with(parent_id, |proc| {
+            (proc.spec.clone(), proc.cluster_instance_ids.clone())
+        });
+        let Some((spec, instance_ids)) = tracked else {
+            return Ok(false);
         };
 
         info!(
@@ -710,12 +2951,11 @@ impl Supervisor {
         // Reload each instance one at a time
         for (i, old_instance_id) in instance_ids.iter().enumerate() {
             // Get the instance spec
-            let _instance_spec = {
-                let processes = self.processes.read();
-                match processes.get(old_instance_id) {
-                    Some(proc) => proc.spec.clone(),
-                    None => continue,
-                }
+            let Some(_instance_spec) = self
+                .processes
+                .with(*old_instance_id, |proc| proc.spec.clone())
+            else {
+                continue;
             };
 
             info!(
@@ -731,8 +2971,12 @@ impl Supervisor {
             new_instance_spec.name = format!("{}-{}-reload", spec.name, i);
             new_instance_spec.id = 0;
 
-            // Start new instance
-            let new_id = match self.start_single(new_instance_spec).await {
+            // Start new instance, taking over the old one's port
+            self.ports.lock().hand_over(*old_instance_id);
+            let new_id = match self
+                .start_single(new_instance_spec, &ProgressSink::none())
+                .await
+            {
                 Ok(id) => id,
                 Err(e) => {
                     error!("Failed to start new instance {}: {}", i, e);
@@ -741,8 +2985,11 @@ impl Supervisor {
             };
 
             // Wait for health
-            if spec.health_check.is_some() {
-                if !self.wait_for_healthy(new_id, Duration::from_secs(30)).await {
+            if spec.health_check.is_some()
+                || spec.readiness_check.is_some()
+                || spec.warmup.is_some()
+            {
+                if !self.wait_for_healthy(new_id, RELOAD_HEALTH_TIMEOUT).await {
                     error!("Instance {} failed health check, skipping", i);
                     let _ = self.stop(new_id).await;
                     let _ = self.delete(new_id).await;
@@ -752,73 +2999,132 @@ impl Supervisor {
                 tokio::time::sleep(Duration::from_millis(500)).await;
             }
 
-            // Stop old instance
+            // Let the old instance drain, then stop it
+            let instance_name = format!("{}-{}", spec.name, i);
+            self.run_reload_hook(
+                &spec,
+                *old_instance_id,
+                &instance_name,
+                self.pid_of(*old_instance_id),
+                self.pid_of(new_id),
+            )
+            .await;
             self.stop(*old_instance_id).await?;
             self.delete(*old_instance_id).await?;
 
             // Update the new instance name
-            {
-                let mut processes = self.processes.write();
-                if let Some(proc) = processes.get_mut(&new_id) {
-                    proc.spec.name = format!("{}-{}", spec.name, i);
-                }
+            self.processes.with_mut(new_id, |proc| {
+                proc.spec.name = instance_name;
+            });
 
-                // Update parent's cluster instance IDs
-                if let Some(parent) = processes.get_mut(&parent_id) {
-                    if let Some(pos) = parent.cluster_instance_ids.iter().position(|x| x == old_instance_id) {
-                        parent.cluster_instance_ids[pos] = new_id;
-                    }
+            // Update parent's cluster instance IDs
+            self.processes.with_mut(parent_id, |parent| {
+                if let Some(pos) = parent
+                    .cluster_instance_ids
+                    .iter()
+                    .position(|x| x == old_instance_id)
+                {
+                    parent.cluster_instance_ids[pos] = new_id;
                 }
-            }
+            });
 
             info!("Instance {} reloaded successfully", i);
         }
 
+        self.record_event(AppEvent::new(
+            parent_id,
+            spec.qualified_name(),
+            EventKind::Reloaded,
+        ));
+        self.run_hook(&spec, HookEvent::PostReload, parent_id, None, None);
         info!("Rolling reload completed for cluster '{}'", spec.name);
         Ok(true)
     }
 
-    /// Wait for an instance to become healthy
+    /// Wait for an instance to pass `success_threshold` consecutive health
+    /// checks, then the same for its readiness check if it has one, then for
+    /// its warm-up to finish. Each check gets `timeout`; the warm-up gets its
+    /// own `timeout_secs` plus `timeout`, as it only starts once the
+    /// instance is healthy.
     async fn wait_for_healthy(&self, app_id: u32, timeout: Duration) -> bool {
-        let start = Instant::now();
-        let check_interval = Duration::from_millis(500);
+        let tracked = self.processes.with(app_id, |proc| {
+            (
+                [
+                    (Probe::Liveness, proc.spec.health_check.clone()),
+                    (Probe::Readiness, proc.spec.readiness_check.clone()),
+                ],
+                proc.spec.warmup.as_ref().map(|w| w.timeout_secs),
+            )
+        });
+        let Some((checks, warmup)) = tracked else {
+            return false;
+        };
 
-        // Get the health check config
-        let health_config = {
-            let processes = self.processes.read();
-            if let Some(proc) = processes.get(&app_id) {
-                proc.spec.health_check.clone()
-            } else {
-                return false;
+        // Checks that are not configured count as passed
+        for (probe, config) in checks {
+            if let Some(config) = config {
+                if !self.wait_for_probe(app_id, probe, config, timeout).await {
+                    return false;
+                }
             }
-        };
+        }
+        if let Some(warmup_secs) = warmup {
+            let budget = timeout + Duration::from_secs(warmup_secs);
+            return self.wait_for_warmup(app_id, budget).await;
+        }
+        true
+    }
 
-        // No health check configured, assume healthy
-        let health_config = match health_config {
-            Some(c) => c,
-            None => return true,
-        };
+    /// Wait for an instance's warm-up to finish and the instance to be ready
+    async fn wait_for_warmup(&self, app_id: u32, timeout: Duration) -> bool {
+        let start = tokio::time::Instant::now();
+        while start.elapsed() < timeout {
+            match self.processes.published(app_id) {
+                Some(proc) if proc.state.ready => return true,
+                Some(proc) if proc.state.status.is_running() => {}
+                _ => return false,
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        false
+    }
+
+    async fn wait_for_probe(
+        &self,
+        app_id: u32,
+        probe: Probe,
+        config: HealthCheck,
+        timeout: Duration,
+    ) -> bool {
+        let check_interval = Duration::from_millis(500);
+        let mut monitor = HealthMonitor::new(config);
 
-        let mut monitor = HealthMonitor::new(health_config);
+        // The timeout starts counting once the initial delay is over
+        tokio::time::sleep(monitor.initial_delay()).await;
+        let start = Instant::now();
 
         while start.elapsed() < timeout {
             // Perform health check outside the lock
             let result = monitor.check().await;
+            record_health(&self.health_history, app_id, probe, &result);
 
             // Update state and check result
-            {
-                let mut processes = self.processes.write();
-                if let Some(proc) = processes.get_mut(&app_id) {
-                    proc.state.healthy = result.healthy;
+            let tracked = self.processes.with_mut(app_id, |proc| match probe {
+                Probe::Liveness => {
+                    proc.state.healthy = monitor.is_healthy();
                     proc.state.last_health_check = Some(chrono::Utc::now());
-
-                    if result.healthy {
-                        return true;
-                    }
-                } else {
-                    // Process not found
-                    return false;
                 }
+                Probe::Readiness => {
+                    proc.state.ready =
+                        monitor.is_healthy() && !is_warming(&self.warming, app_id, proc.state.pid)
+                }
+            });
+            if tracked.is_none() {
+                // Process not found
+                return false;
+            }
+            if monitor.is_healthy() {
+                return true;
             }
 
             tokio::time::sleep(check_interval).await;
@@ -831,24 +3137,25 @@ impl Supervisor {
     fn spawn_health_check_task(&self, app_id: u32) {
         let processes = Arc::clone(&self.processes);
         let notifier = Arc::clone(&self.notifier);
+        let paused = Arc::clone(&self.supervision_paused);
+        let actions = self.actions_tx.clone();
+        let health_history = Arc::clone(&self.health_history);
+        let events = self.db.events();
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
         tokio::spawn(async move {
             // Get initial interval and health check config
-            let (interval, health_config) = {
-                let procs = processes.read();
-                match procs.get(&app_id) {
-                    Some(proc) => {
-                        let interval = proc
-                            .health_monitor
-                            .as_ref()
-                            .map(|m| m.interval())
-                            .unwrap_or(Duration::from_secs(30));
-                        let config = proc.spec.health_check.clone();
-                        (interval, config)
-                    }
-                    None => return,
-                }
+            let tracked = processes.with(app_id, |proc| {
+                let interval = proc
+                    .health_monitor
+                    .as_ref()
+                    .map(|m| m.interval())
+                    .unwrap_or(Duration::from_secs(30));
+                let config = proc.spec.health_check.clone();
+                (interval, config)
+            });
+            let Some((interval, health_config)) = tracked else {
+                return;
             };
 
             // Create our own health checker (doesn't need to be in the process struct)
@@ -859,7 +3166,7 @@ impl Supervisor {
             let mut monitor = HealthMonitor::new(health_config);
 
             // Wait for process to start before first health check
-            tokio::time::sleep(Duration::from_secs(5)).await;
+            tokio::time::sleep(monitor.initial_delay()).await;
 
             loop {
                 tokio::select! {
@@ -868,96 +3175,133 @@ impl Supervisor {
                     }
                     _ = tokio::time::sleep(interval) => {
                         // First check if we should continue (without holding lock across await)
-                        let should_check = {
-                            let procs = processes.read();
-                            if let Some(proc) = procs.get(&app_id) {
-                                proc.state.status.is_running()
-                            } else {
-                                false
-                            }
-                        };
+                        let should_check = processes
+                            .published(app_id)
+                            .is_some_and(|proc| proc.state.status.is_running());
 
                         if !should_check {
                             break;
                         }
+                        // A frozen app cannot answer; check again once resumed
+                        if is_paused(&processes, app_id) {
+                            continue;
+                        }
 
                         // Perform health check outside the lock
                         let result = monitor.check().await;
                         let is_unhealthy = monitor.is_unhealthy();
+                        record_health(&health_history, app_id, Probe::Liveness, &result);
 
                         // Update state inside the lock
-                        {
-                            let mut procs = processes.write();
-                            if let Some(proc) = procs.get_mut(&app_id) {
+                        let Some(action) = processes.with_mut(app_id, |proc| {
+                            // A failure flips healthy off at once; coming back
+                            // takes `success_threshold` passes in a row
+                            if !result.healthy || monitor.is_healthy() {
                                 proc.state.healthy = result.healthy;
-                                proc.state.last_health_check = Some(chrono::Utc::now());
+                            }
+                            proc.state.last_health_check = Some(chrono::Utc::now());
 
-                                if result.healthy {
-                                    proc.state.health_check_failures = 0;
-                                    debug!("Health check passed for app {}", app_id);
-                                } else {
-                                    proc.state.health_check_failures += 1;
-                                    warn!(
-                                        "Health check failed for app {} ({} consecutive failures): {:?}",
-                                        app_id,
-                                        proc.state.health_check_failures,
-                                        result.message
-                                    );
-
-                                    // Check if we should mark as unhealthy
-                                    if is_unhealthy {
-                                        warn!("App {} marked as unhealthy", app_id);
-                                        proc.state.status = AppStatus::Errored;
-
-                                        // Send health check failure notification
-                                        let name = proc.spec.name.clone();
-                                        let endpoint = proc.spec.health_check
-                                            .as_ref()
-                                            .and_then(|hc| hc.http_url.clone())
-                                            .unwrap_or_else(|| "unknown".to_string());
-                                        let notifier_clone = Arc::clone(&notifier);
+                            if result.healthy {
+                                proc.state.health_check_failures = 0;
+                                debug!("Health check passed for app {}", app_id);
+                                None
+                            } else {
+                                proc.state.health_check_failures += 1;
+                                warn!(
+                                    "Health check failed for app {} ({} consecutive failures): {:?}",
+                                    app_id,
+                                    proc.state.health_check_failures,
+                                    result.message
+                                );
+
+                                // Check if we should mark as unhealthy
+                                if is_unhealthy && paused.load(Ordering::SeqCst) {
+                                    debug!("Supervision paused, skipping unhealthy action for app {}", app_id);
+                                    None
+                                } else if is_unhealthy {
+                                    let mut event = AppEvent::new(app_id, proc.spec.qualified_name(), EventKind::Unhealthy);
+                                    event.reason = result.message.clone();
+                                    spawn_record_event(events.clone(), event);
+
+                                    let action = unhealthy_action(proc, app_id);
+                                    match action {
+                                        Some((SupervisorAction::Restart { attempt, .. }, delay)) => {
+                                            proc.state.restarts_in_window = attempt;
+                                            proc.state.next_restart_at = chrono::Duration::from_std(delay)
+                                                .ok()
+                                                .map(|delay| chrono::Utc::now() + delay);
+                                        }
+                                        Some(_) => {}
+                                        None => {
+                                            warn!("App {} marked as unhealthy", app_id);
+                                            proc.state.status = AppStatus::Errored;
+                                        }
+                                    }
+
+                                    // Send health check failure notification
+                                    let name = proc.spec.name.clone();
+                                    let endpoint = proc.spec.health_check
+                                        .as_ref()
+                                        .map(|hc| hc.endpoint())
+                                        .unwrap_or_else(|| "unknown".to_string());
+                                    let notifier_clone = Arc::clone(&notifier.read());
+                                    tokio::spawn(async move {
+                                        let event = ProcessEvent::HealthCheckFailed {
+                                            name,
+                                            id: app_id,
+                                            endpoint,
+                                        };
+                                        if let Err(e) = notifier_clone.notify(&event).await {
+                                            warn!("Failed to send health check notification: {}", e);
+                                        }
+                                    });
+
+                                    // Run on_error hook if configured
+                                    if let Some(error_hook) = proc.spec.hooks.on_error.clone() {
+                                        let hook_name = proc.spec.name.clone();
+                                        let hook_env = HookEnv::of(&proc.spec);
+                                        let pid = proc.state.pid;
                                         tokio::spawn(async move {
-                                            let event = ProcessEvent::HealthCheckFailed {
-                                                name,
-                                                id: app_id,
-                                                endpoint,
+                                            let call = HookCall {
+                                                app_id,
+                                                app_name: &hook_name,
+                                                event: "error",
+                                                pid,
+                                                exit_code: None,
+                                                extra: &[],
                                             };
-                                            if let Err(e) = notifier_clone.notify(&event).await {
-                                                warn!("Failed to send health check notification: {}", e);
-                                            }
-                                        });
-
-                                        // Run on_error hook if configured
-                                        if let Some(error_script) = proc.spec.hooks.on_error.clone() {
-                                            let hook_name = proc.spec.name.clone();
-                                            let pid = proc.state.pid;
-                                            tokio::spawn(async move {
-                                                let result = run_hook_script(
-                                                    &error_script,
-                                                    app_id,
-                                                    &hook_name,
-                                                    "error",
-                                                    pid,
-                                                    None,
-                                                ).await;
-                                                match result {
-                                                    Ok(output) => {
-                                                        if !output.is_empty() {
-                                                            debug!("Error hook output for {}: {}", hook_name, output);
-                                                        }
-                                                        info!("Error hook completed successfully for {}", hook_name);
-                                                    }
-                                                    Err(e) => {
-                                                        error!("Error hook failed for {}: {}", hook_name, e);
+                                            let result = run_hook_script(&error_hook, &call, &hook_env).await;
+                                            match result {
+                                                Ok(output) => {
+                                                    if !output.is_empty() {
+                                                        debug!("Error hook output for {}: {}", hook_name, output);
                                                     }
+                                                    info!("Error hook completed successfully for {}", hook_name);
                                                 }
-                                            });
-                                        }
+                                                Err(e) => {
+                                                    error!("Error hook failed for {}: {}", hook_name, e);
+                                                }
+                                            }
+                                        });
                                     }
+                                    action
+                                } else {
+                                    None
                                 }
-                            } else {
-                                break;
                             }
+                        }) else {
+                            break;
+                        };
+
+                        // Hand the restart/stop to the supervisor; this task ends
+                        // and the restarted process gets a fresh one
+                        if let Some((action, delay)) = action {
+                            let actions = actions.clone();
+                            tokio::spawn(async move {
+                                tokio::time::sleep(delay).await;
+                                let _ = actions.send(action);
+                            });
+                            break;
                         }
                     }
                 }
@@ -965,21 +3309,195 @@ impl Supervisor {
         });
     }
 
-    /// Spawn supervision task for an app
-    fn spawn_supervision_task(&self, app_id: u32) {
+    /// Spawn the readiness check task for a process. Failing readiness never
+    /// restarts anything: after `retries` failures in a row the app is marked
+    /// not ready, and `success_threshold` passes make it ready again. An app
+    /// is not ready while its warm-up runs, whatever the check says.
+    fn spawn_readiness_task(&self, app_id: u32) {
         let processes = Arc::clone(&self.processes);
-        let notifier = Arc::clone(&self.notifier);
+        let warming = Arc::clone(&self.warming);
+        let health_history = Arc::clone(&self.health_history);
+        let events = self.db.events();
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
         tokio::spawn(async move {
-            loop {
+            let (pid, config) = match processes.published(app_id) {
+                Some(proc) => (proc.state.pid, proc.spec.readiness_check),
+                None => return,
+            };
+            let Some(config) = config else { return };
+            let mut monitor = HealthMonitor::new(config);
+            let interval = monitor.interval();
+            // Becoming ready after a start is expected; only a recovery is an event
+            let mut was_ready = false;
+
+            tokio::time::sleep(monitor.initial_delay()).await;
+
+            loop {
+                // The task belongs to one process; a restarted app gets a new one
+                let current = processes
+                    .published(app_id)
+                    .is_some_and(|proc| proc.state.pid == pid && proc.state.status.is_running());
+                if !current {
+                    break;
+                }
+                if is_paused(&processes, app_id) {
+                    tokio::select! {
+                        _ = shutdown_rx.recv() => break,
+                        _ = tokio::time::sleep(interval) => continue,
+                    }
+                }
+
+                let result = monitor.check().await;
+                record_health(&health_history, app_id, Probe::Readiness, &result);
+
+                let current = processes.with_mut(app_id, |proc| {
+                    if proc.state.pid != pid {
+                        return false;
+                    }
+                    if result.healthy {
+                        proc.state.readiness_failures = 0;
+                    } else {
+                        proc.state.readiness_failures += 1;
+                    }
+
+                    let ready = if proc.state.ready {
+                        !monitor.is_unhealthy()
+                    } else {
+                        monitor.is_healthy() && !is_warming(&warming, app_id, pid)
+                    };
+                    if ready != proc.state.ready {
+                        proc.state.ready = ready;
+                        if ready {
+                            info!("App {} is ready", proc.spec.name);
+                            if was_ready {
+                                spawn_record_event(
+                                    events.clone(),
+                                    AppEvent::new(
+                                        app_id,
+                                        proc.spec.qualified_name(),
+                                        EventKind::Ready,
+                                    ),
+                                );
+                            }
+                        } else {
+                            warn!("App {} is not ready: {:?}", proc.spec.name, result.message);
+                            let mut event = AppEvent::new(
+                                app_id,
+                                proc.spec.qualified_name(),
+                                EventKind::NotReady,
+                            );
+                            event.reason = result.message.clone();
+                            spawn_record_event(events.clone(), event);
+                        }
+                    }
+                    was_ready |= ready;
+                    true
+                });
+                if current != Some(true) {
+                    break;
+                }
+
+                // Check again soon after a warm-up, so the app turns ready once it ends
+                let wait = if is_warming(&warming, app_id, pid) {
+                    WARMUP_POLL
+                } else {
+                    interval
+                };
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break,
+                    _ = tokio::time::sleep(wait) => {}
+                }
+            }
+        });
+    }
+
+    /// Spawn the warm-up task for a process: once the app passed a health
+    /// check (or stayed up for `ROLLOUT_SETTLE_MS` without one), fire its
+    /// warm-up requests, then let it turn ready. A failed or timed out
+    /// warm-up is logged and the app is marked ready anyway.
+    fn spawn_warmup_task(&self, app_id: u32) {
+        let processes = Arc::clone(&self.processes);
+        let warming = Arc::clone(&self.warming);
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+
+        tokio::spawn(async move {
+            let (pid, spec) = match processes.published(app_id) {
+                Some(proc) => (proc.state.pid, proc.spec),
+                None => return,
+            };
+            let Some(warmup) = spec.warmup.clone() else {
+                return;
+            };
+            // The task belongs to one process; a restarted app gets a new one
+            let current = |state: &RunState| state.pid == pid && state.status.is_running();
+
+            let healthy = loop {
+                let healthy = match processes.published(app_id) {
+                    Some(proc) if current(&proc.state) => passed_health_check(&proc),
+                    _ => break false,
+                };
+                if healthy {
+                    break true;
+                }
+                tokio::select! {
+                    _ = shutdown_rx.recv() => break false,
+                    _ = tokio::time::sleep(WARMUP_POLL) => {}
+                }
+            };
+
+            if healthy {
+                info!("Warming up app {}", spec.name);
+                let result = HealthChecker::new().warm_up(&warmup, &spec.cwd).await;
+                match &result.message {
+                    None => info!(
+                        "App {} warmed up with {} request(s) in {}ms",
+                        spec.name, result.sent, result.duration_ms
+                    ),
+                    Some(message) => warn!(
+                        "Warm-up of app {} fell short ({} of {} request(s) failed): {}",
+                        spec.name,
+                        result.failed,
+                        result.sent + result.failed,
+                        message
+                    ),
+                }
+            }
+
+            {
+                let mut warming = warming.write();
+                if pid.is_some_and(|pid| warming.get(&app_id) == Some(&pid)) {
+                    warming.remove(&app_id);
+                }
+            }
+            processes.with_mut(app_id, |proc| {
+                // With a readiness check, its task turns the app ready
+                if current(&proc.state) && proc.spec.readiness_check.is_none() {
+                    proc.state.ready = true;
+                    info!("App {} is ready", proc.spec.name);
+                }
+            });
+        });
+    }
+
+    /// Spawn supervision task for an app
+    fn spawn_supervision_task(&self, app_id: u32) {
+        let processes = Arc::clone(&self.processes);
+        let notifier = Arc::clone(&self.notifier);
+        let events = self.db.events();
+        let runs = self.db.runs();
+        let paused = Arc::clone(&self.supervision_paused);
+        let actions = self.actions_tx.clone();
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+
+        tokio::spawn(async move {
+            loop {
                 tokio::select! {
                     _ = shutdown_rx.recv() => {
                         break;
                     }
                     _ = tokio::time::sleep(Duration::from_millis(500)) => {
-                        let mut procs = processes.write();
-                        if let Some(proc) = procs.get_mut(&app_id) {
+                        let watching = processes.with_mut(app_id, |proc| {
                             if let Some(child) = &mut proc.child {
                                 // Check if process has exited
                                 match child.try_wait() {
@@ -996,13 +3514,24 @@ impl Supervisor {
                                             // Unexpected exit (crash)
                                             warn!("Process {} exited unexpectedly", app_id);
                                             proc.state.status = AppStatus::Errored;
+                                            spawn_record_event(
+                                                events.clone(),
+                                                AppEvent::new(app_id, proc.spec.qualified_name(), EventKind::Crashed)
+                                                    .with_exit_code(exit_code),
+                                            );
+                                            let runs = runs.clone();
+                                            tokio::spawn(async move {
+                                                if let Err(e) = runs.finish_latest(app_id, AppStatus::Errored, exit_code).await {
+                                                    warn!("Failed to record end of run for app {}: {}", app_id, e);
+                                                }
+                                            });
 
                                             // Send crash notification
                                             let name = proc.spec.name.clone();
                                             let error = exit_code
                                                 .map(|c| format!("Exit code {}", c))
                                                 .unwrap_or_else(|| "Unknown error".to_string());
-                                            let notifier_clone = Arc::clone(&notifier);
+                                            let notifier_clone = Arc::clone(&notifier.read());
                                             tokio::spawn(async move {
                                                 let event = ProcessEvent::Crashed {
                                                     name,
@@ -1015,17 +3544,19 @@ impl Supervisor {
                                             });
 
                                             // Run on_crash hook if configured
-                                            if let Some(crash_script) = proc.spec.hooks.on_crash.clone() {
+                                            if let Some(crash_hook) = proc.spec.hooks.on_crash.clone() {
                                                 let hook_name = proc.spec.name.clone();
+                                                let hook_env = HookEnv::of(&proc.spec);
                                                 tokio::spawn(async move {
-                                                    let result = run_hook_script(
-                                                        &crash_script,
+                                                    let call = HookCall {
                                                         app_id,
-                                                        &hook_name,
-                                                        "crash",
-                                                        None,
+                                                        app_name: &hook_name,
+                                                        event: "crash",
+                                                        pid: None,
                                                         exit_code,
-                                                    ).await;
+                                                        extra: &[],
+                                                    };
+                                                    let result = run_hook_script(&crash_hook, &call, &hook_env).await;
                                                     match result {
                                                         Ok(output) => {
                                                             if !output.is_empty() {
@@ -1040,13 +3571,35 @@ impl Supervisor {
                                                 });
                                             }
 
-                                            // TODO: Handle restart logic here
+                                            if paused.load(Ordering::SeqCst) {
+                                                info!("Supervision paused, not restarting app {}", app_id);
+                                            } else if let Some((action, delay)) = crash_action(proc, app_id) {
+                                                if let SupervisorAction::CrashRestart { attempt, .. } = action {
+                                                    proc.state.restarts_in_window = attempt;
+                                                }
+                                                proc.state.next_restart_at = chrono::Duration::from_std(delay)
+                                                    .ok()
+                                                    .map(|delay| chrono::Utc::now() + delay);
+                                                let actions = actions.clone();
+                                                tokio::spawn(async move {
+                                                    tokio::time::sleep(delay).await;
+                                                    let _ = actions.send(action);
+                                                });
+                                            }
+                                            // A restarted process gets a fresh task
+                                            return false;
                                         }
                                     }
                                     Ok(None) => {
                                         // Still running, update uptime
                                         if let Some(started) = proc.started_at {
                                             proc.state.uptime_secs = started.elapsed().as_secs();
+                                            // Up long enough: the crash-loop streak is over
+                                            if proc.state.uptime_secs >= proc.spec.restart_policy.crash_window_secs
+                                                && proc.state.next_restart_at.is_none()
+                                            {
+                                                proc.state.restarts_in_window = 0;
+                                            }
                                         }
                                     }
                                     Err(e) => {
@@ -1054,8 +3607,10 @@ impl Supervisor {
                                     }
                                 }
                             }
-                        } else {
-                            // Process removed, exit task
+                            true
+                        });
+                        // Process removed, or crashed and handed to a fresh task
+                        if watching != Some(true) {
                             break;
                         }
                     }
@@ -1067,23 +3622,26 @@ impl Supervisor {
     /// Spawn watch task for an app
     fn spawn_watch_task(&self, app_id: u32) {
         let processes = Arc::clone(&self.processes);
+        let paused = Arc::clone(&self.supervision_paused);
+        let actions = self.actions_tx.clone();
 
         tokio::spawn(async move {
-            // Get app spec
-            let spec = {
-                let procs = processes.read();
-                procs.get(&app_id).map(|p| p.spec.clone())
-            };
+            // Get app spec and the process this task watches for
+            let watched = processes.published(app_id).map(|p| (p.spec, p.state.pid));
 
-            let spec = match spec {
-                Some(s) => s,
+            let (spec, pid) = match watched {
+                Some(w) => w,
                 None => return,
             };
 
-            // Create watcher
+            // Create watcher, also honoring the project's ignore files
+            let mut ignore = spec.ignore_patterns.clone();
+            ignore.extend(ignore_file_patterns(&spec.cwd));
             let config = WatchConfig {
-                ignore: spec.ignore_patterns.clone(),
+                ignore,
                 debounce_ms: 200,
+                extensions: spec.watch_extensions.clone(),
+                poll_interval: spec.watch_poll_ms.map(Duration::from_millis),
             };
 
             let mut watcher = match FileWatcher::new(config) {
@@ -1094,26 +3652,322 @@ impl Supervisor {
                 }
             };
 
-            if let Err(e) = watcher.watch(&spec.cwd) {
-                warn!("Failed to watch directory for {}: {}", app_id, e);
-                return;
+            for path in spec.watch_targets() {
+                if let Err(e) = watcher.watch(&path) {
+                    warn!("Failed to watch {} for {}: {}", path.display(), app_id, e);
+                    return;
+                }
             }
 
-            info!("Watch mode active for {} in {}", spec.name, spec.cwd.display());
+            info!(
+                "Watch mode active for {} on {}{}",
+                spec.name,
+                watcher
+                    .watched_paths()
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                if spec.watch_poll_ms.is_some() {
+                    " (polling)"
+                } else {
+                    ""
+                }
+            );
+
+            let runner = get_runner(spec.mode);
+            let compiled = runner.prepare_command(&spec).is_some();
 
             loop {
-                // Check if still running
-                {
-                    let procs = processes.read();
-                    if !procs.contains_key(&app_id) {
+                // Stop once the process is gone or replaced; a restarted
+                // process gets a watch task of its own
+                if processes.published(app_id).and_then(|p| p.state.pid) != pid {
+                    break;
+                }
+
+                // Check for changes without blocking the runtime
+                let Some(event) = watcher.poll() else {
+                    tokio::time::sleep(Duration::from_millis(250)).await;
+                    continue;
+                };
+                if paused.load(Ordering::SeqCst) {
+                    debug!("Supervision paused, ignoring file change for {}", spec.name);
+                    continue;
+                }
+                info!("File change detected for {}: {:?}", spec.name, event.paths);
+
+                if !compiled {
+                    let _ = actions.send(SupervisorAction::WatchRestart {
+                        id: app_id,
+                        prebuilt: None,
+                    });
+                    break;
+                }
+
+                // Build while the old process keeps serving, and only swap
+                // to the new binary once it compiled
+                info!("Rebuilding {} before restarting it", spec.name);
+                let timeout = Duration::from_secs(
+                    spec.build_timeout_secs
+                        .unwrap_or(constants::DEFAULT_BUILD_TIMEOUT_SECS),
+                );
+                let rebuilt = tokio::time::timeout(
+                    timeout,
+                    prepare_logged(runner.as_ref(), &spec, &ProgressSink::none()),
+                )
+                .await
+                .unwrap_or_else(|_| {
+                    Err(Error::Timeout(format!(
+                        "Rebuild did not finish within {}s",
+                        timeout.as_secs()
+                    )))
+                });
+                match rebuilt {
+                    Ok(result) if result.success => {
+                        let _ = actions.send(SupervisorAction::WatchRestart {
+                            id: app_id,
+                            prebuilt: result.build,
+                        });
                         break;
                     }
+                    Ok(result) => {
+                        warn!(
+                            "Rebuild of {} failed, keeping the running process",
+                            spec.name
+                        );
+                        if let Err(e) = log_build_failure(&spec, &result.output) {
+                            warn!(
+                                "Failed to write build errors to the log of {}: {}",
+                                spec.name, e
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Rebuild of {} failed, keeping the running process: {}",
+                            spec.name, e
+                        );
+                        if let Err(e) = log_build_failure(&spec, &e.to_string()) {
+                            warn!(
+                                "Failed to write build errors to the log of {}: {}",
+                                spec.name, e
+                            );
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Spawn a task that notices when the executable of a binary-mode app is
+    /// replaced on disk, e.g. by a CI deploy. It restarts the app when
+    /// `reload_on_change` is set and otherwise only warns.
+    fn spawn_binary_check_task(&self, app_id: u32) {
+        let processes = Arc::clone(&self.processes);
+        let actions = self.actions_tx.clone();
+
+        tokio::spawn(async move {
+            let checked = processes
+                .published(app_id)
+                .map(|p| (p.spec, p.state.pid, p.state.build.and_then(|b| b.sha256)));
+            let Some((spec, pid, Some(running_sha))) = checked else {
+                return;
+            };
+            let path = binary::binary_path(&spec);
+            let stamp = |m: &std::fs::Metadata| (m.modified().ok(), m.len());
+            let mut last_stamp = tokio::fs::metadata(&path).await.ok().map(|m| stamp(&m));
+            let mut warned_sha: Option<String> = None;
+
+            loop {
+                tokio::time::sleep(Duration::from_secs(constants::BINARY_CHECK_INTERVAL_SECS))
+                    .await;
+                if processes.published(app_id).and_then(|p| p.state.pid) != pid {
+                    break;
+                }
+
+                // Only hash again when the file looks different
+                let current = tokio::fs::metadata(&path).await.ok().map(|m| stamp(&m));
+                if current.is_none() || current == last_stamp {
+                    continue;
+                }
+                last_stamp = current;
+
+                let hashed = path.clone();
+                let sha =
+                    match tokio::task::spawn_blocking(move || binary::sha256_file(&hashed)).await {
+                        Ok(Ok(sha)) => sha,
+                        // Likely caught mid-copy; the next check sees the final file
+                        _ => continue,
+                    };
+                if sha == running_sha || warned_sha.as_deref() == Some(sha.as_str()) {
+                    continue;
+                }
+
+                if spec.reload_on_change {
+                    info!(
+                        "Binary of {} changed on disk (sha256 {}), restarting",
+                        spec.name,
+                        &sha[..12]
+                    );
+                    let _ = actions.send(SupervisorAction::WatchRestart {
+                        id: app_id,
+                        prebuilt: None,
+                    });
+                    break;
+                }
+                warn!(
+                    "Binary of {} changed on disk (sha256 {}); the running process still uses the old one, restart to pick it up",
+                    spec.name,
+                    &sha[..12]
+                );
+                let note = format!(
+                    "[oxidepm] {} changed on disk (sha256 {}, running {}); restart to pick it up",
+                    path.display(),
+                    sha,
+                    running_sha
+                );
+                if let Err(e) = LogWriter::new(spec.stderr_log_path(), RotationConfig::default())
+                    .and_then(|log| {
+                        log.with_prefix(LinePrefix::for_app(&spec))
+                            .write_line(&note)
+                    })
+                {
+                    warn!("Failed to write to the log of {}: {}", spec.name, e);
+                }
+                warned_sha = Some(sha);
+            }
+        });
+    }
+
+    /// Spawn a task that notices when an app's `env_file` changes on disk
+    /// and asks for the app to pick up the new variables. It ends once the
+    /// app is stopped, deleted or started again, which spawns a fresh one.
+    fn spawn_env_file_task(&self, app_id: u32) {
+        let processes = Arc::clone(&self.processes);
+        let actions = self.actions_tx.clone();
+        let watched = processes
+            .with(app_id, |p| Some((p.spec.env_file.clone()?, p.started_at)))
+            .flatten();
+        let Some((path, started_at)) = watched else {
+            return;
+        };
+        // Content, not metadata: editors and secret managers often rewrite a
+        // file without changing it. Read now, so a change right after the
+        // start is not taken for the original.
+        let mut last = std::fs::read(&path).ok();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(constants::ENV_FILE_CHECK_INTERVAL_SECS))
+                    .await;
+                let current = processes.with(app_id, |p| {
+                    p.started_at == started_at && p.state.status.is_running()
+                });
+                if current != Some(true) {
+                    break;
+                }
+
+                // A missing file is likely mid-replace; wait for the new one
+                let Ok(current) = tokio::fs::read(&path).await else {
+                    continue;
+                };
+                if last.as_ref() == Some(&current) {
+                    continue;
+                }
+                last = Some(current);
+                let _ = actions.send(SupervisorAction::EnvFileChanged { id: app_id });
+            }
+        });
+    }
+
+    /// Spawn the task that keeps app logs within the global disk budget
+    /// (`OXIDEPM_LOG_BUDGET` or `log_budget` in daemon.toml) by deleting the
+    /// oldest rotated files first
+    fn spawn_log_vacuum(&self) {
+        let apps = self.db.apps();
+        let processes = Arc::clone(&self.processes);
+        let log_budget = Arc::clone(&self.log_budget);
+
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(constants::LOG_VACUUM_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                let Some(budget) = *log_budget.read() else {
+                    continue;
+                };
+
+                let specs = match apps.get_all().await {
+                    Ok(specs) => specs,
+                    Err(e) => {
+                        warn!("Log vacuum: failed to load apps: {}", e);
+                        continue;
+                    }
+                };
+                // The tracked spec knows about custom log paths
+                let paths: BTreeSet<PathBuf> = {
+                    let mut procs = processes.published_all();
+                    specs
+                        .into_iter()
+                        .map(|spec| procs.remove(&spec.id).map_or(spec, |p| p.spec))
+                        .flat_map(|spec| [spec.stdout_log_path(), spec.stderr_log_path()])
+                        .collect()
+                };
+
+                let freed = tokio::task::spawn_blocking(move || {
+                    let mut files: Vec<_> = paths
+                        .iter()
+                        .flat_map(|path| oxidepm_logs::log_files(path))
+                        .collect();
+                    oxidepm_logs::trim_to_size(&mut files, budget)
+                })
+                .await
+                .unwrap_or(0);
+                if freed > 0 {
+                    info!(
+                        "Log vacuum: removed {} bytes of rotated logs to stay within budget",
+                        freed
+                    );
                 }
+            }
+        });
+    }
 
-                // Wait for changes
-                if let Some(event) = watcher.wait(Duration::from_secs(1)) {
-                    info!("File change detected for {}: {:?}", spec.name, event.paths);
-                    // Restart logic would go here
+    /// Spawn the task that keeps the database tidy: `PRAGMA optimize` every
+    /// hour and a VACUUM when the last one is older than a week
+    fn spawn_db_maintenance(&self) {
+        let db = self.db.clone();
+
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(constants::DB_MAINTENANCE_INTERVAL_SECS));
+            // The first tick fires immediately; leave startup alone
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = db.optimize().await {
+                    warn!("Database maintenance: optimize failed: {}", e);
+                }
+                let due = match db.last_vacuum().await {
+                    Ok(Some(at)) => {
+                        (chrono::Utc::now() - at).num_seconds()
+                            >= constants::DB_VACUUM_INTERVAL_SECS as i64
+                    }
+                    Ok(None) => true,
+                    Err(e) => {
+                        warn!("Database maintenance: {}", e);
+                        false
+                    }
+                };
+                if due {
+                    let started = Instant::now();
+                    match db.vacuum().await {
+                        Ok(()) => {
+                            info!("Database maintenance: vacuumed in {:?}", started.elapsed())
+                        }
+                        Err(e) => warn!("Database maintenance: {}", e),
+                    }
                 }
             }
         });
@@ -1122,94 +3976,132 @@ impl Supervisor {
     /// Spawn metrics collector task with limit enforcement
     fn spawn_metrics_collector(&self) {
         let processes = Arc::clone(&self.processes);
-        let system = Arc::clone(&self.system);
+        let events = self.db.events();
+        let host_usage = Arc::clone(&self.host_usage);
         let notifier = Arc::clone(&self.notifier);
+        let paused = Arc::clone(&self.supervision_paused);
 
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(2));
+            // Owned by this task: refreshing it never blocks a status query
+            let mut sys = System::new();
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(constants::metrics_interval_secs()));
             // Track which processes have already been notified/scheduled for restart
             let mut memory_limit_notified: std::collections::HashSet<u32> = std::collections::HashSet::new();
             let mut pending_restarts: std::collections::HashSet<u32> = std::collections::HashSet::new();
 
+            let mut last_refresh = Instant::now();
+
             loop {
                 interval.tick().await;
 
-                // Refresh system info
-                {
-                    let mut sys = system.write();
-                    sys.refresh_all();
+                // Sample on a blocking thread with no lock held; disk
+                // counters are relative to the last refresh
+                let since_refresh = last_refresh.elapsed().as_secs_f64().max(0.001);
+                let targets: Vec<(u32, u32)> = processes
+                    .published_all()
+                    .into_iter()
+                    .filter_map(|(id, proc)| proc.state.pid.map(|pid| (id, pid)))
+                    .collect();
+                if targets.is_empty() {
+                    // Nothing to sample; host totals are a cheap read
+                    sys.refresh_memory();
+                    *host_usage.write() = HostUsage::of(&sys);
+                    last_refresh = Instant::now();
+                    continue;
                 }
+                let sampled = tokio::task::spawn_blocking(move || {
+                    let samples = sample_processes(&mut sys, &targets, since_refresh);
+                    (sys, samples)
+                })
+                .await;
+                let samples = match sampled {
+                    Ok((refreshed, samples)) => {
+                        sys = refreshed;
+                        samples
+                    }
+                    Err(e) => {
+                        warn!("Metrics sampling failed: {}", e);
+                        sys = System::new();
+                        continue;
+                    }
+                };
+                last_refresh = Instant::now();
+                *host_usage.write() = HostUsage::of(&sys);
 
                 // Collect processes that need restart due to limits
                 let mut restart_needed: Vec<(u32, String, String)> = Vec::new();
 
                 // Update process metrics and check limits
-                {
-                    let mut procs = processes.write();
-                    let sys = system.read();
-
-                    for (app_id, proc) in procs.iter_mut() {
-                        if let Some(pid) = proc.state.pid {
-                            if let Some(process) = sys.process(Pid::from(pid as usize)) {
-                                proc.state.cpu_percent = process.cpu_usage();
-                                proc.state.memory_bytes = process.memory();
-                            }
-                        }
-
-                        // Skip if not running or already pending restart
-                        if !proc.state.status.is_running() || pending_restarts.contains(app_id) {
-                            continue;
+                processes.for_each_mut(|app_id, proc| {
+                    // Skip samples of a pid the app has since replaced
+                    if let Some((pid, sample)) = samples.get(&app_id) {
+                        if proc.state.pid == Some(*pid) {
+                            sample.apply(&mut proc.state);
                         }
+                    }
+                    if let Some(dropped) = &proc.log_lines_dropped {
+                        proc.state.log_lines_dropped = dropped.load(Ordering::Relaxed);
+                    }
 
-                        // Check memory limit - enforce restart if exceeded
-                        if let Some(limit_mb) = proc.spec.max_memory_mb {
-                            let memory_mb = proc.state.memory_bytes / (1024 * 1024);
-                            if memory_mb > limit_mb {
-                                warn!(
-                                    "Process {} (id: {}) exceeded memory limit: {}MB > {}MB, scheduling restart",
-                                    proc.spec.name, app_id, memory_mb, limit_mb
-                                );
+                    // Skip limit enforcement while supervision is paused
+                    if paused.load(Ordering::SeqCst) {
+                        return;
+                    }
 
-                                // Send notification if not already sent
-                                if !memory_limit_notified.contains(app_id) {
-                                    memory_limit_notified.insert(*app_id);
-                                    let name = proc.spec.name.clone();
-                                    let id = *app_id;
-                                    let notifier_clone = Arc::clone(&notifier);
-                                    tokio::spawn(async move {
-                                        let event = ProcessEvent::MemoryLimit {
-                                            name,
-                                            id,
-                                            memory_mb,
-                                            limit_mb,
-                                        };
-                                        if let Err(e) = notifier_clone.notify(&event).await {
-                                            warn!("Failed to send memory limit notification: {}", e);
-                                        }
-                                    });
-                                }
+                    // Skip if not running or already pending restart
+                    if !proc.state.status.is_running() || pending_restarts.contains(&app_id) {
+                        return;
+                    }
 
-                                restart_needed.push((*app_id, proc.spec.name.clone(), "memory_limit".to_string()));
-                                pending_restarts.insert(*app_id);
-                            } else if memory_mb < limit_mb {
-                                // Reset notification flag when memory is back under limit
-                                memory_limit_notified.remove(app_id);
+                    // Check memory limit - enforce restart if exceeded
+                    if let Some(limit_mb) = proc.spec.max_memory_mb {
+                        let memory_mb = proc.state.memory_bytes / (1024 * 1024);
+                        if memory_mb > limit_mb {
+                            warn!(
+                                "Process {} (id: {}) exceeded memory limit: {}MB > {}MB, scheduling restart",
+                                proc.spec.name, app_id, memory_mb, limit_mb
+                            );
+
+                            // Send notification if not already sent
+                            if !memory_limit_notified.contains(&app_id) {
+                                memory_limit_notified.insert(app_id);
+                                let name = proc.spec.name.clone();
+                                let id = app_id;
+                                let notifier_clone = Arc::clone(&notifier.read());
+                                tokio::spawn(async move {
+                                    let event = ProcessEvent::MemoryLimit {
+                                        name,
+                                        id,
+                                        memory_mb,
+                                        limit_mb,
+                                    };
+                                    if let Err(e) = notifier_clone.notify(&event).await {
+                                        warn!("Failed to send memory limit notification: {}", e);
+                                    }
+                                });
                             }
+
+                            restart_needed.push((app_id, proc.spec.name.clone(), "memory_limit".to_string()));
+                            pending_restarts.insert(app_id);
+                        } else if memory_mb < limit_mb {
+                            // Reset notification flag when memory is back under limit
+                            memory_limit_notified.remove(&app_id);
                         }
+                    }
 
-                        // Check max uptime limit - enforce restart if exceeded
-                        if let Some(max_uptime) = proc.spec.max_uptime_secs {
-                            if proc.state.uptime_secs >= max_uptime {
-                                warn!(
-                                    "Process {} (id: {}) exceeded max uptime: {}s >= {}s, scheduling restart",
-                                    proc.spec.name, app_id, proc.state.uptime_secs, max_uptime
-                                );
-                                restart_needed.push((*app_id, proc.spec.name.clone(), "max_uptime".to_string()));
-                                pending_restarts.insert(*app_id);
-                            }
+                    // Check max uptime limit - enforce restart if exceeded
+                    if let Some(max_uptime) = proc.spec.max_uptime_secs {
+                        if proc.state.uptime_secs >= max_uptime {
+                            warn!(
+                                "Process {} (id: {}) exceeded max uptime: {}s >= {}s, scheduling restart",
+                                proc.spec.name, app_id, proc.state.uptime_secs, max_uptime
+                            );
+                            restart_needed.push((app_id, proc.spec.name.clone(), "max_uptime".to_string()));
+                            pending_restarts.insert(app_id);
                         }
                     }
-                }
+                });
 
                 // Handle restarts outside of the lock
                 for (app_id, name, reason) in restart_needed {
@@ -1219,32 +4111,29 @@ impl Supervisor {
                     );
 
                     // Get the spec and child for restart
-                    let spec_and_child = {
-                        let mut procs = processes.write();
-                        if let Some(proc) = procs.get_mut(&app_id) {
-                            // Mark as stopping
-                            proc.state.status = AppStatus::Stopping;
-                            let child = proc.child.take();
-                            let spec = proc.spec.clone();
-                            Some((spec, child, proc.state.pid))
-                        } else {
-                            None
-                        }
-                    };
+                    let spec_and_child = processes.with_mut(app_id, |proc| {
+                        // Mark as stopping
+                        proc.state.status = AppStatus::Stopping;
+                        let child = proc.child.take();
+                        let spec = proc.spec.clone();
+                        (spec, child, proc.state.pid)
+                    });
 
                     if let Some((spec, child, pid)) = spec_and_child {
                         // Run on_restart hook if configured (for auto-restart scenarios)
-                        if let Some(restart_script) = spec.hooks.on_restart.clone() {
+                        if let Some(restart_hook) = spec.hooks.on_restart.clone() {
                             let hook_name = spec.name.clone();
+                            let hook_env = HookEnv::of(&spec);
                             tokio::spawn(async move {
-                                let result = run_hook_script(
-                                    &restart_script,
+                                let call = HookCall {
                                     app_id,
-                                    &hook_name,
-                                    "restart",
+                                    app_name: &hook_name,
+                                    event: "restart",
                                     pid,
-                                    None,
-                                ).await;
+                                    exit_code: None,
+                                    extra: &[],
+                                };
+                                let result = run_hook_script(&restart_hook, &call, &hook_env).await;
                                 match result {
                                     Ok(output) => {
                                         if !output.is_empty() {
@@ -1259,214 +4148,2455 @@ impl Supervisor {
                             });
                         }
 
-                        // Stop the current process
-                        if let Some(mut child) = child {
-                            // Send SIGTERM first
-                            #[cfg(unix)]
-                            {
-                                use nix::sys::signal::{kill, Signal};
-                                use nix::unistd::Pid as NixPid;
+                        // Stop the current process
+                        if let Some(mut child) = child {
+                            // Send SIGTERM first
+                            #[cfg(unix)]
+                            {
+                                use nix::sys::signal::{kill, Signal};
+                                use nix::unistd::Pid as NixPid;
+
+                                if let Some(pid) = pid {
+                                    let _ = kill(NixPid::from_raw(pid as i32), Signal::SIGTERM);
+                                }
+                            }
+
+                            // Wait with timeout then kill
+                            let timeout = Duration::from_millis(spec.kill_timeout_ms);
+                            let kill_result = tokio::time::timeout(timeout, child.wait()).await;
+
+                            if kill_result.is_err() {
+                                warn!("Process didn't stop gracefully, sending SIGKILL");
+                                let _ = child.kill().await;
+                            }
+                        }
+
+                        // Update state to stopped
+                        processes.with_mut(app_id, |proc| {
+                            proc.state.status = AppStatus::Stopped;
+                            proc.state.pid = None;
+                            proc.started_at = None;
+                        });
+                        spawn_record_event(
+                            events.clone(),
+                            AppEvent::new(app_id, spec.qualified_name(), EventKind::Stopped)
+                                .with_reason(reason.clone()),
+                        );
+
+                        // Clear from pending restarts so it can be started again
+                        pending_restarts.remove(&app_id);
+                        memory_limit_notified.remove(&app_id);
+
+                        // Note: The actual restart will be handled by the supervision task
+                        // which watches for process exits. We've stopped the process,
+                        // so the supervision task will detect this and restart if auto-restart is enabled.
+                        info!(
+                            "Process {} (id: {}) stopped for {} restart",
+                            spec.name, app_id, reason
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    /// Run the app's hook for `event` in a background task (see
+    /// `run_hook_script` for what the hook gets)
+    fn run_hook(
+        &self,
+        spec: &AppSpec,
+        event: HookEvent,
+        app_id: u32,
+        pid: Option<u32>,
+        exit_code: Option<i32>,
+    ) {
+        if let Some(hook) = spec.hooks.get(event) {
+            let hook = hook.clone();
+            let hook_env = HookEnv::of(spec);
+            let app_name = spec.name.clone();
+            let event_name = event.as_str();
+
+            info!(
+                "Running {} hook for {} (id: {}): {}",
+                event_name, app_name, app_id, hook
+            );
+
+            tokio::spawn(async move {
+                let call = HookCall {
+                    app_id,
+                    app_name: &app_name,
+                    event: event_name,
+                    pid,
+                    exit_code,
+                    extra: &[],
+                };
+                let result = run_hook_script(&hook, &call, &hook_env).await;
+                match result {
+                    Ok(output) => {
+                        if !output.is_empty() {
+                            debug!("Hook output for {} ({}): {}", app_name, event_name, output);
+                        }
+                        info!("Hook {} completed successfully for {}", event_name, app_name);
+                    }
+                    Err(e) => {
+                        error!("Hook {} failed for {}: {}", event_name, app_name, e);
+                    }
+                }
+            });
+        }
+    }
+
+    /// Run the pre_reload hook for a process about to be replaced and wait for
+    /// it, so a drain finishes before the process is stopped. The hook gets the
+    /// old pid as `OPM_PID` and the replacement's as `OPM_NEW_PID` (`{pid}` and
+    /// `{new_pid}` in args); a failure
+    /// is logged and the reload goes on.
+    async fn run_reload_hook(
+        &self,
+        spec: &AppSpec,
+        app_id: u32,
+        app_name: &str,
+        pid: Option<u32>,
+        new_pid: Option<u32>,
+    ) {
+        let Some(hook) = spec.hooks.get(HookEvent::PreReload) else {
+            return;
+        };
+        info!(
+            "Running pre_reload hook for {} (id: {}): {}",
+            app_name, app_id, hook
+        );
+
+        let new_pid = new_pid.map(|pid| pid.to_string());
+        let extra: Vec<(&str, &str)> = new_pid
+            .iter()
+            .map(|pid| ("new_pid", pid.as_str()))
+            .collect();
+        let event = HookEvent::PreReload.as_str();
+        let call = HookCall {
+            app_id,
+            app_name,
+            event,
+            pid,
+            exit_code: None,
+            extra: &extra,
+        };
+        match run_hook_script(hook, &call, &HookEnv::of(spec)).await {
+            Ok(output) => {
+                if !output.is_empty() {
+                    debug!("Hook output for {} ({}): {}", app_name, event, output);
+                }
+                info!("Hook {} completed successfully for {}", event, app_name);
+            }
+            Err(e) => warn!(
+                "Hook {} failed for {}, reloading anyway: {}",
+                event, app_name, e
+            ),
+        }
+    }
+
+    fn pid_of(&self, id: u32) -> Option<u32> {
+        self.processes.published(id).and_then(|proc| proc.state.pid)
+    }
+}
+
+/// Write an event in the background; failures are only logged
+fn spawn_record_event(events: Arc<dyn EventStore>, event: AppEvent) {
+    tokio::spawn(async move {
+        if let Err(e) = events.insert(&event).await {
+            warn!(
+                "Failed to record {} event for {}: {}",
+                event.kind, event.name, e
+            );
+        }
+    });
+}
+
+/// Host totals as of the metrics collector's last refresh
+#[derive(Debug, Clone, Copy)]
+struct HostUsage {
+    cpu_count: usize,
+    total_memory_bytes: u64,
+    used_memory_bytes: u64,
+}
+
+impl HostUsage {
+    /// Read the host's totals from scratch
+    fn current() -> Self {
+        let mut sys = System::new();
+        sys.refresh_cpu();
+        sys.refresh_memory();
+        Self::of(&sys)
+    }
+
+    fn of(sys: &System) -> Self {
+        Self {
+            cpu_count: sys.cpus().len(),
+            total_memory_bytes: sys.total_memory(),
+            used_memory_bytes: sys.used_memory(),
+        }
+    }
+}
+
+/// Refresh host memory and just the given processes and their descendants
+/// (not every process on the host) and sample each tree, keyed by app id
+/// along with the pid sampled
+fn sample_processes(
+    sys: &mut System,
+    targets: &[(u32, u32)],
+    since_refresh: f64,
+) -> HashMap<u32, (u32, ProcessSample)> {
+    let trees: Vec<(u32, u32, Vec<u32>)> = targets
+        .iter()
+        .map(|&(id, pid)| (id, pid, descendants(pid)))
+        .collect();
+    // sysinfo keeps exited processes it was asked about; start over once
+    // enough have piled up
+    let tracked: usize = trees
+        .iter()
+        .map(|(_, _, children)| children.len() + 1)
+        .sum();
+    if sys.processes().len() > tracked * 4 + 64 {
+        *sys = System::new();
+    }
+    if sys.cpus().is_empty() {
+        sys.refresh_cpu();
+    }
+    sys.refresh_memory();
+    let kind = ProcessRefreshKind::new()
+        .with_cpu()
+        .with_memory()
+        .with_disk_usage();
+    let mut samples = HashMap::new();
+    for (id, pid, children) in trees {
+        if !sys.refresh_process_specifics(Pid::from(pid as usize), kind) {
+            continue;
+        }
+        let Some(mut sample) = ProcessSample::of(sys, pid, since_refresh) else {
+            continue;
+        };
+        // An npm or shell wrapper's real workload is further down the tree
+        for child in children {
+            if sys.refresh_process_specifics(Pid::from(child as usize), kind) {
+                if let Some(child) = ProcessSample::of(sys, child, since_refresh) {
+                    sample.add(&child);
+                }
+            }
+        }
+        samples.insert(id, (pid, sample));
+    }
+    samples
+}
+
+/// Every process descended from `pid`, from the kernel's per-thread child
+/// lists (Linux only)
+fn descendants(pid: u32) -> Vec<u32> {
+    if !cfg!(target_os = "linux") {
+        return Vec::new();
+    }
+    let mut seen = HashSet::from([pid]);
+    let mut found = Vec::new();
+    let mut queue = vec![pid];
+    while let Some(parent) = queue.pop() {
+        let Ok(tasks) = std::fs::read_dir(format!("/proc/{}/task", parent)) else {
+            continue;
+        };
+        for task in tasks.flatten() {
+            let Ok(children) = std::fs::read_to_string(task.path().join("children")) else {
+                continue;
+            };
+            for child in children.split_whitespace().filter_map(|c| c.parse().ok()) {
+                if seen.insert(child) {
+                    found.push(child);
+                    queue.push(child);
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Send `signal` to `pid` and every process descended from it
+#[cfg(unix)]
+fn signal_tree(pid: u32, signal: nix::sys::signal::Signal) {
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid as NixPid;
+
+    for pid in std::iter::once(pid).chain(descendants(pid)) {
+        let _ = kill(NixPid::from_raw(pid as i32), signal);
+    }
+}
+
+/// Whether the process passed a health check since it started or, without
+/// one, stayed up for `ROLLOUT_SETTLE_MS`
+fn passed_health_check(proc: &PublishedProcess) -> bool {
+    match (&proc.spec.health_check, proc.state.started_at) {
+        (None, Some(started)) => {
+            chrono::Utc::now() - started
+                >= chrono::Duration::milliseconds(constants::ROLLOUT_SETTLE_MS)
+        }
+        (Some(_), Some(started)) => {
+            proc.state.healthy
+                && proc
+                    .state
+                    .last_health_check
+                    .is_some_and(|checked| checked >= started)
+        }
+        (_, None) => false,
+    }
+}
+
+fn join_targets(targets: &[WaitTarget]) -> String {
+    targets
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Whether the warm-up of the process with `pid` is still running
+fn is_warming(warming: &RwLock<HashMap<u32, u32>>, app_id: u32, pid: Option<u32>) -> bool {
+    pid.is_some_and(|pid| warming.read().get(&app_id) == Some(&pid))
+}
+
+/// Whether the app is frozen with `oxidepm pause`
+fn is_paused(processes: &ProcessTable<SupervisedProcess>, app_id: u32) -> bool {
+    processes
+        .published(app_id)
+        .is_some_and(|proc| proc.state.status == AppStatus::Paused)
+}
+
+/// One process's usage, read while no supervisor lock is held
+#[derive(Debug, Clone, Copy)]
+struct ProcessSample {
+    cpu_percent: f32,
+    memory_bytes: u64,
+    processes: Option<u32>,
+    threads: Option<u32>,
+    open_fds: Option<u32>,
+    disk_read_bytes_per_sec: u64,
+    disk_write_bytes_per_sec: u64,
+}
+
+impl ProcessSample {
+    fn of(sys: &System, pid: u32, since_refresh: f64) -> Option<Self> {
+        let process = sys.process(Pid::from(pid as usize))?;
+        let disk = process.disk_usage();
+        Some(Self {
+            cpu_percent: process.cpu_usage(),
+            memory_bytes: process.memory(),
+            processes: cfg!(target_os = "linux").then_some(1),
+            threads: thread_count(pid),
+            open_fds: open_fd_count(pid),
+            disk_read_bytes_per_sec: (disk.read_bytes as f64 / since_refresh) as u64,
+            disk_write_bytes_per_sec: (disk.written_bytes as f64 / since_refresh) as u64,
+        })
+    }
+
+    /// Count a descendant's usage in with this process's
+    fn add(&mut self, child: &ProcessSample) {
+        fn sum(total: Option<u32>, part: Option<u32>) -> Option<u32> {
+            total.map(|total| total + part.unwrap_or(0))
+        }
+        self.cpu_percent += child.cpu_percent;
+        self.memory_bytes += child.memory_bytes;
+        self.processes = sum(self.processes, child.processes);
+        self.threads = sum(self.threads, child.threads);
+        self.open_fds = sum(self.open_fds, child.open_fds);
+        self.disk_read_bytes_per_sec += child.disk_read_bytes_per_sec;
+        self.disk_write_bytes_per_sec += child.disk_write_bytes_per_sec;
+    }
+
+    fn apply(&self, state: &mut RunState) {
+        state.cpu_percent = self.cpu_percent;
+        state.memory_bytes = self.memory_bytes;
+        state.processes = self.processes;
+        state.threads = self.threads;
+        state.open_fds = self.open_fds;
+        state.disk_read_bytes_per_sec = self.disk_read_bytes_per_sec;
+        state.disk_write_bytes_per_sec = self.disk_write_bytes_per_sec;
+    }
+}
+
+/// Count of open file descriptors from /proc/<pid>/fd on Linux
+fn open_fd_count(pid: u32) -> Option<u32> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let entries = std::fs::read_dir(format!("/proc/{}/fd", pid)).ok()?;
+    Some(entries.count() as u32)
+}
+
+/// Thread count from /proc/<pid>/status on Linux
+fn thread_count(pid: u32) -> Option<u32> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Threads:"))
+        .and_then(|count| count.trim().parse().ok())
+}
+
+/// State as shown to clients: only running apps are ready, a cluster
+/// parent is ready while any of its instances is, and drained or paused
+/// apps never are
+fn reported_state(
+    processes: &HashMap<u32, PublishedProcess>,
+    drains: &HashMap<u32, Drain>,
+    proc: &PublishedProcess,
+) -> RunState {
+    let mut state = proc.state.clone();
+    state.draining = drains.contains_key(&proc.spec.id);
+    if state.draining {
+        state.ready = false;
+    } else if !proc.cluster_instance_ids.is_empty() {
+        state.ready = proc
+            .cluster_instance_ids
+            .iter()
+            .filter(|id| !drains.contains_key(id))
+            .filter_map(|id| processes.get(id))
+            .any(|instance| {
+                instance.state.status.is_running()
+                    && instance.state.status != AppStatus::Paused
+                    && instance.state.ready
+            });
+    } else if !state.status.is_running() || state.status == AppStatus::Paused {
+        state.ready = false;
+    }
+    state
+}
+
+/// Append a check result to an app's history, dropping the oldest past the limit
+fn record_health(history: &HealthHistory, app_id: u32, probe: Probe, result: &HealthCheckResult) {
+    let mut history = history.write();
+    let records = history.entry((app_id, probe)).or_default();
+    if records.len() >= constants::HEALTH_HISTORY_SIZE {
+        records.pop_front();
+    }
+    records.push_back(HealthRecord {
+        timestamp: result.timestamp,
+        healthy: result.healthy,
+        duration_ms: result.duration_ms,
+        message: result.message.clone(),
+    });
+}
+
+/// Decide whether a crashed app is restarted, with the delay before it
+/// runs: none with `auto_restart` off or once `max_restarts` crashes in a row
+/// came within `crash_window_secs` of their start; the delay backs off
+/// exponentially over the streak.
+fn crash_action(proc: &SupervisedProcess, app_id: u32) -> Option<(SupervisorAction, Duration)> {
+    let policy = &proc.spec.restart_policy;
+    if !policy.auto_restart {
+        return None;
+    }
+    let stable = proc
+        .started_at
+        .is_some_and(|started| started.elapsed().as_secs() >= policy.crash_window_secs);
+    let attempt = if stable {
+        0
+    } else {
+        proc.state.restarts_in_window
+    };
+    if attempt >= policy.max_restarts {
+        warn!(
+            "App {} crashed {} times in a row, not restarting",
+            app_id,
+            attempt + 1
+        );
+        return None;
+    }
+
+    let delay_ms = policy.backoff_delay_ms(attempt);
+    info!("App {} crashed, restarting in {}ms", app_id, delay_ms);
+    Some((
+        SupervisorAction::CrashRestart {
+            id: app_id,
+            attempt,
+        },
+        Duration::from_millis(delay_ms),
+    ))
+}
+
+/// Decide what the `on_unhealthy` policy asks for, with the delay before it
+/// runs. Restarts stop once `max_restarts` consecutive health restarts have
+/// failed to help; the streak resets after `crash_window_secs` of uptime.
+fn unhealthy_action(proc: &SupervisedProcess, app_id: u32) -> Option<(SupervisorAction, Duration)> {
+    let policy = &proc.spec.restart_policy;
+    let on_unhealthy = proc.spec.health_check.as_ref()?.on_unhealthy;
+
+    match on_unhealthy {
+        UnhealthyAction::NotifyOnly => None,
+        UnhealthyAction::Stop => Some((SupervisorAction::Stop { id: app_id }, Duration::ZERO)),
+        UnhealthyAction::Restart | UnhealthyAction::RestartWithBackoff => {
+            let stable = proc
+                .started_at
+                .is_some_and(|started| started.elapsed().as_secs() >= policy.crash_window_secs);
+            let attempt = if stable {
+                0
+            } else {
+                proc.state.restarts_in_window
+            };
+            if attempt >= policy.max_restarts {
+                warn!(
+                    "App {} still unhealthy after {} restarts, giving up",
+                    app_id, attempt
+                );
+                return None;
+            }
+
+            let delay_ms = if on_unhealthy == UnhealthyAction::RestartWithBackoff {
+                policy.backoff_delay_ms(attempt)
+            } else {
+                policy.restart_delay_ms
+            };
+            info!(
+                "App {} unhealthy, restarting in {}ms ({})",
+                app_id, delay_ms, on_unhealthy
+            );
+            Some((
+                SupervisorAction::Restart {
+                    id: app_id,
+                    attempt,
+                },
+                Duration::from_millis(delay_ms),
+            ))
+        }
+    }
+}
+
+/// Variables hooks keep from the daemon's environment; everything else
+/// comes from the app's own `env`
+const HOOK_BASE_ENV: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "SHELL", "LANG", "LC_ALL", "TZ", "TMPDIR",
+];
+
+/// What an app's hooks run with: the app's environment and, with
+/// `hook_user`, the account to run as
+#[derive(Debug, Clone, Default)]
+struct HookEnv {
+    env: HashMap<String, String>,
+    user: Option<String>,
+    /// Name the hook log is kept under (`AppSpec::log_name`); the app name when unset
+    log_name: Option<String>,
+}
+
+impl HookEnv {
+    fn of(spec: &AppSpec) -> Self {
+        Self {
+            env: spec.env.clone(),
+            user: spec.hook_user.clone(),
+            log_name: Some(spec.log_name()),
+        }
+    }
+}
+
+/// One run of a hook: the event and the values it passes on
+struct HookCall<'a> {
+    app_id: u32,
+    app_name: &'a str,
+    event: &'a str,
+    pid: Option<u32>,
+    exit_code: Option<i32>,
+    /// Event-specific values, e.g. `("new_pid", "123")`: `{new_pid}` in
+    /// args and `OPM_NEW_PID` in the environment
+    extra: &'a [(&'a str, &'a str)],
+}
+
+impl HookCall<'_> {
+    /// Placeholder names and values; pid and exit code only when known
+    fn values(&self) -> Vec<(&str, String)> {
+        let mut values = vec![
+            ("app_id", self.app_id.to_string()),
+            ("app_name", self.app_name.to_string()),
+            ("event", self.event.to_string()),
+        ];
+        values.extend(self.pid.map(|pid| ("pid", pid.to_string())));
+        values.extend(self.exit_code.map(|code| ("exit_code", code.to_string())));
+        values.extend(
+            self.extra
+                .iter()
+                .map(|(name, value)| (*name, value.to_string())),
+        );
+        values
+    }
+}
+
+/// Execute a hook
+///
+/// A shell hook (a plain string or `run`) goes through `sh -c`; a `command`
+/// hook is executed directly with its `args` interpolated. Either way the hook
+/// starts from a clean environment: a few basics of the daemon's
+/// (`HOOK_BASE_ENV`), the app's `env`, and
+/// - OPM_APP_ID: The application ID
+/// - OPM_APP_NAME: The application name
+/// - OPM_EVENT: The event type (start, stop, restart, crash, error, ...)
+/// - OPM_PID: The process ID (if available)
+/// - OPM_EXIT_CODE: The exit code (if available)
+/// - OPM_<NAME> for each of the call's extra values
+///
+/// The hook runs in a process group of its own, which is killed when the
+/// hook's timeout runs out. Hook output is logged to a separate hook log file.
+async fn run_hook_script(
+    hook: &Hook,
+    call: &HookCall<'_>,
+    hook_env: &HookEnv,
+) -> std::result::Result<String, String> {
+    use std::process::Stdio;
+    use tokio::process::Command;
+
+    let values = call.values();
+    let mut cmd = match hook {
+        Hook::Shell(script)
+        | Hook::Detailed(HookSpec {
+            run: Some(script), ..
+        }) => {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(script);
+            cmd
+        }
+        Hook::Detailed(
+            spec @ HookSpec {
+                command: Some(program),
+                ..
+            },
+        ) => {
+            let mut cmd = Command::new(program);
+            cmd.args(spec.interpolated_args(&values));
+            cmd
+        }
+        Hook::Detailed(_) => return Err("Hook has neither `run` nor `command`".to_string()),
+    };
+
+    cmd.env_clear();
+    cmd.envs(std::env::vars().filter(|(key, _)| HOOK_BASE_ENV.contains(&key.as_str())));
+    cmd.envs(&hook_env.env);
+    for (name, value) in &values {
+        cmd.env(format!("OPM_{}", name.to_uppercase()), value);
+    }
+
+    #[cfg(unix)]
+    if let Some(user) = &hook_env.user {
+        let account = nix::unistd::User::from_name(user)
+            .map_err(|e| format!("Failed to look up hook_user '{}': {}", user, e))?
+            .ok_or_else(|| format!("Unknown hook_user '{}'", user))?;
+        cmd.uid(account.uid.as_raw()).gid(account.gid.as_raw());
+        cmd.env("HOME", &account.dir);
+        cmd.env("USER", &account.name);
+        cmd.env("LOGNAME", &account.name);
+    }
+
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    cmd.kill_on_drop(true);
+
+    let child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to execute hook: {}", e))?;
+    let pgid = child.id();
+    let timeout_secs = hook.timeout_secs();
+    let result =
+        tokio::time::timeout(Duration::from_secs(timeout_secs), child.wait_with_output()).await;
+
+    match result {
+        Ok(Ok(output)) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+
+            // Log hook output to a separate file
+            let log_name = hook_env.log_name.as_deref().unwrap_or(call.app_name);
+            if let Err(e) = log_hook_output(log_name, call.event, &stdout, &stderr) {
+                warn!("Failed to log hook output: {}", e);
+            }
+
+            if output.status.success() {
+                Ok(stdout.trim().to_string())
+            } else {
+                Err(format!(
+                    "Hook exited with code {:?}: {}",
+                    output.status.code(),
+                    stderr.trim()
+                ))
+            }
+        }
+        Ok(Err(e)) => Err(format!("Failed to execute hook: {}", e)),
+        Err(_) => {
+            // Whatever the hook started goes too
+            #[cfg(unix)]
+            if let Some(pgid) = pgid {
+                use nix::sys::signal::{killpg, Signal};
+                let _ = killpg(nix::unistd::Pid::from_raw(pgid as i32), Signal::SIGKILL);
+            }
+            Err(format!("Hook timed out after {}s", timeout_secs))
+        }
+    }
+}
+
+/// Append a failed watch rebuild's compiler output to the app's error log
+/// Builds started for one app, or for all instances of a cluster, share a
+/// group: the app's qualified name without the instance suffix
+fn build_group(spec: &AppSpec) -> String {
+    let name = spec.qualified_name();
+    match spec.instance_id {
+        Some(instance) => name
+            .strip_suffix(&format!("-{}", instance))
+            .map(String::from)
+            .unwrap_or(name),
+        None => name,
+    }
+}
+
+/// Run the runner's prepare step, copying build output to the app's log
+/// (prefixed with `[build]`) and to `progress` as it is produced
+async fn prepare_logged(
+    runner: &dyn Runner,
+    spec: &AppSpec,
+    progress: &ProgressSink,
+) -> Result<PrepareResult> {
+    let log = match LogWriter::new(spec.stdout_log_path(), RotationConfig::default()) {
+        Ok(log) => Some(Mutex::new(log.with_prefix(LinePrefix::for_app(spec)))),
+        Err(e) => {
+            warn!(
+                "Failed to open the log of {} for build output: {}",
+                spec.name, e
+            );
+            None
+        }
+    };
+    let log = Arc::new(log);
+    let outer = progress.clone();
+    let logged = ProgressSink::new(move |line| {
+        if let Some(log) = log.as_ref() {
+            let _ = log.lock().write_line(&format!("[build] {}", line));
+        }
+        outer.send(line);
+    });
+    runner.prepare_with_progress(spec, &logged).await
+}
+
+fn log_build_failure(spec: &AppSpec, output: &str) -> Result<()> {
+    let mut log = LogWriter::new(spec.stderr_log_path(), RotationConfig::default())?
+        .with_prefix(LinePrefix::for_app(spec));
+    log.write_line(
+        "[oxidepm] Rebuild after file change failed; the previous build is still running",
+    )?;
+    for line in output.lines() {
+        log.write_line(line)?;
+    }
+    Ok(())
+}
+
+/// File hook output for an app is appended to
+/// Log files of an app and where they go once it is renamed to `to`: both
+/// streams with their rotated copies, for the app and each cluster
+/// instance, and the hook logs. Custom log paths stay where they are.
+fn log_moves(from: &AppSpec, to: &AppSpec) -> Vec<(PathBuf, PathBuf)> {
+    let mut pairs = vec![(from.clone(), to.clone())];
+    if from.instances > 1 {
+        pairs.extend(
+            (0..from.instances).map(|i| (from.for_instance(i, None), to.for_instance(i, None))),
+        );
+    }
+
+    let mut moves = Vec::new();
+    for (old, new) in &pairs {
+        let logs = [
+            (old.stdout_log_path(), new.stdout_log_path()),
+            (old.stderr_log_path(), new.stderr_log_path()),
+            (
+                hook_log_path(&old.log_name()),
+                hook_log_path(&new.log_name()),
+            ),
+        ];
+        for (old_path, new_path) in logs {
+            if old_path == new_path {
+                continue;
+            }
+            let old_name = old_path.file_name().unwrap_or_default().to_string_lossy();
+            let new_name = new_path.file_name().unwrap_or_default().to_string_lossy();
+            for file in oxidepm_logs::log_files(&old_path) {
+                // The active file, or a rotated copy with its `.N` suffix
+                let file_name = file.path.file_name().unwrap_or_default().to_string_lossy();
+                let suffix = file_name
+                    .strip_prefix(old_name.as_ref())
+                    .unwrap_or_default();
+                let dest = new_path.with_file_name(format!("{}{}", new_name, suffix));
+                moves.push((file.path, dest));
+            }
+        }
+    }
+    // Merged streams share a file
+    moves.sort();
+    moves.dedup();
+    moves
+}
+
+fn hook_log_path(log_name: &str) -> PathBuf {
+    constants::logs_dir().join(format!("{}-hooks.log", log_name))
+}
+
+/// Log hook output to a separate log file
+fn log_hook_output(log_name: &str, event: &str, stdout: &str, stderr: &str) -> std::io::Result<()> {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    let log_dir = constants::logs_dir();
+    if !log_dir.exists() {
+        std::fs::create_dir_all(&log_dir)?;
+    }
+
+    let log_path = hook_log_path(log_name);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+
+    let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
+    writeln!(file, "[{}] Event: {}", timestamp, event)?;
+
+    if !stdout.is_empty() {
+        writeln!(file, "  stdout: {}", stdout.trim())?;
+    }
+    if !stderr.is_empty() {
+        writeln!(file, "  stderr: {}", stderr.trim())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::{EnvSource, Hooks};
+    
+
+    async fn test_supervisor() -> (Supervisor, tempfile::TempDir) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = Database::new(&dir.path().join("test.db")).await.unwrap();
+        (Supervisor::new(db).await.unwrap(), dir)
+    }
+
+    /// Lines a test hook has appended to `path`, waiting for at least `count`
+    async fn hook_lines(path: &std::path::Path, count: usize) -> Vec<String> {
+        for _ in 0..100 {
+            let lines: Vec<String> = std::fs::read_to_string(path)
+                .unwrap_or_default()
+                .lines()
+                .map(String::from)
+                .collect();
+            if lines.len() >= count {
+                return lines;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("hooks did not write {} lines to {}", count, path.display());
+    }
+
+    #[tokio::test]
+    async fn test_reload_hooks_run_in_order() {
+        let (supervisor, dir) = test_supervisor().await;
+        let record = dir.path().join("hooks.log");
+        let hook = Hook::from(format!(
+            "echo \"$OPM_EVENT $OPM_APP_NAME pid=$OPM_PID new=$OPM_NEW_PID label=$LABEL\" >> {}",
+            record.display()
+        ));
+        let mut spec = AppSpec::new(
+            "hooked".to_string(),
+            AppMode::Cmd,
+            "sleep".to_string(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec!["30".to_string()]);
+        spec.env
+            .insert("LABEL".to_string(), "from-app-env".to_string());
+        spec.logs.out = Some(dir.path().join("out.log"));
+        spec.logs.err = Some(dir.path().join("err.log"));
+        spec.hooks = Hooks {
+            on_start: Some(hook.clone()),
+            on_stop: Some(hook.clone()),
+            on_pre_reload: Some(hook.clone()),
+            on_post_reload: Some(hook),
+            ..Default::default()
+        };
+
+        let old_id = supervisor.start(spec).await.unwrap();
+        let old_pid = supervisor.pid_of(old_id).unwrap();
+        hook_lines(&record, 1).await;
+
+        assert!(supervisor.reload(old_id).await.unwrap());
+        let new_id = supervisor
+            .processes
+            .published_all()
+            .into_keys()
+            .find(|id| *id != old_id)
+            .unwrap();
+        let new_pid = supervisor.pid_of(new_id).unwrap();
+        let mut lines = hook_lines(&record, 5).await;
+        let _ = supervisor.stop(new_id).await;
+
+        assert_eq!(
+            lines[0],
+            format!("start hooked pid={} new= label=from-app-env", old_pid)
+        );
+        assert_eq!(
+            lines[1],
+            format!(
+                "start hooked-reload pid={} new= label=from-app-env",
+                new_pid
+            )
+        );
+        // The drain hook runs with both pids, after the replacement started and before the old process stops
+        assert_eq!(
+            lines[2],
+            format!(
+                "pre_reload hooked pid={} new={} label=from-app-env",
+                old_pid, new_pid
+            )
+        );
+        // Stop and post_reload are fired together once the old process is gone
+        lines[3..].sort();
+        assert_eq!(
+            lines[3..],
+            [
+                format!("post_reload hooked pid={} new= label=from-app-env", new_pid),
+                format!("stop hooked pid={} new= label=from-app-env", old_pid),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pause_resume_supervision() {
+        let (supervisor, _dir) = test_supervisor().await;
+        assert!(!supervisor.is_supervision_paused());
+
+        assert!(supervisor.set_supervision_paused(true));
+        assert!(supervisor.is_supervision_paused());
+        // Pausing twice is a no-op
+        assert!(!supervisor.set_supervision_paused(true));
+
+        assert!(supervisor.set_supervision_paused(false));
+        assert!(!supervisor.is_supervision_paused());
+    }
+
+    #[tokio::test]
+    async fn test_apply_dry_run_plan() {
+        let (supervisor, _dir) = test_supervisor().await;
+        let spec = |name: &str| {
+            AppSpec::new(
+                name.to_string(),
+                oxidepm_core::AppMode::Cmd,
+                "sleep".to_string(),
+                std::path::PathBuf::from("/tmp"),
+            )
+        };
+        supervisor.db.apps().insert(&spec("api")).await.unwrap();
+        supervisor.db.apps().insert(&spec("old")).await.unwrap();
+
+        let mut api = spec("api");
+        api.env.insert("PORT".to_string(), "3000".to_string());
+        let report = supervisor
+            .apply(vec![api, spec("new")], true, true)
+            .await
+            .unwrap();
+
+        assert!(report.dry_run);
+        assert_eq!(report.reloaded, vec!["api"]);
+        assert_eq!(report.started, vec!["new"]);
+        assert_eq!(report.deleted, vec!["old"]);
+        // Nothing was changed
+        assert_eq!(supervisor.status().await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_unhealthy_action_policy() {
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cmd,
+            "server".into(),
+            "/".into(),
+        );
+        spec.restart_policy.restart_delay_ms = 1000;
+        spec.restart_policy.max_restarts = 3;
+        let mut proc = SupervisedProcess {
+            spec,
+            state: RunState::new(1),
+            child: None,
+            restart_count: 0,
+            last_restart: None,
+            started_at: Some(Instant::now()),
+            health_monitor: None,
+            cluster_instance_ids: Vec::new(),
+            parent_id: None,
+            log_lines_dropped: None,
+        };
+        proc.state.restarts_in_window = 2;
+        let mut with_policy = |action| {
+            proc.spec.health_check = Some(oxidepm_core::HealthCheck {
+                on_unhealthy: action,
+                ..Default::default()
+            });
+            unhealthy_action(&proc, 1)
+        };
+
+        assert_eq!(with_policy(UnhealthyAction::NotifyOnly), None);
+        assert_eq!(
+            with_policy(UnhealthyAction::Stop),
+            Some((SupervisorAction::Stop { id: 1 }, Duration::ZERO))
+        );
+        assert_eq!(
+            with_policy(UnhealthyAction::Restart),
+            Some((
+                SupervisorAction::Restart { id: 1, attempt: 2 },
+                Duration::from_millis(1000)
+            ))
+        );
+        assert_eq!(
+            with_policy(UnhealthyAction::RestartWithBackoff),
+            Some((
+                SupervisorAction::Restart { id: 1, attempt: 2 },
+                Duration::from_millis(4000)
+            ))
+        );
+
+        // Out of restarts: fall back to marking the app errored
+        proc.state.restarts_in_window = 3;
+        proc.spec.health_check = Some(oxidepm_core::HealthCheck {
+            on_unhealthy: UnhealthyAction::Restart,
+            ..Default::default()
+        });
+        assert_eq!(unhealthy_action(&proc, 1), None);
+    }
+
+    #[test]
+    fn test_crash_action_policy() {
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cmd,
+            "server".into(),
+            "/".into(),
+        );
+        spec.restart_policy.restart_delay_ms = 1000;
+        spec.restart_policy.max_restarts = 3;
+        let mut proc = SupervisedProcess {
+            spec,
+            state: RunState::new(1),
+            child: None,
+            restart_count: 0,
+            last_restart: None,
+            started_at: Some(Instant::now()),
+            health_monitor: None,
+            cluster_instance_ids: Vec::new(),
+            parent_id: None,
+            log_lines_dropped: None,
+        };
+
+        proc.state.restarts_in_window = 2;
+        assert_eq!(
+            crash_action(&proc, 1),
+            Some((
+                SupervisorAction::CrashRestart { id: 1, attempt: 2 },
+                Duration::from_millis(4000)
+            ))
+        );
+
+        // Crash-looping: give up
+        proc.state.restarts_in_window = 3;
+        assert_eq!(crash_action(&proc, 1), None);
+
+        // Up for the whole crash window: the streak starts over
+        proc.spec.restart_policy.crash_window_secs = 0;
+        assert_eq!(
+            crash_action(&proc, 1),
+            Some((
+                SupervisorAction::CrashRestart { id: 1, attempt: 0 },
+                Duration::from_millis(1000)
+            ))
+        );
+
+        proc.spec.restart_policy.auto_restart = false;
+        assert_eq!(crash_action(&proc, 1), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_proc_counts() {
+        let pid = std::process::id();
+        assert!(open_fd_count(pid).unwrap() >= 3);
+        assert!(thread_count(pid).unwrap() >= 1);
+        assert_eq!(open_fd_count(u32::MAX), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_process_tree_sample() {
+        let mut wrapper = std::process::Command::new("sh")
+            .args(["-c", "sleep 30 & sleep 30 & wait"])
+            .spawn()
+            .unwrap();
+        let pid = wrapper.id();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while descendants(pid).len() < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let children = descendants(pid);
+
+        let mut sys = System::new();
+        let samples = sample_processes(&mut sys, &[(1, pid)], 1.0);
+        let (_, sample) = samples[&1];
+        let _ = wrapper.kill();
+        let _ = wrapper.wait();
+        for child in children.iter() {
+            let _ = nix::sys::signal::kill(
+                nix::unistd::Pid::from_raw(*child as i32),
+                nix::sys::signal::Signal::SIGKILL,
+            );
+        }
+
+        assert_eq!(children.len(), 2);
+        assert_eq!(sample.processes, Some(3));
+        assert!(sample.threads.unwrap() >= 3);
+        let wrapper_only = ProcessSample::of(&sys, pid, 1.0).unwrap();
+        assert!(sample.memory_bytes > wrapper_only.memory_bytes);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_process_sample() {
+        let pid = std::process::id();
+        let mut sys = System::new();
+        let samples = sample_processes(&mut sys, &[(1, pid), (2, u32::MAX)], 1.0);
+        assert_eq!(samples.len(), 1);
+        let (sampled_pid, sample) = samples[&1];
+        assert_eq!(sampled_pid, pid);
+        assert!(sample.memory_bytes > 0);
+        // Only the processes asked about are refreshed
+        assert_eq!(sys.processes().len(), 1);
+        assert!(HostUsage::of(&sys).total_memory_bytes > 0);
+
+        let mut state = RunState::new(1);
+        sample.apply(&mut state);
+        assert_eq!(state.memory_bytes, sample.memory_bytes);
+        assert_eq!(state.open_fds, sample.open_fds);
+    }
+
+    #[test]
+    fn test_reported_state_readiness() {
+        let spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cmd,
+            "server".into(),
+            "/".into(),
+        );
+        let process = |state: RunState, instances: Vec<u32>| PublishedProcess {
+            spec: spec.clone(),
+            state,
+            cluster_instance_ids: instances,
+        };
+        let mut not_ready = RunState::running(3, 300);
+        not_ready.ready = false;
+        let mut processes = HashMap::from([
+            (1, process(RunState::running(1, 100), vec![2, 3])),
+            (2, process(RunState::running(2, 200), Vec::new())),
+            (3, process(not_ready, Vec::new())),
+        ]);
+
+        // A cluster is ready while any instance is
+        assert!(reported_state(&processes, &HashMap::new(), &processes[&1]).ready);
+        assert!(!reported_state(&processes, &HashMap::new(), &processes[&3]).ready);
+        processes.get_mut(&2).unwrap().state.ready = false;
+        assert!(!reported_state(&processes, &HashMap::new(), &processes[&1]).ready);
+
+        // Stopped apps are never ready
+        let mut stopped = RunState::running(2, 200);
+        stopped.status = AppStatus::Stopped;
+        processes.get_mut(&3).unwrap().state = stopped;
+        assert!(!reported_state(&processes, &HashMap::new(), &processes[&3]).ready);
+    }
+
+    #[tokio::test]
+    async fn test_save_keeps_tracked_spec() {
+        let (supervisor, dir) = test_supervisor().await;
+        let track = |spec: AppSpec| {
+            supervisor.processes.insert(
+                spec.id,
+                SupervisedProcess {
+                    state: RunState::new(spec.id),
+                    spec,
+                    child: None,
+                    restart_count: 0,
+                    last_restart: None,
+                    started_at: None,
+                    health_monitor: None,
+                    cluster_instance_ids: Vec::new(),
+                    parent_id: None,
+                    log_lines_dropped: None,
+                },
+            );
+        };
+
+        let mut api = AppSpec::new("api".into(), AppMode::Cmd, "server".into(), "/srv".into())
+            .with_instances(2)
+            .with_port(4000);
+        api.health_check = Some(oxidepm_core::HealthCheck::tcp("127.0.0.1", 4000));
+        api.hooks.on_start = Some(Hook::from("./warm.sh"));
+        api.sandbox = Some(oxidepm_core::Sandbox::default());
+        api.limits.nofile = Some(4096);
+        api.id = supervisor.db.apps().insert(&api).await.unwrap();
+        let mut instance = api.for_instance(0, Some(4000));
+        instance.id = supervisor.db.apps().insert(&instance).await.unwrap();
+        let idle = AppSpec::new("idle".into(), AppMode::Cmd, "worker".into(), "/srv".into());
+        supervisor.db.apps().insert(&idle).await.unwrap();
+        track(api.clone());
+        track(instance);
+
+        let path = dir.path().join("saved.json");
+        let (count, _) = supervisor.save(Some(&path)).await.unwrap();
+        assert_eq!(count, 2);
+
+        let (saved, _) = supervisor.load_saved(Some(&path)).unwrap().unwrap();
+        let names: Vec<&str> = saved.apps.iter().map(|spec| spec.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "idle"]);
+        let restored = &saved.apps[0];
+        assert_eq!(restored.instances, 2);
+        assert_eq!(restored.port, Some(4000));
+        assert_eq!(
+            restored
+                .health_check
+                .as_ref()
+                .map(HealthCheck::endpoint)
+                .as_deref(),
+            Some("tcp://127.0.0.1:4000")
+        );
+        assert_eq!(
+            restored.hooks.get(HookEvent::Start),
+            Some(&Hook::from("./warm.sh"))
+        );
+        assert_eq!(restored.sandbox, api.sandbox);
+        assert_eq!(restored.limits.nofile, Some(4096));
+    }
+
+    /// Wait until app `id` has crashed
+    async fn wait_errored(supervisor: &Supervisor, id: u32) {
+        for _ in 0..100 {
+            if supervisor.processes.published(id).unwrap().state.status == AppStatus::Errored {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("app {} did not crash", id);
+    }
+
+    #[tokio::test]
+    async fn test_drain_and_undrain() {
+        let (supervisor, dir) = test_supervisor().await;
+        let mut actions = supervisor.take_actions().unwrap();
+        let spec = AppSpec::new(
+            "api".into(),
+            AppMode::Cmd,
+            "sleep".into(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec!["30".into()]);
+        let id = supervisor.start(spec).await.unwrap();
+        let state = |supervisor: &Supervisor| {
+            let processes = supervisor.processes.published_all();
+            reported_state(&processes, &supervisor.drains.read(), &processes[&id])
+        };
+        assert!(state(&supervisor).ready);
+
+        assert_eq!(supervisor.drain(&[id], Duration::from_millis(50)), 1);
+        let drained = state(&supervisor);
+        assert!(drained.draining);
+        assert!(!drained.ready);
+        assert!(drained.status.is_running());
+
+        let action = tokio::time::timeout(Duration::from_secs(5), actions.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(action, SupervisorAction::DrainTimeout { id: drained, .. } if drained == id)
+        );
+        supervisor.run_action(action).await;
+        assert_eq!(state(&supervisor).status, AppStatus::Stopped);
+
+        assert_eq!(supervisor.undrain(&[id]).await.unwrap(), (1, 1));
+        let undrained = state(&supervisor);
+        assert!(!undrained.draining);
+        assert!(undrained.ready);
+        assert!(undrained.status.is_running());
+
+        // A timeout of a drain that was ended does nothing
+        supervisor
+            .run_action(SupervisorAction::DrainTimeout {
+                id,
+                since: Utc::now(),
+            })
+            .await;
+        assert!(state(&supervisor).status.is_running());
+        supervisor.stop(id).await.unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        let (supervisor, dir) = test_supervisor().await;
+        let spec = AppSpec::new(
+            "api".into(),
+            AppMode::Cmd,
+            "sleep".into(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec!["30".into()]);
+        let id = supervisor.start(spec).await.unwrap();
+        let pid = supervisor.pid_of(id).unwrap();
+        // Signals land asynchronously; wait for the state to settle
+        let is_stopped = |stopped: bool| async move {
+            for _ in 0..100 {
+                let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+                // The state follows the parenthesised command name
+                let state = stat.rsplit(')').next().unwrap().split_whitespace().next();
+                if (state == Some("T")) == stopped {
+                    return true;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            false
+        };
+        let state = |supervisor: &Supervisor| {
+            let processes = supervisor.processes.published_all();
+            reported_state(&processes, &supervisor.drains.read(), &processes[&id])
+        };
+
+        assert!(supervisor.pause(id).unwrap());
+        let paused = state(&supervisor);
+        assert_eq!(paused.status, AppStatus::Paused);
+        assert!(!paused.ready);
+        assert!(is_stopped(true).await);
+        // Pausing twice changes nothing
+        assert!(!supervisor.pause(id).unwrap());
+
+        assert!(supervisor.resume(id).unwrap());
+        assert_eq!(state(&supervisor).status, AppStatus::Running);
+        assert!(state(&supervisor).ready);
+        assert!(is_stopped(false).await);
+        assert!(!supervisor.resume(id).unwrap());
+
+        // A paused app still stops on SIGTERM rather than the kill timeout
+        assert!(supervisor.pause(id).unwrap());
+        let started = std::time::Instant::now();
+        assert!(supervisor.stop(id).await.unwrap());
+        assert_eq!(state(&supervisor).status, AppStatus::Stopped);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_signal_passthrough() {
+        let (supervisor, dir) = test_supervisor().await;
+        let spec = AppSpec::new(
+            "api".into(),
+            AppMode::Cmd,
+            "sh".into(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec![
+            "-c".into(),
+            "trap 'echo reopened > signalled' USR1; while :; do sleep 0.05; done".into(),
+        ]);
+        let id = supervisor.start(spec).await.unwrap();
+        // Give the shell time to install its trap
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        assert!(supervisor.signal(id, AppSignal::Sigusr1, false).unwrap());
+        let marker = dir.path().join("signalled");
+        for _ in 0..100 {
+            if marker.exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(marker.exists());
+        // The app handled it and keeps running
+        let status = supervisor.processes.published(id).unwrap().state.status;
+        assert!(status.is_running());
+
+        supervisor.stop(id).await.unwrap();
+        assert!(!supervisor.signal(id, AppSignal::Sigusr1, false).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_exec_in_app_context() {
+        let (supervisor, dir) = test_supervisor().await;
+        let mut spec = AppSpec::new(
+            "api".into(),
+            AppMode::Cmd,
+            "sleep".into(),
+            dir.path().to_path_buf(),
+        );
+        spec.env
+            .insert("DATABASE_URL".into(), "postgres://db".into());
+
+        let lines = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&lines);
+        let output = ProgressSink::new(move |line| seen.lock().push(line.to_string()));
+        let command: Vec<String> = ["sh", "-c", "pwd; echo $DATABASE_URL; exit 3"]
+            .map(String::from)
+            .to_vec();
+        let code = supervisor.exec(&spec, &command, &output).await.unwrap();
+
+        assert_eq!(code, 3);
+        let cwd = dir.path().canonicalize().unwrap();
+        assert_eq!(
+            *lines.lock(),
+            vec![cwd.display().to_string(), "postgres://db".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_env_file_change_restarts_app() {
+        let (supervisor, dir) = test_supervisor().await;
+        let mut actions = supervisor.take_actions().unwrap();
+        let env_file = dir.path().join(".env");
+        std::fs::write(&env_file, "SECRET=one\n").unwrap();
+        let seen = dir.path().join("seen");
+
+        let mut spec = AppSpec::new(
+            "api".into(),
+            AppMode::Cmd,
+            "sh".into(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec![
+            "-c".into(),
+            format!("echo $SECRET > {}; exec sleep 30", seen.display()),
+        ]);
+        spec.env.insert("SECRET".into(), "one".into());
+        spec.env_sources.insert("SECRET".into(), EnvSource::EnvFile);
+        spec.env_file = Some(env_file.clone());
+        spec.watch_env_file = Some(EnvFileAction::Restart);
+        let id = supervisor.start(spec).await.unwrap();
+        let first_pid = supervisor.pid_of(id);
+
+        std::fs::write(&env_file, "SECRET=two\n").unwrap();
+        let action = tokio::time::timeout(Duration::from_secs(10), actions.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(action, SupervisorAction::EnvFileChanged { id });
+        supervisor.run_action(action).await;
+
+        {
+            let processes = supervisor.processes.published_all();
+            assert_eq!(processes[&id].spec.env["SECRET"], "two");
+            assert_eq!(processes[&id].state.restarts, 1);
+        }
+        assert_ne!(supervisor.pid_of(id), first_pid);
+        for _ in 0..100 {
+            if std::fs::read_to_string(&seen).unwrap_or_default() == "two\n" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(std::fs::read_to_string(&seen).unwrap(), "two\n");
+        supervisor.stop(id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_crash_restart_respects_pause() {
+        let (supervisor, dir) = test_supervisor().await;
+        let mut actions = supervisor.take_actions().unwrap();
+        let crashing = |name: &str, script: &str| {
+            let mut spec = AppSpec::new(
+                name.into(),
+                AppMode::Cmd,
+                "sh".into(),
+                dir.path().to_path_buf(),
+            )
+            .with_args(vec!["-c".into(), script.into()]);
+            spec.restart_policy.restart_delay_ms = 0;
+            spec
+        };
+
+        // Crashes once, then stays up
+        let id = supervisor
+            .start(crashing(
+                "flaky",
+                "test -e crashed && exec sleep 30; touch crashed; exit 3",
+            ))
+            .await
+            .unwrap();
+        let action = tokio::time::timeout(Duration::from_secs(5), actions.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(action, SupervisorAction::CrashRestart { id, attempt: 0 });
+
+        // Paused while the restart was pending: it is dropped
+        supervisor.set_supervision_paused(true);
+        supervisor.run_action(action.clone()).await;
+        assert_eq!(
+            supervisor.processes.published(id).unwrap().state.status,
+            AppStatus::Errored
+        );
+
+        supervisor.set_supervision_paused(false);
+        supervisor.run_action(action).await;
+        {
+            let processes = supervisor.processes.published_all();
+            assert_eq!(processes[&id].state.status, AppStatus::Running);
+            assert_eq!(processes[&id].state.restarts, 1);
+        }
+        supervisor.stop(id).await.unwrap();
+
+        // Crashing while paused schedules nothing
+        supervisor.set_supervision_paused(true);
+        let id = supervisor
+            .start(crashing("doomed", "exit 4"))
+            .await
+            .unwrap();
+        wait_errored(&supervisor, id).await;
+        assert!(tokio::time::timeout(Duration::from_secs(1), actions.recv())
+            .await
+            .is_err());
+        let processes = supervisor.processes.published_all();
+        assert_eq!(processes[&id].state.pid, None);
+        assert_eq!(processes[&id].state.restarts, 0);
+        assert_eq!(processes[&id].state.next_restart_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_purge_namespaced_cluster() {
+        let (supervisor, dir) = test_supervisor().await;
+        let spec = AppSpec::new(
+            "purge-web".into(),
+            AppMode::Cmd,
+            "sleep".into(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec!["30".into()])
+        .with_namespace("purge-shop")
+        .with_instances(2);
+        let id = supervisor.start(spec).await.unwrap();
+
+        let specs: Vec<AppSpec> = supervisor
+            .processes
+            .published_all()
+            .into_values()
+            .map(|proc| proc.spec)
+            .collect();
+        assert_eq!(specs.len(), 3);
+        let mut paths = Vec::new();
+        for spec in &specs {
+            let hook_log = hook_log_path(&spec.log_name());
+            std::fs::create_dir_all(hook_log.parent().unwrap()).unwrap();
+            std::fs::write(&hook_log, "[start]\n").unwrap();
+            std::fs::write(spec.stdout_log_path(), "out\n").unwrap();
+            std::fs::write(spec.stderr_log_path(), "err\n").unwrap();
+            paths.extend([hook_log, spec.stdout_log_path(), spec.stderr_log_path()]);
+        }
+        assert!(paths
+            .iter()
+            .any(|path| path.ends_with("purge-shop.purge-web-1-hooks.log")));
+
+        assert!(supervisor.purge(id).await.unwrap());
+        for path in &paths {
+            assert!(!path.exists(), "{} survived the purge", path.display());
+        }
+        assert!(supervisor.processes.published_all().is_empty());
+        assert!(supervisor.db.apps().get_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resurrect_starts_dependencies_first() {
+        let (supervisor, dir) = test_supervisor().await;
+        let app = |name: &str, deps: &[&str]| {
+            let mut spec = AppSpec::new(
+                name.into(),
+                AppMode::Cmd,
+                "sleep".into(),
+                dir.path().to_path_buf(),
+            )
+            .with_args(vec!["30".into()]);
+            spec.depends_on = deps.iter().map(|dep| dep.to_string()).collect();
+            spec
+        };
+        // Saved dependents first, so saved order alone would start them first
+        let apps = vec![app("web", &["api"]), app("api", &["db"]), app("db", &[])];
+        let path = dir.path().join("saved.json");
+        std::fs::write(
+            &path,
+            serde_json::to_string(&SavedState::new(apps)).unwrap(),
+        )
+        .unwrap();
+
+        let report = supervisor.resurrect(Some(&path), false).await.unwrap();
+        assert_eq!(report.started, 3);
+
+        let started: HashMap<String, _> = supervisor
+            .processes
+            .published_all()
+            .into_values()
+            .map(|proc| (proc.spec.name, proc.state.started_at.unwrap()))
+            .collect();
+        assert!(started["db"] <= started["api"]);
+        assert!(started["api"] <= started["web"]);
+
+        let ids: Vec<u32> = supervisor.processes.published_all().into_keys().collect();
+        for id in ids {
+            let _ = supervisor.stop(id).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resurrect_starts_in_priority_order() {
+        let (supervisor, dir) = test_supervisor().await;
+        let app = |name: &str, priority: i32| {
+            AppSpec::new(
+                name.into(),
+                AppMode::Cmd,
+                "sleep".into(),
+                dir.path().to_path_buf(),
+            )
+            .with_args(vec!["30".into()])
+            .with_priority(priority)
+        };
+        let apps = vec![app("web", 20), app("api", 10), app("db", 0)];
+        let path = dir.path().join("saved.json");
+        std::fs::write(
+            &path,
+            serde_json::to_string(&SavedState::new(apps)).unwrap(),
+        )
+        .unwrap();
+
+        let report = supervisor.resurrect(Some(&path), false).await.unwrap();
+        assert_eq!(report.started, 3);
+
+        let started: HashMap<String, _> = supervisor
+            .processes
+            .published_all()
+            .into_values()
+            .map(|proc| (proc.spec.name, proc.state.started_at.unwrap()))
+            .collect();
+        assert!(started["db"] <= started["api"]);
+        assert!(started["api"] <= started["web"]);
+
+        let ids: Vec<u32> = supervisor.processes.published_all().into_keys().collect();
+        for id in ids {
+            let _ = supervisor.stop(id).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_history() {
+        let (supervisor, _dir) = test_supervisor().await;
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cmd,
+            "server".into(),
+            "/".into(),
+        );
+        spec.id = 7;
+        spec.health_check = Some(oxidepm_core::HealthCheck::tcp("127.0.0.1", 1));
+        spec.readiness_check = Some(oxidepm_core::HealthCheck::http("http://127.0.0.1:1/ready"));
+        supervisor.processes.insert(
+            7,
+            SupervisedProcess {
+                spec,
+                state: RunState::new(7),
+                child: None,
+                restart_count: 0,
+                last_restart: None,
+                started_at: None,
+                health_monitor: None,
+                cluster_instance_ids: Vec::new(),
+                parent_id: None,
+                log_lines_dropped: None,
+            },
+        );
+
+        for i in 0..constants::HEALTH_HISTORY_SIZE + 2 {
+            let result = if i % 2 == 0 {
+                HealthCheckResult::healthy(i as u64)
+            } else {
+                HealthCheckResult::unhealthy(i as u64, "Timeout")
+            };
+            record_health(&supervisor.health_history, 7, Probe::Liveness, &result);
+        }
+
+        record_health(
+            &supervisor.health_history,
+            7,
+            Probe::Readiness,
+            &HealthCheckResult::healthy(1),
+        );
+
+        let health = supervisor.health(&[7, 8]);
+        assert_eq!(health.len(), 2);
+        assert_eq!(health[0].probe, Probe::Liveness);
+        assert_eq!(health[0].endpoint, "tcp://127.0.0.1:1");
+        assert_eq!(health[0].history.len(), constants::HEALTH_HISTORY_SIZE);
+        // Oldest entries were dropped
+        assert_eq!(health[0].history[0].duration_ms, 2);
+        assert_eq!(
+            health[0].history.last().unwrap().message.as_deref(),
+            Some("Timeout")
+        );
+        // Readiness results are kept apart
+        assert_eq!(health[1].probe, Probe::Readiness);
+        assert_eq!(health[1].endpoint, "http://127.0.0.1:1/ready");
+        assert_eq!(health[1].history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_wait_healthy() {
+        let (supervisor, _dir) = test_supervisor().await;
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cmd,
+            "server".into(),
+            "/".into(),
+        );
+        spec.id = 3;
+        spec.health_check = Some(oxidepm_core::HealthCheck::tcp("127.0.0.1", 1));
+        let mut state = RunState::new(3);
+        state.status = AppStatus::Running;
+        state.started_at = Some(chrono::Utc::now());
+        supervisor.processes.insert(
+            3,
+            SupervisedProcess {
+                spec,
+                state,
+                child: None,
+                restart_count: 0,
+                last_restart: None,
+                started_at: None,
+                health_monitor: None,
+                cluster_instance_ids: Vec::new(),
+                parent_id: None,
+                log_lines_dropped: None,
+            },
+        );
+
+        // Running but not checked since it started
+        let err = supervisor
+            .wait_healthy(3, Duration::from_millis(300))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)), "{}", err);
+
+        supervisor.processes.with_mut(3, |proc| {
+            proc.state.healthy = true;
+            proc.state.last_health_check = Some(chrono::Utc::now());
+        });
+        supervisor
+            .wait_healthy(3, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        // Healthy but not ready yet
+        supervisor.processes.with_mut(3, |proc| {
+            proc.spec.readiness_check = Some(oxidepm_core::HealthCheck::tcp("127.0.0.1", 1));
+        });
+        let err = supervisor
+            .wait_healthy(3, Duration::from_millis(300))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)), "{}", err);
+        supervisor.processes.with_mut(3, |proc| {
+            proc.state.ready = true;
+        });
+        supervisor
+            .wait_healthy(3, Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        supervisor.processes.with_mut(3, |proc| {
+            proc.state.status = AppStatus::Errored;
+        });
+        let err = supervisor
+            .wait_healthy(3, Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ProcessNotRunning(_)), "{}", err);
+    }
 
-                                if let Some(pid) = pid {
-                                    let _ = kill(NixPid::from_raw(pid as i32), Signal::SIGTERM);
-                                }
-                            }
+    #[tokio::test]
+    async fn test_warmup_gates_ready() {
+        let (supervisor, dir) = test_supervisor().await;
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cmd,
+            "server".into(),
+            dir.path().to_path_buf(),
+        );
+        spec.id = 4;
+        spec.warmup = Some(oxidepm_core::Warmup {
+            urls: Vec::new(),
+            requests: 1,
+            script: Some("sleep 0.5 && touch warmed".into()),
+            timeout_secs: 5,
+        });
+        let mut state = RunState::new(4);
+        state.status = AppStatus::Running;
+        state.pid = Some(42);
+        state.started_at = Some(chrono::Utc::now() - chrono::Duration::seconds(2));
+        state.ready = false;
+        supervisor.processes.insert(
+            4,
+            SupervisedProcess {
+                spec,
+                state,
+                child: None,
+                restart_count: 0,
+                last_restart: None,
+                started_at: None,
+                health_monitor: None,
+                cluster_instance_ids: Vec::new(),
+                parent_id: None,
+                log_lines_dropped: None,
+            },
+        );
+        supervisor.warming.write().insert(4, 42);
+        supervisor.spawn_warmup_task(4);
+
+        // Healthy, but not ready until the warm-up is done
+        let err = supervisor
+            .wait_healthy(4, Duration::from_millis(200))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)), "{}", err);
+        assert!(is_warming(&supervisor.warming, 4, Some(42)));
+
+        supervisor
+            .wait_healthy(4, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(dir.path().join("warmed").exists());
+        assert!(!is_warming(&supervisor.warming, 4, Some(42)));
+    }
 
-                            // Wait with timeout then kill
-                            let timeout = Duration::from_millis(spec.kill_timeout_ms);
-                            let kill_result = tokio::time::timeout(timeout, child.wait()).await;
+    #[tokio::test]
+    async fn test_reload_waits_for_slow_warmup() {
+        let (supervisor, dir) = test_supervisor().await;
+        tokio::time::pause();
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cmd,
+            "server".into(),
+            dir.path().to_path_buf(),
+        );
+        spec.id = 5;
+        spec.warmup = Some(oxidepm_core::Warmup {
+            urls: vec!["http://127.0.0.1:9/".into()],
+            requests: 1,
+            script: None,
+            timeout_secs: oxidepm_core::DEFAULT_WARMUP_TIMEOUT_SECS,
+        });
+        let mut state = RunState::new(5);
+        state.status = AppStatus::Running;
+        state.pid = Some(std::process::id());
+        state.ready = false;
+        supervisor.processes.insert(
+            5,
+            SupervisedProcess {
+                spec,
+                state,
+                child: None,
+                restart_count: 0,
+                last_restart: None,
+                started_at: None,
+                health_monitor: None,
+                cluster_instance_ids: Vec::new(),
+                parent_id: None,
+                log_lines_dropped: None,
+            },
+        );
 
-                            if kill_result.is_err() {
-                                warn!("Process didn't stop gracefully, sending SIGKILL");
-                                let _ = child.kill().await;
-                            }
-                        }
+        // The warm-up takes longer than a check may, but within its timeout
+        let processes = Arc::clone(&supervisor.processes);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(45)).await;
+            processes.with_mut(5, |proc| {
+                proc.state.ready = true;
+            });
+        });
+        assert!(supervisor.wait_for_healthy(5, RELOAD_HEALTH_TIMEOUT).await);
 
-                        // Update state to stopped
-                        {
-                            let mut procs = processes.write();
-                            if let Some(proc) = procs.get_mut(&app_id) {
-                                proc.state.status = AppStatus::Stopped;
-                                proc.state.pid = None;
-                                proc.started_at = None;
-                            }
-                        }
+        // One that never finishes still gives up
+        supervisor.processes.with_mut(5, |proc| {
+            proc.state.ready = false;
+        });
+        let started = tokio::time::Instant::now();
+        assert!(!supervisor.wait_for_healthy(5, RELOAD_HEALTH_TIMEOUT).await);
+        assert!(started.elapsed() >= RELOAD_HEALTH_TIMEOUT + Duration::from_secs(60));
+    }
 
-                        // Clear from pending restarts so it can be started again
-                        pending_restarts.remove(&app_id);
-                        memory_limit_notified.remove(&app_id);
+    #[tokio::test]
+    async fn test_wait_for_targets() {
+        let (supervisor, dir) = test_supervisor().await;
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cmd,
+            "server".into(),
+            dir.path().to_path_buf(),
+        );
+        spec.wait_for = vec!["ready".into()];
+        spec.wait_for_timeout_secs = Some(1);
+
+        let err = supervisor.wait_for_targets(&spec).await.unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)), "{}", err);
+        assert!(
+            err.to_string().contains("still waiting for ready"),
+            "{}",
+            err
+        );
 
-                        // Note: The actual restart will be handled by the supervision task
-                        // which watches for process exits. We've stopped the process,
-                        // so the supervision task will detect this and restart if auto-restart is enabled.
-                        info!(
-                            "Process {} (id: {}) stopped for {} restart",
-                            spec.name, app_id, reason
-                        );
-                    }
-                }
-            }
+        let ready = dir.path().join("ready");
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            std::fs::write(ready, "").unwrap();
         });
+        spec.wait_for_timeout_secs = Some(5);
+        supervisor.wait_for_targets(&spec).await.unwrap();
     }
 
-    /// Run a hook script asynchronously
-    ///
-    /// Executes the hook script in a background task with environment variables:
-    /// - OPM_APP_ID: The application ID
-    /// - OPM_APP_NAME: The application name
-    /// - OPM_EVENT: The event type (start, stop, restart, crash, error)
-    /// - OPM_PID: The process ID (if available)
-    /// - OPM_EXIT_CODE: The exit code (if available, for crash/stop events)
-    fn run_hook(
-        &self,
-        hooks: &Hooks,
-        event: HookEvent,
-        app_id: u32,
-        app_name: &str,
-        pid: Option<u32>,
-        exit_code: Option<i32>,
-    ) {
-        if let Some(script) = hooks.get(event) {
-            let script = script.to_string();
-            let app_name = app_name.to_string();
-            let event_name = event.to_string();
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_status_not_blocked_by_stuck_start() {
+        let (supervisor, dir) = test_supervisor().await;
+        let supervisor = Arc::new(supervisor);
+        let mut spec = AppSpec::new(
+            "worker".into(),
+            oxidepm_core::AppMode::Cmd,
+            "sleep".into(),
+            dir.path().to_path_buf(),
+        );
+        spec.args = vec!["30".into()];
+        let worker = supervisor.start(spec.clone()).await.unwrap();
 
-            info!(
-                "Running {} hook for {} (id: {}): {}",
-                event_name, app_name, app_id, script
-            );
+        // This start waits a minute for a file that never shows up
+        spec.name = "api".into();
+        spec.wait_for = vec!["never".into()];
+        spec.wait_for_timeout_secs = Some(60);
 
-            tokio::spawn(async move {
-                let result = run_hook_script(&script, app_id, &app_name, &event_name, pid, exit_code).await;
-                match result {
-                    Ok(output) => {
-                        if !output.is_empty() {
-                            debug!("Hook output for {} ({}): {}", app_name, event_name, output);
-                        }
-                        info!("Hook {} completed successfully for {}", event_name, app_name);
-                    }
-                    Err(e) => {
-                        error!("Hook {} failed for {}: {}", event_name, app_name, e);
-                    }
-                }
-            });
-        }
+        let starting = tokio::spawn({
+            let supervisor = Arc::clone(&supervisor);
+            async move { supervisor.start(spec).await }
+        });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!starting.is_finished());
+
+        let status = tokio::time::timeout(Duration::from_secs(1), supervisor.status())
+            .await
+            .expect("status waited on the stuck start")
+            .unwrap();
+        let worker = status.iter().find(|app| app.spec.id == worker).unwrap();
+        assert!(worker.state.status.is_running());
+        assert!(status.iter().any(|app| app.spec.name == "api"));
+
+        starting.abort();
+        supervisor.stop(worker.spec.id).await.unwrap();
     }
-}
 
-/// Execute a hook script with environment variables
-///
-/// The script is run through the shell (sh -c) with the following environment variables:
-/// - OPM_APP_ID: The application ID
-/// - OPM_APP_NAME: The application name
-/// - OPM_EVENT: The event type (start, stop, restart, crash, error)
-/// - OPM_PID: The process ID (if available)
-/// - OPM_EXIT_CODE: The exit code (if available)
-///
-/// Hook output is logged to a separate hook log file.
-async fn run_hook_script(
-    script: &str,
-    app_id: u32,
-    app_name: &str,
-    event: &str,
-    pid: Option<u32>,
-    exit_code: Option<i32>,
-) -> std::result::Result<String, String> {
-    use std::process::Stdio;
-    use tokio::process::Command;
+    #[tokio::test]
+    async fn test_set_env_persists() {
+        let (supervisor, _dir) = test_supervisor().await;
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            oxidepm_core::AppMode::Cmd,
+            "sleep".to_string(),
+            std::path::PathBuf::from("/tmp"),
+        );
+        spec.env.insert("OLD".to_string(), "1".to_string());
+        let id = supervisor.db.apps().insert(&spec).await.unwrap();
+
+        let set = HashMap::from([("LOG_LEVEL".to_string(), "debug".to_string())]);
+        let running = supervisor
+            .set_env(id, &set, &["OLD".to_string()])
+            .await
+            .unwrap();
+        assert!(!running);
+
+        let env = supervisor.get_spec(id).await.unwrap().unwrap().env;
+        assert_eq!(env, set);
+
+        let bad = HashMap::from([("A=B".to_string(), "x".to_string())]);
+        assert!(supervisor.set_env(id, &bad, &[]).await.is_err());
+        assert!(matches!(
+            supervisor.set_env(id + 1, &set, &[]).await,
+            Err(Error::AppNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_spec_persists() {
+        let (supervisor, _dir) = test_supervisor().await;
+        let spec = AppSpec::new(
+            "api".to_string(),
+            oxidepm_core::AppMode::Cmd,
+            "sleep".to_string(),
+            std::path::PathBuf::from("/tmp"),
+        );
+        let id = supervisor.db.apps().insert(&spec).await.unwrap();
+
+        let update = SpecUpdate {
+            watch: Some(true),
+            max_restarts: Some(2),
+            max_memory_mb: Some(256),
+            max_uptime_secs: Some(3600),
+            port: Some(8080),
+            health_check: Some(HealthCheck::http("http://localhost:8080/health")),
+            ..Default::default()
+        };
+        assert!(!supervisor.update_spec(id, &update).await.unwrap());
+        let stored = supervisor.get_spec(id).await.unwrap().unwrap();
+        assert!(stored.watch);
+        assert_eq!(stored.restart_policy.max_restarts, 2);
+        assert_eq!(stored.max_memory_mb, Some(256));
+        assert_eq!(stored.max_uptime_secs, Some(3600));
+        assert_eq!(stored.port, Some(8080));
+        assert_eq!(stored.health_check, update.health_check);
+
+        // 0 clears a limit
+        let clear = SpecUpdate {
+            max_memory_mb: Some(0),
+            ..Default::default()
+        };
+        supervisor.update_spec(id, &clear).await.unwrap();
+        let stored = supervisor.get_spec(id).await.unwrap().unwrap();
+        assert_eq!(stored.max_memory_mb, None);
+        assert_eq!(stored.port, Some(8080));
+        assert!(matches!(
+            supervisor.update_spec(id + 1, &update).await,
+            Err(Error::AppNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rename_moves_logs() {
+        let (supervisor, dir) = test_supervisor().await;
+        let spec = AppSpec::new(
+            "rename-old".into(),
+            AppMode::Cmd,
+            "sleep".into(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec!["30".into()]);
+        let id = supervisor.start(spec.clone()).await.unwrap();
+        supervisor
+            .db
+            .apps()
+            .insert(&AppSpec::new(
+                "rename-taken".into(),
+                AppMode::Cmd,
+                "sleep".into(),
+                dir.path().to_path_buf(),
+            ))
+            .await
+            .unwrap();
+
+        let out = spec.stdout_log_path();
+        let rotated = out.with_file_name("rename-old-out.log.1");
+        let hook_log = hook_log_path("rename-old");
+        // The logs directory outlives earlier runs
+        for name in [
+            "rename-new-out.log",
+            "rename-new-err.log",
+            "rename-new-out.log.1",
+        ] {
+            let _ = std::fs::remove_file(out.with_file_name(name));
+        }
+        let _ = std::fs::remove_file(hook_log_path("rename-new"));
+        std::fs::write(&rotated, "older\n").unwrap();
+        std::fs::write(&hook_log, "[start]\n").unwrap();
+
+        assert!(matches!(
+            supervisor.rename(id, "rename-taken").await,
+            Err(Error::AppAlreadyExists(_))
+        ));
+        assert!(supervisor.rename(id, "bad name").await.is_err());
+
+        assert!(supervisor.rename(id, "rename-new").await.unwrap());
+        assert!(supervisor.pid_of(id).is_some());
+        assert!(!rotated.exists() && !hook_log.exists());
+        assert!(out.with_file_name("rename-new-out.log.1").exists());
+        assert!(hook_log_path("rename-new").exists());
+        let stored = supervisor.db.apps().get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(stored.name, "rename-new");
+        assert_eq!(
+            supervisor.get_spec(id).await.unwrap().unwrap().name,
+            "rename-new"
+        );
 
-    let mut cmd = Command::new("sh");
-    cmd.arg("-c").arg(script);
+        supervisor.purge(id).await.unwrap();
+    }
 
-    // Set environment variables
-    cmd.env("OPM_APP_ID", app_id.to_string());
-    cmd.env("OPM_APP_NAME", app_name);
-    cmd.env("OPM_EVENT", event);
+    #[tokio::test]
+    async fn test_rename_rolls_back_failed_move() {
+        let (supervisor, dir) = test_supervisor().await;
+        let spec = AppSpec::new(
+            "rollback-old".into(),
+            AppMode::Cmd,
+            "sleep".into(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec!["30".into()]);
+        let id = supervisor.start(spec.clone()).await.unwrap();
+        let first_pid = supervisor.pid_of(id);
+
+        let out = spec.stdout_log_path();
+        let rotated = out.with_file_name("rollback-old-out.log.1");
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&out)
+            .unwrap();
+        std::fs::write(&rotated, "older\n").unwrap();
+
+        // `<name>-out.log` still fits in a file name, `<name>-out.log.1`
+        // does not: the active file moves, then its rotated copy fails to
+        let new_name = "n".repeat(247);
+        // The logs directory outlives earlier runs
+        for stream in ["out", "err"] {
+            let stale = out.with_file_name(format!("{}-{}.log", new_name, stream));
+            let _ = std::fs::remove_file(stale);
+        }
+        let err = supervisor.rename(id, &new_name).await.unwrap_err();
+        assert!(err.to_string().contains("Failed to move"), "{}", err);
+
+        assert!(out.exists() && rotated.exists());
+        assert!(!out.with_file_name(format!("{}-out.log", new_name)).exists());
+        let stored = supervisor.db.apps().get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(stored.name, "rollback-old");
+        assert_eq!(
+            supervisor.get_spec(id).await.unwrap().unwrap().name,
+            "rollback-old"
+        );
+        // It was running, so it runs again
+        assert!(supervisor.pid_of(id).is_some());
+        assert_ne!(supervisor.pid_of(id), first_pid);
 
-    if let Some(pid) = pid {
-        cmd.env("OPM_PID", pid.to_string());
+        supervisor.purge(id).await.unwrap();
     }
 
-    if let Some(code) = exit_code {
-        cmd.env("OPM_EXIT_CODE", code.to_string());
+    #[tokio::test]
+    async fn test_resolve_namespaced_names() {
+        let (supervisor, _dir) = test_supervisor().await;
+        let spec = |name: &str, namespace: &str| {
+            AppSpec::new(
+                name.to_string(),
+                oxidepm_core::AppMode::Cmd,
+                "sleep".to_string(),
+                std::path::PathBuf::from("/tmp"),
+            )
+            .with_namespace(namespace)
+        };
+        let shop_api = supervisor
+            .db
+            .apps()
+            .insert(&spec("api", "shop"))
+            .await
+            .unwrap();
+        let blog_api = supervisor
+            .db
+            .apps()
+            .insert(&spec("api", "blog"))
+            .await
+            .unwrap();
+        let shop_web = supervisor
+            .db
+            .apps()
+            .insert(&spec("web", "shop"))
+            .await
+            .unwrap();
+
+        let resolve = |s: &str| {
+            let selector = Selector::parse(s);
+            let supervisor = &supervisor;
+            async move { supervisor.resolve_selector(&selector).await }
+        };
+        assert_eq!(resolve("shop/api").await.unwrap(), vec![shop_api]);
+        assert_eq!(resolve("web").await.unwrap(), vec![shop_web]);
+        assert_eq!(resolve("ns:shop").await.unwrap(), vec![shop_api, shop_web]);
+        assert!(matches!(
+            resolve("api").await,
+            Err(Error::InvalidSelector(_))
+        ));
+        assert!(matches!(
+            resolve("ns:nope").await,
+            Err(Error::AppNotFound(_))
+        ));
+
+        // A bare name prefers the default namespace
+        let default_api = supervisor
+            .db
+            .apps()
+            .insert(&spec("api", "default"))
+            .await
+            .unwrap();
+        assert_eq!(resolve("api").await.unwrap(), vec![default_api]);
+        assert_eq!(resolve("blog/api").await.unwrap(), vec![blog_api]);
+
+        // Pruning only touches the namespaces being applied
+        let report = supervisor
+            .apply(vec![spec("web", "shop")], true, true)
+            .await
+            .unwrap();
+        assert_eq!(report.deleted, vec!["shop/api"]);
     }
 
-    // Capture output
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::piped());
+    #[tokio::test]
+    async fn test_run_bulk_results() {
+        let (supervisor, _dir) = test_supervisor().await;
+        let spec = |name: &str| {
+            AppSpec::new(
+                name.to_string(),
+                oxidepm_core::AppMode::Cmd,
+                "sleep".to_string(),
+                std::path::PathBuf::from("/tmp"),
+            )
+        };
+        let api = supervisor.db.apps().insert(&spec("api")).await.unwrap();
+        let web = supervisor.db.apps().insert(&spec("web")).await.unwrap();
+
+        // Neither app is running, so there is nothing to stop
+        let results = supervisor
+            .run_bulk(vec![api, web], |id| supervisor.stop(id))
+            .await
+            .unwrap();
+        assert!(results.iter().all(|r| r.outcome == AppOpOutcome::Skipped));
+
+        let results = supervisor
+            .run_bulk(vec![web, api, 99], |id| {
+                let supervisor = &supervisor;
+                async move {
+                    if id == 99 {
+                        return Err(Error::AppNotFound(id.to_string()));
+                    }
+                    supervisor.delete(id).await
+                }
+            })
+            .await
+            .unwrap();
+        let summary: Vec<_> = results
+            .iter()
+            .map(|r| (r.id, r.name.as_str(), &r.outcome))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (web, "web", &AppOpOutcome::Done),
+                (api, "api", &AppOpOutcome::Done),
+                (
+                    99,
+                    "99",
+                    &AppOpOutcome::Failed {
+                        error: "App not found: 99".to_string()
+                    }
+                ),
+            ]
+        );
+        assert_eq!(AppOpResult::count_done(&results), 2);
+        assert!(supervisor.db.apps().get_all().await.unwrap().is_empty());
+    }
 
-    // Run the command with a timeout
-    let timeout = Duration::from_secs(30);
-    let result = tokio::time::timeout(timeout, cmd.output()).await;
+    #[tokio::test]
+    async fn test_restart_starts_stopped_app() {
+        let (supervisor, dir) = test_supervisor().await;
+        // Registered but not started since the daemon came up
+        let spec = AppSpec::new(
+            "api".into(),
+            AppMode::Cmd,
+            "sleep".into(),
+            dir.path().to_path_buf(),
+        )
+        .with_args(vec!["30".into()]);
+        let id = supervisor.db.apps().insert(&spec).await.unwrap();
 
-    match result {
-        Ok(Ok(output)) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(supervisor.restart(id, false).await.unwrap());
+        assert!(supervisor.pid_of(id).is_some());
+        supervisor.stop(id).await.unwrap();
+    }
 
-            // Log hook output to a separate file
-            if let Err(e) = log_hook_output(app_name, event, &stdout, &stderr) {
-                warn!("Failed to log hook output: {}", e);
+    #[tokio::test]
+    async fn test_run_bulk_halt_on_error() {
+        let (supervisor, _dir) = test_supervisor().await;
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let lines = Arc::clone(&lines);
+            ProgressSink::new(move |line| lines.lock().push(line.to_string()))
+        };
+        let fail_on_2 = |id: u32| async move {
+            if id == 2 {
+                return Err(Error::AppNotFound(id.to_string()));
             }
+            Ok(true)
+        };
 
-            if output.status.success() {
-                Ok(stdout.trim().to_string())
-            } else {
-                Err(format!(
-                    "Hook exited with code {:?}: {}",
-                    output.status.code(),
-                    stderr.trim()
-                ))
-            }
-        }
-        Ok(Err(e)) => Err(format!("Failed to execute hook: {}", e)),
-        Err(_) => Err("Hook timed out after 30 seconds".to_string()),
+        // One at a time, so the failure stops everything after it
+        let options = BulkOptions {
+            parallel: 1,
+            halt_on_error: true,
+            progress: true,
+        };
+        let results = supervisor
+            .run_bulk_with(vec![1, 2, 3, 4], &options, &sink, fail_on_2)
+            .await
+            .unwrap();
+        let outcomes: Vec<_> = results.iter().map(|r| &r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                &AppOpOutcome::Done,
+                &AppOpOutcome::Failed {
+                    error: "App not found: 2".to_string()
+                },
+                &AppOpOutcome::Skipped,
+                &AppOpOutcome::Skipped,
+            ]
+        );
+        assert_eq!(
+            *lines.lock(),
+            vec!["[1/4] 1 ok", "[2/4] 2 failed: App not found: 2"]
+        );
+
+        let options = BulkOptions {
+            parallel: 1,
+            ..Default::default()
+        };
+        let results = supervisor
+            .run_bulk_with(vec![1, 2, 3, 4], &options, &sink, fail_on_2)
+            .await
+            .unwrap();
+        assert_eq!(AppOpResult::count_done(&results), 3);
     }
-}
 
-/// Log hook output to a separate log file
-fn log_hook_output(
-    app_name: &str,
-    event: &str,
-    stdout: &str,
-    stderr: &str,
-) -> std::io::Result<()> {
-    use std::fs::OpenOptions;
-    use std::io::Write;
+    #[test]
+    fn test_log_build_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let err_log = dir.path().join("api-error.log");
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cargo,
+            ".".into(),
+            dir.path().to_path_buf(),
+        );
+        spec.logs.err = Some(err_log.clone());
 
-    let log_dir = constants::logs_dir();
-    if !log_dir.exists() {
-        std::fs::create_dir_all(&log_dir)?;
+        log_build_failure(
+            &spec,
+            "error[E0425]: cannot find value `x`\n --> src/main.rs:2:5",
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&err_log).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with("the previous build is still running"));
+        assert!(lines[1].ends_with("error[E0425]: cannot find value `x`"));
+        assert!(lines[2].ends_with(" --> src/main.rs:2:5"));
     }
 
-    let log_path = log_dir.join(format!("{}-hooks.log", app_name));
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)?;
+    /// Runner whose build never finishes
+    struct HangingRunner;
 
-    let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
-    writeln!(file, "[{}] Event: {}", timestamp, event)?;
+    #[async_trait::async_trait]
+    impl Runner for HangingRunner {
+        async fn prepare(&self, _spec: &AppSpec) -> Result<PrepareResult> {
+            std::future::pending().await
+        }
 
-    if !stdout.is_empty() {
-        writeln!(file, "  stdout: {}", stdout.trim())?;
+        async fn start(&self, _spec: &AppSpec) -> Result<oxidepm_runtime::RunningProcess> {
+            unreachable!("never prepared")
+        }
+
+        fn argv(&self, _spec: &AppSpec) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn mode_name(&self) -> &'static str {
+            "hanging"
+        }
     }
-    if !stderr.is_empty() {
-        writeln!(file, "  stderr: {}", stderr.trim())?;
+
+    #[tokio::test]
+    async fn test_prepare_timeout_and_cancel() {
+        let (supervisor, dir) = test_supervisor().await;
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cargo,
+            ".".into(),
+            dir.path().to_path_buf(),
+        );
+        spec.id = 7;
+        spec.logs.out = Some(dir.path().join("api-out.log"));
+        spec.build_timeout_secs = Some(1);
+
+        let err = supervisor
+            .prepare_tracked(&spec, &HangingRunner, &ProgressSink::none())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)), "{}", err);
+        assert!(supervisor.processes.published(7).is_none());
+
+        spec.build_timeout_secs = None;
+        let progress = ProgressSink::none();
+        let build = supervisor.prepare_tracked(&spec, &HangingRunner, &progress);
+        let cancel = async {
+            while supervisor.processes.published(7).map(|p| p.state.status)
+                != Some(AppStatus::Building)
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            assert!(supervisor.stop(7).await.unwrap());
+        };
+        let (result, ()) = tokio::join!(build, cancel);
+        assert!(result.unwrap_err().to_string().contains("cancelled"));
+        assert!(supervisor.builds.lock().is_empty());
+        assert!(supervisor.processes.published(7).is_none());
     }
 
-    Ok(())
-}
+    #[test]
+    fn test_build_group() {
+        let mut spec = AppSpec::new(
+            "api".into(),
+            oxidepm_core::AppMode::Cargo,
+            ".".into(),
+            "/srv".into(),
+        );
+        spec.namespace = "shop".into();
+        assert_eq!(build_group(&spec), "shop/api");
+        assert_eq!(build_group(&spec.for_instance(2, None)), "shop/api");
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use oxidepm_core::Hooks;
-    
+    /// Run a shell hook without extra values or app environment
+    async fn run_shell_hook(
+        script: &str,
+        app_id: u32,
+        app_name: &str,
+        event: &str,
+        pid: Option<u32>,
+        exit_code: Option<i32>,
+    ) -> std::result::Result<String, String> {
+        let call = HookCall {
+            app_id,
+            app_name,
+            event,
+            pid,
+            exit_code,
+            extra: &[],
+        };
+        run_hook_script(&Hook::from(script), &call, &HookEnv::default()).await
+    }
 
     #[tokio::test]
     async fn test_run_hook_script_simple() {
-        let result = run_hook_script(
+        let result = run_shell_hook(
             "echo 'hello world'",
             1,
             "test-app",
@@ -1482,7 +6612,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_run_hook_script_with_env_vars() {
-        let result = run_hook_script(
+        let result = run_shell_hook(
             "echo $OPM_APP_ID $OPM_APP_NAME $OPM_EVENT",
             42,
             "my-app",
@@ -1498,7 +6628,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_run_hook_script_with_exit_code() {
-        let result = run_hook_script(
+        let result = run_shell_hook(
             "echo $OPM_EXIT_CODE",
             1,
             "crashed-app",
@@ -1514,15 +6644,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_run_hook_script_with_pid() {
-        let result = run_hook_script(
-            "echo $OPM_PID",
-            1,
-            "app",
-            "start",
-            Some(9999),
-            None,
-        )
-        .await;
+        let result = run_shell_hook("echo $OPM_PID", 1, "app", "start", Some(9999), None).await;
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "9999");
@@ -1530,15 +6652,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_run_hook_script_failure() {
-        let result = run_hook_script(
-            "exit 1",
-            1,
-            "failing-app",
-            "start",
-            None,
-            None,
-        )
-        .await;
+        let result = run_shell_hook("exit 1", 1, "failing-app", "start", None, None).await;
 
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Hook exited with code"));
@@ -1546,15 +6660,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_run_hook_script_command_not_found() {
-        let result = run_hook_script(
-            "/nonexistent/command",
-            1,
-            "app",
-            "start",
-            None,
-            None,
-        )
-        .await;
+        let result = run_shell_hook("/nonexistent/command", 1, "app", "start", None, None).await;
 
         assert!(result.is_err());
     }
@@ -1568,18 +6674,25 @@ mod tests {
     #[test]
     fn test_hooks_get_event() {
         let hooks = Hooks {
-            on_start: Some("start.sh".to_string()),
-            on_stop: Some("stop.sh".to_string()),
+            on_start: Some(Hook::from("start.sh")),
+            on_stop: Some(Hook::from("stop.sh")),
             on_restart: None,
-            on_crash: Some("crash.sh".to_string()),
+            on_crash: Some(Hook::from("crash.sh")),
             on_error: None,
+            on_pre_reload: Some(Hook::from("drain.sh")),
+            on_post_reload: None,
         };
 
-        assert_eq!(hooks.get(HookEvent::Start), Some("start.sh"));
-        assert_eq!(hooks.get(HookEvent::Stop), Some("stop.sh"));
+        assert_eq!(hooks.get(HookEvent::Start), Some(&Hook::from("start.sh")));
+        assert_eq!(hooks.get(HookEvent::Stop), Some(&Hook::from("stop.sh")));
         assert_eq!(hooks.get(HookEvent::Restart), None);
-        assert_eq!(hooks.get(HookEvent::Crash), Some("crash.sh"));
+        assert_eq!(hooks.get(HookEvent::Crash), Some(&Hook::from("crash.sh")));
         assert_eq!(hooks.get(HookEvent::Error), None);
+        assert_eq!(
+            hooks.get(HookEvent::PreReload),
+            Some(&Hook::from("drain.sh"))
+        );
+        assert_eq!(hooks.get(HookEvent::PostReload), None);
     }
 
     #[test]
@@ -1589,11 +6702,13 @@ mod tests {
         assert_eq!(HookEvent::Restart.to_string(), "restart");
         assert_eq!(HookEvent::Crash.to_string(), "crash");
         assert_eq!(HookEvent::Error.to_string(), "error");
+        assert_eq!(HookEvent::PreReload.to_string(), "pre_reload");
+        assert_eq!(HookEvent::PostReload.to_string(), "post_reload");
     }
 
     #[tokio::test]
     async fn test_run_hook_script_multiline_output() {
-        let result = run_hook_script(
+        let result = run_shell_hook(
             "echo 'line1'; echo 'line2'; echo 'line3'",
             1,
             "app",
@@ -1612,18 +6727,104 @@ mod tests {
 
     #[tokio::test]
     async fn test_run_hook_script_stderr() {
-        let result = run_hook_script(
-            "echo 'error' >&2; exit 1",
-            1,
-            "app",
-            "start",
-            None,
-            None,
-        )
-        .await;
+        let result =
+            run_shell_hook("echo 'error' >&2; exit 1", 1, "app", "start", None, None).await;
 
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.contains("error") || err.contains("Hook exited"));
     }
+
+    #[tokio::test]
+    async fn test_run_hook_script_command_args() {
+        let hook = Hook::Detailed(HookSpec {
+            command: Some("printf".into()),
+            args: vec![
+                "%s|".into(),
+                "{app_name}:{pid}".into(),
+                "{new_pid}".into(),
+                "$OPM_EVENT; echo no".into(),
+            ],
+            ..Default::default()
+        });
+        let call = HookCall {
+            app_id: 1,
+            app_name: "api",
+            event: "pre_reload",
+            pid: Some(10),
+            exit_code: None,
+            extra: &[("new_pid", "11")],
+        };
+        // No shell: the values are passed on verbatim
+        let output = run_hook_script(&hook, &call, &HookEnv::default())
+            .await
+            .unwrap();
+        assert_eq!(output, "api:10|11|$OPM_EVENT; echo no|");
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_script_environment() {
+        std::env::set_var("OPM_TEST_DAEMON_ONLY", "leaked");
+        let hook_env = HookEnv {
+            env: HashMap::from([("DATABASE_URL".to_string(), "postgres://db".to_string())]),
+            user: None,
+            log_name: None,
+        };
+        let call = HookCall {
+            app_id: 1,
+            app_name: "api",
+            event: "pre_reload",
+            pid: None,
+            exit_code: None,
+            extra: &[("new_pid", "11")],
+        };
+        let hook = Hook::from(
+            "echo \"$DATABASE_URL|$OPM_TEST_DAEMON_ONLY|$OPM_NEW_PID|$OPM_PID\"; test -n \"$PATH\"",
+        );
+        let output = run_hook_script(&hook, &call, &hook_env).await.unwrap();
+        assert_eq!(output, "postgres://db||11|");
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_script_timeout() {
+        let hook = Hook::Detailed(HookSpec {
+            run: Some("sleep 30".into()),
+            timeout_secs: Some(1),
+            ..Default::default()
+        });
+        let call = HookCall {
+            app_id: 1,
+            app_name: "api",
+            event: "stop",
+            pid: None,
+            exit_code: None,
+            extra: &[],
+        };
+        let started = Instant::now();
+        let err = run_hook_script(&hook, &call, &HookEnv::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Hook timed out after 1s");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_script_unknown_user() {
+        let hook_env = HookEnv {
+            user: Some("opm-no-such-user".into()),
+            ..Default::default()
+        };
+        let call = HookCall {
+            app_id: 1,
+            app_name: "api",
+            event: "start",
+            pid: None,
+            exit_code: None,
+            extra: &[],
+        };
+        let err = run_hook_script(&Hook::from("true"), &call, &hook_env)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Unknown hook_user 'opm-no-such-user'");
+    }
 }
@@ -0,0 +1,189 @@
+//! Log triggers - patterns in an app's output that notify, restart the app
+//! or run a command
+//!
+//! Lines are matched in the log capture tasks. A match only queues a
+//! `SupervisorAction`; the supervisor carries it out, so slow actions never
+//! hold up the output of the app. Cooldowns are kept per app rather than per
+//! process, so a restart trigger on a line the app prints at startup does not
+//! restart it in a loop.
+
+use oxidepm_core::{AppSpec, LogTrigger};
+use oxidepm_logs::LineTap;
+use parking_lot::Mutex;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::supervisor::SupervisorAction;
+
+/// Longest part of a matching line passed on to notifications and hooks
+const MAX_LINE_CHARS: usize = 500;
+
+/// When each trigger last fired, by app id and pattern
+pub type Cooldowns = Arc<Mutex<HashMap<(u32, String), Instant>>>;
+
+/// Compiled triggers of one running process
+struct Triggers {
+    app_id: u32,
+    pid: u32,
+    triggers: Vec<(Regex, LogTrigger)>,
+    fired: Cooldowns,
+}
+
+impl Triggers {
+    fn new(spec: &AppSpec, pid: u32, fired: Cooldowns) -> Self {
+        let triggers: Vec<(Regex, LogTrigger)> = spec
+            .triggers
+            .iter()
+            .filter_map(|trigger| match Regex::new(&trigger.pattern) {
+                Ok(regex) => Some((regex, trigger.clone())),
+                Err(e) => {
+                    warn!(
+                        "Ignoring log trigger '{}' of {}: {}",
+                        trigger.pattern, spec.name, e
+                    );
+                    None
+                }
+            })
+            .collect();
+        Self {
+            app_id: spec.id,
+            pid,
+            triggers,
+            fired,
+        }
+    }
+
+    /// Triggers that match `line` and are not cooling down, marked as fired
+    fn check(&self, line: &str, now: Instant) -> Vec<LogTrigger> {
+        let mut matched = Vec::new();
+        for (regex, trigger) in &self.triggers {
+            if !regex.is_match(line) {
+                continue;
+            }
+            let key = (self.app_id, trigger.pattern.clone());
+            let mut fired = self.fired.lock();
+            let cooling = fired.get(&key).is_some_and(|at| {
+                now.saturating_duration_since(*at) < Duration::from_secs(trigger.cooldown_secs)
+            });
+            if !cooling {
+                fired.insert(key, now);
+                matched.push(trigger.clone());
+            }
+        }
+        matched
+    }
+}
+
+/// Tap for the log capture of the process `pid` of `spec`; None when the app
+/// has no triggers
+pub fn tap(
+    spec: &AppSpec,
+    pid: u32,
+    cooldowns: Cooldowns,
+    actions: mpsc::UnboundedSender<SupervisorAction>,
+) -> Option<LineTap> {
+    if spec.triggers.is_empty() {
+        return None;
+    }
+    let triggers = Triggers::new(spec, pid, cooldowns);
+    Some(Arc::new(move |line: &str| {
+        for trigger in triggers.check(line, Instant::now()) {
+            let _ = actions.send(SupervisorAction::LogTrigger {
+                id: triggers.app_id,
+                pid: triggers.pid,
+                trigger,
+                line: line.chars().take(MAX_LINE_CHARS).collect(),
+            });
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidepm_core::{AppMode, TriggerAction};
+    use std::path::PathBuf;
+
+    fn trigger(pattern: &str, action: TriggerAction, cooldown_secs: u64) -> LogTrigger {
+        LogTrigger {
+            pattern: pattern.to_string(),
+            action,
+            run: None,
+            cooldown_secs,
+        }
+    }
+
+    #[test]
+    fn test_check_with_cooldown() {
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Cmd,
+            "api".to_string(),
+            PathBuf::from("/"),
+        );
+        spec.triggers = vec![
+            trigger("FATAL: out of memory", TriggerAction::Restart, 60),
+            trigger(r"^panic:", TriggerAction::Notify, 0),
+            trigger("(unclosed", TriggerAction::Notify, 0),
+        ];
+        let cooldowns = Cooldowns::default();
+        let triggers = Triggers::new(&spec, 42, cooldowns.clone());
+        assert_eq!(triggers.triggers.len(), 2);
+
+        let t0 = Instant::now();
+        assert!(triggers.check("all good", t0).is_empty());
+        let fired = triggers.check("FATAL: out of memory", t0);
+        assert_eq!(fired[0].action, TriggerAction::Restart);
+        // Cooling down
+        assert!(triggers
+            .check("FATAL: out of memory", t0 + Duration::from_secs(30))
+            .is_empty());
+        assert_eq!(
+            triggers
+                .check("FATAL: out of memory", t0 + Duration::from_secs(61))
+                .len(),
+            1
+        );
+
+        // No cooldown: every match fires
+        assert_eq!(triggers.check("panic: boom", t0).len(), 1);
+        assert_eq!(triggers.check("panic: boom", t0).len(), 1);
+        assert!(triggers.check("no panic: here", t0).is_empty());
+
+        // The cooldown carries over to the next process of the app
+        let restarted = Triggers::new(&spec, 43, cooldowns);
+        assert!(restarted
+            .check("FATAL: out of memory", t0 + Duration::from_secs(62))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tap_sends_actions() {
+        let mut spec = AppSpec::new(
+            "api".to_string(),
+            AppMode::Cmd,
+            "api".to_string(),
+            PathBuf::from("/"),
+        );
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        assert!(tap(&spec, 42, Cooldowns::default(), tx.clone()).is_none());
+
+        spec.triggers = vec![trigger("panic:", TriggerAction::Notify, 60)];
+        let tap = tap(&spec, 42, Cooldowns::default(), tx).unwrap();
+        tap("thread 'main' panicked");
+        tap("panic: boom");
+        tap("panic: again");
+        match rx.try_recv().unwrap() {
+            SupervisorAction::LogTrigger { pid, line, .. } => {
+                assert_eq!(pid, 42);
+                assert_eq!(line, "panic: boom");
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+}
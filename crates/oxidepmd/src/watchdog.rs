@@ -0,0 +1,117 @@
+//! Daemon self-monitoring
+//!
+//! A background task sleeps for `WATCHDOG_PROBE_MS` at a time; when it wakes
+//! up much later than asked, something held up the runtime and it counts an
+//! event-loop stall. The same task rewrites `heartbeat.json` every
+//! `HEARTBEAT_INTERVAL_SECS`, so `oxidepm doctor` can tell a hung daemon
+//! from one that is gone.
+
+use chrono::Utc;
+use oxidepm_core::{constants, Heartbeat};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::handlers::RequestHandler;
+
+/// Uptime and stall counters for the heartbeat
+pub struct Watchdog {
+    started: Instant,
+    stalls: AtomicU64,
+    max_stall_ms: AtomicU64,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            stalls: AtomicU64::new(0),
+            max_stall_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Heartbeat with the given app counts
+    pub fn heartbeat(&self, managed: usize, running: usize) -> Heartbeat {
+        Heartbeat {
+            pid: std::process::id(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: self.started.elapsed().as_secs(),
+            managed,
+            running,
+            at: Utc::now(),
+            stalls: self.stalls.load(Ordering::Relaxed),
+            max_stall_ms: self.max_stall_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Record how late a probe woke up; true when that counts as a stall
+    fn record_lateness(&self, late: Duration) -> bool {
+        let late_ms = late.as_millis() as u64;
+        if late_ms < constants::WATCHDOG_STALL_MS {
+            return false;
+        }
+        self.stalls.fetch_add(1, Ordering::Relaxed);
+        self.max_stall_ms.fetch_max(late_ms, Ordering::Relaxed);
+        true
+    }
+}
+
+/// Probe the event loop and keep the heartbeat file fresh
+pub fn spawn(handler: Arc<RequestHandler>) {
+    tokio::spawn(async move {
+        let probe = Duration::from_millis(constants::WATCHDOG_PROBE_MS);
+        let heartbeat_every = Duration::from_secs(constants::HEARTBEAT_INTERVAL_SECS);
+        let mut last_written: Option<Instant> = None;
+
+        loop {
+            if last_written.map_or(true, |at| at.elapsed() >= heartbeat_every) {
+                write_heartbeat(&handler.heartbeat()).await;
+                last_written = Some(Instant::now());
+            }
+
+            let asleep = Instant::now();
+            tokio::time::sleep(probe).await;
+            let late = asleep.elapsed().saturating_sub(probe);
+            if handler.watchdog().record_lateness(late) {
+                warn!("Event loop stalled for {}ms", late.as_millis());
+            }
+        }
+    });
+}
+
+/// Replace the heartbeat file in one step so readers never see half of it
+async fn write_heartbeat(heartbeat: &Heartbeat) {
+    let path = constants::heartbeat_path();
+    let tmp = path.with_extension("json.tmp");
+    let result = async {
+        tokio::fs::write(&tmp, serde_json::to_vec(heartbeat)?).await?;
+        tokio::fs::rename(&tmp, &path).await
+    }
+    .await;
+
+    match result {
+        Ok(()) => debug!("Heartbeat written to {}", path.display()),
+        Err(e) => warn!("Failed to write heartbeat {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_accounting() {
+        let watchdog = Watchdog::new();
+        assert!(!watchdog.record_lateness(Duration::from_millis(5)));
+        assert!(watchdog.record_lateness(Duration::from_millis(constants::WATCHDOG_STALL_MS + 500)));
+        assert!(watchdog.record_lateness(Duration::from_millis(constants::WATCHDOG_STALL_MS)));
+
+        let heartbeat = watchdog.heartbeat(3, 2);
+        assert_eq!(heartbeat.stalls, 2);
+        assert_eq!(heartbeat.max_stall_ms, constants::WATCHDOG_STALL_MS + 500);
+        assert_eq!((heartbeat.managed, heartbeat.running), (3, 2));
+        assert_eq!(heartbeat.pid, std::process::id());
+        assert!(!heartbeat.is_stale());
+    }
+}